@@ -130,14 +130,70 @@ typedef struct SeleneRuntimeGetOperationInterface {
   void (*set_batch_time_fn)(SeleneRuntimeGetOperationInstance,
                             uint64_t,
                             uint64_t);
+  void (*u_fn)(SeleneRuntimeGetOperationInstance,
+               uint64_t,
+               double,
+               double,
+               double);
+  void (*two_qubit_unitary_fn)(SeleneRuntimeGetOperationInstance,
+                               uint64_t,
+                               uint64_t,
+                               const double*);
+  void (*diagonal_phase_fn)(SeleneRuntimeGetOperationInstance,
+                            const uint64_t*,
+                            uint64_t,
+                            const double*);
+  void (*qalloc_fn)(SeleneRuntimeGetOperationInstance,
+                    uint64_t);
+  void (*qfree_fn)(SeleneRuntimeGetOperationInstance,
+                   uint64_t);
 } SeleneRuntimeGetOperationInterface;
 
 typedef void *SeleneRuntimeExtractOperationInstance;
 
+/**
+ * The leading fields of `SeleneRuntimeGetOperationInterface`, which are all
+ * that `extract_fn` reads. It is retained for error models built against
+ * error model API 0.2.2 or earlier; newer error models call
+ * `extract_by_ref_fn` with a pointer to the full interface and its size.
+ */
+typedef struct SeleneRuntimeGetOperationBaseInterface {
+  void (*rzz_fn)(SeleneRuntimeGetOperationInstance,
+                 uint64_t,
+                 uint64_t,
+                 double);
+  void (*rxy_fn)(SeleneRuntimeGetOperationInstance,
+                 uint64_t,
+                 double,
+                 double);
+  void (*rz_fn)(SeleneRuntimeGetOperationInstance,
+                uint64_t,
+                double);
+  void (*measure_fn)(SeleneRuntimeGetOperationInstance,
+                     uint64_t,
+                     uint64_t);
+  void (*measure_leaked_fn)(SeleneRuntimeGetOperationInstance,
+                            uint64_t,
+                            uint64_t);
+  void (*reset_fn)(SeleneRuntimeGetOperationInstance,
+                   uint64_t);
+  void (*custom_fn)(SeleneRuntimeGetOperationInstance,
+                    size_t,
+                    const void*,
+                    size_t);
+  void (*set_batch_time_fn)(SeleneRuntimeGetOperationInstance,
+                            uint64_t,
+                            uint64_t);
+} SeleneRuntimeGetOperationBaseInterface;
+
 typedef struct SeleneRuntimeExtractOperationInterface {
   void (*extract_fn)(SeleneRuntimeExtractOperationInstance,
                      SeleneRuntimeGetOperationInstance,
-                     struct SeleneRuntimeGetOperationInterface);
+                     struct SeleneRuntimeGetOperationBaseInterface);
+  void (*extract_by_ref_fn)(SeleneRuntimeExtractOperationInstance,
+                            SeleneRuntimeGetOperationInstance,
+                            const struct SeleneRuntimeGetOperationInterface*,
+                            size_t);
 } SeleneRuntimeExtractOperationInterface;
 
 typedef int32_t SeleneErrno;
@@ -78,6 +78,18 @@ SeleneErrno selene_error_model_shot_start(SeleneErrorModelInstance instance,
                                           uint64_t error_model_seed,
                                           uint64_t simulator_seed);
 
+/**
+ * This function is called before the first of a run of shots is started, to
+ * announce the ids of the shots that will follow and the seeds they will be
+ * started with. Error models that pass the simulator seed to the simulator
+ * unchanged should pass the plan on to the simulator's plan_shots function.
+ */
+SeleneErrno selene_error_model_plan_shots(SeleneErrorModelInstance instance,
+                                          const uint64_t *shot_ids,
+                                          const uint64_t *error_model_seeds,
+                                          const uint64_t *simulator_seeds,
+                                          uint64_t n_shots);
+
 /**
  * This function is called at the end of a shot, and it is responsible for
  * finalising the error model plugin for that shot. For example, it may
@@ -215,6 +215,41 @@ int32_t selene_runtime_rz_gate(RuntimeInstance instance,
                                uint64_t qubit_id,
                                double theta);
 
+/**
+ * Instruct the runtime to apply a general single-qubit unitary
+ * U(theta, phi, lambda) to the qubit with the given ID. This is optional:
+ * if it is not exported, Selene decomposes the gate into RZ and RXY gates.
+ */
+int32_t selene_runtime_u_gate(RuntimeInstance instance,
+                              uint64_t qubit_id,
+                              double theta,
+                              double phi,
+                              double lambda);
+
+/**
+ * Instruct the runtime to apply a general two-qubit unitary to the qubits
+ * with the given IDs. The matrix is 4x4 in row-major order, stored as
+ * interleaved real and imaginary parts, with qubit_id_1 as the least
+ * significant bit of the matrix indices. This is optional: if it is not
+ * exported, Selene decomposes the gate into RXY, RZ and RZZ gates.
+ */
+int32_t selene_runtime_two_qubit_unitary_gate(RuntimeInstance instance,
+                                              uint64_t qubit_id_1,
+                                              uint64_t qubit_id_2,
+                                              const double *matrix);
+
+/**
+ * Instruct the runtime to apply the phase e^{i phases[k]} to each basis
+ * state k of the qubits with the given IDs, where phases has 2^qubit_ids_len
+ * elements and the first qubit is the least significant bit of k. This is
+ * optional: if it is not exported, Selene decomposes the gate into RXY, RZ
+ * and RZZ gates.
+ */
+int32_t selene_runtime_diagonal_phase_gate(RuntimeInstance instance,
+                                           const uint64_t *qubit_ids,
+                                           uint64_t qubit_ids_len,
+                                           const double *phases);
+
 /**
  * Instruct the runtime that a measurement is to be requested and to write
  * a reference ID to the result to the `result` pointer.
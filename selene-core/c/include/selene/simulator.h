@@ -70,6 +70,18 @@ int32_t selene_simulator_shot_start(SeleneSimulatorInstance instance,
                                     uint64_t shot_id,
                                     uint64_t seed);
 
+/**
+ * This function is called before the first of a run of shots is started, to
+ * announce the ids of the shots that will follow and the seeds they will be
+ * started with. It is a hint for simulators that serve many shots from a
+ * single pass, and each shot is still started with
+ * `selene_simulator_shot_start`.
+ */
+int32_t selene_simulator_plan_shots(SeleneSimulatorInstance instance,
+                                    const uint64_t *shot_ids,
+                                    const uint64_t *seeds,
+                                    uint64_t n_shots);
+
 /**
  * This function is called at the end of a shot, and it is responsible for
  * finalising the simulator plugin for that shot. For example, it may
@@ -118,6 +130,41 @@ int32_t selene_simulator_operation_rz(SeleneSimulatorInstance instance,
                                       uint64_t qubit,
                                       double theta);
 
+/**
+ * Apply the general single-qubit unitary
+ * $U(\theta, \phi, \lambda) = e^{i(\phi+\lambda)/2} R_z(\phi)R_y(\theta)R_z(\lambda)$
+ * to the qubit at the requested index. This is optional: if absent, Selene
+ * decomposes the gate into RZ and RXY gates.
+ */
+int32_t selene_simulator_operation_u(SeleneSimulatorInstance instance,
+                                     uint64_t qubit,
+                                     double theta,
+                                     double phi,
+                                     double lambda);
+
+/**
+ * Apply a general two-qubit unitary to the qubits at the requested indices.
+ * The matrix is 4x4 in row-major order, stored as interleaved real and
+ * imaginary parts, and qubit1 is the least significant bit of the matrix
+ * indices. This is optional: if absent, Selene decomposes the gate into
+ * RXY, RZ and RZZ gates.
+ */
+int32_t selene_simulator_operation_two_qubit_unitary(SeleneSimulatorInstance instance,
+                                                     uint64_t qubit1,
+                                                     uint64_t qubit2,
+                                                     const double *matrix);
+
+/**
+ * Apply the phase $e^{i \phi_k}$ to each basis state k of the requested
+ * qubits, where phases has 2^n_qubits elements and the first qubit is the
+ * least significant bit of k. This is optional: if absent, Selene
+ * decomposes the gate into RXY, RZ and RZZ gates.
+ */
+int32_t selene_simulator_operation_diagonal_phase(SeleneSimulatorInstance instance,
+                                                  const uint64_t *qubits,
+                                                  uint64_t n_qubits,
+                                                  const double *phases);
+
 /**
  * Measure the qubit at the requested index. This is a destructive
  * operation.
@@ -140,6 +187,120 @@ int32_t selene_simulator_operation_postselect(SeleneSimulatorInstance instance,
 int32_t selene_simulator_operation_reset(SeleneSimulatorInstance instance,
                                          uint64_t qubit);
 
+/**
+ * Notify the simulator that the qubit at the requested index has
+ * been allocated by the runtime.
+ */
+int32_t selene_simulator_operation_qalloc(SeleneSimulatorInstance instance,
+                                          uint64_t qubit);
+
+/**
+ * Notify the simulator that the qubit at the requested index has
+ * been freed by the runtime.
+ */
+int32_t selene_simulator_operation_qfree(SeleneSimulatorInstance instance,
+                                         uint64_t qubit);
+
+/**
+ * Measure the qubit at the requested index, distinguishing leaked
+ * states. Returns 0 or 1 for the computational states, and 2 if the
+ * qubit is found outside of the computational subspace. Simulators
+ * that do not model leakage perform a normal measurement.
+ */
+int32_t selene_simulator_operation_measure_leaked(SeleneSimulatorInstance instance,
+                                                  uint64_t qubit);
+
+/**
+ * Apply a rotation between the |1> and leaked |2> states of the qubit
+ * at the requested index, with the same conventions as the RXY gate.
+ * Simulators that do not model leakage should return an error.
+ */
+int32_t selene_simulator_operation_leakage_rxy(SeleneSimulatorInstance instance,
+                                               uint64_t qubit,
+                                               double theta,
+                                               double phi);
+
+/**
+ * Capture the current quantum state of the simulator instance, writing
+ * a handle to the snapshot into `handle_out`. The handle can later be
+ * passed to `selene_simulator_restore`.
+ */
+int32_t selene_simulator_snapshot(SeleneSimulatorInstance instance, uint64_t *handle_out);
+
+/**
+ * Replace the current quantum state of the simulator instance with
+ * the snapshot with the given handle. The snapshot is left intact.
+ */
+int32_t selene_simulator_restore(SeleneSimulatorInstance instance, uint64_t handle);
+
+/**
+ * Release the snapshot with the given handle, freeing the state it
+ * holds. The handle cannot be restored afterwards.
+ */
+int32_t selene_simulator_release_snapshot(SeleneSimulatorInstance instance, uint64_t handle);
+
+/**
+ * Write the probability of each Z-basis outcome of the requested qubits
+ * into `probabilities_out`, which must have space for 2^n_qubits
+ * elements. The first qubit provided is the least significant bit of
+ * the outcome index. The state is not disturbed.
+ */
+int32_t selene_simulator_marginal_probabilities(SeleneSimulatorInstance instance,
+                                                const uint64_t *qubits,
+                                                uint64_t n_qubits,
+                                                double *probabilities_out);
+
+/**
+ * Write the expectation value of a pauli string into `expectation_out`.
+ * The pauli acting on each of the requested qubits is given by the
+ * corresponding element of `paulis`: 0 for I, 1 for X, 2 for Y and
+ * 3 for Z. The state is not disturbed.
+ */
+int32_t selene_simulator_pauli_expectation(SeleneSimulatorInstance instance,
+                                           const uint64_t *qubits,
+                                           const uint8_t *paulis,
+                                           uint64_t n_qubits,
+                                           double *expectation_out);
+
+/**
+ * Apply a pauli channel to qubit q0, applying X, Y or Z with
+ * probabilities p_x, p_y and p_z respectively.
+ */
+int32_t selene_simulator_channel_pauli(SeleneSimulatorInstance instance,
+                                       uint64_t q0,
+                                       double p_x,
+                                       double p_y,
+                                       double p_z);
+
+/**
+ * Apply an amplitude damping channel with decay probability gamma
+ * to qubit q0.
+ */
+int32_t selene_simulator_channel_amplitude_damping(SeleneSimulatorInstance instance,
+                                                   uint64_t q0,
+                                                   double gamma);
+
+/**
+ * Apply a phase damping channel with damping parameter lambda to
+ * qubit q0.
+ */
+int32_t selene_simulator_channel_phase_damping(SeleneSimulatorInstance instance,
+                                               uint64_t q0,
+                                               double lambda);
+
+/**
+ * Apply the channel defined by n_operators Kraus operators to the
+ * requested qubits. Each operator is a 2^n_qubits x 2^n_qubits matrix
+ * in row-major order, stored as interleaved real and imaginary parts,
+ * and the first qubit provided is the least significant bit of the
+ * matrix indices.
+ */
+int32_t selene_simulator_channel_kraus(SeleneSimulatorInstance instance,
+                                       const uint64_t *qubits,
+                                       uint64_t n_qubits,
+                                       const double *kraus,
+                                       uint64_t n_operators);
+
 /**
  * Get a metric from the simulator instance.
  *
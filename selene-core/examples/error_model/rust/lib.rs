@@ -72,6 +72,9 @@ impl ErrorModelInterface for ExampleErrorModel {
         self.rng = Pcg64Mcg::seed_from_u64(error_model_seed);
         Ok(())
     }
+    fn plan_shots(&mut self, shot_ids: &[u64], _seeds: &[u64], simulator_seeds: &[u64]) -> Result<()> {
+        self.simulator.plan_shots(shot_ids, simulator_seeds)
+    }
     fn shot_end(&mut self) -> Result<()> {
         self.simulator.shot_end()?;
         Ok(())
@@ -79,7 +82,10 @@ impl ErrorModelInterface for ExampleErrorModel {
 
     fn handle_operations(&mut self, operations: BatchOperation) -> Result<BatchResult> {
         let mut results = BatchResult::default();
-        for op in operations {
+        // This example only models errors on RXY, RZ and RZZ gates, so any
+        // U, two-qubit unitary or diagonal phase gates are first decomposed
+        // into those.
+        for op in operations.decompose_extended_gates() {
             match op {
                 Operation::RXYGate {
                     qubit_id,
@@ -219,6 +225,17 @@ impl ErrorModelInterface for ExampleErrorModel {
                 Operation::Custom { .. } => {
                     // Passively ignore custom operations
                 }
+                Operation::QAlloc { qubit_id } => {
+                    self.simulator.qalloc(qubit_id)?;
+                }
+                Operation::QFree { qubit_id } => {
+                    self.simulator.qfree(qubit_id)?;
+                }
+                Operation::UGate { .. }
+                | Operation::TwoQubitUnitaryGate { .. }
+                | Operation::DiagonalPhaseGate { .. } => {
+                    unreachable!("Extended gates have been decomposed")
+                }
             }
         }
         Ok(results)
@@ -130,7 +130,16 @@ impl RuntimeInterface for ExampleRuntime {
                             last_op_using_qubits = i;
                         }
                     }
-                    Operation::Custom { .. } => {}
+                    Operation::UGate { .. }
+                    | Operation::TwoQubitUnitaryGate { .. }
+                    | Operation::DiagonalPhaseGate { .. } => {
+                        if op.get_qubit_ids().iter().any(|q| qubits.contains(q)) {
+                            last_op_using_qubits = i;
+                        }
+                    }
+                    Operation::Custom { .. }
+                    | Operation::QAlloc { .. }
+                    | Operation::QFree { .. } => {}
                 }
             }
         }
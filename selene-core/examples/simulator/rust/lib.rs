@@ -19,6 +19,7 @@ pub struct ExampleSimulator {
     bias: f64,
     total_flips: u64,
     true_flips: u64,
+    n_snapshots: u64,
 }
 impl ExampleSimulator {
     pub fn flip(&mut self) -> bool {
@@ -117,6 +118,24 @@ impl SimulatorInterface for ExampleSimulator {
         }
     }
 
+    fn snapshot(&mut self) -> Result<u64> {
+        // Measurement outcomes are independent coin flips, so there is no
+        // quantum state to capture. We only need to hand out valid handles.
+        self.n_snapshots += 1;
+        Ok(self.n_snapshots - 1)
+    }
+
+    fn restore(&mut self, handle: u64) -> Result<()> {
+        if handle < self.n_snapshots {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Snapshot {handle} does not exist. Only {} snapshots have been taken.",
+                self.n_snapshots
+            ))
+        }
+    }
+
     fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
         match nth_metric {
             0 => {
@@ -155,6 +174,7 @@ impl SimulatorInterfaceFactory for ExampleSimulatorFactory {
                 bias: params.bias,
                 total_flips: 0,
                 true_flips: 0,
+                n_snapshots: 0,
             })),
         }
     }
@@ -3,6 +3,7 @@ use anyhow::Result;
 use std::ffi::OsStr;
 use std::sync;
 
+pub mod clocks;
 pub mod helper;
 pub mod interface;
 pub mod plugin;
@@ -72,6 +73,7 @@ impl ErrorModelInterface for ErrorModel {
     delegate! {
         to self.0.as_mut() {
             fn shot_start(&mut self, shot_id: u64, error_model_seed: u64, simulator_seed: u64) -> Result<()>;
+            fn plan_shots(&mut self, shot_ids: &[u64], seeds: &[u64], simulator_seeds: &[u64]) -> Result<()>;
             fn shot_end(&mut self) -> Result<()>;
             fn handle_operations(&mut self, operations: BatchOperation) -> Result<BatchResult>;
             fn exit(&mut self) -> Result<()>;
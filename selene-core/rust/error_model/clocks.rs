@@ -0,0 +1,59 @@
+//! Per-qubit clocks for error models whose noise depends on the time that
+//! passes while qubits are live.
+//!
+//! A qubit's clock starts when it is allocated (or reset, or first used if the
+//! runtime does not send allocation notifications), and stops when it is
+//! freed. While the clock runs, it holds the time up to which noise has been
+//! applied to the qubit, which each batch acting on it advances to the end of
+//! the batch.
+use anyhow::{Result, bail};
+
+#[derive(Debug, Clone)]
+pub struct QubitClocks {
+    last_update_ns: Vec<Option<u64>>,
+}
+
+impl QubitClocks {
+    /// Clocks for `n_qubits` qubits, all of which are stopped.
+    pub fn new(n_qubits: u64) -> Self {
+        Self {
+            last_update_ns: vec![None; n_qubits as usize],
+        }
+    }
+
+    /// Stop every clock, as at the start of a shot.
+    pub fn stop_all(&mut self) {
+        self.last_update_ns.fill(None);
+    }
+
+    /// Start (or restart) the clock of `qubit` at `time_ns`, as when it is
+    /// allocated or reset.
+    pub fn start(&mut self, qubit: u64, time_ns: u64) -> Result<()> {
+        *self.clock(qubit)? = Some(time_ns);
+        Ok(())
+    }
+
+    /// Stop the clock of `qubit`, as when it is freed.
+    pub fn stop(&mut self, qubit: u64) -> Result<()> {
+        *self.clock(qubit)? = None;
+        Ok(())
+    }
+
+    /// Advance the clock of `qubit` to `end_ns`, the end of a batch acting on
+    /// it, returning the time from the clock's previous value until `time_ns`.
+    /// A stopped clock is started instead, and None returned.
+    pub fn advance(&mut self, qubit: u64, time_ns: u64, end_ns: u64) -> Result<Option<u64>> {
+        let last_update_ns = self.clock(qubit)?.replace(end_ns);
+        Ok(last_update_ns.map(|last_update_ns| time_ns.saturating_sub(last_update_ns)))
+    }
+
+    fn clock(&mut self, qubit: u64) -> Result<&mut Option<u64>> {
+        let n_qubits = self.last_update_ns.len();
+        match self.last_update_ns.get_mut(qubit as usize) {
+            Some(clock) => Ok(clock),
+            None => {
+                bail!("Error: qubit {qubit} must be less than the number of qubits ({n_qubits}).")
+            }
+        }
+    }
+}
@@ -105,6 +105,26 @@ impl<F: ErrorModelInterfaceFactory> Helper<F> {
             }),
         )
     }
+    pub unsafe fn plan_shots(
+        instance: ErrorModelInstance,
+        shot_ids: *const u64,
+        error_model_seeds: *const u64,
+        simulator_seeds: *const u64,
+        n_shots: u64,
+    ) -> Errno {
+        let shot_ids = unsafe { std::slice::from_raw_parts(shot_ids, n_shots as usize) };
+        let error_model_seeds =
+            unsafe { std::slice::from_raw_parts(error_model_seeds, n_shots as usize) };
+        let simulator_seeds =
+            unsafe { std::slice::from_raw_parts(simulator_seeds, n_shots as usize) };
+        result_to_errno(
+            "Failed to plan shots",
+            Self::with_error_model_instance(instance, |e| {
+                e.plan_shots(shot_ids, error_model_seeds, simulator_seeds)
+            }),
+        )
+    }
+
     pub unsafe fn shot_end(instance: ErrorModelInstance) -> Errno {
         result_to_errno(
             "Failed to end the current shot",
@@ -146,8 +166,15 @@ impl<F: ErrorModelInterfaceFactory> Helper<F> {
             Self::with_error_model_instance(instance, |error_model| unsafe {
                 let mut batch_builder: BatchBuilder = BatchBuilder::default();
                 let (builder_instance, builder_interface) = batch_builder.runtime_get_operation();
-                let RuntimeExtractOperationInterface { extract_fn, .. } = &*extract_ops_interface;
-                extract_fn(extract_ops_instance, builder_instance, builder_interface);
+                let RuntimeExtractOperationInterface {
+                    extract_by_ref_fn, ..
+                } = &*extract_ops_interface;
+                extract_by_ref_fn(
+                    extract_ops_instance,
+                    builder_instance,
+                    &raw const builder_interface,
+                    std::mem::size_of_val(&builder_interface),
+                );
                 let results = error_model.handle_operations(batch_builder.finish())?;
                 let ErrorModelSetResultInterface {
                     set_bool_result_fn,
@@ -340,6 +367,27 @@ macro_rules! export_error_model_plugin {
                 Helper::shot_start(instance, shot_id, error_model_seed, simulator_seed)
             }
 
+            /// This function is called before the first of a run of shots is started, to
+            /// announce the ids of the shots that will follow and the seeds they will be
+            /// started with. Error models that pass the simulator seed to the simulator
+            /// unchanged should pass the plan on to the simulator's plan_shots function.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_error_model_plan_shots(
+                instance: ErrorModelInstance,
+                shot_ids: *const u64,
+                error_model_seeds: *const u64,
+                simulator_seeds: *const u64,
+                n_shots: u64,
+            ) -> Errno {
+                Helper::plan_shots(
+                    instance,
+                    shot_ids,
+                    error_model_seeds,
+                    simulator_seeds,
+                    n_shots,
+                )
+            }
+
             /// This function is called at the end of a shot, and it is responsible for
             /// finalising the error model plugin for that shot. For example, it may
             /// clean up any intra-shot state, such as accumulators or buffers. A call to
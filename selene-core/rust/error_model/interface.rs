@@ -30,6 +30,19 @@ pub trait ErrorModelInterface {
     /// deterministically repeated by reseeding the error model with the same seed,
     /// regardless of whether or not it is the first or Nth in a sequence of shots.
     fn shot_start(&mut self, shot_id: u64, seed: u64, _simulator_seed: u64) -> Result<()>;
+    /// Called before the first of a run of shots is started, to announce the shots that
+    /// will follow with the seeds that shot_start will be given for each of them. Error
+    /// models that start their simulator with the simulator seeds unchanged should pass
+    /// the plan on to the simulator's plan_shots, so that simulators that serve many
+    /// shots from a single pass can prepare them. The default is to ignore the plan.
+    fn plan_shots(
+        &mut self,
+        _shot_ids: &[u64],
+        _seeds: &[u64],
+        _simulator_seeds: &[u64],
+    ) -> Result<()> {
+        Ok(())
+    }
     /// Called to signal that the current shot has ended
     fn shot_end(&mut self) -> Result<()>;
     /// Called to get the simulator to dump its state to the provided file.
@@ -56,6 +56,21 @@ pub struct ErrorModelPluginInterface {
         ) -> Errno,
     >,
 
+    #[borrows(lib)]
+    #[covariant]
+    plan_shots_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(
+                handle: ErrorModelInstance,
+                shot_ids: *const u64,
+                error_model_seeds: *const u64,
+                simulator_seeds: *const u64,
+                n_shots: u64,
+            ) -> Errno,
+        >,
+    >,
+
     #[borrows(lib)]
     #[covariant]
     shot_end_fn:
@@ -147,6 +162,9 @@ impl ErrorModelPluginInterface {
             init_fn_builder: |lib| unsafe { lib.get(b"selene_error_model_init") },
             exit_fn_builder: |lib| unsafe { Ok(lib.get(b"selene_error_model_exit").ok()) },
             shot_start_fn_builder: |lib| unsafe { lib.get(b"selene_error_model_shot_start") },
+            plan_shots_fn_builder: |lib| unsafe {
+                Ok(lib.get(b"selene_error_model_plan_shots").ok())
+            },
             shot_end_fn_builder: |lib| unsafe { lib.get(b"selene_error_model_shot_end") },
             handle_operations_fn_builder: |lib| unsafe {
                 lib.get(b"selene_error_model_handle_operations")
@@ -242,6 +260,31 @@ impl ErrorModelInterface for ErrorModelPlugin {
             || anyhow!("ErrorModelPlugin: shot_start failed"),
         )
     }
+    fn plan_shots(
+        &mut self,
+        shot_ids: &[u64],
+        error_model_seeds: &[u64],
+        simulator_seeds: &[u64],
+    ) -> Result<()> {
+        let Some(plan_shots_fn) = self.interface.borrow_plan_shots_fn() else {
+            return Ok(());
+        };
+        if error_model_seeds.len() != shot_ids.len() || simulator_seeds.len() != shot_ids.len() {
+            bail!("ErrorModelPlugin: plan_shots requires seeds for each shot");
+        }
+        check_errno(
+            unsafe {
+                plan_shots_fn(
+                    self.instance,
+                    shot_ids.as_ptr(),
+                    error_model_seeds.as_ptr(),
+                    simulator_seeds.as_ptr(),
+                    shot_ids.len() as u64,
+                )
+            },
+            || anyhow!("ErrorModelPlugin: plan_shots failed"),
+        )
+    }
     fn shot_end(&mut self) -> Result<()> {
         check_errno(
             unsafe { self.interface.borrow_shot_end_fn()(self.instance) },
@@ -249,8 +292,26 @@ impl ErrorModelInterface for ErrorModelPlugin {
         )
     }
     fn handle_operations(&mut self, operations: BatchOperation) -> Result<BatchResult> {
-        let mut batch_extractor =
-            crate::runtime::plugin::BatchExtractor::from_batch_operation(operations);
+        let version = self.interface.borrow_version();
+        let (extended_gates, allocations) = (
+            version.supports_extended_gates(),
+            version.supports_allocations(),
+        );
+        let operations = if extended_gates {
+            operations
+        } else {
+            operations.decompose_extended_gates()
+        };
+        let operations = if allocations {
+            operations
+        } else {
+            operations.without_allocations()
+        };
+        let mut batch_extractor = crate::runtime::plugin::BatchExtractor::from_batch_operation(
+            operations,
+            extended_gates,
+            allocations,
+        );
         let (batch_instance, batch_interface) = batch_extractor.runtime_batch_extraction();
         let mut result_builder = BatchResultBuilder::default();
         let (result_instance, result_interface) = result_builder.error_model_set_result();
@@ -36,15 +36,42 @@ pub const CURRENT_API_VERSION: ErrorModelAPIVersion = ErrorModelAPIVersion {
     reserved: 0,
     major: 0,
     minor: 2,
-    patch: 0,
+    patch: 3,
 };
 
 // Changelog:
 // 0.1.0: Initial version.
 // 0.2.0: Replaced set_measurement_result with set_bool_result and set_u64_result in
 //   ErrorModelSetResultInterface
+// 0.2.1: Added the U, two-qubit unitary and diagonal phase gate functions to
+//   RuntimeGetOperationInterface. Error models at this version still receive these
+//   gates decomposed into RXY, RZ and RZZ gates (see 0.2.3).
+// 0.2.2: Added the qubit allocation and deallocation functions to
+//   RuntimeGetOperationInterface. Error models at this version still do not receive
+//   these notifications (see 0.2.3).
+// 0.2.3: Error models pass the operation interface to the runtime's extract_by_ref_fn by
+//   pointer along with its size. This is the first version to receive the extended gates
+//   and allocation notifications added in 0.2.1 and 0.2.2. Older error models pass the
+//   interface by value to extract_fn, and only the fields present in 0.2.0 are read, so
+//   they receive extended gates decomposed and no allocation notifications.
 
 impl ErrorModelAPIVersion {
+    /// Whether an error model with this version can receive the extended
+    /// gates (U, two-qubit unitary and diagonal phase gates) in a batch.
+    /// Error models before 0.2.3 pass the operation interface by value, so
+    /// the functions for these gates cannot safely be read from it.
+    pub fn supports_extended_gates(&self) -> bool {
+        (self.major, self.minor, self.patch) >= (0, 2, 3)
+    }
+
+    /// Whether an error model with this version can receive qubit allocation
+    /// and deallocation notifications in a batch. As with extended gates,
+    /// error models before 0.2.3 pass the operation interface by value, so
+    /// the allocation functions cannot safely be read from it.
+    pub fn supports_allocations(&self) -> bool {
+        (self.major, self.minor, self.patch) >= (0, 2, 3)
+    }
+
     pub fn validate(&self) -> Result<()> {
         // Note: this is a naive check at the moment, as we have not introduced a breaking
         // change since versioning was introduced. This logic should evolve as and when
@@ -1,3 +1,4 @@
+pub mod decompose;
 pub mod helper;
 pub mod interface;
 pub mod plugin;
@@ -53,6 +54,39 @@ pub enum Operation {
         qubit_id: u64,
         result_id: u64,
     },
+    /// A general single-qubit unitary
+    ///   ⎡ cos(θ/2)          -e^{iλ}⋅sin(θ/2)     ⎤
+    ///   ⎣ e^{iφ}⋅sin(θ/2)    e^{i(φ+λ)}⋅cos(θ/2) ⎦
+    UGate {
+        qubit_id: u64,
+        theta: f64,
+        phi: f64,
+        lambda: f64,
+    },
+    /// A general two-qubit unitary, given as a row-major 4x4 matrix stored as
+    /// interleaved real and imaginary parts, with `qubit_id_1` as the least
+    /// significant bit of the row and column indices.
+    TwoQubitUnitaryGate {
+        qubit_id_1: u64,
+        qubit_id_2: u64,
+        matrix: Box<[f64; 32]>,
+    },
+    /// A diagonal gate applying the phase e^{i phases[k]} to the basis state
+    /// k of the given qubits, with the first qubit as the least significant
+    /// bit of k.
+    DiagonalPhaseGate {
+        qubit_ids: Box<[u64]>,
+        phases: Box<[f64]>,
+    },
+    /// A notification that the runtime has allocated the given qubit. This
+    /// allows simulators to only hold the qubits that are in use.
+    QAlloc {
+        qubit_id: u64,
+    },
+    /// A notification that the runtime has freed the given qubit.
+    QFree {
+        qubit_id: u64,
+    },
 }
 
 impl Operation {
@@ -62,7 +96,10 @@ impl Operation {
             | Operation::Reset { qubit_id }
             | Operation::RXYGate { qubit_id, .. }
             | Operation::RZGate { qubit_id, .. }
-            | Operation::MeasureLeaked { qubit_id, .. } => {
+            | Operation::MeasureLeaked { qubit_id, .. }
+            | Operation::UGate { qubit_id, .. }
+            | Operation::QAlloc { qubit_id }
+            | Operation::QFree { qubit_id } => {
                 let mut set = HashSet::new();
                 set.insert(*qubit_id);
                 set
@@ -71,15 +108,60 @@ impl Operation {
                 qubit_id_1,
                 qubit_id_2,
                 ..
+            }
+            | Operation::TwoQubitUnitaryGate {
+                qubit_id_1,
+                qubit_id_2,
+                ..
             } => {
                 let mut set = HashSet::new();
                 set.insert(*qubit_id_1);
                 set.insert(*qubit_id_2);
                 set
             }
+            Operation::DiagonalPhaseGate { qubit_ids, .. } => qubit_ids.iter().copied().collect(),
             Operation::Custom { .. } => HashSet::new(),
         }
     }
+
+    /// Whether this is one of the extended gates (U, two-qubit unitary and
+    /// diagonal phase gates), which plugins are not required to support.
+    pub fn is_extended_gate(&self) -> bool {
+        matches!(
+            self,
+            Operation::UGate { .. }
+                | Operation::TwoQubitUnitaryGate { .. }
+                | Operation::DiagonalPhaseGate { .. }
+        )
+    }
+
+    /// Whether this is a qubit allocation or deallocation notification, which
+    /// plugins are not required to support.
+    pub fn is_allocation(&self) -> bool {
+        matches!(self, Operation::QAlloc { .. } | Operation::QFree { .. })
+    }
+
+    /// Express an extended gate in terms of RXY, RZ and RZZ gates, up to a
+    /// global phase. Any other operation is returned unchanged.
+    pub fn decompose(self) -> Vec<Operation> {
+        match self {
+            Operation::UGate {
+                qubit_id,
+                theta,
+                phi,
+                lambda,
+            } => decompose::u_gate(qubit_id, theta, phi, lambda),
+            Operation::TwoQubitUnitaryGate {
+                qubit_id_1,
+                qubit_id_2,
+                matrix,
+            } => decompose::two_qubit_unitary_gate(qubit_id_1, qubit_id_2, &matrix),
+            Operation::DiagonalPhaseGate { qubit_ids, phases } => {
+                decompose::diagonal_phase_gate(&qubit_ids, &phases)
+            }
+            op => vec![op],
+        }
+    }
 }
 
 #[derive(Default, Clone, Debug)]
@@ -135,6 +217,38 @@ impl BatchOperation {
     pub fn add_operation(&mut self, op: Operation) {
         self.ops.push(op);
     }
+
+    /// Replace any extended gates in the batch with their decompositions, for
+    /// consumers that do not support them natively.
+    pub fn decompose_extended_gates(self) -> Self {
+        if !self.ops.iter().any(Operation::is_extended_gate) {
+            return self;
+        }
+        Self {
+            ops: self
+                .ops
+                .into_iter()
+                .flat_map(Operation::decompose)
+                .collect(),
+            ..self
+        }
+    }
+
+    /// Remove any qubit allocation and deallocation notifications from the
+    /// batch, for consumers that do not support them.
+    pub fn without_allocations(self) -> Self {
+        if !self.ops.iter().any(Operation::is_allocation) {
+            return self;
+        }
+        Self {
+            ops: self
+                .ops
+                .into_iter()
+                .filter(|op| !op.is_allocation())
+                .collect(),
+            ..self
+        }
+    }
 }
 
 impl IntoIterator for BatchOperation {
@@ -196,6 +310,9 @@ impl RuntimeInterface for Runtime {
             fn rxy_gate(&mut self, qubit_id: u64, theta: f64, phi: f64) -> Result<()>;
             fn rzz_gate(&mut self, qubit_id_1: u64, qubit_id_2: u64, theta: f64) -> Result<()>;
             fn rz_gate(&mut self, qubit_id: u64, theta: f64) -> Result<()>;
+            fn u_gate(&mut self, qubit_id: u64, theta: f64, phi: f64, lambda: f64) -> Result<()>;
+            fn two_qubit_unitary_gate(&mut self, qubit_id_1: u64, qubit_id_2: u64, matrix: &[f64; 32]) -> Result<()>;
+            fn diagonal_phase_gate(&mut self, qubit_ids: &[u64], phases: &[f64]) -> Result<()>;
             fn measure(&mut self, qubit_id: u64) -> Result<u64>;
             fn measure_leaked(&mut self, qubit_id: u64) -> Result<u64>;
             fn reset(&mut self, qubit_id: u64) -> Result<()>;
@@ -0,0 +1,579 @@
+//! Decompositions of the extended gate set ([Operation::UGate],
+//! [Operation::TwoQubitUnitaryGate] and [Operation::DiagonalPhaseGate]) into
+//! the RXY, RZ and RZZ gates that every runtime, error model and simulator
+//! supports. These are used as a fallback whenever a plugin does not support
+//! an extended gate natively.
+//!
+//! All decompositions are exact up to a global phase, using the conventions
+//!     RZ(θ) = diag(e^{-iθ/2}, e^{iθ/2})
+//!     RZZ(θ) ∝ diag(1, e^{iθ}, e^{iθ}, 1)
+//! and RXY(θ, π/2) = RY(θ).
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use anyhow::{Result, bail};
+
+use super::Operation;
+
+/// A minimal complex number, sufficient for the arithmetic required below.
+#[derive(Clone, Copy, Debug)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    const ZERO: Self = Self { re: 0.0, im: 0.0 };
+
+    fn conj(self) -> Self {
+        Self {
+            re: self.re,
+            im: -self.im,
+        }
+    }
+    fn norm_sqr(self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+    fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+    fn scale(self, factor: f64) -> Self {
+        Self {
+            re: self.re * factor,
+            im: self.im * factor,
+        }
+    }
+    fn mul(self, other: Self) -> Self {
+        Self {
+            re: self.re * other.re - self.im * other.im,
+            im: self.re * other.im + self.im * other.re,
+        }
+    }
+    fn add(self, other: Self) -> Self {
+        Self {
+            re: self.re + other.re,
+            im: self.im + other.im,
+        }
+    }
+    fn sub(self, other: Self) -> Self {
+        Self {
+            re: self.re - other.re,
+            im: self.im - other.im,
+        }
+    }
+}
+
+/// Check that `phases` has one entry per computational basis state of
+/// `qubit_ids`, and that the qubits are distinct.
+pub fn validate_diagonal_phases(qubit_ids: &[u64], phases: &[f64]) -> Result<()> {
+    if qubit_ids.is_empty() || qubit_ids.len() >= usize::BITS as usize {
+        bail!(
+            "Diagonal phase gates must act on between 1 and {} qubits, got {}.",
+            usize::BITS - 1,
+            qubit_ids.len()
+        );
+    }
+    if phases.len() != 1 << qubit_ids.len() {
+        bail!(
+            "A diagonal phase gate on {} qubits requires {} phases, got {}.",
+            qubit_ids.len(),
+            1usize << qubit_ids.len(),
+            phases.len()
+        );
+    }
+    for (i, qubit_id) in qubit_ids.iter().enumerate() {
+        if qubit_ids[..i].contains(qubit_id) {
+            bail!("Diagonal phase gates must act on distinct qubits, but {qubit_id} is repeated.");
+        }
+    }
+    Ok(())
+}
+
+/// Check that `matrix` is a unitary 4x4 matrix in the format accepted by
+/// [two_qubit_unitary_gate], and that the qubits are distinct.
+pub fn validate_two_qubit_unitary(
+    qubit_id_1: u64,
+    qubit_id_2: u64,
+    matrix: &[f64; 32],
+) -> Result<()> {
+    if qubit_id_1 == qubit_id_2 {
+        bail!("Two-qubit unitaries must act on distinct qubits, but {qubit_id_1} is repeated.");
+    }
+    crate::simulator::validate_kraus_operators(2, matrix)
+        .map(|_| ())
+        .map_err(|_| anyhow::anyhow!("The provided two-qubit matrix is not unitary."))
+}
+
+/// U(θ, φ, λ) = e^{i(φ+λ)/2} RZ(φ) RY(θ) RZ(λ).
+pub fn u_gate(qubit_id: u64, theta: f64, phi: f64, lambda: f64) -> Vec<Operation> {
+    vec![
+        Operation::RZGate {
+            qubit_id,
+            theta: lambda,
+        },
+        Operation::RXYGate {
+            qubit_id,
+            theta,
+            phi: FRAC_PI_2,
+        },
+        Operation::RZGate {
+            qubit_id,
+            theta: phi,
+        },
+    ]
+}
+
+/// A diagonal gate applying e^{i phases[k]} to the basis state k, where the
+/// first qubit is the least significant bit of k.
+///
+/// The phase function is expanded in the Walsh basis as a sum of terms
+/// c_S Z_S over subsets S of the qubits. Each term is a single RZ or RZZ
+/// gate, with the parity of larger subsets gathered by a CX ladder.
+pub fn diagonal_phase_gate(qubit_ids: &[u64], phases: &[f64]) -> Vec<Operation> {
+    let mut coefficients = phases.to_vec();
+    let mut half = 1;
+    while half < coefficients.len() {
+        for block in coefficients.chunks_mut(2 * half) {
+            let (lo, hi) = block.split_at_mut(half);
+            for (a, b) in lo.iter_mut().zip(hi.iter_mut()) {
+                (*a, *b) = (*a + *b, *a - *b);
+            }
+        }
+        half *= 2;
+    }
+    let norm = coefficients.len() as f64;
+    let mut ops = Vec::new();
+    // The empty subset is a global phase, and is skipped.
+    for (subset, coefficient) in coefficients.into_iter().enumerate().skip(1) {
+        let coefficient = coefficient / norm;
+        if coefficient.abs() < 1e-15 {
+            continue;
+        }
+        let qubits: Vec<u64> = qubit_ids
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (subset >> i) & 1 == 1)
+            .map(|(_, &q)| q)
+            .collect();
+        // exp(i c Z_S) is an RZ or RZZ with angle -2c.
+        let theta = -2.0 * coefficient;
+        match qubits.as_slice() {
+            [qubit_id] => ops.push(Operation::RZGate {
+                qubit_id: *qubit_id,
+                theta,
+            }),
+            [.., second_last, last] => {
+                let ladder = &qubits[..qubits.len() - 1];
+                for pair in ladder.windows(2) {
+                    cx(&mut ops, pair[0], pair[1]);
+                }
+                ops.push(Operation::RZZGate {
+                    qubit_id_1: *second_last,
+                    qubit_id_2: *last,
+                    theta,
+                });
+                for pair in ladder.windows(2).rev() {
+                    cx(&mut ops, pair[0], pair[1]);
+                }
+            }
+            [] => unreachable!("Non-empty subsets contain at least one qubit"),
+        }
+    }
+    ops
+}
+
+/// A general two-qubit unitary, given as a row-major 4x4 matrix of
+/// interleaved real and imaginary parts, with `qubit_id_1` as the least
+/// significant bit of the row and column indices.
+///
+/// The matrix is reduced to a diagonal by a sequence of two-level (Givens)
+/// rotations between basis states that differ in a single bit, each of which
+/// is a controlled single-qubit unitary. The remaining diagonal is then a
+/// diagonal phase gate.
+pub fn two_qubit_unitary_gate(
+    qubit_id_1: u64,
+    qubit_id_2: u64,
+    matrix: &[f64; 32],
+) -> Vec<Operation> {
+    let mut m = [[Complex::ZERO; 4]; 4];
+    for (row, m_row) in m.iter_mut().enumerate() {
+        for (col, element) in m_row.iter_mut().enumerate() {
+            let index = 2 * (4 * row + col);
+            *element = Complex {
+                re: matrix[index],
+                im: matrix[index + 1],
+            };
+        }
+    }
+    let qubits = [qubit_id_1, qubit_id_2];
+    // Visiting the basis states in Gray code order ensures that every
+    // rotation acts between neighbouring states.
+    const GRAY: [usize; 4] = [0, 1, 3, 2];
+    // Each entry is (p, q, V), where V is the 2x2 block of a rotation G† in
+    // the (p, q) subspace. The rotations satisfy G_K ... G_1 U = D.
+    let mut rotations = Vec::new();
+    for (i, &col) in GRAY.iter().enumerate().take(3) {
+        for r in (i + 1..4).rev() {
+            let (p, q) = (GRAY[r - 1], GRAY[r]);
+            let (x, y) = (m[p][col], m[q][col]);
+            let norm = (x.norm_sqr() + y.norm_sqr()).sqrt();
+            if y.norm_sqr() < 1e-30 {
+                continue;
+            }
+            let (x, y) = (x.scale(1.0 / norm), y.scale(1.0 / norm));
+            let (row_p, row_q) = (m[p], m[q]);
+            for (c, (&mp, &mq)) in row_p.iter().zip(row_q.iter()).enumerate() {
+                m[p][c] = x.conj().mul(mp).add(y.conj().mul(mq));
+                m[q][c] = x.mul(mq).sub(y.mul(mp));
+            }
+            let minus_y_conj = Complex::ZERO.sub(y.conj());
+            rotations.push((p, q, [[x, minus_y_conj], [y, x.conj()]]));
+        }
+    }
+    let phases: Vec<f64> = (0..4).map(|k| m[k][k].arg()).collect();
+    // U = G_1† ... G_K† D, so D is applied first.
+    let mut ops = diagonal_phase_gate(&qubits, &phases);
+    for (p, q, block) in rotations.into_iter().rev() {
+        let target_bit = (p ^ q).trailing_zeros() as usize;
+        let control_bit = 1 - target_bit;
+        // Order the block such that the first row acts on the target's |0>.
+        let v = if (p >> target_bit) & 1 == 0 {
+            block
+        } else {
+            [[block[1][1], block[1][0]], [block[0][1], block[0][0]]]
+        };
+        controlled(
+            &mut ops,
+            qubits[control_bit],
+            (p >> control_bit) & 1 == 1,
+            qubits[target_bit],
+            v,
+        );
+    }
+    ops
+}
+
+/// CX (up to a global phase), as a CZ conjugated by RY rotations on the target.
+fn cx(ops: &mut Vec<Operation>, control: u64, target: u64) {
+    ops.extend([
+        Operation::RXYGate {
+            qubit_id: target,
+            theta: -FRAC_PI_2,
+            phi: FRAC_PI_2,
+        },
+        Operation::RZGate {
+            qubit_id: control,
+            theta: FRAC_PI_2,
+        },
+        Operation::RZGate {
+            qubit_id: target,
+            theta: FRAC_PI_2,
+        },
+        Operation::RZZGate {
+            qubit_id_1: control,
+            qubit_id_2: target,
+            theta: -FRAC_PI_2,
+        },
+        Operation::RXYGate {
+            qubit_id: target,
+            theta: FRAC_PI_2,
+            phi: FRAC_PI_2,
+        },
+    ]);
+}
+
+/// Apply the 2x2 unitary `v` to `target` when `control` is in the state
+/// `control_value`, using the standard construction
+///     e^{iα} A X B X C
+/// with ABC = I, where X is a CX from the control.
+fn controlled(
+    ops: &mut Vec<Operation>,
+    control: u64,
+    control_value: bool,
+    target: u64,
+    v: [[Complex; 2]; 2],
+) {
+    // v = e^{iα} W with W in SU(2), and W = RZ(β) RY(γ) RZ(δ).
+    let det = v[0][0].mul(v[1][1]).sub(v[0][1].mul(v[1][0]));
+    let alpha = det.arg() / 2.0;
+    let (a, b) = (v[0][0], v[1][0]);
+    let (arg_a, arg_b) = (a.arg() - alpha, b.arg() - alpha);
+    let gamma = 2.0 * b.norm_sqr().sqrt().atan2(a.norm_sqr().sqrt());
+    let beta = arg_b - arg_a;
+    let delta = -arg_a - arg_b;
+
+    let flip = |ops: &mut Vec<Operation>| {
+        ops.push(Operation::RXYGate {
+            qubit_id: control,
+            theta: PI,
+            phi: 0.0,
+        })
+    };
+    if !control_value {
+        flip(ops);
+    }
+    // C = RZ((δ-β)/2)
+    ops.push(Operation::RZGate {
+        qubit_id: target,
+        theta: (delta - beta) / 2.0,
+    });
+    cx(ops, control, target);
+    // B = RY(-γ/2) RZ(-(δ+β)/2)
+    ops.push(Operation::RZGate {
+        qubit_id: target,
+        theta: -(delta + beta) / 2.0,
+    });
+    ops.push(Operation::RXYGate {
+        qubit_id: target,
+        theta: -gamma / 2.0,
+        phi: FRAC_PI_2,
+    });
+    cx(ops, control, target);
+    // A = RZ(β) RY(γ/2)
+    ops.push(Operation::RXYGate {
+        qubit_id: target,
+        theta: gamma / 2.0,
+        phi: FRAC_PI_2,
+    });
+    ops.push(Operation::RZGate {
+        qubit_id: target,
+        theta: beta,
+    });
+    // diag(1, e^{iα}) on the control, up to a global phase.
+    ops.push(Operation::RZGate {
+        qubit_id: control,
+        theta: alpha,
+    });
+    if !control_value {
+        flip(ops);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(re: f64, im: f64) -> Complex {
+        Complex { re, im }
+    }
+
+    fn phase(theta: f64) -> Complex {
+        c(theta.cos(), theta.sin())
+    }
+
+    /// Apply a 2x2 matrix to `qubit` of a statevector.
+    fn apply_1q(state: &mut [Complex], qubit: u64, m: [[Complex; 2]; 2]) {
+        let bit = 1 << qubit;
+        for i in (0..state.len()).filter(|i| i & bit == 0) {
+            let (a, b) = (state[i], state[i | bit]);
+            state[i] = m[0][0].mul(a).add(m[0][1].mul(b));
+            state[i | bit] = m[1][0].mul(a).add(m[1][1].mul(b));
+        }
+    }
+
+    fn apply(state: &mut [Complex], op: &Operation) {
+        match *op {
+            Operation::RZGate { qubit_id, theta } => apply_1q(
+                state,
+                qubit_id,
+                [
+                    [phase(-theta / 2.0), Complex::ZERO],
+                    [Complex::ZERO, phase(theta / 2.0)],
+                ],
+            ),
+            Operation::RXYGate {
+                qubit_id,
+                theta,
+                phi,
+            } => {
+                let (cos, sin) = ((theta / 2.0).cos(), (theta / 2.0).sin());
+                let off_diagonal = |phi: f64| c(0.0, -sin).mul(phase(phi));
+                apply_1q(
+                    state,
+                    qubit_id,
+                    [
+                        [c(cos, 0.0), off_diagonal(-phi)],
+                        [off_diagonal(phi), c(cos, 0.0)],
+                    ],
+                )
+            }
+            Operation::RZZGate {
+                qubit_id_1,
+                qubit_id_2,
+                theta,
+            } => {
+                for (i, amplitude) in state.iter_mut().enumerate() {
+                    let parity = ((i >> qubit_id_1) ^ (i >> qubit_id_2)) & 1;
+                    let sign = if parity == 0 { -1.0 } else { 1.0 };
+                    *amplitude = amplitude.mul(phase(sign * theta / 2.0));
+                }
+            }
+            ref other => panic!("Unexpected operation in decomposition: {other:?}"),
+        }
+    }
+
+    /// The unitary implemented by `ops` on `n_qubits`, as columns.
+    fn unitary(n_qubits: usize, ops: &[Operation]) -> Vec<Vec<Complex>> {
+        (0..1 << n_qubits)
+            .map(|column| {
+                let mut state = vec![Complex::ZERO; 1 << n_qubits];
+                state[column] = c(1.0, 0.0);
+                for op in ops {
+                    apply(&mut state, op);
+                }
+                state
+            })
+            .collect()
+    }
+
+    /// Check that `actual` equals `expected` up to a global phase.
+    fn assert_equivalent(actual: &[Vec<Complex>], expected: &[Vec<Complex>]) {
+        let (column, row) = (0..expected.len())
+            .flat_map(|col| (0..expected.len()).map(move |row| (col, row)))
+            .max_by(|a, b| {
+                let norm = |(col, row): &(usize, usize)| expected[*col][*row].norm_sqr();
+                norm(a).total_cmp(&norm(b))
+            })
+            .unwrap();
+        let global = actual[column][row].arg() - expected[column][row].arg();
+        for (actual_column, expected_column) in actual.iter().zip(expected) {
+            for (a, e) in actual_column.iter().zip(expected_column) {
+                let difference = a.sub(e.mul(phase(global)));
+                assert!(
+                    difference.norm_sqr() < 1e-20,
+                    "Decomposition differs: {actual:?} != {expected:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn u_gate_matches_definition() {
+        for (theta, phi, lambda) in [(0.3, 1.1, -0.7), (PI, 0.0, PI), (FRAC_PI_2, 2.5, 0.4)] {
+            let (cos, sin) = ((theta / 2.0).cos(), (theta / 2.0).sin());
+            let expected = vec![
+                vec![c(cos, 0.0), phase(phi).scale(sin)],
+                vec![phase(lambda).scale(-sin), phase(phi + lambda).scale(cos)],
+            ];
+            assert_equivalent(&unitary(1, &u_gate(0, theta, phi, lambda)), &expected);
+        }
+    }
+
+    #[test]
+    fn diagonal_phase_gate_matches_definition() {
+        // The first qubit id is the least significant bit of the phase index,
+        // regardless of its position in the register.
+        let qubit_ids = [2, 0, 1];
+        let phases = [0.1, -0.4, 1.3, 2.0, 0.0, PI, -1.7, 0.6];
+        let expected: Vec<Vec<Complex>> = (0..8)
+            .map(|column| {
+                let index = (0..3)
+                    .filter(|i| (column >> qubit_ids[*i]) & 1 == 1)
+                    .map(|i| 1 << i)
+                    .sum::<usize>();
+                let mut state = vec![Complex::ZERO; 8];
+                state[column] = phase(phases[index]);
+                state
+            })
+            .collect();
+        let ops = diagonal_phase_gate(&qubit_ids, &phases);
+        assert_equivalent(&unitary(3, &ops), &expected);
+    }
+
+    #[test]
+    fn diagonal_phase_gate_validation() {
+        assert!(validate_diagonal_phases(&[0, 1], &[0.0; 4]).is_ok());
+        assert!(validate_diagonal_phases(&[0, 1], &[0.0; 3]).is_err());
+        assert!(validate_diagonal_phases(&[0, 0], &[0.0; 4]).is_err());
+        assert!(validate_diagonal_phases(&[], &[0.0]).is_err());
+    }
+
+    /// Flatten a 4x4 matrix, given as columns over the register, into the
+    /// row-major interleaved format with `qubit_id_1` as the least
+    /// significant bit, where the register is (qubit_id_1, qubit_id_2) =
+    /// (1, 0).
+    fn to_matrix(columns: &[Vec<Complex>]) -> [f64; 32] {
+        let swap = |i: usize| ((i & 1) << 1) | (i >> 1);
+        let mut matrix = [0.0; 32];
+        for row in 0..4 {
+            for col in 0..4 {
+                let element = columns[swap(col)][swap(row)];
+                matrix[2 * (4 * row + col)] = element.re;
+                matrix[2 * (4 * row + col) + 1] = element.im;
+            }
+        }
+        matrix
+    }
+
+    #[test]
+    fn two_qubit_unitary_gate_matches_definition() {
+        let generic = unitary(
+            2,
+            &[
+                Operation::RXYGate {
+                    qubit_id: 0,
+                    theta: 0.7,
+                    phi: 0.2,
+                },
+                Operation::RXYGate {
+                    qubit_id: 1,
+                    theta: 1.9,
+                    phi: -1.3,
+                },
+                Operation::RZZGate {
+                    qubit_id_1: 0,
+                    qubit_id_2: 1,
+                    theta: 0.8,
+                },
+                Operation::RZGate {
+                    qubit_id: 0,
+                    theta: 2.2,
+                },
+                Operation::RXYGate {
+                    qubit_id: 0,
+                    theta: -0.4,
+                    phi: 0.9,
+                },
+                Operation::RZZGate {
+                    qubit_id_1: 0,
+                    qubit_id_2: 1,
+                    theta: -1.6,
+                },
+                Operation::RXYGate {
+                    qubit_id: 1,
+                    theta: 0.5,
+                    phi: 2.8,
+                },
+            ],
+        );
+        let permutation = |map: [usize; 4]| -> Vec<Vec<Complex>> {
+            map.iter()
+                .map(|&target| {
+                    let mut column = vec![Complex::ZERO; 4];
+                    column[target] = c(1.0, 0.0);
+                    column
+                })
+                .collect()
+        };
+        let swap = permutation([0, 2, 1, 3]);
+        let cx = permutation([0, 1, 3, 2]);
+        let identity = permutation([0, 1, 2, 3]);
+        for expected in [generic, swap, cx, identity] {
+            let matrix = to_matrix(&expected);
+            validate_two_qubit_unitary(1, 0, &matrix).unwrap();
+            let ops = two_qubit_unitary_gate(1, 0, &matrix);
+            assert_equivalent(&unitary(2, &ops), &expected);
+        }
+    }
+
+    #[test]
+    fn two_qubit_unitary_validation() {
+        let mut matrix = [0.0; 32];
+        for k in 0..4 {
+            matrix[2 * (4 * k + k)] = 1.0;
+        }
+        assert!(validate_two_qubit_unitary(0, 1, &matrix).is_ok());
+        assert!(validate_two_qubit_unitary(0, 0, &matrix).is_err());
+        matrix[0] = 2.0;
+        assert!(validate_two_qubit_unitary(0, 1, &matrix).is_err());
+    }
+}
@@ -105,6 +105,11 @@ impl<F: RuntimeInterfaceFactory> Helper<F> {
                     reset_fn,
                     custom_fn,
                     set_batch_time_fn,
+                    u_fn,
+                    two_qubit_unitary_fn,
+                    diagonal_phase_fn,
+                    qalloc_fn,
+                    qfree_fn,
                     ..
                 } = unsafe { &*callbacks };
                 unsafe { set_batch_time_fn(goi, start.into(), duration.into()) };
@@ -136,6 +141,29 @@ impl<F: RuntimeInterfaceFactory> Helper<F> {
                             let (ptr, len) = (data.as_ptr() as *const ffi::c_void, data.len());
                             unsafe { custom_fn(goi, custom_tag, ptr, len) }
                         }
+                        Operation::UGate {
+                            qubit_id,
+                            theta,
+                            phi,
+                            lambda,
+                        } => unsafe { u_fn(goi, qubit_id, theta, phi, lambda) },
+                        Operation::TwoQubitUnitaryGate {
+                            qubit_id_1,
+                            qubit_id_2,
+                            matrix,
+                        } => unsafe {
+                            two_qubit_unitary_fn(goi, qubit_id_1, qubit_id_2, matrix.as_ptr())
+                        },
+                        Operation::DiagonalPhaseGate { qubit_ids, phases } => unsafe {
+                            diagonal_phase_fn(
+                                goi,
+                                qubit_ids.as_ptr(),
+                                qubit_ids.len() as u64,
+                                phases.as_ptr(),
+                            )
+                        },
+                        Operation::QAlloc { qubit_id } => unsafe { qalloc_fn(goi, qubit_id) },
+                        Operation::QFree { qubit_id } => unsafe { qfree_fn(goi, qubit_id) },
                     }
                 }
                 anyhow::Ok(())
@@ -252,6 +280,52 @@ impl<F: RuntimeInterfaceFactory> Helper<F> {
         )
     }
 
+    pub unsafe fn u_gate(
+        instance: RuntimeInstance,
+        qubit_id: u64,
+        theta: f64,
+        phi: f64,
+        lambda: f64,
+    ) -> Errno {
+        result_to_errno(
+            "Failed in u_gate",
+            Self::with_runtime_instance(instance, |runtime| {
+                runtime.u_gate(qubit_id, theta, phi, lambda)
+            }),
+        )
+    }
+
+    pub unsafe fn two_qubit_unitary_gate(
+        instance: RuntimeInstance,
+        qubit_id_1: u64,
+        qubit_id_2: u64,
+        matrix: *const f64,
+    ) -> Errno {
+        let matrix = unsafe { &*(matrix as *const [f64; 32]) };
+        result_to_errno(
+            "Failed in two_qubit_unitary_gate",
+            Self::with_runtime_instance(instance, |runtime| {
+                runtime.two_qubit_unitary_gate(qubit_id_1, qubit_id_2, matrix)
+            }),
+        )
+    }
+
+    pub unsafe fn diagonal_phase_gate(
+        instance: RuntimeInstance,
+        qubit_ids: *const u64,
+        qubit_ids_len: u64,
+        phases: *const f64,
+    ) -> Errno {
+        let qubit_ids = unsafe { std::slice::from_raw_parts(qubit_ids, qubit_ids_len as usize) };
+        let phases = unsafe { std::slice::from_raw_parts(phases, 1 << qubit_ids_len) };
+        result_to_errno(
+            "Failed in diagonal_phase_gate",
+            Self::with_runtime_instance(instance, |runtime| {
+                runtime.diagonal_phase_gate(qubit_ids, phases)
+            }),
+        )
+    }
+
     pub unsafe fn measure(instance: RuntimeInstance, qubit_id: u64, result: *mut u64) -> Errno {
         result_to_errno(
             "Failed in measure",
@@ -689,6 +763,47 @@ macro_rules! export_runtime_plugin {
                 Helper::rz_gate(instance, qubit_id, theta)
             }
 
+            /// Instruct the runtime to apply a general single-qubit unitary U(theta, phi, lambda)
+            /// to the qubit with the given ID. This is optional: if it is not exported, Selene
+            /// decomposes the gate into RZ and RXY gates instead.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_runtime_u_gate(
+                instance: RuntimeInstance,
+                qubit_id: u64,
+                theta: f64,
+                phi: f64,
+                lambda: f64,
+            ) -> i32 {
+                Helper::u_gate(instance, qubit_id, theta, phi, lambda)
+            }
+
+            /// Instruct the runtime to apply a general two-qubit unitary, given as a row-major
+            /// 4x4 matrix of interleaved real and imaginary parts, to the qubits with the given
+            /// IDs. This is optional: if it is not exported, Selene decomposes the gate into
+            /// RXY, RZ and RZZ gates instead.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_runtime_two_qubit_unitary_gate(
+                instance: RuntimeInstance,
+                qubit_id_1: u64,
+                qubit_id_2: u64,
+                matrix: *const f64,
+            ) -> i32 {
+                Helper::two_qubit_unitary_gate(instance, qubit_id_1, qubit_id_2, matrix)
+            }
+
+            /// Instruct the runtime to apply a diagonal phase gate, with 2^qubit_ids_len phases,
+            /// to the qubits with the given IDs. This is optional: if it is not exported, Selene
+            /// decomposes the gate into RXY, RZ and RZZ gates instead.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_runtime_diagonal_phase_gate(
+                instance: RuntimeInstance,
+                qubit_ids: *const u64,
+                qubit_ids_len: u64,
+                phases: *const f64,
+            ) -> i32 {
+                Helper::diagonal_phase_gate(instance, qubit_ids, qubit_ids_len, phases)
+            }
+
             /// Instruct the runtime that a measurement is to be requested and to write
             /// a reference ID to the result to the `result` pointer.
             ///
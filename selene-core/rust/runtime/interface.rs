@@ -3,7 +3,36 @@ use std::sync::Arc;
 
 use crate::utils::MetricValue;
 
-use super::BatchOperation;
+use super::{BatchOperation, Operation, decompose};
+
+/// Schedule the RXY, RZ and RZZ gates of a decomposition produced by
+/// [decompose] on a runtime.
+pub fn apply_decomposition<R: RuntimeInterface + ?Sized>(
+    runtime: &mut R,
+    operations: Vec<Operation>,
+) -> Result<()> {
+    for operation in operations {
+        match operation {
+            Operation::RXYGate {
+                qubit_id,
+                theta,
+                phi,
+            } => runtime.rxy_gate(qubit_id, theta, phi)?,
+            Operation::RZGate { qubit_id, theta } => runtime.rz_gate(qubit_id, theta)?,
+            Operation::RZZGate {
+                qubit_id_1,
+                qubit_id_2,
+                theta,
+            } => runtime.rzz_gate(qubit_id_1, qubit_id_2, theta)?,
+            other => {
+                return Err(anyhow!(
+                    "Unexpected operation in a decomposition: {other:?}"
+                ));
+            }
+        }
+    }
+    Ok(())
+}
 
 /// Instances of runtime plugins implement this interface.
 ///
@@ -78,6 +107,39 @@ pub trait RuntimeInterface {
     /// Schedule an RZ gate to allocated qubit `qubit_id` with the given angle.
     fn rz_gate(&mut self, qubit_id: u64, theta: f64) -> Result<()>;
 
+    /// Schedule a general single-qubit unitary U(θ, φ, λ) on allocated qubit
+    /// `qubit_id`. See [Operation::UGate] for the definition.
+    ///
+    /// By default the gate is decomposed into RZ and RXY gates. Runtimes
+    /// override this to pass the gate through to the error model natively.
+    fn u_gate(&mut self, qubit_id: u64, theta: f64, phi: f64, lambda: f64) -> Result<()> {
+        apply_decomposition(self, decompose::u_gate(qubit_id, theta, phi, lambda))
+    }
+
+    /// Schedule a general two-qubit unitary on allocated qubits `qubit_id_1` and
+    /// `qubit_id_2`. See [Operation::TwoQubitUnitaryGate] for the matrix layout.
+    ///
+    /// By default the gate is decomposed into RXY, RZ and RZZ gates.
+    fn two_qubit_unitary_gate(
+        &mut self,
+        qubit_id_1: u64,
+        qubit_id_2: u64,
+        matrix: &[f64; 32],
+    ) -> Result<()> {
+        apply_decomposition(
+            self,
+            decompose::two_qubit_unitary_gate(qubit_id_1, qubit_id_2, matrix),
+        )
+    }
+
+    /// Schedule a diagonal phase gate on the allocated qubits `qubit_ids`. See
+    /// [Operation::DiagonalPhaseGate] for the layout of `phases`.
+    ///
+    /// By default the gate is decomposed into RXY, RZ and RZZ gates.
+    fn diagonal_phase_gate(&mut self, qubit_ids: &[u64], phases: &[f64]) -> Result<()> {
+        apply_decomposition(self, decompose::diagonal_phase_gate(qubit_ids, phases))
+    }
+
     /// Schedule a measurement of allocated qubit `qubit_id`. The plugin should return a
     /// new result index. That result index must have a reference count of 1.
     fn measure(&mut self, qubit_id: u64) -> Result<u64>;
@@ -1,7 +1,10 @@
 use crate::runtime::Operation;
 use crate::utils::{MetricValue, check_errno, read_raw_metric, with_strings_to_cargs};
 
-use super::{BatchOperation, RuntimeAPIVersion, RuntimeInterface, RuntimeInterfaceFactory};
+use super::interface::apply_decomposition;
+use super::{
+    BatchOperation, RuntimeAPIVersion, RuntimeInterface, RuntimeInterfaceFactory, decompose,
+};
 use anyhow::{Result, anyhow};
 use core::slice;
 use libloading;
@@ -139,6 +142,49 @@ pub struct RuntimePluginInterface {
         unsafe extern "C" fn(handle: RuntimeInstance, qubit: u64, theta: f64) -> Errno,
     >,
 
+    #[borrows(lib)]
+    #[covariant]
+    u_gate_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(
+                handle: RuntimeInstance,
+                qubit: u64,
+                theta: f64,
+                phi: f64,
+                lambda: f64,
+            ) -> Errno,
+        >,
+    >,
+
+    #[borrows(lib)]
+    #[covariant]
+    two_qubit_unitary_gate_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(
+                handle: RuntimeInstance,
+                qubit0: u64,
+                qubit1: u64,
+                matrix: *const f64,
+            ) -> Errno,
+        >,
+    >,
+
+    #[borrows(lib)]
+    #[covariant]
+    diagonal_phase_gate_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(
+                handle: RuntimeInstance,
+                qubits: *const u64,
+                qubits_len: u64,
+                phases: *const f64,
+            ) -> Errno,
+        >,
+    >,
+
     #[borrows(lib)]
     #[covariant]
     measure_fn: libloading::Symbol<
@@ -276,6 +322,13 @@ impl RuntimePluginInterface {
             rxy_gate_fn_builder: |lib| unsafe { lib.get(b"selene_runtime_rxy_gate") },
             rzz_gate_fn_builder: |lib| unsafe { lib.get(b"selene_runtime_rzz_gate") },
             rz_gate_fn_builder: |lib| unsafe { lib.get(b"selene_runtime_rz_gate") },
+            u_gate_fn_builder: |lib| unsafe { Ok(lib.get(b"selene_runtime_u_gate").ok()) },
+            two_qubit_unitary_gate_fn_builder: |lib| unsafe {
+                Ok(lib.get(b"selene_runtime_two_qubit_unitary_gate").ok())
+            },
+            diagonal_phase_gate_fn_builder: |lib| unsafe {
+                Ok(lib.get(b"selene_runtime_diagonal_phase_gate").ok())
+            },
             measure_fn_builder: |lib| unsafe { lib.get(b"selene_runtime_measure") },
             measure_leaked_fn_builder: |lib| unsafe { lib.get(b"selene_runtime_measure_leaked") },
             reset_fn_builder: |lib| unsafe { lib.get(b"selene_runtime_reset") },
@@ -442,6 +495,56 @@ impl RuntimeInterface for RuntimePlugin {
         )
     }
 
+    fn u_gate(&mut self, qubit_id: u64, theta: f64, phi: f64, lambda: f64) -> Result<()> {
+        let Some(u_gate_fn) = self.interface.borrow_u_gate_fn() else {
+            return apply_decomposition(self, decompose::u_gate(qubit_id, theta, phi, lambda));
+        };
+        check_errno(
+            unsafe { u_gate_fn(self.instance, qubit_id, theta, phi, lambda) },
+            || anyhow!("RuntimePlugin: u_gate failed"),
+        )
+    }
+
+    fn two_qubit_unitary_gate(
+        &mut self,
+        qubit_id_1: u64,
+        qubit_id_2: u64,
+        matrix: &[f64; 32],
+    ) -> Result<()> {
+        decompose::validate_two_qubit_unitary(qubit_id_1, qubit_id_2, matrix)?;
+        let Some(two_qubit_unitary_gate_fn) = self.interface.borrow_two_qubit_unitary_gate_fn()
+        else {
+            return apply_decomposition(
+                self,
+                decompose::two_qubit_unitary_gate(qubit_id_1, qubit_id_2, matrix),
+            );
+        };
+        check_errno(
+            unsafe {
+                two_qubit_unitary_gate_fn(self.instance, qubit_id_1, qubit_id_2, matrix.as_ptr())
+            },
+            || anyhow!("RuntimePlugin: two_qubit_unitary_gate failed"),
+        )
+    }
+
+    fn diagonal_phase_gate(&mut self, qubit_ids: &[u64], phases: &[f64]) -> Result<()> {
+        decompose::validate_diagonal_phases(qubit_ids, phases)?;
+        let Some(diagonal_phase_gate_fn) = self.interface.borrow_diagonal_phase_gate_fn() else {
+            return apply_decomposition(self, decompose::diagonal_phase_gate(qubit_ids, phases));
+        };
+        check_errno(
+            unsafe {
+                diagonal_phase_gate_fn(
+                    self.instance,
+                    qubit_ids.as_ptr(),
+                    qubit_ids.len() as u64,
+                    phases.as_ptr(),
+                )
+            },
+            || anyhow!("RuntimePlugin: diagonal_phase_gate failed"),
+        )
+    }
+
     fn measure(&mut self, qubit_id: u64) -> Result<u64> {
         let mut result = 0;
         let result_ref = &mut result;
@@ -654,6 +757,58 @@ impl BatchBuilder {
         )
     }
 
+    unsafe extern "C" fn u(
+        interface: RuntimeGetOperationInstance,
+        qubit_id: u64,
+        theta: f64,
+        phi: f64,
+        lambda: f64,
+    ) {
+        Self::push(
+            interface,
+            Operation::UGate {
+                qubit_id,
+                theta,
+                phi,
+                lambda,
+            },
+        )
+    }
+
+    unsafe extern "C" fn two_qubit_unitary(
+        interface: RuntimeGetOperationInstance,
+        qubit_id_1: u64,
+        qubit_id_2: u64,
+        matrix: *const f64,
+    ) {
+        let matrix = Box::new(unsafe { *(matrix as *const [f64; 32]) });
+        Self::push(
+            interface,
+            Operation::TwoQubitUnitaryGate {
+                qubit_id_1,
+                qubit_id_2,
+                matrix,
+            },
+        )
+    }
+
+    unsafe extern "C" fn diagonal_phase(
+        interface: RuntimeGetOperationInstance,
+        qubit_ids: *const u64,
+        qubit_ids_len: u64,
+        phases: *const f64,
+    ) {
+        let qubit_ids = unsafe { slice::from_raw_parts(qubit_ids, qubit_ids_len as usize) };
+        let phases = unsafe { slice::from_raw_parts(phases, 1 << qubit_ids_len) };
+        Self::push(
+            interface,
+            Operation::DiagonalPhaseGate {
+                qubit_ids: qubit_ids.into(),
+                phases: phases.into(),
+            },
+        )
+    }
+
     unsafe extern "C" fn measure(
         interface: RuntimeGetOperationInstance,
         qubit_id: u64,
@@ -686,6 +841,14 @@ impl BatchBuilder {
         Self::push(interface, Operation::Reset { qubit_id })
     }
 
+    unsafe extern "C" fn qalloc(interface: RuntimeGetOperationInstance, qubit_id: u64) {
+        Self::push(interface, Operation::QAlloc { qubit_id })
+    }
+
+    unsafe extern "C" fn qfree(interface: RuntimeGetOperationInstance, qubit_id: u64) {
+        Self::push(interface, Operation::QFree { qubit_id })
+    }
+
     unsafe extern "C" fn custom(
         interface: RuntimeGetOperationInstance,
         custom_tag: usize,
@@ -728,6 +891,11 @@ impl BatchBuilder {
             reset_fn: Self::reset,
             custom_fn: Self::custom,
             set_batch_time_fn: Self::set_batch_time,
+            u_fn: Self::u,
+            two_qubit_unitary_fn: Self::two_qubit_unitary,
+            diagonal_phase_fn: Self::diagonal_phase,
+            qalloc_fn: Self::qalloc,
+            qfree_fn: Self::qfree,
             _marker: PhantomData,
         };
         (instance, interface)
@@ -756,6 +924,38 @@ pub type RuntimeGetOperationInstance = *mut ffi::c_void;
 /// within to populate a batch. All such calls must pass the instance as the
 /// first parameter.
 pub struct RuntimeGetOperationInterface<'a> {
+    pub rzz_fn: unsafe extern "C" fn(RuntimeGetOperationInstance, u64, u64, f64),
+    pub rxy_fn: unsafe extern "C" fn(RuntimeGetOperationInstance, u64, f64, f64),
+    pub rz_fn: unsafe extern "C" fn(RuntimeGetOperationInstance, u64, f64),
+    pub measure_fn: unsafe extern "C" fn(RuntimeGetOperationInstance, u64, u64),
+    pub measure_leaked_fn: unsafe extern "C" fn(RuntimeGetOperationInstance, u64, u64),
+    pub reset_fn: unsafe extern "C" fn(RuntimeGetOperationInstance, u64),
+    pub custom_fn:
+        unsafe extern "C" fn(RuntimeGetOperationInstance, usize, *const ffi::c_void, usize),
+    pub set_batch_time_fn: unsafe extern "C" fn(RuntimeGetOperationInstance, u64, u64),
+    // The following were introduced in runtime API 0.2.2, and the allocation
+    // functions below in runtime API 0.2.3. Runtimes built against older
+    // versions are not given them, and do not look for them. Error models
+    // only provide them from error model API 0.2.3, in which the interface
+    // is passed to BatchExtractor::extract_by_ref along with its size, and
+    // each group is only read if the error model supports it.
+    pub u_fn: unsafe extern "C" fn(RuntimeGetOperationInstance, u64, f64, f64, f64),
+    pub two_qubit_unitary_fn:
+        unsafe extern "C" fn(RuntimeGetOperationInstance, u64, u64, *const f64),
+    pub diagonal_phase_fn:
+        unsafe extern "C" fn(RuntimeGetOperationInstance, *const u64, u64, *const f64),
+    pub qalloc_fn: unsafe extern "C" fn(RuntimeGetOperationInstance, u64),
+    pub qfree_fn: unsafe extern "C" fn(RuntimeGetOperationInstance, u64),
+    _marker: PhantomData<&'a ()>,
+}
+
+#[repr(C)]
+#[non_exhaustive]
+/// The leading fields of [RuntimeGetOperationInterface], which every error
+/// model provides. Error models built against error model API 0.2.2 or
+/// earlier pass the interface by value to [BatchExtractor::extract], and the
+/// fields after these may not be present.
+pub struct RuntimeGetOperationBaseInterface<'a> {
     pub rzz_fn: unsafe extern "C" fn(RuntimeGetOperationInstance, u64, u64, f64),
     pub rxy_fn: unsafe extern "C" fn(RuntimeGetOperationInstance, u64, f64, f64),
     pub rz_fn: unsafe extern "C" fn(RuntimeGetOperationInstance, u64, f64),
@@ -769,71 +969,201 @@ pub struct RuntimeGetOperationInterface<'a> {
 }
 
 #[derive(Default)]
-pub struct BatchExtractor(BatchOperation);
+pub struct BatchExtractor {
+    batch: BatchOperation,
+    extended_gates: bool,
+    allocations: bool,
+}
+
+/// The functions of a [RuntimeGetOperationInterface] that an extraction may
+/// call. The extended gate and allocation functions are only present when
+/// the receiving error model supports them and provided them.
+struct ExtractTarget<'a> {
+    instance: RuntimeGetOperationInstance,
+    base: &'a RuntimeGetOperationBaseInterface<'a>,
+    extended_gates: Option<&'a RuntimeGetOperationInterface<'a>>,
+    allocations: Option<&'a RuntimeGetOperationInterface<'a>>,
+}
 
 impl BatchExtractor {
-    pub fn from_batch_operation(batch: BatchOperation) -> Self {
-        Self(batch)
+    /// Extract the given batch. `extended_gates` and `allocations` should
+    /// reflect whether the receiving error model supports them, as the
+    /// corresponding interface functions are not read otherwise.
+    pub fn from_batch_operation(
+        batch: BatchOperation,
+        extended_gates: bool,
+        allocations: bool,
+    ) -> Self {
+        Self {
+            batch,
+            extended_gates,
+            allocations,
+        }
     }
+
+    /// Called by error models built against error model API 0.2.2 or
+    /// earlier, which pass the interface by value. Only the leading fields
+    /// are read, so any extended gates are decomposed and allocation
+    /// notifications are dropped.
     pub unsafe extern "C" fn extract(
         instance_in: RuntimeExtractOperationInstance,
         instance_out: RuntimeGetOperationInstance,
-        interface_out: RuntimeGetOperationInterface,
+        interface_out: RuntimeGetOperationBaseInterface,
+    ) {
+        let extractor = unsafe { &*(instance_in as *const BatchExtractor) };
+        let target = ExtractTarget {
+            instance: instance_out,
+            base: &interface_out,
+            extended_gates: None,
+            allocations: None,
+        };
+        unsafe { extractor.extract_into(&target) }
+    }
+
+    /// Called by error models built against error model API 0.2.3 or later,
+    /// which pass the interface by pointer along with its size in bytes. The
+    /// fields after the leading ones are only read if the interface is large
+    /// enough to hold them, and the error model supports them.
+    pub unsafe extern "C" fn extract_by_ref(
+        instance_in: RuntimeExtractOperationInstance,
+        instance_out: RuntimeGetOperationInstance,
+        interface_out: *const RuntimeGetOperationInterface,
+        interface_size: usize,
     ) {
-        let batch_ptr = instance_in as *const BatchOperation;
-        let batch: &BatchOperation = unsafe { &*batch_ptr };
-        let RuntimeGetOperationInterface {
-            rzz_fn,
-            rxy_fn,
-            rz_fn,
-            measure_fn,
-            measure_leaked_fn,
-            reset_fn,
-            custom_fn,
-            set_batch_time_fn,
-            ..
-        } = interface_out;
-        unsafe { set_batch_time_fn(instance_out, batch.start().into(), batch.duration().into()) };
-        for operation in batch.iter_ops() {
-            match operation {
+        let extractor = unsafe { &*(instance_in as *const BatchExtractor) };
+        let base = unsafe { &*(interface_out as *const RuntimeGetOperationBaseInterface) };
+        let full = (interface_size >= std::mem::size_of::<RuntimeGetOperationInterface>())
+            .then(|| unsafe { &*interface_out });
+        let target = ExtractTarget {
+            instance: instance_out,
+            base,
+            extended_gates: full.filter(|_| extractor.extended_gates),
+            allocations: full.filter(|_| extractor.allocations),
+        };
+        unsafe { extractor.extract_into(&target) }
+    }
+
+    unsafe fn extract_into(&self, target: &ExtractTarget) {
+        unsafe {
+            (target.base.set_batch_time_fn)(
+                target.instance,
+                self.batch.start().into(),
+                self.batch.duration().into(),
+            )
+        };
+        for operation in self.batch.iter_ops() {
+            unsafe { Self::extract_operation(operation, target) };
+        }
+    }
+
+    unsafe fn extract_operation(operation: &Operation, target: &ExtractTarget) {
+        let ExtractTarget {
+            instance: instance_out,
+            base,
+            extended_gates,
+            allocations,
+        } = *target;
+        match (operation, extended_gates) {
+            (
                 Operation::Measure {
                     qubit_id,
                     result_id,
-                } => unsafe { measure_fn(instance_out, *qubit_id, *result_id) },
+                },
+                _,
+            ) => unsafe { (base.measure_fn)(instance_out, *qubit_id, *result_id) },
+            (
                 Operation::MeasureLeaked {
                     qubit_id,
                     result_id,
-                } => unsafe { measure_leaked_fn(instance_out, *qubit_id, *result_id) },
-                Operation::Reset { qubit_id } => unsafe { reset_fn(instance_out, *qubit_id) },
+                },
+                _,
+            ) => unsafe { (base.measure_leaked_fn)(instance_out, *qubit_id, *result_id) },
+            (Operation::Reset { qubit_id }, _) => unsafe {
+                (base.reset_fn)(instance_out, *qubit_id)
+            },
+            (
                 Operation::RXYGate {
                     qubit_id,
                     theta,
                     phi,
-                } => unsafe { rxy_fn(instance_out, *qubit_id, *theta, *phi) },
-                Operation::RZGate { qubit_id, theta } => unsafe {
-                    rz_fn(instance_out, *qubit_id, *theta)
                 },
+                _,
+            ) => unsafe { (base.rxy_fn)(instance_out, *qubit_id, *theta, *phi) },
+            (Operation::RZGate { qubit_id, theta }, _) => unsafe {
+                (base.rz_fn)(instance_out, *qubit_id, *theta)
+            },
+            (
                 Operation::RZZGate {
                     qubit_id_1,
                     qubit_id_2,
                     theta,
-                } => unsafe { rzz_fn(instance_out, *qubit_id_1, *qubit_id_2, *theta) },
-                Operation::Custom { custom_tag, data } => {
-                    let (ptr, len) = (data.as_ptr() as *const ffi::c_void, data.len());
-                    unsafe { custom_fn(instance_out, *custom_tag, ptr, len) }
+                },
+                _,
+            ) => unsafe { (base.rzz_fn)(instance_out, *qubit_id_1, *qubit_id_2, *theta) },
+            (Operation::Custom { custom_tag, data }, _) => {
+                let (ptr, len) = (data.as_ptr() as *const ffi::c_void, data.len());
+                unsafe { (base.custom_fn)(instance_out, *custom_tag, ptr, len) }
+            }
+            (
+                Operation::UGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                    lambda,
+                },
+                Some(interface),
+            ) => unsafe { (interface.u_fn)(instance_out, *qubit_id, *theta, *phi, *lambda) },
+            (
+                Operation::TwoQubitUnitaryGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    matrix,
+                },
+                Some(interface),
+            ) => unsafe {
+                (interface.two_qubit_unitary_fn)(
+                    instance_out,
+                    *qubit_id_1,
+                    *qubit_id_2,
+                    matrix.as_ptr(),
+                )
+            },
+            (Operation::DiagonalPhaseGate { qubit_ids, phases }, Some(interface)) => unsafe {
+                (interface.diagonal_phase_fn)(
+                    instance_out,
+                    qubit_ids.as_ptr(),
+                    qubit_ids.len() as u64,
+                    phases.as_ptr(),
+                )
+            },
+            (Operation::QAlloc { qubit_id }, _) => {
+                if let Some(interface) = allocations {
+                    unsafe { (interface.qalloc_fn)(instance_out, *qubit_id) }
+                }
+            }
+            (Operation::QFree { qubit_id }, _) => {
+                if let Some(interface) = allocations {
+                    unsafe { (interface.qfree_fn)(instance_out, *qubit_id) }
+                }
+            }
+            (extended_gate, None) => {
+                for operation in extended_gate.clone().decompose() {
+                    unsafe { Self::extract_operation(&operation, target) };
                 }
             }
         }
     }
+
     pub fn runtime_batch_extraction(
         &mut self,
     ) -> (
         RuntimeExtractOperationInstance,
         RuntimeExtractOperationInterface<'_>,
     ) {
-        let instance = &raw mut self.0 as RuntimeExtractOperationInstance;
+        let instance = self as *mut Self as RuntimeExtractOperationInstance;
         let reoi = RuntimeExtractOperationInterface {
             extract_fn: Self::extract,
+            extract_by_ref_fn: Self::extract_by_ref,
             _marker: PhantomData,
         };
         (instance, reoi)
@@ -847,7 +1177,14 @@ pub struct RuntimeExtractOperationInterface<'a> {
     pub extract_fn: unsafe extern "C" fn(
         RuntimeExtractOperationInstance,
         RuntimeGetOperationInstance,
-        RuntimeGetOperationInterface,
+        RuntimeGetOperationBaseInterface,
+    ),
+    // Introduced in error model API 0.2.3.
+    pub extract_by_ref_fn: unsafe extern "C" fn(
+        RuntimeExtractOperationInstance,
+        RuntimeGetOperationInstance,
+        *const RuntimeGetOperationInterface,
+        usize,
     ),
     _marker: PhantomData<&'a ()>,
 }
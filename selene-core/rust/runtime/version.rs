@@ -35,12 +35,16 @@ pub const CURRENT_API_VERSION: RuntimeAPIVersion = RuntimeAPIVersion {
     reserved: 0,
     major: 0,
     minor: 2,
-    patch: 1,
+    patch: 3,
 };
 
 // CHANGELOG:
 // 0.0.1: Initial version
 // 0.0.2: Introduced MeasureLeaked, changed get_result to get_bool_result and get_u64_result
+// 0.2.2: Introduced optional U, two-qubit unitary and diagonal phase gates, and appended
+//   their callbacks to RuntimeGetOperationInterface
+// 0.2.3: Introduced qubit allocation and deallocation notifications, and appended their
+//   callbacks to RuntimeGetOperationInterface
 
 impl RuntimeAPIVersion {
     pub fn validate(&self) -> Result<()> {
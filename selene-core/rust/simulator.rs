@@ -2,12 +2,16 @@ pub mod conformance_testing;
 pub mod helper;
 pub mod interface;
 pub mod plugin;
+pub mod recording;
 pub mod version;
 
 use std::ffi::OsStr;
 use std::sync::Arc;
 
-pub use interface::{SimulatorInterface, SimulatorInterfaceFactory};
+pub use interface::{
+    Pauli, SimulatorInterface, SimulatorInterfaceFactory, kraus_operator_size,
+    validate_kraus_operators, validate_pauli_channel,
+};
 pub use version::SimulatorAPIVersion;
 
 use crate::utils::MetricValue;
@@ -61,13 +65,30 @@ impl SimulatorInterface for Simulator {
         to self.0 {
             fn exit(&mut self) -> Result<()>;
             fn shot_start(&mut self, shot_id: u64, seed: u64) -> Result<()>;
+            fn plan_shots(&mut self, shot_ids: &[u64], seeds: &[u64]) -> Result<()>;
             fn shot_end(&mut self) -> Result<()>;
             fn rxy(&mut self, qubit: u64, theta: f64, phi: f64) -> Result<()>;
             fn rzz(&mut self, qubit1: u64, qubit2: u64, theta: f64) -> Result<()>;
             fn rz(&mut self, qubit: u64, theta: f64) -> Result<()>;
+            fn u(&mut self, qubit: u64, theta: f64, phi: f64, lambda: f64) -> Result<()>;
+            fn two_qubit_unitary(&mut self, qubit1: u64, qubit2: u64, matrix: &[f64; 32]) -> Result<()>;
+            fn diagonal_phase(&mut self, qubits: &[u64], phases: &[f64]) -> Result<()>;
             fn measure(&mut self, qubit: u64) -> Result<bool>;
             fn postselect(&mut self, qubit: u64, target_value: bool) -> Result<()>;
             fn reset(&mut self, qubit: u64) -> Result<()>;
+            fn qalloc(&mut self, qubit: u64) -> Result<()>;
+            fn qfree(&mut self, qubit: u64) -> Result<()>;
+            fn measure_leaked(&mut self, qubit: u64) -> Result<u64>;
+            fn leakage_rxy(&mut self, qubit: u64, theta: f64, phi: f64) -> Result<()>;
+            fn snapshot(&mut self) -> Result<u64>;
+            fn restore(&mut self, handle: u64) -> Result<()>;
+            fn release_snapshot(&mut self, handle: u64) -> Result<()>;
+            fn marginal_probabilities(&mut self, qubits: &[u64]) -> Result<Vec<f64>>;
+            fn pauli_expectation(&mut self, pauli_string: &[(u64, Pauli)]) -> Result<f64>;
+            fn pauli_channel(&mut self, qubit: u64, p_x: f64, p_y: f64, p_z: f64) -> Result<()>;
+            fn amplitude_damping(&mut self, qubit: u64, gamma: f64) -> Result<()>;
+            fn phase_damping(&mut self, qubit: u64, lambda: f64) -> Result<()>;
+            fn kraus_channel(&mut self, qubits: &[u64], kraus_operators: &[f64]) -> Result<()>;
             fn dump_state(&mut self, file: &std::path::Path, qubits: &[u64]) -> Result<()>;
             fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>>;
         }
@@ -8,10 +8,14 @@ and a test function that validates measurement outcomes. Tests
 do not impact subsequent operations, so one can test the state
 of the simulator at any point in the circuit.
 
-This works by generating a new simulator for each test shot, running
+This works by starting a new shot for each test iteration, running
 the circuit up until the test, and then measuring and invoking the
 test function. Once this test is complete, it won't be invoked for
-subsequent tests.
+subsequent tests. If the circuit up until the test contains no
+measurements or resets, every iteration reaches the same state, so
+on simulators that support snapshots it is simulated once and
+restored at the start of each iteration, and the snapshot is released
+once the test is complete.
 
 Consider the following example:
 
@@ -62,24 +66,22 @@ In this example, this is what will happen:
 
 # Run test 1
 - create a vector V of 2^2 elements, all initialized to 0
+- create a new 4-qubit simulator, run h(0) and cx(0, 1), and snapshot the result
 - for i in 0..100:
-  -  create a new 4-qubit simulator
-  -  run h(0)
-  -  run cx(0, 1)
+  -  start a new shot and restore the snapshot
   -  measure qubits 0 and 1, concatenated as an int e.g. 0b00, 0b01, 0b10 or 0b11
   -  increment the element in V corresponding to the measurement result
+- release the snapshot
 - run the first test function against V
 
 # Run test 2
 - create a vector V' of 2^4 elements, all initialized to 0
+- create a new 4-qubit simulator, run h(0), cx(0, 1), cx(0, 2) and cx(0, 3), and snapshot the result
 - for i in 0..200:
-  - create a new 4-qubit simulator
-  - run h(0)
-  - run cx(0, 1)
-  - run cx(0, 2)
-  - run cx(0, 3)
+  - start a new shot and restore the snapshot
   - measure all qubits, concatenated as an int, i.e. one of 0b0000, 0b0001, ..., 0b1111
   - increment the element in V' corresponding to the measurement result
+- release the snapshot
 - run the second test function against V'
 
 If any operations come *after* the last test, they will never be invoked.
@@ -101,6 +103,20 @@ enum Operation {
     Measure(u64),
 }
 
+impl Operation {
+    /// Whether the operation has a random outcome, in which case iterations
+    /// of a test can't share the state that follows it.
+    fn is_random(&self) -> bool {
+        matches!(self, Operation::Reset(_) | Operation::Measure(_))
+    }
+}
+
+/// A snapshot of the simulator, along with the phases tracked alongside it.
+struct EngineSnapshot {
+    handle: u64,
+    qubit_phases: Vec<f64>,
+}
+
 struct EngineState {
     simulator: Simulator,
     qubit_phases: Vec<f64>,
@@ -111,12 +127,9 @@ impl EngineState {
     pub fn new(
         simulator_interface: Arc<impl SimulatorInterfaceFactory + 'static>,
         n_qubits: u64,
-        shot_id: u64,
-        random_seed: u64,
         args: Vec<String>,
     ) -> Self {
-        let mut simulator = Simulator::new(simulator_interface, n_qubits, &args).unwrap();
-        simulator.shot_start(shot_id, random_seed).unwrap();
+        let simulator = Simulator::new(simulator_interface, n_qubits, &args).unwrap();
         Self {
             simulator,
             qubit_phases: vec![0.0; n_qubits as usize],
@@ -124,6 +137,26 @@ impl EngineState {
             n_measurements: 0,
         }
     }
+    pub fn start_shot(&mut self, shot_id: u64, random_seed: u64) {
+        self.simulator.shot_start(shot_id, random_seed).unwrap();
+        self.qubit_phases.fill(0.0);
+        self.circuit_measurements = 0;
+        self.n_measurements = 0;
+    }
+    pub fn snapshot(&mut self) -> Option<EngineSnapshot> {
+        let handle = self.simulator.snapshot().ok()?;
+        Some(EngineSnapshot {
+            handle,
+            qubit_phases: self.qubit_phases.clone(),
+        })
+    }
+    pub fn restore(&mut self, snapshot: &EngineSnapshot) {
+        self.simulator.restore(snapshot.handle).unwrap();
+        self.qubit_phases.clone_from(&snapshot.qubit_phases);
+    }
+    pub fn release(&mut self, snapshot: EngineSnapshot) {
+        self.simulator.release_snapshot(snapshot.handle).unwrap();
+    }
     pub fn run_operation(&mut self, operation: &Operation) {
         match operation {
             Operation::Rz(q, theta) => {
@@ -211,18 +244,31 @@ impl MeasurementTest {
         stage: usize,
         n_circuit_measurements: u64,
     ) -> Option<PopulationResult> {
-        //! Generate `n_iterations` circuits and perform the requested qubit measurements on each,
-        //! gathering populations of resulting states in a vector (indexed such that the first
-        //! qubit to measure is the LSB). The resulting vector is tested according to the supplied
-        //! function.
+        //! Run `n_iterations` shots of the circuit and perform the requested qubit measurements
+        //! on each, gathering populations of resulting states in a vector (indexed such that the
+        //! first qubit to measure is the LSB). The resulting vector is tested according to the
+        //! supplied function.
         //!
         //! If the test fails, return the population result for analysis.
         //! Otherwise, return None.
         let mut result =
             PopulationResult::new(n_circuit_measurements, self.qubits_to_measure.len() as u64);
+        let mut simulator_state = EngineState::new(interface, circuit.n_qubits, args);
+        // If every iteration reaches the same state, simulate the circuit once
+        // and branch from a snapshot of it, if the simulator supports snapshots.
+        let snapshot = if circuit.is_deterministic_to_stage(stage) {
+            simulator_state.start_shot(shot_id, 0);
+            circuit.run_to_stage(&mut simulator_state, stage);
+            simulator_state.snapshot()
+        } else {
+            None
+        };
         for i in 0..self.n_iterations {
-            let mut simulator_state =
-                circuit.run_to_stage(interface.clone(), args.clone(), shot_id, i, stage);
+            simulator_state.start_shot(shot_id, i);
+            match &snapshot {
+                Some(snapshot) => simulator_state.restore(snapshot),
+                None => circuit.run_to_stage(&mut simulator_state, stage),
+            }
             result.add_circuit_measurements(simulator_state.circuit_measurements as u64);
 
             let mut test_measurement: u64 = 0;
@@ -234,6 +280,9 @@ impl MeasurementTest {
 
             result.add_test_measurements(test_measurement);
         }
+        if let Some(snapshot) = snapshot {
+            simulator_state.release(snapshot);
+        }
         if (self.test)(&result) {
             None
         } else {
@@ -333,20 +382,18 @@ impl TestFramework {
             )));
         self
     }
-    fn run_to_stage(
-        &self,
-        interface: Arc<impl SimulatorInterfaceFactory + 'static>,
-        args: Vec<String>,
-        shot_id: u64,
-        random_seed: u64,
-        stage_index: usize,
-    ) -> EngineState {
-        //! Run the operations up to the specified stage,
-        //! returning the state of the simulator at that point.
+    fn is_deterministic_to_stage(&self, stage_index: usize) -> bool {
+        //! Whether the operations up to the specified stage
+        //! lead to the same state on every shot.
+        self.stages[..stage_index].iter().all(|stage| match stage {
+            CircuitTestOperation::Operation(operation) => !operation.is_random(),
+            CircuitTestOperation::Test(_) => true,
+        })
+    }
+    fn run_to_stage(&self, simulator_state: &mut EngineState, stage_index: usize) {
+        //! Run the operations up to the specified stage
+        //! on a simulator at the start of a shot.
         //! Tests are skipped, as they are destructive.
-        let mut simulator_state =
-            EngineState::new(interface, self.n_qubits, shot_id, random_seed, args);
-
         for stage in 0..stage_index {
             match &self.stages[stage] {
                 CircuitTestOperation::Operation(operation) => {
@@ -355,7 +402,6 @@ impl TestFramework {
                 CircuitTestOperation::Test(_) => {}
             }
         }
-        simulator_state
     }
     pub fn run(&self, interface: Arc<impl SimulatorInterfaceFactory + 'static>, args: Vec<String>) {
         let mut nth_test = 0;
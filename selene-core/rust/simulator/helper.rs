@@ -5,7 +5,7 @@
 use std::{ffi, mem, sync::Arc};
 
 use super::{
-    SimulatorInterface,
+    Pauli, SimulatorInterface,
     interface::SimulatorInterfaceFactory,
     plugin::{Errno, SimulatorInstance},
 };
@@ -83,6 +83,21 @@ impl<F: SimulatorInterfaceFactory> Helper<F> {
             }),
         )
     }
+    pub unsafe fn plan_shots(
+        instance: SimulatorInstance,
+        shot_ids: *const u64,
+        seeds: *const u64,
+        n_shots: u64,
+    ) -> Errno {
+        let shot_ids = unsafe { std::slice::from_raw_parts(shot_ids, n_shots as usize) };
+        let seeds = unsafe { std::slice::from_raw_parts(seeds, n_shots as usize) };
+        result_to_errno(
+            "Failed to plan shots",
+            Self::with_simulator_instance(instance, |simulator| {
+                simulator.plan_shots(shot_ids, seeds)
+            }),
+        )
+    }
     pub unsafe fn shot_end(instance: SimulatorInstance) -> Errno {
         result_to_errno(
             "Failed to end the current shot",
@@ -143,6 +158,49 @@ impl<F: SimulatorInterfaceFactory> Helper<F> {
             }),
         )
     }
+    pub unsafe fn u(
+        instance: SimulatorInstance,
+        qubit: u64,
+        theta: f64,
+        phi: f64,
+        lambda: f64,
+    ) -> Errno {
+        result_to_errno(
+            "Failed to apply U gate",
+            Self::with_simulator_instance(instance, |simulator| {
+                simulator.u(qubit, theta, phi, lambda)
+            }),
+        )
+    }
+    pub unsafe fn two_qubit_unitary(
+        instance: SimulatorInstance,
+        qubit1: u64,
+        qubit2: u64,
+        matrix: *const f64,
+    ) -> Errno {
+        let matrix = unsafe { &*(matrix as *const [f64; 32]) };
+        result_to_errno(
+            "Failed to apply two-qubit unitary",
+            Self::with_simulator_instance(instance, |simulator| {
+                simulator.two_qubit_unitary(qubit1, qubit2, matrix)
+            }),
+        )
+    }
+    pub unsafe fn diagonal_phase(
+        instance: SimulatorInstance,
+        qubits: *const u64,
+        n_qubits: u64,
+        phases: *const f64,
+    ) -> Errno {
+        let qubits = unsafe { std::slice::from_raw_parts(qubits, n_qubits as usize) };
+        let phases = unsafe { std::slice::from_raw_parts(phases, 1 << n_qubits) };
+        result_to_errno(
+            "Failed to apply diagonal phase gate",
+            Self::with_simulator_instance(instance, |simulator| {
+                simulator.diagonal_phase(qubits, phases)
+            }),
+        )
+    }
     pub unsafe fn measure(instance: SimulatorInstance, qubit: u64) -> Errno {
         let result = Self::with_simulator_instance(instance, |simulator| simulator.measure(qubit));
         match result {
@@ -168,6 +226,168 @@ impl<F: SimulatorInterfaceFactory> Helper<F> {
             Self::with_simulator_instance(instance, |simulator| simulator.reset(qubit)),
         )
     }
+    pub unsafe fn qalloc(instance: SimulatorInstance, qubit: u64) -> Errno {
+        result_to_errno(
+            "Failed to allocate qubit",
+            Self::with_simulator_instance(instance, |simulator| simulator.qalloc(qubit)),
+        )
+    }
+    pub unsafe fn qfree(instance: SimulatorInstance, qubit: u64) -> Errno {
+        result_to_errno(
+            "Failed to free qubit",
+            Self::with_simulator_instance(instance, |simulator| simulator.qfree(qubit)),
+        )
+    }
+    pub unsafe fn measure_leaked(instance: SimulatorInstance, qubit: u64) -> Errno {
+        let result =
+            Self::with_simulator_instance(instance, |simulator| simulator.measure_leaked(qubit));
+        match result {
+            Ok(value @ 0..=2) => value as Errno,
+            Ok(value) => {
+                eprintln!("Failed to measure qubit {qubit}: invalid leakage measurement {value}");
+                -1
+            }
+            Err(e) => {
+                eprintln!("Failed to measure qubit {qubit}: {e:?}");
+                -1
+            }
+        }
+    }
+    pub unsafe fn leakage_rxy(
+        instance: SimulatorInstance,
+        qubit: u64,
+        theta: f64,
+        phi: f64,
+    ) -> Errno {
+        result_to_errno(
+            "Failed to apply leakage RXY gate",
+            Self::with_simulator_instance(instance, |simulator| {
+                simulator.leakage_rxy(qubit, theta, phi)
+            }),
+        )
+    }
+    pub unsafe fn snapshot(instance: SimulatorInstance, handle_out: *mut u64) -> Errno {
+        result_to_errno(
+            "Failed to take snapshot",
+            Self::with_simulator_instance(instance, |simulator| simulator.snapshot()).map(
+                |handle| unsafe {
+                    *handle_out = handle;
+                },
+            ),
+        )
+    }
+    pub unsafe fn restore(instance: SimulatorInstance, handle: u64) -> Errno {
+        result_to_errno(
+            format!("Failed to restore snapshot {handle}"),
+            Self::with_simulator_instance(instance, |simulator| simulator.restore(handle)),
+        )
+    }
+    pub unsafe fn release_snapshot(instance: SimulatorInstance, handle: u64) -> Errno {
+        result_to_errno(
+            format!("Failed to release snapshot {handle}"),
+            Self::with_simulator_instance(instance, |simulator| simulator.release_snapshot(handle)),
+        )
+    }
+    pub unsafe fn marginal_probabilities(
+        instance: SimulatorInstance,
+        qubits: *const u64,
+        n_qubits: u64,
+        probabilities_out: *mut f64,
+    ) -> Errno {
+        let qubits = unsafe { std::slice::from_raw_parts(qubits, n_qubits as usize) };
+        result_to_errno(
+            "Failed to compute marginal probabilities",
+            Self::with_simulator_instance(instance, |simulator| -> anyhow::Result<()> {
+                let probabilities = simulator.marginal_probabilities(qubits)?;
+                if probabilities.len() != 1 << qubits.len() {
+                    anyhow::bail!(
+                        "Expected {} marginal probabilities, got {}",
+                        1u64 << qubits.len(),
+                        probabilities.len()
+                    );
+                }
+                unsafe {
+                    std::ptr::copy_nonoverlapping(
+                        probabilities.as_ptr(),
+                        probabilities_out,
+                        probabilities.len(),
+                    )
+                };
+                Ok(())
+            }),
+        )
+    }
+    pub unsafe fn pauli_expectation(
+        instance: SimulatorInstance,
+        qubits: *const u64,
+        paulis: *const u8,
+        n_qubits: u64,
+        expectation_out: *mut f64,
+    ) -> Errno {
+        let qubits = unsafe { std::slice::from_raw_parts(qubits, n_qubits as usize) };
+        let paulis = unsafe { std::slice::from_raw_parts(paulis, n_qubits as usize) };
+        result_to_errno(
+            "Failed to compute pauli expectation value",
+            Self::with_simulator_instance(instance, |simulator| -> anyhow::Result<()> {
+                let pauli_string = qubits
+                    .iter()
+                    .zip(paulis)
+                    .map(|(&qubit, &pauli)| Ok((qubit, Pauli::try_from(pauli)?)))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let expectation = simulator.pauli_expectation(&pauli_string)?;
+                unsafe { *expectation_out = expectation };
+                Ok(())
+            }),
+        )
+    }
+    pub unsafe fn pauli_channel(
+        instance: SimulatorInstance,
+        q0: u64,
+        p_x: f64,
+        p_y: f64,
+        p_z: f64,
+    ) -> Errno {
+        result_to_errno(
+            format!("Failed to apply pauli channel to qubit {q0}"),
+            Self::with_simulator_instance(instance, |simulator| {
+                simulator.pauli_channel(q0, p_x, p_y, p_z)
+            }),
+        )
+    }
+    pub unsafe fn amplitude_damping(instance: SimulatorInstance, q0: u64, gamma: f64) -> Errno {
+        result_to_errno(
+            format!("Failed to apply amplitude damping to qubit {q0}"),
+            Self::with_simulator_instance(instance, |simulator| {
+                simulator.amplitude_damping(q0, gamma)
+            }),
+        )
+    }
+    pub unsafe fn phase_damping(instance: SimulatorInstance, q0: u64, lambda: f64) -> Errno {
+        result_to_errno(
+            format!("Failed to apply phase damping to qubit {q0}"),
+            Self::with_simulator_instance(instance, |simulator| {
+                simulator.phase_damping(q0, lambda)
+            }),
+        )
+    }
+    pub unsafe fn kraus_channel(
+        instance: SimulatorInstance,
+        qubits: *const u64,
+        n_qubits: u64,
+        kraus: *const f64,
+        n_operators: u64,
+    ) -> Errno {
+        let qubits = unsafe { std::slice::from_raw_parts(qubits, n_qubits as usize) };
+        result_to_errno(
+            "Failed to apply kraus channel",
+            Self::with_simulator_instance(instance, |simulator| -> anyhow::Result<()> {
+                let size = super::kraus_operator_size(qubits.len())?;
+                let kraus_operators =
+                    unsafe { std::slice::from_raw_parts(kraus, size * n_operators as usize) };
+                simulator.kraus_channel(qubits, kraus_operators)
+            }),
+        )
+    }
 }
 
 #[macro_export]
@@ -302,6 +522,21 @@ macro_rules! export_simulator_plugin {
                 Helper::shot_start(instance, shot_id, seed)
             }
 
+            /// This function is called before the first of a run of shots is started, to
+            /// announce the ids of the shots that will follow and the seeds they will be
+            /// started with. It is a hint for simulators that serve many shots from a
+            /// single pass, and each shot is still started with
+            /// `selene_simulator_shot_start`.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_plan_shots(
+                instance: SimulatorInstance,
+                shot_ids: *const u64,
+                seeds: *const u64,
+                n_shots: u64,
+            ) -> i32 {
+                Helper::plan_shots(instance, shot_ids, seeds, n_shots)
+            }
+
             /// This function is called at the end of a shot, and it is responsible for
             /// finalising the simulator plugin for that shot. For example, it may
             /// clean up any state, such as accumulators or buffers, or set a state vector
@@ -393,6 +628,202 @@ macro_rules! export_simulator_plugin {
                 Helper::reset(instance, qubit)
             }
 
+            /// Notify the simulator that the qubit at the requested index has
+            /// been allocated by the runtime.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_operation_qalloc(
+                instance: SimulatorInstance,
+                qubit: u64,
+            ) -> i32 {
+                Helper::qalloc(instance, qubit)
+            }
+
+            /// Notify the simulator that the qubit at the requested index has
+            /// been freed by the runtime.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_operation_qfree(
+                instance: SimulatorInstance,
+                qubit: u64,
+            ) -> i32 {
+                Helper::qfree(instance, qubit)
+            }
+
+            /// Apply the general single-qubit unitary U(theta, phi, lambda) to the
+            /// qubit at the requested index.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_operation_u(
+                instance: SimulatorInstance,
+                qubit: u64,
+                theta: f64,
+                phi: f64,
+                lambda: f64,
+            ) -> i32 {
+                Helper::u(instance, qubit, theta, phi, lambda)
+            }
+
+            /// Apply a general two-qubit unitary, given as a 4x4 row-major matrix
+            /// of interleaved real and imaginary parts, to the qubits at the
+            /// requested indices. The first qubit is the least significant bit of
+            /// the matrix indices.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_operation_two_qubit_unitary(
+                instance: SimulatorInstance,
+                qubit1: u64,
+                qubit2: u64,
+                matrix: *const f64,
+            ) -> i32 {
+                Helper::two_qubit_unitary(instance, qubit1, qubit2, matrix)
+            }
+
+            /// Apply the phase e^{i phases[k]} to each basis state k of the
+            /// requested qubits, where the first qubit is the least significant
+            /// bit of k.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_operation_diagonal_phase(
+                instance: SimulatorInstance,
+                qubits: *const u64,
+                n_qubits: u64,
+                phases: *const f64,
+            ) -> i32 {
+                Helper::diagonal_phase(instance, qubits, n_qubits, phases)
+            }
+
+            /// Measure the qubit at the requested index, distinguishing leaked
+            /// states. Returns 0 or 1 for the computational states, and 2 if the
+            /// qubit is found outside of the computational subspace. Simulators
+            /// that do not model leakage perform a normal measurement.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_operation_measure_leaked(
+                instance: SimulatorInstance,
+                qubit: u64,
+            ) -> i32 {
+                Helper::measure_leaked(instance, qubit)
+            }
+
+            /// Apply a rotation between the |1> and leaked |2> states of the qubit
+            /// at the requested index, with the same conventions as the RXY gate.
+            /// Simulators that do not model leakage should return an error.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_operation_leakage_rxy(
+                instance: SimulatorInstance,
+                qubit: u64,
+                theta: f64,
+                phi: f64,
+            ) -> i32 {
+                Helper::leakage_rxy(instance, qubit, theta, phi)
+            }
+
+            /// Capture the current quantum state of the simulator instance, writing
+            /// a handle to the snapshot into `handle_out`. The handle can later be
+            /// passed to `selene_simulator_restore`.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_snapshot(
+                instance: SimulatorInstance,
+                handle_out: *mut u64,
+            ) -> i32 {
+                Helper::snapshot(instance, handle_out)
+            }
+
+            /// Replace the current quantum state of the simulator instance with
+            /// the snapshot with the given handle. The snapshot is left intact.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_restore(
+                instance: SimulatorInstance,
+                handle: u64,
+            ) -> i32 {
+                Helper::restore(instance, handle)
+            }
+
+            /// Release the snapshot with the given handle, freeing the state it
+            /// holds. The handle cannot be restored afterwards.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_release_snapshot(
+                instance: SimulatorInstance,
+                handle: u64,
+            ) -> i32 {
+                Helper::release_snapshot(instance, handle)
+            }
+
+            /// Write the probability of each Z-basis outcome of the requested qubits
+            /// into `probabilities_out`, which must have space for 2^n_qubits
+            /// elements. The first qubit provided is the least significant bit of
+            /// the outcome index. The state is not disturbed.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_marginal_probabilities(
+                instance: SimulatorInstance,
+                qubits: *const u64,
+                n_qubits: u64,
+                probabilities_out: *mut f64,
+            ) -> i32 {
+                Helper::marginal_probabilities(instance, qubits, n_qubits, probabilities_out)
+            }
+
+            /// Write the expectation value of a pauli string into `expectation_out`.
+            /// The pauli acting on each of the requested qubits is given by the
+            /// corresponding element of `paulis`: 0 for I, 1 for X, 2 for Y and
+            /// 3 for Z. The state is not disturbed.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_pauli_expectation(
+                instance: SimulatorInstance,
+                qubits: *const u64,
+                paulis: *const u8,
+                n_qubits: u64,
+                expectation_out: *mut f64,
+            ) -> i32 {
+                Helper::pauli_expectation(instance, qubits, paulis, n_qubits, expectation_out)
+            }
+
+            /// Apply a pauli channel to qubit q0, applying X, Y or Z with
+            /// probabilities p_x, p_y and p_z respectively.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_channel_pauli(
+                instance: SimulatorInstance,
+                q0: u64,
+                p_x: f64,
+                p_y: f64,
+                p_z: f64,
+            ) -> i32 {
+                Helper::pauli_channel(instance, q0, p_x, p_y, p_z)
+            }
+
+            /// Apply an amplitude damping channel with decay probability gamma
+            /// to qubit q0.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_channel_amplitude_damping(
+                instance: SimulatorInstance,
+                q0: u64,
+                gamma: f64,
+            ) -> i32 {
+                Helper::amplitude_damping(instance, q0, gamma)
+            }
+
+            /// Apply a phase damping channel with damping parameter lambda to
+            /// qubit q0.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_channel_phase_damping(
+                instance: SimulatorInstance,
+                q0: u64,
+                lambda: f64,
+            ) -> i32 {
+                Helper::phase_damping(instance, q0, lambda)
+            }
+
+            /// Apply the channel defined by n_operators Kraus operators to the
+            /// requested qubits. Each operator is a 2^n_qubits x 2^n_qubits matrix
+            /// in row-major order, stored as interleaved real and imaginary parts,
+            /// and the first qubit provided is the least significant bit of the
+            /// matrix indices.
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn selene_simulator_channel_kraus(
+                instance: SimulatorInstance,
+                qubits: *const u64,
+                n_qubits: u64,
+                kraus: *const f64,
+                n_operators: u64,
+            ) -> i32 {
+                Helper::kraus_channel(instance, qubits, n_qubits, kraus, n_operators)
+            }
+
             /// Get a metric from the simulator instance.
             ///
             /// nth_metric is the index of the metric to retrieve, starting from 0,
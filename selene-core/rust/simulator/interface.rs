@@ -1,8 +1,122 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
 use std::sync::Arc;
 
+use crate::runtime::{Operation, decompose};
 use crate::utils::MetricValue;
 
+/// A single-qubit Pauli operator, used to describe Pauli strings. The
+/// discriminants are used to pass Pauli strings across the plugin interface,
+/// and match the Pauli codes used by QuEST.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pauli {
+    I = 0,
+    X = 1,
+    Y = 2,
+    Z = 3,
+}
+
+impl TryFrom<u8> for Pauli {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Pauli::I),
+            1 => Ok(Pauli::X),
+            2 => Ok(Pauli::Y),
+            3 => Ok(Pauli::Z),
+            _ => bail!("Invalid Pauli code {value}. Expected 0 (I), 1 (X), 2 (Y) or 3 (Z)."),
+        }
+    }
+}
+
+/// The number of values used to encode a single Kraus operator on `n_qubits`
+/// qubits, being the real and imaginary parts of each element of a
+/// 2^n x 2^n matrix.
+pub fn kraus_operator_size(n_qubits: usize) -> Result<usize> {
+    if n_qubits == 0 {
+        bail!("Kraus operators must act on at least one qubit.");
+    }
+    1usize
+        .checked_shl(2 * n_qubits as u32 + 1)
+        .ok_or_else(|| anyhow::anyhow!("Cannot encode Kraus operators on {n_qubits} qubits."))
+}
+
+/// Check that the given probabilities describe a Pauli channel.
+pub fn validate_pauli_channel(p_x: f64, p_y: f64, p_z: f64) -> Result<()> {
+    if [p_x, p_y, p_z].iter().any(|p| !(0.0..=1.0).contains(p)) || p_x + p_y + p_z > 1.0 + 1e-12 {
+        bail!(
+            "Pauli channel probabilities must be in [0, 1] and sum to at most 1, got p_x={p_x}, p_y={p_y}, p_z={p_z}."
+        );
+    }
+    Ok(())
+}
+
+/// The number of Kraus operators on `n_qubits` qubits encoded in
+/// `kraus_operators`, checking that the encoding is well formed and that the
+/// operators describe a trace-preserving channel.
+pub fn validate_kraus_operators(n_qubits: usize, kraus_operators: &[f64]) -> Result<usize> {
+    let size = kraus_operator_size(n_qubits)?;
+    if kraus_operators.is_empty() || !kraus_operators.len().is_multiple_of(size) {
+        bail!(
+            "Expected a non-empty set of Kraus operators on {n_qubits} qubits, each consisting of {size} values, but got {} values.",
+            kraus_operators.len()
+        );
+    }
+    // Σ K†K must be the identity, and its (i, j) element is Σ_r conj(K_ri) K_rj.
+    let dimension = 1usize << n_qubits;
+    let element = |k: &[f64], row: usize, col: usize| {
+        let index = 2 * (row * dimension + col);
+        (k[index], k[index + 1])
+    };
+    let mut deviation: f64 = 0.0;
+    for i in 0..dimension {
+        for j in 0..dimension {
+            let (mut re, mut im) = (if i == j { -1.0 } else { 0.0 }, 0.0);
+            for k in kraus_operators.chunks_exact(size) {
+                for r in 0..dimension {
+                    let (a_re, a_im) = element(k, r, i);
+                    let (b_re, b_im) = element(k, r, j);
+                    re += a_re * b_re + a_im * b_im;
+                    im += a_re * b_im - a_im * b_re;
+                }
+            }
+            deviation = deviation.max(re.hypot(im));
+        }
+    }
+    if deviation > 1e-8 {
+        bail!(
+            "The provided Kraus operators do not form a trace-preserving channel (deviation from completeness: {deviation:.2e})"
+        );
+    }
+    Ok(kraus_operators.len() / size)
+}
+
+/// Apply the RXY, RZ and RZZ gates of a decomposition produced by
+/// [crate::runtime::decompose] to a simulator.
+pub fn apply_decomposition<S: SimulatorInterface + ?Sized>(
+    simulator: &mut S,
+    operations: Vec<Operation>,
+) -> Result<()> {
+    for operation in operations {
+        match operation {
+            Operation::RXYGate {
+                qubit_id,
+                theta,
+                phi,
+            } => simulator.rxy(qubit_id, theta, phi)?,
+            Operation::RZGate { qubit_id, theta } => simulator.rz(qubit_id, theta)?,
+            Operation::RZZGate {
+                qubit_id_1,
+                qubit_id_2,
+                theta,
+            } => simulator.rzz(qubit_id_1, qubit_id_2, theta)?,
+            other => bail!("Unexpected operation in a decomposition: {other:?}"),
+        }
+    }
+    Ok(())
+}
+
 pub trait SimulatorInterface {
     // Signals that the instance of the simulator should cleanup. Plugins
     // should `Err` from any functions called on an instance after `exit`.
@@ -12,6 +126,16 @@ pub trait SimulatorInterface {
     // with the given random seed.
     fn shot_start(&mut self, shot_id: u64, seed: u64) -> Result<()>;
 
+    // Announce the shots that will follow, in order, by their shot ids and
+    // seeds, before the first of them is started. Simulators that serve many
+    // shots from a single pass can use this to draw the randomness of each
+    // shot from its own seed ahead of time. It is only a hint: every shot is
+    // still started with shot_start, and a shot that does not match the plan
+    // must be simulated as usual. The default is to ignore the plan.
+    fn plan_shots(&mut self, _shot_ids: &[u64], _seeds: &[u64]) -> Result<()> {
+        Ok(())
+    }
+
     // Called to signal that the simulator should prepare to end the current
     // shot.
     fn shot_end(&mut self) -> Result<()>;
@@ -27,6 +151,34 @@ pub trait SimulatorInterface {
     // This gate is also known as zz_phase, phase_shift, and R2ZZ
     fn rzz(&mut self, qubit1: u64, qubit2: u64, theta: f64) -> Result<()>;
 
+    // Perform a general single-qubit unitary U(θ, φ, λ) on the given qubit,
+    // as defined by runtime::Operation::UGate. The default implementation
+    // decomposes the gate into Rz and Rxy gates, which is exact up to a
+    // global phase.
+    fn u(&mut self, qubit: u64, theta: f64, phi: f64, lambda: f64) -> Result<()> {
+        apply_decomposition(self, decompose::u_gate(qubit, theta, phi, lambda))
+    }
+
+    // Perform a general two-qubit unitary on the given qubits. The matrix is
+    // 4x4 in row-major order, stored as interleaved real and imaginary parts,
+    // with qubit1 as the least significant bit of the row and column indices.
+    // The default implementation decomposes the gate into Rxy, Rz and Rzz
+    // gates, which is exact up to a global phase.
+    fn two_qubit_unitary(&mut self, qubit1: u64, qubit2: u64, matrix: &[f64; 32]) -> Result<()> {
+        apply_decomposition(
+            self,
+            decompose::two_qubit_unitary_gate(qubit1, qubit2, matrix),
+        )
+    }
+
+    // Apply the phase e^{i phases[k]} to each basis state k of the given
+    // qubits, where the first qubit is the least significant bit of k. The
+    // default implementation decomposes the gate into Rxy, Rz and Rzz gates,
+    // which is exact up to a global phase.
+    fn diagonal_phase(&mut self, qubits: &[u64], phases: &[f64]) -> Result<()> {
+        apply_decomposition(self, decompose::diagonal_phase_gate(qubits, phases))
+    }
+
     // Perform a measurement on the given qubit.
     // The result of the measurement is returned as a boolean.
     fn measure(&mut self, qubit: u64) -> Result<bool>;
@@ -44,6 +196,132 @@ pub trait SimulatorInterface {
     // Reset the given qubit to the |0> state.
     fn reset(&mut self, qubit: u64) -> Result<()>;
 
+    // Notify the simulator that the runtime has allocated the given qubit,
+    // which is in the |0> state unless it was freed without being reset.
+    // Simulators that size their register to the qubits in use can grow it
+    // here. The default, for simulators that hold every qubit up front, is to
+    // do nothing.
+    fn qalloc(&mut self, _qubit: u64) -> Result<()> {
+        Ok(())
+    }
+
+    // Notify the simulator that the runtime has freed the given qubit, which
+    // will not be used again until it is next allocated. Simulators that size
+    // their register to the qubits in use can shrink it here. The default is
+    // to do nothing.
+    fn qfree(&mut self, _qubit: u64) -> Result<()> {
+        Ok(())
+    }
+
+    // Perform a measurement on the given qubit that distinguishes leaked
+    // states, returning 0 or 1 for the computational states and 2 if the
+    // qubit is found outside of the computational subspace. The default,
+    // for simulators that do not model leakage, is a normal measurement.
+    fn measure_leaked(&mut self, qubit: u64) -> Result<u64> {
+        Ok(self.measure(qubit)? as u64)
+    }
+
+    // Perform a rotation between the |1> state and the leaked |2> state of the
+    // given qubit, with the same conventions as rxy. This allows error models
+    // to drive coherent leakage (|1> to |2>) and seepage (|2> to |1>).
+    // This is optional functionality, and the default is to raise an error.
+    fn leakage_rxy(&mut self, _qubit: u64, _theta: f64, _phi: f64) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "Leakage is not supported on the chosen simulator."
+        ))
+    }
+
+    // Take a snapshot of the current quantum state, returning a handle that
+    // can later be passed to restore. The random number generator is not part
+    // of the snapshot, so that branches restored within shots with different
+    // seeds go on to diverge. Snapshots remain valid until they are released
+    // with release_snapshot, or until the simulator exits.
+    // This is optional functionality, and the default is to raise an error.
+    fn snapshot(&mut self) -> Result<u64> {
+        Err(anyhow::anyhow!(
+            "Snapshots are not supported on the chosen simulator."
+        ))
+    }
+
+    // Replace the current quantum state with the state captured by the
+    // snapshot with the given handle. The snapshot itself is unaffected, so
+    // it can be restored any number of times.
+    fn restore(&mut self, _handle: u64) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "Snapshots are not supported on the chosen simulator."
+        ))
+    }
+
+    // Release the snapshot with the given handle, freeing the state it holds.
+    // The handle cannot be restored afterwards. This is optional functionality,
+    // and the default is to do nothing, leaving the snapshot held until the
+    // simulator exits.
+    fn release_snapshot(&mut self, _handle: u64) -> Result<()> {
+        Ok(())
+    }
+
+    // Compute the probability of each Z-basis outcome of the given qubits,
+    // without disturbing the state. The result has 2^n entries for n qubits,
+    // indexed such that the first qubit provided is the least significant bit.
+    // This is optional functionality, and the default is to raise an error.
+    fn marginal_probabilities(&mut self, _qubits: &[u64]) -> Result<Vec<f64>> {
+        Err(anyhow::anyhow!(
+            "Marginal probabilities are not supported on the chosen simulator."
+        ))
+    }
+
+    // Compute the expectation value of the Pauli string given as pairs of
+    // qubits and the Pauli operators acting on them, without disturbing the
+    // state. Qubits that are not provided are acted on by the identity.
+    // This is optional functionality, and the default is to raise an error.
+    fn pauli_expectation(&mut self, _pauli_string: &[(u64, Pauli)]) -> Result<f64> {
+        Err(anyhow::anyhow!(
+            "Pauli expectation values are not supported on the chosen simulator."
+        ))
+    }
+
+    // Apply a Pauli channel to the given qubit, applying X, Y or Z with the
+    // given probabilities and the identity otherwise. Simulators that track
+    // mixed states apply the channel exactly, whereas pure state simulators
+    // sample a single branch.
+    // This is optional functionality, and the default is to raise an error.
+    fn pauli_channel(&mut self, _qubit: u64, _p_x: f64, _p_y: f64, _p_z: f64) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "Pauli channels are not supported on the chosen simulator."
+        ))
+    }
+
+    // Apply an amplitude damping channel with decay probability gamma, which
+    // takes |1> to |0> with probability gamma.
+    // This is optional functionality, and the default is to raise an error.
+    fn amplitude_damping(&mut self, _qubit: u64, _gamma: f64) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "Amplitude damping is not supported on the chosen simulator."
+        ))
+    }
+
+    // Apply a phase damping channel with damping parameter lambda, which
+    // scales the off-diagonal elements of the qubit's density matrix by
+    // sqrt(1 - lambda).
+    // This is optional functionality, and the default is to raise an error.
+    fn phase_damping(&mut self, _qubit: u64, _lambda: f64) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "Phase damping is not supported on the chosen simulator."
+        ))
+    }
+
+    // Apply the channel ρ -> Σ K ρ K† defined by the given Kraus operators to
+    // the given qubits. Each operator is a 2^n x 2^n matrix for n qubits,
+    // stored in row-major order as interleaved real and imaginary parts, with
+    // the first qubit provided as the least significant bit of the row and
+    // column indices. The operators are concatenated.
+    // This is optional functionality, and the default is to raise an error.
+    fn kraus_channel(&mut self, _qubits: &[u64], _kraus_operators: &[f64]) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "Kraus channels are not supported on the chosen simulator."
+        ))
+    }
+
     // Provide a metric to the output stream.
     // Will be called with incrementing `nth_metric` until `None` is returned.
     fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>>;
@@ -1,4 +1,9 @@
-use super::{SimulatorAPIVersion, SimulatorInterface, SimulatorInterfaceFactory};
+use super::interface::apply_decomposition;
+use super::{
+    Pauli, SimulatorAPIVersion, SimulatorInterface, SimulatorInterfaceFactory,
+    validate_kraus_operators,
+};
+use crate::runtime::decompose;
 use crate::utils::{MetricValue, check_errno, read_raw_metric, with_strings_to_cargs};
 use anyhow::{Result, anyhow};
 use libloading;
@@ -32,6 +37,16 @@ pub type Errno = i32;
 ///   Called at the start of a shot. The plugin should return 0 on success, and non-zero on
 ///   failure.
 ///
+/// - (optional) `int selene_simulator_plan_shots(
+///      void* handle,
+///      const uint64_t* shot_ids, // ids of the shots that will follow, in order
+///      const uint64_t* seeds,    // the seed each of those shots will be started with
+///      uint64_t n_shots          // number of shots provided
+///   )`
+///   Called before the first of a run of shots is started, to announce the shots that will
+///   follow. This is a hint for simulators that serve many shots from a single pass, and
+///   may be ignored. Return nonzero on failure.
+///
 /// - `int selene_simulator_shot_end(void* handle)`
 ///   Called at the end of a shot. The plugin should return 0 on success, and non-zero on
 ///   failure.
@@ -82,6 +97,150 @@ pub type Errno = i32;
 ///    )`
 ///    Reset qubit `q0` to the |0> state. Return nonzero on failure.
 ///
+/// - (optional) `int selene_simulator_operation_measure_leaked(
+///       *void,      // user-set state
+///       uint64_t q0 // qubit to measure
+///    )`
+///    Measure qubit `q0`, distinguishing leaked states. Return 0 or 1 for the computational
+///    states, 2 if the qubit is found outside of the computational subspace, and any other
+///    value for failure. If absent, a call to `selene_simulator_operation_measure` is made
+///    instead.
+///
+/// - (optional) `int selene_simulator_operation_leakage_rxy(
+///      *void,        // user-set state
+///      uint64_t q0,  // qubit to apply the rotation to
+///      double theta, // angle
+///      double phi    // angle
+///    )`
+///    Apply a rotation between the |1> and leaked |2> states of qubit `q0`, with the same
+///    conventions as the RXY gate. Return nonzero on failure, including if leakage is not
+///    supported by the simulator.
+///
+/// - (optional) `int selene_simulator_operation_qalloc(
+///      *void,      // user-set state
+///      uint64_t q0 // qubit that has been allocated
+///    )`
+///    Notify the simulator that the runtime has allocated qubit `q0`, so that simulators which
+///    only hold the qubits in use can grow their register. Return nonzero on failure. If
+///    absent, the notification is ignored.
+///
+/// - (optional) `int selene_simulator_operation_qfree(
+///      *void,      // user-set state
+///      uint64_t q0 // qubit that has been freed
+///    )`
+///    Notify the simulator that the runtime has freed qubit `q0`, so that simulators which
+///    only hold the qubits in use can shrink their register. Return nonzero on failure. If
+///    absent, the notification is ignored.
+///
+/// - (optional) `int selene_simulator_operation_u(
+///      *void,         // user-set state
+///      uint64_t q0,   // qubit to apply the gate to
+///      double theta,  // angle
+///      double phi,    // angle
+///      double lambda  // angle
+///    )`
+///    Apply the general single-qubit unitary U(theta, phi, lambda) to qubit `q0`. Return
+///    nonzero on failure. If absent, the gate is decomposed into RZ and RXY gates.
+///
+/// - (optional) `int selene_simulator_operation_two_qubit_unitary(
+///      *void,               // user-set state
+///      uint64_t q0,         // first qubit, the least significant bit of the matrix indices
+///      uint64_t q1,         // second qubit
+///      const double* matrix // 4x4 row-major matrix, as interleaved real and imaginary parts
+///    )`
+///    Apply a general two-qubit unitary to qubits `q0` and `q1`. Return nonzero on failure.
+///    If absent, the gate is decomposed into RXY, RZ and RZZ gates.
+///
+/// - (optional) `int selene_simulator_operation_diagonal_phase(
+///      *void,                  // user-set state
+///      const uint64_t* qubits, // qubits to apply the gate to
+///      uint64_t n_qubits,      // number of qubits provided
+///      const double* phases    // 2^n_qubits phases
+///    )`
+///    Apply the phase e^{i phases[k]} to each basis state k of the given qubits, with the
+///    first qubit as the least significant bit of k. Return nonzero on failure. If absent,
+///    the gate is decomposed into RXY, RZ and RZZ gates.
+///
+/// - (optional) `int selene_simulator_snapshot(
+///       *void,               // user-set state
+///       uint64_t* handle_out // where to write the snapshot handle
+///    )`
+///    Capture the current quantum state and write a handle to it. Return nonzero on failure,
+///    including if snapshots are not supported by the simulator.
+///
+/// - (optional) `int selene_simulator_restore(
+///       *void,          // user-set state
+///       uint64_t handle // handle previously written by selene_simulator_snapshot
+///    )`
+///    Replace the current quantum state with the snapshot with the given handle, leaving the
+///    snapshot intact. Return nonzero on failure.
+///
+/// - (optional) `int selene_simulator_release_snapshot(
+///       *void,          // user-set state
+///       uint64_t handle // handle previously written by selene_simulator_snapshot
+///    )`
+///    Release the snapshot with the given handle, which is not restored afterwards. Return
+///    nonzero on failure. If absent, snapshots are held until the simulator exits.
+///
+/// - (optional) `int selene_simulator_marginal_probabilities(
+///       *void,                     // user-set state
+///       const uint64_t* qubits,    // qubits to compute the marginal distribution of
+///       uint64_t n_qubits,         // number of qubits provided
+///       double* probabilities_out  // array of 2^n_qubits probabilities to write into
+///    )`
+///    Write the probability of each Z-basis outcome of the given qubits, indexed such that
+///    the first qubit is the least significant bit, without disturbing the state. Return
+///    nonzero on failure, including if this is not supported by the simulator.
+///
+/// - (optional) `int selene_simulator_pauli_expectation(
+///       *void,                  // user-set state
+///       const uint64_t* qubits, // qubits acted on by the pauli string
+///       const uint8_t* paulis,  // pauli acting on each qubit: 0 => I, 1 => X, 2 => Y, 3 => Z
+///       uint64_t n_qubits,      // number of qubits provided
+///       double* expectation_out // where to write the expectation value
+///    )`
+///    Write the expectation value of the given pauli string without disturbing the state.
+///    Return nonzero on failure, including if this is not supported by the simulator.
+///
+/// - (optional) `int selene_simulator_channel_pauli(
+///       *void,       // user-set state
+///       uint64_t q0, // qubit to apply the channel to
+///       double p_x,  // probability of applying X
+///       double p_y,  // probability of applying Y
+///       double p_z   // probability of applying Z
+///    )`
+///    Apply a Pauli channel to qubit `q0`, exactly for mixed state simulators or by sampling
+///    a single branch for pure state simulators. Return nonzero on failure, including if
+///    this is not supported by the simulator.
+///
+/// - (optional) `int selene_simulator_channel_amplitude_damping(
+///       *void,       // user-set state
+///       uint64_t q0, // qubit to apply the channel to
+///       double gamma // probability of decay from |1> to |0>
+///    )`
+///    Apply an amplitude damping channel to qubit `q0`. Return nonzero on failure, including
+///    if this is not supported by the simulator.
+///
+/// - (optional) `int selene_simulator_channel_phase_damping(
+///       *void,        // user-set state
+///       uint64_t q0,  // qubit to apply the channel to
+///       double lambda // damping parameter
+///    )`
+///    Apply a phase damping channel to qubit `q0`, scaling its coherences by sqrt(1 - lambda).
+///    Return nonzero on failure, including if this is not supported by the simulator.
+///
+/// - (optional) `int selene_simulator_channel_kraus(
+///       *void,                  // user-set state
+///       const uint64_t* qubits, // qubits to apply the channel to
+///       uint64_t n_qubits,      // number of qubits provided
+///       const double* kraus,    // n_operators 2^n_qubits x 2^n_qubits row-major matrices,
+///                               // as interleaved real and imaginary parts
+///       uint64_t n_operators    // number of Kraus operators provided
+///    )`
+///    Apply the channel defined by the given Kraus operators, with the first qubit as the
+///    least significant bit of the matrix indices. Return nonzero on failure, including if
+///    this is not supported by the simulator.
+///
 /// - (optional) `int selene_simulator_get_metrics(
 ///       *void  // user-set state
 ///       uint8_t nth_metric, // index of metric to fetch (called with 0 to 255 until a non-zero
@@ -134,6 +293,19 @@ pub struct SimulatorPluginInterface {
     >,
     #[borrows(lib)]
     #[covariant]
+    plan_shots_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(
+                handle: SimulatorInstance,
+                shot_ids: *const u64,
+                seeds: *const u64,
+                n_shots: u64,
+            ) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
     shot_end_fn:
         libloading::Symbol<'this, unsafe extern "C" fn(handle: SimulatorInstance) -> Errno>,
     #[borrows(lib)]
@@ -185,6 +357,178 @@ pub struct SimulatorPluginInterface {
     >,
     #[borrows(lib)]
     #[covariant]
+    qalloc_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(handle: SimulatorInstance, qubit: u64) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
+    qfree_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(handle: SimulatorInstance, qubit: u64) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
+    measure_leaked_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(handle: SimulatorInstance, qubit: u64) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
+    leakage_rxy_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(
+                handle: SimulatorInstance,
+                qubit: u64,
+                theta: f64,
+                phi: f64,
+            ) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
+    snapshot_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(handle: SimulatorInstance, snapshot_out: *mut u64) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
+    restore_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(handle: SimulatorInstance, snapshot: u64) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
+    release_snapshot_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(handle: SimulatorInstance, snapshot: u64) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
+    marginal_probabilities_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(
+                handle: SimulatorInstance,
+                qubits: *const u64,
+                n_qubits: u64,
+                probabilities_out: *mut f64,
+            ) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
+    pauli_expectation_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(
+                handle: SimulatorInstance,
+                qubits: *const u64,
+                paulis: *const u8,
+                n_qubits: u64,
+                expectation_out: *mut f64,
+            ) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
+    pauli_channel_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(
+                handle: SimulatorInstance,
+                q0: u64,
+                p_x: f64,
+                p_y: f64,
+                p_z: f64,
+            ) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
+    amplitude_damping_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(handle: SimulatorInstance, q0: u64, gamma: f64) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
+    phase_damping_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(handle: SimulatorInstance, q0: u64, lambda: f64) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
+    u_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(
+                handle: SimulatorInstance,
+                q0: u64,
+                theta: f64,
+                phi: f64,
+                lambda: f64,
+            ) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
+    two_qubit_unitary_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(
+                handle: SimulatorInstance,
+                q0: u64,
+                q1: u64,
+                matrix: *const f64,
+            ) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
+    diagonal_phase_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(
+                handle: SimulatorInstance,
+                qubits: *const u64,
+                n_qubits: u64,
+                phases: *const f64,
+            ) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
+    kraus_channel_fn: Option<
+        libloading::Symbol<
+            'this,
+            unsafe extern "C" fn(
+                handle: SimulatorInstance,
+                qubits: *const u64,
+                n_qubits: u64,
+                kraus: *const f64,
+                n_operators: u64,
+            ) -> Errno,
+        >,
+    >,
+    #[borrows(lib)]
+    #[covariant]
     get_metrics_fn: Option<
         libloading::Symbol<
             'this,
@@ -254,6 +598,9 @@ impl SimulatorPluginInterface {
             init_fn_builder: |lib| unsafe { lib.get(b"selene_simulator_init") },
             exit_fn_builder: |lib| unsafe { Ok(lib.get(b"selene_simulator_exit").ok()) },
             shot_start_fn_builder: |lib| unsafe { lib.get(b"selene_simulator_shot_start") },
+            plan_shots_fn_builder: |lib| unsafe {
+                Ok(lib.get(b"selene_simulator_plan_shots").ok())
+            },
             shot_end_fn_builder: |lib| unsafe { lib.get(b"selene_simulator_shot_end") },
             rxy_fn_builder: |lib| unsafe { lib.get(b"selene_simulator_operation_rxy") },
             rz_fn_builder: |lib| unsafe { lib.get(b"selene_simulator_operation_rz") },
@@ -263,6 +610,50 @@ impl SimulatorPluginInterface {
                 Ok(lib.get(b"selene_simulator_operation_postselect").ok())
             },
             reset_fn_builder: |lib| unsafe { lib.get(b"selene_simulator_operation_reset") },
+            qalloc_fn_builder: |lib| unsafe {
+                Ok(lib.get(b"selene_simulator_operation_qalloc").ok())
+            },
+            qfree_fn_builder: |lib| unsafe {
+                Ok(lib.get(b"selene_simulator_operation_qfree").ok())
+            },
+            measure_leaked_fn_builder: |lib| unsafe {
+                Ok(lib.get(b"selene_simulator_operation_measure_leaked").ok())
+            },
+            leakage_rxy_fn_builder: |lib| unsafe {
+                Ok(lib.get(b"selene_simulator_operation_leakage_rxy").ok())
+            },
+            snapshot_fn_builder: |lib| unsafe { Ok(lib.get(b"selene_simulator_snapshot").ok()) },
+            restore_fn_builder: |lib| unsafe { Ok(lib.get(b"selene_simulator_restore").ok()) },
+            release_snapshot_fn_builder: |lib| unsafe {
+                Ok(lib.get(b"selene_simulator_release_snapshot").ok())
+            },
+            marginal_probabilities_fn_builder: |lib| unsafe {
+                Ok(lib.get(b"selene_simulator_marginal_probabilities").ok())
+            },
+            pauli_expectation_fn_builder: |lib| unsafe {
+                Ok(lib.get(b"selene_simulator_pauli_expectation").ok())
+            },
+            pauli_channel_fn_builder: |lib| unsafe {
+                Ok(lib.get(b"selene_simulator_channel_pauli").ok())
+            },
+            amplitude_damping_fn_builder: |lib| unsafe {
+                Ok(lib.get(b"selene_simulator_channel_amplitude_damping").ok())
+            },
+            phase_damping_fn_builder: |lib| unsafe {
+                Ok(lib.get(b"selene_simulator_channel_phase_damping").ok())
+            },
+            u_fn_builder: |lib| unsafe { Ok(lib.get(b"selene_simulator_operation_u").ok()) },
+            two_qubit_unitary_fn_builder: |lib| unsafe {
+                Ok(lib
+                    .get(b"selene_simulator_operation_two_qubit_unitary")
+                    .ok())
+            },
+            diagonal_phase_fn_builder: |lib| unsafe {
+                Ok(lib.get(b"selene_simulator_operation_diagonal_phase").ok())
+            },
+            kraus_channel_fn_builder: |lib| unsafe {
+                Ok(lib.get(b"selene_simulator_channel_kraus").ok())
+            },
             get_metrics_fn_builder: |lib| unsafe {
                 Ok(lib.get(b"selene_simulator_get_metrics").ok())
             },
@@ -320,6 +711,33 @@ impl SimulatorInterface for SimulatorPlugin {
             },
         )
     }
+    fn plan_shots(&mut self, shot_ids: &[u64], seeds: &[u64]) -> Result<()> {
+        let Some(plan_shots_fn) = self.interface.borrow_plan_shots_fn() else {
+            return Ok(());
+        };
+        if shot_ids.len() != seeds.len() {
+            return Err(anyhow!(
+                "SimulatorPlugin({}): plan_shots requires a seed for each shot",
+                self.interface.borrow_name()
+            ));
+        }
+        check_errno(
+            unsafe {
+                plan_shots_fn(
+                    self.instance,
+                    shot_ids.as_ptr(),
+                    seeds.as_ptr(),
+                    shot_ids.len() as u64,
+                )
+            },
+            || {
+                anyhow!(
+                    "SimulatorPlugin({}): plan_shots failed",
+                    self.interface.borrow_name()
+                )
+            },
+        )
+    }
     fn shot_end(&mut self) -> Result<()> {
         check_errno(
             unsafe { self.interface.borrow_shot_end_fn()(self.instance) },
@@ -402,6 +820,282 @@ impl SimulatorInterface for SimulatorPlugin {
             },
         )
     }
+    fn qalloc(&mut self, qubit: u64) -> Result<()> {
+        let Some(qalloc_fn) = self.interface.borrow_qalloc_fn() else {
+            return Ok(());
+        };
+        check_errno(unsafe { qalloc_fn(self.instance, qubit) }, || {
+            anyhow!(
+                "SimulatorPlugin({}): qalloc failed",
+                self.interface.borrow_name()
+            )
+        })
+    }
+    fn qfree(&mut self, qubit: u64) -> Result<()> {
+        let Some(qfree_fn) = self.interface.borrow_qfree_fn() else {
+            return Ok(());
+        };
+        check_errno(unsafe { qfree_fn(self.instance, qubit) }, || {
+            anyhow!(
+                "SimulatorPlugin({}): qfree failed",
+                self.interface.borrow_name()
+            )
+        })
+    }
+    fn measure_leaked(&mut self, qubit: u64) -> Result<u64> {
+        let Some(measure_leaked_fn) = self.interface.borrow_measure_leaked_fn() else {
+            return Ok(self.measure(qubit)? as u64);
+        };
+        let result = unsafe { measure_leaked_fn(self.instance, qubit) };
+        match result {
+            0..=2 => Ok(result as u64),
+            _ => Err(anyhow!(
+                "SimulatorPlugin({}): measure_leaked failed",
+                self.interface.borrow_name()
+            )),
+        }
+    }
+    fn leakage_rxy(&mut self, qubit: u64, theta: f64, phi: f64) -> Result<()> {
+        let Some(leakage_rxy_fn) = self.interface.borrow_leakage_rxy_fn() else {
+            return Err(anyhow!("The chosen simulator does not support leakage"));
+        };
+        check_errno(
+            unsafe { leakage_rxy_fn(self.instance, qubit, theta, phi) },
+            || {
+                anyhow!(
+                    "SimulatorPlugin({}): leakage_rxy failed",
+                    self.interface.borrow_name()
+                )
+            },
+        )
+    }
+    fn u(&mut self, qubit: u64, theta: f64, phi: f64, lambda: f64) -> Result<()> {
+        let Some(u_fn) = self.interface.borrow_u_fn() else {
+            return apply_decomposition(self, decompose::u_gate(qubit, theta, phi, lambda));
+        };
+        check_errno(
+            unsafe { u_fn(self.instance, qubit, theta, phi, lambda) },
+            || {
+                anyhow!(
+                    "SimulatorPlugin({}): u failed",
+                    self.interface.borrow_name()
+                )
+            },
+        )
+    }
+    fn two_qubit_unitary(&mut self, qubit1: u64, qubit2: u64, matrix: &[f64; 32]) -> Result<()> {
+        decompose::validate_two_qubit_unitary(qubit1, qubit2, matrix)?;
+        let Some(two_qubit_unitary_fn) = self.interface.borrow_two_qubit_unitary_fn() else {
+            return apply_decomposition(
+                self,
+                decompose::two_qubit_unitary_gate(qubit1, qubit2, matrix),
+            );
+        };
+        check_errno(
+            unsafe { two_qubit_unitary_fn(self.instance, qubit1, qubit2, matrix.as_ptr()) },
+            || {
+                anyhow!(
+                    "SimulatorPlugin({}): two_qubit_unitary failed",
+                    self.interface.borrow_name()
+                )
+            },
+        )
+    }
+    fn diagonal_phase(&mut self, qubits: &[u64], phases: &[f64]) -> Result<()> {
+        decompose::validate_diagonal_phases(qubits, phases)?;
+        let Some(diagonal_phase_fn) = self.interface.borrow_diagonal_phase_fn() else {
+            return apply_decomposition(self, decompose::diagonal_phase_gate(qubits, phases));
+        };
+        check_errno(
+            unsafe {
+                diagonal_phase_fn(
+                    self.instance,
+                    qubits.as_ptr(),
+                    qubits.len() as u64,
+                    phases.as_ptr(),
+                )
+            },
+            || {
+                anyhow!(
+                    "SimulatorPlugin({}): diagonal_phase failed",
+                    self.interface.borrow_name()
+                )
+            },
+        )
+    }
+    fn snapshot(&mut self) -> Result<u64> {
+        let Some(snapshot_fn) = self.interface.borrow_snapshot_fn() else {
+            return Err(anyhow!("The chosen simulator does not support snapshots"));
+        };
+        let mut handle: u64 = 0;
+        check_errno(unsafe { snapshot_fn(self.instance, &mut handle) }, || {
+            anyhow!(
+                "SimulatorPlugin({}): snapshot failed",
+                self.interface.borrow_name()
+            )
+        })?;
+        Ok(handle)
+    }
+    fn restore(&mut self, handle: u64) -> Result<()> {
+        let Some(restore_fn) = self.interface.borrow_restore_fn() else {
+            return Err(anyhow!("The chosen simulator does not support snapshots"));
+        };
+        check_errno(unsafe { restore_fn(self.instance, handle) }, || {
+            anyhow!(
+                "SimulatorPlugin({}): restore of snapshot {handle} failed",
+                self.interface.borrow_name()
+            )
+        })
+    }
+    fn release_snapshot(&mut self, handle: u64) -> Result<()> {
+        let Some(release_snapshot_fn) = self.interface.borrow_release_snapshot_fn() else {
+            return Ok(());
+        };
+        check_errno(
+            unsafe { release_snapshot_fn(self.instance, handle) },
+            || {
+                anyhow!(
+                    "SimulatorPlugin({}): release of snapshot {handle} failed",
+                    self.interface.borrow_name()
+                )
+            },
+        )
+    }
+    fn marginal_probabilities(&mut self, qubits: &[u64]) -> Result<Vec<f64>> {
+        let Some(marginal_probabilities_fn) = self.interface.borrow_marginal_probabilities_fn()
+        else {
+            return Err(anyhow!(
+                "The chosen simulator does not support marginal probabilities"
+            ));
+        };
+        let n_outcomes = 1usize.checked_shl(qubits.len() as u32).ok_or_else(|| {
+            anyhow!(
+                "Cannot compute marginal probabilities of {} qubits",
+                qubits.len()
+            )
+        })?;
+        let mut probabilities = vec![0.0; n_outcomes];
+        check_errno(
+            unsafe {
+                marginal_probabilities_fn(
+                    self.instance,
+                    qubits.as_ptr(),
+                    qubits.len() as u64,
+                    probabilities.as_mut_ptr(),
+                )
+            },
+            || {
+                anyhow!(
+                    "SimulatorPlugin({}): marginal_probabilities failed",
+                    self.interface.borrow_name()
+                )
+            },
+        )?;
+        Ok(probabilities)
+    }
+    fn pauli_expectation(&mut self, pauli_string: &[(u64, Pauli)]) -> Result<f64> {
+        let Some(pauli_expectation_fn) = self.interface.borrow_pauli_expectation_fn() else {
+            return Err(anyhow!(
+                "The chosen simulator does not support pauli expectation values"
+            ));
+        };
+        let (qubits, paulis): (Vec<u64>, Vec<u8>) = pauli_string
+            .iter()
+            .map(|&(qubit, pauli)| (qubit, pauli as u8))
+            .unzip();
+        let mut expectation = 0.0;
+        check_errno(
+            unsafe {
+                pauli_expectation_fn(
+                    self.instance,
+                    qubits.as_ptr(),
+                    paulis.as_ptr(),
+                    qubits.len() as u64,
+                    &mut expectation,
+                )
+            },
+            || {
+                anyhow!(
+                    "SimulatorPlugin({}): pauli_expectation failed",
+                    self.interface.borrow_name()
+                )
+            },
+        )?;
+        Ok(expectation)
+    }
+    fn pauli_channel(&mut self, qubit: u64, p_x: f64, p_y: f64, p_z: f64) -> Result<()> {
+        let Some(pauli_channel_fn) = self.interface.borrow_pauli_channel_fn() else {
+            return Err(anyhow!(
+                "The chosen simulator does not support pauli channels"
+            ));
+        };
+        check_errno(
+            unsafe { pauli_channel_fn(self.instance, qubit, p_x, p_y, p_z) },
+            || {
+                anyhow!(
+                    "SimulatorPlugin({}): pauli_channel(q0={qubit}, p_x={p_x}, p_y={p_y}, p_z={p_z}) failed",
+                    self.interface.borrow_name()
+                )
+            },
+        )
+    }
+    fn amplitude_damping(&mut self, qubit: u64, gamma: f64) -> Result<()> {
+        let Some(amplitude_damping_fn) = self.interface.borrow_amplitude_damping_fn() else {
+            return Err(anyhow!(
+                "The chosen simulator does not support amplitude damping"
+            ));
+        };
+        check_errno(
+            unsafe { amplitude_damping_fn(self.instance, qubit, gamma) },
+            || {
+                anyhow!(
+                    "SimulatorPlugin({}): amplitude_damping(q0={qubit}, gamma={gamma}) failed",
+                    self.interface.borrow_name()
+                )
+            },
+        )
+    }
+    fn phase_damping(&mut self, qubit: u64, lambda: f64) -> Result<()> {
+        let Some(phase_damping_fn) = self.interface.borrow_phase_damping_fn() else {
+            return Err(anyhow!(
+                "The chosen simulator does not support phase damping"
+            ));
+        };
+        check_errno(
+            unsafe { phase_damping_fn(self.instance, qubit, lambda) },
+            || {
+                anyhow!(
+                    "SimulatorPlugin({}): phase_damping(q0={qubit}, lambda={lambda}) failed",
+                    self.interface.borrow_name()
+                )
+            },
+        )
+    }
+    fn kraus_channel(&mut self, qubits: &[u64], kraus_operators: &[f64]) -> Result<()> {
+        let Some(kraus_channel_fn) = self.interface.borrow_kraus_channel_fn() else {
+            return Err(anyhow!(
+                "The chosen simulator does not support kraus channels"
+            ));
+        };
+        let n_operators = validate_kraus_operators(qubits.len(), kraus_operators)?;
+        check_errno(
+            unsafe {
+                kraus_channel_fn(
+                    self.instance,
+                    qubits.as_ptr(),
+                    qubits.len() as u64,
+                    kraus_operators.as_ptr(),
+                    n_operators as u64,
+                )
+            },
+            || {
+                anyhow!(
+                    "SimulatorPlugin({}): kraus_channel failed",
+                    self.interface.borrow_name()
+                )
+            },
+        )
+    }
     fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
         let Some(get_metrics_fn) = self.interface.borrow_get_metrics_fn() else {
             return Ok(None);
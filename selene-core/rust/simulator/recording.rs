@@ -0,0 +1,415 @@
+//! A simulator that records every operation it receives, for testing error
+//! models and other plugins that wrap a simulator.
+//!
+//! Qubits are tracked as classical bits: rotations by π about an axis in the
+//! XY plane flip them, resets clear them, and measurements report them. All
+//! other rotations are recorded without changing the bits. Noise channels are
+//! recorded but not applied, and are only accepted if the factory enables
+//! them, so that both code paths of plugins that fall back to sampling when a
+//! simulator lacks channels can be exercised. Qubits can be leaked through the
+//! [Recording], after which leakage-aware measurements of them report 2 until
+//! they are reset.
+//!
+//! The functions at the end of this module drive an error model on a
+//! recording simulator, so that tests of each error model need only make
+//! assertions about its own behaviour.
+use std::f64::consts::PI;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Result, bail};
+
+use super::{Simulator, SimulatorInterface, SimulatorInterfaceFactory};
+use crate::error_model::{BatchResult, ErrorModelInterface};
+use crate::runtime::{BatchOperation, Operation};
+use crate::utils::MetricValue;
+
+/// An operation received by a [RecordingSimulator].
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordedOperation {
+    ShotStart {
+        shot_id: u64,
+        seed: u64,
+    },
+    ShotEnd,
+    Rxy {
+        qubit: u64,
+        theta: f64,
+        phi: f64,
+    },
+    Rz {
+        qubit: u64,
+        theta: f64,
+    },
+    Rzz {
+        qubit1: u64,
+        qubit2: u64,
+        theta: f64,
+    },
+    U {
+        qubit: u64,
+        theta: f64,
+        phi: f64,
+        lambda: f64,
+    },
+    TwoQubitUnitary {
+        qubit1: u64,
+        qubit2: u64,
+    },
+    DiagonalPhase {
+        qubits: Vec<u64>,
+        phases: Vec<f64>,
+    },
+    Measure {
+        qubit: u64,
+    },
+    MeasureLeaked {
+        qubit: u64,
+    },
+    Reset {
+        qubit: u64,
+    },
+    QAlloc {
+        qubit: u64,
+    },
+    QFree {
+        qubit: u64,
+    },
+    PauliChannel {
+        qubit: u64,
+        p_x: f64,
+        p_y: f64,
+        p_z: f64,
+    },
+    AmplitudeDamping {
+        qubit: u64,
+        gamma: f64,
+    },
+    PhaseDamping {
+        qubit: u64,
+        lambda: f64,
+    },
+    KrausChannel {
+        qubits: Vec<u64>,
+    },
+}
+
+/// The operations recorded by every simulator created from a
+/// [RecordingSimulatorFactory], shared with the test that inspects them.
+#[derive(Clone, Default, Debug)]
+pub struct Recording(Arc<Mutex<RecordingState>>);
+
+#[derive(Default, Debug)]
+struct RecordingState {
+    operations: Vec<RecordedOperation>,
+    leaked: Vec<u64>,
+}
+
+impl Recording {
+    fn push(&self, operation: RecordedOperation) {
+        self.0.lock().unwrap().operations.push(operation);
+    }
+
+    /// Remove and return the operations recorded so far.
+    pub fn take(&self) -> Vec<RecordedOperation> {
+        std::mem::take(&mut self.0.lock().unwrap().operations)
+    }
+
+    /// Leak `qubit`, so that leakage-aware measurements report 2 for it until
+    /// it is reset or the next shot starts.
+    pub fn leak(&self, qubit: u64) {
+        self.0.lock().unwrap().leaked.push(qubit);
+    }
+
+    fn is_leaked(&self, qubit: u64) -> bool {
+        self.0.lock().unwrap().leaked.contains(&qubit)
+    }
+
+    fn unleak(&self, qubit: u64) {
+        self.0.lock().unwrap().leaked.retain(|&q| q != qubit);
+    }
+
+    fn unleak_all(&self) {
+        self.0.lock().unwrap().leaked.clear();
+    }
+}
+
+#[derive(Default)]
+pub struct RecordingSimulatorFactory {
+    /// Where the operations of created simulators are recorded.
+    pub recording: Recording,
+    /// Whether created simulators accept noise channels.
+    pub channels: bool,
+}
+
+impl RecordingSimulatorFactory {
+    /// A simulator of `n_qubits` qubits that records into the returned
+    /// [Recording], and accepts noise channels if `channels` is set.
+    pub fn simulator(n_qubits: u64, channels: bool) -> Result<(Simulator, Recording)> {
+        let factory = Arc::new(Self {
+            channels,
+            ..Default::default()
+        });
+        let recording = factory.recording.clone();
+        let simulator = Simulator::new(factory, n_qubits, &[""])?;
+        Ok((simulator, recording))
+    }
+}
+
+impl SimulatorInterfaceFactory for RecordingSimulatorFactory {
+    type Interface = RecordingSimulator;
+
+    fn init(
+        self: Arc<Self>,
+        n_qubits: u64,
+        _args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        Ok(Box::new(RecordingSimulator {
+            recording: self.recording.clone(),
+            channels: self.channels,
+            bits: vec![false; n_qubits as usize],
+        }))
+    }
+}
+
+pub struct RecordingSimulator {
+    recording: Recording,
+    channels: bool,
+    bits: Vec<bool>,
+}
+
+impl RecordingSimulator {
+    fn bit(&mut self, qubit: u64) -> Result<&mut bool> {
+        let n_qubits = self.bits.len();
+        match self.bits.get_mut(qubit as usize) {
+            Some(bit) => Ok(bit),
+            None => bail!("Qubit {qubit} is out of range for {n_qubits} qubits"),
+        }
+    }
+
+    fn flip_if_pi(&mut self, qubit: u64, theta: f64) -> Result<()> {
+        let bit = self.bit(qubit)?;
+        if ((theta.abs() - PI) % (2.0 * PI)).abs() < 1e-9 {
+            *bit = !*bit;
+        }
+        Ok(())
+    }
+
+    fn channel(&mut self, operation: RecordedOperation) -> Result<()> {
+        if !self.channels {
+            bail!("Noise channels are not supported by this recording simulator");
+        }
+        self.recording.push(operation);
+        Ok(())
+    }
+}
+
+impl SimulatorInterface for RecordingSimulator {
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+    fn shot_start(&mut self, shot_id: u64, seed: u64) -> Result<()> {
+        self.bits.fill(false);
+        self.recording.unleak_all();
+        self.recording
+            .push(RecordedOperation::ShotStart { shot_id, seed });
+        Ok(())
+    }
+    fn shot_end(&mut self) -> Result<()> {
+        self.recording.push(RecordedOperation::ShotEnd);
+        Ok(())
+    }
+    fn rxy(&mut self, qubit: u64, theta: f64, phi: f64) -> Result<()> {
+        self.flip_if_pi(qubit, theta)?;
+        self.recording
+            .push(RecordedOperation::Rxy { qubit, theta, phi });
+        Ok(())
+    }
+    fn rz(&mut self, qubit: u64, theta: f64) -> Result<()> {
+        self.bit(qubit)?;
+        self.recording.push(RecordedOperation::Rz { qubit, theta });
+        Ok(())
+    }
+    fn rzz(&mut self, qubit1: u64, qubit2: u64, theta: f64) -> Result<()> {
+        self.bit(qubit1)?;
+        self.bit(qubit2)?;
+        self.recording.push(RecordedOperation::Rzz {
+            qubit1,
+            qubit2,
+            theta,
+        });
+        Ok(())
+    }
+    fn u(&mut self, qubit: u64, theta: f64, phi: f64, lambda: f64) -> Result<()> {
+        self.flip_if_pi(qubit, theta)?;
+        self.recording.push(RecordedOperation::U {
+            qubit,
+            theta,
+            phi,
+            lambda,
+        });
+        Ok(())
+    }
+    fn two_qubit_unitary(&mut self, qubit1: u64, qubit2: u64, _matrix: &[f64; 32]) -> Result<()> {
+        self.bit(qubit1)?;
+        self.bit(qubit2)?;
+        self.recording
+            .push(RecordedOperation::TwoQubitUnitary { qubit1, qubit2 });
+        Ok(())
+    }
+    fn diagonal_phase(&mut self, qubits: &[u64], phases: &[f64]) -> Result<()> {
+        for &qubit in qubits {
+            self.bit(qubit)?;
+        }
+        self.recording.push(RecordedOperation::DiagonalPhase {
+            qubits: qubits.to_vec(),
+            phases: phases.to_vec(),
+        });
+        Ok(())
+    }
+    fn measure(&mut self, qubit: u64) -> Result<bool> {
+        let value = *self.bit(qubit)?;
+        self.recording.push(RecordedOperation::Measure { qubit });
+        Ok(value)
+    }
+    fn measure_leaked(&mut self, qubit: u64) -> Result<u64> {
+        let value = *self.bit(qubit)?;
+        self.recording
+            .push(RecordedOperation::MeasureLeaked { qubit });
+        if self.recording.is_leaked(qubit) {
+            return Ok(2);
+        }
+        Ok(value as u64)
+    }
+    fn reset(&mut self, qubit: u64) -> Result<()> {
+        *self.bit(qubit)? = false;
+        self.recording.unleak(qubit);
+        self.recording.push(RecordedOperation::Reset { qubit });
+        Ok(())
+    }
+    fn qalloc(&mut self, qubit: u64) -> Result<()> {
+        self.bit(qubit)?;
+        self.recording.push(RecordedOperation::QAlloc { qubit });
+        Ok(())
+    }
+    fn qfree(&mut self, qubit: u64) -> Result<()> {
+        self.bit(qubit)?;
+        self.recording.push(RecordedOperation::QFree { qubit });
+        Ok(())
+    }
+    fn marginal_probabilities(&mut self, qubits: &[u64]) -> Result<Vec<f64>> {
+        let mut index = 0;
+        for (i, &qubit) in qubits.iter().enumerate() {
+            index |= (*self.bit(qubit)? as usize) << i;
+        }
+        let mut probabilities = vec![0.0; 1 << qubits.len()];
+        probabilities[index] = 1.0;
+        Ok(probabilities)
+    }
+    fn pauli_channel(&mut self, qubit: u64, p_x: f64, p_y: f64, p_z: f64) -> Result<()> {
+        self.bit(qubit)?;
+        self.channel(RecordedOperation::PauliChannel {
+            qubit,
+            p_x,
+            p_y,
+            p_z,
+        })
+    }
+    fn amplitude_damping(&mut self, qubit: u64, gamma: f64) -> Result<()> {
+        self.bit(qubit)?;
+        self.channel(RecordedOperation::AmplitudeDamping { qubit, gamma })
+    }
+    fn phase_damping(&mut self, qubit: u64, lambda: f64) -> Result<()> {
+        self.bit(qubit)?;
+        self.channel(RecordedOperation::PhaseDamping { qubit, lambda })
+    }
+    fn kraus_channel(&mut self, qubits: &[u64], _kraus_operators: &[f64]) -> Result<()> {
+        for &qubit in qubits {
+            self.bit(qubit)?;
+        }
+        self.channel(RecordedOperation::KrausChannel {
+            qubits: qubits.to_vec(),
+        })
+    }
+    fn get_metric(&mut self, _nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        Ok(None)
+    }
+}
+
+/// Build an error model with `build` on a recording simulator of `n_qubits`
+/// qubits, which accepts noise channels if `channels` is set, and start its
+/// first shot. The operations sent to the simulator so far are discarded.
+pub fn recorded_error_model<E: ErrorModelInterface>(
+    n_qubits: u64,
+    channels: bool,
+    build: impl FnOnce(Simulator) -> Result<E>,
+) -> Result<(E, Recording)> {
+    let (simulator, recording) = RecordingSimulatorFactory::simulator(n_qubits, channels)?;
+    let mut error_model = build(simulator)?;
+    error_model.shot_start(0, 0, 0)?;
+    recording.take();
+    Ok((error_model, recording))
+}
+
+/// Pass the operations to the error model as a single batch starting at time
+/// zero.
+pub fn run_batch(
+    error_model: &mut impl ErrorModelInterface,
+    operations: Vec<Operation>,
+) -> Result<BatchResult> {
+    run_timed_batch(error_model, 0, 0, operations)
+}
+
+/// Pass the operations to the error model as a single batch with the given
+/// start time and duration.
+pub fn run_timed_batch(
+    error_model: &mut impl ErrorModelInterface,
+    start_ns: u64,
+    duration_ns: u64,
+    operations: Vec<Operation>,
+) -> Result<BatchResult> {
+    let batch = BatchOperation::new(operations, start_ns.into(), duration_ns.into());
+    error_model.handle_operations(batch)
+}
+
+/// Measure a qubit through the error model, returning 2 if it is reported as
+/// leaked.
+pub fn measure_leaked(error_model: &mut impl ErrorModelInterface, qubit_id: u64) -> Result<u64> {
+    let operation = Operation::MeasureLeaked {
+        qubit_id,
+        result_id: 0,
+    };
+    let results = run_batch(error_model, vec![operation])?;
+    Ok(results.u64_results[0].value)
+}
+
+/// An RXY gate on the given qubit by an angle that does not flip its bit in
+/// a [RecordingSimulator].
+pub fn rxy(qubit_id: u64) -> Operation {
+    Operation::RXYGate {
+        qubit_id,
+        theta: 1.0,
+        phi: 0.0,
+    }
+}
+
+/// All metrics reported by the error model.
+pub fn metrics(error_model: &mut impl ErrorModelInterface) -> Result<Vec<(String, MetricValue)>> {
+    let mut metrics = Vec::new();
+    for nth_metric in 0..=u8::MAX {
+        match error_model.get_metric(nth_metric)? {
+            Some(metric) => metrics.push(metric),
+            None => break,
+        }
+    }
+    Ok(metrics)
+}
+
+/// The error model's metric with the given tag, if it reports one.
+pub fn find_metric(
+    error_model: &mut impl ErrorModelInterface,
+    tag: &str,
+) -> Result<Option<MetricValue>> {
+    Ok(metrics(error_model)?
+        .into_iter()
+        .find_map(|(t, value)| (t == tag).then_some(value)))
+}
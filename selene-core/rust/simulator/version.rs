@@ -35,7 +35,7 @@ pub const CURRENT_API_VERSION: SimulatorAPIVersion = SimulatorAPIVersion {
     reserved: 0,
     major: 0,
     minor: 1,
-    patch: 0,
+    patch: 6,
 };
 
 impl SimulatorAPIVersion {
@@ -17,6 +17,7 @@ pub fn selene_str_to_bytes(s: SeleneStr) -> &'static [u8] {
 }
 
 /// Represents different types of metric values.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MetricValue {
     Bool(bool),
     I64(i64),
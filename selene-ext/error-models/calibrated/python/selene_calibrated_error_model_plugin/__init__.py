@@ -0,0 +1,3 @@
+from .plugin import CalibratedPlugin
+
+__all__ = ["CalibratedPlugin"]
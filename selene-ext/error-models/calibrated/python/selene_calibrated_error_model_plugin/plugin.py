@@ -0,0 +1,51 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+
+from selene_core import ErrorModel
+
+
+@dataclass
+class CalibratedPlugin(ErrorModel):
+    """
+    A plugin for simulating Pauli noise at rates taken from a device
+    calibration file. The file is YAML or JSON, and may provide default,
+    per-qubit and per-pair Pauli fault probabilities, per-qubit asymmetric
+    readout error and initialization error, and overrides for specific gate
+    types. The total number of faults of each kind is reported in the error
+    model's metrics.
+    Attributes:
+        calibration_file (Path): The path to the calibration file. See the
+            crate documentation for its format.
+        per_qubit_metrics (bool): Whether to also report the faults on each
+            qubit. Metrics are limited in number, so this is only supported
+            for up to 82 qubits.
+    """
+
+    calibration_file: Path | str
+    per_qubit_metrics: bool = False
+
+    def __post_init__(self):
+        self.calibration_file = Path(self.calibration_file)
+        assert self.calibration_file.is_file(), (
+            f"calibration_file ({self.calibration_file}) must be an existing file"
+        )
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_calibrated_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_calibrated_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_calibrated_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    def get_init_args(self):
+        args = [f"--calibration-file={Path(self.calibration_file).resolve()}"]
+        if self.per_qubit_metrics:
+            args.append("--per-qubit-metrics")
+        return args
@@ -0,0 +1,390 @@
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Probabilities of an X, Y or Z fault on a single qubit.
+pub type SingleQubitChannel = [f64; 3];
+/// Probabilities of each of the 15 non-identity two-qubit Pauli faults,
+/// ordered IX, IY, IZ, XI, XX, ..., ZZ.
+pub type TwoQubitChannel = [f64; 15];
+
+/// The gate types that can be given their own fault rates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GateKind {
+    Rxy,
+    Rz,
+    U,
+    Rzz,
+    TwoQubitUnitary,
+    DiagonalPhase,
+}
+
+impl GateKind {
+    fn parse(name: &str, two_qubit: bool) -> Result<Self> {
+        let kind = match name {
+            "rxy" => GateKind::Rxy,
+            "rz" => GateKind::Rz,
+            "u" => GateKind::U,
+            "rzz" => GateKind::Rzz,
+            "two_qubit_unitary" => GateKind::TwoQubitUnitary,
+            "diagonal_phase" => GateKind::DiagonalPhase,
+            _ => bail!("unknown gate type '{name}'"),
+        };
+        if kind.is_two_qubit() != two_qubit && kind != GateKind::DiagonalPhase {
+            bail!(
+                "gate type '{name}' acts on {} but was given {} rates",
+                if two_qubit { "one qubit" } else { "two qubits" },
+                if two_qubit {
+                    "two-qubit"
+                } else {
+                    "single-qubit"
+                },
+            );
+        }
+        Ok(kind)
+    }
+    fn is_two_qubit(self) -> bool {
+        matches!(self, GateKind::Rzz | GateKind::TwoQubitUnitary)
+    }
+}
+
+/// Asymmetric readout error for a single qubit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Readout {
+    /// The probability of reading 1 when the qubit was measured as 0.
+    #[serde(default)]
+    pub p1_given_0: f64,
+    /// The probability of reading 0 when the qubit was measured as 1.
+    #[serde(default)]
+    pub p0_given_1: f64,
+}
+
+impl Readout {
+    /// The probability that a measurement with the given outcome is flipped.
+    pub fn flip_probability(&self, outcome: bool) -> f64 {
+        if outcome {
+            self.p0_given_1
+        } else {
+            self.p1_given_0
+        }
+    }
+    fn validate(&self) -> Result<()> {
+        check_probability("p1_given_0", self.p1_given_0)?;
+        check_probability("p0_given_1", self.p0_given_1)
+    }
+}
+
+// The on-disk layout. Pauli channels are written as maps from labels
+// ("X", "ZZ", ...) to probabilities so that a calibration only needs to
+// list the faults it has measured.
+type RawChannel = BTreeMap<String, f64>;
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct RawDefaults {
+    single_qubit: Option<RawChannel>,
+    two_qubit: Option<RawChannel>,
+    readout: Option<Readout>,
+    init: Option<f64>,
+    #[serde(default)]
+    single_qubit_gates: BTreeMap<String, RawChannel>,
+    #[serde(default)]
+    two_qubit_gates: BTreeMap<String, RawChannel>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawQubit {
+    qubit: u64,
+    single_qubit: Option<RawChannel>,
+    readout: Option<Readout>,
+    init: Option<f64>,
+    #[serde(default)]
+    gates: BTreeMap<String, RawChannel>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPair {
+    qubits: [u64; 2],
+    two_qubit: Option<RawChannel>,
+    #[serde(default)]
+    gates: BTreeMap<String, RawChannel>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawCalibration {
+    #[serde(default)]
+    default: RawDefaults,
+    #[serde(default)]
+    qubits: Vec<RawQubit>,
+    #[serde(default)]
+    pairs: Vec<RawPair>,
+}
+
+#[derive(Default)]
+struct QubitCalibration {
+    single_qubit: Option<SingleQubitChannel>,
+    readout: Option<Readout>,
+    init: Option<f64>,
+    gates: HashMap<GateKind, SingleQubitChannel>,
+}
+
+#[derive(Default)]
+struct PairCalibration {
+    two_qubit: Option<TwoQubitChannel>,
+    gates: HashMap<GateKind, TwoQubitChannel>,
+}
+
+/// Fault rates for a device, resolved from a calibration file.
+///
+/// Lookups prefer the most specific entry available: a per-qubit (or
+/// per-pair) gate override, then the per-qubit (or per-pair) rates, then
+/// the default gate override, then the default rates. Anything not listed
+/// is noiseless.
+#[derive(Default)]
+pub struct Calibration {
+    single_qubit: SingleQubitChannel,
+    two_qubit: TwoQubitChannel,
+    readout: Readout,
+    init: f64,
+    single_qubit_gates: HashMap<GateKind, SingleQubitChannel>,
+    two_qubit_gates: HashMap<GateKind, TwoQubitChannel>,
+    qubits: HashMap<u64, QubitCalibration>,
+    pairs: HashMap<(u64, u64), PairCalibration>,
+}
+
+impl Calibration {
+    pub fn from_file(path: impl AsRef<Path>, n_qubits: u64) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read calibration file {}", path.display()))?;
+        Self::parse(&text, n_qubits)
+            .with_context(|| format!("Invalid calibration file {}", path.display()))
+    }
+
+    /// Parses a calibration for `n_qubits` qubits from YAML. As YAML is a
+    /// superset of JSON, JSON calibrations are accepted too.
+    pub fn parse(text: &str, n_qubits: u64) -> Result<Self> {
+        let raw: RawCalibration = serde_yml::from_str(text)?;
+        let mut calibration = Calibration::default();
+
+        let defaults = raw.default;
+        if let Some(channel) = defaults.single_qubit {
+            calibration.single_qubit = single_qubit_channel(&channel).context("default")?;
+        }
+        if let Some(channel) = defaults.two_qubit {
+            calibration.two_qubit = two_qubit_channel(&channel).context("default")?;
+        }
+        if let Some(readout) = defaults.readout {
+            readout.validate().context("default readout")?;
+            calibration.readout = readout;
+        }
+        if let Some(init) = defaults.init {
+            check_probability("default init", init)?;
+            calibration.init = init;
+        }
+        calibration.single_qubit_gates =
+            single_qubit_gates(&defaults.single_qubit_gates).context("default")?;
+        calibration.two_qubit_gates =
+            two_qubit_gates(&defaults.two_qubit_gates).context("default")?;
+
+        for entry in raw.qubits {
+            let context = || format!("qubit {}", entry.qubit);
+            if entry.qubit >= n_qubits {
+                bail!(
+                    "qubit {} is out of range for {n_qubits} qubits",
+                    entry.qubit
+                );
+            }
+            let mut qubit = QubitCalibration {
+                gates: single_qubit_gates(&entry.gates).with_context(context)?,
+                ..Default::default()
+            };
+            if let Some(channel) = &entry.single_qubit {
+                qubit.single_qubit = Some(single_qubit_channel(channel).with_context(context)?);
+            }
+            if let Some(readout) = entry.readout {
+                readout.validate().with_context(context)?;
+                qubit.readout = Some(readout);
+            }
+            if let Some(init) = entry.init {
+                check_probability("init", init).with_context(context)?;
+                qubit.init = Some(init);
+            }
+            if calibration.qubits.insert(entry.qubit, qubit).is_some() {
+                bail!("qubit {} is listed more than once", entry.qubit);
+            }
+        }
+
+        for entry in raw.pairs {
+            let [q0, q1] = entry.qubits;
+            let context = || format!("pair ({q0}, {q1})");
+            if q0 == q1 {
+                bail!("pair ({q0}, {q1}) must name two different qubits");
+            }
+            if q0 >= n_qubits || q1 >= n_qubits {
+                bail!("pair ({q0}, {q1}) is out of range for {n_qubits} qubits");
+            }
+            let mut pair = PairCalibration {
+                gates: two_qubit_gates(&entry.gates).with_context(context)?,
+                ..Default::default()
+            };
+            if let Some(channel) = &entry.two_qubit {
+                pair.two_qubit = Some(two_qubit_channel(channel).with_context(context)?);
+            }
+            if calibration.pairs.contains_key(&(q1, q0))
+                || calibration.pairs.insert((q0, q1), pair).is_some()
+            {
+                bail!("pair ({q0}, {q1}) is listed more than once");
+            }
+        }
+        Ok(calibration)
+    }
+
+    pub fn single_qubit(&self, qubit: u64, gate: GateKind) -> &SingleQubitChannel {
+        let entry = self.qubits.get(&qubit);
+        entry
+            .and_then(|e| e.gates.get(&gate))
+            .or_else(|| entry.and_then(|e| e.single_qubit.as_ref()))
+            .or_else(|| self.single_qubit_gates.get(&gate))
+            .unwrap_or(&self.single_qubit)
+    }
+
+    /// The channel for a two-qubit gate, along with whether the calibration
+    /// was given for the pair in the opposite order (in which case the two
+    /// halves of a sampled fault must be swapped).
+    pub fn two_qubit(&self, q0: u64, q1: u64, gate: GateKind) -> (&TwoQubitChannel, bool) {
+        let (entry, reversed) = match self.pairs.get(&(q0, q1)) {
+            Some(entry) => (Some(entry), false),
+            None => match self.pairs.get(&(q1, q0)) {
+                Some(entry) => (Some(entry), true),
+                None => (None, false),
+            },
+        };
+        let specific = entry
+            .and_then(|e| e.gates.get(&gate))
+            .or_else(|| entry.and_then(|e| e.two_qubit.as_ref()));
+        match specific {
+            Some(channel) => (channel, reversed),
+            None => (
+                self.two_qubit_gates.get(&gate).unwrap_or(&self.two_qubit),
+                false,
+            ),
+        }
+    }
+
+    pub fn readout(&self, qubit: u64) -> &Readout {
+        self.qubits
+            .get(&qubit)
+            .and_then(|e| e.readout.as_ref())
+            .unwrap_or(&self.readout)
+    }
+
+    pub fn init(&self, qubit: u64) -> f64 {
+        self.qubits
+            .get(&qubit)
+            .and_then(|e| e.init)
+            .unwrap_or(self.init)
+    }
+}
+
+/// Picks a fault from a channel given a uniform random number in [0, 1),
+/// returning its index or None if no fault occurs.
+pub fn sample(channel: &[f64], random_float: f64) -> Option<usize> {
+    let mut cumulative = 0.0;
+    for (index, probability) in channel.iter().enumerate() {
+        cumulative += probability;
+        if random_float < cumulative {
+            return Some(index);
+        }
+    }
+    None
+}
+
+fn check_probability(name: &str, value: f64) -> Result<()> {
+    if !(0.0..=1.0).contains(&value) {
+        bail!("{name} ({value}) must be between 0 and 1 (both inclusive)");
+    }
+    Ok(())
+}
+
+fn pauli_index(label: char) -> Option<usize> {
+    match label {
+        'I' => Some(0),
+        'X' => Some(1),
+        'Y' => Some(2),
+        'Z' => Some(3),
+        _ => None,
+    }
+}
+
+fn channel<const N: usize>(
+    raw: &RawChannel,
+    index_of: impl Fn(&str) -> Option<usize>,
+) -> Result<[f64; N]> {
+    let mut channel = [0.0; N];
+    for (label, &probability) in raw {
+        let Some(index) = index_of(&label.to_ascii_uppercase()) else {
+            bail!("'{label}' is not a valid non-identity Pauli label for this channel");
+        };
+        check_probability(label, probability)?;
+        channel[index] = probability;
+    }
+    let total: f64 = channel.iter().sum();
+    if total > 1.0 {
+        bail!("fault probabilities sum to {total}, which exceeds 1");
+    }
+    Ok(channel)
+}
+
+fn single_qubit_channel(raw: &RawChannel) -> Result<SingleQubitChannel> {
+    channel(raw, |label| {
+        let mut chars = label.chars();
+        match (chars.next().and_then(pauli_index), chars.next()) {
+            (Some(p), None) if p > 0 => Some(p - 1),
+            _ => None,
+        }
+    })
+}
+
+fn two_qubit_channel(raw: &RawChannel) -> Result<TwoQubitChannel> {
+    channel(raw, |label| {
+        let mut chars = label.chars();
+        match (
+            chars.next().and_then(pauli_index),
+            chars.next().and_then(pauli_index),
+            chars.next(),
+        ) {
+            (Some(p0), Some(p1), None) if p0 + p1 > 0 => Some(p0 * 4 + p1 - 1),
+            _ => None,
+        }
+    })
+}
+
+fn single_qubit_gates(
+    raw: &BTreeMap<String, RawChannel>,
+) -> Result<HashMap<GateKind, SingleQubitChannel>> {
+    raw.iter()
+        .map(|(name, channel)| {
+            let gate = GateKind::parse(name, false)?;
+            let channel = single_qubit_channel(channel).with_context(|| format!("gate {name}"))?;
+            Ok((gate, channel))
+        })
+        .collect()
+}
+
+fn two_qubit_gates(
+    raw: &BTreeMap<String, RawChannel>,
+) -> Result<HashMap<GateKind, TwoQubitChannel>> {
+    raw.iter()
+        .map(|(name, channel)| {
+            let gate = GateKind::parse(name, true)?;
+            let channel = two_qubit_channel(channel).with_context(|| format!("gate {name}"))?;
+            Ok((gate, channel))
+        })
+        .collect()
+}
@@ -0,0 +1,391 @@
+/// An error model that injects Pauli faults at rates read from a device
+/// calibration file.
+//
+// The calibration file is YAML (or JSON) with three optional sections:
+//
+//   default:
+//     single_qubit: { X: 1.0e-4, Y: 1.0e-4, Z: 2.0e-4 }
+//     two_qubit: { XX: 5.0e-4, ZZ: 1.0e-3 }
+//     readout: { p1_given_0: 1.0e-3, p0_given_1: 3.0e-3 }
+//     init: 1.0e-4
+//     single_qubit_gates: { rz: {} }
+//     two_qubit_gates: { rzz: { ZZ: 2.0e-3 } }
+//   qubits:
+//     - qubit: 3
+//       single_qubit: { Z: 4.0e-4 }
+//       readout: { p1_given_0: 2.0e-3, p0_given_1: 6.0e-3 }
+//       gates: { rxy: { X: 3.0e-4 } }
+//   pairs:
+//     - qubits: [0, 1]
+//       two_qubit: { ZZ: 1.5e-3 }
+//       gates: { rzz: { ZZ: 2.5e-3 } }
+//
+// Pauli faults not listed have probability zero. Each gate samples a fault
+// from the most specific channel available (see Calibration), and applies
+// it before the gate itself, as the depolarizing model does. Measurements
+// are flipped according to the outcome-dependent readout error, and resets
+// leave the qubit in |1> with the init probability.
+//
+// Faults are counted per qubit. A two-qubit fault counts towards each
+// qubit that received a non-identity Pauli. The totals over all qubits are
+// always reported as metrics. With --per-qubit-metrics, the counts for each
+// qubit follow them. Metrics are indexed by a u8, so per-qubit metrics are
+// limited to MAX_PER_QUBIT_METRIC_QUBITS qubits, and requesting them for a
+// larger register is an error.
+mod calibration;
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Result, anyhow};
+use calibration::{Calibration, GateKind, sample};
+use clap::Parser;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use selene_core::error_model::interface::ErrorModelInterfaceFactory;
+use selene_core::error_model::{BatchResult, ErrorModelInterface};
+use selene_core::export_error_model_plugin;
+use selene_core::runtime::{BatchOperation, Operation};
+use selene_core::simulator::{Simulator, SimulatorInterface};
+use selene_core::utils::MetricValue;
+use std::ffi::OsStr;
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// Path to a YAML or JSON calibration file
+    #[arg(long)]
+    calibration_file: PathBuf,
+    /// Report fault counts for each qubit as well as the totals
+    #[arg(long, default_value_t = false)]
+    per_qubit_metrics: bool,
+}
+
+#[derive(Default, Clone)]
+struct QubitStats {
+    pauli_faults: u64,
+    readout_faults: u64,
+    init_faults: u64,
+}
+
+#[derive(Default)]
+struct Stats {
+    gate_count_1q: u64,
+    gate_count_2q: u64,
+    measure_count: u64,
+    init_count: u64,
+    qubits: Vec<QubitStats>,
+}
+
+const N_GLOBAL_METRICS: usize = 7;
+const N_QUBIT_METRICS: usize = 3;
+/// The largest register for which every per-qubit metric has an index.
+/// Selene reads metrics at the indices below u8::MAX.
+const MAX_PER_QUBIT_METRIC_QUBITS: u64 =
+    ((u8::MAX as usize - N_GLOBAL_METRICS) / N_QUBIT_METRICS) as u64;
+
+pub enum ErrorType {
+    I,
+    X,
+    Y,
+    Z,
+}
+
+impl ErrorType {
+    fn from_index(index: usize) -> Self {
+        match index {
+            0 => ErrorType::I,
+            1 => ErrorType::X,
+            2 => ErrorType::Y,
+            _ => ErrorType::Z,
+        }
+    }
+}
+
+pub struct CalibratedErrorModel {
+    n_qubits: u64,
+    rng: Pcg64Mcg,
+    simulator: Simulator,
+    calibration: Calibration,
+    per_qubit_metrics: bool,
+    stats: Stats,
+}
+
+impl CalibratedErrorModel {
+    fn new(params: &Params, n_qubits: u64, simulator: Simulator) -> Result<Self> {
+        if params.per_qubit_metrics && n_qubits > MAX_PER_QUBIT_METRIC_QUBITS {
+            return Err(anyhow!(
+                "Per-qubit metrics are limited to {MAX_PER_QUBIT_METRIC_QUBITS} qubits, but {n_qubits} were requested."
+            ));
+        }
+        Ok(Self {
+            n_qubits,
+            rng: Pcg64Mcg::seed_from_u64(0),
+            simulator,
+            calibration: Calibration::from_file(&params.calibration_file, n_qubits)?,
+            per_qubit_metrics: params.per_qubit_metrics,
+            stats: Stats {
+                qubits: vec![QubitStats::default(); n_qubits as usize],
+                ..Default::default()
+            },
+        })
+    }
+    fn apply_error(&mut self, qubit: u64, error: ErrorType) -> Result<()> {
+        match error {
+            ErrorType::I => return Ok(()),
+            ErrorType::X => {
+                self.simulator.rxy(qubit, std::f64::consts::PI, 0.0)?;
+            }
+            ErrorType::Y => {
+                self.simulator
+                    .rxy(qubit, std::f64::consts::PI, std::f64::consts::PI / 2.0)?;
+            }
+            ErrorType::Z => {
+                self.simulator.rz(qubit, std::f64::consts::PI)?;
+            }
+        }
+        self.stats.qubits[qubit as usize].pauli_faults += 1;
+        Ok(())
+    }
+    fn total(&self, count: impl Fn(&QubitStats) -> u64) -> u64 {
+        self.stats.qubits.iter().map(count).sum()
+    }
+    fn validate_qubit(&self, qubit: u64) -> Result<()> {
+        if qubit >= self.n_qubits {
+            return Err(anyhow!(
+                "Error: qubit {} must be less than the number of qubits ({}).",
+                qubit,
+                self.n_qubits
+            ));
+        }
+        Ok(())
+    }
+    fn maybe_apply_1q_error(&mut self, q0: u64, gate: GateKind) -> Result<()> {
+        self.validate_qubit(q0)?;
+        self.stats.gate_count_1q += 1;
+        let channel = self.calibration.single_qubit(q0, gate);
+        if let Some(index) = sample(channel, self.rng.random::<f64>()) {
+            self.apply_error(q0, ErrorType::from_index(index + 1))?;
+        }
+        Ok(())
+    }
+    fn maybe_apply_2q_error(&mut self, q0: u64, q1: u64, gate: GateKind) -> Result<()> {
+        self.validate_qubit(q0)?;
+        self.validate_qubit(q1)?;
+        self.stats.gate_count_2q += 1;
+        let (channel, reversed) = self.calibration.two_qubit(q0, q1, gate);
+        if let Some(index) = sample(channel, self.rng.random::<f64>()) {
+            let (mut p0, mut p1) = ((index + 1) / 4, (index + 1) % 4);
+            if reversed {
+                (p0, p1) = (p1, p0);
+            }
+            self.apply_error(q0, ErrorType::from_index(p0))?;
+            self.apply_error(q1, ErrorType::from_index(p1))?;
+        }
+        Ok(())
+    }
+    fn maybe_flip_measurement(&mut self, qubit: u64, result: bool) -> Result<bool> {
+        self.validate_qubit(qubit)?;
+        self.stats.measure_count += 1;
+        let p_flip = self.calibration.readout(qubit).flip_probability(result);
+        if self.rng.random::<f64>() < p_flip {
+            self.stats.qubits[qubit as usize].readout_faults += 1;
+            Ok(!result)
+        } else {
+            Ok(result)
+        }
+    }
+    fn maybe_flip_on_init(&mut self, qubit: u64) -> Result<()> {
+        self.validate_qubit(qubit)?;
+        self.stats.init_count += 1;
+        if self.rng.random::<f64>() < self.calibration.init(qubit) {
+            self.stats.qubits[qubit as usize].init_faults += 1;
+            self.simulator.rxy(qubit, std::f64::consts::PI, 0.0)?;
+        }
+        Ok(())
+    }
+}
+
+impl ErrorModelInterface for CalibratedErrorModel {
+    fn shot_start(&mut self, shot_id: u64, seed: u64, simulator_seed: u64) -> Result<()> {
+        self.rng = Pcg64Mcg::seed_from_u64(seed);
+        self.simulator.shot_start(shot_id, simulator_seed)?;
+        self.stats = Stats {
+            qubits: vec![QubitStats::default(); self.n_qubits as usize],
+            ..Default::default()
+        };
+        Ok(())
+    }
+    fn plan_shots(
+        &mut self,
+        shot_ids: &[u64],
+        _seeds: &[u64],
+        simulator_seeds: &[u64],
+    ) -> Result<()> {
+        self.simulator.plan_shots(shot_ids, simulator_seeds)
+    }
+    fn shot_end(&mut self) -> Result<()> {
+        self.simulator.shot_end()?;
+        Ok(())
+    }
+
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn handle_operations(&mut self, operations: BatchOperation) -> Result<BatchResult> {
+        let mut results = BatchResult::default();
+        for op in operations {
+            match op {
+                Operation::RXYGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                } => {
+                    self.maybe_apply_1q_error(qubit_id, GateKind::Rxy)?;
+                    self.simulator.rxy(qubit_id, theta, phi)?;
+                }
+                Operation::RZGate { qubit_id, theta } => {
+                    self.maybe_apply_1q_error(qubit_id, GateKind::Rz)?;
+                    self.simulator.rz(qubit_id, theta)?;
+                }
+                Operation::RZZGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    theta,
+                } => {
+                    self.maybe_apply_2q_error(qubit_id_1, qubit_id_2, GateKind::Rzz)?;
+                    self.simulator.rzz(qubit_id_1, qubit_id_2, theta)?;
+                }
+                Operation::UGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                    lambda,
+                } => {
+                    self.maybe_apply_1q_error(qubit_id, GateKind::U)?;
+                    self.simulator.u(qubit_id, theta, phi, lambda)?;
+                }
+                Operation::TwoQubitUnitaryGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    matrix,
+                } => {
+                    self.maybe_apply_2q_error(qubit_id_1, qubit_id_2, GateKind::TwoQubitUnitary)?;
+                    self.simulator
+                        .two_qubit_unitary(qubit_id_1, qubit_id_2, &matrix)?;
+                }
+                Operation::DiagonalPhaseGate { qubit_ids, phases } => {
+                    // As in the depolarizing model, two-qubit phase gates use the
+                    // pair's channel and other widths use per-qubit channels.
+                    match *qubit_ids {
+                        [q0, q1] => self.maybe_apply_2q_error(q0, q1, GateKind::DiagonalPhase)?,
+                        _ => {
+                            for &qubit_id in qubit_ids.iter() {
+                                self.maybe_apply_1q_error(qubit_id, GateKind::DiagonalPhase)?;
+                            }
+                        }
+                    }
+                    self.simulator.diagonal_phase(&qubit_ids, &phases)?;
+                }
+                Operation::Measure {
+                    qubit_id,
+                    result_id,
+                } => {
+                    let measurement = self.simulator.measure(qubit_id)?;
+                    let modified_measurement =
+                        self.maybe_flip_measurement(qubit_id, measurement)?;
+                    results.set_bool_result(result_id, modified_measurement);
+                }
+                Operation::MeasureLeaked {
+                    qubit_id,
+                    result_id,
+                } => {
+                    // Leaked outcomes are reported unchanged; readout error only
+                    // applies to computational outcomes.
+                    let measurement = match self.simulator.measure_leaked(qubit_id)? {
+                        2 => 2,
+                        outcome => self.maybe_flip_measurement(qubit_id, outcome == 1)? as u64,
+                    };
+                    results.set_u64_result(result_id, measurement);
+                }
+                Operation::Reset { qubit_id } => {
+                    self.simulator.reset(qubit_id)?;
+                    self.maybe_flip_on_init(qubit_id)?;
+                }
+                Operation::Custom { .. } => {
+                    // Passively ignore custom operations
+                }
+                Operation::QAlloc { qubit_id } => {
+                    self.simulator.qalloc(qubit_id)?;
+                }
+                Operation::QFree { qubit_id } => {
+                    self.simulator.qfree(qubit_id)?;
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        let (tag, value) = match nth_metric {
+            0 => ("gates_1q".to_string(), self.stats.gate_count_1q),
+            1 => ("gates_2q".to_string(), self.stats.gate_count_2q),
+            2 => ("measurements".to_string(), self.stats.measure_count),
+            3 => ("inits".to_string(), self.stats.init_count),
+            4 => ("pauli_faults".to_string(), self.total(|q| q.pauli_faults)),
+            5 => (
+                "readout_faults".to_string(),
+                self.total(|q| q.readout_faults),
+            ),
+            6 => ("init_faults".to_string(), self.total(|q| q.init_faults)),
+            _ if !self.per_qubit_metrics => return Ok(None),
+            _ => {
+                let index = nth_metric as usize - N_GLOBAL_METRICS;
+                let Some(stats) = self.stats.qubits.get(index / N_QUBIT_METRICS) else {
+                    return Ok(None);
+                };
+                let qubit = index / N_QUBIT_METRICS;
+                match index % N_QUBIT_METRICS {
+                    0 => (format!("pauli_faults_q{qubit}"), stats.pauli_faults),
+                    1 => (format!("readout_faults_q{qubit}"), stats.readout_faults),
+                    _ => (format!("init_faults_q{qubit}"), stats.init_faults),
+                }
+            }
+        };
+        Ok(Some((tag, MetricValue::U64(value))))
+    }
+    fn get_simulator_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        self.simulator.get_metric(nth_metric)
+    }
+}
+
+#[derive(Default)]
+pub struct CalibratedErrorModelFactory;
+
+impl ErrorModelInterfaceFactory for CalibratedErrorModelFactory {
+    type Interface = CalibratedErrorModel;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        error_model_args: &[impl AsRef<str>],
+        simulator_path: &impl AsRef<OsStr>,
+        simulator_args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        match Params::try_parse_from(error_model_args.iter().map(|s| s.as_ref())) {
+            Err(e) => Err(anyhow!(
+                "Error parsing arguments to calibrated error model plugin: {}",
+                e
+            )),
+            Ok(params) => {
+                let simulator =
+                    Simulator::load_from_file(simulator_path, n_qubits, simulator_args)?;
+                Ok(Box::new(CalibratedErrorModel::new(
+                    &params, n_qubits, simulator,
+                )?))
+            }
+        }
+    }
+}
+
+export_error_model_plugin!(crate::CalibratedErrorModelFactory);
@@ -0,0 +1,244 @@
+use crate::calibration::{Calibration, GateKind, Readout, sample};
+use crate::{CalibratedErrorModel, Params};
+use clap::Parser;
+use selene_core::error_model::ErrorModelInterface;
+use selene_core::runtime::{BatchOperation, Operation};
+use selene_core::simulator::recording::{
+    RecordedOperation, Recording, RecordingSimulatorFactory, metrics,
+};
+use selene_core::utils::MetricValue;
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const CALIBRATION: &str = r#"
+default:
+  single_qubit: { X: 0.01, Y: 0.02, Z: 0.03 }
+  two_qubit: { ZZ: 0.1 }
+  readout: { p1_given_0: 0.001, p0_given_1: 0.004 }
+  init: 0.005
+  single_qubit_gates:
+    rz: {}
+  two_qubit_gates:
+    rzz: { XX: 0.2 }
+qubits:
+  - qubit: 1
+    single_qubit: { z: 0.5 }
+    readout: { p1_given_0: 0.1, p0_given_1: 0.3 }
+    gates:
+      rxy: { X: 0.25 }
+pairs:
+  - qubits: [0, 1]
+    two_qubit: { IX: 0.3 }
+"#;
+
+#[test]
+fn lookups_prefer_the_most_specific_entry() {
+    let calibration = Calibration::parse(CALIBRATION, 4).unwrap();
+
+    assert_eq!(
+        *calibration.single_qubit(0, GateKind::Rxy),
+        [0.01, 0.02, 0.03]
+    );
+    assert_eq!(*calibration.single_qubit(0, GateKind::Rz), [0.0, 0.0, 0.0]);
+    assert_eq!(
+        *calibration.single_qubit(1, GateKind::Rxy),
+        [0.25, 0.0, 0.0]
+    );
+    assert_eq!(*calibration.single_qubit(1, GateKind::Rz), [0.0, 0.0, 0.5]);
+
+    let (channel, reversed) = calibration.two_qubit(0, 1, GateKind::Rzz);
+    assert!(!reversed);
+    assert_eq!(channel[0], 0.3);
+    let (channel, reversed) = calibration.two_qubit(1, 0, GateKind::Rzz);
+    assert!(reversed);
+    assert_eq!(channel[0], 0.3);
+    let (channel, reversed) = calibration.two_qubit(2, 3, GateKind::Rzz);
+    assert!(!reversed);
+    assert_eq!(channel[4], 0.2);
+    let (channel, _) = calibration.two_qubit(2, 3, GateKind::TwoQubitUnitary);
+    assert_eq!(channel[14], 0.1);
+
+    assert_eq!(
+        *calibration.readout(1),
+        Readout {
+            p1_given_0: 0.1,
+            p0_given_1: 0.3
+        }
+    );
+    assert_eq!(calibration.readout(2).flip_probability(false), 0.001);
+    assert_eq!(calibration.readout(2).flip_probability(true), 0.004);
+    assert_eq!(calibration.init(1), 0.005);
+}
+
+#[test]
+fn json_calibrations_are_accepted() {
+    let calibration = Calibration::parse(
+        r#"{"default": {"init": 0.5}, "qubits": [{"qubit": 0, "init": 0.25}]}"#,
+        2,
+    )
+    .unwrap();
+    assert_eq!(calibration.init(0), 0.25);
+    assert_eq!(calibration.init(1), 0.5);
+}
+
+#[test]
+fn invalid_calibrations_are_rejected() {
+    for text in [
+        "default: { single_qubit: { X: 0.6, Z: 0.6 } }",
+        "default: { single_qubit: { I: 0.1 } }",
+        "default: { two_qubit: { X: 0.1 } }",
+        "default: { readout: { p1_given_0: 1.5 } }",
+        "default: { single_qubit_gates: { rzz: { X: 0.1 } } }",
+        "default: { single_qubit_gates: { cx: { X: 0.1 } } }",
+        "default: { p_1q: 0.1 }",
+        "qubits: [{ qubit: 0, init: 0.1 }, { qubit: 0, init: 0.2 }]",
+        "pairs: [{ qubits: [0, 1] }, { qubits: [1, 0] }]",
+        "pairs: [{ qubits: [2, 2] }]",
+        "qubits: [{ qubit: 4, init: 0.1 }]",
+        "pairs: [{ qubits: [0, 4] }]",
+    ] {
+        assert!(Calibration::parse(text, 4).is_err(), "accepted: {text}");
+    }
+}
+
+#[test]
+fn sampling_walks_the_cumulative_distribution() {
+    let channel = [0.1, 0.2, 0.3];
+    assert_eq!(sample(&channel, 0.05), Some(0));
+    assert_eq!(sample(&channel, 0.25), Some(1));
+    assert_eq!(sample(&channel, 0.55), Some(2));
+    assert_eq!(sample(&channel, 0.65), None);
+}
+
+/// A calibrated error model for `n_qubits` qubits on a recording simulator.
+fn calibrated(
+    calibration: &str,
+    n_qubits: u64,
+    extra_args: &[&str],
+) -> anyhow::Result<(CalibratedErrorModel, Recording)> {
+    static FILES: AtomicUsize = AtomicUsize::new(0);
+    let file = std::env::temp_dir().join(format!(
+        "selene-calibrated-{}-{}.yaml",
+        std::process::id(),
+        FILES.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::write(&file, calibration).unwrap();
+    let calibration_file = format!("--calibration-file={}", file.display());
+    let args = ["", calibration_file.as_str()]
+        .into_iter()
+        .chain(extra_args.iter().copied());
+    let params = Params::try_parse_from(args).unwrap();
+    let (simulator, recording) = RecordingSimulatorFactory::simulator(n_qubits, false).unwrap();
+    let error_model = CalibratedErrorModel::new(&params, n_qubits, simulator);
+    std::fs::remove_file(&file).unwrap();
+    Ok((error_model?, recording))
+}
+
+#[test]
+fn faults_are_injected_and_counted_per_qubit() {
+    // Qubit 1 always has an X fault on RXY gates, qubit 2 always reads out
+    // flipped, and qubit 0 is always initialized to |1>.
+    let calibration = r#"
+qubits:
+  - qubit: 0
+    init: 1.0
+  - qubit: 1
+    gates: { rxy: { X: 1.0 } }
+  - qubit: 2
+    readout: { p1_given_0: 1.0, p0_given_1: 1.0 }
+"#;
+    let (mut error_model, recording) =
+        calibrated(calibration, 3, &["--per-qubit-metrics"]).unwrap();
+    error_model.shot_start(0, 1, 2).unwrap();
+    let batch = BatchOperation::new(
+        vec![
+            Operation::RXYGate {
+                qubit_id: 1,
+                theta: PI / 2.0,
+                phi: 0.0,
+            },
+            Operation::RXYGate {
+                qubit_id: 0,
+                theta: PI / 2.0,
+                phi: 0.0,
+            },
+            Operation::Reset { qubit_id: 0 },
+            Operation::Measure {
+                qubit_id: 0,
+                result_id: 0,
+            },
+            Operation::Measure {
+                qubit_id: 2,
+                result_id: 1,
+            },
+        ],
+        0.into(),
+        10.into(),
+    );
+    let results = error_model.handle_operations(batch).unwrap();
+    let values: Vec<bool> = results.bool_results.iter().map(|r| r.value).collect();
+    assert_eq!(values, vec![true, true]);
+
+    assert_eq!(
+        recording.take(),
+        vec![
+            RecordedOperation::ShotStart {
+                shot_id: 0,
+                seed: 2
+            },
+            // The fault precedes the gate.
+            RecordedOperation::Rxy {
+                qubit: 1,
+                theta: PI,
+                phi: 0.0
+            },
+            RecordedOperation::Rxy {
+                qubit: 1,
+                theta: PI / 2.0,
+                phi: 0.0
+            },
+            RecordedOperation::Rxy {
+                qubit: 0,
+                theta: PI / 2.0,
+                phi: 0.0
+            },
+            // The init fault follows the reset.
+            RecordedOperation::Reset { qubit: 0 },
+            RecordedOperation::Rxy {
+                qubit: 0,
+                theta: PI,
+                phi: 0.0
+            },
+            RecordedOperation::Measure { qubit: 0 },
+            RecordedOperation::Measure { qubit: 2 },
+        ]
+    );
+
+    let metrics = metrics(&mut error_model).unwrap();
+    let metric = |tag: &str| {
+        let (_, value) = metrics.iter().find(|(t, _)| t == tag).unwrap();
+        *value
+    };
+    assert_eq!(metric("gates_1q"), MetricValue::U64(2));
+    assert_eq!(metric("measurements"), MetricValue::U64(2));
+    assert_eq!(metric("inits"), MetricValue::U64(1));
+    assert_eq!(metric("pauli_faults"), MetricValue::U64(1));
+    assert_eq!(metric("readout_faults"), MetricValue::U64(1));
+    assert_eq!(metric("init_faults"), MetricValue::U64(1));
+    assert_eq!(metric("pauli_faults_q0"), MetricValue::U64(0));
+    assert_eq!(metric("pauli_faults_q1"), MetricValue::U64(1));
+    assert_eq!(metric("readout_faults_q2"), MetricValue::U64(1));
+    assert_eq!(metric("init_faults_q0"), MetricValue::U64(1));
+    assert_eq!(metrics.len(), 7 + 3 * 3);
+}
+
+#[test]
+fn per_qubit_metrics_are_limited_to_the_metric_index_space() {
+    let (mut error_model, _) = calibrated("{}", 200, &[]).unwrap();
+    assert_eq!(metrics(&mut error_model).unwrap().len(), 7);
+    assert!(calibrated("{}", 83, &["--per-qubit-metrics"]).is_err());
+    let (mut error_model, _) = calibrated("{}", 82, &["--per-qubit-metrics"]).unwrap();
+    let metrics = metrics(&mut error_model).unwrap();
+    assert_eq!(metrics.len(), 7 + 3 * 82);
+    assert_eq!(metrics.last().unwrap().0, "init_faults_q81");
+}
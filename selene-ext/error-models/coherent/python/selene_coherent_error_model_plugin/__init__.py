@@ -0,0 +1,3 @@
+from .plugin import CoherentPlugin
+
+__all__ = ["CoherentPlugin"]
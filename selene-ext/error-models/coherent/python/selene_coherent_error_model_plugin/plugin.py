@@ -0,0 +1,78 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+
+from selene_core import ErrorModel
+
+
+@dataclass
+class CoherentPlugin(ErrorModel):
+    """
+    A plugin for simulating systematic coherent errors. The angles of RXY, RZ
+    and RZZ gates are transformed as theta * (1 + scale) + offset before being
+    passed to the simulator, and each qubit may have a phase offset that
+    rotates the axis of its RXY and U gates. The offsets and scales may drift
+    as a Gaussian random walk from shot to shot.
+    Attributes:
+        rxy_scale (float): The fractional over-rotation of RXY gates.
+        rxy_offset (float): The additive angle offset of RXY gates.
+        rz_scale (float): The fractional over-rotation of RZ gates.
+        rz_offset (float): The additive angle offset of RZ gates.
+        rzz_scale (float): The fractional over-rotation of RZZ gates.
+        rzz_offset (float): The additive angle offset of RZZ gates.
+        phase_offsets (dict[int, float] | None): The phase offset of each
+            qubit's RXY and U gates, keyed by qubit index.
+        offset_drift (float): The standard deviation of each shot's drift of
+            the angle and phase offsets. Must be non-negative.
+        scale_drift (float): The standard deviation of each shot's drift of
+            the scales. Must be non-negative.
+    """
+
+    rxy_scale: float = 0.0
+    rxy_offset: float = 0.0
+    rz_scale: float = 0.0
+    rz_offset: float = 0.0
+    rzz_scale: float = 0.0
+    rzz_offset: float = 0.0
+    phase_offsets: dict[int, float] | None = None
+    offset_drift: float = 0.0
+    scale_drift: float = 0.0
+
+    def __post_init__(self):
+        assert self.offset_drift >= 0, (
+            f"offset_drift ({self.offset_drift}) must be non-negative"
+        )
+        assert self.scale_drift >= 0, (
+            f"scale_drift ({self.scale_drift}) must be non-negative"
+        )
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_coherent_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_coherent_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_coherent_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    def get_init_args(self):
+        args = [
+            f"--rxy-scale={self.rxy_scale}",
+            f"--rxy-offset={self.rxy_offset}",
+            f"--rz-scale={self.rz_scale}",
+            f"--rz-offset={self.rz_offset}",
+            f"--rzz-scale={self.rzz_scale}",
+            f"--rzz-offset={self.rzz_offset}",
+            f"--offset-drift={self.offset_drift}",
+            f"--scale-drift={self.scale_drift}",
+        ]
+        if self.phase_offsets:
+            offsets = ",".join(
+                f"{qubit}:{offset}" for qubit, offset in self.phase_offsets.items()
+            )
+            args.append(f"--phase-offsets={offsets}")
+        return args
@@ -0,0 +1,392 @@
+/// An error model that applies systematic coherent errors by modifying the
+/// angles of gates before they reach the simulator.
+//
+// RXY, RZ and RZZ angles are each transformed as theta * (1 + scale) + offset,
+// with a separate scale and offset for each gate type. U gates take the RXY
+// transformation of theta. Each qubit may also have a phase offset, which
+// rotates the axis of its RXY and U gates about Z, as a miscalibrated drive
+// phase would.
+//
+// Unlike Pauli noise, these errors are the same every time a gate is applied,
+// so they add coherently. To model calibrations going stale, the offsets and
+// scales can drift: at the start of each shot after the first, each takes a
+// Gaussian random-walk step with the given standard deviation. The current
+// values are reported as metrics, followed by the phase offset of each qubit
+// for as many qubits as the metric indices allow.
+//
+// Two-qubit unitaries and diagonal phase gates have no single angle to
+// modify, so they are passed through unchanged, as are measurements and
+// resets.
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use rand::{Rng, SeedableRng};
+use rand_distr::StandardNormal;
+use rand_pcg::Pcg64Mcg;
+use selene_core::error_model::interface::ErrorModelInterfaceFactory;
+use selene_core::error_model::{BatchResult, ErrorModelInterface};
+use selene_core::export_error_model_plugin;
+use selene_core::runtime::{BatchOperation, Operation};
+use selene_core::simulator::{Simulator, SimulatorInterface};
+use selene_core::utils::MetricValue;
+use std::ffi::OsStr;
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// The fractional over-rotation of RXY gates
+    #[arg(long, default_value_t = 0.0)]
+    rxy_scale: f64,
+    /// The additive angle offset of RXY gates
+    #[arg(long, default_value_t = 0.0)]
+    rxy_offset: f64,
+    /// The fractional over-rotation of RZ gates
+    #[arg(long, default_value_t = 0.0)]
+    rz_scale: f64,
+    /// The additive angle offset of RZ gates
+    #[arg(long, default_value_t = 0.0)]
+    rz_offset: f64,
+    /// The fractional over-rotation of RZZ gates
+    #[arg(long, default_value_t = 0.0)]
+    rzz_scale: f64,
+    /// The additive angle offset of RZZ gates
+    #[arg(long, default_value_t = 0.0)]
+    rzz_offset: f64,
+    /// Per-qubit phase offsets of RXY and U gates, e.g. "0:0.01,3:-0.02"
+    #[arg(long)]
+    phase_offsets: Option<String>,
+    /// The standard deviation of each shot's drift of the angle and phase offsets
+    #[arg(long, default_value_t = 0.0)]
+    offset_drift: f64,
+    /// The standard deviation of each shot's drift of the scales
+    #[arg(long, default_value_t = 0.0)]
+    scale_drift: f64,
+}
+
+fn parse_phase_offsets(offsets: &str, n_qubits: u64) -> Result<Vec<f64>> {
+    let mut phases = vec![0.0; n_qubits as usize];
+    for entry in offsets.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parsed = entry.split_once(':').and_then(|(qubit, offset)| {
+            Some((
+                qubit.trim().parse::<u64>().ok()?,
+                offset.trim().parse::<f64>().ok()?,
+            ))
+        });
+        let Some((qubit, offset)) = parsed else {
+            bail!(
+                "Invalid phase offset '{entry}': expected a qubit index and offset joined by ':'"
+            );
+        };
+        if qubit >= n_qubits {
+            bail!(
+                "Invalid phase offset '{entry}': qubit must be less than the number of qubits ({n_qubits})"
+            );
+        }
+        phases[qubit as usize] = offset;
+    }
+    Ok(phases)
+}
+
+/// A scale and offset applied to a gate angle.
+#[derive(Clone, Copy, Debug, Default)]
+struct AngleError {
+    scale: f64,
+    offset: f64,
+}
+
+impl AngleError {
+    fn apply(&self, theta: f64) -> f64 {
+        theta * (1.0 + self.scale) + self.offset
+    }
+}
+
+/// The current coherent errors, which may drift from shot to shot.
+#[derive(Clone, Debug)]
+struct CoherentErrors {
+    rxy: AngleError,
+    rz: AngleError,
+    rzz: AngleError,
+    phases: Vec<f64>,
+}
+
+impl CoherentErrors {
+    fn new(params: &Params, n_qubits: u64) -> Result<Self> {
+        Ok(Self {
+            rxy: AngleError {
+                scale: params.rxy_scale,
+                offset: params.rxy_offset,
+            },
+            rz: AngleError {
+                scale: params.rz_scale,
+                offset: params.rz_offset,
+            },
+            rzz: AngleError {
+                scale: params.rzz_scale,
+                offset: params.rzz_offset,
+            },
+            phases: match &params.phase_offsets {
+                Some(offsets) => parse_phase_offsets(offsets, n_qubits)?,
+                None => vec![0.0; n_qubits as usize],
+            },
+        })
+    }
+
+    fn drift(&mut self, rng: &mut impl Rng, scale_std: f64, offset_std: f64) {
+        let mut step = |std: f64| {
+            if std > 0.0 {
+                std * rng.sample::<f64, _>(StandardNormal)
+            } else {
+                0.0
+            }
+        };
+        for angle_error in [&mut self.rxy, &mut self.rz, &mut self.rzz] {
+            angle_error.scale += step(scale_std);
+            angle_error.offset += step(offset_std);
+        }
+        for phase in self.phases.iter_mut() {
+            *phase += step(offset_std);
+        }
+    }
+
+    fn rxy(&self, qubit: u64, theta: f64, phi: f64) -> (f64, f64) {
+        (self.rxy.apply(theta), phi + self.phases[qubit as usize])
+    }
+
+    // Rotating the axis of U(theta, phi, lambda) = RZ(phi) RY(theta) RZ(lambda)
+    // by delta gives U(theta, phi + delta, lambda - delta).
+    fn u(&self, qubit: u64, theta: f64, phi: f64, lambda: f64) -> (f64, f64, f64) {
+        let delta = self.phases[qubit as usize];
+        (self.rxy.apply(theta), phi + delta, lambda - delta)
+    }
+}
+
+pub struct CoherentErrorModel {
+    n_qubits: u64,
+    rng: Pcg64Mcg,
+    simulator: Simulator,
+    error_params: Params,
+    errors: CoherentErrors,
+    shots_started: u64,
+    modified_gates: u64,
+}
+
+/// The number of metrics reported before the per-qubit phase offsets.
+const N_GLOBAL_METRICS: u8 = 7;
+
+impl CoherentErrorModel {
+    fn new(params: Params, n_qubits: u64, simulator: Simulator) -> Result<Self> {
+        if params.offset_drift < 0.0 || params.scale_drift < 0.0 {
+            bail!("offset_drift and scale_drift must be non-negative");
+        }
+        let errors = CoherentErrors::new(&params, n_qubits)?;
+        Ok(Self {
+            n_qubits,
+            rng: Pcg64Mcg::seed_from_u64(0),
+            simulator,
+            error_params: params,
+            errors,
+            shots_started: 0,
+            modified_gates: 0,
+        })
+    }
+
+    fn validate_qubit(&self, qubit: u64) -> Result<()> {
+        if qubit >= self.n_qubits {
+            return Err(anyhow!(
+                "Error: qubit {} must be less than the number of qubits ({}).",
+                qubit,
+                self.n_qubits
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl ErrorModelInterface for CoherentErrorModel {
+    fn shot_start(&mut self, shot_id: u64, seed: u64, simulator_seed: u64) -> Result<()> {
+        self.rng = Pcg64Mcg::seed_from_u64(seed);
+        self.simulator.shot_start(shot_id, simulator_seed)?;
+        if self.shots_started > 0 {
+            self.errors.drift(
+                &mut self.rng,
+                self.error_params.scale_drift,
+                self.error_params.offset_drift,
+            );
+        }
+        self.shots_started += 1;
+        self.modified_gates = 0;
+        Ok(())
+    }
+    fn plan_shots(
+        &mut self,
+        shot_ids: &[u64],
+        _seeds: &[u64],
+        simulator_seeds: &[u64],
+    ) -> Result<()> {
+        self.simulator.plan_shots(shot_ids, simulator_seeds)
+    }
+    fn shot_end(&mut self) -> Result<()> {
+        self.simulator.shot_end()?;
+        Ok(())
+    }
+
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn handle_operations(&mut self, operations: BatchOperation) -> Result<BatchResult> {
+        let mut results = BatchResult::default();
+        for op in operations {
+            match op {
+                Operation::RXYGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                } => {
+                    self.validate_qubit(qubit_id)?;
+                    let (theta, phi) = self.errors.rxy(qubit_id, theta, phi);
+                    self.modified_gates += 1;
+                    self.simulator.rxy(qubit_id, theta, phi)?;
+                }
+                Operation::RZGate { qubit_id, theta } => {
+                    self.modified_gates += 1;
+                    self.simulator.rz(qubit_id, self.errors.rz.apply(theta))?;
+                }
+                Operation::RZZGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    theta,
+                } => {
+                    self.modified_gates += 1;
+                    self.simulator
+                        .rzz(qubit_id_1, qubit_id_2, self.errors.rzz.apply(theta))?;
+                }
+                Operation::UGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                    lambda,
+                } => {
+                    self.validate_qubit(qubit_id)?;
+                    let (theta, phi, lambda) = self.errors.u(qubit_id, theta, phi, lambda);
+                    self.modified_gates += 1;
+                    self.simulator.u(qubit_id, theta, phi, lambda)?;
+                }
+                Operation::TwoQubitUnitaryGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    matrix,
+                } => {
+                    self.simulator
+                        .two_qubit_unitary(qubit_id_1, qubit_id_2, &matrix)?;
+                }
+                Operation::DiagonalPhaseGate { qubit_ids, phases } => {
+                    self.simulator.diagonal_phase(&qubit_ids, &phases)?;
+                }
+                Operation::Measure {
+                    qubit_id,
+                    result_id,
+                } => {
+                    let measurement = self.simulator.measure(qubit_id)?;
+                    results.set_bool_result(result_id, measurement);
+                }
+                Operation::MeasureLeaked {
+                    qubit_id,
+                    result_id,
+                } => {
+                    let measurement = self.simulator.measure_leaked(qubit_id)?;
+                    results.set_u64_result(result_id, measurement);
+                }
+                Operation::Reset { qubit_id } => {
+                    self.simulator.reset(qubit_id)?;
+                }
+                Operation::Custom { .. } => {
+                    // Passively ignore custom operations
+                }
+                Operation::QAlloc { qubit_id } => {
+                    self.simulator.qalloc(qubit_id)?;
+                }
+                Operation::QFree { qubit_id } => {
+                    self.simulator.qfree(qubit_id)?;
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "modified_gates".to_string(),
+                MetricValue::U64(self.modified_gates),
+            ))),
+            1 => Ok(Some((
+                "rxy_scale".to_string(),
+                MetricValue::F64(self.errors.rxy.scale),
+            ))),
+            2 => Ok(Some((
+                "rxy_offset".to_string(),
+                MetricValue::F64(self.errors.rxy.offset),
+            ))),
+            3 => Ok(Some((
+                "rz_scale".to_string(),
+                MetricValue::F64(self.errors.rz.scale),
+            ))),
+            4 => Ok(Some((
+                "rz_offset".to_string(),
+                MetricValue::F64(self.errors.rz.offset),
+            ))),
+            5 => Ok(Some((
+                "rzz_scale".to_string(),
+                MetricValue::F64(self.errors.rzz.scale),
+            ))),
+            6 => Ok(Some((
+                "rzz_offset".to_string(),
+                MetricValue::F64(self.errors.rzz.offset),
+            ))),
+            n => {
+                let qubit = (n - N_GLOBAL_METRICS) as usize;
+                Ok(self
+                    .errors
+                    .phases
+                    .get(qubit)
+                    .map(|&phase| (format!("phase_offset_{qubit}"), MetricValue::F64(phase))))
+            }
+        }
+    }
+    fn get_simulator_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        self.simulator.get_metric(nth_metric)
+    }
+}
+
+#[derive(Default)]
+pub struct CoherentErrorModelFactory;
+
+impl ErrorModelInterfaceFactory for CoherentErrorModelFactory {
+    type Interface = CoherentErrorModel;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        error_model_args: &[impl AsRef<str>],
+        simulator_path: &impl AsRef<OsStr>,
+        simulator_args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        match Params::try_parse_from(error_model_args.iter().map(|s| s.as_ref())) {
+            Err(e) => Err(anyhow!(
+                "Error parsing arguments to coherent error model plugin: {}",
+                e
+            )),
+            Ok(params) => {
+                let simulator =
+                    Simulator::load_from_file(simulator_path, n_qubits, simulator_args)?;
+                Ok(Box::new(CoherentErrorModel::new(
+                    params, n_qubits, simulator,
+                )?))
+            }
+        }
+    }
+}
+
+export_error_model_plugin!(crate::CoherentErrorModelFactory);
@@ -0,0 +1,196 @@
+use crate::{CoherentErrorModel, Params};
+use anyhow::Result;
+use approx::assert_relative_eq;
+use clap::Parser;
+use selene_core::error_model::ErrorModelInterface;
+use selene_core::runtime::Operation;
+use selene_core::simulator::recording::{
+    RecordedOperation, Recording, find_metric, metrics, recorded_error_model, run_batch,
+};
+use selene_core::utils::MetricValue;
+
+/// A coherent error model on a recording simulator, after the start of the
+/// first shot.
+fn coherent(args: &[&str], n_qubits: u64) -> Result<(CoherentErrorModel, Recording)> {
+    let params = Params::try_parse_from(std::iter::once("").chain(args.iter().copied()))?;
+    recorded_error_model(n_qubits, false, |simulator| {
+        CoherentErrorModel::new(params, n_qubits, simulator)
+    })
+}
+
+fn f64_metric(error_model: &mut CoherentErrorModel, tag: &str) -> f64 {
+    let Some(MetricValue::F64(value)) = find_metric(error_model, tag).unwrap() else {
+        panic!("{tag} is not an F64 metric");
+    };
+    value
+}
+
+fn rxy(qubit_id: u64) -> Operation {
+    Operation::RXYGate {
+        qubit_id,
+        theta: 1.0,
+        phi: 0.5,
+    }
+}
+
+#[test]
+fn modified_angles_reach_the_simulator() {
+    let (mut error_model, recording) = coherent(
+        &[
+            "--rxy-scale=0.1",
+            "--rxy-offset=0.01",
+            "--rz-offset=-0.02",
+            "--rzz-scale=-0.05",
+            "--phase-offsets=1:0.3",
+        ],
+        2,
+    )
+    .unwrap();
+    run_batch(
+        &mut error_model,
+        vec![
+            rxy(0),
+            rxy(1),
+            Operation::UGate {
+                qubit_id: 1,
+                theta: 1.0,
+                phi: 0.5,
+                lambda: 0.25,
+            },
+            Operation::RZGate {
+                qubit_id: 0,
+                theta: 0.0,
+            },
+            Operation::RZZGate {
+                qubit_id_1: 0,
+                qubit_id_2: 1,
+                theta: 2.0,
+            },
+            Operation::Reset { qubit_id: 0 },
+        ],
+    )
+    .unwrap();
+    let operations = recording.take();
+    let [
+        RecordedOperation::Rxy {
+            qubit: 0,
+            theta: theta_0,
+            phi: phi_0,
+        },
+        RecordedOperation::Rxy {
+            qubit: 1,
+            theta: theta_1,
+            phi: phi_1,
+        },
+        RecordedOperation::U {
+            qubit: 1,
+            theta: u_theta,
+            phi: u_phi,
+            lambda: u_lambda,
+        },
+        RecordedOperation::Rz {
+            qubit: 0,
+            theta: rz_theta,
+        },
+        RecordedOperation::Rzz {
+            qubit1: 0,
+            qubit2: 1,
+            theta: rzz_theta,
+        },
+        RecordedOperation::Reset { qubit: 0 },
+    ] = operations[..]
+    else {
+        panic!("unexpected operations: {operations:?}");
+    };
+    assert_relative_eq!(theta_0, 1.11);
+    assert_relative_eq!(phi_0, 0.5);
+    assert_relative_eq!(theta_1, 1.11);
+    assert_relative_eq!(phi_1, 0.8);
+    // Rotating the axis of a U gate shifts phi and lambda in opposite directions.
+    assert_relative_eq!(u_theta, 1.11);
+    assert_relative_eq!(u_phi, 0.8);
+    assert_relative_eq!(u_lambda, -0.05);
+    assert_relative_eq!(rz_theta, -0.02);
+    assert_relative_eq!(rzz_theta, 1.9);
+
+    let metrics = metrics(&mut error_model).unwrap();
+    assert_eq!(
+        metrics[0],
+        ("modified_gates".to_string(), MetricValue::U64(5))
+    );
+    let tags: Vec<_> = metrics.iter().map(|(tag, _)| tag.as_str()).collect();
+    assert_eq!(tags[7..], ["phase_offset_0", "phase_offset_1"]);
+    assert_eq!(f64_metric(&mut error_model, "phase_offset_0"), 0.0);
+    assert_eq!(f64_metric(&mut error_model, "phase_offset_1"), 0.3);
+}
+
+#[test]
+fn errors_drift_between_shots() {
+    let args = ["--rxy-offset=0.01", "--offset-drift=0.001"];
+    let (mut error_model, recording) = coherent(&args, 2).unwrap();
+    // The first shot uses the configured values.
+    run_batch(&mut error_model, vec![rxy(0), rxy(1)]).unwrap();
+    let first = recording.take();
+    assert_eq!(f64_metric(&mut error_model, "rxy_offset"), 0.01);
+    assert_eq!(f64_metric(&mut error_model, "phase_offset_1"), 0.0);
+
+    error_model.shot_end().unwrap();
+    error_model.shot_start(1, 7, 0).unwrap();
+    recording.take();
+    run_batch(&mut error_model, vec![rxy(0), rxy(1)]).unwrap();
+    let second = recording.take();
+    assert_ne!(first, second);
+    let offset = f64_metric(&mut error_model, "rxy_offset");
+    let phase = f64_metric(&mut error_model, "phase_offset_1");
+    assert_ne!(offset, 0.01);
+    assert_ne!(phase, 0.0);
+    // Only the offsets drift, and the simulator sees the drifted values.
+    assert_eq!(f64_metric(&mut error_model, "rxy_scale"), 0.0);
+    let RecordedOperation::Rxy { theta, phi, .. } = second[1] else {
+        panic!("unexpected operation: {:?}", second[1]);
+    };
+    assert_relative_eq!(theta, 1.0 + offset);
+    assert_relative_eq!(phi, 0.5 + phase);
+
+    // The drift is determined by the error model seeds.
+    let (mut replay, replay_recording) = coherent(&args, 2).unwrap();
+    replay.shot_end().unwrap();
+    replay.shot_start(1, 7, 0).unwrap();
+    replay_recording.take();
+    run_batch(&mut replay, vec![rxy(0), rxy(1)]).unwrap();
+    assert_eq!(replay_recording.take(), second);
+}
+
+#[test]
+fn errors_do_not_drift_by_default() {
+    let (mut error_model, recording) = coherent(&["--rz-scale=0.5"], 1).unwrap();
+    let rz = Operation::RZGate {
+        qubit_id: 0,
+        theta: 1.0,
+    };
+    run_batch(&mut error_model, vec![rz.clone()]).unwrap();
+    let first = recording.take();
+    error_model.shot_start(1, 7, 0).unwrap();
+    recording.take();
+    run_batch(&mut error_model, vec![rz]).unwrap();
+    assert_eq!(recording.take(), first);
+    assert_eq!(
+        first,
+        vec![RecordedOperation::Rz {
+            qubit: 0,
+            theta: 1.5
+        }]
+    );
+}
+
+#[test]
+fn invalid_arguments_are_rejected() {
+    assert!(coherent(&["--phase-offsets=0:0.1,1"], 2).is_err());
+    assert!(coherent(&["--phase-offsets=2:0.1"], 2).is_err());
+    assert!(coherent(&["--phase-offsets=a:b"], 2).is_err());
+    assert!(coherent(&["--offset-drift=-0.1"], 2).is_err());
+    assert!(coherent(&["--phase-offsets="], 2).is_ok());
+    // Gates on qubits beyond the configured number are rejected.
+    let (mut error_model, _) = coherent(&[], 1).unwrap();
+    assert!(run_batch(&mut error_model, vec![rxy(1)]).is_err());
+}
@@ -0,0 +1,3 @@
+from .plugin import CorrelatedReadoutPlugin
+
+__all__ = ["CorrelatedReadoutPlugin"]
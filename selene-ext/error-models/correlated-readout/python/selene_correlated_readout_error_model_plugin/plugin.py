@@ -0,0 +1,61 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+
+from selene_core import ErrorModel
+
+
+@dataclass
+class CorrelatedReadoutPlugin(ErrorModel):
+    """
+    A plugin for simulating correlated readout errors. The measurements in
+    each batch are assigned their reported outcomes together, using full
+    confusion matrices over groups of qubits, or per-qubit asymmetric readout
+    errors along with joint flips of pairs of qubits, as read from a confusion
+    file in YAML or JSON. Qubits that are not measured while a batch of
+    measurements takes place may also be dephased.
+
+    Confusion matrices and pairs only apply to measurements in the same batch,
+    so they need a runtime that groups measurements, such as SoftRZRuntime.
+    With SimpleRuntime, every measurement falls back to its per-qubit readout
+    error.
+    Attributes:
+        confusion_file (Path | None): The path to the confusion file. See the
+            crate documentation for its format. Readout is error-free if
+            omitted.
+        spectator_dephasing (float): The probability that each measurement
+            applies a Z fault to each live qubit that is not measured in the
+            same batch. Must be between 0 and 1 (inclusive).
+    """
+
+    confusion_file: Path | str | None = None
+    spectator_dephasing: float = 0.0
+
+    def __post_init__(self):
+        if self.confusion_file is not None:
+            self.confusion_file = Path(self.confusion_file)
+            assert self.confusion_file.is_file(), (
+                f"confusion_file ({self.confusion_file}) must be an existing file"
+            )
+        assert 0 <= self.spectator_dephasing <= 1, (
+            f"spectator_dephasing ({self.spectator_dephasing}) must be between 0 and 1 (both inclusive)"
+        )
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_correlated_readout_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_correlated_readout_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_correlated_readout_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    def get_init_args(self):
+        args = [f"--spectator-dephasing={self.spectator_dephasing}"]
+        if self.confusion_file is not None:
+            args.append(f"--confusion-file={Path(self.confusion_file).resolve()}")
+        return args
@@ -0,0 +1,278 @@
+use anyhow::{Context, Result, bail};
+use rand::Rng;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// The largest number of qubits a full confusion matrix may cover.
+pub const MAX_BLOCK_QUBITS: usize = 10;
+
+/// Asymmetric readout error for a single qubit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Readout {
+    /// The probability of reading 1 when the qubit was measured as 0.
+    #[serde(default)]
+    pub p1_given_0: f64,
+    /// The probability of reading 0 when the qubit was measured as 1.
+    #[serde(default)]
+    pub p0_given_1: f64,
+}
+
+impl Readout {
+    /// The probability that a measurement with the given outcome is flipped.
+    pub fn flip_probability(&self, outcome: bool) -> f64 {
+        if outcome {
+            self.p0_given_1
+        } else {
+            self.p1_given_0
+        }
+    }
+    fn validate(&self) -> Result<()> {
+        check_probability("p1_given_0", self.p1_given_0)?;
+        check_probability("p0_given_1", self.p0_given_1)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawQubit {
+    qubit: u64,
+    #[serde(default)]
+    p1_given_0: f64,
+    #[serde(default)]
+    p0_given_1: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPair {
+    qubits: [u64; 2],
+    p_joint_flip: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawBlock {
+    qubits: Vec<u64>,
+    matrix: Vec<Vec<f64>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfusion {
+    #[serde(default)]
+    default: Readout,
+    #[serde(default)]
+    qubits: Vec<RawQubit>,
+    #[serde(default)]
+    pairs: Vec<RawPair>,
+    #[serde(default)]
+    blocks: Vec<RawBlock>,
+}
+
+/// A full confusion matrix over a group of qubits. Row i holds the
+/// probabilities of each reported bitstring given the measured bitstring i,
+/// with the first qubit as the least significant bit.
+#[derive(Debug)]
+struct Block {
+    qubits: Vec<u64>,
+    matrix: Vec<Vec<f64>>,
+}
+
+/// Readout assignment errors for a device, resolved from a confusion file.
+///
+/// Measurements in the same batch are assigned together. A block's full
+/// confusion matrix is used when all of its qubits are measured in the batch.
+/// Every other measurement is flipped according to its qubit's asymmetric
+/// readout error, and then each listed pair of such measurements is flipped
+/// jointly with the pair's probability. Anything not listed is error-free.
+#[derive(Debug, Default)]
+pub struct Confusion {
+    default: Readout,
+    qubits: HashMap<u64, Readout>,
+    pairs: BTreeMap<(u64, u64), f64>,
+    blocks: Vec<Block>,
+}
+
+impl Confusion {
+    pub fn from_file(path: impl AsRef<Path>, n_qubits: u64) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read confusion file {}", path.display()))?;
+        Self::parse(&text, n_qubits)
+            .with_context(|| format!("Invalid confusion file {}", path.display()))
+    }
+
+    /// Parses a confusion file for a device of `n_qubits` qubits from YAML. As
+    /// YAML is a superset of JSON, JSON files are accepted too.
+    pub fn parse(text: &str, n_qubits: u64) -> Result<Self> {
+        let raw: RawConfusion = serde_yml::from_str(text)?;
+        raw.default.validate().context("default")?;
+        let mut confusion = Confusion {
+            default: raw.default,
+            ..Default::default()
+        };
+
+        for entry in raw.qubits {
+            if entry.qubit >= n_qubits {
+                bail!(
+                    "qubit {} is out of range for {n_qubits} qubits",
+                    entry.qubit
+                );
+            }
+            let readout = Readout {
+                p1_given_0: entry.p1_given_0,
+                p0_given_1: entry.p0_given_1,
+            };
+            readout
+                .validate()
+                .with_context(|| format!("qubit {}", entry.qubit))?;
+            if confusion.qubits.insert(entry.qubit, readout).is_some() {
+                bail!("qubit {} is listed more than once", entry.qubit);
+            }
+        }
+
+        for entry in raw.pairs {
+            let [q0, q1] = entry.qubits;
+            if q0 >= n_qubits || q1 >= n_qubits {
+                bail!("pair ({q0}, {q1}) is out of range for {n_qubits} qubits");
+            }
+            if q0 == q1 {
+                bail!("pair ({q0}, {q1}) must name two different qubits");
+            }
+            check_probability("p_joint_flip", entry.p_joint_flip)
+                .with_context(|| format!("pair ({q0}, {q1})"))?;
+            let key = (q0.min(q1), q0.max(q1));
+            if confusion.pairs.insert(key, entry.p_joint_flip).is_some() {
+                bail!("pair ({q0}, {q1}) is listed more than once");
+            }
+        }
+
+        for entry in raw.blocks {
+            let context = || format!("block {:?}", entry.qubits);
+            let block =
+                Block::new(entry.qubits.clone(), entry.matrix, n_qubits).with_context(context)?;
+            for &qubit in &block.qubits {
+                if confusion.blocks.iter().any(|b| b.qubits.contains(&qubit)) {
+                    bail!("qubit {qubit} is in more than one block");
+                }
+            }
+            confusion.blocks.push(block);
+        }
+        Ok(confusion)
+    }
+
+    pub fn readout(&self, qubit: u64) -> &Readout {
+        self.qubits.get(&qubit).unwrap_or(&self.default)
+    }
+
+    /// Assigns the reported outcomes of the measurements made in one batch,
+    /// given as pairs of qubits and measured outcomes. Each qubit may only be
+    /// measured once per batch.
+    pub fn assign(&self, measured: &[(u64, bool)], rng: &mut impl Rng) -> Result<Vec<bool>> {
+        let mut reported: Vec<bool> = measured.iter().map(|&(_, outcome)| outcome).collect();
+        let mut position: HashMap<u64, usize> = HashMap::with_capacity(measured.len());
+        for (i, &(qubit, _)) in measured.iter().enumerate() {
+            if position.insert(qubit, i).is_some() {
+                bail!("qubit {qubit} is measured more than once in the same batch");
+            }
+        }
+        let mut in_block = vec![false; measured.len()];
+
+        for block in &self.blocks {
+            let Some(positions) = block
+                .qubits
+                .iter()
+                .map(|qubit| position.get(qubit).copied())
+                .collect::<Option<Vec<usize>>>()
+            else {
+                continue;
+            };
+            let actual = positions.iter().enumerate().fold(0, |index, (bit, &i)| {
+                index | ((measured[i].1 as usize) << bit)
+            });
+            let assigned = block.sample(actual, rng.random::<f64>());
+            for (bit, &i) in positions.iter().enumerate() {
+                reported[i] = (assigned >> bit) & 1 == 1;
+                in_block[i] = true;
+            }
+        }
+
+        for (i, &(qubit, outcome)) in measured.iter().enumerate() {
+            if !in_block[i] && rng.random_bool(self.readout(qubit).flip_probability(outcome)) {
+                reported[i] = !reported[i];
+            }
+        }
+
+        for (&(q0, q1), &p_joint_flip) in &self.pairs {
+            let (Some(&i0), Some(&i1)) = (position.get(&q0), position.get(&q1)) else {
+                continue;
+            };
+            if !in_block[i0] && !in_block[i1] && rng.random_bool(p_joint_flip) {
+                reported[i0] = !reported[i0];
+                reported[i1] = !reported[i1];
+            }
+        }
+        Ok(reported)
+    }
+}
+
+impl Block {
+    fn new(qubits: Vec<u64>, matrix: Vec<Vec<f64>>, n_qubits: u64) -> Result<Self> {
+        if qubits.is_empty() || qubits.len() > MAX_BLOCK_QUBITS {
+            bail!("a block must have between 1 and {MAX_BLOCK_QUBITS} qubits");
+        }
+        for (i, qubit) in qubits.iter().enumerate() {
+            if *qubit >= n_qubits {
+                bail!("qubit {qubit} is out of range for {n_qubits} qubits");
+            }
+            if qubits[..i].contains(qubit) {
+                bail!("qubit {qubit} is listed more than once");
+            }
+        }
+        let size = 1 << qubits.len();
+        if matrix.len() != size {
+            bail!(
+                "the confusion matrix of {} qubits must have {size} rows, got {}",
+                qubits.len(),
+                matrix.len()
+            );
+        }
+        for (i, row) in matrix.iter().enumerate() {
+            if row.len() != size {
+                bail!("row {i} must have {size} entries, got {}", row.len());
+            }
+            for &probability in row {
+                check_probability(&format!("row {i} entry"), probability)?;
+            }
+            let total: f64 = row.iter().sum();
+            if (total - 1.0).abs() > 1e-9 {
+                bail!("row {i} sums to {total} rather than 1");
+            }
+        }
+        Ok(Self { qubits, matrix })
+    }
+
+    /// Picks a reported bitstring for the measured bitstring `actual`, given
+    /// a uniform random number in [0, 1).
+    fn sample(&self, actual: usize, random_float: f64) -> usize {
+        let row = &self.matrix[actual];
+        let mut cumulative = 0.0;
+        for (reported, probability) in row.iter().enumerate() {
+            cumulative += probability;
+            if random_float < cumulative {
+                return reported;
+            }
+        }
+        // Rounding may leave the row summing to slightly less than 1.
+        actual
+    }
+}
+
+fn check_probability(name: &str, value: f64) -> Result<()> {
+    if !(0.0..=1.0).contains(&value) {
+        bail!("{name} ({value}) must be between 0 and 1 (both inclusive)");
+    }
+    Ok(())
+}
@@ -0,0 +1,374 @@
+/// An error model that applies correlated readout assignment errors to the
+/// measurements in each batch, and dephases the qubits that are not measured
+/// while a batch of measurements takes place.
+//
+// The confusion file is YAML (or JSON) with four optional sections:
+//
+//   default: { p1_given_0: 1.0e-3, p0_given_1: 3.0e-3 }
+//   qubits:
+//     - { qubit: 3, p1_given_0: 2.0e-3, p0_given_1: 6.0e-3 }
+//   pairs:
+//     - { qubits: [0, 1], p_joint_flip: 5.0e-4 }
+//   blocks:
+//     - qubits: [4, 5]
+//       matrix:
+//         - [0.97, 0.01, 0.01, 0.01]
+//         - [0.02, 0.96, 0.00, 0.02]
+//         - [0.02, 0.00, 0.96, 0.02]
+//         - [0.01, 0.03, 0.03, 0.93]
+//
+// Each block gives a full confusion matrix, in which row i holds the
+// probabilities of each reported bitstring when bitstring i was measured,
+// with the first qubit of the block as the least significant bit. A block is
+// used when all of its qubits are measured in the same batch. Otherwise its
+// qubits fall back to the per-qubit readout error, as do all other qubits,
+// and pairs of such qubits measured in the same batch are then flipped
+// together with the pair's probability (see Confusion::assign).
+//
+// Batches only contain several measurements when the runtime groups them, as
+// the soft_rz runtime does. With runtimes that emit one operation per batch,
+// blocks and pairs have no effect, and every measurement falls back to the
+// per-qubit readout error.
+//
+// Measurement-induced dephasing is applied after each batch containing
+// measurements. Each measurement in the batch applies a Z fault to each live
+// qubit that is not being measured with probability --spectator-dephasing.
+mod confusion;
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use confusion::Confusion;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use selene_core::error_model::interface::ErrorModelInterfaceFactory;
+use selene_core::error_model::{BatchResult, ErrorModelInterface};
+use selene_core::export_error_model_plugin;
+use selene_core::runtime::{BatchOperation, Operation};
+use selene_core::simulator::{Simulator, SimulatorInterface};
+use selene_core::utils::MetricValue;
+use std::ffi::OsStr;
+use std::path::PathBuf;
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// Path to a YAML or JSON confusion file. Readout is error-free if omitted.
+    #[arg(long)]
+    confusion_file: Option<PathBuf>,
+    /// The probability that each measurement dephases each live qubit that
+    /// is not measured in the same batch
+    #[arg(long, default_value_t = 0.0)]
+    spectator_dephasing: f64,
+}
+
+impl Params {
+    fn validate(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.spectator_dephasing) {
+            bail!(
+                "spectator_dephasing ({}) must be between 0 and 1 (both inclusive)",
+                self.spectator_dephasing
+            );
+        }
+        Ok(())
+    }
+
+    /// The probability that a spectator is dephased by a batch containing
+    /// `n_measurements` measurements.
+    fn spectator_dephasing_probability(&self, n_measurements: usize) -> f64 {
+        1.0 - (1.0 - self.spectator_dephasing).powi(n_measurements as i32)
+    }
+}
+
+// A measurement awaiting its reported outcome.
+struct PendingMeasurement {
+    qubit: u64,
+    result_id: u64,
+    outcome: bool,
+    leaked: bool,
+}
+
+#[derive(Default)]
+struct Stats {
+    measurements: u64,
+    assignment_errors: u64,
+    correlated_assignment_errors: u64,
+    spectator_dephasing_faults: u64,
+}
+
+pub struct CorrelatedReadoutErrorModel {
+    n_qubits: u64,
+    rng: Pcg64Mcg,
+    simulator: Simulator,
+    error_params: Params,
+    confusion: Confusion,
+    // Whether each qubit is allocated, or has been used if the runtime does
+    // not send allocation notifications.
+    live: Vec<bool>,
+    stats: Stats,
+}
+
+impl CorrelatedReadoutErrorModel {
+    fn new(params: Params, n_qubits: u64, simulator: Simulator) -> Result<Self> {
+        params.validate()?;
+        let confusion = match &params.confusion_file {
+            Some(path) => Confusion::from_file(path, n_qubits)?,
+            None => Confusion::default(),
+        };
+        Ok(Self {
+            n_qubits,
+            rng: Pcg64Mcg::seed_from_u64(0),
+            simulator,
+            error_params: params,
+            confusion,
+            live: vec![false; n_qubits as usize],
+            stats: Stats::default(),
+        })
+    }
+    fn touch(&mut self, qubit: u64) -> Result<()> {
+        if qubit >= self.n_qubits {
+            bail!(
+                "Qubit ID {} is out of bounds for this error model with {} qubits",
+                qubit,
+                self.n_qubits
+            );
+        }
+        self.live[qubit as usize] = true;
+        Ok(())
+    }
+    fn assign_results(
+        &mut self,
+        pending: &[PendingMeasurement],
+        results: &mut BatchResult,
+    ) -> Result<()> {
+        let measured: Vec<(u64, bool)> = pending.iter().map(|m| (m.qubit, m.outcome)).collect();
+        let reported = self.confusion.assign(&measured, &mut self.rng)?;
+        let mut errors = 0;
+        for (measurement, reported) in pending.iter().zip(reported) {
+            if reported != measurement.outcome {
+                errors += 1;
+            }
+            if measurement.leaked {
+                results.set_u64_result(measurement.result_id, reported as u64);
+            } else {
+                results.set_bool_result(measurement.result_id, reported);
+            }
+        }
+        self.stats.measurements += pending.len() as u64;
+        self.stats.assignment_errors += errors;
+        if errors > 1 {
+            self.stats.correlated_assignment_errors += 1;
+        }
+        Ok(())
+    }
+    fn dephase_spectators(&mut self, measured_qubits: &[u64]) -> Result<()> {
+        let p_dephase = self
+            .error_params
+            .spectator_dephasing_probability(measured_qubits.len());
+        if p_dephase == 0.0 {
+            return Ok(());
+        }
+        for qubit in 0..self.n_qubits {
+            if self.live[qubit as usize]
+                && !measured_qubits.contains(&qubit)
+                && self.rng.random_bool(p_dephase)
+            {
+                self.stats.spectator_dephasing_faults += 1;
+                self.simulator.rz(qubit, std::f64::consts::PI)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ErrorModelInterface for CorrelatedReadoutErrorModel {
+    fn shot_start(&mut self, shot_id: u64, seed: u64, simulator_seed: u64) -> Result<()> {
+        self.rng = Pcg64Mcg::seed_from_u64(seed);
+        self.simulator.shot_start(shot_id, simulator_seed)?;
+        self.live.fill(false);
+        self.stats = Stats::default();
+        Ok(())
+    }
+    fn plan_shots(
+        &mut self,
+        shot_ids: &[u64],
+        _seeds: &[u64],
+        simulator_seeds: &[u64],
+    ) -> Result<()> {
+        self.simulator.plan_shots(shot_ids, simulator_seeds)
+    }
+    fn shot_end(&mut self) -> Result<()> {
+        self.simulator.shot_end()?;
+        Ok(())
+    }
+
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn handle_operations(&mut self, operations: BatchOperation) -> Result<BatchResult> {
+        let mut results = BatchResult::default();
+        // Measurements are made as they arrive, but their outcomes are
+        // assigned together once the whole batch has been seen. Leaked
+        // outcomes are reported as they are.
+        let mut pending = Vec::new();
+        let mut measured_qubits = Vec::new();
+        for op in operations {
+            match op {
+                Operation::RXYGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                } => {
+                    self.touch(qubit_id)?;
+                    self.simulator.rxy(qubit_id, theta, phi)?;
+                }
+                Operation::RZGate { qubit_id, theta } => {
+                    self.touch(qubit_id)?;
+                    self.simulator.rz(qubit_id, theta)?;
+                }
+                Operation::RZZGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    theta,
+                } => {
+                    self.touch(qubit_id_1)?;
+                    self.touch(qubit_id_2)?;
+                    self.simulator.rzz(qubit_id_1, qubit_id_2, theta)?;
+                }
+                Operation::UGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                    lambda,
+                } => {
+                    self.touch(qubit_id)?;
+                    self.simulator.u(qubit_id, theta, phi, lambda)?;
+                }
+                Operation::TwoQubitUnitaryGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    matrix,
+                } => {
+                    self.touch(qubit_id_1)?;
+                    self.touch(qubit_id_2)?;
+                    self.simulator
+                        .two_qubit_unitary(qubit_id_1, qubit_id_2, &matrix)?;
+                }
+                Operation::DiagonalPhaseGate { qubit_ids, phases } => {
+                    for &qubit_id in qubit_ids.iter() {
+                        self.touch(qubit_id)?;
+                    }
+                    self.simulator.diagonal_phase(&qubit_ids, &phases)?;
+                }
+                Operation::Measure {
+                    qubit_id,
+                    result_id,
+                } => {
+                    self.touch(qubit_id)?;
+                    measured_qubits.push(qubit_id);
+                    pending.push(PendingMeasurement {
+                        qubit: qubit_id,
+                        result_id,
+                        outcome: self.simulator.measure(qubit_id)?,
+                        leaked: false,
+                    });
+                }
+                Operation::MeasureLeaked {
+                    qubit_id,
+                    result_id,
+                } => {
+                    self.touch(qubit_id)?;
+                    measured_qubits.push(qubit_id);
+                    match self.simulator.measure_leaked(qubit_id)? {
+                        outcome @ (0 | 1) => pending.push(PendingMeasurement {
+                            qubit: qubit_id,
+                            result_id,
+                            outcome: outcome == 1,
+                            leaked: true,
+                        }),
+                        outcome => results.set_u64_result(result_id, outcome),
+                    }
+                }
+                Operation::Reset { qubit_id } => {
+                    self.touch(qubit_id)?;
+                    self.simulator.reset(qubit_id)?;
+                }
+                Operation::Custom { .. } => {
+                    // Passively ignore custom operations
+                }
+                Operation::QAlloc { qubit_id } => {
+                    self.touch(qubit_id)?;
+                    self.simulator.qalloc(qubit_id)?;
+                }
+                Operation::QFree { qubit_id } => {
+                    self.simulator.qfree(qubit_id)?;
+                    if qubit_id < self.n_qubits {
+                        self.live[qubit_id as usize] = false;
+                    }
+                }
+            }
+        }
+        if !measured_qubits.is_empty() {
+            self.assign_results(&pending, &mut results)?;
+            self.dephase_spectators(&measured_qubits)?;
+        }
+        Ok(results)
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "measurements".to_string(),
+                MetricValue::U64(self.stats.measurements),
+            ))),
+            1 => Ok(Some((
+                "assignment_errors".to_string(),
+                MetricValue::U64(self.stats.assignment_errors),
+            ))),
+            2 => Ok(Some((
+                "correlated_assignment_errors".to_string(),
+                MetricValue::U64(self.stats.correlated_assignment_errors),
+            ))),
+            3 => Ok(Some((
+                "spectator_dephasing_faults".to_string(),
+                MetricValue::U64(self.stats.spectator_dephasing_faults),
+            ))),
+            _ => Ok(None),
+        }
+    }
+    fn get_simulator_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        self.simulator.get_metric(nth_metric)
+    }
+}
+
+#[derive(Default)]
+pub struct CorrelatedReadoutErrorModelFactory;
+
+impl ErrorModelInterfaceFactory for CorrelatedReadoutErrorModelFactory {
+    type Interface = CorrelatedReadoutErrorModel;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        error_model_args: &[impl AsRef<str>],
+        simulator_path: &impl AsRef<OsStr>,
+        simulator_args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        match Params::try_parse_from(error_model_args.iter().map(|s| s.as_ref())) {
+            Err(e) => Err(anyhow!(
+                "Error parsing arguments to correlated readout error model plugin: {}",
+                e
+            )),
+            Ok(params) => {
+                let simulator =
+                    Simulator::load_from_file(simulator_path, n_qubits, simulator_args)?;
+                Ok(Box::new(CorrelatedReadoutErrorModel::new(
+                    params, n_qubits, simulator,
+                )?))
+            }
+        }
+    }
+}
+
+export_error_model_plugin!(crate::CorrelatedReadoutErrorModelFactory);
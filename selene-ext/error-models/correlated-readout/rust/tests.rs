@@ -0,0 +1,224 @@
+use crate::confusion::{Confusion, Readout};
+use crate::{CorrelatedReadoutErrorModel, Params};
+use approx::assert_relative_eq;
+use clap::Parser;
+use rand::SeedableRng;
+use rand_pcg::Pcg64Mcg;
+use selene_core::runtime::Operation;
+use selene_core::simulator::recording::{
+    RecordedOperation, Recording, measure_leaked, recorded_error_model, run_batch, rxy,
+};
+
+// Qubits 4 and 5 always swap 01 and 10, qubit 2 always flips, and qubits 0
+// and 1 always flip together.
+const CONFUSION: &str = r#"
+default: { p1_given_0: 0.0, p0_given_1: 0.0 }
+qubits:
+  - { qubit: 2, p1_given_0: 1.0, p0_given_1: 1.0 }
+  - { qubit: 3, p0_given_1: 0.25 }
+pairs:
+  - { qubits: [1, 0], p_joint_flip: 1.0 }
+blocks:
+  - qubits: [4, 5]
+    matrix:
+      - [1, 0, 0, 0]
+      - [0, 0, 1, 0]
+      - [0, 1, 0, 0]
+      - [0, 0, 0, 1]
+"#;
+
+#[test]
+fn assignment_is_correlated_within_a_batch() {
+    let confusion = Confusion::parse(CONFUSION, 7).unwrap();
+    let mut rng = Pcg64Mcg::seed_from_u64(0);
+
+    assert_eq!(
+        *confusion.readout(3),
+        Readout {
+            p1_given_0: 0.0,
+            p0_given_1: 0.25
+        }
+    );
+
+    // The whole block is measured, so its matrix is used.
+    let measured = [(4, true), (5, false), (2, false), (6, true)];
+    assert_eq!(
+        confusion.assign(&measured, &mut rng).unwrap(),
+        vec![false, true, true, true]
+    );
+
+    // The pair flips together, but only when both are measured.
+    let measured = [(0, false), (1, true)];
+    assert_eq!(
+        confusion.assign(&measured, &mut rng).unwrap(),
+        vec![true, false]
+    );
+    assert_eq!(
+        confusion.assign(&[(0, false)], &mut rng).unwrap(),
+        vec![false]
+    );
+
+    // Part of a block falls back to the per-qubit readout error.
+    assert_eq!(
+        confusion.assign(&[(4, true)], &mut rng).unwrap(),
+        vec![true]
+    );
+
+    // A qubit's measurements can't be told apart within a batch.
+    assert!(
+        confusion
+            .assign(&[(4, true), (5, false), (4, true)], &mut rng)
+            .is_err()
+    );
+}
+
+#[test]
+fn invalid_confusion_files_are_rejected() {
+    // Rows must sum to one.
+    assert!(
+        Confusion::parse("blocks: [{ qubits: [0], matrix: [[0.5, 0.4], [0, 1]] }]", 4).is_err()
+    );
+    // Matrices must match the number of qubits.
+    assert!(Confusion::parse("blocks: [{ qubits: [0, 1], matrix: [[1, 0], [0, 1]] }]", 4).is_err());
+    // Qubits may only be in one block.
+    assert!(
+        Confusion::parse("blocks: [{ qubits: [0], matrix: [[1, 0], [0, 1]] }, { qubits: [0], matrix: [[1, 0], [0, 1]] }]", 4)
+        .is_err()
+    );
+    assert!(
+        Confusion::parse(
+            "pairs: [{ qubits: [0, 1], p_joint_flip: 0.1 }, { qubits: [1, 0], p_joint_flip: 0.1 }]",
+            4
+        )
+        .is_err()
+    );
+    assert!(Confusion::parse("pairs: [{ qubits: [2, 2], p_joint_flip: 0.1 }]", 4).is_err());
+    assert!(Confusion::parse("default: { p1_given_0: 1.5 }", 4).is_err());
+    // Qubits must be on the device.
+    assert!(Confusion::parse("qubits: [{ qubit: 4, p1_given_0: 0.1 }]", 4).is_err());
+    assert!(Confusion::parse("pairs: [{ qubits: [3, 4], p_joint_flip: 0.1 }]", 4).is_err());
+    assert!(Confusion::parse("blocks: [{ qubits: [4], matrix: [[1, 0], [0, 1]] }]", 4).is_err());
+    assert!(Confusion::parse("unknown: 1", 4).is_err());
+}
+
+#[test]
+fn spectator_dephasing_compounds_per_measurement() {
+    let params = Params::try_parse_from(["", "--spectator-dephasing=0.1"]).unwrap();
+    params.validate().unwrap();
+    assert_eq!(params.spectator_dephasing_probability(0), 0.0);
+    assert_relative_eq!(params.spectator_dephasing_probability(1), 0.1);
+    assert_relative_eq!(params.spectator_dephasing_probability(2), 0.19);
+    assert!(
+        Params::try_parse_from(["", "--spectator-dephasing=2"])
+            .unwrap()
+            .validate()
+            .is_err()
+    );
+}
+
+/// A correlated readout error model over seven qubits with the confusion
+/// above, after the start of a shot.
+fn confusion_error_model(args: &[&str]) -> (CorrelatedReadoutErrorModel, Recording) {
+    let params = Params::try_parse_from(std::iter::once("").chain(args.iter().copied())).unwrap();
+    let (mut error_model, recording) = recorded_error_model(7, false, |simulator| {
+        CorrelatedReadoutErrorModel::new(params, 7, simulator)
+    })
+    .unwrap();
+    error_model.confusion = Confusion::parse(CONFUSION, 7).unwrap();
+    (error_model, recording)
+}
+
+/// An X rotation, which flips the qubit's bit in the recording simulator.
+fn flip(qubit_id: u64) -> Operation {
+    Operation::RXYGate {
+        qubit_id,
+        theta: std::f64::consts::PI,
+        phi: 0.0,
+    }
+}
+
+fn measure(qubit_id: u64) -> Operation {
+    Operation::Measure {
+        qubit_id,
+        result_id: qubit_id,
+    }
+}
+
+#[test]
+fn blocks_and_pairs_apply_across_a_batch() {
+    let (mut error_model, _) = confusion_error_model(&[]);
+    run_batch(&mut error_model, vec![flip(0), flip(4)]).unwrap();
+    // Qubits 0 and 1 flip together, and qubits 4 and 5 have 10 swapped to
+    // 01. Qubit 3 is error-free when measured as 0.
+    let results = run_batch(
+        &mut error_model,
+        vec![measure(0), measure(1), measure(3), measure(4), measure(5)],
+    )
+    .unwrap();
+    let reported: Vec<(u64, bool)> = results
+        .bool_results
+        .iter()
+        .map(|r| (r.result_id, r.value))
+        .collect();
+    assert_eq!(
+        reported,
+        vec![(0, false), (1, true), (3, false), (4, false), (5, true)]
+    );
+    assert_eq!(error_model.stats.assignment_errors, 4);
+    assert_eq!(error_model.stats.correlated_assignment_errors, 1);
+
+    // Measured in separate batches, neither the block nor the pair applies.
+    let results = run_batch(&mut error_model, vec![measure(4)]).unwrap();
+    assert!(results.bool_results[0].value);
+    let results = run_batch(&mut error_model, vec![measure(0)]).unwrap();
+    assert!(results.bool_results[0].value);
+
+    assert!(run_batch(&mut error_model, vec![measure(4), measure(4)]).is_err());
+}
+
+#[test]
+fn leaked_outcomes_are_reported_unchanged() {
+    let (mut error_model, recording) = confusion_error_model(&[]);
+    // Qubit 2 always flips when it is in the computational basis.
+    assert_eq!(measure_leaked(&mut error_model, 2).unwrap(), 1);
+    recording.leak(2);
+    assert_eq!(measure_leaked(&mut error_model, 2).unwrap(), 2);
+    assert_eq!(error_model.stats.measurements, 1);
+}
+
+#[test]
+fn spectator_dephasing_only_applies_to_live_unmeasured_qubits() {
+    let (mut error_model, recording) = confusion_error_model(&["--spectator-dephasing=1"]);
+    run_batch(
+        &mut error_model,
+        vec![
+            Operation::QAlloc { qubit_id: 0 },
+            Operation::QAlloc { qubit_id: 1 },
+            Operation::QAlloc { qubit_id: 3 },
+            Operation::QAlloc { qubit_id: 6 },
+            Operation::QFree { qubit_id: 6 },
+        ],
+    )
+    .unwrap();
+    recording.take();
+    run_batch(&mut error_model, vec![measure(0), measure(1)]).unwrap();
+    let dephased: Vec<u64> = recording
+        .take()
+        .into_iter()
+        .filter_map(|op| match op {
+            RecordedOperation::Rz { qubit, theta } if theta == std::f64::consts::PI => Some(qubit),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(dephased, vec![3]);
+    assert_eq!(error_model.stats.spectator_dephasing_faults, 1);
+
+    // Batches without measurements do not dephase.
+    run_batch(&mut error_model, vec![rxy(3)]).unwrap();
+    assert!(
+        !recording
+            .take()
+            .iter()
+            .any(|op| matches!(op, RecordedOperation::Rz { .. }))
+    );
+}
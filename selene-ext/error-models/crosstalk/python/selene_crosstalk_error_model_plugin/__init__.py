@@ -0,0 +1,3 @@
+from .plugin import CrosstalkPlugin
+
+__all__ = ["CrosstalkPlugin"]
@@ -0,0 +1,68 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+
+from selene_core import ErrorModel
+
+
+@dataclass
+class CrosstalkPlugin(ErrorModel):
+    """
+    A plugin for simulating crosstalk onto spectator qubits. Qubits driven by
+    different operations in the same batch crosstalk with each other, and
+    qubits adjacent to a driven qubit in the adjacency graph crosstalk as
+    spectators. Batches only contain parallel operations if the runtime groups
+    them, as the SoftRZ runtime does.
+    Attributes:
+        zz_angle (float): The angle of the ZZ rotation applied to each
+            crosstalking pair of qubits.
+        p_pauli (float): The probability of a random Pauli fault on each qubit
+            affected by crosstalk. Must be between 0 and 1 (inclusive).
+        adjacency (list[tuple[int, int]] | None): The edges of the adjacency
+            graph. If None, all qubits in a batch crosstalk with each other and
+            there are no neighbouring spectators.
+        batch_crosstalk (bool): Whether qubits driven by the same batch
+            crosstalk with each other.
+        measurement_crosstalk (bool): Whether measurements and resets cause
+            crosstalk, as well as gates.
+    """
+
+    zz_angle: float = 0.0
+    p_pauli: float = 0.0
+    adjacency: list[tuple[int, int]] | None = None
+    batch_crosstalk: bool = True
+    measurement_crosstalk: bool = False
+
+    def __post_init__(self):
+        assert 0 <= self.p_pauli <= 1, (
+            f"p_pauli ({self.p_pauli}) must be between 0 and 1 (both inclusive)"
+        )
+        for a, b in self.adjacency or []:
+            assert a != b, f"adjacency edge ({a}, {b}) must join two different qubits"
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_crosstalk_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_crosstalk_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_crosstalk_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    def get_init_args(self):
+        args = [
+            f"--zz-angle={self.zz_angle}",
+            f"--p-pauli={self.p_pauli}",
+        ]
+        if self.adjacency is not None:
+            edges = ",".join(f"{a}-{b}" for a, b in self.adjacency)
+            args.append(f"--adjacency={edges}")
+        if not self.batch_crosstalk:
+            args.append("--no-batch-crosstalk")
+        if self.measurement_crosstalk:
+            args.append("--measurement-crosstalk")
+        return args
@@ -0,0 +1,447 @@
+/// An error model that applies crosstalk to spectator qubits, using the
+/// runtime's grouping of parallel operations into batches.
+//
+// Crosstalk arises between qubits in two ways:
+//
+// - Qubits driven by different operations in the same batch interfere with
+//   each other. If an adjacency graph is given, only adjacent pairs do.
+// - Qubits that are adjacent to a driven qubit, but are not driven by the
+//   batch themselves, pick up crosstalk as spectators.
+//
+// For each crosstalking pair, a ZZ rotation of --zz-angle is applied between
+// the two qubits, and a uniformly random Pauli fault is applied with
+// probability --p-pauli to each affected qubit (both qubits for pairs within
+// a batch, the spectator only for neighbours). Crosstalk is applied before
+// the batch's operations, as errors are in the depolarizing model.
+//
+// Batches only contain parallel operations when the runtime groups them, as
+// the soft_rz runtime does. With runtimes that emit one operation per batch,
+// only neighbour crosstalk has any effect.
+//
+// Spectators only pick up crosstalk while they are allocated, as tracked from
+// the runtime's allocation notifications. If the runtime does not send them,
+// every qubit is treated as allocated.
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use selene_core::error_model::interface::ErrorModelInterfaceFactory;
+use selene_core::error_model::{BatchResult, ErrorModelInterface};
+use selene_core::export_error_model_plugin;
+use selene_core::runtime::{BatchOperation, Operation};
+use selene_core::simulator::{Simulator, SimulatorInterface};
+use selene_core::utils::MetricValue;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// The angle of the ZZ rotation applied to each crosstalking pair
+    #[arg(long, default_value_t = 0.0)]
+    zz_angle: f64,
+    /// The probability of a Pauli fault on each qubit affected by crosstalk
+    #[arg(long, default_value_t = 0.0)]
+    p_pauli: f64,
+    /// Comma-separated edges of the adjacency graph, e.g. "0-1,1-2,2-3"
+    #[arg(long)]
+    adjacency: Option<String>,
+    /// Disable crosstalk between qubits driven by the same batch
+    #[arg(long)]
+    no_batch_crosstalk: bool,
+    /// Treat measurements and resets as sources of crosstalk, as well as gates
+    #[arg(long)]
+    measurement_crosstalk: bool,
+}
+
+type Adjacency = BTreeMap<u64, BTreeSet<u64>>;
+
+fn parse_adjacency(edges: &str, n_qubits: u64) -> Result<Adjacency> {
+    let mut adjacency = Adjacency::new();
+    for edge in edges.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parsed = edge.split_once('-').and_then(|(a, b)| {
+            Some((a.trim().parse::<u64>().ok()?, b.trim().parse::<u64>().ok()?))
+        });
+        let Some((a, b)) = parsed else {
+            bail!("Invalid adjacency edge '{edge}': expected two qubit indices joined by '-'");
+        };
+        if a == b || a >= n_qubits || b >= n_qubits {
+            bail!(
+                "Invalid adjacency edge '{edge}': qubits must be distinct and less than the number of qubits ({n_qubits})"
+            );
+        }
+        adjacency.entry(a).or_default().insert(b);
+        adjacency.entry(b).or_default().insert(a);
+    }
+    Ok(adjacency)
+}
+
+/// The pairs of qubits that crosstalk in a batch, in a deterministic order.
+#[derive(Debug, Default)]
+struct CrosstalkPairs {
+    /// Pairs of qubits driven by different operations in the batch.
+    batch: Vec<(u64, u64)>,
+    /// (driven, spectator) pairs for neighbours that the batch does not drive.
+    neighbours: Vec<(u64, u64)>,
+}
+
+/// Find the crosstalking pairs in a batch, given the qubits driven by each of
+/// its operations.
+fn crosstalk_pairs(
+    sources: &[Vec<u64>],
+    adjacency: Option<&Adjacency>,
+    batch_crosstalk: bool,
+) -> CrosstalkPairs {
+    let adjacent =
+        |a: u64, b: u64| adjacency.is_none_or(|adj| adj.get(&a).is_some_and(|n| n.contains(&b)));
+
+    let mut batch_pairs = BTreeSet::new();
+    if batch_crosstalk {
+        for (i, first) in sources.iter().enumerate() {
+            for second in &sources[i + 1..] {
+                for &a in first {
+                    for &b in second {
+                        if a != b && adjacent(a, b) {
+                            batch_pairs.insert((a.min(b), a.max(b)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut neighbour_pairs = Vec::new();
+    if let Some(adjacency) = adjacency {
+        let driven: BTreeSet<u64> = sources.iter().flatten().copied().collect();
+        for &qubit in &driven {
+            for &neighbour in adjacency.get(&qubit).into_iter().flatten() {
+                if !driven.contains(&neighbour) {
+                    neighbour_pairs.push((qubit, neighbour));
+                }
+            }
+        }
+    }
+    CrosstalkPairs {
+        batch: batch_pairs.into_iter().collect(),
+        neighbours: neighbour_pairs,
+    }
+}
+
+#[derive(Default)]
+struct Stats {
+    batch_pairs: u64,
+    neighbour_pairs: u64,
+    faults_x: u64,
+    faults_y: u64,
+    faults_z: u64,
+}
+
+pub struct CrosstalkErrorModel {
+    n_qubits: u64,
+    rng: Pcg64Mcg,
+    simulator: Simulator,
+    error_params: Params,
+    adjacency: Option<Adjacency>,
+    // Whether the runtime has sent allocation notifications this shot, and
+    // if so, which qubits are allocated.
+    tracking_allocations: bool,
+    live: Vec<bool>,
+    stats: Stats,
+}
+
+impl CrosstalkErrorModel {
+    fn new(params: Params, n_qubits: u64, simulator: Simulator) -> Result<Self> {
+        if !(0.0..=1.0).contains(&params.p_pauli) {
+            bail!(
+                "p_pauli ({}) must be between 0 and 1 (both inclusive)",
+                params.p_pauli
+            );
+        }
+        let adjacency = params
+            .adjacency
+            .as_deref()
+            .map(|edges| parse_adjacency(edges, n_qubits))
+            .transpose()?;
+        Ok(Self {
+            n_qubits,
+            rng: Pcg64Mcg::seed_from_u64(0),
+            simulator,
+            error_params: params,
+            adjacency,
+            tracking_allocations: false,
+            live: vec![false; n_qubits as usize],
+            stats: Stats::default(),
+        })
+    }
+    fn is_live(&self, qubit: u64) -> bool {
+        !self.tracking_allocations || self.live[qubit as usize]
+    }
+    fn set_live(&mut self, qubit: u64, live: bool) -> Result<()> {
+        self.validate_qubit(qubit)?;
+        self.tracking_allocations = true;
+        self.live[qubit as usize] = live;
+        Ok(())
+    }
+    // The qubits through which an operation can cause crosstalk, if any.
+    fn source_qubits(&self, op: &Operation) -> Option<Vec<u64>> {
+        match op {
+            Operation::RXYGate { qubit_id, .. }
+            | Operation::RZGate { qubit_id, .. }
+            | Operation::UGate { qubit_id, .. } => Some(vec![*qubit_id]),
+            Operation::RZZGate {
+                qubit_id_1,
+                qubit_id_2,
+                ..
+            }
+            | Operation::TwoQubitUnitaryGate {
+                qubit_id_1,
+                qubit_id_2,
+                ..
+            } => Some(vec![*qubit_id_1, *qubit_id_2]),
+            Operation::DiagonalPhaseGate { qubit_ids, .. } => Some(qubit_ids.to_vec()),
+            Operation::Measure { qubit_id, .. }
+            | Operation::MeasureLeaked { qubit_id, .. }
+            | Operation::Reset { qubit_id }
+                if self.error_params.measurement_crosstalk =>
+            {
+                Some(vec![*qubit_id])
+            }
+            _ => None,
+        }
+    }
+    fn validate_qubit(&self, qubit: u64) -> Result<()> {
+        if qubit >= self.n_qubits {
+            return Err(anyhow!(
+                "Error: qubit {} must be less than the number of qubits ({}).",
+                qubit,
+                self.n_qubits
+            ));
+        }
+        Ok(())
+    }
+    fn maybe_apply_pauli(&mut self, qubit: u64) -> Result<()> {
+        let random_float = self.rng.random::<f64>();
+        if random_float >= self.error_params.p_pauli {
+            return Ok(());
+        }
+        match (random_float * 3.0 / self.error_params.p_pauli) as u64 {
+            0 => {
+                self.stats.faults_x += 1;
+                self.simulator.rxy(qubit, std::f64::consts::PI, 0.0)?;
+            }
+            1 => {
+                self.stats.faults_y += 1;
+                self.simulator
+                    .rxy(qubit, std::f64::consts::PI, std::f64::consts::PI / 2.0)?;
+            }
+            _ => {
+                self.stats.faults_z += 1;
+                self.simulator.rz(qubit, std::f64::consts::PI)?;
+            }
+        }
+        Ok(())
+    }
+    fn apply_crosstalk(&mut self, operations: &BatchOperation) -> Result<()> {
+        let sources: Vec<Vec<u64>> = operations
+            .iter_ops()
+            .filter_map(|op| self.source_qubits(op))
+            .collect();
+        for &qubit in sources.iter().flatten() {
+            self.validate_qubit(qubit)?;
+        }
+        let mut pairs = crosstalk_pairs(
+            &sources,
+            self.adjacency.as_ref(),
+            !self.error_params.no_batch_crosstalk,
+        );
+        pairs
+            .neighbours
+            .retain(|&(_, spectator)| self.is_live(spectator));
+        for (q0, q1) in pairs.batch {
+            self.stats.batch_pairs += 1;
+            if self.error_params.zz_angle != 0.0 {
+                self.simulator.rzz(q0, q1, self.error_params.zz_angle)?;
+            }
+            self.maybe_apply_pauli(q0)?;
+            self.maybe_apply_pauli(q1)?;
+        }
+        for (driven, spectator) in pairs.neighbours {
+            self.stats.neighbour_pairs += 1;
+            if self.error_params.zz_angle != 0.0 {
+                self.simulator
+                    .rzz(driven, spectator, self.error_params.zz_angle)?;
+            }
+            self.maybe_apply_pauli(spectator)?;
+        }
+        Ok(())
+    }
+}
+
+impl ErrorModelInterface for CrosstalkErrorModel {
+    fn shot_start(&mut self, shot_id: u64, seed: u64, simulator_seed: u64) -> Result<()> {
+        self.rng = Pcg64Mcg::seed_from_u64(seed);
+        self.simulator.shot_start(shot_id, simulator_seed)?;
+        self.tracking_allocations = false;
+        self.live.fill(false);
+        self.stats = Stats::default();
+        Ok(())
+    }
+    fn plan_shots(
+        &mut self,
+        shot_ids: &[u64],
+        _seeds: &[u64],
+        simulator_seeds: &[u64],
+    ) -> Result<()> {
+        self.simulator.plan_shots(shot_ids, simulator_seeds)
+    }
+    fn shot_end(&mut self) -> Result<()> {
+        self.simulator.shot_end()?;
+        Ok(())
+    }
+
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn handle_operations(&mut self, operations: BatchOperation) -> Result<BatchResult> {
+        // Qubits allocated in this batch are allocated before its operations
+        // run, so they are already spectators. Frees follow the operations.
+        for op in operations.iter_ops() {
+            if let Operation::QAlloc { qubit_id } = op {
+                self.set_live(*qubit_id, true)?;
+            }
+        }
+        self.apply_crosstalk(&operations)?;
+        let mut results = BatchResult::default();
+        for op in operations {
+            match op {
+                Operation::RXYGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                } => {
+                    self.simulator.rxy(qubit_id, theta, phi)?;
+                }
+                Operation::RZZGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    theta,
+                } => {
+                    self.simulator.rzz(qubit_id_1, qubit_id_2, theta)?;
+                }
+                Operation::RZGate { qubit_id, theta } => {
+                    self.simulator.rz(qubit_id, theta)?;
+                }
+                Operation::UGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                    lambda,
+                } => {
+                    self.simulator.u(qubit_id, theta, phi, lambda)?;
+                }
+                Operation::TwoQubitUnitaryGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    matrix,
+                } => {
+                    self.simulator
+                        .two_qubit_unitary(qubit_id_1, qubit_id_2, &matrix)?;
+                }
+                Operation::DiagonalPhaseGate { qubit_ids, phases } => {
+                    self.simulator.diagonal_phase(&qubit_ids, &phases)?;
+                }
+                Operation::Measure {
+                    qubit_id,
+                    result_id,
+                } => {
+                    let measurement = self.simulator.measure(qubit_id)?;
+                    results.set_bool_result(result_id, measurement);
+                }
+                Operation::MeasureLeaked {
+                    qubit_id,
+                    result_id,
+                } => {
+                    let measurement = self.simulator.measure_leaked(qubit_id)?;
+                    results.set_u64_result(result_id, measurement);
+                }
+                Operation::Reset { qubit_id } => {
+                    self.simulator.reset(qubit_id)?;
+                }
+                Operation::Custom { .. } => {
+                    // Passively ignore custom operations
+                }
+                Operation::QAlloc { qubit_id } => {
+                    self.simulator.qalloc(qubit_id)?;
+                }
+                Operation::QFree { qubit_id } => {
+                    self.simulator.qfree(qubit_id)?;
+                    self.set_live(qubit_id, false)?;
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "batch_crosstalk_pairs".to_string(),
+                MetricValue::U64(self.stats.batch_pairs),
+            ))),
+            1 => Ok(Some((
+                "neighbour_crosstalk_pairs".to_string(),
+                MetricValue::U64(self.stats.neighbour_pairs),
+            ))),
+            2 => Ok(Some((
+                "crosstalk_faults_x".to_string(),
+                MetricValue::U64(self.stats.faults_x),
+            ))),
+            3 => Ok(Some((
+                "crosstalk_faults_y".to_string(),
+                MetricValue::U64(self.stats.faults_y),
+            ))),
+            4 => Ok(Some((
+                "crosstalk_faults_z".to_string(),
+                MetricValue::U64(self.stats.faults_z),
+            ))),
+            _ => Ok(None),
+        }
+    }
+    fn get_simulator_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        self.simulator.get_metric(nth_metric)
+    }
+}
+
+#[derive(Default)]
+pub struct CrosstalkErrorModelFactory;
+
+impl ErrorModelInterfaceFactory for CrosstalkErrorModelFactory {
+    type Interface = CrosstalkErrorModel;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        error_model_args: &[impl AsRef<str>],
+        simulator_path: &impl AsRef<OsStr>,
+        simulator_args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        match Params::try_parse_from(error_model_args.iter().map(|s| s.as_ref())) {
+            Err(e) => Err(anyhow!(
+                "Error parsing arguments to crosstalk error model plugin: {}",
+                e
+            )),
+            Ok(params) => {
+                let simulator =
+                    Simulator::load_from_file(simulator_path, n_qubits, simulator_args)?;
+                Ok(Box::new(CrosstalkErrorModel::new(
+                    params, n_qubits, simulator,
+                )?))
+            }
+        }
+    }
+}
+
+export_error_model_plugin!(crate::CrosstalkErrorModelFactory);
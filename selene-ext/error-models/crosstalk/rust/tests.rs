@@ -0,0 +1,96 @@
+use crate::{CrosstalkErrorModel, Params, crosstalk_pairs, parse_adjacency};
+use clap::Parser;
+use selene_core::error_model::ErrorModelInterface;
+use selene_core::runtime::Operation;
+use selene_core::simulator::recording::{
+    RecordedOperation, Recording, recorded_error_model, run_batch, rxy,
+};
+
+#[test]
+fn batch_pairs_without_adjacency() {
+    let sources = vec![vec![0], vec![2, 3], vec![5]];
+    let pairs = crosstalk_pairs(&sources, None, true);
+    assert_eq!(pairs.batch, vec![(0, 2), (0, 3), (0, 5), (2, 5), (3, 5)]);
+    assert!(pairs.neighbours.is_empty());
+
+    let pairs = crosstalk_pairs(&sources, None, false);
+    assert!(pairs.batch.is_empty());
+}
+
+#[test]
+fn adjacency_restricts_batch_pairs_and_finds_spectators() {
+    // A line of qubits 0 - 1 - 2 - 3 - 4
+    let adjacency = parse_adjacency("0-1, 1-2,2-3,3-4", 5).unwrap();
+    let sources = vec![vec![1], vec![2]];
+    let pairs = crosstalk_pairs(&sources, Some(&adjacency), true);
+    assert_eq!(pairs.batch, vec![(1, 2)]);
+    assert_eq!(pairs.neighbours, vec![(1, 0), (2, 3)]);
+
+    let sources = vec![vec![0], vec![4]];
+    let pairs = crosstalk_pairs(&sources, Some(&adjacency), true);
+    assert!(pairs.batch.is_empty());
+    assert_eq!(pairs.neighbours, vec![(0, 1), (4, 3)]);
+}
+
+#[test]
+fn invalid_adjacency_is_rejected() {
+    assert!(parse_adjacency("0-1,1", 4).is_err());
+    assert!(parse_adjacency("0-0", 4).is_err());
+    assert!(parse_adjacency("0-4", 4).is_err());
+    assert!(parse_adjacency("a-b", 4).is_err());
+    assert!(parse_adjacency("", 4).unwrap().is_empty());
+}
+
+/// A crosstalk error model on a line of three qubits 0 - 1 - 2, applying a ZZ
+/// rotation to each crosstalking pair, after the start of a shot.
+fn line_error_model() -> (CrosstalkErrorModel, Recording) {
+    let params = Params::try_parse_from(["", "--zz-angle=0.1", "--adjacency=0-1,1-2"]).unwrap();
+    recorded_error_model(3, false, |simulator| {
+        CrosstalkErrorModel::new(params, 3, simulator)
+    })
+    .unwrap()
+}
+
+/// Run a batch, returning the crosstalk pairs that received a ZZ rotation.
+fn crosstalk(
+    error_model: &mut CrosstalkErrorModel,
+    recording: &Recording,
+    ops: Vec<Operation>,
+) -> Vec<(u64, u64)> {
+    run_batch(error_model, ops).unwrap();
+    recording
+        .take()
+        .into_iter()
+        .filter_map(|op| match op {
+            RecordedOperation::Rzz {
+                qubit1,
+                qubit2,
+                theta: 0.1,
+            } => Some((qubit1, qubit2)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn only_allocated_spectators_pick_up_crosstalk() {
+    let (mut error_model, recording) = line_error_model();
+    let ops = vec![
+        Operation::QAlloc { qubit_id: 0 },
+        Operation::QAlloc { qubit_id: 1 },
+        rxy(1),
+    ];
+    assert_eq!(crosstalk(&mut error_model, &recording, ops), vec![(1, 0)]);
+
+    let ops = vec![rxy(0), Operation::QFree { qubit_id: 1 }];
+    assert_eq!(crosstalk(&mut error_model, &recording, ops), vec![(0, 1)]);
+    assert!(crosstalk(&mut error_model, &recording, vec![rxy(0)]).is_empty());
+
+    // Allocation is tracked afresh in each shot.
+    error_model.shot_start(1, 0, 0).unwrap();
+    recording.take();
+    assert_eq!(
+        crosstalk(&mut error_model, &recording, vec![rxy(1)]),
+        vec![(1, 0), (1, 2)]
+    );
+}
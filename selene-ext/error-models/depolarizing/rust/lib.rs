@@ -203,6 +203,14 @@ impl ErrorModelInterface for DepolarizingErrorModel {
         self.stats = Stats::default();
         Ok(())
     }
+    fn plan_shots(
+        &mut self,
+        shot_ids: &[u64],
+        _seeds: &[u64],
+        simulator_seeds: &[u64],
+    ) -> Result<()> {
+        self.simulator.plan_shots(shot_ids, simulator_seeds)
+    }
     fn shot_end(&mut self) -> Result<()> {
         self.simulator.shot_end()?;
         Ok(())
@@ -236,6 +244,38 @@ impl ErrorModelInterface for DepolarizingErrorModel {
                     self.maybe_apply_2q_error(qubit_id_1, qubit_id_2)?;
                     self.simulator.rzz(qubit_id_1, qubit_id_2, theta)?;
                 }
+                Operation::UGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                    lambda,
+                } => {
+                    self.maybe_apply_1q_error(qubit_id)?;
+                    self.simulator.u(qubit_id, theta, phi, lambda)?;
+                }
+                Operation::TwoQubitUnitaryGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    matrix,
+                } => {
+                    self.maybe_apply_2q_error(qubit_id_1, qubit_id_2)?;
+                    self.simulator
+                        .two_qubit_unitary(qubit_id_1, qubit_id_2, &matrix)?;
+                }
+                Operation::DiagonalPhaseGate { qubit_ids, phases } => {
+                    // One and two qubit phase gates are treated like RZ and RZZ.
+                    // Wider gates have no native error rate, so each qubit
+                    // receives an independent single-qubit error.
+                    match *qubit_ids {
+                        [q0, q1] => self.maybe_apply_2q_error(q0, q1)?,
+                        _ => {
+                            for &qubit_id in qubit_ids.iter() {
+                                self.maybe_apply_1q_error(qubit_id)?;
+                            }
+                        }
+                    }
+                    self.simulator.diagonal_phase(&qubit_ids, &phases)?;
+                }
                 Operation::Measure {
                     qubit_id,
                     result_id,
@@ -248,11 +288,14 @@ impl ErrorModelInterface for DepolarizingErrorModel {
                     qubit_id,
                     result_id,
                 } => {
-                    // We aren't modelling leakage so this is the same as a normal measurement,
-                    // except we set the u64 future as 0 or 1 (leakage would include higher values)
-                    let measurement = self.simulator.measure(qubit_id)?;
-                    let modified_measurement = self.maybe_flip_measurement(qubit_id, measurement);
-                    results.set_u64_result(result_id, if modified_measurement { 1 } else { 0 });
+                    // We aren't modelling leakage ourselves, but the simulator may be.
+                    // A leaked qubit is reported as 2, and is not subject to the
+                    // measurement flip, which only applies to computational outcomes.
+                    let measurement = match self.simulator.measure_leaked(qubit_id)? {
+                        2 => 2,
+                        outcome => self.maybe_flip_measurement(qubit_id, outcome == 1) as u64,
+                    };
+                    results.set_u64_result(result_id, measurement);
                 }
                 Operation::Reset { qubit_id } => {
                     self.simulator.reset(qubit_id)?;
@@ -261,6 +304,12 @@ impl ErrorModelInterface for DepolarizingErrorModel {
                 Operation::Custom { .. } => {
                     // Passively ignore custom operations
                 }
+                Operation::QAlloc { qubit_id } => {
+                    self.simulator.qalloc(qubit_id)?;
+                }
+                Operation::QFree { qubit_id } => {
+                    self.simulator.qfree(qubit_id)?;
+                }
             }
         }
         Ok(results)
@@ -0,0 +1,3 @@
+from .plugin import ErasurePlugin
+
+__all__ = ["ErasurePlugin"]
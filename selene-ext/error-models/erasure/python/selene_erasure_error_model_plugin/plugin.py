@@ -0,0 +1,58 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+
+from selene_core import ErrorModel
+
+
+@dataclass
+class ErasurePlugin(ErrorModel):
+    """
+    A plugin for simulating heralded erasure errors. After each gate, each
+    qubit it acts on may be erased, leaving it fully depolarised and flagged.
+    Measuring an erased qubit with leakage detection returns 2, so that the
+    user program can react to the erasure, and the flag is cleared when the
+    qubit is reset. The number of erasures in each shot is reported as a
+    metric.
+    Attributes:
+        p_1q (float): The probability that a single-qubit gate erases its
+            qubit. Must be between 0 and 1 (inclusive).
+        p_2q (float): The probability that a two-qubit gate erases each of its
+            qubits. Must be between 0 and 1 (inclusive).
+        exact_channels (bool): Whether to depolarise erased qubits with the
+            simulator's Pauli channel rather than by sampling a Pauli.
+    """
+
+    p_1q: float = 0.0
+    p_2q: float = 0.0
+    exact_channels: bool = False
+
+    def __post_init__(self):
+        assert 0 <= self.p_1q <= 1, (
+            f"p_1q ({self.p_1q}) must be between 0 and 1 (both inclusive)"
+        )
+        assert 0 <= self.p_2q <= 1, (
+            f"p_2q ({self.p_2q}) must be between 0 and 1 (both inclusive)"
+        )
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_erasure_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_erasure_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_erasure_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    def get_init_args(self):
+        args = [
+            f"--p-1q={self.p_1q}",
+            f"--p-2q={self.p_2q}",
+        ]
+        if self.exact_channels:
+            args.append("--exact-channels")
+        return args
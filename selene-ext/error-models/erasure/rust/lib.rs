@@ -0,0 +1,296 @@
+/// An error model in which gates erase qubits, leaving them fully depolarised
+/// and flagged so that the erasure is heralded to the user program.
+//
+// After each gate, every qubit it acts on is erased with the probability for
+// that kind of gate. An erased qubit is depolarised by a uniformly random
+// Pauli, or with the simulator's Pauli channel if --exact-channels is given,
+// and remains flagged until it is reset, allocated or freed. Gates continue
+// to act on erased qubits as normal.
+//
+// The erasure is heralded through MeasureLeaked, which returns 2 for erased
+// qubits, so that a user program can detect and react to it. Ordinary
+// measurements of erased qubits return the outcome of the depolarised state.
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use selene_core::error_model::interface::ErrorModelInterfaceFactory;
+use selene_core::error_model::{BatchResult, ErrorModelInterface};
+use selene_core::export_error_model_plugin;
+use selene_core::runtime::{BatchOperation, Operation};
+use selene_core::simulator::{Pauli, Simulator, SimulatorInterface};
+use selene_core::utils::MetricValue;
+use std::ffi::OsStr;
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// The probability that a single-qubit gate erases its qubit
+    #[arg(long, default_value_t = 0.0)]
+    p_1q: f64,
+    /// The probability that a two-qubit gate erases each of its qubits
+    #[arg(long, default_value_t = 0.0)]
+    p_2q: f64,
+    /// Depolarise erased qubits with the simulator's Pauli channel rather
+    /// than sampling a Pauli
+    #[arg(long)]
+    exact_channels: bool,
+}
+
+impl Params {
+    fn validate(&self) -> Result<()> {
+        for (name, p) in [("p_1q", self.p_1q), ("p_2q", self.p_2q)] {
+            if !(0.0..=1.0).contains(&p) {
+                bail!("{name} ({p}) must be between 0 and 1 (both inclusive)");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct Stats {
+    erasures: u64,
+    erasure_detections: u64,
+    erased_measurements: u64,
+}
+
+pub struct ErasureErrorModel {
+    n_qubits: u64,
+    rng: Pcg64Mcg,
+    simulator: Simulator,
+    erased: Vec<bool>,
+    error_params: Params,
+    stats: Stats,
+}
+
+impl ErasureErrorModel {
+    fn new(params: Params, n_qubits: u64, simulator: Simulator) -> Result<Self> {
+        params.validate()?;
+        Ok(Self {
+            n_qubits,
+            rng: Pcg64Mcg::seed_from_u64(0),
+            simulator,
+            erased: vec![false; n_qubits as usize],
+            error_params: params,
+            stats: Stats::default(),
+        })
+    }
+    fn is_erased(&self, qubit: u64) -> Result<bool> {
+        if qubit >= self.n_qubits {
+            bail!(
+                "Qubit ID {} is out of bounds for this error model with {} qubits",
+                qubit,
+                self.n_qubits
+            );
+        }
+        Ok(self.erased[qubit as usize])
+    }
+    fn clear(&mut self, qubit: u64) -> Result<()> {
+        if self.is_erased(qubit)? {
+            self.erased[qubit as usize] = false;
+        }
+        Ok(())
+    }
+    fn erase(&mut self, qubit: u64) -> Result<()> {
+        self.erased[qubit as usize] = true;
+        self.stats.erasures += 1;
+        if self.error_params.exact_channels {
+            return self.simulator.pauli_channel(qubit, 0.25, 0.25, 0.25);
+        }
+        match Pauli::try_from(self.rng.random_range(0..4u8))? {
+            Pauli::I => {}
+            Pauli::X => self.simulator.rxy(qubit, std::f64::consts::PI, 0.0)?,
+            Pauli::Y => {
+                self.simulator
+                    .rxy(qubit, std::f64::consts::PI, std::f64::consts::PI / 2.0)?;
+            }
+            Pauli::Z => self.simulator.rz(qubit, std::f64::consts::PI)?,
+        }
+        Ok(())
+    }
+    fn maybe_erase(&mut self, qubits: &[u64], p_erasure: f64) -> Result<()> {
+        for &qubit in qubits {
+            if !self.is_erased(qubit)? && self.rng.random_bool(p_erasure) {
+                self.erase(qubit)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ErrorModelInterface for ErasureErrorModel {
+    fn shot_start(&mut self, shot_id: u64, seed: u64, simulator_seed: u64) -> Result<()> {
+        self.rng = Pcg64Mcg::seed_from_u64(seed);
+        self.simulator.shot_start(shot_id, simulator_seed)?;
+        self.erased.fill(false);
+        self.stats = Stats::default();
+        Ok(())
+    }
+    fn plan_shots(
+        &mut self,
+        shot_ids: &[u64],
+        _seeds: &[u64],
+        simulator_seeds: &[u64],
+    ) -> Result<()> {
+        self.simulator.plan_shots(shot_ids, simulator_seeds)
+    }
+    fn shot_end(&mut self) -> Result<()> {
+        self.simulator.shot_end()?;
+        Ok(())
+    }
+
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn handle_operations(&mut self, operations: BatchOperation) -> Result<BatchResult> {
+        let mut results = BatchResult::default();
+        for op in operations {
+            match op {
+                Operation::RXYGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                } => {
+                    self.simulator.rxy(qubit_id, theta, phi)?;
+                    self.maybe_erase(&[qubit_id], self.error_params.p_1q)?;
+                }
+                Operation::RZGate { qubit_id, theta } => {
+                    self.simulator.rz(qubit_id, theta)?;
+                    self.maybe_erase(&[qubit_id], self.error_params.p_1q)?;
+                }
+                Operation::RZZGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    theta,
+                } => {
+                    self.simulator.rzz(qubit_id_1, qubit_id_2, theta)?;
+                    self.maybe_erase(&[qubit_id_1, qubit_id_2], self.error_params.p_2q)?;
+                }
+                Operation::UGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                    lambda,
+                } => {
+                    self.simulator.u(qubit_id, theta, phi, lambda)?;
+                    self.maybe_erase(&[qubit_id], self.error_params.p_1q)?;
+                }
+                Operation::TwoQubitUnitaryGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    matrix,
+                } => {
+                    self.simulator
+                        .two_qubit_unitary(qubit_id_1, qubit_id_2, &matrix)?;
+                    self.maybe_erase(&[qubit_id_1, qubit_id_2], self.error_params.p_2q)?;
+                }
+                Operation::DiagonalPhaseGate { qubit_ids, phases } => {
+                    self.simulator.diagonal_phase(&qubit_ids, &phases)?;
+                    // As in the depolarizing model, two-qubit phase gates are
+                    // treated like RZZ, and other widths like single-qubit gates.
+                    let p_erasure = if qubit_ids.len() == 2 {
+                        self.error_params.p_2q
+                    } else {
+                        self.error_params.p_1q
+                    };
+                    self.maybe_erase(&qubit_ids, p_erasure)?;
+                }
+                Operation::Measure {
+                    qubit_id,
+                    result_id,
+                } => {
+                    if self.is_erased(qubit_id)? {
+                        self.stats.erased_measurements += 1;
+                    }
+                    let measurement = self.simulator.measure(qubit_id)?;
+                    results.set_bool_result(result_id, measurement);
+                }
+                Operation::MeasureLeaked {
+                    qubit_id,
+                    result_id,
+                } => {
+                    let measurement = self.simulator.measure_leaked(qubit_id)?;
+                    let measurement = if self.is_erased(qubit_id)? {
+                        self.stats.erasure_detections += 1;
+                        2
+                    } else {
+                        measurement
+                    };
+                    results.set_u64_result(result_id, measurement);
+                }
+                Operation::Reset { qubit_id } => {
+                    self.simulator.reset(qubit_id)?;
+                    self.clear(qubit_id)?;
+                }
+                Operation::Custom { .. } => {
+                    // Passively ignore custom operations
+                }
+                Operation::QAlloc { qubit_id } => {
+                    self.simulator.qalloc(qubit_id)?;
+                    self.clear(qubit_id)?;
+                }
+                Operation::QFree { qubit_id } => {
+                    self.simulator.qfree(qubit_id)?;
+                    self.clear(qubit_id)?;
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "erasures".to_string(),
+                MetricValue::U64(self.stats.erasures),
+            ))),
+            1 => Ok(Some((
+                "erasure_detections".to_string(),
+                MetricValue::U64(self.stats.erasure_detections),
+            ))),
+            2 => Ok(Some((
+                "erased_measurements".to_string(),
+                MetricValue::U64(self.stats.erased_measurements),
+            ))),
+            _ => Ok(None),
+        }
+    }
+    fn get_simulator_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        self.simulator.get_metric(nth_metric)
+    }
+}
+
+#[derive(Default)]
+pub struct ErasureErrorModelFactory;
+
+impl ErrorModelInterfaceFactory for ErasureErrorModelFactory {
+    type Interface = ErasureErrorModel;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        error_model_args: &[impl AsRef<str>],
+        simulator_path: &impl AsRef<OsStr>,
+        simulator_args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        match Params::try_parse_from(error_model_args.iter().map(|s| s.as_ref())) {
+            Err(e) => Err(anyhow!(
+                "Error parsing arguments to erasure error model plugin: {}",
+                e
+            )),
+            Ok(params) => {
+                let simulator =
+                    Simulator::load_from_file(simulator_path, n_qubits, simulator_args)?;
+                Ok(Box::new(ErasureErrorModel::new(
+                    params, n_qubits, simulator,
+                )?))
+            }
+        }
+    }
+}
+
+export_error_model_plugin!(crate::ErasureErrorModelFactory);
@@ -0,0 +1,151 @@
+use crate::{ErasureErrorModel, Params};
+use anyhow::Result;
+use clap::Parser;
+use selene_core::runtime::Operation;
+use selene_core::simulator::recording::{
+    RecordedOperation, Recording, find_metric, measure_leaked, recorded_error_model, run_batch, rxy,
+};
+use selene_core::utils::MetricValue;
+use std::f64::consts::PI;
+
+/// An erasure error model on a recording simulator, which accepts noise
+/// channels if `channels` is set, after the start of a shot.
+fn erasure(args: &[&str], n_qubits: u64, channels: bool) -> Result<(ErasureErrorModel, Recording)> {
+    let params = Params::try_parse_from(std::iter::once("").chain(args.iter().copied()))?;
+    recorded_error_model(n_qubits, channels, |simulator| {
+        ErasureErrorModel::new(params, n_qubits, simulator)
+    })
+}
+
+fn rzz(qubit_id_1: u64, qubit_id_2: u64) -> Operation {
+    Operation::RZZGate {
+        qubit_id_1,
+        qubit_id_2,
+        theta: 1.0,
+    }
+}
+
+#[test]
+fn erasures_are_heralded_by_measure_leaked() {
+    let (mut error_model, recording) = erasure(&["--p-1q=1"], 2, false).unwrap();
+    run_batch(&mut error_model, vec![rxy(0)]).unwrap();
+    // The gate is applied, followed by a uniformly sampled Pauli.
+    let operations = recording.take();
+    assert_eq!(
+        operations[0],
+        RecordedOperation::Rxy {
+            qubit: 0,
+            theta: 1.0,
+            phi: 0.0
+        }
+    );
+    let paulis = [
+        vec![],
+        vec![RecordedOperation::Rxy {
+            qubit: 0,
+            theta: PI,
+            phi: 0.0,
+        }],
+        vec![RecordedOperation::Rxy {
+            qubit: 0,
+            theta: PI,
+            phi: PI / 2.0,
+        }],
+        vec![RecordedOperation::Rz {
+            qubit: 0,
+            theta: PI,
+        }],
+    ];
+    assert!(paulis.contains(&operations[1..].to_vec()));
+
+    // Erased qubits are not erased again, and gates still act on them.
+    run_batch(&mut error_model, vec![rxy(0)]).unwrap();
+    assert_eq!(recording.take().len(), 1);
+    assert_eq!(measure_leaked(&mut error_model, 0).unwrap(), 2);
+    assert_eq!(measure_leaked(&mut error_model, 1).unwrap(), 0);
+    let measure = Operation::Measure {
+        qubit_id: 0,
+        result_id: 0,
+    };
+    run_batch(&mut error_model, vec![measure]).unwrap();
+    assert_eq!(
+        find_metric(&mut error_model, "erasures").unwrap(),
+        Some(MetricValue::U64(1))
+    );
+    assert_eq!(
+        find_metric(&mut error_model, "erasure_detections").unwrap(),
+        Some(MetricValue::U64(1))
+    );
+    assert_eq!(
+        find_metric(&mut error_model, "erased_measurements").unwrap(),
+        Some(MetricValue::U64(1))
+    );
+}
+
+#[test]
+fn two_qubit_gates_erase_each_qubit() {
+    let (mut error_model, _) = erasure(&["--p-2q=1"], 3, false).unwrap();
+    run_batch(&mut error_model, vec![rxy(2)]).unwrap();
+    run_batch(&mut error_model, vec![rzz(0, 1)]).unwrap();
+    assert_eq!(measure_leaked(&mut error_model, 0).unwrap(), 2);
+    assert_eq!(measure_leaked(&mut error_model, 1).unwrap(), 2);
+    assert_eq!(measure_leaked(&mut error_model, 2).unwrap(), 0);
+    assert_eq!(
+        find_metric(&mut error_model, "erasures").unwrap(),
+        Some(MetricValue::U64(2))
+    );
+}
+
+#[test]
+fn exact_channels_depolarise_erased_qubits() {
+    let (mut error_model, recording) = erasure(&["--p-1q=1", "--exact-channels"], 1, true).unwrap();
+    run_batch(&mut error_model, vec![rxy(0)]).unwrap();
+    assert_eq!(
+        recording.take()[1..],
+        [RecordedOperation::PauliChannel {
+            qubit: 0,
+            p_x: 0.25,
+            p_y: 0.25,
+            p_z: 0.25
+        }]
+    );
+}
+
+#[test]
+fn resets_and_allocations_clear_erasures() {
+    let clears = [
+        Operation::Reset { qubit_id: 0 },
+        Operation::QFree { qubit_id: 0 },
+        Operation::QAlloc { qubit_id: 0 },
+    ];
+    for clear in clears {
+        let (mut error_model, _) = erasure(&["--p-1q=1"], 1, false).unwrap();
+        run_batch(&mut error_model, vec![rxy(0)]).unwrap();
+        run_batch(&mut error_model, vec![clear]).unwrap();
+        assert_ne!(measure_leaked(&mut error_model, 0).unwrap(), 2);
+        assert_eq!(
+            find_metric(&mut error_model, "erasure_detections").unwrap(),
+            Some(MetricValue::U64(0))
+        );
+    }
+}
+
+#[test]
+fn erasure_probabilities_are_validated() {
+    let (mut error_model, _) = erasure(&[], 1, false).unwrap();
+    run_batch(&mut error_model, vec![rxy(0)]).unwrap();
+    assert_eq!(
+        find_metric(&mut error_model, "erasures").unwrap(),
+        Some(MetricValue::U64(0))
+    );
+    assert!(
+        erasure(
+            &["--p-1q=0.001", "--p-2q=0.01", "--exact-channels"],
+            1,
+            true
+        )
+        .is_ok()
+    );
+    assert!(erasure(&["--p-1q=1.5"], 1, false).is_err());
+    assert!(erasure(&["--p-2q=-0.1"], 1, false).is_err());
+}
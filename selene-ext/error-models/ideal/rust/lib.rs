@@ -19,6 +19,14 @@ impl ErrorModelInterface for IdealErrorModel {
         self.simulator.shot_start(shot_id, simulator_seed)?;
         Ok(())
     }
+    fn plan_shots(
+        &mut self,
+        shot_ids: &[u64],
+        _seeds: &[u64],
+        simulator_seeds: &[u64],
+    ) -> Result<()> {
+        self.simulator.plan_shots(shot_ids, simulator_seeds)
+    }
     fn shot_end(&mut self) -> Result<()> {
         self.simulator.shot_end()?;
         Ok(())
@@ -45,6 +53,25 @@ impl ErrorModelInterface for IdealErrorModel {
                 Operation::RZGate { qubit_id, theta } => {
                     self.simulator.rz(qubit_id, theta)?;
                 }
+                Operation::UGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                    lambda,
+                } => {
+                    self.simulator.u(qubit_id, theta, phi, lambda)?;
+                }
+                Operation::TwoQubitUnitaryGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    matrix,
+                } => {
+                    self.simulator
+                        .two_qubit_unitary(qubit_id_1, qubit_id_2, &matrix)?;
+                }
+                Operation::DiagonalPhaseGate { qubit_ids, phases } => {
+                    self.simulator.diagonal_phase(&qubit_ids, &phases)?;
+                }
                 Operation::Measure {
                     qubit_id,
                     result_id,
@@ -56,10 +83,11 @@ impl ErrorModelInterface for IdealErrorModel {
                     qubit_id,
                     result_id,
                 } => {
-                    // In this ideal model, there's no leakage.
-                    // Just do a normal measurement and stick to the [0,1] range
-                    let measurement = self.simulator.measure(qubit_id)?;
-                    results.set_u64_result(result_id, measurement.into());
+                    // This ideal model introduces no leakage of its own, but the
+                    // simulator may model it, in which case it reports 2 for a
+                    // leaked qubit.
+                    let measurement = self.simulator.measure_leaked(qubit_id)?;
+                    results.set_u64_result(result_id, measurement);
                 }
 
                 Operation::Reset { qubit_id } => {
@@ -68,6 +96,12 @@ impl ErrorModelInterface for IdealErrorModel {
                 Operation::Custom { .. } => {
                     // Passively ignore custom operations
                 }
+                Operation::QAlloc { qubit_id } => {
+                    self.simulator.qalloc(qubit_id)?;
+                }
+                Operation::QFree { qubit_id } => {
+                    self.simulator.qfree(qubit_id)?;
+                }
             }
         }
         Ok(results)
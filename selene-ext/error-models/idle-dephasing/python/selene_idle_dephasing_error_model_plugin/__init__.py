@@ -0,0 +1,3 @@
+from .plugin import IdleDephasingPlugin
+
+__all__ = ["IdleDephasingPlugin"]
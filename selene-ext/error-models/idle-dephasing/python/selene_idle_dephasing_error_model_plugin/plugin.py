@@ -0,0 +1,57 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+
+from selene_core import ErrorModel
+
+
+@dataclass
+class IdleDephasingPlugin(ErrorModel):
+    """
+    A plugin for simulating noise on idle qubits. Each qubit's idle time is
+    tracked from the timing of the runtime's operation batches, including
+    delays from barriers and simulated delays, and dephasing (and optionally
+    relaxation) is applied for that time when the qubit is next operated on
+    or measured.
+    Attributes:
+        t2_ns (float): The dephasing time T2, in nanoseconds. Must be positive.
+        t1_ns (float | None): The relaxation time T1, in nanoseconds. If None,
+            qubits do not relax. Otherwise, must be at least t2_ns / 2.
+        exact_channels (bool): If True, apply the simulator's amplitude and
+            phase damping channels rather than sampling Pauli faults. This is
+            exact on mixed state simulators, but requires a simulator that
+            supports these channels.
+    """
+
+    t2_ns: float = float("inf")
+    t1_ns: float | None = None
+    exact_channels: bool = False
+
+    def __post_init__(self):
+        assert self.t2_ns > 0, f"t2_ns ({self.t2_ns}) must be positive"
+        if self.t1_ns is not None:
+            assert self.t1_ns > 0, f"t1_ns ({self.t1_ns}) must be positive"
+            assert self.t2_ns <= 2 * self.t1_ns, (
+                f"t2_ns ({self.t2_ns}) must be at most twice t1_ns ({self.t1_ns})"
+            )
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_idle_dephasing_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_idle_dephasing_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_idle_dephasing_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    def get_init_args(self):
+        args = [f"--t2-ns={self.t2_ns}"]
+        if self.t1_ns is not None:
+            args.append(f"--t1-ns={self.t1_ns}")
+        if self.exact_channels:
+            args.append("--exact-channels")
+        return args
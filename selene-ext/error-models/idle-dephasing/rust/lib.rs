@@ -0,0 +1,331 @@
+/// An error model that dephases, and optionally relaxes, qubits in
+/// proportion to the time they spend idle.
+//
+// Every batch of operations carries a start time and a duration. For each
+// qubit we record when the last batch that touched it ended, and when it is
+// next operated on or measured, the time since then is treated as idle time
+// and noise is applied for it before the operation. Delays from barriers and
+// simulate_delay advance the runtime's clock, so they show up as idle time.
+//
+// Idle time is only counted while a qubit is live, as tracked by
+// selene_core's QubitClocks. Noise accumulated before a reset is discarded
+// along with the state.
+//
+// By default, the idle noise is the Pauli twirl of combined relaxation and
+// dephasing, sampled as X, Y and Z faults so that any simulator can be used.
+// With --exact-channels the simulator's amplitude and phase damping channels
+// are applied instead, which is exact on mixed state simulators.
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use selene_core::error_model::clocks::QubitClocks;
+use selene_core::error_model::interface::ErrorModelInterfaceFactory;
+use selene_core::error_model::{BatchResult, ErrorModelInterface};
+use selene_core::export_error_model_plugin;
+use selene_core::runtime::{BatchOperation, Operation};
+use selene_core::simulator::{Simulator, SimulatorInterface};
+use selene_core::utils::MetricValue;
+use std::ffi::OsStr;
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// The dephasing time T2, in nanoseconds
+    #[arg(long)]
+    t2_ns: f64,
+    /// The relaxation time T1, in nanoseconds. Qubits do not relax if omitted.
+    #[arg(long)]
+    t1_ns: Option<f64>,
+    /// Apply the simulator's damping channels rather than sampling Pauli faults
+    #[arg(long)]
+    exact_channels: bool,
+}
+
+impl Params {
+    fn validate(&self) -> Result<()> {
+        if self.t2_ns.is_nan() || self.t2_ns <= 0.0 {
+            bail!("t2_ns ({}) must be positive", self.t2_ns);
+        }
+        if let Some(t1_ns) = self.t1_ns {
+            if t1_ns.is_nan() || t1_ns <= 0.0 {
+                bail!("t1_ns ({t1_ns}) must be positive");
+            }
+            if self.t2_ns > 2.0 * t1_ns {
+                bail!(
+                    "t2_ns ({}) must be at most twice t1_ns ({t1_ns})",
+                    self.t2_ns
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// The probability that a qubit idling for `idle_ns` has decayed to |0>.
+    fn relaxation_probability(&self, idle_ns: f64) -> f64 {
+        self.t1_ns
+            .map_or(0.0, |t1_ns| 1.0 - (-idle_ns / t1_ns).exp())
+    }
+
+    /// The probabilities of X, Y and Z faults after idling for `idle_ns`,
+    /// under the Pauli twirling approximation.
+    fn pauli_probabilities(&self, idle_ns: f64) -> [f64; 3] {
+        let p_relax = self.relaxation_probability(idle_ns);
+        let p_xy = p_relax / 4.0;
+        let p_z = (1.0 - (-idle_ns / self.t2_ns).exp()) / 2.0 - p_xy;
+        [p_xy, p_xy, p_z.max(0.0)]
+    }
+
+    /// The amplitude damping and phase damping parameters for idling for
+    /// `idle_ns`. Amplitude damping contributes to the decay of coherences,
+    /// so phase damping only makes up the remainder of the T2 decay.
+    fn damping_parameters(&self, idle_ns: f64) -> (f64, f64) {
+        let gamma = self.relaxation_probability(idle_ns);
+        let relaxation_rate = self.t1_ns.map_or(0.0, |t1_ns| 1.0 / t1_ns);
+        let lambda = 1.0 - (-idle_ns * (2.0 / self.t2_ns - relaxation_rate)).exp();
+        (gamma, lambda.max(0.0))
+    }
+}
+
+#[derive(Default)]
+struct Stats {
+    idle_periods: u64,
+    idle_time_ns: u64,
+    faults_x: u64,
+    faults_y: u64,
+    faults_z: u64,
+}
+
+pub struct IdleDephasingErrorModel {
+    rng: Pcg64Mcg,
+    simulator: Simulator,
+    error_params: Params,
+    clocks: QubitClocks,
+    stats: Stats,
+}
+
+impl IdleDephasingErrorModel {
+    fn new(params: Params, n_qubits: u64, simulator: Simulator) -> Result<Self> {
+        params.validate()?;
+        Ok(Self {
+            rng: Pcg64Mcg::seed_from_u64(0),
+            simulator,
+            error_params: params,
+            clocks: QubitClocks::new(n_qubits),
+            stats: Stats::default(),
+        })
+    }
+    // Apply noise for the time the qubit has spent idle before an operation
+    // in the batch running from start_ns to end_ns.
+    fn apply_idle_noise(&mut self, qubit: u64, start_ns: u64, end_ns: u64) -> Result<()> {
+        let Some(idle_ns) = self.clocks.advance(qubit, start_ns, end_ns)? else {
+            return Ok(());
+        };
+        if idle_ns == 0 {
+            return Ok(());
+        }
+        self.stats.idle_periods += 1;
+        self.stats.idle_time_ns += idle_ns;
+        if self.error_params.exact_channels {
+            let (gamma, lambda) = self.error_params.damping_parameters(idle_ns as f64);
+            if gamma > 0.0 {
+                self.simulator.amplitude_damping(qubit, gamma)?;
+            }
+            if lambda > 0.0 {
+                self.simulator.phase_damping(qubit, lambda)?;
+            }
+        } else {
+            let [p_x, p_y, p_z] = self.error_params.pauli_probabilities(idle_ns as f64);
+            let random_float = self.rng.random::<f64>();
+            if random_float < p_x {
+                self.stats.faults_x += 1;
+                self.simulator.rxy(qubit, std::f64::consts::PI, 0.0)?;
+            } else if random_float < p_x + p_y {
+                self.stats.faults_y += 1;
+                self.simulator
+                    .rxy(qubit, std::f64::consts::PI, std::f64::consts::PI / 2.0)?;
+            } else if random_float < p_x + p_y + p_z {
+                self.stats.faults_z += 1;
+                self.simulator.rz(qubit, std::f64::consts::PI)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ErrorModelInterface for IdleDephasingErrorModel {
+    fn shot_start(&mut self, shot_id: u64, seed: u64, simulator_seed: u64) -> Result<()> {
+        self.rng = Pcg64Mcg::seed_from_u64(seed);
+        self.simulator.shot_start(shot_id, simulator_seed)?;
+        self.clocks.stop_all();
+        self.stats = Stats::default();
+        Ok(())
+    }
+    fn plan_shots(
+        &mut self,
+        shot_ids: &[u64],
+        _seeds: &[u64],
+        simulator_seeds: &[u64],
+    ) -> Result<()> {
+        self.simulator.plan_shots(shot_ids, simulator_seeds)
+    }
+    fn shot_end(&mut self) -> Result<()> {
+        self.simulator.shot_end()?;
+        Ok(())
+    }
+
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn handle_operations(&mut self, operations: BatchOperation) -> Result<BatchResult> {
+        let start_ns = u64::from(operations.start());
+        let end_ns = u64::from(operations.end());
+        let mut results = BatchResult::default();
+        for op in operations {
+            match op {
+                Operation::RXYGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                } => {
+                    self.apply_idle_noise(qubit_id, start_ns, end_ns)?;
+                    self.simulator.rxy(qubit_id, theta, phi)?;
+                }
+                Operation::RZGate { qubit_id, theta } => {
+                    self.apply_idle_noise(qubit_id, start_ns, end_ns)?;
+                    self.simulator.rz(qubit_id, theta)?;
+                }
+                Operation::RZZGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    theta,
+                } => {
+                    self.apply_idle_noise(qubit_id_1, start_ns, end_ns)?;
+                    self.apply_idle_noise(qubit_id_2, start_ns, end_ns)?;
+                    self.simulator.rzz(qubit_id_1, qubit_id_2, theta)?;
+                }
+                Operation::UGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                    lambda,
+                } => {
+                    self.apply_idle_noise(qubit_id, start_ns, end_ns)?;
+                    self.simulator.u(qubit_id, theta, phi, lambda)?;
+                }
+                Operation::TwoQubitUnitaryGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    matrix,
+                } => {
+                    self.apply_idle_noise(qubit_id_1, start_ns, end_ns)?;
+                    self.apply_idle_noise(qubit_id_2, start_ns, end_ns)?;
+                    self.simulator
+                        .two_qubit_unitary(qubit_id_1, qubit_id_2, &matrix)?;
+                }
+                Operation::DiagonalPhaseGate { qubit_ids, phases } => {
+                    for &qubit_id in qubit_ids.iter() {
+                        self.apply_idle_noise(qubit_id, start_ns, end_ns)?;
+                    }
+                    self.simulator.diagonal_phase(&qubit_ids, &phases)?;
+                }
+                Operation::Measure {
+                    qubit_id,
+                    result_id,
+                } => {
+                    self.apply_idle_noise(qubit_id, start_ns, end_ns)?;
+                    let measurement = self.simulator.measure(qubit_id)?;
+                    results.set_bool_result(result_id, measurement);
+                }
+                Operation::MeasureLeaked {
+                    qubit_id,
+                    result_id,
+                } => {
+                    self.apply_idle_noise(qubit_id, start_ns, end_ns)?;
+                    let measurement = self.simulator.measure_leaked(qubit_id)?;
+                    results.set_u64_result(result_id, measurement);
+                }
+                Operation::Reset { qubit_id } => {
+                    self.simulator.reset(qubit_id)?;
+                    self.clocks.start(qubit_id, end_ns)?;
+                }
+                Operation::Custom { .. } => {
+                    // Passively ignore custom operations
+                }
+                Operation::QAlloc { qubit_id } => {
+                    self.simulator.qalloc(qubit_id)?;
+                    self.clocks.start(qubit_id, start_ns)?;
+                }
+                Operation::QFree { qubit_id } => {
+                    self.simulator.qfree(qubit_id)?;
+                    self.clocks.stop(qubit_id)?;
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "idle_periods".to_string(),
+                MetricValue::U64(self.stats.idle_periods),
+            ))),
+            1 => Ok(Some((
+                "idle_time_ns".to_string(),
+                MetricValue::U64(self.stats.idle_time_ns),
+            ))),
+            2 => Ok(Some((
+                "faults_x".to_string(),
+                MetricValue::U64(self.stats.faults_x),
+            ))),
+            3 => Ok(Some((
+                "faults_y".to_string(),
+                MetricValue::U64(self.stats.faults_y),
+            ))),
+            4 => Ok(Some((
+                "faults_z".to_string(),
+                MetricValue::U64(self.stats.faults_z),
+            ))),
+            _ => Ok(None),
+        }
+    }
+    fn get_simulator_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        self.simulator.get_metric(nth_metric)
+    }
+}
+
+#[derive(Default)]
+pub struct IdleDephasingErrorModelFactory;
+
+impl ErrorModelInterfaceFactory for IdleDephasingErrorModelFactory {
+    type Interface = IdleDephasingErrorModel;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        error_model_args: &[impl AsRef<str>],
+        simulator_path: &impl AsRef<OsStr>,
+        simulator_args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        match Params::try_parse_from(error_model_args.iter().map(|s| s.as_ref())) {
+            Err(e) => Err(anyhow!(
+                "Error parsing arguments to idle dephasing error model plugin: {}",
+                e
+            )),
+            Ok(params) => {
+                params.validate()?;
+                let simulator =
+                    Simulator::load_from_file(simulator_path, n_qubits, simulator_args)?;
+                Ok(Box::new(IdleDephasingErrorModel::new(
+                    params, n_qubits, simulator,
+                )?))
+            }
+        }
+    }
+}
+
+export_error_model_plugin!(crate::IdleDephasingErrorModelFactory);
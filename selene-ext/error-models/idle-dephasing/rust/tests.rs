@@ -0,0 +1,203 @@
+use crate::{IdleDephasingErrorModel, Params};
+use anyhow::Result;
+use approx::assert_relative_eq;
+use clap::Parser;
+use selene_core::runtime::Operation;
+use selene_core::simulator::recording::{
+    RecordedOperation, Recording, find_metric, recorded_error_model, run_timed_batch, rxy,
+};
+use selene_core::utils::MetricValue;
+use std::f64::consts::PI;
+
+/// An idle dephasing error model on a recording simulator that accepts
+/// noise channels, after the start of a shot.
+fn error_model(args: &[&str], n_qubits: u64) -> Result<(IdleDephasingErrorModel, Recording)> {
+    let params = Params::try_parse_from(std::iter::once("").chain(args.iter().copied()))?;
+    recorded_error_model(n_qubits, true, |simulator| {
+        IdleDephasingErrorModel::new(params, n_qubits, simulator)
+    })
+}
+
+/// The parameters of the phase damping channels applied to each qubit.
+fn phase_damping(recording: &Recording) -> Vec<(u64, f64)> {
+    recording
+        .take()
+        .into_iter()
+        .filter_map(|op| match op {
+            RecordedOperation::PhaseDamping { qubit, lambda } => Some((qubit, lambda)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn idle_time_accumulates_from_allocation() {
+    let (mut error_model, recording) =
+        error_model(&["--t2-ns=1000", "--exact-channels"], 2).unwrap();
+    run_timed_batch(
+        &mut error_model,
+        0,
+        0,
+        vec![Operation::QAlloc { qubit_id: 0 }],
+    )
+    .unwrap();
+    run_timed_batch(&mut error_model, 1000, 10, vec![rxy(0)]).unwrap();
+    // Qubit 1 was never allocated, so its clock starts when it is first used.
+    run_timed_batch(&mut error_model, 1010, 10, vec![rxy(1)]).unwrap();
+    run_timed_batch(&mut error_model, 1520, 10, vec![rxy(0)]).unwrap();
+    run_timed_batch(&mut error_model, 1520, 10, vec![rxy(1)]).unwrap();
+
+    let damping = phase_damping(&recording);
+    assert_eq!(
+        damping.iter().map(|(qubit, _)| *qubit).collect::<Vec<_>>(),
+        vec![0, 0, 1]
+    );
+    // Coherences decay by exp(-t/T2) over each idle period.
+    for ((_, lambda), idle_ns) in damping.iter().zip([1000.0, 510.0, 500.0]) {
+        assert_relative_eq!((1.0 - lambda).sqrt(), (-idle_ns / 1000.0f64).exp());
+    }
+    assert_eq!(
+        find_metric(&mut error_model, "idle_periods").unwrap(),
+        Some(MetricValue::U64(3))
+    );
+    assert_eq!(
+        find_metric(&mut error_model, "idle_time_ns").unwrap(),
+        Some(MetricValue::U64(2010))
+    );
+}
+
+#[test]
+fn reset_restarts_and_free_stops_the_clock() {
+    let (mut error_model, recording) =
+        error_model(&["--t2-ns=1000", "--exact-channels"], 1).unwrap();
+    run_timed_batch(
+        &mut error_model,
+        0,
+        0,
+        vec![Operation::QAlloc { qubit_id: 0 }],
+    )
+    .unwrap();
+    // Time before a reset is discarded along with the state.
+    run_timed_batch(
+        &mut error_model,
+        2000,
+        40,
+        vec![Operation::Reset { qubit_id: 0 }],
+    )
+    .unwrap();
+    run_timed_batch(&mut error_model, 2040, 10, vec![rxy(0)]).unwrap();
+    assert!(phase_damping(&recording).is_empty());
+
+    // A freed qubit does not idle until it is allocated again.
+    run_timed_batch(
+        &mut error_model,
+        2050,
+        0,
+        vec![Operation::QFree { qubit_id: 0 }],
+    )
+    .unwrap();
+    run_timed_batch(
+        &mut error_model,
+        5000,
+        0,
+        vec![Operation::QAlloc { qubit_id: 0 }],
+    )
+    .unwrap();
+    run_timed_batch(&mut error_model, 5100, 10, vec![rxy(0)]).unwrap();
+    assert_eq!(phase_damping(&recording).len(), 1);
+    assert_eq!(
+        find_metric(&mut error_model, "idle_periods").unwrap(),
+        Some(MetricValue::U64(1))
+    );
+    assert_eq!(
+        find_metric(&mut error_model, "idle_time_ns").unwrap(),
+        Some(MetricValue::U64(100))
+    );
+}
+
+#[test]
+fn exact_channels_relax_and_dephase() {
+    let (mut error_model, recording) =
+        error_model(&["--t1-ns=2000", "--t2-ns=1000", "--exact-channels"], 1).unwrap();
+    run_timed_batch(
+        &mut error_model,
+        0,
+        0,
+        vec![Operation::QAlloc { qubit_id: 0 }],
+    )
+    .unwrap();
+    run_timed_batch(&mut error_model, 1000, 10, vec![rxy(0)]).unwrap();
+    let [
+        RecordedOperation::QAlloc { qubit: 0 },
+        RecordedOperation::AmplitudeDamping { qubit: 0, gamma },
+        RecordedOperation::PhaseDamping { qubit: 0, lambda },
+        RecordedOperation::Rxy { qubit: 0, .. },
+    ] = recording.take()[..]
+    else {
+        panic!("unexpected operations");
+    };
+    assert_relative_eq!(gamma, 1.0 - (-0.5f64).exp());
+    // Amplitude and phase damping together decay coherences by exp(-t/T2).
+    assert_relative_eq!(
+        ((1.0 - gamma) * (1.0 - lambda)).sqrt(),
+        (-1.0f64).exp(),
+        epsilon = 1e-12
+    );
+}
+
+#[test]
+fn sampled_faults_are_applied_and_counted() {
+    // Idling for many multiples of T2 fully dephases a qubit, so each idle
+    // period applies a Z fault with probability 1/2.
+    let (mut error_model, recording) = error_model(&["--t2-ns=1"], 1).unwrap();
+    run_timed_batch(
+        &mut error_model,
+        0,
+        0,
+        vec![Operation::QAlloc { qubit_id: 0 }],
+    )
+    .unwrap();
+    for i in 1..=200 {
+        run_timed_batch(&mut error_model, 1000 * i, 10, vec![rxy(0)]).unwrap();
+    }
+    let operations = recording.take();
+    let faults = operations
+        .iter()
+        .filter(|op| {
+            **op == RecordedOperation::Rz {
+                qubit: 0,
+                theta: PI,
+            }
+        })
+        .count() as u64;
+    assert!(
+        operations
+            .iter()
+            .all(|op| !matches!(op, RecordedOperation::PhaseDamping { .. }))
+    );
+    assert_eq!(
+        find_metric(&mut error_model, "faults_z").unwrap(),
+        Some(MetricValue::U64(faults))
+    );
+    assert!((50..150).contains(&faults), "{faults} faults");
+    assert_eq!(
+        find_metric(&mut error_model, "faults_x").unwrap(),
+        Some(MetricValue::U64(0))
+    );
+    assert_eq!(
+        find_metric(&mut error_model, "faults_y").unwrap(),
+        Some(MetricValue::U64(0))
+    );
+    assert_eq!(
+        find_metric(&mut error_model, "idle_periods").unwrap(),
+        Some(MetricValue::U64(200))
+    );
+}
+
+#[test]
+fn invalid_times_are_rejected() {
+    assert!(error_model(&["--t2-ns=0"], 1).is_err());
+    assert!(error_model(&["--t2-ns=100", "--t1-ns=-1"], 1).is_err());
+    assert!(error_model(&["--t2-ns=300", "--t1-ns=100"], 1).is_err());
+    assert!(error_model(&["--t2-ns=200", "--t1-ns=100"], 1).is_ok());
+}
@@ -0,0 +1,3 @@
+from .plugin import LRU_TAG, LeakagePlugin
+
+__all__ = ["LRU_TAG", "LeakagePlugin"]
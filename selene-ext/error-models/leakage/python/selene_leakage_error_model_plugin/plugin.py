@@ -0,0 +1,106 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+
+from selene_core import ErrorModel
+
+LRU_TAG = 0x4C5255
+
+
+@dataclass
+class LeakagePlugin(ErrorModel):
+    """
+    A plugin for simulating leakage with gate-specific leakage rates, seepage
+    back into the computational subspace, resets and leakage reduction units
+    that return leaked qubits to |0>, and misclassification by leakage
+    detection. Gates acting on leaked qubits are not applied, and may spread
+    leakage to the other qubit of a two-qubit gate.
+
+    A leakage reduction unit is requested with a custom runtime call with the
+    tag `lru_tag`, whose data is a list of little-endian u64 qubit indices.
+    Attributes:
+        p_leak_rxy (float): The probability of leakage on an RXY or U gate.
+        p_leak_rz (float): The probability of leakage on an RZ gate or
+            single-qubit diagonal phase gate.
+        p_leak_rzz (float): The probability of leakage on each qubit of an RZZ
+            gate, two-qubit unitary or multi-qubit diagonal phase gate.
+        p_seep (float): The probability that a leaked qubit returns to the
+            computational subspace on each gate acting on it.
+        seep_bias (float): The probability that a qubit seeping back returns
+            in |1> rather than |0>.
+        p_spread (float): The probability that a gate between a leaked and an
+            unleaked qubit leaks the unleaked qubit.
+        p_reset_clears (float): The probability that a reset returns a leaked
+            qubit to |0>.
+        leak_measurement_bias (float): The probability of measuring a leaked
+            qubit as 1.
+        p_missed_leakage (float): The probability that a leakage-detecting
+            measurement reports a leaked qubit as unleaked.
+        p_false_leakage (float): The probability that a leakage-detecting
+            measurement reports an unleaked qubit as leaked.
+        lru_tag (int): The custom operation tag of a leakage reduction unit.
+        p_lru (float): The probability that a leakage reduction unit returns a
+            leaked qubit to |0>.
+    """
+
+    p_leak_rxy: float = 0.0
+    p_leak_rz: float = 0.0
+    p_leak_rzz: float = 0.0
+    p_seep: float = 0.0
+    seep_bias: float = 0.5
+    p_spread: float = 0.0
+    p_reset_clears: float = 1.0
+    leak_measurement_bias: float = 0.5
+    p_missed_leakage: float = 0.0
+    p_false_leakage: float = 0.0
+    lru_tag: int = LRU_TAG
+    p_lru: float = 1.0
+
+    def __post_init__(self):
+        for name in [
+            "p_leak_rxy",
+            "p_leak_rz",
+            "p_leak_rzz",
+            "p_seep",
+            "seep_bias",
+            "p_spread",
+            "p_reset_clears",
+            "leak_measurement_bias",
+            "p_missed_leakage",
+            "p_false_leakage",
+            "p_lru",
+        ]:
+            value = getattr(self, name)
+            assert 0 <= value <= 1, (
+                f"{name} ({value}) must be between 0 and 1 (both inclusive)"
+            )
+        assert self.lru_tag >= 0, f"lru_tag ({self.lru_tag}) must be non-negative"
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_leakage_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_leakage_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_leakage_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    def get_init_args(self):
+        return [
+            f"--p-leak-rxy={self.p_leak_rxy}",
+            f"--p-leak-rz={self.p_leak_rz}",
+            f"--p-leak-rzz={self.p_leak_rzz}",
+            f"--p-seep={self.p_seep}",
+            f"--seep-bias={self.seep_bias}",
+            f"--p-spread={self.p_spread}",
+            f"--p-reset-clears={self.p_reset_clears}",
+            f"--leak-measurement-bias={self.leak_measurement_bias}",
+            f"--p-missed-leakage={self.p_missed_leakage}",
+            f"--p-false-leakage={self.p_false_leakage}",
+            f"--lru-tag={self.lru_tag}",
+            f"--p-lru={self.p_lru}",
+        ]
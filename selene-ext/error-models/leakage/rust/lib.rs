@@ -0,0 +1,436 @@
+/// A leakage error model in which qubits can leak with gate-specific rates,
+/// seep back into the computational subspace, be recovered by resets and
+/// leakage reduction units, and be misclassified by leakage detection.
+//
+// When a qubit leaks, it is reset in the simulator so that its computational
+// state is discarded, and it stops responding to gates. Gates that act on a
+// leaked qubit are not applied, and two-qubit gates may spread leakage onto
+// their other qubit. Each gate acting on a leaked qubit gives it a chance to
+// seep back into the computational subspace, returning in |0> or |1>.
+//
+// A leakage reduction unit (LRU) is requested through a custom operation with
+// the configured tag, whose data is a list of little-endian u64 qubit
+// indices. It returns leaked qubits to |0> with a configurable probability,
+// and acts as the identity on qubits that have not leaked.
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use selene_core::error_model::interface::ErrorModelInterfaceFactory;
+use selene_core::error_model::{BatchResult, ErrorModelInterface};
+use selene_core::export_error_model_plugin;
+use selene_core::runtime::{BatchOperation, Operation};
+use selene_core::simulator::{Simulator, SimulatorInterface};
+use selene_core::utils::MetricValue;
+use std::ffi::OsStr;
+
+/// The default custom operation tag of a leakage reduction unit ("LRU").
+const DEFAULT_LRU_TAG: usize = 0x4c5255;
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// The probability of leakage on each qubit of an RXY or U gate
+    #[arg(long, default_value_t = 0.0)]
+    p_leak_rxy: f64,
+    /// The probability of leakage on each qubit of an RZ gate or single-qubit
+    /// diagonal phase gate
+    #[arg(long, default_value_t = 0.0)]
+    p_leak_rz: f64,
+    /// The probability of leakage on each qubit of an RZZ gate, two-qubit
+    /// unitary or multi-qubit diagonal phase gate
+    #[arg(long, default_value_t = 0.0)]
+    p_leak_rzz: f64,
+    /// The probability that a leaked qubit seeps back into the computational
+    /// subspace on each gate acting on it
+    #[arg(long, default_value_t = 0.0)]
+    p_seep: f64,
+    /// The probability that a qubit seeping back returns in |1> rather than |0>
+    #[arg(long, default_value_t = 0.5)]
+    seep_bias: f64,
+    /// The probability that a gate between a leaked and an unleaked qubit
+    /// leaks the unleaked qubit
+    #[arg(long, default_value_t = 0.0)]
+    p_spread: f64,
+    /// The probability that a reset returns a leaked qubit to |0>
+    #[arg(long, default_value_t = 1.0)]
+    p_reset_clears: f64,
+    /// The probability that a leaked qubit measures as 1
+    #[arg(long, default_value_t = 0.5)]
+    leak_measurement_bias: f64,
+    /// The probability that a leakage-detecting measurement reports a leaked
+    /// qubit as unleaked
+    #[arg(long, default_value_t = 0.0)]
+    p_missed_leakage: f64,
+    /// The probability that a leakage-detecting measurement reports an
+    /// unleaked qubit as leaked
+    #[arg(long, default_value_t = 0.0)]
+    p_false_leakage: f64,
+    /// The custom operation tag of a leakage reduction unit
+    #[arg(long, default_value_t = DEFAULT_LRU_TAG)]
+    lru_tag: usize,
+    /// The probability that a leakage reduction unit returns a leaked qubit
+    /// to |0>
+    #[arg(long, default_value_t = 1.0)]
+    p_lru: f64,
+}
+
+impl Params {
+    fn validate(&self) -> Result<()> {
+        for (name, p) in [
+            ("p_leak_rxy", self.p_leak_rxy),
+            ("p_leak_rz", self.p_leak_rz),
+            ("p_leak_rzz", self.p_leak_rzz),
+            ("p_seep", self.p_seep),
+            ("seep_bias", self.seep_bias),
+            ("p_spread", self.p_spread),
+            ("p_reset_clears", self.p_reset_clears),
+            ("leak_measurement_bias", self.leak_measurement_bias),
+            ("p_missed_leakage", self.p_missed_leakage),
+            ("p_false_leakage", self.p_false_leakage),
+            ("p_lru", self.p_lru),
+        ] {
+            if !(0.0..=1.0).contains(&p) {
+                bail!("{name} ({p}) must be between 0 and 1 (both inclusive)");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Decode the qubits targeted by a leakage reduction unit from the data of
+/// its custom operation.
+fn parse_lru_qubits(data: &[u8]) -> Result<Vec<u64>> {
+    if !data.len().is_multiple_of(8) {
+        bail!(
+            "Leakage reduction unit data must be a list of u64 qubit indices, got {} bytes",
+            data.len()
+        );
+    }
+    Ok(data
+        .chunks_exact(8)
+        .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+        .collect())
+}
+
+#[derive(Default)]
+struct Stats {
+    gate_leaks: u64,
+    spread_leaks: u64,
+    seepages: u64,
+    lru_returns: u64,
+    reset_failures: u64,
+    detection_errors: u64,
+}
+
+pub struct LeakageErrorModel {
+    n_qubits: u64,
+    rng: Pcg64Mcg,
+    simulator: Simulator,
+    leak_register: Vec<bool>,
+    error_params: Params,
+    stats: Stats,
+}
+
+impl LeakageErrorModel {
+    fn new(params: Params, n_qubits: u64, simulator: Simulator) -> Result<Self> {
+        params.validate()?;
+        Ok(Self {
+            n_qubits,
+            rng: Pcg64Mcg::seed_from_u64(0),
+            simulator,
+            leak_register: vec![false; n_qubits as usize],
+            error_params: params,
+            stats: Stats::default(),
+        })
+    }
+    fn is_leaked(&self, qubit: u64) -> Result<bool> {
+        if qubit >= self.n_qubits {
+            bail!(
+                "Qubit ID {} is out of bounds for this error model with {} qubits",
+                qubit,
+                self.n_qubits
+            );
+        }
+        Ok(self.leak_register[qubit as usize])
+    }
+    fn leak(&mut self, qubit: u64) -> Result<()> {
+        if !self.is_leaked(qubit)? {
+            // Discard the qubit's computational state, which is restored to
+            // |0> if it later returns.
+            self.simulator.reset(qubit)?;
+            self.leak_register[qubit as usize] = true;
+        }
+        Ok(())
+    }
+    fn unleak(&mut self, qubit: u64) {
+        self.leak_register[qubit as usize] = false;
+    }
+    // Seep leaked qubits and spread leakage between the qubits of a gate,
+    // returning whether the gate should be applied.
+    fn before_gate(&mut self, qubits: &[u64]) -> Result<bool> {
+        for &qubit in qubits {
+            if self.is_leaked(qubit)? && self.rng.random_bool(self.error_params.p_seep) {
+                self.unleak(qubit);
+                self.stats.seepages += 1;
+                if self.rng.random_bool(self.error_params.seep_bias) {
+                    self.simulator.rxy(qubit, std::f64::consts::PI, 0.0)?;
+                }
+            }
+        }
+        let mut leaked = 0;
+        for &qubit in qubits {
+            leaked += self.is_leaked(qubit)? as usize;
+        }
+        if leaked > 0 && leaked < qubits.len() {
+            for &qubit in qubits {
+                if !self.is_leaked(qubit)? && self.rng.random_bool(self.error_params.p_spread) {
+                    self.leak(qubit)?;
+                    self.stats.spread_leaks += 1;
+                }
+            }
+        }
+        Ok(leaked == 0)
+    }
+    fn after_gate(&mut self, qubits: &[u64], p_leak: f64) -> Result<()> {
+        for &qubit in qubits {
+            if !self.is_leaked(qubit)? && self.rng.random_bool(p_leak) {
+                self.leak(qubit)?;
+                self.stats.gate_leaks += 1;
+            }
+        }
+        Ok(())
+    }
+    fn leakage_reduction_unit(&mut self, qubits: &[u64]) -> Result<()> {
+        for &qubit in qubits {
+            if self.is_leaked(qubit)? && self.rng.random_bool(self.error_params.p_lru) {
+                self.unleak(qubit);
+                self.stats.lru_returns += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ErrorModelInterface for LeakageErrorModel {
+    fn shot_start(&mut self, shot_id: u64, seed: u64, simulator_seed: u64) -> Result<()> {
+        self.rng = Pcg64Mcg::seed_from_u64(seed);
+        self.simulator.shot_start(shot_id, simulator_seed)?;
+        self.leak_register = vec![false; self.n_qubits as usize];
+        self.stats = Stats::default();
+        Ok(())
+    }
+    fn plan_shots(
+        &mut self,
+        shot_ids: &[u64],
+        _seeds: &[u64],
+        simulator_seeds: &[u64],
+    ) -> Result<()> {
+        self.simulator.plan_shots(shot_ids, simulator_seeds)
+    }
+    fn shot_end(&mut self) -> Result<()> {
+        self.simulator.shot_end()?;
+        Ok(())
+    }
+
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn handle_operations(&mut self, operations: BatchOperation) -> Result<BatchResult> {
+        let mut results = BatchResult::default();
+        for op in operations {
+            match op {
+                Operation::RXYGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                } => {
+                    if self.before_gate(&[qubit_id])? {
+                        self.simulator.rxy(qubit_id, theta, phi)?;
+                    }
+                    self.after_gate(&[qubit_id], self.error_params.p_leak_rxy)?;
+                }
+                Operation::RZGate { qubit_id, theta } => {
+                    if self.before_gate(&[qubit_id])? {
+                        self.simulator.rz(qubit_id, theta)?;
+                    }
+                    self.after_gate(&[qubit_id], self.error_params.p_leak_rz)?;
+                }
+                Operation::RZZGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    theta,
+                } => {
+                    let qubits = [qubit_id_1, qubit_id_2];
+                    if self.before_gate(&qubits)? {
+                        self.simulator.rzz(qubit_id_1, qubit_id_2, theta)?;
+                    }
+                    self.after_gate(&qubits, self.error_params.p_leak_rzz)?;
+                }
+                Operation::UGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                    lambda,
+                } => {
+                    if self.before_gate(&[qubit_id])? {
+                        self.simulator.u(qubit_id, theta, phi, lambda)?;
+                    }
+                    self.after_gate(&[qubit_id], self.error_params.p_leak_rxy)?;
+                }
+                Operation::TwoQubitUnitaryGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    matrix,
+                } => {
+                    let qubits = [qubit_id_1, qubit_id_2];
+                    if self.before_gate(&qubits)? {
+                        self.simulator
+                            .two_qubit_unitary(qubit_id_1, qubit_id_2, &matrix)?;
+                    }
+                    self.after_gate(&qubits, self.error_params.p_leak_rzz)?;
+                }
+                Operation::DiagonalPhaseGate { qubit_ids, phases } => {
+                    if self.before_gate(&qubit_ids)? {
+                        self.simulator.diagonal_phase(&qubit_ids, &phases)?;
+                    }
+                    let p_leak = if qubit_ids.len() > 1 {
+                        self.error_params.p_leak_rzz
+                    } else {
+                        self.error_params.p_leak_rz
+                    };
+                    self.after_gate(&qubit_ids, p_leak)?;
+                }
+                Operation::Measure {
+                    qubit_id,
+                    result_id,
+                } => {
+                    let measurement = if self.is_leaked(qubit_id)? {
+                        self.rng
+                            .random_bool(self.error_params.leak_measurement_bias)
+                    } else {
+                        self.simulator.measure(qubit_id)?
+                    };
+                    results.set_bool_result(result_id, measurement);
+                }
+                Operation::MeasureLeaked {
+                    qubit_id,
+                    result_id,
+                } => {
+                    let measurement = if self.is_leaked(qubit_id)? {
+                        if self.rng.random_bool(self.error_params.p_missed_leakage) {
+                            self.stats.detection_errors += 1;
+                            self.rng
+                                .random_bool(self.error_params.leak_measurement_bias)
+                                as u64
+                        } else {
+                            2
+                        }
+                    } else {
+                        let measurement = self.simulator.measure_leaked(qubit_id)?;
+                        if measurement < 2
+                            && self.rng.random_bool(self.error_params.p_false_leakage)
+                        {
+                            self.stats.detection_errors += 1;
+                            2
+                        } else {
+                            measurement
+                        }
+                    };
+                    results.set_u64_result(result_id, measurement);
+                }
+                Operation::Reset { qubit_id } => {
+                    self.simulator.reset(qubit_id)?;
+                    if self.is_leaked(qubit_id)? {
+                        if self.rng.random_bool(self.error_params.p_reset_clears) {
+                            self.unleak(qubit_id);
+                        } else {
+                            self.stats.reset_failures += 1;
+                        }
+                    }
+                }
+                Operation::Custom { custom_tag, data } => {
+                    if custom_tag == self.error_params.lru_tag {
+                        let qubits = parse_lru_qubits(&data)?;
+                        self.leakage_reduction_unit(&qubits)?;
+                    }
+                    // Passively ignore other custom operations
+                }
+                Operation::QAlloc { qubit_id } => {
+                    self.simulator.qalloc(qubit_id)?;
+                }
+                Operation::QFree { qubit_id } => {
+                    self.simulator.qfree(qubit_id)?;
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "gate_leaks".to_string(),
+                MetricValue::U64(self.stats.gate_leaks),
+            ))),
+            1 => Ok(Some((
+                "spread_leaks".to_string(),
+                MetricValue::U64(self.stats.spread_leaks),
+            ))),
+            2 => Ok(Some((
+                "seepages".to_string(),
+                MetricValue::U64(self.stats.seepages),
+            ))),
+            3 => Ok(Some((
+                "lru_returns".to_string(),
+                MetricValue::U64(self.stats.lru_returns),
+            ))),
+            4 => Ok(Some((
+                "reset_failures".to_string(),
+                MetricValue::U64(self.stats.reset_failures),
+            ))),
+            5 => Ok(Some((
+                "detection_errors".to_string(),
+                MetricValue::U64(self.stats.detection_errors),
+            ))),
+            _ => Ok(None),
+        }
+    }
+    fn get_simulator_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        self.simulator.get_metric(nth_metric)
+    }
+}
+
+#[derive(Default)]
+pub struct LeakageErrorModelFactory;
+
+impl ErrorModelInterfaceFactory for LeakageErrorModelFactory {
+    type Interface = LeakageErrorModel;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        error_model_args: &[impl AsRef<str>],
+        simulator_path: &impl AsRef<OsStr>,
+        simulator_args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        match Params::try_parse_from(error_model_args.iter().map(|s| s.as_ref())) {
+            Err(e) => Err(anyhow!(
+                "Error parsing arguments to leakage error model plugin: {}",
+                e
+            )),
+            Ok(params) => {
+                let simulator =
+                    Simulator::load_from_file(simulator_path, n_qubits, simulator_args)?;
+                Ok(Box::new(LeakageErrorModel::new(
+                    params, n_qubits, simulator,
+                )?))
+            }
+        }
+    }
+}
+
+export_error_model_plugin!(crate::LeakageErrorModelFactory);
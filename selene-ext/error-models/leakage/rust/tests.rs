@@ -0,0 +1,209 @@
+use crate::{DEFAULT_LRU_TAG, LeakageErrorModel, Params};
+use anyhow::Result;
+use clap::Parser;
+use selene_core::runtime::Operation;
+use selene_core::simulator::recording::{
+    RecordedOperation, Recording, find_metric, measure_leaked, recorded_error_model, run_batch, rxy,
+};
+use selene_core::utils::MetricValue;
+use std::f64::consts::PI;
+
+/// A leakage error model on a recording simulator, after the start of a shot.
+fn leakage(args: &[&str], n_qubits: u64) -> Result<(LeakageErrorModel, Recording)> {
+    let params = Params::try_parse_from(std::iter::once("").chain(args.iter().copied()))?;
+    recorded_error_model(n_qubits, false, |simulator| {
+        LeakageErrorModel::new(params, n_qubits, simulator)
+    })
+}
+
+fn rz(qubit_id: u64) -> Operation {
+    Operation::RZGate {
+        qubit_id,
+        theta: 1.0,
+    }
+}
+
+fn lru(custom_tag: usize, qubits: &[u64]) -> Operation {
+    Operation::Custom {
+        custom_tag,
+        data: qubits.iter().flat_map(|q| q.to_le_bytes()).collect(),
+    }
+}
+
+#[test]
+fn leaked_qubits_ignore_gates() {
+    let (mut error_model, recording) = leakage(&["--p-leak-rz=1"], 1).unwrap();
+    run_batch(&mut error_model, vec![rz(0)]).unwrap();
+    // The gate is applied before the qubit leaks and its state is discarded.
+    assert_eq!(
+        recording.take(),
+        vec![
+            RecordedOperation::Rz {
+                qubit: 0,
+                theta: 1.0
+            },
+            RecordedOperation::Reset { qubit: 0 },
+        ]
+    );
+    run_batch(&mut error_model, vec![rxy(0)]).unwrap();
+    run_batch(&mut error_model, vec![rz(0)]).unwrap();
+    assert!(recording.take().is_empty());
+    assert_eq!(measure_leaked(&mut error_model, 0).unwrap(), 2);
+    assert_eq!(
+        find_metric(&mut error_model, "gate_leaks").unwrap(),
+        Some(MetricValue::U64(1))
+    );
+}
+
+#[test]
+fn leaked_qubits_seep_back() {
+    for (seep_bias, flips) in [("0", 0), ("1", 1)] {
+        let (mut error_model, recording) = leakage(
+            &[
+                "--p-leak-rz=1",
+                "--p-seep=1",
+                &format!("--seep-bias={seep_bias}"),
+            ],
+            1,
+        )
+        .unwrap();
+        run_batch(&mut error_model, vec![rz(0)]).unwrap();
+        recording.take();
+        // The qubit returns before the gate, which is then applied.
+        run_batch(&mut error_model, vec![rxy(0)]).unwrap();
+        let operations = recording.take();
+        assert_eq!(operations.len(), flips + 1);
+        if flips > 0 {
+            assert_eq!(
+                operations[0],
+                RecordedOperation::Rxy {
+                    qubit: 0,
+                    theta: PI,
+                    phi: 0.0
+                }
+            );
+        }
+        assert_eq!(measure_leaked(&mut error_model, 0).unwrap(), flips as u64);
+        assert_eq!(
+            find_metric(&mut error_model, "seepages").unwrap(),
+            Some(MetricValue::U64(1))
+        );
+    }
+}
+
+#[test]
+fn two_qubit_gates_spread_leakage() {
+    let (mut error_model, recording) = leakage(&["--p-leak-rz=1", "--p-spread=1"], 2).unwrap();
+    run_batch(&mut error_model, vec![rz(0)]).unwrap();
+    recording.take();
+    let rzz = Operation::RZZGate {
+        qubit_id_1: 0,
+        qubit_id_2: 1,
+        theta: 1.0,
+    };
+    run_batch(&mut error_model, vec![rzz]).unwrap();
+    // The gate is not applied, and the other qubit leaks.
+    assert_eq!(
+        recording.take(),
+        vec![RecordedOperation::Reset { qubit: 1 }]
+    );
+    assert_eq!(measure_leaked(&mut error_model, 1).unwrap(), 2);
+    assert_eq!(
+        find_metric(&mut error_model, "spread_leaks").unwrap(),
+        Some(MetricValue::U64(1))
+    );
+    assert_eq!(
+        find_metric(&mut error_model, "gate_leaks").unwrap(),
+        Some(MetricValue::U64(1))
+    );
+}
+
+#[test]
+fn resets_clear_leakage() {
+    let (mut error_model, _) = leakage(&["--p-leak-rz=1"], 1).unwrap();
+    run_batch(&mut error_model, vec![rz(0)]).unwrap();
+    run_batch(&mut error_model, vec![Operation::Reset { qubit_id: 0 }]).unwrap();
+    assert_eq!(measure_leaked(&mut error_model, 0).unwrap(), 0);
+    assert_eq!(
+        find_metric(&mut error_model, "reset_failures").unwrap(),
+        Some(MetricValue::U64(0))
+    );
+
+    let (mut error_model, _) = leakage(&["--p-leak-rz=1", "--p-reset-clears=0"], 1).unwrap();
+    run_batch(&mut error_model, vec![rz(0)]).unwrap();
+    run_batch(&mut error_model, vec![Operation::Reset { qubit_id: 0 }]).unwrap();
+    assert_eq!(measure_leaked(&mut error_model, 0).unwrap(), 2);
+    assert_eq!(
+        find_metric(&mut error_model, "reset_failures").unwrap(),
+        Some(MetricValue::U64(1))
+    );
+}
+
+#[test]
+fn leakage_detection_can_misclassify() {
+    let (mut error_model, recording) = leakage(
+        &[
+            "--p-leak-rz=1",
+            "--p-missed-leakage=1",
+            "--leak-measurement-bias=1",
+        ],
+        1,
+    )
+    .unwrap();
+    run_batch(&mut error_model, vec![rz(0)]).unwrap();
+    recording.take();
+    // A leaked qubit is reported as unleaked, without consulting the simulator.
+    assert_eq!(measure_leaked(&mut error_model, 0).unwrap(), 1);
+    assert!(recording.take().is_empty());
+    assert_eq!(
+        find_metric(&mut error_model, "detection_errors").unwrap(),
+        Some(MetricValue::U64(1))
+    );
+
+    let (mut error_model, _) = leakage(&["--p-false-leakage=1"], 1).unwrap();
+    assert_eq!(measure_leaked(&mut error_model, 0).unwrap(), 2);
+    assert_eq!(
+        find_metric(&mut error_model, "detection_errors").unwrap(),
+        Some(MetricValue::U64(1))
+    );
+}
+
+#[test]
+fn leakage_reduction_units_return_leaked_qubits() {
+    let (mut error_model, recording) = leakage(&["--p-leak-rz=1"], 3).unwrap();
+    run_batch(&mut error_model, vec![rz(0)]).unwrap();
+    run_batch(&mut error_model, vec![rz(2)]).unwrap();
+    recording.take();
+    // Custom operations with other tags are ignored.
+    run_batch(&mut error_model, vec![lru(DEFAULT_LRU_TAG + 1, &[0])]).unwrap();
+    assert_eq!(measure_leaked(&mut error_model, 0).unwrap(), 2);
+    // Unleaked qubits are unaffected.
+    run_batch(&mut error_model, vec![lru(DEFAULT_LRU_TAG, &[0, 1])]).unwrap();
+    assert_eq!(measure_leaked(&mut error_model, 0).unwrap(), 0);
+    assert_eq!(measure_leaked(&mut error_model, 2).unwrap(), 2);
+    assert_eq!(
+        find_metric(&mut error_model, "lru_returns").unwrap(),
+        Some(MetricValue::U64(1))
+    );
+    assert!(
+        recording
+            .take()
+            .iter()
+            .all(|op| matches!(op, RecordedOperation::MeasureLeaked { .. }))
+    );
+
+    let (mut error_model, _) = leakage(&["--lru-tag=7"], 2).unwrap();
+    assert!(run_batch(&mut error_model, vec![lru(7, &[2])]).is_err());
+    let malformed = Operation::Custom {
+        custom_tag: 7,
+        data: vec![0; 12].into(),
+    };
+    assert!(run_batch(&mut error_model, vec![malformed]).is_err());
+}
+
+#[test]
+fn probabilities_are_validated() {
+    assert!(leakage(&["--p-seep=1.5"], 1).is_err());
+    assert!(leakage(&["--p-false-leakage=-0.1"], 1).is_err());
+    assert!(leakage(&["--p-leak-rzz=0.01", "--p-seep=0.5", "--lru-tag=7"], 1).is_ok());
+}
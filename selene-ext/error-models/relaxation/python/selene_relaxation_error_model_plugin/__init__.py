@@ -0,0 +1,3 @@
+from .plugin import RelaxationPlugin
+
+__all__ = ["RelaxationPlugin"]
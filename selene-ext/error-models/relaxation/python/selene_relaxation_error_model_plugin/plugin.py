@@ -0,0 +1,70 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+
+from selene_core import ErrorModel
+
+
+@dataclass
+class RelaxationPlugin(ErrorModel):
+    """
+    A plugin for simulating energy relaxation and dephasing with per-qubit T1
+    and T2 times. Noise is applied for all of the time a qubit is live,
+    including the durations of the operations acting on it, as reported by the
+    runtime. Where the simulator supports damping channels they are applied
+    directly, and otherwise relaxation is applied as quantum trajectory jumps.
+    The expected and realised numbers of decay events are reported as metrics.
+    Attributes:
+        t1_ns (float): The default relaxation time T1, in nanoseconds.
+        t2_ns (float): The default dephasing time T2, in nanoseconds. Must be
+            at most twice t1_ns.
+        qubit_times (dict[int, tuple[float, float]] | None): The (T1, T2) times
+            of individual qubits, keyed by qubit index, overriding the
+            defaults.
+        trajectories (bool): Whether to apply relaxation as quantum trajectory
+            jumps even if the simulator supports damping channels.
+    """
+
+    t1_ns: float = float("inf")
+    t2_ns: float = float("inf")
+    qubit_times: dict[int, tuple[float, float]] | None = None
+    trajectories: bool = False
+
+    def __post_init__(self):
+        times = [(self.t1_ns, self.t2_ns)]
+        if self.qubit_times:
+            times.extend(self.qubit_times.values())
+        for t1_ns, t2_ns in times:
+            assert t1_ns > 0, f"t1_ns ({t1_ns}) must be positive"
+            assert t2_ns > 0, f"t2_ns ({t2_ns}) must be positive"
+            assert t2_ns <= 2 * t1_ns, (
+                f"t2_ns ({t2_ns}) must be at most twice t1_ns ({t1_ns})"
+            )
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_relaxation_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_relaxation_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_relaxation_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    def get_init_args(self):
+        args = [
+            f"--t1-ns={self.t1_ns}",
+            f"--t2-ns={self.t2_ns}",
+        ]
+        if self.qubit_times:
+            times = ",".join(
+                f"{qubit}:{t1_ns}:{t2_ns}"
+                for qubit, (t1_ns, t2_ns) in self.qubit_times.items()
+            )
+            args.append(f"--qubit-times={times}")
+        if self.trajectories:
+            args.append("--trajectories")
+        return args
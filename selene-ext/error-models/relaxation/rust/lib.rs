@@ -0,0 +1,416 @@
+/// An error model that relaxes qubits toward |0> and dephases them according
+/// to per-qubit T1 and T2 times, over all of the time that passes while they
+/// are live, including the duration of the operations acting on them.
+//
+// Every batch of operations carries a start time and a duration, which the
+// simple and soft_rz runtimes derive from their --duration-ns-* parameters.
+// For each qubit we record the end of the last batch that acted on it. When a
+// batch next acts on the qubit, noise is applied before the operation for the
+// time from then until the end of the new batch, so both idle time and the
+// time spent in the operation itself are accounted for.
+//
+// Time is only counted while a qubit is live, as tracked by selene_core's
+// QubitClocks.
+//
+// Where the simulator supports them, relaxation and dephasing are applied
+// with its amplitude damping and phase damping channels, which is exact on
+// mixed state simulators. Otherwise relaxation is unravelled into quantum
+// trajectory jumps: with probability gamma the qubit is measured, and if it
+// is found in |1> it is flipped to |0>. This reproduces the populations of the
+// amplitude damping channel exactly, but decays coherences at the rate 1/T1
+// rather than 1/(2 T1), so the remaining dephasing is applied as Z faults and
+// qubits with T2 > T1 dephase as though T2 = T1.
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use selene_core::error_model::clocks::QubitClocks;
+use selene_core::error_model::interface::ErrorModelInterfaceFactory;
+use selene_core::error_model::{BatchResult, ErrorModelInterface};
+use selene_core::export_error_model_plugin;
+use selene_core::runtime::{BatchOperation, Operation};
+use selene_core::simulator::{Simulator, SimulatorInterface};
+use selene_core::utils::MetricValue;
+use std::ffi::OsStr;
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// The default relaxation time T1, in nanoseconds
+    #[arg(long, default_value_t = f64::INFINITY)]
+    t1_ns: f64,
+    /// The default dephasing time T2, in nanoseconds
+    #[arg(long, default_value_t = f64::INFINITY)]
+    t2_ns: f64,
+    /// Per-qubit overrides of the form "qubit:t1:t2", separated by commas
+    #[arg(long)]
+    qubit_times: Option<String>,
+    /// Always apply relaxation by quantum trajectory jumps, even if the
+    /// simulator supports damping channels
+    #[arg(long)]
+    trajectories: bool,
+}
+
+/// The T1 and T2 times of a qubit, in nanoseconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct RelaxationTimes {
+    t1_ns: f64,
+    t2_ns: f64,
+}
+
+impl RelaxationTimes {
+    fn new(t1_ns: f64, t2_ns: f64) -> Result<Self> {
+        if t1_ns.is_nan() || t1_ns <= 0.0 {
+            bail!("t1_ns ({t1_ns}) must be positive");
+        }
+        if t2_ns.is_nan() || t2_ns <= 0.0 {
+            bail!("t2_ns ({t2_ns}) must be positive");
+        }
+        if t2_ns > 2.0 * t1_ns {
+            bail!("t2_ns ({t2_ns}) must be at most twice t1_ns ({t1_ns})");
+        }
+        Ok(Self { t1_ns, t2_ns })
+    }
+
+    /// The probability that an excited qubit decays to |0> in `time_ns`.
+    fn decay_probability(&self, time_ns: f64) -> f64 {
+        1.0 - (-time_ns / self.t1_ns).exp()
+    }
+
+    /// The phase damping parameter that, alongside amplitude damping with
+    /// `decay_probability`, decays coherences by exp(-time_ns / T2).
+    fn phase_damping_parameter(&self, time_ns: f64) -> f64 {
+        let lambda = 1.0 - (-time_ns * (2.0 / self.t2_ns - 1.0 / self.t1_ns)).exp();
+        lambda.max(0.0)
+    }
+
+    /// The probability of a Z fault that, alongside trajectory jumps with
+    /// `decay_probability`, decays coherences by exp(-time_ns / T2). This is
+    /// zero when T2 > T1, as the jumps alone decay coherences too quickly.
+    fn trajectory_dephasing_probability(&self, time_ns: f64) -> f64 {
+        let p_z = (1.0 - (-time_ns * (1.0 / self.t2_ns - 1.0 / self.t1_ns)).exp()) / 2.0;
+        p_z.max(0.0)
+    }
+}
+
+fn parse_qubit_times(
+    times: &str,
+    n_qubits: u64,
+    default: RelaxationTimes,
+) -> Result<Vec<RelaxationTimes>> {
+    let mut qubit_times = vec![default; n_qubits as usize];
+    for entry in times.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let fields: Vec<&str> = entry.split(':').map(str::trim).collect();
+        let parsed = match fields.as_slice() {
+            [qubit, t1_ns, t2_ns] => match (qubit.parse(), t1_ns.parse(), t2_ns.parse()) {
+                (Ok(qubit), Ok(t1_ns), Ok(t2_ns)) => Some((qubit, t1_ns, t2_ns)),
+                _ => None,
+            },
+            _ => None,
+        };
+        let Some((qubit, t1_ns, t2_ns)): Option<(u64, f64, f64)> = parsed else {
+            bail!("Invalid qubit times '{entry}': expected a qubit index, T1 and T2 joined by ':'");
+        };
+        if qubit >= n_qubits {
+            bail!(
+                "Invalid qubit times '{entry}': qubit must be less than the number of qubits ({n_qubits})"
+            );
+        }
+        qubit_times[qubit as usize] = RelaxationTimes::new(t1_ns, t2_ns)
+            .map_err(|e| anyhow!("Invalid qubit times '{entry}': {e}"))?;
+    }
+    Ok(qubit_times)
+}
+
+#[derive(Default)]
+struct Stats {
+    expected_decays: f64,
+    realised_decays: u64,
+    dephasing_faults: u64,
+}
+
+pub struct RelaxationErrorModel {
+    rng: Pcg64Mcg,
+    simulator: Simulator,
+    qubit_times: Vec<RelaxationTimes>,
+    trajectories: bool,
+    // Whether the simulator supports damping channels and marginal
+    // probabilities, once we have tried to use them.
+    supports_channels: Option<bool>,
+    supports_marginals: Option<bool>,
+    clocks: QubitClocks,
+    stats: Stats,
+}
+
+impl RelaxationErrorModel {
+    fn new(params: Params, n_qubits: u64, simulator: Simulator) -> Result<Self> {
+        let default = RelaxationTimes::new(params.t1_ns, params.t2_ns)?;
+        let qubit_times = match params.qubit_times.as_deref() {
+            Some(times) => parse_qubit_times(times, n_qubits, default)?,
+            None => vec![default; n_qubits as usize],
+        };
+        Ok(Self {
+            rng: Pcg64Mcg::seed_from_u64(0),
+            simulator,
+            qubit_times,
+            trajectories: params.trajectories,
+            supports_channels: None,
+            supports_marginals: None,
+            clocks: QubitClocks::new(n_qubits),
+            stats: Stats::default(),
+        })
+    }
+    // The probability that the qubit is in |1>, or one if the simulator
+    // cannot tell us, in which case the expected number of decays is an
+    // upper bound.
+    fn excited_population(&mut self, qubit: u64) -> Result<f64> {
+        if self.supports_marginals == Some(false) {
+            return Ok(1.0);
+        }
+        match self.simulator.marginal_probabilities(&[qubit]) {
+            Ok(probabilities) => {
+                self.supports_marginals = Some(true);
+                Ok(probabilities[1])
+            }
+            Err(e) if self.supports_marginals.is_some() => Err(e),
+            Err(_) => {
+                self.supports_marginals = Some(false);
+                Ok(1.0)
+            }
+        }
+    }
+    // Apply the simulator's damping channels, returning false if the
+    // simulator does not support them. Support for both channels is probed
+    // with no-op parameters before either is applied, so that a simulator
+    // supporting only one of them never receives part of the noise in
+    // addition to the trajectory fallback.
+    fn apply_channels(&mut self, qubit: u64, gamma: f64, lambda: f64) -> Result<bool> {
+        let supports_channels = match self.supports_channels {
+            Some(supports_channels) => supports_channels,
+            None => {
+                let supports_channels = self.simulator.amplitude_damping(qubit, 0.0).is_ok()
+                    && self.simulator.phase_damping(qubit, 0.0).is_ok();
+                self.supports_channels = Some(supports_channels);
+                supports_channels
+            }
+        };
+        if !supports_channels {
+            return Ok(false);
+        }
+        if gamma > 0.0 {
+            self.simulator.amplitude_damping(qubit, gamma)?;
+        }
+        if lambda > 0.0 {
+            self.simulator.phase_damping(qubit, lambda)?;
+        }
+        Ok(true)
+    }
+    fn apply_trajectory(&mut self, qubit: u64, gamma: f64, p_z: f64) -> Result<()> {
+        if self.rng.random::<f64>() < gamma && self.simulator.measure(qubit)? {
+            self.stats.realised_decays += 1;
+            self.simulator.rxy(qubit, std::f64::consts::PI, 0.0)?;
+        }
+        if self.rng.random::<f64>() < p_z {
+            self.stats.dephasing_faults += 1;
+            self.simulator.rz(qubit, std::f64::consts::PI)?;
+        }
+        Ok(())
+    }
+    // Apply noise for the time since the qubit was last updated, up to the
+    // end of the batch running until end_ns.
+    fn apply_relaxation(&mut self, qubit: u64, end_ns: u64) -> Result<()> {
+        let Some(elapsed_ns) = self.clocks.advance(qubit, end_ns, end_ns)? else {
+            return Ok(());
+        };
+        if elapsed_ns == 0 {
+            return Ok(());
+        }
+        let elapsed_ns = elapsed_ns as f64;
+        let times = self.qubit_times[qubit as usize];
+        let gamma = times.decay_probability(elapsed_ns);
+        if gamma > 0.0 {
+            self.stats.expected_decays += gamma * self.excited_population(qubit)?;
+        }
+        let lambda = times.phase_damping_parameter(elapsed_ns);
+        if gamma == 0.0 && lambda == 0.0 {
+            return Ok(());
+        }
+        if self.trajectories || !self.apply_channels(qubit, gamma, lambda)? {
+            let p_z = times.trajectory_dephasing_probability(elapsed_ns);
+            self.apply_trajectory(qubit, gamma, p_z)?;
+        }
+        Ok(())
+    }
+}
+
+impl ErrorModelInterface for RelaxationErrorModel {
+    fn shot_start(&mut self, shot_id: u64, seed: u64, simulator_seed: u64) -> Result<()> {
+        self.rng = Pcg64Mcg::seed_from_u64(seed);
+        self.simulator.shot_start(shot_id, simulator_seed)?;
+        self.clocks.stop_all();
+        self.stats = Stats::default();
+        Ok(())
+    }
+    fn plan_shots(
+        &mut self,
+        shot_ids: &[u64],
+        _seeds: &[u64],
+        simulator_seeds: &[u64],
+    ) -> Result<()> {
+        self.simulator.plan_shots(shot_ids, simulator_seeds)
+    }
+    fn shot_end(&mut self) -> Result<()> {
+        self.simulator.shot_end()?;
+        Ok(())
+    }
+
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn handle_operations(&mut self, operations: BatchOperation) -> Result<BatchResult> {
+        let start_ns = u64::from(operations.start());
+        let end_ns = u64::from(operations.end());
+        let mut results = BatchResult::default();
+        for op in operations {
+            match op {
+                Operation::RXYGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                } => {
+                    self.apply_relaxation(qubit_id, end_ns)?;
+                    self.simulator.rxy(qubit_id, theta, phi)?;
+                }
+                Operation::RZGate { qubit_id, theta } => {
+                    self.apply_relaxation(qubit_id, end_ns)?;
+                    self.simulator.rz(qubit_id, theta)?;
+                }
+                Operation::RZZGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    theta,
+                } => {
+                    self.apply_relaxation(qubit_id_1, end_ns)?;
+                    self.apply_relaxation(qubit_id_2, end_ns)?;
+                    self.simulator.rzz(qubit_id_1, qubit_id_2, theta)?;
+                }
+                Operation::UGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                    lambda,
+                } => {
+                    self.apply_relaxation(qubit_id, end_ns)?;
+                    self.simulator.u(qubit_id, theta, phi, lambda)?;
+                }
+                Operation::TwoQubitUnitaryGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    matrix,
+                } => {
+                    self.apply_relaxation(qubit_id_1, end_ns)?;
+                    self.apply_relaxation(qubit_id_2, end_ns)?;
+                    self.simulator
+                        .two_qubit_unitary(qubit_id_1, qubit_id_2, &matrix)?;
+                }
+                Operation::DiagonalPhaseGate { qubit_ids, phases } => {
+                    for &qubit_id in qubit_ids.iter() {
+                        self.apply_relaxation(qubit_id, end_ns)?;
+                    }
+                    self.simulator.diagonal_phase(&qubit_ids, &phases)?;
+                }
+                Operation::Measure {
+                    qubit_id,
+                    result_id,
+                } => {
+                    self.apply_relaxation(qubit_id, end_ns)?;
+                    let measurement = self.simulator.measure(qubit_id)?;
+                    results.set_bool_result(result_id, measurement);
+                }
+                Operation::MeasureLeaked {
+                    qubit_id,
+                    result_id,
+                } => {
+                    self.apply_relaxation(qubit_id, end_ns)?;
+                    let measurement = self.simulator.measure_leaked(qubit_id)?;
+                    results.set_u64_result(result_id, measurement);
+                }
+                Operation::Reset { qubit_id } => {
+                    self.simulator.reset(qubit_id)?;
+                    self.clocks.start(qubit_id, end_ns)?;
+                }
+                Operation::Custom { .. } => {
+                    // Passively ignore custom operations
+                }
+                Operation::QAlloc { qubit_id } => {
+                    self.simulator.qalloc(qubit_id)?;
+                    self.clocks.start(qubit_id, start_ns)?;
+                }
+                Operation::QFree { qubit_id } => {
+                    self.simulator.qfree(qubit_id)?;
+                    self.clocks.stop(qubit_id)?;
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "expected_decays".to_string(),
+                MetricValue::F64(self.stats.expected_decays),
+            ))),
+            1 => Ok(Some((
+                "realised_decays".to_string(),
+                MetricValue::U64(self.stats.realised_decays),
+            ))),
+            2 => Ok(Some((
+                "dephasing_faults".to_string(),
+                MetricValue::U64(self.stats.dephasing_faults),
+            ))),
+            3 => Ok(Some((
+                "exact_channels".to_string(),
+                MetricValue::Bool(self.supports_channels == Some(true) && !self.trajectories),
+            ))),
+            _ => Ok(None),
+        }
+    }
+    fn get_simulator_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        self.simulator.get_metric(nth_metric)
+    }
+}
+
+#[derive(Default)]
+pub struct RelaxationErrorModelFactory;
+
+impl ErrorModelInterfaceFactory for RelaxationErrorModelFactory {
+    type Interface = RelaxationErrorModel;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        error_model_args: &[impl AsRef<str>],
+        simulator_path: &impl AsRef<OsStr>,
+        simulator_args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        match Params::try_parse_from(error_model_args.iter().map(|s| s.as_ref())) {
+            Err(e) => Err(anyhow!(
+                "Error parsing arguments to relaxation error model plugin: {}",
+                e
+            )),
+            Ok(params) => {
+                let simulator =
+                    Simulator::load_from_file(simulator_path, n_qubits, simulator_args)?;
+                Ok(Box::new(RelaxationErrorModel::new(
+                    params, n_qubits, simulator,
+                )?))
+            }
+        }
+    }
+}
+
+export_error_model_plugin!(crate::RelaxationErrorModelFactory);
@@ -0,0 +1,367 @@
+use crate::{Params, RelaxationErrorModel, RelaxationTimes, parse_qubit_times};
+use anyhow::{Result, bail};
+use approx::assert_relative_eq;
+use clap::Parser;
+use selene_core::error_model::ErrorModelInterface;
+use selene_core::runtime::Operation;
+use selene_core::simulator::recording::{
+    RecordedOperation, Recording, RecordingSimulator, RecordingSimulatorFactory, find_metric,
+    recorded_error_model, run_timed_batch,
+};
+use selene_core::simulator::{Simulator, SimulatorInterface, SimulatorInterfaceFactory};
+use selene_core::utils::MetricValue;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// A relaxation error model on the given simulator, after the start of a
+/// shot.
+fn relaxation(
+    args: &[&str],
+    factory: Arc<impl SimulatorInterfaceFactory + 'static>,
+) -> RelaxationErrorModel {
+    let params = Params::try_parse_from(std::iter::once("").chain(args.iter().copied())).unwrap();
+    let simulator = Simulator::new(factory, 1, &[""]).unwrap();
+    let mut error_model = RelaxationErrorModel::new(params, 1, simulator).unwrap();
+    error_model.shot_start(0, 0, 0).unwrap();
+    error_model
+}
+
+/// A relaxation error model on a recording simulator, which accepts noise
+/// channels if `channels` is set.
+fn recorded_relaxation(args: &[&str], channels: bool) -> (RelaxationErrorModel, Recording) {
+    let params = Params::try_parse_from(std::iter::once("").chain(args.iter().copied())).unwrap();
+    recorded_error_model(1, channels, |simulator| {
+        RelaxationErrorModel::new(params, 1, simulator)
+    })
+    .unwrap()
+}
+
+fn flip() -> Operation {
+    Operation::RXYGate {
+        qubit_id: 0,
+        theta: PI,
+        phi: 0.0,
+    }
+}
+
+fn measure() -> Operation {
+    Operation::Measure {
+        qubit_id: 0,
+        result_id: 0,
+    }
+}
+
+fn expected_decays(error_model: &mut RelaxationErrorModel) -> f64 {
+    let Some(MetricValue::F64(value)) = find_metric(error_model, "expected_decays").unwrap() else {
+        panic!("expected_decays is not an F64");
+    };
+    value
+}
+
+fn realised_decays(error_model: &mut RelaxationErrorModel) -> u64 {
+    let Some(MetricValue::U64(value)) = find_metric(error_model, "realised_decays").unwrap() else {
+        panic!("realised_decays is not a U64");
+    };
+    value
+}
+
+fn exact_channels(error_model: &mut RelaxationErrorModel) -> bool {
+    let Some(MetricValue::Bool(value)) = find_metric(error_model, "exact_channels").unwrap() else {
+        panic!("exact_channels is not a Bool");
+    };
+    value
+}
+
+/// A recording simulator that supports amplitude damping but not phase
+/// damping.
+struct AmplitudeDampingOnly(Box<RecordingSimulator>);
+
+impl SimulatorInterface for AmplitudeDampingOnly {
+    fn exit(&mut self) -> Result<()> {
+        self.0.exit()
+    }
+    fn shot_start(&mut self, shot_id: u64, seed: u64) -> Result<()> {
+        self.0.shot_start(shot_id, seed)
+    }
+    fn shot_end(&mut self) -> Result<()> {
+        self.0.shot_end()
+    }
+    fn rz(&mut self, qubit: u64, theta: f64) -> Result<()> {
+        self.0.rz(qubit, theta)
+    }
+    fn rxy(&mut self, qubit: u64, theta: f64, phi: f64) -> Result<()> {
+        self.0.rxy(qubit, theta, phi)
+    }
+    fn rzz(&mut self, qubit1: u64, qubit2: u64, theta: f64) -> Result<()> {
+        self.0.rzz(qubit1, qubit2, theta)
+    }
+    fn measure(&mut self, qubit: u64) -> Result<bool> {
+        self.0.measure(qubit)
+    }
+    fn reset(&mut self, qubit: u64) -> Result<()> {
+        self.0.reset(qubit)
+    }
+    fn qalloc(&mut self, qubit: u64) -> Result<()> {
+        self.0.qalloc(qubit)
+    }
+    fn marginal_probabilities(&mut self, qubits: &[u64]) -> Result<Vec<f64>> {
+        self.0.marginal_probabilities(qubits)
+    }
+    fn amplitude_damping(&mut self, qubit: u64, gamma: f64) -> Result<()> {
+        self.0.amplitude_damping(qubit, gamma)
+    }
+    fn phase_damping(&mut self, _qubit: u64, _lambda: f64) -> Result<()> {
+        bail!("Phase damping is not supported")
+    }
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        self.0.get_metric(nth_metric)
+    }
+}
+
+struct AmplitudeDampingOnlyFactory(Arc<RecordingSimulatorFactory>);
+
+impl SimulatorInterfaceFactory for AmplitudeDampingOnlyFactory {
+    type Interface = AmplitudeDampingOnly;
+
+    fn init(
+        self: Arc<Self>,
+        n_qubits: u64,
+        args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        let simulator = self.0.clone().init(n_qubits, args)?;
+        Ok(Box::new(AmplitudeDampingOnly(simulator)))
+    }
+}
+
+#[test]
+fn channels_decay_coherences_at_t2() {
+    let times = RelaxationTimes::new(100.0, 150.0).unwrap();
+    let time_ns = 40.0;
+    let gamma = times.decay_probability(time_ns);
+    let lambda = times.phase_damping_parameter(time_ns);
+    assert_relative_eq!(gamma, 1.0 - (-0.4f64).exp());
+    // Amplitude damping scales coherences by sqrt(1 - gamma), and phase
+    // damping by sqrt(1 - lambda).
+    assert_relative_eq!(
+        ((1.0 - gamma) * (1.0 - lambda)).sqrt(),
+        (-time_ns / 150.0f64).exp()
+    );
+
+    // At the T2 = 2 T1 limit, amplitude damping alone accounts for T2.
+    let times = RelaxationTimes::new(100.0, 200.0).unwrap();
+    assert_relative_eq!(times.phase_damping_parameter(time_ns), 0.0);
+
+    let ideal = RelaxationTimes::new(f64::INFINITY, f64::INFINITY).unwrap();
+    assert_eq!(ideal.decay_probability(time_ns), 0.0);
+    assert_eq!(ideal.phase_damping_parameter(time_ns), 0.0);
+}
+
+#[test]
+fn trajectories_decay_coherences_at_t2_up_to_t1() {
+    let time_ns = 40.0;
+    let times = RelaxationTimes::new(100.0, 50.0).unwrap();
+    let gamma = times.decay_probability(time_ns);
+    let p_z = times.trajectory_dephasing_probability(time_ns);
+    // Jumps scale coherences by 1 - gamma, and Z faults by 1 - 2 p_z.
+    assert_relative_eq!(
+        (1.0 - gamma) * (1.0 - 2.0 * p_z),
+        (-time_ns / 50.0f64).exp()
+    );
+
+    let times = RelaxationTimes::new(100.0, 150.0).unwrap();
+    assert_eq!(times.trajectory_dephasing_probability(time_ns), 0.0);
+
+    // Pure dephasing without relaxation.
+    let times = RelaxationTimes::new(f64::INFINITY, 50.0).unwrap();
+    assert_relative_eq!(
+        1.0 - 2.0 * times.trajectory_dephasing_probability(time_ns),
+        (-time_ns / 50.0f64).exp()
+    );
+}
+
+#[test]
+fn qubit_times_override_the_default() {
+    let default = RelaxationTimes::new(100.0, 100.0).unwrap();
+    let times = parse_qubit_times("1:200:300, 3:inf:50", 4, default).unwrap();
+    assert_eq!(times[0], default);
+    assert_eq!(times[1], RelaxationTimes::new(200.0, 300.0).unwrap());
+    assert_eq!(times[2], default);
+    assert_eq!(times[3].t1_ns, f64::INFINITY);
+    assert_eq!(times[3].t2_ns, 50.0);
+    assert_eq!(parse_qubit_times("", 2, default).unwrap(), vec![default; 2]);
+
+    assert!(parse_qubit_times("0:100", 2, default).is_err());
+    assert!(parse_qubit_times("2:100:100", 2, default).is_err());
+    assert!(parse_qubit_times("0:a:100", 2, default).is_err());
+    assert!(parse_qubit_times("0:100:300", 2, default).is_err());
+    assert!(parse_qubit_times("0:0:100", 2, default).is_err());
+}
+
+#[test]
+fn channels_are_applied_where_supported() {
+    let (mut error_model, recording) = recorded_relaxation(&["--t1-ns=1000", "--t2-ns=1000"], true);
+    run_timed_batch(
+        &mut error_model,
+        0,
+        0,
+        vec![Operation::QAlloc { qubit_id: 0 }],
+    )
+    .unwrap();
+    run_timed_batch(&mut error_model, 1000, 10, vec![flip()]).unwrap();
+    let [
+        RecordedOperation::QAlloc { qubit: 0 },
+        // Support for both channels is probed before either is applied.
+        RecordedOperation::AmplitudeDamping {
+            qubit: 0,
+            gamma: 0.0,
+        },
+        RecordedOperation::PhaseDamping {
+            qubit: 0,
+            lambda: 0.0,
+        },
+        RecordedOperation::AmplitudeDamping { qubit: 0, gamma },
+        RecordedOperation::PhaseDamping { qubit: 0, lambda },
+        RecordedOperation::Rxy { qubit: 0, .. },
+    ] = recording.take()[..]
+    else {
+        panic!("unexpected operations");
+    };
+    // Noise covers the idle time and the duration of the gate.
+    assert_relative_eq!(gamma, 1.0 - (-1.01f64).exp());
+    assert_relative_eq!(
+        ((1.0 - gamma) * (1.0 - lambda)).sqrt(),
+        (-1.01f64).exp(),
+        epsilon = 1e-12
+    );
+    assert!(exact_channels(&mut error_model));
+    assert_eq!(realised_decays(&mut error_model), 0);
+}
+
+#[test]
+fn trajectories_are_used_without_channels() {
+    // Either the simulator lacks channels, or trajectories are requested.
+    for (args, channels) in [(vec![], false), (vec!["--trajectories"], true)] {
+        let args = [&["--t1-ns=1", "--t2-ns=1"], &args[..]].concat();
+        let (mut error_model, recording) = recorded_relaxation(&args, channels);
+        run_timed_batch(
+            &mut error_model,
+            0,
+            0,
+            vec![Operation::QAlloc { qubit_id: 0 }],
+        )
+        .unwrap();
+        run_timed_batch(&mut error_model, 0, 0, vec![flip()]).unwrap();
+        // After many multiples of T1 the excited qubit is sure to decay,
+        // which is found by measuring it and flipping it back to |0>.
+        run_timed_batch(&mut error_model, 1000, 10, vec![measure()]).unwrap();
+        assert_eq!(
+            recording.take(),
+            vec![
+                RecordedOperation::QAlloc { qubit: 0 },
+                RecordedOperation::Rxy {
+                    qubit: 0,
+                    theta: PI,
+                    phi: 0.0
+                },
+                RecordedOperation::Measure { qubit: 0 },
+                RecordedOperation::Rxy {
+                    qubit: 0,
+                    theta: PI,
+                    phi: 0.0
+                },
+                RecordedOperation::Measure { qubit: 0 },
+            ]
+        );
+        assert!(!exact_channels(&mut error_model));
+        assert_eq!(expected_decays(&mut error_model), 1.0);
+        assert_eq!(realised_decays(&mut error_model), 1);
+    }
+}
+
+#[test]
+fn partial_channel_support_falls_back_to_trajectories() {
+    let recording_factory = Arc::new(RecordingSimulatorFactory {
+        channels: true,
+        ..Default::default()
+    });
+    let recording = recording_factory.recording.clone();
+    let mut error_model = relaxation(
+        &["--t1-ns=1", "--t2-ns=1"],
+        Arc::new(AmplitudeDampingOnlyFactory(recording_factory)),
+    );
+    run_timed_batch(
+        &mut error_model,
+        0,
+        0,
+        vec![Operation::QAlloc { qubit_id: 0 }],
+    )
+    .unwrap();
+    run_timed_batch(&mut error_model, 0, 0, vec![flip()]).unwrap();
+    recording.take();
+    run_timed_batch(&mut error_model, 1000, 10, vec![measure()]).unwrap();
+    // Only the no-op probe reaches the simulator's amplitude damping channel,
+    // so relaxation is not applied twice.
+    let operations = recording.take();
+    assert_eq!(
+        operations[0],
+        RecordedOperation::AmplitudeDamping {
+            qubit: 0,
+            gamma: 0.0
+        }
+    );
+    assert!(
+        operations[1..]
+            .iter()
+            .all(|op| !matches!(op, RecordedOperation::AmplitudeDamping { .. }))
+    );
+    assert!(!exact_channels(&mut error_model));
+    assert_eq!(realised_decays(&mut error_model), 1);
+}
+
+#[test]
+fn expected_decays_accumulate_over_timed_batches() {
+    let (mut error_model, _) = recorded_relaxation(&["--t1-ns=1000", "--t2-ns=2000"], true);
+    run_timed_batch(
+        &mut error_model,
+        0,
+        0,
+        vec![Operation::QAlloc { qubit_id: 0 }],
+    )
+    .unwrap();
+    // The qubit is in |0> for the first 100ns, so cannot decay.
+    run_timed_batch(&mut error_model, 0, 100, vec![flip()]).unwrap();
+    assert_eq!(expected_decays(&mut error_model), 0.0);
+    let rz = Operation::RZGate {
+        qubit_id: 0,
+        theta: 1.0,
+    };
+    run_timed_batch(&mut error_model, 1100, 0, vec![rz]).unwrap();
+    let after_first = 1.0 - (-1.0f64).exp();
+    assert_relative_eq!(expected_decays(&mut error_model), after_first);
+
+    // Time while the qubit is freed is not counted.
+    run_timed_batch(
+        &mut error_model,
+        1200,
+        0,
+        vec![Operation::QFree { qubit_id: 0 }],
+    )
+    .unwrap();
+    run_timed_batch(
+        &mut error_model,
+        5000,
+        0,
+        vec![Operation::QAlloc { qubit_id: 0 }],
+    )
+    .unwrap();
+    run_timed_batch(&mut error_model, 5000, 500, vec![measure()]).unwrap();
+    assert_relative_eq!(
+        expected_decays(&mut error_model),
+        after_first + 1.0 - (-0.5f64).exp()
+    );
+    // Decays through channels are not sampled, so none are realised.
+    assert_eq!(realised_decays(&mut error_model), 0);
+
+    error_model.shot_start(1, 1, 1).unwrap();
+    assert_eq!(expected_decays(&mut error_model), 0.0);
+}
@@ -62,10 +62,15 @@ impl SimpleLeakageErrorModel {
         }
         Ok(())
     }
-    fn spread_leakage(&mut self, qubit_1: u64, qubit_2: u64) -> Result<()> {
-        if self.is_leaked(qubit_1)? ^ self.is_leaked(qubit_2)? {
-            self.leak(qubit_1)?;
-            self.leak(qubit_2)?;
+    fn spread_leakage(&mut self, qubits: &[u64]) -> Result<()> {
+        let mut leaked = 0;
+        for &qubit in qubits {
+            leaked += self.is_leaked(qubit)? as usize;
+        }
+        if leaked > 0 && leaked < qubits.len() {
+            for &qubit in qubits {
+                self.leak(qubit)?;
+            }
             self.stats.interaction_leaks += 1;
         }
         Ok(())
@@ -80,6 +85,14 @@ impl ErrorModelInterface for SimpleLeakageErrorModel {
         self.stats = Stats::default();
         Ok(())
     }
+    fn plan_shots(
+        &mut self,
+        shot_ids: &[u64],
+        _seeds: &[u64],
+        simulator_seeds: &[u64],
+    ) -> Result<()> {
+        self.simulator.plan_shots(shot_ids, simulator_seeds)
+    }
     fn shot_end(&mut self) -> Result<()> {
         self.simulator.shot_end()?;
         Ok(())
@@ -112,9 +125,36 @@ impl ErrorModelInterface for SimpleLeakageErrorModel {
                 } => {
                     self.maybe_leak(qubit_id_1)?;
                     self.maybe_leak(qubit_id_2)?;
-                    self.spread_leakage(qubit_id_1, qubit_id_2)?;
+                    self.spread_leakage(&[qubit_id_1, qubit_id_2])?;
                     self.simulator.rzz(qubit_id_1, qubit_id_2, theta)?;
                 }
+                Operation::UGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                    lambda,
+                } => {
+                    self.maybe_leak(qubit_id)?;
+                    self.simulator.u(qubit_id, theta, phi, lambda)?;
+                }
+                Operation::TwoQubitUnitaryGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    matrix,
+                } => {
+                    self.maybe_leak(qubit_id_1)?;
+                    self.maybe_leak(qubit_id_2)?;
+                    self.spread_leakage(&[qubit_id_1, qubit_id_2])?;
+                    self.simulator
+                        .two_qubit_unitary(qubit_id_1, qubit_id_2, &matrix)?;
+                }
+                Operation::DiagonalPhaseGate { qubit_ids, phases } => {
+                    for &qubit_id in qubit_ids.iter() {
+                        self.maybe_leak(qubit_id)?;
+                    }
+                    self.spread_leakage(&qubit_ids)?;
+                    self.simulator.diagonal_phase(&qubit_ids, &phases)?;
+                }
                 Operation::Measure {
                     qubit_id,
                     result_id,
@@ -134,7 +174,7 @@ impl ErrorModelInterface for SimpleLeakageErrorModel {
                     let measurement = if self.leak_register[qubit_id as usize] {
                         2
                     } else {
-                        self.simulator.measure(qubit_id)? as u64
+                        self.simulator.measure_leaked(qubit_id)?
                     };
                     results.set_u64_result(result_id, measurement);
                 }
@@ -145,6 +185,12 @@ impl ErrorModelInterface for SimpleLeakageErrorModel {
                 Operation::Custom { .. } => {
                     // Passively ignore custom operations
                 }
+                Operation::QAlloc { qubit_id } => {
+                    self.simulator.qalloc(qubit_id)?;
+                }
+                Operation::QFree { qubit_id } => {
+                    self.simulator.qfree(qubit_id)?;
+                }
             }
         }
         Ok(results)
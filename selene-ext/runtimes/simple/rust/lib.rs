@@ -1,3 +1,6 @@
+#[cfg(test)]
+mod tests;
+
 use std::collections::VecDeque;
 
 use anyhow::{Result, bail};
@@ -40,6 +43,9 @@ struct FutureResult {
 struct SimpleRuntime {
     qubits: Vec<QubitStatus>,
     operation_queue: VecDeque<BatchOperation>,
+    // Allocation notifications take no time, so rather than being given
+    // batches of their own, they are delivered with the next batch.
+    allocation_notifications: Vec<Operation>,
     future_results: Vec<FutureResult>,
     start: selene_core::time::Instant,
     params: Params,
@@ -50,6 +56,7 @@ impl SimpleRuntime {
         Self {
             qubits: vec![QubitStatus::Free; n_qubits as usize],
             operation_queue: VecDeque::with_capacity(10000),
+            allocation_notifications: Vec::new(),
             future_results: Vec::with_capacity(1000),
             start,
             params,
@@ -60,16 +67,25 @@ impl SimpleRuntime {
         let duration_ns = match op {
             Operation::RXYGate { .. } => self.params.duration_ns_rxy,
             Operation::RZZGate { .. } => self.params.duration_ns_rzz,
+            Operation::UGate { .. } => self.params.duration_ns_rxy,
+            Operation::TwoQubitUnitaryGate { .. } => self.params.duration_ns_rzz,
+            // Single-qubit phases are free, like RZ, whereas entangling phases
+            // take the time of an RZZ gate.
+            Operation::DiagonalPhaseGate { ref qubit_ids, .. } if qubit_ids.len() > 1 => {
+                self.params.duration_ns_rzz
+            }
             Operation::Measure { .. } => self.params.duration_ns_measure,
             Operation::Reset { .. } => self.params.duration_ns_reset,
             Operation::MeasureLeaked { .. } => self.params.duration_ns_measure_leaked,
             _ => 0,
         };
-        self.operation_queue.push_back(BatchOperation::new(
-            vec![op],
-            self.start,
-            duration_ns.into(),
-        ));
+        let ops = self
+            .allocation_notifications
+            .drain(..)
+            .chain(std::iter::once(op))
+            .collect();
+        self.operation_queue
+            .push_back(BatchOperation::new(ops, self.start, duration_ns.into()));
         self.start += duration_ns.into();
     }
 }
@@ -77,6 +93,7 @@ impl SimpleRuntime {
 impl RuntimeInterface for SimpleRuntime {
     fn exit(&mut self) -> Result<()> {
         self.operation_queue.clear();
+        self.allocation_notifications.clear();
         self.qubits.clear();
         self.future_results.clear();
         Ok(())
@@ -92,17 +109,21 @@ impl RuntimeInterface for SimpleRuntime {
     fn shot_end(&mut self) -> Result<()> {
         self.qubits = vec![QubitStatus::Free; self.qubits.len()];
         self.operation_queue.clear();
+        self.allocation_notifications.clear();
         self.future_results.clear();
         Ok(())
     }
-    fn global_barrier(&mut self, _sleep_ns: u64) -> Result<()> {
-        // This runtime isn't lazy, so a barrier is not relevant
-        // to its operation.
+    fn global_barrier(&mut self, sleep_ns: u64) -> Result<()> {
+        // This runtime isn't lazy, so a barrier is only relevant
+        // to its operation through the time it sleeps for.
+        self.start += selene_core::time::Duration::from(sleep_ns);
         Ok(())
     }
-    fn local_barrier(&mut self, _qubits: &[u64], _sleep_ns: u64) -> Result<()> {
-        // This runtime isn't lazy, so a barrier is not relevant
-        // to its operation.
+    fn local_barrier(&mut self, _qubits: &[u64], sleep_ns: u64) -> Result<()> {
+        // This runtime isn't lazy, so a barrier is only relevant
+        // to its operation through the time it sleeps for. Operations
+        // run one at a time, so every qubit waits.
+        self.start += selene_core::time::Duration::from(sleep_ns);
         Ok(())
     }
     // Allocation
@@ -110,6 +131,8 @@ impl RuntimeInterface for SimpleRuntime {
         for (i, qubit) in self.qubits.iter_mut().enumerate() {
             if *qubit == QubitStatus::Free {
                 *qubit = QubitStatus::Active;
+                self.allocation_notifications
+                    .push(Operation::QAlloc { qubit_id: i as u64 });
                 return Ok(i as u64);
             }
         }
@@ -120,6 +143,8 @@ impl RuntimeInterface for SimpleRuntime {
             bail!("freeing out-of-bounds qubit {qubit_id}")
         } else {
             self.qubits[qubit_id as usize] = QubitStatus::Free;
+            self.allocation_notifications
+                .push(Operation::QFree { qubit_id });
             Ok(())
         }
     }
@@ -161,6 +186,61 @@ impl RuntimeInterface for SimpleRuntime {
         self.push(Operation::RZGate { qubit_id, theta });
         Ok(())
     }
+    fn u_gate(&mut self, qubit_id: u64, theta: f64, phi: f64, lambda: f64) -> Result<()> {
+        if qubit_id >= self.qubits.len() as u64 {
+            bail!("applying u gate to out-of-bounds qubit {qubit_id}");
+        }
+        let QubitStatus::Active = self.qubits[qubit_id as usize] else {
+            bail!("Qubit {qubit_id} is not active");
+        };
+        self.push(Operation::UGate {
+            qubit_id,
+            theta,
+            phi,
+            lambda,
+        });
+        Ok(())
+    }
+    fn two_qubit_unitary_gate(
+        &mut self,
+        qubit_id_1: u64,
+        qubit_id_2: u64,
+        matrix: &[f64; 32],
+    ) -> Result<()> {
+        if qubit_id_1 >= self.qubits.len() as u64 {
+            bail!("applying two-qubit unitary to out-of-bounds qubit1 {qubit_id_1}");
+        }
+        if qubit_id_2 >= self.qubits.len() as u64 {
+            bail!("applying two-qubit unitary to out-of-bounds qubit2 {qubit_id_2}");
+        }
+        let QubitStatus::Active = self.qubits[qubit_id_1 as usize] else {
+            bail!("Qubit {qubit_id_1} is not active");
+        };
+        let QubitStatus::Active = self.qubits[qubit_id_2 as usize] else {
+            bail!("Qubit {qubit_id_2} is not active");
+        };
+        self.push(Operation::TwoQubitUnitaryGate {
+            qubit_id_1,
+            qubit_id_2,
+            matrix: Box::new(*matrix),
+        });
+        Ok(())
+    }
+    fn diagonal_phase_gate(&mut self, qubit_ids: &[u64], phases: &[f64]) -> Result<()> {
+        for &qubit_id in qubit_ids {
+            if qubit_id >= self.qubits.len() as u64 {
+                bail!("applying diagonal phase gate to out-of-bounds qubit {qubit_id}");
+            }
+            let QubitStatus::Active = self.qubits[qubit_id as usize] else {
+                bail!("Qubit {qubit_id} is not active");
+            };
+        }
+        self.push(Operation::DiagonalPhaseGate {
+            qubit_ids: qubit_ids.into(),
+            phases: phases.into(),
+        });
+        Ok(())
+    }
     // Lifetime ops
     fn measure(&mut self, qubit_id: u64) -> Result<u64> {
         if qubit_id >= self.qubits.len() as u64 {
@@ -260,6 +340,18 @@ impl RuntimeInterface for SimpleRuntime {
         self.start += selene_core::time::Duration::from(delay_ns);
         Ok(())
     }
+    fn custom_call(&mut self, tag: u64, data: &[u8]) -> Result<u64> {
+        // Custom calls are passed on to the error model as custom operations
+        // whatever their tag, as the runtime cannot know which tags the error
+        // model understands. Error models ignore custom operations they do not
+        // recognise, so an unrecognised call does nothing rather than failing,
+        // and every call returns 0.
+        self.push(Operation::Custom {
+            custom_tag: tag as usize,
+            data: data.into(),
+        });
+        Ok(0)
+    }
 }
 
 #[derive(Default)]
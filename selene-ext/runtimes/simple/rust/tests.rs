@@ -0,0 +1,90 @@
+use crate::{SimpleRuntime, SimpleRuntimeFactory};
+use selene_core::runtime::interface::RuntimeInterfaceFactory;
+use selene_core::runtime::{Operation, RuntimeInterface};
+use std::sync::Arc;
+
+fn runtime(n_qubits: u64) -> Box<SimpleRuntime> {
+    Arc::new(SimpleRuntimeFactory)
+        .init(
+            n_qubits,
+            0.into(),
+            &[
+                "",
+                "--duration-ns-rxy=10",
+                "--duration-ns-rzz=20",
+                "--duration-ns-measure=30",
+                "--duration-ns-reset=40",
+                "--duration-ns-measure-leaked=50",
+            ],
+        )
+        .unwrap()
+}
+
+/// The start time and operations of every batch emitted so far.
+fn batches(runtime: &mut SimpleRuntime) -> Vec<(u64, Vec<Operation>)> {
+    std::iter::from_fn(|| runtime.get_next_operations().unwrap())
+        .map(|batch| (batch.start().into(), batch.into_iter().collect()))
+        .collect()
+}
+
+#[test]
+fn barriers_and_delays_advance_the_clock() {
+    let mut runtime = runtime(2);
+    let q0 = runtime.qalloc().unwrap();
+    let q1 = runtime.qalloc().unwrap();
+    runtime.rxy_gate(q0, 1.0, 0.0).unwrap();
+    runtime.global_barrier(100).unwrap();
+    runtime.rxy_gate(q1, 1.0, 0.0).unwrap();
+    runtime.local_barrier(&[q0], 5).unwrap();
+    runtime.rzz_gate(q0, q1, 1.0).unwrap();
+    runtime.simulate_delay(7).unwrap();
+    runtime.reset(q1).unwrap();
+
+    let batches = batches(&mut runtime);
+    let starts: Vec<u64> = batches.iter().map(|(start, _)| *start).collect();
+    assert_eq!(starts, vec![0, 110, 125, 152]);
+    // The allocations are delivered with the first batch.
+    assert_eq!(
+        batches[0].1[..2],
+        [
+            Operation::QAlloc { qubit_id: 0 },
+            Operation::QAlloc { qubit_id: 1 }
+        ]
+    );
+}
+
+#[test]
+fn extended_gates_require_active_qubits() {
+    let mut runtime = runtime(3);
+    let q0 = runtime.qalloc().unwrap();
+    let q1 = runtime.qalloc().unwrap();
+    let identity: [f64; 32] = std::array::from_fn(|i| if i % 10 == 0 { 1.0 } else { 0.0 });
+    runtime.two_qubit_unitary_gate(q0, q1, &identity).unwrap();
+    runtime.diagonal_phase_gate(&[q0, q1], &[0.0; 4]).unwrap();
+    assert!(runtime.two_qubit_unitary_gate(q0, 2, &identity).is_err());
+    assert!(runtime.diagonal_phase_gate(&[2], &[0.0; 2]).is_err());
+    runtime.qfree(q1).unwrap();
+    assert!(runtime.two_qubit_unitary_gate(q0, q1, &identity).is_err());
+    assert!(runtime.diagonal_phase_gate(&[q0, q1], &[0.0; 4]).is_err());
+}
+
+#[test]
+fn custom_calls_are_forwarded_in_order() {
+    let mut runtime = runtime(1);
+    let q0 = runtime.qalloc().unwrap();
+    runtime.rxy_gate(q0, 1.0, 0.0).unwrap();
+    // Any tag is forwarded, whether or not the error model understands it.
+    assert_eq!(runtime.custom_call(7, &q0.to_le_bytes()).unwrap(), 0);
+    runtime.rxy_gate(q0, 1.0, 0.0).unwrap();
+
+    let batches = batches(&mut runtime);
+    let starts: Vec<u64> = batches.iter().map(|(start, _)| *start).collect();
+    assert_eq!(starts, vec![0, 10, 10]);
+    assert_eq!(
+        batches[1].1,
+        vec![Operation::Custom {
+            custom_tag: 7,
+            data: q0.to_le_bytes().into(),
+        }]
+    );
+}
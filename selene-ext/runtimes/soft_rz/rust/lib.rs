@@ -1,3 +1,6 @@
+#[cfg(test)]
+mod tests;
+
 use std::collections::VecDeque;
 
 use anyhow::{Result, bail};
@@ -42,8 +45,15 @@ struct SoftRZRuntime {
     qubits: Vec<QubitStatus>,
     operation_queue: VecDeque<BatchOperation>,
     flush_size: usize,
+    // Allocation notifications that have not yet been placed in a batch.
+    // They take no time, so rather than being given batches of their own,
+    // they are placed in the batch of the next operation that needs them.
+    pending_allocations: Vec<Operation>,
     future_results: Vec<FutureResult>,
     start: selene_core::time::Instant,
+    // Batches that start before this point cannot take new operations, so
+    // that delays are not undone by scheduling later operations early.
+    earliest_append: selene_core::time::Instant,
     params: Params,
 }
 
@@ -58,8 +68,10 @@ impl SoftRZRuntime {
             qubits: vec![QubitStatus::Free; n_qubits as usize],
             operation_queue: VecDeque::with_capacity(10000),
             flush_size: 0,
+            pending_allocations: Vec::new(),
             future_results: Vec::with_capacity(1000),
             start,
+            earliest_append: start,
             params,
         }
     }
@@ -71,6 +83,9 @@ impl SoftRZRuntime {
         // and the max batch size.
         let mut append_idx = self.operation_queue.len();
         for (i, operation) in self.operation_queue.iter().enumerate().rev() {
+            if u64::from(operation.start()) < u64::from(self.earliest_append) {
+                break;
+            }
             let append_result = self.append_search_impl(&op, operation);
             if append_result.can_append {
                 append_idx = i;
@@ -80,29 +95,93 @@ impl SoftRZRuntime {
             }
         }
 
+        // A qubit's allocation must be delivered before the first operation on
+        // it. Pending frees are for qubits that have no operations left in the
+        // queue, so they can be delivered with any batch.
+        let op_qubits = op.get_qubit_ids();
+        let (notifications, pending): (Vec<Operation>, Vec<Operation>) =
+            std::mem::take(&mut self.pending_allocations)
+                .into_iter()
+                .partition(|notification| match notification {
+                    Operation::QAlloc { qubit_id } => op_qubits.contains(qubit_id),
+                    _ => true,
+                });
+        self.pending_allocations = pending;
+
         if append_idx < self.operation_queue.len() {
             // We found a batch to append to!
-            self.operation_queue[append_idx].add_operation(op);
+            let batch = &mut self.operation_queue[append_idx];
+            for notification in notifications {
+                batch.add_operation(notification);
+            }
+            batch.add_operation(op);
         } else {
             // We didn't find a batch to append to, so we need to create a new batch for this operation.
             let duration = match op {
                 Operation::RXYGate { .. } => self.params.duration_ns_rxy,
                 Operation::RZZGate { .. } => self.params.duration_ns_rzz,
+                Operation::UGate { .. } => self.params.duration_ns_rxy,
+                Operation::TwoQubitUnitaryGate { .. } => self.params.duration_ns_rzz,
+                Operation::DiagonalPhaseGate { ref qubit_ids, .. } if qubit_ids.len() > 1 => {
+                    self.params.duration_ns_rzz
+                }
                 Operation::Measure { .. } => self.params.duration_ns_measure,
                 Operation::Reset { .. } => self.params.duration_ns_reset,
                 Operation::MeasureLeaked { .. } => self.params.duration_ns_measure_leaked,
                 _ => 0, // Unhandled ops have no duration, since we don't know their semantics.
             };
-            self.operation_queue.push_back(BatchOperation::new(
-                vec![op],
-                self.start,
-                duration.into(),
-            ));
+            let ops = notifications
+                .into_iter()
+                .chain(std::iter::once(op))
+                .collect();
+            self.operation_queue
+                .push_back(BatchOperation::new(ops, self.start, duration.into()));
             self.start += duration.into();
         }
     }
 
+    fn delay(&mut self, delay_ns: u64) {
+        if delay_ns > 0 {
+            self.start += selene_core::time::Duration::from(delay_ns);
+            self.earliest_append = self.start;
+        }
+    }
+
+    fn push_free_notification(&mut self, qubit_id: u64) {
+        // If the qubit was never used, neither notification needs to be sent.
+        let allocation = Operation::QAlloc { qubit_id };
+        if let Some(i) = self
+            .pending_allocations
+            .iter()
+            .position(|notification| *notification == allocation)
+        {
+            self.pending_allocations.remove(i);
+            return;
+        }
+        // Otherwise the free must follow the last operation on the qubit.
+        let notification = Operation::QFree { qubit_id };
+        match self
+            .operation_queue
+            .iter_mut()
+            .rev()
+            .find(|batch| batch.get_qubit_ids().contains(&qubit_id))
+        {
+            Some(batch) => batch.add_operation(notification),
+            None => self.pending_allocations.push(notification),
+        }
+    }
+
     fn append_search_impl(&self, op: &Operation, batch: &BatchOperation) -> AppendSearchResult {
+        // custom operations may act on any qubit, so nothing can be moved before them.
+        if batch
+            .iter_ops()
+            .any(|batch_op| matches!(batch_op, Operation::Custom { .. }))
+        {
+            return AppendSearchResult {
+                can_append: false,
+                can_continue_search: false,
+            };
+        }
         // first, check if the current batch operates intersects op's qubits.
         // if it does, we can't append and we can't continue searching due to causality.
         if batch
@@ -117,23 +196,34 @@ impl SoftRZRuntime {
             };
         }
         // next, check if there's space in this batch to append op. If there isn't, we can't append, but we can continue searching for an earlier batch that op might fit into.
-        if batch.len() >= self.params.max_batch_size {
+        let batch_size = batch.iter_ops().filter(|op| !op.is_allocation()).count();
+        if batch_size >= self.params.max_batch_size {
             return AppendSearchResult {
                 can_append: false,
                 can_continue_search: true,
             };
         }
         // next, check the type of the operations in this batch. If they aren't the same type as op, we can't append, but we can continue searching for an earlier batch that op might fit into.
-        let same_type = batch.iter_ops().all(|batch_op| match (batch_op, op) {
-            (Operation::RXYGate { .. }, Operation::RXYGate { .. }) => true,
-            (Operation::RZGate { .. }, Operation::RZGate { .. }) => true,
-            (Operation::RZZGate { .. }, Operation::RZZGate { .. }) => true,
-            (Operation::Measure { .. }, Operation::Measure { .. }) => true,
-            (Operation::MeasureLeaked { .. }, Operation::MeasureLeaked { .. }) => true,
-            (Operation::Reset { .. }, Operation::Reset { .. }) => true,
-            // don't allow custom ops to be batched, since we don't know their semantics
-            _ => false,
-        });
+        // Allocation notifications are not operations in their own right, so
+        // they don't affect which operations can be batched together.
+        let same_type = batch
+            .iter_ops()
+            .filter(|batch_op| !batch_op.is_allocation())
+            .all(|batch_op| match (batch_op, op) {
+                (Operation::RXYGate { .. }, Operation::RXYGate { .. }) => true,
+                (Operation::RZGate { .. }, Operation::RZGate { .. }) => true,
+                (Operation::RZZGate { .. }, Operation::RZZGate { .. }) => true,
+                (Operation::Measure { .. }, Operation::Measure { .. }) => true,
+                (Operation::MeasureLeaked { .. }, Operation::MeasureLeaked { .. }) => true,
+                (Operation::Reset { .. }, Operation::Reset { .. }) => true,
+                (Operation::UGate { .. }, Operation::UGate { .. }) => true,
+                (Operation::TwoQubitUnitaryGate { .. }, Operation::TwoQubitUnitaryGate { .. }) => {
+                    true
+                }
+                (Operation::DiagonalPhaseGate { .. }, Operation::DiagonalPhaseGate { .. }) => true,
+                // don't allow custom ops to be batched, since we don't know their semantics
+                _ => false,
+            });
         if !same_type {
             AppendSearchResult {
                 can_append: false,
@@ -153,6 +243,7 @@ impl RuntimeInterface for SoftRZRuntime {
         self.operation_queue.clear();
         self.qubits.clear();
         self.flush_size = 0;
+        self.pending_allocations.clear();
         self.future_results.clear();
         Ok(())
     }
@@ -176,14 +267,16 @@ impl RuntimeInterface for SoftRZRuntime {
         self.qubits = vec![QubitStatus::Free; self.qubits.len()];
         self.operation_queue.clear();
         self.flush_size = 0;
+        self.pending_allocations.clear();
         self.future_results.clear();
         Ok(())
     }
-    fn global_barrier(&mut self, _sleep_ns: u64) -> Result<()> {
+    fn global_barrier(&mut self, sleep_ns: u64) -> Result<()> {
         self.flush_size = self.operation_queue.len();
+        self.delay(sleep_ns);
         Ok(())
     }
-    fn local_barrier(&mut self, qubits: &[u64], _sleep_ns: u64) -> Result<()> {
+    fn local_barrier(&mut self, qubits: &[u64], sleep_ns: u64) -> Result<()> {
         // search backwards through the operation queue for the last operation that uses any of the barrier's qubits.
         // All operations up to and including that operation can be flushed.
         //
@@ -199,6 +292,9 @@ impl RuntimeInterface for SoftRZRuntime {
         if let Some((i, _)) = found {
             self.flush_size = self.flush_size.max(i + 1);
         }
+        // The runtime keeps a single clock, so sleeping on some qubits delays
+        // every subsequent operation.
+        self.delay(sleep_ns);
         Ok(())
     }
     // Allocation
@@ -206,6 +302,8 @@ impl RuntimeInterface for SoftRZRuntime {
         for (i, qubit) in self.qubits.iter_mut().enumerate() {
             if *qubit == QubitStatus::Free {
                 *qubit = QubitStatus::Active { phase: 0.0 };
+                self.pending_allocations
+                    .push(Operation::QAlloc { qubit_id: i as u64 });
                 return Ok(i as u64);
             }
         }
@@ -216,6 +314,7 @@ impl RuntimeInterface for SoftRZRuntime {
             bail!("freeing out-of-bounds qubit {qubit_id}")
         } else {
             self.qubits[qubit_id as usize] = QubitStatus::Free;
+            self.push_free_notification(qubit_id);
             Ok(())
         }
     }
@@ -262,6 +361,76 @@ impl RuntimeInterface for SoftRZRuntime {
         };
         Ok(())
     }
+    fn u_gate(&mut self, qubit_id: u64, theta: f64, phi: f64, lambda: f64) -> Result<()> {
+        if qubit_id >= self.qubits.len() as u64 {
+            bail!("applying u gate to out-of-bounds qubit {qubit_id}");
+        }
+        let QubitStatus::Active { phase } = self.qubits[qubit_id as usize] else {
+            bail!("Qubit {qubit_id} is not active");
+        };
+        // U(θ, φ, λ) RZ(phase) = RZ(φ) U(θ, 0, λ + phase) up to a global phase,
+        // so the accumulated phase is consumed by λ and φ becomes the new phase.
+        self.push(Operation::UGate {
+            qubit_id,
+            theta,
+            phi: 0.0,
+            lambda: lambda + phase,
+        });
+        self.qubits[qubit_id as usize] = QubitStatus::Active { phase: phi };
+        Ok(())
+    }
+    fn two_qubit_unitary_gate(
+        &mut self,
+        qubit_id_1: u64,
+        qubit_id_2: u64,
+        matrix: &[f64; 32],
+    ) -> Result<()> {
+        let mut phases = [0.0; 2];
+        for (phase, qubit_id) in phases.iter_mut().zip([qubit_id_1, qubit_id_2]) {
+            if qubit_id >= self.qubits.len() as u64 {
+                bail!("applying two-qubit unitary to out-of-bounds qubit {qubit_id}");
+            }
+            let QubitStatus::Active { phase: qubit_phase } = self.qubits[qubit_id as usize] else {
+                bail!("Qubit {qubit_id} is not active");
+            };
+            *phase = qubit_phase;
+            self.qubits[qubit_id as usize] = QubitStatus::Active { phase: 0.0 };
+        }
+        // The accumulated phases are enacted by multiplying the matrix on the
+        // right by RZ(phase_1) ⊗ RZ(phase_2), scaling each column.
+        let mut matrix = Box::new(*matrix);
+        for col in 0..4 {
+            let angle = (0..2)
+                .map(|bit| phases[bit] * (((col >> bit) & 1) as f64 - 0.5))
+                .sum::<f64>();
+            let (sin, cos) = angle.sin_cos();
+            for row in 0..4 {
+                let index = 2 * (4 * row + col);
+                let (re, im) = (matrix[index], matrix[index + 1]);
+                matrix[index] = re * cos - im * sin;
+                matrix[index + 1] = re * sin + im * cos;
+            }
+        }
+        self.push(Operation::TwoQubitUnitaryGate {
+            qubit_id_1,
+            qubit_id_2,
+            matrix,
+        });
+        Ok(())
+    }
+    fn diagonal_phase_gate(&mut self, qubit_ids: &[u64], phases: &[f64]) -> Result<()> {
+        for &qubit_id in qubit_ids {
+            if qubit_id >= self.qubits.len() as u64 {
+                bail!("applying diagonal phase gate to out-of-bounds qubit {qubit_id}");
+            }
+        }
+        // Diagonal gates commute with the accumulated phases, which are left as they are.
+        self.push(Operation::DiagonalPhaseGate {
+            qubit_ids: qubit_ids.into(),
+            phases: phases.into(),
+        });
+        Ok(())
+    }
     // Lifetime ops
     fn measure(&mut self, qubit_id: u64) -> Result<u64> {
         if qubit_id >= self.qubits.len() as u64 {
@@ -369,9 +538,21 @@ impl RuntimeInterface for SoftRZRuntime {
         Ok(None)
     }
     fn simulate_delay(&mut self, delay_ns: u64) -> Result<()> {
-        self.start += selene_core::time::Duration::from(delay_ns);
+        self.delay(delay_ns);
         Ok(())
     }
+    fn custom_call(&mut self, tag: u64, data: &[u8]) -> Result<u64> {
+        // Custom calls are passed on to the error model as custom operations
+        // whatever their tag, as the runtime cannot know which tags the error
+        // model understands. Error models ignore custom operations they do not
+        // recognise, so an unrecognised call does nothing rather than failing,
+        // and every call returns 0.
+        self.push(Operation::Custom {
+            custom_tag: tag as usize,
+            data: data.into(),
+        });
+        Ok(0)
+    }
 }
 
 #[derive(Default)]
@@ -0,0 +1,96 @@
+use crate::{SoftRZRuntime, SoftRZRuntimeFactory};
+use selene_core::runtime::interface::RuntimeInterfaceFactory;
+use selene_core::runtime::{Operation, RuntimeInterface};
+use std::sync::Arc;
+
+fn runtime(n_qubits: u64) -> Box<SoftRZRuntime> {
+    Arc::new(SoftRZRuntimeFactory)
+        .init(
+            n_qubits,
+            0.into(),
+            &[
+                "",
+                "--duration-ns-rxy=10",
+                "--duration-ns-rzz=20",
+                "--duration-ns-measure=30",
+                "--duration-ns-reset=40",
+                "--duration-ns-measure-leaked=50",
+                "--max-batch-size=8",
+            ],
+        )
+        .unwrap()
+}
+
+/// The start time and non-allocation operations of every batch flushed by a
+/// global barrier.
+fn batches(runtime: &mut SoftRZRuntime) -> Vec<(u64, Vec<Operation>)> {
+    runtime.global_barrier(0).unwrap();
+    std::iter::from_fn(|| runtime.get_next_operations().unwrap())
+        .map(|batch| {
+            let ops = batch.iter_ops().filter(|op| !op.is_allocation()).cloned();
+            (batch.start().into(), ops.collect())
+        })
+        .collect()
+}
+
+#[test]
+fn independent_operations_are_batched() {
+    let mut runtime = runtime(2);
+    let q0 = runtime.qalloc().unwrap();
+    let q1 = runtime.qalloc().unwrap();
+    runtime.rxy_gate(q0, 1.0, 0.0).unwrap();
+    runtime.rxy_gate(q1, 1.0, 0.0).unwrap();
+    let batches = batches(&mut runtime);
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].1.len(), 2);
+}
+
+#[test]
+fn operations_are_not_hoisted_across_a_delay() {
+    let mut runtime = runtime(2);
+    let q0 = runtime.qalloc().unwrap();
+    let q1 = runtime.qalloc().unwrap();
+    runtime.rxy_gate(q0, 1.0, 0.0).unwrap();
+    runtime.simulate_delay(100).unwrap();
+    // This could share the first batch, were it not for the delay.
+    runtime.rxy_gate(q1, 1.0, 0.0).unwrap();
+    runtime.global_barrier(5).unwrap();
+    runtime.rxy_gate(q0, 1.0, 0.0).unwrap();
+
+    let batches = batches(&mut runtime);
+    let starts: Vec<u64> = batches.iter().map(|(start, _)| *start).collect();
+    assert_eq!(starts, vec![0, 110, 125]);
+    assert!(batches.iter().all(|(_, ops)| ops.len() == 1));
+}
+
+#[test]
+fn operations_are_not_reordered_around_custom_calls() {
+    let mut runtime = runtime(2);
+    let q0 = runtime.qalloc().unwrap();
+    let q1 = runtime.qalloc().unwrap();
+    runtime.rxy_gate(q0, 1.0, 0.0).unwrap();
+    runtime.rzz_gate(q0, q1, 1.0).unwrap();
+    // The custom call gets a batch of its own, after the RZZ gate.
+    assert_eq!(runtime.custom_call(7, &q0.to_le_bytes()).unwrap(), 0);
+    // This could share the first batch, were it not for the custom call.
+    runtime.rxy_gate(q1, 1.0, 0.0).unwrap();
+
+    let batches = batches(&mut runtime);
+    let starts: Vec<u64> = batches.iter().map(|(start, _)| *start).collect();
+    assert_eq!(starts, vec![0, 10, 30, 30]);
+    assert_eq!(
+        batches[2].1,
+        vec![Operation::Custom {
+            custom_tag: 7,
+            data: q0.to_le_bytes().into(),
+        }]
+    );
+    assert_eq!(
+        batches[3].1,
+        vec![Operation::RXYGate {
+            qubit_id: q1,
+            theta: 1.0,
+            phi: 0.0,
+        }]
+    );
+}
@@ -0,0 +1,3 @@
+from .plugin import BitstringPlugin
+
+__all__ = ["BitstringPlugin"]
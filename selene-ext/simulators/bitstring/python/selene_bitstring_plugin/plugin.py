@@ -0,0 +1,54 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+
+from selene_core import Simulator
+
+
+@dataclass
+class BitstringPlugin(Simulator):
+    """
+    A plugin for simulating programs whose gates keep every qubit in the
+    computational basis, such as arithmetic and oracles. The state is tracked
+    as a single bitstring, so registers of millions of qubits can be simulated
+    for functional testing of classical subroutines.
+
+    Supported gates are RXY rotations by multiples of pi (bit flips), RZ, RZZ
+    and diagonal phase gates of any angle (which only contribute a global
+    phase), U gates with theta a multiple of pi, and two-qubit unitaries that
+    map the current basis state to a basis state. Any gate that would create
+    a superposition raises an error. Note that entangling gates such as CX are
+    only supported when they reach the simulator as two-qubit unitaries, as
+    their decomposition into RXY and RZZ gates passes through a superposition.
+
+    Attributes:
+        angle_threshold (float, default 1e-4): Angles within this distance of a
+            multiple of pi are treated as exact multiples of pi. Must be
+            greater than zero, as floating point errors can cause numerical
+            instability.
+    """
+
+    angle_threshold: float = 1e-4
+
+    def __post_init__(self):
+        assert self.angle_threshold > 0, (
+            "angle_threshold must be greater than zero to avoid numerical instability"
+        )
+
+    def get_init_args(self):
+        return [
+            f"--angle-threshold={self.angle_threshold}",
+        ]
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_bitstring_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_bitstring_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_bitstring_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
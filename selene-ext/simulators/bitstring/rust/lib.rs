@@ -0,0 +1,376 @@
+/// A bitstring simulator plugin for Selene, for functional testing of
+/// classical (reversible) logic on very large registers.
+//
+// The state is a single computational basis state, stored as one bit per
+// qubit, so memory scales linearly with the number of qubits and every
+// operation is O(1). This is exact for programs whose gates keep the register
+// in the computational basis:
+//
+// - RXY(θ, φ) where θ is a multiple of π, which is either the identity or a
+//   bit flip (X or Y up to a phase),
+// - RZ, RZZ and diagonal phase gates of any angle, which only contribute a
+//   phase,
+// - U(θ, φ, λ) where θ is a multiple of π, and two-qubit unitaries that map
+//   the current basis state to a single basis state (e.g. CX, SWAP).
+//
+// Phases are global for a basis state, so they are discarded. Any gate that
+// would create a superposition is an error, and names the gate responsible.
+// Note that entangling gates such as CX are only supported when they reach the
+// simulator as a two-qubit unitary, as their decomposition into RXY and RZZ
+// gates passes through a superposition.
+//
+// Noise channels are sampled in the same way as in the statevector simulator,
+// and are supported as long as each branch is a basis state.
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use selene_core::export_simulator_plugin;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::simulator::{
+    Pauli, SimulatorInterface, kraus_operator_size, validate_kraus_operators,
+    validate_pauli_channel,
+};
+use selene_core::utils::MetricValue;
+
+#[cfg(test)]
+mod tests;
+
+/// Matrix elements with a squared magnitude at or below this value are
+/// treated as zero when checking that an operation keeps a basis state.
+const AMPLITUDE_TOLERANCE: f64 = 1e-8;
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// Angles within this distance of a multiple of π are treated as exact
+    /// multiples of π.
+    #[arg(long)]
+    angle_threshold: f64,
+}
+
+pub struct BitstringSimulator {
+    /// The value of each qubit, 64 qubits per word.
+    bits: Vec<u64>,
+    n_qubits: u64,
+    angle_threshold_half: f64,
+    rng: Pcg64Mcg,
+    /// Snapshots by handle, or None once released.
+    snapshots: Vec<Option<Vec<u64>>>,
+}
+
+impl BitstringSimulator {
+    /// The slot of the snapshot with the given handle, which is None if the
+    /// snapshot has been released.
+    fn snapshot_slot(&mut self, handle: u64) -> Result<&mut Option<Vec<u64>>> {
+        let n_snapshots = self.snapshots.len();
+        self.snapshots.get_mut(handle as usize).ok_or_else(|| {
+            anyhow!(
+                "Snapshot {handle} does not exist. Only {n_snapshots} snapshots have been taken."
+            )
+        })
+    }
+
+    fn get(&self, q: u64) -> bool {
+        (self.bits[(q / 64) as usize] >> (q % 64)) & 1 == 1
+    }
+
+    fn set(&mut self, q: u64, value: bool) {
+        let word = &mut self.bits[(q / 64) as usize];
+        *word = (*word & !(1 << (q % 64))) | ((value as u64) << (q % 64));
+    }
+
+    fn flip(&mut self, q: u64) {
+        self.bits[(q / 64) as usize] ^= 1 << (q % 64);
+    }
+
+    /// The basis state of the given qubits, with the first qubit as the least
+    /// significant bit.
+    fn get_local(&self, qubits: &[u64]) -> usize {
+        qubits
+            .iter()
+            .enumerate()
+            .map(|(i, &q)| (self.get(q) as usize) << i)
+            .sum()
+    }
+
+    fn set_local(&mut self, qubits: &[u64], index: usize) {
+        for (i, &q) in qubits.iter().enumerate() {
+            self.set(q, (index >> i) & 1 == 1);
+        }
+    }
+
+    /// Whether `theta` is (approximately) an odd multiple of π, or None if it
+    /// is not a multiple of π at all.
+    fn get_approximate_flip(&self, theta: f64) -> Option<bool> {
+        let half_turns = theta / std::f64::consts::PI;
+        let half_turns_rounded = half_turns.round();
+        if (half_turns - half_turns_rounded).abs() > self.angle_threshold_half {
+            return None;
+        }
+        Some((half_turns_rounded as i64).rem_euclid(2) == 1)
+    }
+
+    fn check_qubits(&self, name: &str, qubits: &[u64]) -> Result<()> {
+        for (i, &q) in qubits.iter().enumerate() {
+            if q >= self.n_qubits {
+                bail!(
+                    "{name}(q={q}) is out of bounds. q must be less than the number of qubits ({}).",
+                    self.n_qubits
+                );
+            }
+            if qubits[..i].contains(&q) {
+                bail!("{name} requires distinct qubits, but qubit {q} was provided twice.");
+            }
+        }
+        Ok(())
+    }
+
+    /// The row of `column` of a 2^n x 2^n matrix (stored as interleaved real
+    /// and imaginary parts in row-major order) that holds its only non-zero
+    /// element.
+    fn single_row(matrix: &[f64], dimension: usize, column: usize) -> Option<usize> {
+        let mut rows = (0..dimension)
+            .map(|row| {
+                let index = 2 * (row * dimension + column);
+                (row, matrix[index].powi(2) + matrix[index + 1].powi(2))
+            })
+            .filter(|&(_, norm_sqr)| norm_sqr > AMPLITUDE_TOLERANCE);
+        match (rows.next(), rows.next()) {
+            (Some((row, _)), None) => Some(row),
+            _ => None,
+        }
+    }
+
+    fn superposition_error(operation: String) -> anyhow::Error {
+        anyhow!(
+            "{operation} would create a superposition, which the bitstring simulator cannot represent."
+        )
+    }
+}
+
+impl SimulatorInterface for BitstringSimulator {
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn shot_start(&mut self, _shot_id: u64, seed: u64) -> Result<()> {
+        self.bits.fill(0);
+        self.rng = Pcg64Mcg::seed_from_u64(seed);
+        Ok(())
+    }
+
+    fn shot_end(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn rz(&mut self, q0: u64, _theta: f64) -> Result<()> {
+        self.check_qubits("RZ", &[q0])
+    }
+
+    fn rxy(&mut self, q0: u64, theta: f64, phi: f64) -> Result<()> {
+        self.check_qubits("RXY", &[q0])?;
+        match self.get_approximate_flip(theta) {
+            Some(true) => self.flip(q0),
+            Some(false) => (),
+            None => {
+                return Err(Self::superposition_error(format!(
+                    "RXY(q0={q0}, theta={theta}, phi={phi})"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn rzz(&mut self, q0: u64, q1: u64, _theta: f64) -> Result<()> {
+        self.check_qubits("RZZ", &[q0, q1])
+    }
+
+    fn u(&mut self, q0: u64, theta: f64, phi: f64, lambda: f64) -> Result<()> {
+        self.check_qubits("U", &[q0])?;
+        match self.get_approximate_flip(theta) {
+            Some(true) => self.flip(q0),
+            Some(false) => (),
+            None => {
+                return Err(Self::superposition_error(format!(
+                    "U(q0={q0}, theta={theta}, phi={phi}, lambda={lambda})"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn two_qubit_unitary(&mut self, q0: u64, q1: u64, matrix: &[f64; 32]) -> Result<()> {
+        self.check_qubits("TwoQubitUnitary", &[q0, q1])?;
+        let column = self.get_local(&[q0, q1]);
+        let row = Self::single_row(matrix, 4, column).ok_or_else(|| {
+            Self::superposition_error(format!("TwoQubitUnitary(q0={q0}, q1={q1})"))
+        })?;
+        self.set_local(&[q0, q1], row);
+        Ok(())
+    }
+
+    fn diagonal_phase(&mut self, qubits: &[u64], _phases: &[f64]) -> Result<()> {
+        self.check_qubits("DiagonalPhase", qubits)
+    }
+
+    fn measure(&mut self, q0: u64) -> Result<bool> {
+        self.check_qubits("Measure", &[q0])?;
+        Ok(self.get(q0))
+    }
+
+    fn postselect(&mut self, q0: u64, target_value: bool) -> Result<()> {
+        self.check_qubits("Postselect", &[q0])?;
+        if self.get(q0) != target_value {
+            bail!(
+                "Postselection of {} on qubit {q0} is impossible, as the qubit is in the basis state {}.",
+                target_value as u8,
+                !target_value as u8
+            );
+        }
+        Ok(())
+    }
+
+    fn reset(&mut self, q0: u64) -> Result<()> {
+        self.check_qubits("Reset", &[q0])?;
+        self.set(q0, false);
+        Ok(())
+    }
+
+    fn snapshot(&mut self) -> Result<u64> {
+        self.snapshots.push(Some(self.bits.clone()));
+        Ok(self.snapshots.len() as u64 - 1)
+    }
+
+    fn restore(&mut self, handle: u64) -> Result<()> {
+        let Some(snapshot) = self.snapshots.get(handle as usize) else {
+            bail!(
+                "Snapshot {handle} does not exist. Only {} snapshots have been taken.",
+                self.snapshots.len()
+            );
+        };
+        let Some(snapshot) = snapshot else {
+            bail!("Snapshot {handle} has been released.");
+        };
+        self.bits.copy_from_slice(snapshot);
+        Ok(())
+    }
+
+    fn release_snapshot(&mut self, handle: u64) -> Result<()> {
+        self.snapshot_slot(handle)?.take();
+        Ok(())
+    }
+
+    fn marginal_probabilities(&mut self, qubits: &[u64]) -> Result<Vec<f64>> {
+        self.check_qubits("MarginalProbabilities", qubits)?;
+        let mut probabilities = vec![0.0; 1 << qubits.len()];
+        probabilities[self.get_local(qubits)] = 1.0;
+        Ok(probabilities)
+    }
+
+    fn pauli_expectation(&mut self, pauli_string: &[(u64, Pauli)]) -> Result<f64> {
+        let qubits: Vec<u64> = pauli_string.iter().map(|&(q, _)| q).collect();
+        self.check_qubits("PauliExpectation", &qubits)?;
+        let mut expectation = 1.0;
+        for &(q, pauli) in pauli_string {
+            match pauli {
+                Pauli::I => (),
+                Pauli::X | Pauli::Y => return Ok(0.0),
+                Pauli::Z if self.get(q) => expectation = -expectation,
+                Pauli::Z => (),
+            }
+        }
+        Ok(expectation)
+    }
+
+    fn pauli_channel(&mut self, q0: u64, p_x: f64, p_y: f64, p_z: f64) -> Result<()> {
+        self.check_qubits("PauliChannel", &[q0])?;
+        validate_pauli_channel(p_x, p_y, p_z)?;
+        // Z only contributes a phase, so X and Y are both a flip.
+        if self.rng.random::<f64>() < p_x + p_y {
+            self.flip(q0);
+        }
+        Ok(())
+    }
+
+    fn amplitude_damping(&mut self, q0: u64, gamma: f64) -> Result<()> {
+        self.check_qubits("AmplitudeDamping", &[q0])?;
+        if !(0.0..=1.0).contains(&gamma) {
+            bail!("The amplitude damping probability must be in [0, 1], got {gamma}.");
+        }
+        if self.get(q0) && self.rng.random::<f64>() < gamma {
+            self.set(q0, false);
+        }
+        Ok(())
+    }
+
+    fn phase_damping(&mut self, q0: u64, lambda: f64) -> Result<()> {
+        self.check_qubits("PhaseDamping", &[q0])?;
+        if !(0.0..=1.0).contains(&lambda) {
+            bail!("The phase damping parameter must be in [0, 1], got {lambda}.");
+        }
+        Ok(())
+    }
+
+    fn kraus_channel(&mut self, qubits: &[u64], kraus_operators: &[f64]) -> Result<()> {
+        self.check_qubits("KrausChannel", qubits)?;
+        validate_kraus_operators(qubits.len(), kraus_operators)?;
+        let size = kraus_operator_size(qubits.len())?;
+        let dimension = 1 << qubits.len();
+        let column = self.get_local(qubits);
+        // Choose an operator K with probability ||K|b>||², which must then map
+        // the basis state |b> to a single basis state.
+        let r = self.rng.random::<f64>();
+        let mut cumulative = 0.0;
+        let mut chosen = None;
+        for operator in kraus_operators.chunks_exact(size) {
+            let probability: f64 = (0..dimension)
+                .map(|row| {
+                    let index = 2 * (row * dimension + column);
+                    operator[index].powi(2) + operator[index + 1].powi(2)
+                })
+                .sum();
+            if probability > 0.0 {
+                chosen = Some(operator);
+            }
+            cumulative += probability;
+            if r < cumulative {
+                break;
+            }
+        }
+        let operator = chosen.ok_or_else(|| anyhow!("No Kraus operator could be selected"))?;
+        let row = Self::single_row(operator, dimension, column)
+            .ok_or_else(|| Self::superposition_error(format!("KrausChannel(qubits={qubits:?})")))?;
+        self.set_local(qubits, row);
+        Ok(())
+    }
+
+    fn get_metric(&mut self, _nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        Ok(None)
+    }
+}
+
+#[derive(Default)]
+pub struct BitstringSimulatorFactory;
+
+impl SimulatorInterfaceFactory for BitstringSimulatorFactory {
+    type Interface = BitstringSimulator;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        let args: Vec<String> = args.iter().map(|s| s.as_ref().to_string()).collect();
+        let params = Params::try_parse_from(args)
+            .map_err(|e| anyhow!("Error parsing arguments to bitstring plugin: {e}"))?;
+        Ok(Box::new(BitstringSimulator {
+            bits: vec![0; n_qubits.div_ceil(64) as usize],
+            n_qubits,
+            angle_threshold_half: params.angle_threshold / std::f64::consts::PI,
+            rng: Pcg64Mcg::seed_from_u64(0),
+            snapshots: Vec::new(),
+        }))
+    }
+}
+
+export_simulator_plugin!(crate::BitstringSimulatorFactory);
@@ -0,0 +1,86 @@
+use crate::BitstringSimulatorFactory;
+use selene_core::simulator::SimulatorInterface;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// A CX gate as a two-qubit unitary, with the control as the first qubit.
+fn cx_matrix() -> [f64; 32] {
+    let mut matrix = [0.0; 32];
+    for (row, column) in [(0, 0), (3, 1), (2, 2), (1, 3)] {
+        matrix[2 * (row * 4 + column)] = 1.0;
+    }
+    matrix
+}
+
+#[test]
+fn classical_logic_on_a_large_register() {
+    let n_qubits = 1_000_000;
+    let mut sim = Arc::new(BitstringSimulatorFactory)
+        .init(n_qubits, &["", "--angle-threshold=0.0001"])
+        .unwrap();
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(0, PI, 0.3).unwrap();
+    sim.rz(0, 0.123).unwrap();
+    sim.rzz(0, n_qubits - 1, 0.456).unwrap();
+    sim.two_qubit_unitary(0, n_qubits - 1, &cx_matrix())
+        .unwrap();
+    sim.two_qubit_unitary(0, 1, &cx_matrix()).unwrap();
+    sim.u(1, -PI, 0.1, 0.2).unwrap();
+    assert!(sim.measure(0).unwrap());
+    assert!(!sim.measure(1).unwrap());
+    assert!(sim.measure(n_qubits - 1).unwrap());
+    assert_eq!(
+        sim.marginal_probabilities(&[1, n_qubits - 1]).unwrap(),
+        vec![0.0, 0.0, 1.0, 0.0]
+    );
+    sim.reset(0).unwrap();
+    assert!(!sim.measure(0).unwrap());
+    sim.shot_end().unwrap();
+
+    sim.shot_start(1, 1).unwrap();
+    assert!(!sim.measure(n_qubits - 1).unwrap());
+}
+
+#[test]
+fn superpositions_are_rejected() {
+    let mut sim = Arc::new(BitstringSimulatorFactory)
+        .init(2, &["", "--angle-threshold=0.0001"])
+        .unwrap();
+    sim.shot_start(0, 0).unwrap();
+    assert!(sim.rxy(0, PI / 2.0, 0.0).is_err());
+    assert!(sim.u(0, PI / 4.0, 0.0, 0.0).is_err());
+    // A controlled Hadamard only creates a superposition once the control is set
+    let mut controlled_h = [0.0; 32];
+    let h = std::f64::consts::FRAC_1_SQRT_2;
+    for (row, column, value) in [
+        (0, 0, 1.0),
+        (2, 2, 1.0),
+        (1, 1, h),
+        (1, 3, h),
+        (3, 1, h),
+        (3, 3, -h),
+    ] {
+        controlled_h[2 * (row * 4 + column)] = value;
+    }
+    sim.two_qubit_unitary(0, 1, &controlled_h).unwrap();
+    sim.rxy(0, PI, 0.0).unwrap();
+    assert!(sim.two_qubit_unitary(0, 1, &controlled_h).is_err());
+}
+
+#[test]
+fn snapshots_can_be_restored_until_released() {
+    let mut sim = Arc::new(BitstringSimulatorFactory)
+        .init(2, &["", "--angle-threshold=0.0001"])
+        .unwrap();
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(1, PI, 0.0).unwrap();
+    let handle = sim.snapshot().unwrap();
+    sim.reset(1).unwrap();
+    sim.restore(handle).unwrap();
+    assert!(sim.measure(1).unwrap());
+    sim.release_snapshot(handle).unwrap();
+    assert!(sim.restore(handle).is_err());
+    // Handles are not reused once released.
+    assert_eq!(sim.snapshot().unwrap(), handle + 1);
+}
@@ -0,0 +1,3 @@
+from .plugin import CrossCheckPlugin
+
+__all__ = ["CrossCheckPlugin"]
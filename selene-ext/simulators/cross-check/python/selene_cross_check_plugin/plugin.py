@@ -0,0 +1,87 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+
+from selene_core import Simulator
+
+
+@dataclass
+class CrossCheckPlugin(Simulator):
+    """
+    A plugin that drives two simulators with the same operations and checks
+    that they agree at every measurement. This is intended for validating a
+    new simulator against a trusted one, e.g. Stim against QuEST on Clifford
+    programs, within real user programs.
+
+    Before each measurement, the probability of measuring 1 is compared
+    between the two simulators (when both support marginal probabilities).
+    The primary simulator then performs the measurement, and the secondary
+    simulator is postselected on the same outcome. Divergences are reported
+    through the `divergent_measurements` and `max_probability_divergence`
+    metrics, or raised as errors if `error_on_divergence` is set. Resets and
+    frees are forwarded to both simulators without measuring, although the
+    probabilities are still compared before a reset. Each simulator collapses
+    qubits entangled with a reset or freed qubit independently, so later
+    comparisons involving them may diverge.
+
+    Noise sampled by the error model reaches both simulators as the same gates,
+    so any error model can be used. Noise channels are forwarded to both
+    simulators, and comparisons after a channel are only meaningful if both
+    simulators apply it exactly.
+
+    Attributes:
+        primary (Simulator): The simulator that performs measurements, and
+            whose state is dumped and metrics are reported.
+        secondary (Simulator): The simulator to check against the primary
+            simulator. It must support postselection.
+        tolerance (float, default 1e-6): The largest difference in measurement
+            probabilities that is not considered a divergence.
+        error_on_divergence (bool, default False): Whether a divergence should
+            be raised as an error rather than only reported as a metric.
+    """
+
+    primary: Simulator | None = None
+    secondary: Simulator | None = None
+    tolerance: float = 1e-6
+    error_on_divergence: bool = False
+
+    def __post_init__(self):
+        assert self.primary is not None, (
+            "A primary simulator must be provided to the cross-check plugin"
+        )
+        assert self.secondary is not None, (
+            "A secondary simulator must be provided to the cross-check plugin"
+        )
+        assert self.tolerance >= 0, "tolerance must be non-negative"
+        self.random_seed = self.primary.random_seed
+
+    def get_init_args(self):
+        assert self.primary is not None and self.secondary is not None
+        return (
+            [
+                f"--primary-path={self.primary.library_file}",
+                f"--secondary-path={self.secondary.library_file}",
+                f"--tolerance={self.tolerance}",
+            ]
+            + [f"--primary-arg={arg}" for arg in self.primary.get_init_args()]
+            + [f"--secondary-arg={arg}" for arg in self.secondary.get_init_args()]
+            + (["--error-on-divergence"] if self.error_on_divergence else [])
+        )
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_cross_check_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_cross_check_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_cross_check_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    @property
+    def library_search_dirs(self):
+        assert self.primary is not None and self.secondary is not None
+        return self.primary.library_search_dirs + self.secondary.library_search_dirs
@@ -0,0 +1,372 @@
+/// A simulator plugin for Selene that drives two wrapped simulators in
+/// lockstep, and checks that they agree at every measurement.
+//
+// Every operation is forwarded to both the primary and the secondary simulator.
+// Before each measurement, the probability of measuring 1 is queried from both
+// with `marginal_probabilities`, and any difference beyond the tolerance is a
+// divergence. The primary simulator then performs the measurement, and the
+// secondary simulator is postselected on the same outcome, so that both
+// continue along the same branch. If the secondary simulator cannot reproduce
+// the outcome, that is also a divergence, and the remainder of the shot is no
+// longer compared.
+//
+// Resets and frees are forwarded to both simulators without measuring, so
+// they do not count as checked measurements or consume random draws. Before a
+// reset, the probabilities of measuring 1 are still compared, as the reset
+// would otherwise hide a divergence on that qubit. Each simulator collapses any
+// qubits entangled with a reset or freed qubit independently, so comparisons
+// involving those qubits may afterwards report divergences that are not
+// errors in either simulator.
+//
+// Noise sampled by the error model reaches both simulators as the same gates,
+// so any error model can be used. Noise channels (Pauli, damping and Kraus
+// channels) are forwarded to both simulators as channels, and a simulator that
+// samples them as trajectories draws its own outcome. Comparisons after such a
+// channel are only meaningful if both simulators apply it exactly.
+//
+// If either simulator does not support marginal probabilities, only the
+// measurement outcomes are compared.
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use selene_core::export_simulator_plugin;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::simulator::{Pauli, Simulator, SimulatorInterface};
+use selene_core::utils::MetricValue;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// The path to the primary simulator plugin, which performs measurements
+    #[arg(long)]
+    primary_path: String,
+    /// Arguments for the primary simulator plugin
+    #[arg(long)]
+    primary_arg: Vec<String>,
+    /// The path to the secondary simulator plugin, which is postselected on
+    /// the outcomes of the primary simulator
+    #[arg(long)]
+    secondary_path: String,
+    /// Arguments for the secondary simulator plugin
+    #[arg(long)]
+    secondary_arg: Vec<String>,
+    /// The largest difference in measurement probabilities that is not
+    /// considered a divergence
+    #[arg(long, default_value_t = 1e-6)]
+    tolerance: f64,
+    /// Whether a divergence should be raised as an error. Otherwise,
+    /// divergences are only reported through metrics.
+    #[arg(long)]
+    error_on_divergence: bool,
+}
+
+pub struct CrossCheckSimulator {
+    primary: Simulator,
+    secondary: Simulator,
+    n_qubits: u64,
+    tolerance: f64,
+    error_on_divergence: bool,
+    /// Whether each simulator supports marginal probabilities, once known.
+    primary_marginals: Option<bool>,
+    secondary_marginals: Option<bool>,
+    current_shot: u64,
+    /// Whether the secondary simulator has followed every measurement outcome
+    /// of the primary simulator in the current shot.
+    in_sync: bool,
+    checked_measurements: u64,
+    divergent_measurements: u64,
+    max_probability_divergence: f64,
+}
+
+/// The probability of measuring 1 on the given qubit, if the simulator
+/// supports marginal probabilities.
+fn probability_of_one(
+    simulator: &mut Simulator,
+    supported: &mut Option<bool>,
+    q0: u64,
+) -> Result<Option<f64>> {
+    match supported {
+        Some(false) => Ok(None),
+        Some(true) => Ok(Some(simulator.marginal_probabilities(&[q0])?[1])),
+        None => {
+            let probabilities = simulator.marginal_probabilities(&[q0]).ok();
+            *supported = Some(probabilities.is_some());
+            Ok(probabilities.map(|p| p[1]))
+        }
+    }
+}
+
+impl CrossCheckSimulator {
+    pub fn new(
+        primary: Simulator,
+        secondary: Simulator,
+        n_qubits: u64,
+        tolerance: f64,
+        error_on_divergence: bool,
+    ) -> Self {
+        Self {
+            primary,
+            secondary,
+            n_qubits,
+            tolerance,
+            error_on_divergence,
+            primary_marginals: None,
+            secondary_marginals: None,
+            current_shot: 0,
+            in_sync: true,
+            checked_measurements: 0,
+            divergent_measurements: 0,
+            max_probability_divergence: 0.0,
+        }
+    }
+
+    fn check_bounds(&self, name: &str, qubits: &[u64]) -> Result<()> {
+        if let Some(q) = qubits.iter().find(|&&q| q >= self.n_qubits) {
+            bail!(
+                "{name} on qubit {q} is out of bounds. Qubits must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        Ok(())
+    }
+
+    /// Compare the probabilities of measuring 1 on the given qubit in both
+    /// simulators, describing the divergence if they differ by more than
+    /// the tolerance.
+    fn compare_probabilities(&mut self, q0: u64) -> Result<Option<String>> {
+        let p_primary = probability_of_one(&mut self.primary, &mut self.primary_marginals, q0)?;
+        let p_secondary =
+            probability_of_one(&mut self.secondary, &mut self.secondary_marginals, q0)?;
+        let (Some(p_primary), Some(p_secondary)) = (p_primary, p_secondary) else {
+            return Ok(None);
+        };
+        let divergence = (p_primary - p_secondary).abs();
+        self.max_probability_divergence = self.max_probability_divergence.max(divergence);
+        Ok((divergence > self.tolerance).then(|| {
+            format!(
+                "the probability of measuring 1 is {p_primary} in the primary simulator and {p_secondary} in the secondary simulator"
+            )
+        }))
+    }
+
+    fn measure_unchecked(&mut self, q0: u64) -> Result<bool> {
+        if !self.in_sync {
+            let outcome = self.primary.measure(q0)?;
+            self.secondary.measure(q0)?;
+            return Ok(outcome);
+        }
+        let mut divergences = Vec::new();
+        divergences.extend(self.compare_probabilities(q0)?);
+        let outcome = self.primary.measure(q0)?;
+        if self.secondary.postselect(q0, outcome).is_err() {
+            divergences.push(format!(
+                "the secondary simulator could not reproduce the outcome {} of the primary simulator",
+                outcome as u8
+            ));
+            self.in_sync = false;
+            self.secondary.measure(q0)?;
+        }
+        self.checked_measurements += 1;
+        if !divergences.is_empty() {
+            self.divergent_measurements += 1;
+            if self.error_on_divergence {
+                bail!(
+                    "Simulators diverged when measuring qubit {q0} on shot {}: {}.",
+                    self.current_shot,
+                    divergences.join(", and ")
+                );
+            }
+        }
+        Ok(outcome)
+    }
+}
+
+impl SimulatorInterface for CrossCheckSimulator {
+    fn exit(&mut self) -> Result<()> {
+        self.primary.exit()?;
+        self.secondary.exit()
+    }
+
+    fn shot_start(&mut self, shot_id: u64, seed: u64) -> Result<()> {
+        self.current_shot = shot_id;
+        self.in_sync = true;
+        self.checked_measurements = 0;
+        self.divergent_measurements = 0;
+        self.max_probability_divergence = 0.0;
+        self.primary.shot_start(shot_id, seed)?;
+        self.secondary.shot_start(shot_id, seed)
+    }
+
+    fn shot_end(&mut self) -> Result<()> {
+        self.primary.shot_end()?;
+        self.secondary.shot_end()
+    }
+
+    fn rxy(&mut self, q0: u64, theta: f64, phi: f64) -> Result<()> {
+        self.check_bounds("RXY", &[q0])?;
+        self.primary.rxy(q0, theta, phi)?;
+        self.secondary.rxy(q0, theta, phi)
+    }
+
+    fn rzz(&mut self, q0: u64, q1: u64, theta: f64) -> Result<()> {
+        self.check_bounds("RZZ", &[q0, q1])?;
+        self.primary.rzz(q0, q1, theta)?;
+        self.secondary.rzz(q0, q1, theta)
+    }
+
+    fn rz(&mut self, q0: u64, theta: f64) -> Result<()> {
+        self.check_bounds("RZ", &[q0])?;
+        self.primary.rz(q0, theta)?;
+        self.secondary.rz(q0, theta)
+    }
+
+    fn u(&mut self, q0: u64, theta: f64, phi: f64, lambda: f64) -> Result<()> {
+        self.check_bounds("U", &[q0])?;
+        self.primary.u(q0, theta, phi, lambda)?;
+        self.secondary.u(q0, theta, phi, lambda)
+    }
+
+    fn two_qubit_unitary(&mut self, q0: u64, q1: u64, matrix: &[f64; 32]) -> Result<()> {
+        self.check_bounds("TwoQubitUnitary", &[q0, q1])?;
+        self.primary.two_qubit_unitary(q0, q1, matrix)?;
+        self.secondary.two_qubit_unitary(q0, q1, matrix)
+    }
+
+    fn diagonal_phase(&mut self, qubits: &[u64], phases: &[f64]) -> Result<()> {
+        self.check_bounds("DiagonalPhase", qubits)?;
+        self.primary.diagonal_phase(qubits, phases)?;
+        self.secondary.diagonal_phase(qubits, phases)
+    }
+
+    fn measure(&mut self, q0: u64) -> Result<bool> {
+        self.check_bounds("Measure", &[q0])?;
+        self.measure_unchecked(q0)
+    }
+
+    fn postselect(&mut self, q0: u64, target_value: bool) -> Result<()> {
+        self.check_bounds("Postselect", &[q0])?;
+        self.primary.postselect(q0, target_value)?;
+        self.secondary.postselect(q0, target_value)
+    }
+
+    fn reset(&mut self, q0: u64) -> Result<()> {
+        self.check_bounds("Reset", &[q0])?;
+        if self.in_sync
+            && let Some(divergence) = self.compare_probabilities(q0)?
+            && self.error_on_divergence
+        {
+            bail!(
+                "Simulators diverged before resetting qubit {q0} on shot {}: {divergence}.",
+                self.current_shot
+            );
+        }
+        self.primary.reset(q0)?;
+        self.secondary.reset(q0)
+    }
+
+    fn qalloc(&mut self, q0: u64) -> Result<()> {
+        self.check_bounds("QAlloc", &[q0])?;
+        self.primary.qalloc(q0)?;
+        self.secondary.qalloc(q0)
+    }
+
+    fn qfree(&mut self, q0: u64) -> Result<()> {
+        self.check_bounds("QFree", &[q0])?;
+        self.primary.qfree(q0)?;
+        self.secondary.qfree(q0)
+    }
+
+    fn marginal_probabilities(&mut self, qubits: &[u64]) -> Result<Vec<f64>> {
+        self.primary.marginal_probabilities(qubits)
+    }
+
+    fn pauli_expectation(&mut self, pauli_string: &[(u64, Pauli)]) -> Result<f64> {
+        self.primary.pauli_expectation(pauli_string)
+    }
+
+    fn pauli_channel(&mut self, q0: u64, p_x: f64, p_y: f64, p_z: f64) -> Result<()> {
+        self.check_bounds("PauliChannel", &[q0])?;
+        self.primary.pauli_channel(q0, p_x, p_y, p_z)?;
+        self.secondary.pauli_channel(q0, p_x, p_y, p_z)
+    }
+
+    fn amplitude_damping(&mut self, q0: u64, gamma: f64) -> Result<()> {
+        self.check_bounds("AmplitudeDamping", &[q0])?;
+        self.primary.amplitude_damping(q0, gamma)?;
+        self.secondary.amplitude_damping(q0, gamma)
+    }
+
+    fn phase_damping(&mut self, q0: u64, lambda: f64) -> Result<()> {
+        self.check_bounds("PhaseDamping", &[q0])?;
+        self.primary.phase_damping(q0, lambda)?;
+        self.secondary.phase_damping(q0, lambda)
+    }
+
+    fn kraus_channel(&mut self, qubits: &[u64], kraus_operators: &[f64]) -> Result<()> {
+        self.check_bounds("KrausChannel", qubits)?;
+        self.primary.kraus_channel(qubits, kraus_operators)?;
+        self.secondary.kraus_channel(qubits, kraus_operators)
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "checked_measurements".to_string(),
+                MetricValue::U64(self.checked_measurements),
+            ))),
+            1 => Ok(Some((
+                "divergent_measurements".to_string(),
+                MetricValue::U64(self.divergent_measurements),
+            ))),
+            2 => Ok(Some((
+                "max_probability_divergence".to_string(),
+                MetricValue::F64(self.max_probability_divergence),
+            ))),
+            n => self.primary.get_metric(n - 3),
+        }
+    }
+
+    fn dump_state(&mut self, file: &std::path::Path, qubits: &[u64]) -> Result<()> {
+        self.primary.dump_state(file, qubits)
+    }
+}
+
+#[derive(Default)]
+pub struct CrossCheckSimulatorFactory;
+
+impl SimulatorInterfaceFactory for CrossCheckSimulatorFactory {
+    type Interface = CrossCheckSimulator;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        let args: Vec<String> = args.iter().map(|s| s.as_ref().to_string()).collect();
+        let params = Params::try_parse_from(args)
+            .map_err(|e| anyhow!("Error parsing arguments to cross-check plugin: {e}"))?;
+        if params.tolerance.is_nan() || params.tolerance < 0.0 {
+            bail!(
+                "The tolerance must be non-negative, got {}",
+                params.tolerance
+            );
+        }
+        let primary =
+            Simulator::load_from_file(&params.primary_path, n_qubits, params.primary_arg.as_ref())?;
+        let secondary = Simulator::load_from_file(
+            &params.secondary_path,
+            n_qubits,
+            params.secondary_arg.as_ref(),
+        )?;
+        Ok(Box::new(CrossCheckSimulator::new(
+            primary,
+            secondary,
+            n_qubits,
+            params.tolerance,
+            params.error_on_divergence,
+        )))
+    }
+}
+
+export_simulator_plugin!(crate::CrossCheckSimulatorFactory);
@@ -0,0 +1,160 @@
+use crate::CrossCheckSimulator;
+use anyhow::{Result, bail};
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::simulator::{Simulator, SimulatorInterface};
+use selene_core::utils::MetricValue;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// A classical simulator of computational basis states, in which RXY(π, φ)
+/// flips a qubit and other gates are ignored. If `faulty`, flips of qubit 1
+/// are also ignored.
+struct BasisSimulator {
+    bits: Vec<bool>,
+    faulty: bool,
+}
+
+impl SimulatorInterface for BasisSimulator {
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+    fn shot_start(&mut self, _shot_id: u64, _seed: u64) -> Result<()> {
+        self.bits.fill(false);
+        Ok(())
+    }
+    fn shot_end(&mut self) -> Result<()> {
+        Ok(())
+    }
+    fn rz(&mut self, _q0: u64, _theta: f64) -> Result<()> {
+        Ok(())
+    }
+    fn rxy(&mut self, q0: u64, theta: f64, _phi: f64) -> Result<()> {
+        if (theta - PI).abs() < 1e-10 && !(self.faulty && q0 == 1) {
+            self.bits[q0 as usize] ^= true;
+        }
+        Ok(())
+    }
+    fn rzz(&mut self, _q0: u64, _q1: u64, _theta: f64) -> Result<()> {
+        Ok(())
+    }
+    fn measure(&mut self, q0: u64) -> Result<bool> {
+        Ok(self.bits[q0 as usize])
+    }
+    fn postselect(&mut self, q0: u64, target_value: bool) -> Result<()> {
+        if self.bits[q0 as usize] != target_value {
+            bail!("Postselection failed");
+        }
+        Ok(())
+    }
+    fn reset(&mut self, q0: u64) -> Result<()> {
+        self.bits[q0 as usize] = false;
+        Ok(())
+    }
+    fn marginal_probabilities(&mut self, qubits: &[u64]) -> Result<Vec<f64>> {
+        let value = self.bits[qubits[0] as usize];
+        Ok(vec![(!value as u8) as f64, (value as u8) as f64])
+    }
+    fn get_metric(&mut self, _nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        Ok(None)
+    }
+}
+
+struct BasisSimulatorFactory {
+    faulty: bool,
+}
+
+impl SimulatorInterfaceFactory for BasisSimulatorFactory {
+    type Interface = BasisSimulator;
+
+    fn init(
+        self: Arc<Self>,
+        n_qubits: u64,
+        _args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        Ok(Box::new(BasisSimulator {
+            bits: vec![false; n_qubits as usize],
+            faulty: self.faulty,
+        }))
+    }
+}
+
+fn cross_check(faulty: bool, error_on_divergence: bool) -> CrossCheckSimulator {
+    let no_args: &[&str] = &[];
+    let primary = Simulator::new(
+        Arc::new(BasisSimulatorFactory { faulty: false }),
+        2,
+        no_args,
+    );
+    let secondary = Simulator::new(Arc::new(BasisSimulatorFactory { faulty }), 2, no_args);
+    CrossCheckSimulator::new(
+        primary.unwrap(),
+        secondary.unwrap(),
+        2,
+        1e-6,
+        error_on_divergence,
+    )
+}
+
+fn metric(sim: &mut CrossCheckSimulator, nth_metric: u8) -> u64 {
+    match sim.get_metric(nth_metric).unwrap() {
+        Some((_, MetricValue::U64(value))) => value,
+        _ => panic!("Expected a U64 metric"),
+    }
+}
+
+#[test]
+fn agreeing_simulators_do_not_diverge() {
+    let mut sim = cross_check(false, true);
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(1, PI, 0.0).unwrap();
+    assert!(!sim.measure(0).unwrap());
+    assert!(sim.measure(1).unwrap());
+    sim.reset(1).unwrap();
+    assert!(!sim.measure(1).unwrap());
+    sim.qfree(1).unwrap();
+    // Resets and frees are forwarded rather than measured.
+    assert_eq!(metric(&mut sim, 0), 3);
+    assert_eq!(metric(&mut sim, 1), 0);
+}
+
+#[test]
+fn divergence_before_a_reset_is_reported() {
+    let mut sim = cross_check(true, false);
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(1, PI, 0.0).unwrap();
+    sim.reset(1).unwrap();
+    assert!(!sim.measure(1).unwrap());
+    // The reset is not a checked measurement, but the divergence it hides is
+    // still recorded.
+    assert_eq!(metric(&mut sim, 0), 1);
+    assert_eq!(metric(&mut sim, 1), 0);
+    assert!(matches!(
+        sim.get_metric(2).unwrap(),
+        Some((_, MetricValue::F64(divergence))) if divergence == 1.0
+    ));
+
+    let mut sim = cross_check(true, true);
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(1, PI, 0.0).unwrap();
+    assert!(sim.reset(0).is_ok());
+    assert!(sim.reset(1).is_err());
+}
+
+#[test]
+fn divergence_is_reported() {
+    let mut sim = cross_check(true, false);
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(1, PI, 0.0).unwrap();
+    assert!(sim.measure(1).unwrap());
+    assert_eq!(metric(&mut sim, 1), 1);
+    assert!(matches!(
+        sim.get_metric(2).unwrap(),
+        Some((_, MetricValue::F64(divergence))) if divergence == 1.0
+    ));
+
+    let mut sim = cross_check(true, true);
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(1, PI, 0.0).unwrap();
+    assert!(sim.measure(0).is_ok());
+    assert!(sim.measure(1).is_err());
+}
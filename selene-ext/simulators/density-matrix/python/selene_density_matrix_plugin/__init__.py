@@ -0,0 +1,3 @@
+from .plugin import DensityMatrixPlugin
+
+__all__ = ["DensityMatrixPlugin"]
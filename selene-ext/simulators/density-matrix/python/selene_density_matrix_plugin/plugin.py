@@ -0,0 +1,98 @@
+import platform
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import Iterable, Iterator
+
+import numpy as np
+from hugr.qsystem.result import TaggedResult
+from selene_core import Simulator
+from selene_quest_plugin import QuestPlugin, SeleneQuestState
+
+
+@dataclass
+class DensityMatrixPlugin(Simulator):
+    """
+    A plugin for using a density-matrix simulator as the backend simulator
+    for selene.
+
+    The full density matrix of the register is stored, so noise can be
+    described by arbitrary channels and is applied exactly rather than
+    sampled per shot. Memory usage grows as 16 * 4^n bytes for n qubits.
+
+    The channels configured here are applied to every qubit that a gate acts
+    on, after the gate itself, in the order: amplitude damping, dephasing,
+    custom Kraus operators.
+
+    State dumps contain the reduced density matrix of the requested qubits
+    and can be inspected with `SeleneQuestState`.
+
+    Attributes:
+        amplitude_damping (float): The probability of decay from |1> to |0>.
+        dephasing (float): The probability of a Z error.
+        kraus (list[np.ndarray]): The 2x2 Kraus operators of an additional
+                                  single-qubit channel. They must satisfy
+                                  sum(K^dagger K) = I.
+    """
+
+    amplitude_damping: float = 0.0
+    dephasing: float = 0.0
+    kraus: list[np.ndarray] = field(default_factory=list)
+
+    def __post_init__(self):
+        assert 0 <= self.amplitude_damping <= 1, (
+            "amplitude_damping must be between 0 and 1"
+        )
+        assert 0 <= self.dephasing <= 1, "dephasing must be between 0 and 1"
+        self.kraus = [np.asarray(k, dtype=np.complex128) for k in self.kraus]
+        for k in self.kraus:
+            assert k.shape == (2, 2), "Kraus operators must be 2x2 matrices"
+        if self.kraus:
+            completeness = sum(k.conj().T @ k for k in self.kraus)
+            assert np.allclose(completeness, np.eye(2)), (
+                "Kraus operators must satisfy sum(K^dagger K) = I"
+            )
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_density_matrix_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_density_matrix_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_density_matrix_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    def get_init_args(self):
+        args = [
+            f"--amplitude-damping={self.amplitude_damping}",
+            f"--dephasing={self.dephasing}",
+        ]
+        for k in self.kraus:
+            values = ",".join(
+                f"{float(x.real)!r},{float(x.imag)!r}" for x in k.flatten()
+            )
+            args.append(f"--kraus={values}")
+        return args
+
+    @staticmethod
+    def extract_states_dict(
+        results: Iterable[TaggedResult],
+        cleanup: bool = True,
+    ) -> dict[str, SeleneQuestState]:
+        """Extract state results from a shot result stream and return them as a
+        dictionary keyed by the state tag. See `QuestPlugin.extract_states_dict`.
+        """
+        return QuestPlugin.extract_states_dict(results, cleanup=cleanup)
+
+    @staticmethod
+    def extract_states(
+        results: Iterable[TaggedResult],
+        cleanup: bool = True,
+    ) -> Iterator[tuple[str, SeleneQuestState]]:
+        """Extract state results from a shot result stream and return them as a
+        pair of (tag, state). See `QuestPlugin.extract_states`.
+        """
+        return QuestPlugin.extract_states(results, cleanup=cleanup)
@@ -0,0 +1,227 @@
+//! Dense density matrix storage and the operations that act on it.
+//!
+//! The n-qubit density matrix is stored as a vector of 4^n elements, with element
+//! (row, col) at index `row | (col << n)`. As with QuEST, qubit 0 is the least
+//! significant bit of both the row and the column index.
+use num_complex::Complex64;
+
+/// A 2x2 complex matrix in row-major order.
+pub type Matrix2 = [[Complex64; 2]; 2];
+
+/// The superoperator of a single-qubit channel, acting on the four elements
+/// (ket, bra) = (0,0), (0,1), (1,0), (1,1) of each 2x2 block.
+pub type Superoperator = [[Complex64; 4]; 4];
+
+/// Build the superoperator of the channel ρ -> Σ K ρ K†.
+pub fn superoperator(kraus_operators: &[Matrix2]) -> Superoperator {
+    let mut result = [[Complex64::new(0.0, 0.0); 4]; 4];
+    for k in kraus_operators {
+        for a in 0..2 {
+            for b in 0..2 {
+                for a_in in 0..2 {
+                    for b_in in 0..2 {
+                        result[a * 2 + b][a_in * 2 + b_in] += k[a][a_in] * k[b][b_in].conj();
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+/// The deviation of Σ K†K from the identity, as the largest absolute difference
+/// between corresponding elements.
+pub fn completeness_error(kraus_operators: &[Matrix2]) -> f64 {
+    let mut sum = [[Complex64::new(0.0, 0.0); 2]; 2];
+    for k in kraus_operators {
+        for (i, row) in sum.iter_mut().enumerate() {
+            for (j, element) in row.iter_mut().enumerate() {
+                *element += k[0][i].conj() * k[0][j] + k[1][i].conj() * k[1][j];
+            }
+        }
+    }
+    let mut error: f64 = 0.0;
+    for (i, row) in sum.iter().enumerate() {
+        for (j, element) in row.iter().enumerate() {
+            let expected = if i == j { 1.0 } else { 0.0 };
+            error = error.max((element - expected).norm());
+        }
+    }
+    error
+}
+
+pub struct DensityMatrix {
+    n_qubits: u64,
+    elements: Vec<Complex64>,
+}
+
+impl DensityMatrix {
+    pub fn new(n_qubits: u64) -> Self {
+        let mut elements = vec![Complex64::new(0.0, 0.0); 1 << (2 * n_qubits)];
+        elements[0] = Complex64::new(1.0, 0.0);
+        Self { n_qubits, elements }
+    }
+
+    fn dimension(&self) -> usize {
+        1 << self.n_qubits
+    }
+
+    fn diagonal_index(&self, basis_state: usize) -> usize {
+        basis_state | (basis_state << self.n_qubits)
+    }
+
+    /// Return the register to |0...0><0...0|.
+    pub fn initialise(&mut self) {
+        self.elements.fill(Complex64::new(0.0, 0.0));
+        self.elements[0] = Complex64::new(1.0, 0.0);
+    }
+
+    /// Apply a single-qubit channel, given by its superoperator, to `qubit`.
+    pub fn apply_superoperator(&mut self, qubit: u64, superoperator: &Superoperator) {
+        let ket = 1usize << qubit;
+        let bra = 1usize << (qubit + self.n_qubits);
+        for base in 0..self.elements.len() {
+            if base & (ket | bra) != 0 {
+                continue;
+            }
+            let indices = [base, base | bra, base | ket, base | ket | bra];
+            let old = indices.map(|i| self.elements[i]);
+            for (row, &index) in superoperator.iter().zip(indices.iter()) {
+                self.elements[index] = row.iter().zip(old.iter()).map(|(s, x)| s * x).sum();
+            }
+        }
+    }
+
+    /// Apply the channel ρ -> Σ K ρ K† to `qubits`, where each Kraus operator is
+    /// a row-major 2^k x 2^k matrix with the first of `qubits` as the least
+    /// significant bit of its row and column indices.
+    pub fn apply_kraus(&mut self, qubits: &[u64], kraus_operators: &[Vec<Complex64>]) {
+        let d = 1usize << qubits.len();
+        let scatter = |x: usize, shift: u64| {
+            qubits
+                .iter()
+                .enumerate()
+                .fold(0usize, |acc, (j, &q)| acc | (((x >> j) & 1) << (q + shift)))
+        };
+        let kets: Vec<usize> = (0..d).map(|x| scatter(x, 0)).collect();
+        let bras: Vec<usize> = (0..d).map(|x| scatter(x, self.n_qubits)).collect();
+        let mask = kets[d - 1] | bras[d - 1];
+        let mut block = vec![Complex64::new(0.0, 0.0); d * d];
+        let mut product = vec![Complex64::new(0.0, 0.0); d * d];
+        for base in 0..self.elements.len() {
+            if base & mask != 0 {
+                continue;
+            }
+            for (r, ket) in kets.iter().enumerate() {
+                for (c, bra) in bras.iter().enumerate() {
+                    block[r * d + c] = self.elements[base | ket | bra];
+                    self.elements[base | ket | bra] = Complex64::new(0.0, 0.0);
+                }
+            }
+            for k in kraus_operators {
+                // product = K B, then ρ += product K†
+                for r in 0..d {
+                    for c in 0..d {
+                        product[r * d + c] = (0..d).map(|m| k[r * d + m] * block[m * d + c]).sum();
+                    }
+                }
+                for (r, ket) in kets.iter().enumerate() {
+                    for (c, bra) in bras.iter().enumerate() {
+                        self.elements[base | ket | bra] += (0..d)
+                            .map(|m| product[r * d + m] * k[c * d + m].conj())
+                            .sum::<Complex64>();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Apply the unitary `u` to `qubit`.
+    pub fn apply_unitary(&mut self, qubit: u64, u: Matrix2) {
+        self.apply_superoperator(qubit, &superoperator(&[u]));
+    }
+
+    /// Apply a diagonal two-qubit unitary, where `diagonal[b0 + 2 * b1]` is the
+    /// factor applied to basis states in which `qubit_0` is b0 and `qubit_1` is b1.
+    pub fn apply_diagonal_2q(&mut self, qubit_0: u64, qubit_1: u64, diagonal: [Complex64; 4]) {
+        let n = self.n_qubits;
+        let row_mask = self.dimension() - 1;
+        let factor = |x: usize| diagonal[((x >> qubit_0) & 1) | (((x >> qubit_1) & 1) << 1)];
+        for (i, element) in self.elements.iter_mut().enumerate() {
+            let row = i & row_mask;
+            let col = i >> n;
+            *element *= factor(row) * factor(col).conj();
+        }
+    }
+
+    /// Apply a diagonal unitary on `qubits`, where `diagonal[k]` is the factor
+    /// applied to basis states in which `qubits[j]` takes the value of bit j of k.
+    pub fn apply_diagonal_nq(&mut self, qubits: &[u64], diagonal: &[Complex64]) {
+        let n = self.n_qubits;
+        let row_mask = self.dimension() - 1;
+        let factor = |x: usize| {
+            diagonal[qubits
+                .iter()
+                .enumerate()
+                .fold(0usize, |acc, (j, &q)| acc | (((x >> q) & 1) << j))]
+        };
+        for (i, element) in self.elements.iter_mut().enumerate() {
+            let row = i & row_mask;
+            let col = i >> n;
+            *element *= factor(row) * factor(col).conj();
+        }
+    }
+
+    /// The probability of measuring `qubit` as 1.
+    pub fn probability_of_one(&self, qubit: u64) -> f64 {
+        (0..self.dimension())
+            .filter(|basis_state| (basis_state >> qubit) & 1 == 1)
+            .map(|basis_state| self.elements[self.diagonal_index(basis_state)].re)
+            .sum()
+    }
+
+    /// Project `qubit` onto `value`, scaling the remainder by `scale`.
+    pub fn project(&mut self, qubit: u64, value: bool, scale: f64) {
+        let value = value as usize;
+        let ket_shift = qubit;
+        let bra_shift = qubit + self.n_qubits;
+        for (i, element) in self.elements.iter_mut().enumerate() {
+            if (i >> ket_shift) & 1 == value && (i >> bra_shift) & 1 == value {
+                *element *= scale;
+            } else {
+                *element = Complex64::new(0.0, 0.0);
+            }
+        }
+    }
+
+    /// Tr(ρ²), which is 1 for pure states and 1/2^n for the maximally mixed state.
+    pub fn purity(&self) -> f64 {
+        // ρ is Hermitian, so Tr(ρ²) = Σ_ij ρ_ij ρ_ji = Σ_ij |ρ_ij|².
+        self.elements.iter().map(|x| x.norm_sqr()).sum()
+    }
+
+    /// The reduced density matrix of `qubits` in row-major order, tracing out all
+    /// other qubits. The first of `qubits` is the most significant bit of the row
+    /// and column indices of the result.
+    pub fn reduced(&self, qubits: &[u64]) -> Vec<Complex64> {
+        let k = qubits.len();
+        let reduced_dimension = 1usize << k;
+        let specified_mask = qubits.iter().fold(0usize, |mask, &q| mask | (1 << q));
+        let reduce = |x: usize| {
+            qubits
+                .iter()
+                .enumerate()
+                .fold(0usize, |acc, (j, &q)| acc | (((x >> q) & 1) << (k - 1 - j)))
+        };
+        let mut result = vec![Complex64::new(0.0, 0.0); reduced_dimension * reduced_dimension];
+        for row in 0..self.dimension() {
+            for col in 0..self.dimension() {
+                if (row ^ col) & !specified_mask == 0 {
+                    result[reduce(row) * reduced_dimension + reduce(col)] +=
+                        self.elements[row | (col << self.n_qubits)];
+                }
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,534 @@
+/// A density-matrix simulator plugin for Selene.
+//
+// The full density matrix of the register is stored, which allows noise to be
+// described by arbitrary (non-unitary) channels rather than by stochastically
+// sampled Pauli errors. Channels configured through the plugin arguments are
+// applied exactly to every qubit a gate acts on, so a single shot evolves the
+// exact noisy state and sampling is only required at measurement. Channels
+// requested by error models through the simulator interface are likewise
+// applied exactly.
+//
+// Gate definitions and the qubit ordering match the QuEST plugin. dump_state
+// writes the reduced density matrix of the requested qubits, which can be read
+// with the state utilities provided by selene_quest_plugin.
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use num_complex::Complex64;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use selene_core::export_simulator_plugin;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::simulator::{
+    SimulatorInterface, kraus_operator_size, validate_kraus_operators, validate_pauli_channel,
+};
+use selene_core::utils::MetricValue;
+use std::io::Write;
+
+mod density_matrix;
+use density_matrix::{DensityMatrix, Matrix2, Superoperator, completeness_error, superoperator};
+
+#[cfg(test)]
+mod tests;
+
+const ZERO: Complex64 = Complex64::new(0.0, 0.0);
+const ONE: Complex64 = Complex64::new(1.0, 0.0);
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// The probability of amplitude damping (decay from |1> to |0>) applied to
+    /// each qubit that a gate acts on.
+    #[arg(long, default_value_t = 0.0)]
+    amplitude_damping: f64,
+    /// The probability of dephasing (a Z error) applied to each qubit that a
+    /// gate acts on.
+    #[arg(long, default_value_t = 0.0)]
+    dephasing: f64,
+    /// A single-qubit Kraus operator, provided as eight comma-separated values
+    /// holding the real and imaginary parts of its elements in row-major order.
+    /// Repeat the argument to provide each operator of the channel. The channel
+    /// is applied to each qubit that a gate acts on, after any amplitude damping
+    /// and dephasing.
+    #[arg(long)]
+    kraus: Vec<String>,
+}
+
+fn amplitude_damping_kraus(gamma: f64) -> Vec<Matrix2> {
+    vec![
+        [
+            [ONE, ZERO],
+            [ZERO, Complex64::new((1.0 - gamma).sqrt(), 0.0)],
+        ],
+        [[ZERO, Complex64::new(gamma.sqrt(), 0.0)], [ZERO, ZERO]],
+    ]
+}
+
+fn dephasing_kraus(p: f64) -> Vec<Matrix2> {
+    let keep = Complex64::new((1.0 - p).sqrt(), 0.0);
+    let flip = Complex64::new(p.sqrt(), 0.0);
+    vec![[[keep, ZERO], [ZERO, keep]], [[flip, ZERO], [ZERO, -flip]]]
+}
+
+fn phase_damping_kraus(lambda: f64) -> Vec<Matrix2> {
+    vec![
+        [
+            [ONE, ZERO],
+            [ZERO, Complex64::new((1.0 - lambda).sqrt(), 0.0)],
+        ],
+        [[ZERO, ZERO], [ZERO, Complex64::new(lambda.sqrt(), 0.0)]],
+    ]
+}
+
+fn pauli_kraus(p_x: f64, p_y: f64, p_z: f64) -> Vec<Matrix2> {
+    let i = Complex64::new(0.0, 1.0);
+    let scaled = |p: f64| Complex64::new(p.max(0.0).sqrt(), 0.0);
+    let (k_i, k_x, k_y, k_z) = (
+        scaled(1.0 - p_x - p_y - p_z),
+        scaled(p_x),
+        scaled(p_y),
+        scaled(p_z),
+    );
+    vec![
+        [[k_i, ZERO], [ZERO, k_i]],
+        [[ZERO, k_x], [k_x, ZERO]],
+        [[ZERO, -i * k_y], [i * k_y, ZERO]],
+        [[k_z, ZERO], [ZERO, -k_z]],
+    ]
+}
+
+fn reset_kraus() -> Vec<Matrix2> {
+    vec![[[ONE, ZERO], [ZERO, ZERO]], [[ZERO, ONE], [ZERO, ZERO]]]
+}
+
+fn parse_kraus_operator(encoded: &str) -> Result<Matrix2> {
+    let values = encoded
+        .split(',')
+        .map(|v| {
+            v.trim()
+                .parse::<f64>()
+                .map_err(|e| anyhow!("Invalid value '{v}' in Kraus operator '{encoded}': {e}"))
+        })
+        .collect::<Result<Vec<f64>>>()?;
+    if values.len() != 8 {
+        bail!(
+            "Kraus operator '{encoded}' must contain 8 values (real and imaginary parts of a 2x2 matrix), found {}",
+            values.len()
+        );
+    }
+    let element = |i: usize| Complex64::new(values[2 * i], values[2 * i + 1]);
+    Ok([[element(0), element(1)], [element(2), element(3)]])
+}
+
+fn compose(first: &Superoperator, second: &Superoperator) -> Superoperator {
+    let mut result = [[ZERO; 4]; 4];
+    for (i, row) in result.iter_mut().enumerate() {
+        for (j, element) in row.iter_mut().enumerate() {
+            *element = (0..4).map(|k| second[i][k] * first[k][j]).sum();
+        }
+    }
+    result
+}
+
+pub struct DensityMatrixSimulator {
+    state: DensityMatrix,
+    n_qubits: u64,
+    rng: Pcg64Mcg,
+    gate_noise: Option<Superoperator>,
+    cumulative_postselect_probability: f64,
+}
+
+impl DensityMatrixSimulator {
+    /// Apply the channel ρ -> Σ K ρ K† to `q0`.
+    pub fn apply_channel(&mut self, q0: u64, kraus_operators: &[Matrix2]) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Channel(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        let error = completeness_error(kraus_operators);
+        if error > 1e-8 {
+            bail!(
+                "The provided Kraus operators do not form a trace-preserving channel (deviation from completeness: {error:.2e})"
+            );
+        }
+        self.state
+            .apply_superoperator(q0, &superoperator(kraus_operators));
+        Ok(())
+    }
+
+    fn apply_gate_noise(&mut self, q0: u64) {
+        if let Some(noise) = &self.gate_noise {
+            self.state.apply_superoperator(q0, noise);
+        }
+    }
+}
+
+impl SimulatorInterface for DensityMatrixSimulator {
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn shot_start(&mut self, _shot_id: u64, seed: u64) -> Result<()> {
+        self.state.initialise();
+        self.rng = Pcg64Mcg::seed_from_u64(seed);
+        self.cumulative_postselect_probability = 1.0;
+        Ok(())
+    }
+
+    fn shot_end(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn rz(&mut self, q0: u64, theta: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "RZ(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        // diag(e^{-iθ/2}, e^{iθ/2}), matching QuEST's rotateZ
+        let phase = Complex64::from_polar(1.0, theta / 2.0);
+        self.state
+            .apply_unitary(q0, [[phase.conj(), ZERO], [ZERO, phase]]);
+        self.apply_gate_noise(q0);
+        Ok(())
+    }
+
+    fn rxy(&mut self, q0: u64, theta: f64, phi: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "RXY(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        // ⎡ cos(θ/2)            -i⋅e^{-iφ}⋅sin(θ/2) ⎤
+        // ⎣ -i⋅e^{iφ}⋅sin(θ/2)   cos(θ/2)           ⎦
+        let cos = Complex64::new((theta / 2.0).cos(), 0.0);
+        let sin = (theta / 2.0).sin();
+        let upper = Complex64::new(0.0, -sin) * Complex64::from_polar(1.0, -phi);
+        let lower = Complex64::new(0.0, -sin) * Complex64::from_polar(1.0, phi);
+        self.state.apply_unitary(q0, [[cos, upper], [lower, cos]]);
+        self.apply_gate_noise(q0);
+        Ok(())
+    }
+
+    fn rzz(&mut self, q0: u64, q1: u64, theta: f64) -> Result<()> {
+        if q0 >= self.n_qubits || q1 >= self.n_qubits {
+            bail!(
+                "RZZ(q0={q0}, q1={q1}) is out of bounds. q0 and q1 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        // diag(1, e^{iθ}, e^{iθ}, 1), matching the QuEST plugin
+        let phase = Complex64::from_polar(1.0, theta);
+        self.state
+            .apply_diagonal_2q(q0, q1, [ONE, phase, phase, ONE]);
+        self.apply_gate_noise(q0);
+        self.apply_gate_noise(q1);
+        Ok(())
+    }
+
+    fn u(&mut self, q0: u64, theta: f64, phi: f64, lambda: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "U(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        // ⎡ cos(θ/2)            -e^{iλ}⋅sin(θ/2)     ⎤
+        // ⎣ e^{iφ}⋅sin(θ/2)     e^{i(φ+λ)}⋅cos(θ/2) ⎦
+        let cos = (theta / 2.0).cos();
+        let sin = (theta / 2.0).sin();
+        self.state.apply_unitary(
+            q0,
+            [
+                [
+                    Complex64::new(cos, 0.0),
+                    -Complex64::from_polar(sin, lambda),
+                ],
+                [
+                    Complex64::from_polar(sin, phi),
+                    Complex64::from_polar(cos, phi + lambda),
+                ],
+            ],
+        );
+        self.apply_gate_noise(q0);
+        Ok(())
+    }
+
+    fn two_qubit_unitary(&mut self, q0: u64, q1: u64, matrix: &[f64; 32]) -> Result<()> {
+        if q0 >= self.n_qubits || q1 >= self.n_qubits {
+            bail!(
+                "TwoQubitUnitary(q0={q0}, q1={q1}) is out of bounds. q0 and q1 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        if q0 == q1 {
+            bail!("TwoQubitUnitary requires distinct qubits, but qubit {q0} was provided twice.");
+        }
+        let matrix: Vec<Complex64> = matrix
+            .chunks_exact(2)
+            .map(|v| Complex64::new(v[0], v[1]))
+            .collect();
+        self.state.apply_kraus(&[q0, q1], &[matrix]);
+        self.apply_gate_noise(q0);
+        self.apply_gate_noise(q1);
+        Ok(())
+    }
+
+    fn diagonal_phase(&mut self, qubits: &[u64], phases: &[f64]) -> Result<()> {
+        for (i, &q) in qubits.iter().enumerate() {
+            if q >= self.n_qubits {
+                bail!(
+                    "DiagonalPhase(q={q}) is out of bounds. q must be less than the number of qubits ({}).",
+                    self.n_qubits
+                );
+            }
+            if qubits[..i].contains(&q) {
+                bail!("DiagonalPhase requires distinct qubits, but qubit {q} was provided twice.");
+            }
+        }
+        if phases.len() != 1 << qubits.len() {
+            bail!(
+                "DiagonalPhase on {} qubits requires {} phases, got {}.",
+                qubits.len(),
+                1usize << qubits.len(),
+                phases.len()
+            );
+        }
+        let diagonal: Vec<Complex64> = phases
+            .iter()
+            .map(|&phase| Complex64::from_polar(1.0, phase))
+            .collect();
+        self.state.apply_diagonal_nq(qubits, &diagonal);
+        for &q in qubits {
+            self.apply_gate_noise(q);
+        }
+        Ok(())
+    }
+
+    fn measure(&mut self, q0: u64) -> Result<bool> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Measure(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        let p1 = self.state.probability_of_one(q0);
+        let outcome = self.rng.random::<f64>() < p1;
+        let probability = if outcome { p1 } else { 1.0 - p1 };
+        self.state.project(q0, outcome, 1.0 / probability);
+        Ok(outcome)
+    }
+
+    fn postselect(&mut self, q0: u64, target_value: bool) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Postselect(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        let p1 = self.state.probability_of_one(q0);
+        let postselect_probability = if target_value { p1 } else { 1.0 - p1 };
+        self.cumulative_postselect_probability *= postselect_probability;
+        if postselect_probability < 1e-10 {
+            bail!(
+                "Postselection of {} on qubit {q0} is too unlikely to postselect. The probability of this outcome is {postselect_probability:.2e}.",
+                target_value as u8
+            );
+        }
+        self.state
+            .project(q0, target_value, 1.0 / postselect_probability);
+        Ok(())
+    }
+
+    fn reset(&mut self, q0: u64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Reset(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        // Reset is applied as the channel ρ -> |0><0|⊗Tr_q0(ρ), which requires
+        // no sampling.
+        self.state
+            .apply_superoperator(q0, &superoperator(&reset_kraus()));
+        Ok(())
+    }
+
+    fn pauli_channel(&mut self, q0: u64, p_x: f64, p_y: f64, p_z: f64) -> Result<()> {
+        validate_pauli_channel(p_x, p_y, p_z)?;
+        self.apply_channel(q0, &pauli_kraus(p_x, p_y, p_z))
+    }
+
+    fn amplitude_damping(&mut self, q0: u64, gamma: f64) -> Result<()> {
+        if !(0.0..=1.0).contains(&gamma) {
+            bail!("Amplitude damping gamma must be a probability in [0, 1], got {gamma}");
+        }
+        self.apply_channel(q0, &amplitude_damping_kraus(gamma))
+    }
+
+    fn phase_damping(&mut self, q0: u64, lambda: f64) -> Result<()> {
+        if !(0.0..=1.0).contains(&lambda) {
+            bail!("Phase damping lambda must be in [0, 1], got {lambda}");
+        }
+        self.apply_channel(q0, &phase_damping_kraus(lambda))
+    }
+
+    fn kraus_channel(&mut self, qubits: &[u64], kraus_operators: &[f64]) -> Result<()> {
+        for (i, &q) in qubits.iter().enumerate() {
+            if q >= self.n_qubits {
+                bail!(
+                    "KrausChannel(q={q}) is out of bounds. q must be less than the number of qubits ({}).",
+                    self.n_qubits
+                );
+            }
+            if qubits[..i].contains(&q) {
+                bail!("KrausChannel requires distinct qubits, but qubit {q} was provided twice.");
+            }
+        }
+        validate_kraus_operators(qubits.len(), kraus_operators)?;
+        let kraus_operators: Vec<Vec<Complex64>> = kraus_operators
+            .chunks_exact(kraus_operator_size(qubits.len())?)
+            .map(|k| {
+                k.chunks_exact(2)
+                    .map(|v| Complex64::new(v[0], v[1]))
+                    .collect()
+            })
+            .collect();
+        self.state.apply_kraus(qubits, &kraus_operators);
+        Ok(())
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "cumulative_postselect_probability".to_string(),
+                MetricValue::F64(self.cumulative_postselect_probability),
+            ))),
+            1 => Ok(Some((
+                "purity".to_string(),
+                MetricValue::F64(self.state.purity()),
+            ))),
+            _ => Ok(None),
+        }
+    }
+
+    fn dump_state(&mut self, file: &std::path::Path, qubits: &[u64]) -> Result<()> {
+        if let Some(q) = qubits.iter().find(|&&q| q >= self.n_qubits) {
+            bail!(
+                "DumpState(q={q}) is out of bounds. All qubits must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        let handle = std::fs::File::create(file)?;
+        let mut writer = std::io::BufWriter::new(handle);
+        writer.write_all(b"selene-rdmat")?;
+        writer.write_all(self.n_qubits.to_le_bytes().as_slice())?;
+        writer.write_all((qubits.len() as u64).to_le_bytes().as_slice())?;
+        for &q in qubits {
+            writer.write_all(q.to_le_bytes().as_slice())?;
+        }
+        for element in self.state.reduced(qubits) {
+            writer.write_all(element.re.to_le_bytes().as_slice())?;
+            writer.write_all(element.im.to_le_bytes().as_slice())?;
+        }
+        Ok(())
+    }
+}
+
+fn check_memory(n_qubits: u64) -> Result<()> {
+    if n_qubits == 0 {
+        bail!("Number of qubits must be greater than 0");
+    } else if n_qubits > 30 {
+        bail!(
+            "It is impossible to describe more than 30 qubits in a density matrix on a computer with a 64-bit address space."
+        );
+    }
+    let bytes_required = bytesize::ByteSize::b(16 * (1 << (2 * n_qubits)));
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    let reported_available = system.available_memory();
+    if reported_available == 0 {
+        eprintln!("-----------------------------------");
+        eprintln!("Unable to determine available memory due to system limitations.");
+        eprintln!("The density matrix simulator is going to try to allocate {bytes_required}");
+        eprintln!("of memory to store the density matrix, and this will be multiplied by");
+        eprintln!("the number of processes if running in multiprocessing mode.");
+        eprintln!();
+        eprintln!("If this fails, verify that your system has sufficient memory.");
+        eprintln!("-----------------------------------");
+    } else {
+        let bytes_available = bytesize::ByteSize::b(reported_available);
+        if bytes_required > bytes_available {
+            bail!(
+                "Insufficient memory available ({bytes_available}) to allocate a density matrix of {n_qubits} qubits ({bytes_required}).",
+            );
+        }
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct DensityMatrixSimulatorFactory;
+
+impl SimulatorInterfaceFactory for DensityMatrixSimulatorFactory {
+    type Interface = DensityMatrixSimulator;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        let args: Vec<String> = args.iter().map(|s| s.as_ref().to_string()).collect();
+        let params = Params::try_parse_from(&args)?;
+        if !(0.0..=1.0).contains(&params.amplitude_damping) {
+            bail!(
+                "amplitude_damping must be a probability in [0, 1], got {}",
+                params.amplitude_damping
+            );
+        }
+        if !(0.0..=1.0).contains(&params.dephasing) {
+            bail!(
+                "dephasing must be a probability in [0, 1], got {}",
+                params.dephasing
+            );
+        }
+        let mut channels = Vec::new();
+        if params.amplitude_damping > 0.0 {
+            channels.push(amplitude_damping_kraus(params.amplitude_damping));
+        }
+        if params.dephasing > 0.0 {
+            channels.push(dephasing_kraus(params.dephasing));
+        }
+        if !params.kraus.is_empty() {
+            let kraus_operators = params
+                .kraus
+                .iter()
+                .map(|k| parse_kraus_operator(k))
+                .collect::<Result<Vec<_>>>()?;
+            let error = completeness_error(&kraus_operators);
+            if error > 1e-8 {
+                bail!(
+                    "The provided Kraus operators do not form a trace-preserving channel (deviation from completeness: {error:.2e})"
+                );
+            }
+            channels.push(kraus_operators);
+        }
+        let gate_noise = channels
+            .iter()
+            .map(|kraus_operators| superoperator(kraus_operators))
+            .reduce(|applied, next| compose(&applied, &next));
+
+        check_memory(n_qubits)?;
+        Ok(Box::new(DensityMatrixSimulator {
+            state: DensityMatrix::new(n_qubits),
+            n_qubits,
+            rng: Pcg64Mcg::seed_from_u64(0),
+            gate_noise,
+            cumulative_postselect_probability: 1.0,
+        }))
+    }
+}
+
+export_simulator_plugin!(crate::DensityMatrixSimulatorFactory);
@@ -0,0 +1,113 @@
+use crate::DensityMatrixSimulatorFactory;
+use selene_core::simulator::SimulatorInterface;
+use selene_core::simulator::conformance_testing::run_basic_tests;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+#[test]
+fn basic_conformance_test() {
+    let interface = Arc::new(DensityMatrixSimulatorFactory);
+    let args = vec![];
+    run_basic_tests(interface, args);
+}
+
+#[test]
+fn amplitude_damping_is_exact() {
+    let mut sim = Arc::new(DensityMatrixSimulatorFactory)
+        .init(2, &["", "--amplitude-damping=0.25"])
+        .unwrap();
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(0, PI, 0.0).unwrap();
+    approx::assert_relative_eq!(sim.state.probability_of_one(0), 0.75, epsilon = 1e-12);
+    sim.rz(0, 0.3).unwrap();
+    approx::assert_relative_eq!(sim.state.probability_of_one(0), 0.5625, epsilon = 1e-12);
+    // qubit 1 is untouched
+    approx::assert_relative_eq!(sim.state.probability_of_one(1), 0.0, epsilon = 1e-12);
+}
+
+#[test]
+fn kraus_operators_match_builtin_dephasing() {
+    let p: f64 = 0.1;
+    let keep = (1.0 - p).sqrt();
+    let flip = p.sqrt();
+    let kraus_args = [
+        "".to_string(),
+        format!("--kraus={keep},0,0,0,0,0,{keep},0"),
+        format!("--kraus={flip},0,0,0,0,0,{},0", -flip),
+    ];
+    let mut from_kraus = Arc::new(DensityMatrixSimulatorFactory)
+        .init(1, &kraus_args)
+        .unwrap();
+    let mut builtin = Arc::new(DensityMatrixSimulatorFactory)
+        .init(1, &["", "--dephasing=0.1"])
+        .unwrap();
+    for sim in [&mut from_kraus, &mut builtin] {
+        sim.shot_start(0, 0).unwrap();
+        sim.rxy(0, PI / 2.0, 0.0).unwrap();
+    }
+    // The off-diagonal elements shrink by a factor of (1 - 2p)
+    let expected_purity = 0.5 + 0.5 * (1.0 - 2.0 * p).powi(2);
+    approx::assert_relative_eq!(from_kraus.state.purity(), expected_purity, epsilon = 1e-12);
+    approx::assert_relative_eq!(builtin.state.purity(), expected_purity, epsilon = 1e-12);
+}
+
+#[test]
+fn reset_is_deterministic() {
+    let mut sim = Arc::new(DensityMatrixSimulatorFactory)
+        .init(2, &[""])
+        .unwrap();
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(0, PI / 2.0, 0.0).unwrap();
+    sim.rxy(1, PI / 2.0, 0.0).unwrap();
+    sim.rzz(0, 1, PI / 2.0).unwrap();
+    sim.reset(0).unwrap();
+    approx::assert_relative_eq!(sim.state.probability_of_one(0), 0.0, epsilon = 1e-12);
+    // tracing out an entangled qubit leaves qubit 1 mixed
+    approx::assert_relative_eq!(sim.state.purity(), 0.5, epsilon = 1e-12);
+}
+
+#[test]
+fn incomplete_kraus_operators_are_rejected() {
+    let result =
+        Arc::new(DensityMatrixSimulatorFactory).init(1, &["", "--kraus=0.5,0,0,0,0,0,0.5,0"]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn interface_channels_are_exact() {
+    let mut sim = Arc::new(DensityMatrixSimulatorFactory)
+        .init(3, &[""])
+        .unwrap();
+    sim.shot_start(0, 0).unwrap();
+    sim.pauli_channel(0, 0.2, 0.1, 0.3).unwrap();
+    approx::assert_relative_eq!(sim.state.probability_of_one(0), 0.3, epsilon = 1e-12);
+    sim.amplitude_damping(0, 0.5).unwrap();
+    approx::assert_relative_eq!(sim.state.probability_of_one(0), 0.15, epsilon = 1e-12);
+
+    sim.shot_start(1, 0).unwrap();
+    sim.rxy(1, PI / 2.0, 0.0).unwrap();
+    sim.phase_damping(1, 0.36).unwrap();
+    // The coherences shrink by a factor of sqrt(1 - λ) = 0.8
+    approx::assert_relative_eq!(sim.state.purity(), 0.5 + 0.5 * 0.64, epsilon = 1e-12);
+
+    // A correlated two-qubit bit flip, applying X⊗X with probability p
+    let p: f64 = 0.25;
+    let (keep, flip) = ((1.0 - p).sqrt(), p.sqrt());
+    let mut kraus = vec![0.0; 64];
+    for i in 0..4 {
+        kraus[2 * (i * 4 + i)] = keep;
+        kraus[32 + 2 * (i * 4 + (3 - i))] = flip;
+    }
+    sim.shot_start(2, 0).unwrap();
+    sim.kraus_channel(&[2, 0], &kraus).unwrap();
+    approx::assert_relative_eq!(sim.state.probability_of_one(0), p, epsilon = 1e-12);
+    approx::assert_relative_eq!(sim.state.probability_of_one(1), 0.0, epsilon = 1e-12);
+    approx::assert_relative_eq!(sim.state.probability_of_one(2), p, epsilon = 1e-12);
+    approx::assert_relative_eq!(
+        sim.state.purity(),
+        (1.0 - p).powi(2) + p.powi(2),
+        epsilon = 1e-12
+    );
+    assert!(sim.kraus_channel(&[0, 1], &kraus[..32]).is_err());
+}
@@ -0,0 +1,3 @@
+from .plugin import FusionPlugin
+
+__all__ = ["FusionPlugin"]
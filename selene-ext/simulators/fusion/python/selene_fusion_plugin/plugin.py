@@ -0,0 +1,58 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+
+from selene_core import Simulator
+
+
+@dataclass
+class FusionPlugin(Simulator):
+    """
+    A plugin that fuses consecutive single-qubit gates on each qubit into a
+    single unitary before passing them to another simulator. This reduces the
+    number of passes a statevector simulator such as QuEST makes over the
+    state, without changing the results.
+
+    Pending gates on a qubit are applied as one U gate when the qubit is next
+    involved in a two-qubit gate, measurement, reset, noise channel, query or
+    state dump. A single pending gate is passed on unchanged. The number of
+    single-qubit gates received and applied is reported through the
+    `received_single_qubit_gates` and `applied_single_qubit_gates` metrics,
+    followed by the metrics of the wrapped simulator.
+
+    Attributes:
+        simulator (Simulator): The simulator to pass the fused gates to. It
+            should support U gates natively for fusion to improve throughput.
+    """
+
+    simulator: Simulator | None = None
+
+    def __post_init__(self):
+        assert self.simulator is not None, (
+            "A simulator must be provided to the fusion plugin"
+        )
+        self.random_seed = self.simulator.random_seed
+
+    def get_init_args(self):
+        assert self.simulator is not None
+        return [f"--simulator-path={self.simulator.library_file}"] + [
+            f"--simulator-arg={arg}" for arg in self.simulator.get_init_args()
+        ]
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_fusion_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_fusion_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_fusion_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    @property
+    def library_search_dirs(self):
+        assert self.simulator is not None
+        return self.simulator.library_search_dirs
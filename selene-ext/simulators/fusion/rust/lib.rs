@@ -0,0 +1,393 @@
+/// A simulator plugin for Selene that fuses runs of single-qubit gates into a
+/// single unitary before passing them to a wrapped simulator.
+//
+// Error models pass each RZ, RXY and U gate to the simulator individually,
+// and a statevector simulator makes a full pass over the state for each of
+// them. This plugin instead accumulates the product of consecutive
+// single-qubit gates on each qubit as a 2x2 unitary, which is applied to the
+// wrapped simulator as one U gate when the qubit is next involved in anything
+// else: a two-qubit gate, a measurement, a reset, a noise channel, a query or
+// a state dump. Snapshots and state dumps flush every qubit, and restoring a
+// snapshot discards any pending gates, as the state they would act on is
+// replaced.
+//
+// A run of a single gate is passed on unchanged, so that simulators restricted
+// to particular gates (such as Clifford simulators) see the same operations as
+// without fusion. Fused runs are applied with U, so the wrapped simulator
+// should support U natively for fusion to pay off. Otherwise, each fused run
+// is decomposed into three gates.
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use num_complex::Complex64;
+use selene_core::export_simulator_plugin;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::simulator::{Pauli, Simulator, SimulatorInterface};
+use selene_core::utils::MetricValue;
+use std::collections::BTreeMap;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// The path to the wrapped simulator plugin
+    #[arg(long)]
+    simulator_path: String,
+    /// Arguments for the wrapped simulator plugin
+    #[arg(long)]
+    simulator_arg: Vec<String>,
+}
+
+/// A 2x2 matrix, indexed by row and then column.
+type Matrix = [[Complex64; 2]; 2];
+
+/// Below this magnitude, a fused unitary is treated as the identity (up to a
+/// global phase) and is not applied at all.
+const IDENTITY_TOLERANCE: f64 = 1e-12;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Gate {
+    Rz { theta: f64 },
+    Rxy { theta: f64, phi: f64 },
+    U { theta: f64, phi: f64, lambda: f64 },
+}
+
+impl Gate {
+    fn matrix(&self) -> Matrix {
+        let i = Complex64::i();
+        match *self {
+            Gate::Rz { theta } => [
+                [Complex64::from_polar(1.0, -theta / 2.0), 0.0.into()],
+                [0.0.into(), Complex64::from_polar(1.0, theta / 2.0)],
+            ],
+            Gate::Rxy { theta, phi } => {
+                let (sin, cos) = (theta / 2.0).sin_cos();
+                [
+                    [cos.into(), -i * Complex64::from_polar(sin, -phi)],
+                    [-i * Complex64::from_polar(sin, phi), cos.into()],
+                ]
+            }
+            Gate::U { theta, phi, lambda } => u_matrix(theta, phi, lambda),
+        }
+    }
+}
+
+/// The matrix of U(θ, φ, λ), as defined by `Operation::UGate`.
+fn u_matrix(theta: f64, phi: f64, lambda: f64) -> Matrix {
+    let (sin, cos) = (theta / 2.0).sin_cos();
+    [
+        [cos.into(), -Complex64::from_polar(sin, lambda)],
+        [
+            Complex64::from_polar(sin, phi),
+            Complex64::from_polar(cos, phi + lambda),
+        ],
+    ]
+}
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let element = |row: usize, col: usize| a[row][0] * b[0][col] + a[row][1] * b[1][col];
+    [
+        [element(0, 0), element(0, 1)],
+        [element(1, 0), element(1, 1)],
+    ]
+}
+
+/// The angles (θ, φ, λ) of U(θ, φ, λ) equal to the given unitary up to a
+/// global phase.
+///
+/// The global phase is taken from the top-left element. Each remaining angle
+/// is read from the phase of an element with a reliable magnitude: when θ is
+/// close to zero, the off-diagonal elements are tiny and λ is instead read
+/// from the bottom-right element, and vice versa when θ is close to π.
+fn u_angles(m: &Matrix) -> (f64, f64, f64) {
+    let theta = 2.0 * m[1][0].norm().atan2(m[0][0].norm());
+    let global_phase = m[0][0].arg();
+    let phi = m[1][0].arg() - global_phase;
+    let lambda = if m[0][0].norm() >= m[1][0].norm() {
+        m[1][1].arg() - m[1][0].arg()
+    } else {
+        (-m[0][1]).arg() - global_phase
+    };
+    (theta, phi, lambda)
+}
+
+/// The single-qubit gates waiting to be applied to a qubit.
+struct PendingGates {
+    /// The product of the gates, in application order.
+    matrix: Matrix,
+    /// The first gate, which is applied directly if it is the only one.
+    first: Gate,
+    count: u64,
+}
+
+pub struct FusionSimulator {
+    simulator: Simulator,
+    n_qubits: u64,
+    pending: BTreeMap<u64, PendingGates>,
+    received_gates: u64,
+    applied_gates: u64,
+}
+
+impl FusionSimulator {
+    pub fn new(simulator: Simulator, n_qubits: u64) -> Self {
+        Self {
+            simulator,
+            n_qubits,
+            pending: BTreeMap::new(),
+            received_gates: 0,
+            applied_gates: 0,
+        }
+    }
+
+    fn check_bounds(&self, name: &str, qubits: &[u64]) -> Result<()> {
+        if let Some(q) = qubits.iter().find(|&&q| q >= self.n_qubits) {
+            bail!(
+                "{name} on qubit {q} is out of bounds. Qubits must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, name: &str, q0: u64, gate: Gate) -> Result<()> {
+        self.check_bounds(name, &[q0])?;
+        self.received_gates += 1;
+        let matrix = gate.matrix();
+        self.pending
+            .entry(q0)
+            .and_modify(|pending| {
+                pending.matrix = multiply(&matrix, &pending.matrix);
+                pending.count += 1;
+            })
+            .or_insert(PendingGates {
+                matrix,
+                first: gate,
+                count: 1,
+            });
+        Ok(())
+    }
+
+    /// Apply the pending gates on the given qubits to the wrapped simulator.
+    fn flush(&mut self, qubits: &[u64]) -> Result<()> {
+        for q0 in qubits {
+            if let Some(pending) = self.pending.remove(q0) {
+                self.apply(*q0, pending)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush_all(&mut self) -> Result<()> {
+        for (q0, pending) in std::mem::take(&mut self.pending) {
+            self.apply(q0, pending)?;
+        }
+        Ok(())
+    }
+
+    fn apply(&mut self, q0: u64, pending: PendingGates) -> Result<()> {
+        let gate = if pending.count == 1 {
+            pending.first
+        } else {
+            let (theta, phi, lambda) = u_angles(&pending.matrix);
+            let phase = Complex64::from_polar(1.0, phi + lambda);
+            if theta.abs() < IDENTITY_TOLERANCE && (phase - 1.0).norm() < IDENTITY_TOLERANCE {
+                return Ok(());
+            }
+            Gate::U { theta, phi, lambda }
+        };
+        self.applied_gates += 1;
+        match gate {
+            Gate::Rz { theta } => self.simulator.rz(q0, theta),
+            Gate::Rxy { theta, phi } => self.simulator.rxy(q0, theta, phi),
+            Gate::U { theta, phi, lambda } => self.simulator.u(q0, theta, phi, lambda),
+        }
+    }
+}
+
+impl SimulatorInterface for FusionSimulator {
+    fn exit(&mut self) -> Result<()> {
+        self.pending.clear();
+        self.simulator.exit()
+    }
+
+    fn shot_start(&mut self, shot_id: u64, seed: u64) -> Result<()> {
+        self.pending.clear();
+        self.received_gates = 0;
+        self.applied_gates = 0;
+        self.simulator.shot_start(shot_id, seed)
+    }
+
+    fn shot_end(&mut self) -> Result<()> {
+        self.flush_all()?;
+        self.simulator.shot_end()
+    }
+
+    fn rz(&mut self, q0: u64, theta: f64) -> Result<()> {
+        self.push("RZ", q0, Gate::Rz { theta })
+    }
+
+    fn rxy(&mut self, q0: u64, theta: f64, phi: f64) -> Result<()> {
+        self.push("RXY", q0, Gate::Rxy { theta, phi })
+    }
+
+    fn u(&mut self, q0: u64, theta: f64, phi: f64, lambda: f64) -> Result<()> {
+        self.push("U", q0, Gate::U { theta, phi, lambda })
+    }
+
+    fn rzz(&mut self, q0: u64, q1: u64, theta: f64) -> Result<()> {
+        self.check_bounds("RZZ", &[q0, q1])?;
+        self.flush(&[q0, q1])?;
+        self.simulator.rzz(q0, q1, theta)
+    }
+
+    fn two_qubit_unitary(&mut self, q0: u64, q1: u64, matrix: &[f64; 32]) -> Result<()> {
+        self.check_bounds("TwoQubitUnitary", &[q0, q1])?;
+        self.flush(&[q0, q1])?;
+        self.simulator.two_qubit_unitary(q0, q1, matrix)
+    }
+
+    fn diagonal_phase(&mut self, qubits: &[u64], phases: &[f64]) -> Result<()> {
+        self.check_bounds("DiagonalPhase", qubits)?;
+        self.flush(qubits)?;
+        self.simulator.diagonal_phase(qubits, phases)
+    }
+
+    fn measure(&mut self, q0: u64) -> Result<bool> {
+        self.check_bounds("Measure", &[q0])?;
+        self.flush(&[q0])?;
+        self.simulator.measure(q0)
+    }
+
+    fn postselect(&mut self, q0: u64, target_value: bool) -> Result<()> {
+        self.check_bounds("Postselect", &[q0])?;
+        self.flush(&[q0])?;
+        self.simulator.postselect(q0, target_value)
+    }
+
+    fn reset(&mut self, q0: u64) -> Result<()> {
+        self.check_bounds("Reset", &[q0])?;
+        self.flush(&[q0])?;
+        self.simulator.reset(q0)
+    }
+
+    fn qalloc(&mut self, q0: u64) -> Result<()> {
+        self.check_bounds("QAlloc", &[q0])?;
+        self.flush(&[q0])?;
+        self.simulator.qalloc(q0)
+    }
+
+    fn qfree(&mut self, q0: u64) -> Result<()> {
+        self.check_bounds("QFree", &[q0])?;
+        self.flush(&[q0])?;
+        self.simulator.qfree(q0)
+    }
+
+    fn measure_leaked(&mut self, q0: u64) -> Result<u64> {
+        self.check_bounds("MeasureLeaked", &[q0])?;
+        self.flush(&[q0])?;
+        self.simulator.measure_leaked(q0)
+    }
+
+    fn leakage_rxy(&mut self, q0: u64, theta: f64, phi: f64) -> Result<()> {
+        self.check_bounds("LeakageRXY", &[q0])?;
+        self.flush(&[q0])?;
+        self.simulator.leakage_rxy(q0, theta, phi)
+    }
+
+    fn snapshot(&mut self) -> Result<u64> {
+        self.flush_all()?;
+        self.simulator.snapshot()
+    }
+
+    fn restore(&mut self, handle: u64) -> Result<()> {
+        self.pending.clear();
+        self.simulator.restore(handle)
+    }
+
+    fn release_snapshot(&mut self, handle: u64) -> Result<()> {
+        self.simulator.release_snapshot(handle)
+    }
+
+    fn marginal_probabilities(&mut self, qubits: &[u64]) -> Result<Vec<f64>> {
+        self.check_bounds("MarginalProbabilities", qubits)?;
+        self.flush(qubits)?;
+        self.simulator.marginal_probabilities(qubits)
+    }
+
+    fn pauli_expectation(&mut self, pauli_string: &[(u64, Pauli)]) -> Result<f64> {
+        let qubits: Vec<u64> = pauli_string.iter().map(|&(q, _)| q).collect();
+        self.check_bounds("PauliExpectation", &qubits)?;
+        self.flush(&qubits)?;
+        self.simulator.pauli_expectation(pauli_string)
+    }
+
+    fn pauli_channel(&mut self, q0: u64, p_x: f64, p_y: f64, p_z: f64) -> Result<()> {
+        self.check_bounds("PauliChannel", &[q0])?;
+        self.flush(&[q0])?;
+        self.simulator.pauli_channel(q0, p_x, p_y, p_z)
+    }
+
+    fn amplitude_damping(&mut self, q0: u64, gamma: f64) -> Result<()> {
+        self.check_bounds("AmplitudeDamping", &[q0])?;
+        self.flush(&[q0])?;
+        self.simulator.amplitude_damping(q0, gamma)
+    }
+
+    fn phase_damping(&mut self, q0: u64, lambda: f64) -> Result<()> {
+        self.check_bounds("PhaseDamping", &[q0])?;
+        self.flush(&[q0])?;
+        self.simulator.phase_damping(q0, lambda)
+    }
+
+    fn kraus_channel(&mut self, qubits: &[u64], kraus_operators: &[f64]) -> Result<()> {
+        self.check_bounds("KrausChannel", qubits)?;
+        self.flush(qubits)?;
+        self.simulator.kraus_channel(qubits, kraus_operators)
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "received_single_qubit_gates".to_string(),
+                MetricValue::U64(self.received_gates),
+            ))),
+            1 => Ok(Some((
+                "applied_single_qubit_gates".to_string(),
+                MetricValue::U64(self.applied_gates),
+            ))),
+            n => self.simulator.get_metric(n - 2),
+        }
+    }
+
+    fn dump_state(&mut self, file: &std::path::Path, qubits: &[u64]) -> Result<()> {
+        // Simulators may dump more than the requested qubits, so everything
+        // is flushed.
+        self.flush_all()?;
+        self.simulator.dump_state(file, qubits)
+    }
+}
+
+#[derive(Default)]
+pub struct FusionSimulatorFactory;
+
+impl SimulatorInterfaceFactory for FusionSimulatorFactory {
+    type Interface = FusionSimulator;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        let args: Vec<String> = args.iter().map(|s| s.as_ref().to_string()).collect();
+        let params = Params::try_parse_from(args)
+            .map_err(|e| anyhow!("Error parsing arguments to fusion plugin: {e}"))?;
+        let simulator = Simulator::load_from_file(
+            &params.simulator_path,
+            n_qubits,
+            params.simulator_arg.as_ref(),
+        )?;
+        Ok(Box::new(FusionSimulator::new(simulator, n_qubits)))
+    }
+}
+
+export_simulator_plugin!(crate::FusionSimulatorFactory);
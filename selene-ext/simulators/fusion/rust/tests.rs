@@ -0,0 +1,223 @@
+use crate::{FusionSimulator, Gate, multiply, u_angles, u_matrix};
+use anyhow::Result;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::simulator::{Simulator, SimulatorInterface};
+use selene_core::utils::MetricValue;
+use std::f64::consts::PI;
+use std::sync::{Arc, Mutex};
+
+/// A simulator that only records the operations it receives.
+struct RecordingSimulator {
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl RecordingSimulator {
+    fn record(&self, operation: String) {
+        self.log.lock().unwrap().push(operation);
+    }
+}
+
+impl SimulatorInterface for RecordingSimulator {
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+    fn shot_start(&mut self, _shot_id: u64, _seed: u64) -> Result<()> {
+        Ok(())
+    }
+    fn shot_end(&mut self) -> Result<()> {
+        Ok(())
+    }
+    fn rz(&mut self, q0: u64, _theta: f64) -> Result<()> {
+        self.record(format!("rz {q0}"));
+        Ok(())
+    }
+    fn rxy(&mut self, q0: u64, _theta: f64, _phi: f64) -> Result<()> {
+        self.record(format!("rxy {q0}"));
+        Ok(())
+    }
+    fn u(&mut self, q0: u64, _theta: f64, _phi: f64, _lambda: f64) -> Result<()> {
+        self.record(format!("u {q0}"));
+        Ok(())
+    }
+    fn rzz(&mut self, q0: u64, q1: u64, _theta: f64) -> Result<()> {
+        self.record(format!("rzz {q0} {q1}"));
+        Ok(())
+    }
+    fn measure(&mut self, q0: u64) -> Result<bool> {
+        self.record(format!("measure {q0}"));
+        Ok(false)
+    }
+    fn reset(&mut self, q0: u64) -> Result<()> {
+        self.record(format!("reset {q0}"));
+        Ok(())
+    }
+    fn snapshot(&mut self) -> Result<u64> {
+        self.record("snapshot".to_string());
+        Ok(0)
+    }
+    fn restore(&mut self, _handle: u64) -> Result<()> {
+        self.record("restore".to_string());
+        Ok(())
+    }
+    fn get_metric(&mut self, _nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        Ok(None)
+    }
+}
+
+struct RecordingSimulatorFactory {
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl SimulatorInterfaceFactory for RecordingSimulatorFactory {
+    type Interface = RecordingSimulator;
+
+    fn init(
+        self: Arc<Self>,
+        _n_qubits: u64,
+        _args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        Ok(Box::new(RecordingSimulator {
+            log: self.log.clone(),
+        }))
+    }
+}
+
+fn fusion() -> (FusionSimulator, Arc<Mutex<Vec<String>>>) {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let no_args: &[&str] = &[];
+    let factory = Arc::new(RecordingSimulatorFactory { log: log.clone() });
+    let simulator = Simulator::new(factory, 3, no_args).unwrap();
+    (FusionSimulator::new(simulator, 3), log)
+}
+
+fn take(log: &Arc<Mutex<Vec<String>>>) -> Vec<String> {
+    std::mem::take(&mut *log.lock().unwrap())
+}
+
+#[test]
+fn fused_angles_reproduce_the_product() {
+    let sequences = [
+        vec![
+            Gate::Rz { theta: 0.3 },
+            Gate::Rxy {
+                theta: 1.1,
+                phi: -0.4,
+            },
+        ],
+        vec![
+            Gate::Rxy {
+                theta: PI,
+                phi: 0.2,
+            },
+            Gate::Rz { theta: 2.5 },
+            Gate::Rxy {
+                theta: PI,
+                phi: 1.7,
+            },
+        ],
+        // θ close to π, where the diagonal elements are tiny
+        vec![
+            Gate::Rxy {
+                theta: PI,
+                phi: 0.9,
+            },
+            Gate::Rz { theta: 1e-9 },
+            Gate::Rz { theta: -0.6 },
+        ],
+        // θ close to zero, where the off-diagonal elements are tiny
+        vec![
+            Gate::Rxy {
+                theta: 0.5,
+                phi: 0.3,
+            },
+            Gate::Rz { theta: 0.8 },
+            Gate::Rxy {
+                theta: -0.5,
+                phi: 0.3 + 1e-9,
+            },
+        ],
+        vec![
+            Gate::U {
+                theta: 2.0,
+                phi: -1.0,
+                lambda: 3.0,
+            },
+            Gate::Rz { theta: -4.0 },
+            Gate::U {
+                theta: -0.7,
+                phi: 0.1,
+                lambda: 0.2,
+            },
+        ],
+    ];
+    for sequence in sequences {
+        let product = sequence
+            .iter()
+            .fold(Gate::Rz { theta: 0.0 }.matrix(), |product, gate| {
+                multiply(&gate.matrix(), &product)
+            });
+        let (theta, phi, lambda) = u_angles(&product);
+        let fused = u_matrix(theta, phi, lambda);
+        // Compare up to the global phase, taken from the largest element
+        let (row, col) = if product[0][0].norm() > product[1][0].norm() {
+            (0, 0)
+        } else {
+            (1, 0)
+        };
+        let phase = product[row][col] / fused[row][col];
+        for row in 0..2 {
+            for col in 0..2 {
+                let difference = product[row][col] - phase * fused[row][col];
+                assert!(difference.norm() < 1e-12, "{sequence:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn gates_are_fused_until_the_qubit_is_used() {
+    let (mut sim, log) = fusion();
+    sim.shot_start(0, 0).unwrap();
+    sim.rz(0, 0.1).unwrap();
+    sim.rxy(0, 0.2, 0.3).unwrap();
+    sim.u(0, 0.4, 0.5, 0.6).unwrap();
+    sim.rz(1, 0.7).unwrap();
+    assert!(take(&log).is_empty());
+    sim.rzz(0, 2, 0.8).unwrap();
+    assert_eq!(take(&log), vec!["u 0", "rzz 0 2"]);
+    // A single pending gate is applied as it was received.
+    sim.measure(1).unwrap();
+    assert_eq!(take(&log), vec!["rz 1", "measure 1"]);
+    // Gates that cancel out are not applied at all.
+    sim.rxy(2, 0.9, 1.0).unwrap();
+    sim.rxy(2, -0.9, 1.0).unwrap();
+    sim.reset(2).unwrap();
+    assert_eq!(take(&log), vec!["reset 2"]);
+    assert!(matches!(
+        sim.get_metric(0).unwrap(),
+        Some((name, MetricValue::U64(6))) if name == "received_single_qubit_gates"
+    ));
+    assert!(matches!(
+        sim.get_metric(1).unwrap(),
+        Some((name, MetricValue::U64(2))) if name == "applied_single_qubit_gates"
+    ));
+    assert!(sim.get_metric(2).unwrap().is_none());
+    assert!(sim.rz(3, 0.1).is_err());
+}
+
+#[test]
+fn snapshots_flush_and_restores_discard_pending_gates() {
+    let (mut sim, log) = fusion();
+    sim.shot_start(0, 0).unwrap();
+    sim.rz(0, 0.1).unwrap();
+    sim.rxy(1, 0.2, 0.3).unwrap();
+    let handle = sim.snapshot().unwrap();
+    assert_eq!(take(&log), vec!["rz 0", "rxy 1", "snapshot"]);
+    sim.rz(0, 0.4).unwrap();
+    sim.restore(handle).unwrap();
+    sim.measure(0).unwrap();
+    assert_eq!(take(&log), vec!["restore", "measure 0"]);
+    sim.rz(2, 0.5).unwrap();
+    sim.shot_end().unwrap();
+    assert_eq!(take(&log), vec!["rz 2"]);
+}
@@ -0,0 +1,3 @@
+from .plugin import HybridPlugin
+
+__all__ = ["HybridPlugin"]
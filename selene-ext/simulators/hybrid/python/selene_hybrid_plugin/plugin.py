@@ -0,0 +1,83 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Iterable, Iterator
+
+from hugr.qsystem.result import TaggedResult
+from selene_core import Simulator
+from selene_quest_plugin import QuestPlugin, SeleneQuestState
+
+
+@dataclass
+class HybridPlugin(Simulator):
+    """
+    A plugin that simulates each shot with Stim's stabilizer tableau until the
+    first non-Clifford gate, and with a statevector from that point onwards.
+
+    Programs that are Clifford throughout never leave the tableau, so they can
+    use far more qubits than a statevector allows. When a shot does switch, the
+    `switched_to_statevector` and `tableau_gate_count` metrics record whether
+    and after how many gates it happened. State dumps use the same format as
+    the QuEST plugin, so they can be inspected with `SeleneQuestState`.
+
+    Attributes:
+        angle_threshold (float, default 1e-4): Angles within this distance of a
+            multiple of pi/2 are treated as Clifford rotations. Must be greater
+            than zero, as floating point errors can cause numerical instability.
+        threads (int): The number of threads used to update the statevector.
+                       Zero (the default) uses one thread per available core.
+    """
+
+    angle_threshold: float = 1e-4
+    threads: int = 0
+
+    def __post_init__(self):
+        assert self.angle_threshold > 0, (
+            "angle_threshold must be greater than zero to avoid numerical instability"
+        )
+        assert self.threads >= 0, "threads must be non-negative"
+
+    def get_init_args(self):
+        return [
+            f"--angle-threshold={self.angle_threshold}",
+            f"--threads={self.threads}",
+        ]
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_hybrid_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_hybrid_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_hybrid_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    @property
+    def library_search_dirs(self) -> list[Path]:
+        if platform.system() == "Windows":
+            return [Path(__file__).parent / "_dist/lib/"]
+        return []
+
+    @staticmethod
+    def extract_states_dict(
+        results: Iterable[TaggedResult],
+        cleanup: bool = True,
+    ) -> dict[str, SeleneQuestState]:
+        """Extract state results from a shot result stream and return them as a
+        dictionary keyed by the state tag. See `QuestPlugin.extract_states_dict`.
+        """
+        return QuestPlugin.extract_states_dict(results, cleanup=cleanup)
+
+    @staticmethod
+    def extract_states(
+        results: Iterable[TaggedResult],
+        cleanup: bool = True,
+    ) -> Iterator[tuple[str, SeleneQuestState]]:
+        """Extract state results from a shot result stream and return them as a
+        pair of (tag, state). See `QuestPlugin.extract_states`.
+        """
+        return QuestPlugin.extract_states(results, cleanup=cleanup)
@@ -0,0 +1,372 @@
+/// A hybrid simulator plugin for Selene, which simulates each shot with a Stim
+/// tableau until the first non-Clifford gate, and with a statevector thereafter.
+//
+// Many programs are Clifford for most of their length, and only use a few
+// arbitrary-angle rotations near the end. While every rotation angle is within
+// `angle_threshold` of a multiple of π/2, gates are applied to a
+// TableauSimulatorMin in the same way as in the Stim plugin. The first rotation
+// that is not converts the tableau into a statevector, and that rotation and
+// everything after it in the shot are applied to the statevector with the same
+// kernels as the statevector plugin. Every shot starts again on the tableau.
+//
+// The statevector is only allocated when a shot first needs it, and is reused
+// by later shots. Stim performs the conversion in single precision, so the
+// converted state is renormalised before use.
+//
+// The tableau bindings are shared with the Stim plugin, and the statevector
+// kernels with the statevector plugin, through the selene-stim-tableau and
+// selene-statevector-state crates.
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use num_complex::Complex64;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use selene_core::export_simulator_plugin;
+use selene_core::simulator::SimulatorInterface;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::utils::MetricValue;
+use selene_statevector_state::{StateVector, check_memory};
+use selene_stim_tableau::TableauSimulatorMin;
+use std::io::Write;
+
+/// The largest register that can be converted into a statevector.
+const MAX_STATEVECTOR_QUBITS: u64 = 32;
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// Angles within this distance of a multiple of π/2 are treated as
+    /// Clifford rotations, and are applied to the tableau.
+    #[arg(long)]
+    angle_threshold: f64,
+    /// The number of threads used to update the statevector. Zero (the
+    /// default) uses one thread per available core.
+    #[arg(long, default_value_t = 0)]
+    threads: usize,
+}
+
+pub struct HybridSimulator {
+    tableau: TableauSimulatorMin,
+    statevector: Option<StateVector>,
+    /// Whether the current shot has switched to the statevector.
+    switched: bool,
+    /// The number of gates applied to the tableau in the current shot.
+    tableau_gate_count: u64,
+    n_qubits: u64,
+    angle_threshold_quad: f64,
+    threads: usize,
+    rng: Pcg64Mcg,
+}
+
+impl HybridSimulator {
+    /// The multiple of π/2 that `theta` is (approximately) equal to, modulo 4.
+    fn get_approximate_quadrant(&self, theta: f64) -> Option<u8> {
+        let quadrant_float = theta / std::f64::consts::FRAC_PI_2;
+        let quadrant_rounded = quadrant_float.round();
+        if (quadrant_float - quadrant_rounded).abs() > self.angle_threshold_quad {
+            return None;
+        }
+        Some((quadrant_rounded as i64).rem_euclid(4) as u8)
+    }
+
+    fn check_qubit(&self, name: &str, q0: u64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "{name}(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        Ok(())
+    }
+
+    /// Apply RZ(quadrant * π/2) to the tableau, up to a global phase.
+    fn tableau_rz(&mut self, q0: u32, quadrant: u8) {
+        match quadrant {
+            1 => self.tableau.sqrt_z(q0),
+            2 => self.tableau.z(q0),
+            3 => self.tableau.sqrt_z_dag(q0),
+            _ => (),
+        }
+    }
+
+    /// Apply RX(quadrant * π/2) to the tableau, up to a global phase.
+    fn tableau_rx(&mut self, q0: u32, quadrant: u8) {
+        match quadrant {
+            1 => self.tableau.sqrt_x(q0),
+            2 => self.tableau.x(q0),
+            3 => self.tableau.sqrt_x_dag(q0),
+            _ => (),
+        }
+    }
+
+    /// Convert the tableau of the current shot into a statevector.
+    fn tableau_amplitudes(&self) -> Result<Vec<Complex64>> {
+        if self.n_qubits > MAX_STATEVECTOR_QUBITS {
+            bail!(
+                "Unable to convert a tableau of {} qubits into a statevector. At most {MAX_STATEVECTOR_QUBITS} qubits are supported.",
+                self.n_qubits
+            );
+        }
+        let amplitudes: Vec<Complex64> = self
+            .tableau
+            .to_state_vector(self.n_qubits as u32)
+            .chunks_exact(2)
+            .map(|v| Complex64::new(v[0] as f64, v[1] as f64))
+            .collect();
+        let norm = amplitudes.iter().map(|a| a.norm_sqr()).sum::<f64>().sqrt();
+        Ok(amplitudes.into_iter().map(|a| a / norm).collect())
+    }
+
+    fn switch_to_statevector(&mut self) -> Result<()> {
+        // The statevector is allocated at the first switch, so check that it
+        // fits before converting the tableau into a second copy.
+        if self.statevector.is_none() && self.n_qubits <= MAX_STATEVECTOR_QUBITS {
+            check_memory(self.n_qubits)?;
+        }
+        let amplitudes = self.tableau_amplitudes()?;
+        let state = match self.statevector.as_mut() {
+            Some(state) => state,
+            None => {
+                let pool = rayon::ThreadPoolBuilder::new()
+                    .num_threads(self.threads)
+                    .build()
+                    .map_err(|e| anyhow!("Failed to create statevector thread pool: {e}"))?;
+                self.statevector
+                    .insert(StateVector::new(self.n_qubits, pool))
+            }
+        };
+        state.replace(amplitudes, 1.0);
+        self.switched = true;
+        Ok(())
+    }
+
+    fn state(&mut self) -> &mut StateVector {
+        self.statevector
+            .as_mut()
+            .expect("The statevector is allocated before the first switch")
+    }
+
+    fn measure_statevector(&mut self, q0: u64) -> bool {
+        let p1 = self.state().probability_of_one(q0);
+        let outcome = self.rng.random::<f64>() < p1;
+        let probability = if outcome { p1 } else { 1.0 - p1 };
+        self.state().project(q0, outcome, 1.0 / probability.sqrt());
+        outcome
+    }
+}
+
+impl SimulatorInterface for HybridSimulator {
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn shot_start(&mut self, _shot_id: u64, seed: u64) -> Result<()> {
+        self.tableau = TableauSimulatorMin::new(self.n_qubits as u32, seed);
+        self.switched = false;
+        self.tableau_gate_count = 0;
+        self.rng = Pcg64Mcg::seed_from_u64(seed);
+        Ok(())
+    }
+
+    fn shot_end(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn rz(&mut self, q0: u64, theta: f64) -> Result<()> {
+        self.check_qubit("RZ", q0)?;
+        if !self.switched {
+            if let Some(quadrant) = self.get_approximate_quadrant(theta) {
+                self.tableau_rz(q0 as u32, quadrant);
+                self.tableau_gate_count += 1;
+                return Ok(());
+            }
+            self.switch_to_statevector()?;
+        }
+        // diag(e^{-iθ/2}, e^{iθ/2})
+        let phase = Complex64::from_polar(1.0, theta / 2.0);
+        self.state().apply_diagonal(q0, phase.conj(), phase);
+        Ok(())
+    }
+
+    fn rxy(&mut self, q0: u64, theta: f64, phi: f64) -> Result<()> {
+        self.check_qubit("RXY", q0)?;
+        if !self.switched {
+            // RXY(θ, φ) = RZ(φ) RX(θ) RZ(-φ), which is Clifford when both θ
+            // and φ are multiples of π/2 (or for any φ when θ is zero).
+            let clifford = match self.get_approximate_quadrant(theta) {
+                Some(0) => Some((0, 0)),
+                Some(theta_quadrant) => self
+                    .get_approximate_quadrant(phi)
+                    .map(|phi_quadrant| (theta_quadrant, phi_quadrant)),
+                None => None,
+            };
+            if let Some((theta_quadrant, phi_quadrant)) = clifford {
+                let q0 = q0 as u32;
+                self.tableau_rz(q0, (4 - phi_quadrant) % 4);
+                self.tableau_rx(q0, theta_quadrant);
+                self.tableau_rz(q0, phi_quadrant);
+                self.tableau_gate_count += 1;
+                return Ok(());
+            }
+            self.switch_to_statevector()?;
+        }
+        // ⎡ cos(θ/2)            -i⋅e^{-iφ}⋅sin(θ/2) ⎤
+        // ⎣ -i⋅e^{iφ}⋅sin(θ/2)   cos(θ/2)           ⎦
+        let cos = Complex64::new((theta / 2.0).cos(), 0.0);
+        let sin = (theta / 2.0).sin();
+        let upper = Complex64::new(0.0, -sin) * Complex64::from_polar(1.0, -phi);
+        let lower = Complex64::new(0.0, -sin) * Complex64::from_polar(1.0, phi);
+        self.state().apply_matrix(q0, [[cos, upper], [lower, cos]]);
+        Ok(())
+    }
+
+    fn rzz(&mut self, q0: u64, q1: u64, theta: f64) -> Result<()> {
+        if q0 >= self.n_qubits || q1 >= self.n_qubits {
+            bail!(
+                "RZZ(q0={q0}, q1={q1}) is out of bounds. q0 and q1 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        if !self.switched {
+            if let Some(quadrant) = self.get_approximate_quadrant(theta) {
+                let (q0, q1) = (q0 as u32, q1 as u32);
+                match quadrant {
+                    1 => self.tableau.sqrt_zz(q0, q1),
+                    2 => {
+                        self.tableau.z(q0);
+                        self.tableau.z(q1);
+                    }
+                    3 => self.tableau.sqrt_zz_dag(q0, q1),
+                    _ => (),
+                }
+                self.tableau_gate_count += 1;
+                return Ok(());
+            }
+            self.switch_to_statevector()?;
+        }
+        // diag(1, e^{iθ}, e^{iθ}, 1), matching the statevector plugin
+        let one = Complex64::new(1.0, 0.0);
+        let phase = Complex64::from_polar(1.0, theta);
+        self.state()
+            .apply_diagonal_2q(q0, q1, [one, phase, phase, one]);
+        Ok(())
+    }
+
+    fn measure(&mut self, q0: u64) -> Result<bool> {
+        self.check_qubit("Measure", q0)?;
+        if self.switched {
+            Ok(self.measure_statevector(q0))
+        } else {
+            Ok(self.tableau.mz(q0 as u32))
+        }
+    }
+
+    fn postselect(&mut self, q0: u64, target_value: bool) -> Result<()> {
+        self.check_qubit("Postselect", q0)?;
+        if !self.switched {
+            return match self.tableau.postselect_z(q0 as u32, target_value) {
+                true => Ok(()),
+                false => Err(anyhow!(
+                    "Postselect(q0={q0}, target_value={target_value}) failed."
+                )),
+            };
+        }
+        let p1 = self.state().probability_of_one(q0);
+        let postselect_probability = if target_value { p1 } else { 1.0 - p1 };
+        if postselect_probability < 1e-10 {
+            bail!(
+                "Postselection of {} on qubit {q0} is too unlikely to postselect. The probability of this outcome is {postselect_probability:.2e}.",
+                target_value as u8
+            );
+        }
+        self.state()
+            .project(q0, target_value, 1.0 / postselect_probability.sqrt());
+        Ok(())
+    }
+
+    fn reset(&mut self, q0: u64) -> Result<()> {
+        self.check_qubit("Reset", q0)?;
+        if !self.switched {
+            if self.tableau.mz(q0 as u32) {
+                self.tableau.x(q0 as u32);
+            }
+        } else if self.measure_statevector(q0) {
+            self.state().flip(q0);
+        }
+        Ok(())
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "switched_to_statevector".to_string(),
+                MetricValue::Bool(self.switched),
+            ))),
+            // When the shot has switched, this is the index of the gate that
+            // caused the switch.
+            1 => Ok(Some((
+                "tableau_gate_count".to_string(),
+                MetricValue::U64(self.tableau_gate_count),
+            ))),
+            _ => Ok(None),
+        }
+    }
+
+    fn dump_state(&mut self, file: &std::path::Path, qubits: &[u64]) -> Result<()> {
+        // The state is always written in the statevector format, converting
+        // the tableau if the shot has not yet switched.
+        let n_qubits = self.n_qubits;
+        let converted;
+        let amplitudes = if self.switched {
+            self.state().amplitudes()
+        } else {
+            converted = self.tableau_amplitudes()?;
+            &converted
+        };
+        let handle = std::fs::File::create(file)?;
+        let mut writer = std::io::BufWriter::new(handle);
+        writer.write_all(b"selene-quest")?;
+        writer.write_all(n_qubits.to_le_bytes().as_slice())?;
+        writer.write_all((qubits.len() as u64).to_le_bytes().as_slice())?;
+        for &q in qubits {
+            writer.write_all(q.to_le_bytes().as_slice())?;
+        }
+        for amplitude in amplitudes {
+            writer.write_all(amplitude.re.to_le_bytes().as_slice())?;
+            writer.write_all(amplitude.im.to_le_bytes().as_slice())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct HybridSimulatorFactory;
+
+impl SimulatorInterfaceFactory for HybridSimulatorFactory {
+    type Interface = HybridSimulator;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        let args: Vec<String> = args.iter().map(|s| s.as_ref().to_string()).collect();
+        let params = Params::try_parse_from(args)
+            .map_err(|e| anyhow!("Error parsing arguments to hybrid plugin: {}", e))?;
+        let n_u32: u32 = n_qubits.try_into()?;
+        Ok(Box::new(HybridSimulator {
+            tableau: TableauSimulatorMin::new(n_u32, 0),
+            statevector: None,
+            switched: false,
+            tableau_gate_count: 0,
+            n_qubits,
+            angle_threshold_quad: params.angle_threshold / std::f64::consts::FRAC_PI_2,
+            threads: params.threads,
+            rng: Pcg64Mcg::seed_from_u64(0),
+        }))
+    }
+}
+
+export_simulator_plugin!(crate::HybridSimulatorFactory);
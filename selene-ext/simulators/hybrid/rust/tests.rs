@@ -0,0 +1,52 @@
+use crate::HybridSimulatorFactory;
+use selene_core::simulator::SimulatorInterface;
+use selene_core::simulator::conformance_testing::run_basic_tests;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::utils::MetricValue;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+#[test]
+fn basic_conformance_test() {
+    let interface = Arc::new(HybridSimulatorFactory);
+    let args = vec!["".to_string(), "--angle-threshold=0.001".to_string()];
+    run_basic_tests(interface, args);
+}
+
+#[test]
+fn switches_at_the_first_non_clifford_gate() {
+    let mut sim = Arc::new(HybridSimulatorFactory)
+        .init(2, &["", "--angle-threshold=0.001", "--threads=1"])
+        .unwrap();
+    let n_shots = 2000;
+    let mut ones = 0;
+    for shot in 0..n_shots {
+        sim.shot_start(shot, shot).unwrap();
+        // |1>|0>, with Clifford phases, prepared on the tableau
+        sim.rxy(0, PI, 0.0).unwrap();
+        sim.rz(0, PI / 2.0).unwrap();
+        sim.rzz(0, 1, PI / 2.0).unwrap();
+        assert!(matches!(
+            sim.get_metric(0).unwrap(),
+            Some((name, MetricValue::Bool(false))) if name == "switched_to_statevector"
+        ));
+        // RX(π/4) is non-Clifford, and leaves P(1) = cos²(π/8) on qubit 0
+        sim.rxy(0, PI / 4.0, 0.0).unwrap();
+        assert!(matches!(
+            sim.get_metric(1).unwrap(),
+            Some((name, MetricValue::U64(3))) if name == "tableau_gate_count"
+        ));
+        if sim.measure(0).unwrap() {
+            ones += 1;
+        }
+        assert!(!sim.measure(1).unwrap());
+        assert!(matches!(
+            sim.get_metric(0).unwrap(),
+            Some((name, MetricValue::Bool(true))) if name == "switched_to_statevector"
+        ));
+        sim.shot_end().unwrap();
+    }
+    let expected = (PI / 8.0).cos().powi(2);
+    let observed = ones as f64 / n_shots as f64;
+    assert!((observed - expected).abs() < 0.05);
+}
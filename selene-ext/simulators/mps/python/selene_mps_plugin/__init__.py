@@ -0,0 +1,3 @@
+from .plugin import MpsPlugin
+
+__all__ = ["MpsPlugin"]
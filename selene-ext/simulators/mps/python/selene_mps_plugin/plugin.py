@@ -0,0 +1,81 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Iterable, Iterator
+
+from hugr.qsystem.result import TaggedResult
+from selene_core import Simulator
+from selene_quest_plugin import QuestPlugin, SeleneQuestState
+
+
+@dataclass
+class MpsPlugin(Simulator):
+    """
+    A plugin for using a matrix product state (MPS) simulator as the backend
+    simulator for selene.
+
+    MPS simulation is suited to circuits on many qubits with limited
+    entanglement. After each two-qubit gate, the bond between the affected
+    qubits is truncated. The accumulated fidelity of these truncations is
+    reported as the `truncation_fidelity` simulator metric, which provides an
+    estimate of the accuracy of each shot.
+
+    State dumps contain the reduced density matrix of the requested qubits
+    and can be inspected with `SeleneQuestState`.
+
+    Attributes:
+        max_bond_dimension (int): The maximum bond dimension between
+                                  neighbouring qubits.
+        truncation_threshold (float): Singular values whose squared magnitude,
+                                      relative to the total weight across the
+                                      bond, falls below this threshold are
+                                      discarded.
+    """
+
+    max_bond_dimension: int = 256
+    truncation_threshold: float = 1e-12
+
+    def __post_init__(self):
+        assert self.max_bond_dimension >= 1, "max_bond_dimension must be at least 1"
+        assert 0 <= self.truncation_threshold < 1, (
+            "truncation_threshold must be in [0, 1)"
+        )
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_mps_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_mps_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_mps_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    def get_init_args(self):
+        return [
+            f"--max-bond-dimension={self.max_bond_dimension}",
+            f"--truncation-threshold={self.truncation_threshold}",
+        ]
+
+    @staticmethod
+    def extract_states_dict(
+        results: Iterable[TaggedResult],
+        cleanup: bool = True,
+    ) -> dict[str, SeleneQuestState]:
+        """Extract state results from a shot result stream and return them as a
+        dictionary keyed by the state tag. See `QuestPlugin.extract_states_dict`.
+        """
+        return QuestPlugin.extract_states_dict(results, cleanup=cleanup)
+
+    @staticmethod
+    def extract_states(
+        results: Iterable[TaggedResult],
+        cleanup: bool = True,
+    ) -> Iterator[tuple[str, SeleneQuestState]]:
+        """Extract state results from a shot result stream and return them as a
+        pair of (tag, state). See `QuestPlugin.extract_states`.
+        """
+        return QuestPlugin.extract_states(results, cleanup=cleanup)
@@ -0,0 +1,297 @@
+/// A matrix product state (MPS) simulator plugin for Selene.
+//
+// The register is stored as a chain of tensors whose bond dimensions grow with
+// the entanglement across each cut, so circuits with limited entanglement can be
+// simulated on far more qubits than a statevector allows. After every two-qubit
+// gate the bond between the affected sites is truncated according to the
+// configured maximum bond dimension and threshold. The product of the weights
+// retained by each truncation is reported as the `truncation_fidelity` metric,
+// which gives an estimate of the accuracy of each shot.
+//
+// Gates between non-neighbouring qubits are applied by swapping the second qubit
+// along the chain until it neighbours the first, applying the gate, and swapping
+// it back. The swaps are subject to the same truncation.
+//
+// Gate definitions and the qubit ordering match the QuEST plugin. dump_state
+// writes the reduced density matrix of the requested qubits, which can be read
+// with the state utilities provided by selene_quest_plugin.
+use anyhow::{Result, bail};
+use clap::Parser;
+use num_complex::Complex64;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use selene_core::export_simulator_plugin;
+use selene_core::simulator::SimulatorInterface;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::utils::MetricValue;
+use std::io::Write;
+
+mod mps;
+use mps::{Matrix4, MatrixProductState, SWAP, Truncation};
+
+#[cfg(test)]
+mod tests;
+
+const ZERO: Complex64 = Complex64::new(0.0, 0.0);
+const ONE: Complex64 = Complex64::new(1.0, 0.0);
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// The maximum bond dimension between neighbouring qubits.
+    #[arg(long, default_value_t = 256)]
+    max_bond_dimension: usize,
+    /// Singular values whose squared magnitude, relative to the total weight
+    /// across the bond, falls below this threshold are discarded.
+    #[arg(long, default_value_t = 1e-12)]
+    truncation_threshold: f64,
+}
+
+pub struct MpsSimulator {
+    state: MatrixProductState,
+    n_qubits: u64,
+    truncation: Truncation,
+    rng: Pcg64Mcg,
+    truncation_fidelity: f64,
+    max_bond_dimension_reached: u64,
+    cumulative_postselect_probability: f64,
+}
+
+impl MpsSimulator {
+    fn apply_adjacent(&mut self, site: usize, gate: &Matrix4) {
+        self.truncation_fidelity *= self.state.apply_2q_adjacent(site, gate, self.truncation);
+        self.max_bond_dimension_reached = self
+            .max_bond_dimension_reached
+            .max(self.state.max_bond_dimension() as u64);
+    }
+
+    /// Apply `gate` to (q0, q1), where the gate is indexed by 2 * s0 + s1 with
+    /// s0 the state of q0 and s1 the state of q1.
+    fn apply_two_qubit(&mut self, q0: u64, q1: u64, gate: &Matrix4) {
+        let (low, high, gate) = if q0 < q1 {
+            (q0 as usize, q1 as usize, *gate)
+        } else {
+            // exchange the roles of s0 and s1
+            let transposed = |i: usize| (i % 2) * 2 + i / 2;
+            let mut swapped = [[ZERO; 4]; 4];
+            for (i, row) in swapped.iter_mut().enumerate() {
+                for (j, element) in row.iter_mut().enumerate() {
+                    *element = gate[transposed(i)][transposed(j)];
+                }
+            }
+            (q1 as usize, q0 as usize, swapped)
+        };
+        for site in (low + 1..high).rev() {
+            self.apply_adjacent(site, &SWAP);
+        }
+        self.apply_adjacent(low, &gate);
+        for site in low + 1..high {
+            self.apply_adjacent(site, &SWAP);
+        }
+    }
+
+    fn measure_unchecked(&mut self, q0: u64) -> bool {
+        let p1 = self.state.probability_of_one(q0);
+        let outcome = self.rng.random::<f64>() < p1;
+        let probability = if outcome { p1 } else { 1.0 - p1 };
+        self.state.project(q0, outcome, 1.0 / probability.sqrt());
+        outcome
+    }
+}
+
+impl SimulatorInterface for MpsSimulator {
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn shot_start(&mut self, _shot_id: u64, seed: u64) -> Result<()> {
+        self.state.initialise();
+        self.rng = Pcg64Mcg::seed_from_u64(seed);
+        self.truncation_fidelity = 1.0;
+        self.max_bond_dimension_reached = 1;
+        self.cumulative_postselect_probability = 1.0;
+        Ok(())
+    }
+
+    fn shot_end(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn rz(&mut self, q0: u64, theta: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "RZ(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        // diag(e^{-iθ/2}, e^{iθ/2}), matching QuEST's rotateZ
+        let phase = Complex64::from_polar(1.0, theta / 2.0);
+        self.state
+            .apply_1q(q0, [[phase.conj(), ZERO], [ZERO, phase]]);
+        Ok(())
+    }
+
+    fn rxy(&mut self, q0: u64, theta: f64, phi: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "RXY(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        // ⎡ cos(θ/2)            -i⋅e^{-iφ}⋅sin(θ/2) ⎤
+        // ⎣ -i⋅e^{iφ}⋅sin(θ/2)   cos(θ/2)           ⎦
+        let cos = Complex64::new((theta / 2.0).cos(), 0.0);
+        let sin = (theta / 2.0).sin();
+        let upper = Complex64::new(0.0, -sin) * Complex64::from_polar(1.0, -phi);
+        let lower = Complex64::new(0.0, -sin) * Complex64::from_polar(1.0, phi);
+        self.state.apply_1q(q0, [[cos, upper], [lower, cos]]);
+        Ok(())
+    }
+
+    fn rzz(&mut self, q0: u64, q1: u64, theta: f64) -> Result<()> {
+        if q0 >= self.n_qubits || q1 >= self.n_qubits {
+            bail!(
+                "RZZ(q0={q0}, q1={q1}) is out of bounds. q0 and q1 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        if q0 == q1 {
+            bail!("RZZ(q0={q0}, q1={q1}) requires two distinct qubits.");
+        }
+        // diag(1, e^{iθ}, e^{iθ}, 1), matching the QuEST plugin
+        let phase = Complex64::from_polar(1.0, theta);
+        let gate = [
+            [ONE, ZERO, ZERO, ZERO],
+            [ZERO, phase, ZERO, ZERO],
+            [ZERO, ZERO, phase, ZERO],
+            [ZERO, ZERO, ZERO, ONE],
+        ];
+        self.apply_two_qubit(q0, q1, &gate);
+        Ok(())
+    }
+
+    fn measure(&mut self, q0: u64) -> Result<bool> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Measure(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        Ok(self.measure_unchecked(q0))
+    }
+
+    fn postselect(&mut self, q0: u64, target_value: bool) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Postselect(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        let p1 = self.state.probability_of_one(q0);
+        let postselect_probability = if target_value { p1 } else { 1.0 - p1 };
+        self.cumulative_postselect_probability *= postselect_probability;
+        if postselect_probability < 1e-10 {
+            bail!(
+                "Postselection of {} on qubit {q0} is too unlikely to postselect. The probability of this outcome is {postselect_probability:.2e}.",
+                target_value as u8
+            );
+        }
+        self.state
+            .project(q0, target_value, 1.0 / postselect_probability.sqrt());
+        Ok(())
+    }
+
+    fn reset(&mut self, q0: u64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Reset(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        if self.measure_unchecked(q0) {
+            self.state.apply_1q(q0, [[ZERO, ONE], [ONE, ZERO]]);
+        }
+        Ok(())
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "truncation_fidelity".to_string(),
+                MetricValue::F64(self.truncation_fidelity),
+            ))),
+            1 => Ok(Some((
+                "max_bond_dimension".to_string(),
+                MetricValue::U64(self.max_bond_dimension_reached),
+            ))),
+            2 => Ok(Some((
+                "cumulative_postselect_probability".to_string(),
+                MetricValue::F64(self.cumulative_postselect_probability),
+            ))),
+            _ => Ok(None),
+        }
+    }
+
+    fn dump_state(&mut self, file: &std::path::Path, qubits: &[u64]) -> Result<()> {
+        if let Some(q) = qubits.iter().find(|&&q| q >= self.n_qubits) {
+            bail!(
+                "DumpState(q={q}) is out of bounds. All qubits must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        let handle = std::fs::File::create(file)?;
+        let mut writer = std::io::BufWriter::new(handle);
+        writer.write_all(b"selene-rdmat")?;
+        writer.write_all(self.n_qubits.to_le_bytes().as_slice())?;
+        writer.write_all((qubits.len() as u64).to_le_bytes().as_slice())?;
+        for &q in qubits {
+            writer.write_all(q.to_le_bytes().as_slice())?;
+        }
+        for element in self.state.reduced(qubits) {
+            writer.write_all(element.re.to_le_bytes().as_slice())?;
+            writer.write_all(element.im.to_le_bytes().as_slice())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct MpsSimulatorFactory;
+
+impl SimulatorInterfaceFactory for MpsSimulatorFactory {
+    type Interface = MpsSimulator;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        let args: Vec<String> = args.iter().map(|s| s.as_ref().to_string()).collect();
+        let params = Params::try_parse_from(&args)?;
+        if n_qubits == 0 {
+            bail!("Number of qubits must be greater than 0");
+        }
+        if params.max_bond_dimension == 0 {
+            bail!("max_bond_dimension must be at least 1");
+        }
+        if !(0.0..1.0).contains(&params.truncation_threshold) {
+            bail!(
+                "truncation_threshold must be in [0, 1), got {}",
+                params.truncation_threshold
+            );
+        }
+        Ok(Box::new(MpsSimulator {
+            state: MatrixProductState::new(n_qubits),
+            n_qubits,
+            truncation: Truncation {
+                max_bond_dimension: params.max_bond_dimension,
+                threshold: params.truncation_threshold,
+            },
+            rng: Pcg64Mcg::seed_from_u64(0),
+            truncation_fidelity: 1.0,
+            max_bond_dimension_reached: 1,
+            cumulative_postselect_probability: 1.0,
+        }))
+    }
+}
+
+export_simulator_plugin!(crate::MpsSimulatorFactory);
@@ -0,0 +1,317 @@
+//! A matrix product state in mixed canonical form.
+//!
+//! Each site holds a rank-3 tensor A[l][s][r] with a physical index s and left
+//! and right bond indices l and r. Sites to the left of the orthogonality centre
+//! are left-orthonormal and sites to the right are right-orthonormal, so local
+//! quantities such as measurement probabilities and the weight discarded by a
+//! truncation can be computed from the centre site alone.
+use nalgebra::DMatrix;
+use num_complex::Complex64;
+
+/// A 2x2 complex matrix in row-major order.
+pub type Matrix2 = [[Complex64; 2]; 2];
+
+/// A 4x4 complex matrix in row-major order, acting on the basis |s0 s1> with
+/// index 2 * s0 + s1.
+pub type Matrix4 = [[Complex64; 4]; 4];
+
+const ZERO: Complex64 = Complex64::new(0.0, 0.0);
+const ONE: Complex64 = Complex64::new(1.0, 0.0);
+
+pub const SWAP: Matrix4 = [
+    [ONE, ZERO, ZERO, ZERO],
+    [ZERO, ZERO, ONE, ZERO],
+    [ZERO, ONE, ZERO, ZERO],
+    [ZERO, ZERO, ZERO, ONE],
+];
+
+/// Limits applied when splitting a two-site tensor back into two sites.
+#[derive(Clone, Copy, Debug)]
+pub struct Truncation {
+    /// The maximum number of singular values kept.
+    pub max_bond_dimension: usize,
+    /// Singular values whose squared magnitude, relative to the total weight,
+    /// falls below this threshold are discarded.
+    pub threshold: f64,
+}
+
+struct Site {
+    left: usize,
+    right: usize,
+    data: Vec<Complex64>,
+}
+
+impl Site {
+    fn zero_state() -> Self {
+        Self {
+            left: 1,
+            right: 1,
+            data: vec![ONE, ZERO],
+        }
+    }
+
+    fn index(&self, l: usize, s: usize, r: usize) -> usize {
+        (l * 2 + s) * self.right + r
+    }
+
+    /// The tensor reshaped to a (2 * left) x right matrix.
+    fn left_matrix(&self) -> DMatrix<Complex64> {
+        DMatrix::from_row_slice(2 * self.left, self.right, &self.data)
+    }
+
+    /// The tensor reshaped to a left x (2 * right) matrix.
+    fn right_matrix(&self) -> DMatrix<Complex64> {
+        DMatrix::from_row_slice(self.left, 2 * self.right, &self.data)
+    }
+
+    /// The left x right matrix for a fixed physical index.
+    fn physical_slice(&self, s: usize) -> DMatrix<Complex64> {
+        DMatrix::from_fn(self.left, self.right, |l, r| self.data[self.index(l, s, r)])
+    }
+
+    fn from_matrix(left: usize, right: usize, matrix: &DMatrix<Complex64>) -> Self {
+        debug_assert_eq!(matrix.len(), left * 2 * right);
+        let mut data = Vec::with_capacity(matrix.len());
+        for row in 0..matrix.nrows() {
+            for col in 0..matrix.ncols() {
+                data.push(matrix[(row, col)]);
+            }
+        }
+        Self { left, right, data }
+    }
+}
+
+pub struct MatrixProductState {
+    sites: Vec<Site>,
+    centre: usize,
+}
+
+impl MatrixProductState {
+    pub fn new(n_qubits: u64) -> Self {
+        Self {
+            sites: (0..n_qubits).map(|_| Site::zero_state()).collect(),
+            centre: 0,
+        }
+    }
+
+    /// Return the register to the product state |0...0>.
+    pub fn initialise(&mut self) {
+        for site in self.sites.iter_mut() {
+            *site = Site::zero_state();
+        }
+        self.centre = 0;
+    }
+
+    /// The largest bond dimension currently present in the state.
+    pub fn max_bond_dimension(&self) -> usize {
+        self.sites.iter().map(|s| s.right).max().unwrap_or(1)
+    }
+
+    fn move_centre_right(&mut self) {
+        let i = self.centre;
+        let qr = self.sites[i].left_matrix().qr();
+        let q = qr.q();
+        let r = qr.r();
+        let bond = q.ncols();
+        self.sites[i] = Site::from_matrix(self.sites[i].left, bond, &q);
+        let next = &self.sites[i + 1];
+        let merged = r * next.right_matrix();
+        self.sites[i + 1] = Site::from_matrix(bond, next.right, &merged);
+        self.centre += 1;
+    }
+
+    fn move_centre_left(&mut self) {
+        let i = self.centre;
+        // M = R†Q† where M† = QR, leaving Q† with orthonormal rows.
+        let qr = self.sites[i].right_matrix().adjoint().qr();
+        let q = qr.q();
+        let r = qr.r();
+        let bond = q.ncols();
+        self.sites[i] = Site::from_matrix(bond, self.sites[i].right, &q.adjoint());
+        let previous = &self.sites[i - 1];
+        let merged = previous.left_matrix() * r.adjoint();
+        self.sites[i - 1] = Site::from_matrix(previous.left, bond, &merged);
+        self.centre -= 1;
+    }
+
+    fn move_centre(&mut self, target: usize) {
+        while self.centre < target {
+            self.move_centre_right();
+        }
+        while self.centre > target {
+            self.move_centre_left();
+        }
+    }
+
+    /// Apply a single-qubit gate to `qubit`. This preserves the canonical form.
+    pub fn apply_1q(&mut self, qubit: u64, gate: Matrix2) {
+        let site = &mut self.sites[qubit as usize];
+        for l in 0..site.left {
+            for r in 0..site.right {
+                let i0 = site.index(l, 0, r);
+                let i1 = site.index(l, 1, r);
+                let (x0, x1) = (site.data[i0], site.data[i1]);
+                site.data[i0] = gate[0][0] * x0 + gate[0][1] * x1;
+                site.data[i1] = gate[1][0] * x0 + gate[1][1] * x1;
+            }
+        }
+    }
+
+    /// Apply a two-qubit gate to the neighbouring sites `site` and `site + 1`,
+    /// returning the fidelity retained by the truncation that follows.
+    pub fn apply_2q_adjacent(
+        &mut self,
+        site: usize,
+        gate: &Matrix4,
+        truncation: Truncation,
+    ) -> f64 {
+        self.move_centre(site);
+        let (a, b) = (&self.sites[site], &self.sites[site + 1]);
+        let (left, right) = (a.left, b.right);
+        // θ[(l, s0), (s1, r)] = Σ_m A[l][s0][m] B[m][s1][r]
+        let theta = a.left_matrix() * b.right_matrix();
+        let mut updated = DMatrix::from_element(2 * left, 2 * right, ZERO);
+        for l in 0..left {
+            for r in 0..right {
+                let inputs = [
+                    theta[(2 * l, r)],
+                    theta[(2 * l, right + r)],
+                    theta[(2 * l + 1, r)],
+                    theta[(2 * l + 1, right + r)],
+                ];
+                for (out, row) in gate.iter().enumerate() {
+                    let (s0, s1) = (out / 2, out % 2);
+                    updated[(2 * l + s0, s1 * right + r)] =
+                        row.iter().zip(inputs.iter()).map(|(g, x)| g * x).sum();
+                }
+            }
+        }
+
+        let svd = updated.svd(true, true);
+        let u = svd.u.expect("SVD was requested with U");
+        let v_t = svd.v_t.expect("SVD was requested with V^T");
+        let mut order: Vec<usize> = (0..svd.singular_values.len()).collect();
+        order.sort_by(|&i, &j| svd.singular_values[j].total_cmp(&svd.singular_values[i]));
+
+        let weights: Vec<f64> = order
+            .iter()
+            .map(|&i| svd.singular_values[i].powi(2))
+            .collect();
+        let total: f64 = weights.iter().sum();
+        let keep = weights
+            .iter()
+            .take(truncation.max_bond_dimension)
+            .take_while(|&&w| w >= truncation.threshold * total)
+            .count()
+            .max(1);
+        let kept: f64 = weights[..keep].iter().sum();
+        let renormalisation = (total / kept).sqrt();
+
+        let new_a = DMatrix::from_fn(2 * left, keep, |row, k| u[(row, order[k])]);
+        let new_b = DMatrix::from_fn(keep, 2 * right, |k, col| {
+            v_t[(order[k], col)] * svd.singular_values[order[k]] * renormalisation
+        });
+        self.sites[site] = Site::from_matrix(left, keep, &new_a);
+        self.sites[site + 1] = Site::from_matrix(keep, right, &new_b);
+        self.centre = site + 1;
+        kept / total
+    }
+
+    /// The probability of measuring `qubit` as 1.
+    pub fn probability_of_one(&mut self, qubit: u64) -> f64 {
+        self.move_centre(qubit as usize);
+        let site = &self.sites[qubit as usize];
+        let mut p1 = 0.0;
+        let mut total = 0.0;
+        for l in 0..site.left {
+            for s in 0..2 {
+                for r in 0..site.right {
+                    let weight = site.data[site.index(l, s, r)].norm_sqr();
+                    total += weight;
+                    if s == 1 {
+                        p1 += weight;
+                    }
+                }
+            }
+        }
+        p1 / total
+    }
+
+    /// Project `qubit` onto `value`, scaling the remainder by `scale`.
+    pub fn project(&mut self, qubit: u64, value: bool, scale: f64) {
+        self.move_centre(qubit as usize);
+        let site = &mut self.sites[qubit as usize];
+        for l in 0..site.left {
+            for s in 0..2 {
+                for r in 0..site.right {
+                    let i = site.index(l, s, r);
+                    if s == value as usize {
+                        site.data[i] *= scale;
+                    } else {
+                        site.data[i] = ZERO;
+                    }
+                }
+            }
+        }
+    }
+
+    /// The reduced density matrix of `qubits` in row-major order, tracing out all
+    /// other qubits. The first of `qubits` is the most significant bit of the row
+    /// and column indices of the result.
+    pub fn reduced(&self, qubits: &[u64]) -> Vec<Complex64> {
+        // The environment holds one bond matrix E[l][l'] per pair of (ket, bra)
+        // configurations of the requested sites seen so far, in site order.
+        let mut open = 0usize;
+        let mut environment = vec![DMatrix::from_element(1, 1, ONE)];
+        for (i, site) in self.sites.iter().enumerate() {
+            let slices = [site.physical_slice(0), site.physical_slice(1)];
+            let conjugates = [slices[0].conjugate(), slices[1].conjugate()];
+            if qubits.contains(&(i as u64)) {
+                let dimension = 1usize << open;
+                let new_dimension = dimension * 2;
+                let mut next =
+                    vec![DMatrix::zeros(site.right, site.right); new_dimension * new_dimension];
+                for ket in 0..dimension {
+                    for bra in 0..dimension {
+                        let e = &environment[ket * dimension + bra];
+                        for s in 0..2 {
+                            let partial = slices[s].transpose() * e;
+                            for s_prime in 0..2 {
+                                next[(2 * ket + s) * new_dimension + 2 * bra + s_prime] =
+                                    &partial * &conjugates[s_prime];
+                            }
+                        }
+                    }
+                }
+                environment = next;
+                open += 1;
+            } else {
+                for e in environment.iter_mut() {
+                    *e = slices[0].transpose() * &*e * &conjugates[0]
+                        + slices[1].transpose() * &*e * &conjugates[1];
+                }
+            }
+        }
+
+        // Reorder from site order to the requested order.
+        let k = qubits.len();
+        let mut sorted: Vec<u64> = qubits.to_vec();
+        sorted.sort_unstable();
+        let to_site_order = |x: usize| {
+            qubits.iter().enumerate().fold(0usize, |acc, (j, q)| {
+                let bit = (x >> (k - 1 - j)) & 1;
+                let position = sorted.iter().position(|s| s == q).unwrap();
+                acc | (bit << (k - 1 - position))
+            })
+        };
+        let dimension = 1usize << k;
+        let mut result = vec![ZERO; dimension * dimension];
+        for row in 0..dimension {
+            for col in 0..dimension {
+                result[row * dimension + col] =
+                    environment[to_site_order(row) * dimension + to_site_order(col)][(0, 0)];
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,79 @@
+use crate::MpsSimulatorFactory;
+use selene_core::simulator::SimulatorInterface;
+use selene_core::simulator::conformance_testing::run_basic_tests;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// A CNOT up to a Z on the control, which is sufficient for preparing
+/// computational basis correlations.
+fn cx(sim: &mut impl SimulatorInterface, control: u64, target: u64) {
+    sim.rxy(target, PI / 2.0, PI / 2.0).unwrap();
+    sim.rz(control, PI / 2.0).unwrap();
+    sim.rz(target, PI / 2.0).unwrap();
+    sim.rzz(control, target, -PI / 2.0).unwrap();
+    sim.rxy(target, -PI / 2.0, PI / 2.0).unwrap();
+}
+
+#[test]
+fn basic_conformance_test() {
+    let interface = Arc::new(MpsSimulatorFactory);
+    let args = vec![];
+    run_basic_tests(interface, args);
+}
+
+#[test]
+fn large_ghz_state() {
+    let n_qubits = 80;
+    let mut sim = Arc::new(MpsSimulatorFactory).init(n_qubits, &[""]).unwrap();
+    for shot in 0..10 {
+        sim.shot_start(shot, shot).unwrap();
+        sim.rxy(0, PI / 2.0, PI / 2.0).unwrap();
+        for q in 0..n_qubits - 1 {
+            cx(&mut *sim, q, q + 1);
+        }
+        let first = sim.measure(0).unwrap();
+        for q in 1..n_qubits {
+            assert_eq!(sim.measure(q).unwrap(), first);
+        }
+        assert_eq!(sim.max_bond_dimension_reached, 2);
+        approx::assert_relative_eq!(sim.truncation_fidelity, 1.0, epsilon = 1e-9);
+    }
+}
+
+#[test]
+fn truncation_is_reported() {
+    let mut sim = Arc::new(MpsSimulatorFactory)
+        .init(2, &["", "--max-bond-dimension=1"])
+        .unwrap();
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(0, PI / 2.0, PI / 2.0).unwrap();
+    cx(&mut *sim, 0, 1);
+    // Truncating a maximally entangled pair to a product state keeps half
+    // of the weight.
+    approx::assert_relative_eq!(sim.truncation_fidelity, 0.5, epsilon = 1e-9);
+    assert_eq!(sim.max_bond_dimension_reached, 1);
+}
+
+#[test]
+fn reduced_density_matrix_of_distant_pair() {
+    let mut sim = Arc::new(MpsSimulatorFactory).init(6, &[""]).unwrap();
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(0, PI / 2.0, PI / 2.0).unwrap();
+    cx(&mut *sim, 0, 5);
+    // |1> on qubit 2, which should not be disturbed by the swaps
+    sim.rxy(2, PI, 0.0).unwrap();
+
+    let pair = sim.state.reduced(&[5, 0]);
+    for (i, element) in pair.iter().enumerate() {
+        let expected = if [0, 3, 12, 15].contains(&i) {
+            0.5
+        } else {
+            0.0
+        };
+        approx::assert_relative_eq!(element.norm(), expected, epsilon = 1e-9);
+    }
+    let single = sim.state.reduced(&[2]);
+    approx::assert_relative_eq!(single[3].re, 1.0, epsilon = 1e-9);
+    approx::assert_relative_eq!(single[0].norm(), 0.0, epsilon = 1e-9);
+}
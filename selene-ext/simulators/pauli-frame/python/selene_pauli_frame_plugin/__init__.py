@@ -0,0 +1,3 @@
+from .plugin import PauliFramePlugin
+
+__all__ = ["PauliFramePlugin"]
@@ -0,0 +1,61 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+
+from selene_core import Simulator
+
+
+@dataclass
+class PauliFramePlugin(Simulator):
+    """
+    A plugin for sampling many shots of a Clifford circuit using Pauli frames.
+
+    Consecutive shots are grouped into batches. The first shot of each batch
+    is simulated with a stabilizer tableau while a Pauli frame is propagated
+    for every shot in the batch, 64 shots per machine word. Later shots in the
+    batch that issue the same operations, as is the case for programs without
+    measurement-dependent control flow, are then served from their own frame
+    without further simulation. Shots that diverge, or that use
+    postselection, fall back to exact simulation, so results are correct for
+    any Clifford program. The frame of each shot is sampled using that shot's
+    own seed, so the results of a shot do not depend on the batch size.
+
+    Whether each shot was served from a batch is reported as the
+    `served_from_batch` simulator metric.
+
+    As with Stim, rotation angles must be (approximate) Clifford angles.
+
+    Attributes:
+        angle_threshold (float): Angles within this distance of a multiple of
+                                 π/2 are treated as Clifford rotations.
+        batch_size (int): The largest number of shots simulated together.
+                          Must be a multiple of 64.
+    """
+
+    angle_threshold: float = 1e-9
+    batch_size: int = 256
+
+    def __post_init__(self):
+        assert self.angle_threshold >= 0, "angle_threshold must be non-negative"
+        assert self.batch_size > 0 and self.batch_size % 64 == 0, (
+            "batch_size must be a positive multiple of 64"
+        )
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_pauli_frame_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_pauli_frame_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_pauli_frame_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    def get_init_args(self):
+        return [
+            f"--angle-threshold={self.angle_threshold}",
+            f"--batch-size={self.batch_size}",
+        ]
@@ -0,0 +1,83 @@
+//! A batch of Pauli frames, propagated bit-parallel through Clifford gates.
+//!
+//! Each lane of the batch represents one shot. The state of a lane is the
+//! reference state with its frame, a Pauli operator, applied on top. The X and
+//! Z components of the frames are stored per qubit as bit vectors over the
+//! lanes, so each gate updates 64 lanes per word operation.
+//!
+//! Every random bit of a lane is drawn from that lane's own random number
+//! generator, in an order that depends only on the operations applied. The
+//! outcomes of a lane are therefore determined by its generator alone, however
+//! many other lanes share the batch.
+use crate::Gate;
+use rand::Rng;
+
+pub struct FrameBatch {
+    x: Vec<Vec<u64>>,
+    z: Vec<Vec<u64>>,
+}
+
+/// Draw one random bit from each lane's generator, packed into words. Bits
+/// past the last lane are zero.
+fn random_words(rngs: &mut [impl Rng]) -> Vec<u64> {
+    rngs.chunks_mut(64)
+        .map(|chunk| {
+            chunk.iter_mut().enumerate().fold(0, |word, (bit, rng)| {
+                word | ((rng.random::<bool>() as u64) << bit)
+            })
+        })
+        .collect()
+}
+
+impl FrameBatch {
+    /// Create frames for one lane per generator on a register initialised to
+    /// |0...0>.
+    pub fn new(n_qubits: u64, rngs: &mut [impl Rng]) -> Self {
+        let words = rngs.len().div_ceil(64);
+        Self {
+            x: vec![vec![0; words]; n_qubits as usize],
+            // Z errors on |0> have no effect, but randomising them ensures that
+            // the lanes sample independently once the qubits leave the Z basis.
+            z: (0..n_qubits).map(|_| random_words(rngs)).collect(),
+        }
+    }
+
+    pub fn apply(&mut self, gate: Gate) {
+        match gate {
+            Gate::H(q) => std::mem::swap(&mut self.x[q], &mut self.z[q]),
+            Gate::S(q) | Gate::SDag(q) => {
+                for (z, x) in self.z[q].iter_mut().zip(self.x[q].iter()) {
+                    *z ^= x;
+                }
+            }
+            // Paulis commute with the frames up to a phase.
+            Gate::X(_) => (),
+            Gate::Cx(control, target) => {
+                for w in 0..self.x[control].len() {
+                    let x_control = self.x[control][w];
+                    self.x[target][w] ^= x_control;
+                    let z_target = self.z[target][w];
+                    self.z[control][w] ^= z_target;
+                }
+            }
+        }
+    }
+
+    /// Measure Z on `q` in every lane, given the outcome of the reference
+    /// state. Returns the outcomes of the lanes as a bit vector, and
+    /// randomises the Z component of the frames, which is equivalent to
+    /// dephasing the collapsed qubit and so leaves each lane unchanged.
+    pub fn measure(&mut self, q: usize, reference: bool, rngs: &mut [impl Rng]) -> Vec<u64> {
+        let flip = if reference { u64::MAX } else { 0 };
+        let outcomes = self.x[q].iter().map(|x| x ^ flip).collect();
+        self.z[q] = random_words(rngs);
+        outcomes
+    }
+
+    /// Reset `q` to |0> in every lane. The reference state must also have been
+    /// reset.
+    pub fn reset(&mut self, q: usize, rngs: &mut [impl Rng]) {
+        self.x[q].iter_mut().for_each(|x| *x = 0);
+        self.z[q] = random_words(rngs);
+    }
+}
@@ -0,0 +1,553 @@
+/// A Pauli-frame simulator plugin for Selene, for fast sampling of many shots
+/// of the same Clifford circuit.
+//
+// Selene announces the shots that will follow, with their seeds, through
+// plan_shots. A shot starts a batch of up to `batch_size` lanes, one for it and
+// one for each of the planned shots that follow it. The first shot of a batch
+// is simulated with a stabilizer tableau, which produces a single reference
+// sample, alongside a batch of Pauli frames with one lane per shot in the
+// batch. The frames are propagated through each gate bit-parallel, so the cost
+// of simulating the whole batch is close to that of simulating the first shot
+// alone. The operations issued during the first shot, and the measurement
+// outcomes of every lane, are recorded. A shot that was not planned is
+// simulated as a batch of its own.
+//
+// The random bits of each lane are drawn from a generator seeded with the seed
+// of that lane's shot, so the results of a shot depend on its own seed alone,
+// and not on the batch size or on its position in the batch.
+//
+// Each subsequent shot in the batch is then served from its own lane: as long
+// as it issues the same operations as the recorded shot, which is the case for
+// programs without measurement-dependent control flow, each measurement
+// returns the recorded outcome for that lane without any further simulation.
+// If a shot diverges from the recording, it falls back to exact simulation by
+// replaying the recorded prefix on a fresh tableau, forced to the outcomes of
+// its lane, and continues exactly from there. The results are therefore
+// correct for any program, with the speedup applying to the shots that follow
+// the recording. Postselection is always handled by this fallback.
+//
+// Shot boundaries are unchanged: each shot is still started and ended through
+// the usual interface, so shot counts, offsets and increments and the per-shot
+// output behave exactly as with any other simulator. Operations injected by an
+// error model are part of the operation stream, so a stochastic error model
+// will generally cause shots to diverge and fall back.
+//
+// As with the stim plugin, rotation angles must be (approximate) Clifford
+// angles.
+use anyhow::{Result, bail};
+use clap::Parser;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use selene_core::export_simulator_plugin;
+use selene_core::simulator::SimulatorInterface;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::utils::MetricValue;
+
+mod frames;
+mod tableau;
+use frames::FrameBatch;
+use tableau::Tableau;
+
+#[cfg(test)]
+mod tests;
+
+/// The Clifford gates that the supported operations are decomposed into.
+#[derive(Clone, Copy, Debug)]
+pub enum Gate {
+    H(usize),
+    S(usize),
+    SDag(usize),
+    X(usize),
+    Cx(usize, usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operation {
+    Rz(u64, f64),
+    Rxy(u64, f64, f64),
+    Rzz(u64, u64, f64),
+    Measure(u64),
+    Reset(u64),
+}
+
+/// The record of the first shot of a batch.
+struct Batch {
+    /// The shot id and seed of the shot served by each lane.
+    lanes: Vec<(u64, u64)>,
+    next_lane: usize,
+    operations: Vec<Operation>,
+    /// The collapse outcomes of each lane, as a bit vector over the lanes, for
+    /// each measurement and reset in `operations`.
+    outcomes: Vec<Vec<u64>>,
+}
+
+impl Batch {
+    fn outcome(&self, index: usize, lane: usize) -> bool {
+        (self.outcomes[index][lane / 64] >> (lane % 64)) & 1 == 1
+    }
+}
+
+enum Mode {
+    /// The first shot of a batch, served from lane 0 while the batch is
+    /// recorded.
+    Recording {
+        reference: Tableau,
+        frames: FrameBatch,
+        /// The random number generator of each lane.
+        rngs: Vec<Pcg64Mcg>,
+    },
+    /// A subsequent shot, served from `lane` of the recording. `cursor` is the
+    /// number of recorded operations replayed so far, and `outcome_cursor` the
+    /// number of recorded outcomes.
+    Replaying {
+        lane: usize,
+        cursor: usize,
+        outcome_cursor: usize,
+    },
+    /// Exact simulation of a single shot.
+    Exact(Tableau),
+}
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// Angles within this distance of a multiple of π/2 are treated as
+    /// Clifford rotations.
+    #[arg(long, default_value_t = 1e-9)]
+    angle_threshold: f64,
+    /// The largest number of planned shots simulated together. Must be a
+    /// multiple of 64.
+    #[arg(long, default_value_t = 256)]
+    batch_size: usize,
+}
+
+pub struct PauliFrameSimulator {
+    n_qubits: u64,
+    angle_threshold_quad: f64,
+    batch_size: usize,
+    rng: Pcg64Mcg,
+    /// The shot id and seed of each shot announced by plan_shots.
+    plan: Vec<(u64, u64)>,
+    batch: Option<Batch>,
+    mode: Mode,
+    lane: u64,
+    cumulative_postselect_probability: f64,
+}
+
+/// The random number generators of the shot with the given seed: one for its
+/// lane of a batch, and one for exact simulation should it fall back.
+fn shot_rngs(seed: u64) -> (Pcg64Mcg, Pcg64Mcg) {
+    let mut rng = Pcg64Mcg::seed_from_u64(seed);
+    let lane_rng = Pcg64Mcg::from_rng(&mut rng);
+    (lane_rng, rng)
+}
+
+impl PauliFrameSimulator {
+    fn get_approximate_quadrant(&self, theta: f64) -> Option<usize> {
+        let quadrant_float = theta / std::f64::consts::FRAC_PI_2;
+        let quadrant_rounded = quadrant_float.round();
+        if (quadrant_float - quadrant_rounded).abs() > self.angle_threshold_quad {
+            return None;
+        }
+        Some((quadrant_rounded as i64).rem_euclid(4) as usize)
+    }
+
+    /// Decompose a unitary operation into Clifford gates.
+    fn lower(&self, operation: Operation) -> Result<Vec<Gate>> {
+        let s_power = |q: usize, quadrant: usize| match quadrant {
+            0 => vec![],
+            1 => vec![Gate::S(q)],
+            2 => vec![Gate::S(q), Gate::S(q)],
+            _ => vec![Gate::SDag(q)],
+        };
+        let rx = |q: usize, quadrant: usize| {
+            [vec![Gate::H(q)], s_power(q, quadrant), vec![Gate::H(q)]].concat()
+        };
+        // RY = S RX S†
+        let ry = |q: usize, quadrant: usize| {
+            [vec![Gate::SDag(q)], rx(q, quadrant), vec![Gate::S(q)]].concat()
+        };
+        match operation {
+            Operation::Rz(q0, theta) => {
+                let Some(quadrant) = self.get_approximate_quadrant(theta) else {
+                    bail!(
+                        "RZ(q0={q0}, theta={theta}) is not representable in stabiliser form. Theta must be an (approximate) multiple of pi/2 for Clifford operations."
+                    );
+                };
+                Ok(s_power(q0 as usize, quadrant))
+            }
+            Operation::Rxy(q0, theta, phi) => {
+                let q = q0 as usize;
+                let Some(theta_quadrant) = self.get_approximate_quadrant(theta) else {
+                    bail!(
+                        "RXY(q0={q0}, theta={theta}, phi={phi}) is not representable in stabiliser form. Theta must be an (approximate) multiple of pi/2 for Clifford operations."
+                    );
+                };
+                if theta_quadrant == 0 {
+                    return Ok(vec![]);
+                }
+                // The rotation axis is cos(φ)X + sin(φ)Y.
+                match self.get_approximate_quadrant(phi) {
+                    Some(0) => return Ok(rx(q, theta_quadrant)),
+                    Some(1) => return Ok(ry(q, theta_quadrant)),
+                    Some(2) => return Ok(rx(q, 4 - theta_quadrant)),
+                    Some(3) => return Ok(ry(q, 4 - theta_quadrant)),
+                    _ => (),
+                }
+                // RXY(π, φ) = X RZ(-2φ) up to global phase
+                match (theta_quadrant, self.get_approximate_quadrant(-2.0 * phi)) {
+                    (2, Some(quadrant)) => Ok([s_power(q, quadrant), vec![Gate::X(q)]].concat()),
+                    (2, None) => bail!(
+                        "RXY(q0={q0}, theta={theta}, phi={phi}) is not representable in stabiliser form. When theta is nonzero, phi must be an (approximate) multiple of pi/4 for Clifford operations."
+                    ),
+                    _ => bail!(
+                        "RXY(q0={q0}, theta={theta}, phi={phi}) is not representable in stabiliser form. When theta is pi/2, phi must be an (approximate) multiple of pi/2."
+                    ),
+                }
+            }
+            Operation::Rzz(q0, q1, theta) => {
+                let Some(quadrant) = self.get_approximate_quadrant(theta) else {
+                    bail!(
+                        "RZZ(q0={q0}, q1={q1}, theta={theta}) is not representable in stabiliser form. Theta must be an (approximate) multiple of pi/2 for Clifford operations."
+                    );
+                };
+                let (a, b) = (q0 as usize, q1 as usize);
+                Ok([
+                    vec![Gate::Cx(a, b)],
+                    s_power(b, quadrant),
+                    vec![Gate::Cx(a, b)],
+                ]
+                .concat())
+            }
+            Operation::Measure(_) | Operation::Reset(_) => Ok(vec![]),
+        }
+    }
+
+    /// Switch the current shot to exact simulation, replaying the recorded
+    /// operations it has issued so far with the outcomes of its lane.
+    fn fall_back(&mut self) -> Result<()> {
+        let (lane, cursor) = match self.mode {
+            Mode::Exact(_) => return Ok(()),
+            Mode::Recording { .. } => (0, self.batch.as_ref().unwrap().operations.len()),
+            Mode::Replaying { lane, cursor, .. } => (lane, cursor),
+        };
+        let batch = self.batch.as_ref().unwrap();
+        let mut tableau = Tableau::new(self.n_qubits);
+        let mut outcome_index = 0;
+        for &operation in &batch.operations[..cursor] {
+            match operation {
+                Operation::Measure(q0) | Operation::Reset(q0) => {
+                    let forced = batch.outcome(outcome_index, lane);
+                    outcome_index += 1;
+                    let (outcome, _) = tableau.measure(q0 as usize, || forced);
+                    if outcome != forced {
+                        bail!(
+                            "Failed to reproduce the recorded outcome of Measure(q0={q0}) while replaying lane {lane} of the batch."
+                        );
+                    }
+                    if matches!(operation, Operation::Reset(_)) && outcome {
+                        tableau.apply(Gate::X(q0 as usize));
+                    }
+                }
+                _ => {
+                    for gate in self.lower(operation)? {
+                        tableau.apply(gate);
+                    }
+                }
+            }
+        }
+        self.mode = Mode::Exact(tableau);
+        Ok(())
+    }
+
+    /// Run an operation in the current mode, returning the outcome of a
+    /// measurement.
+    fn run(&mut self, operation: Operation) -> Result<Option<bool>> {
+        let gates = self.lower(operation)?;
+        let Self {
+            mode, batch, rng, ..
+        } = self;
+        match mode {
+            Mode::Recording {
+                reference,
+                frames,
+                rngs,
+            } => {
+                let batch = batch.as_mut().unwrap();
+                let result = match operation {
+                    Operation::Measure(q0) => {
+                        let (outcome, _) = reference.measure(q0 as usize, || false);
+                        batch
+                            .outcomes
+                            .push(frames.measure(q0 as usize, outcome, rngs));
+                        Some(batch.outcome(batch.outcomes.len() - 1, 0))
+                    }
+                    Operation::Reset(q0) => {
+                        let (outcome, _) = reference.measure(q0 as usize, || false);
+                        batch
+                            .outcomes
+                            .push(frames.measure(q0 as usize, outcome, rngs));
+                        if outcome {
+                            reference.apply(Gate::X(q0 as usize));
+                        }
+                        frames.reset(q0 as usize, rngs);
+                        None
+                    }
+                    _ => {
+                        for gate in gates {
+                            reference.apply(gate);
+                            frames.apply(gate);
+                        }
+                        None
+                    }
+                };
+                batch.operations.push(operation);
+                Ok(result)
+            }
+            Mode::Replaying {
+                lane,
+                cursor,
+                outcome_cursor,
+            } => {
+                let batch = batch.as_ref().unwrap();
+                if batch.operations.get(*cursor) != Some(&operation) {
+                    self.fall_back()?;
+                    return self.run(operation);
+                }
+                *cursor += 1;
+                match operation {
+                    Operation::Measure(_) | Operation::Reset(_) => {
+                        let outcome = batch.outcome(*outcome_cursor, *lane);
+                        *outcome_cursor += 1;
+                        Ok(matches!(operation, Operation::Measure(_)).then_some(outcome))
+                    }
+                    _ => Ok(None),
+                }
+            }
+            Mode::Exact(tableau) => match operation {
+                Operation::Measure(q0) => Ok(Some(
+                    tableau.measure(q0 as usize, || rng.random::<bool>()).0,
+                )),
+                Operation::Reset(q0) => {
+                    if tableau.measure(q0 as usize, || rng.random::<bool>()).0 {
+                        tableau.apply(Gate::X(q0 as usize));
+                    }
+                    Ok(None)
+                }
+                _ => {
+                    for gate in gates {
+                        tableau.apply(gate);
+                    }
+                    Ok(None)
+                }
+            },
+        }
+    }
+}
+
+impl SimulatorInterface for PauliFrameSimulator {
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn shot_start(&mut self, shot_id: u64, seed: u64) -> Result<()> {
+        let (lane_rng, rng) = shot_rngs(seed);
+        self.rng = rng;
+        self.cumulative_postselect_probability = 1.0;
+        if let Some(batch) = self
+            .batch
+            .as_mut()
+            .filter(|b| b.lanes.get(b.next_lane) == Some(&(shot_id, seed)))
+        {
+            self.lane = batch.next_lane as u64;
+            self.mode = Mode::Replaying {
+                lane: batch.next_lane,
+                cursor: 0,
+                outcome_cursor: 0,
+            };
+            batch.next_lane += 1;
+        } else {
+            // Start a batch with this shot and the planned shots that follow it.
+            let lanes = match self.plan.iter().position(|&s| s == (shot_id, seed)) {
+                Some(start) => {
+                    self.plan[start..self.plan.len().min(start + self.batch_size)].to_vec()
+                }
+                None => vec![(shot_id, seed)],
+            };
+            let mut rngs: Vec<Pcg64Mcg> = std::iter::once(lane_rng)
+                .chain(lanes[1..].iter().map(|&(_, seed)| shot_rngs(seed).0))
+                .collect();
+            self.lane = 0;
+            self.mode = Mode::Recording {
+                reference: Tableau::new(self.n_qubits),
+                frames: FrameBatch::new(self.n_qubits, &mut rngs),
+                rngs,
+            };
+            self.batch = Some(Batch {
+                lanes,
+                next_lane: 1,
+                operations: Vec::new(),
+                outcomes: Vec::new(),
+            });
+        }
+        Ok(())
+    }
+
+    fn plan_shots(&mut self, shot_ids: &[u64], seeds: &[u64]) -> Result<()> {
+        self.plan = shot_ids
+            .iter()
+            .copied()
+            .zip(seeds.iter().copied())
+            .collect();
+        Ok(())
+    }
+
+    fn shot_end(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn rz(&mut self, q0: u64, theta: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "RZ(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        self.run(Operation::Rz(q0, theta))?;
+        Ok(())
+    }
+
+    fn rxy(&mut self, q0: u64, theta: f64, phi: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "RXY(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        self.run(Operation::Rxy(q0, theta, phi))?;
+        Ok(())
+    }
+
+    fn rzz(&mut self, q0: u64, q1: u64, theta: f64) -> Result<()> {
+        if q0 >= self.n_qubits || q1 >= self.n_qubits {
+            bail!(
+                "RZZ(q0={q0}, q1={q1}) is out of bounds. q0 and q1 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        if q0 == q1 {
+            bail!("RZZ(q0={q0}, q1={q1}) requires two distinct qubits.");
+        }
+        self.run(Operation::Rzz(q0, q1, theta))?;
+        Ok(())
+    }
+
+    fn measure(&mut self, q0: u64) -> Result<bool> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Measure(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        Ok(self.run(Operation::Measure(q0))?.unwrap())
+    }
+
+    fn postselect(&mut self, q0: u64, target_value: bool) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Postselect(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        self.fall_back()?;
+        let Mode::Exact(tableau) = &mut self.mode else {
+            unreachable!("fall_back always switches to exact simulation");
+        };
+        let (outcome, random) = tableau.measure(q0 as usize, || target_value);
+        let postselect_probability = match (random, outcome == target_value) {
+            (true, _) => 0.5,
+            (false, true) => 1.0,
+            (false, false) => 0.0,
+        };
+        self.cumulative_postselect_probability *= postselect_probability;
+        if postselect_probability < 1e-10 {
+            bail!(
+                "Postselection of {} on qubit {q0} is too unlikely to postselect. The probability of this outcome is {postselect_probability:.2e}.",
+                target_value as u8
+            );
+        }
+        Ok(())
+    }
+
+    fn reset(&mut self, q0: u64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Reset(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        self.run(Operation::Reset(q0))?;
+        Ok(())
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "batch_lane".to_string(),
+                MetricValue::U64(self.lane),
+            ))),
+            1 => Ok(Some((
+                "served_from_batch".to_string(),
+                MetricValue::Bool(!matches!(self.mode, Mode::Exact(_))),
+            ))),
+            2 => Ok(Some((
+                "cumulative_postselect_probability".to_string(),
+                MetricValue::F64(self.cumulative_postselect_probability),
+            ))),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PauliFrameSimulatorFactory;
+
+impl SimulatorInterfaceFactory for PauliFrameSimulatorFactory {
+    type Interface = PauliFrameSimulator;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        let args: Vec<String> = args.iter().map(|s| s.as_ref().to_string()).collect();
+        let params = Params::try_parse_from(&args)?;
+        if n_qubits == 0 {
+            bail!("Number of qubits must be greater than 0");
+        }
+        if params.angle_threshold < 0.0 {
+            bail!(
+                "angle_threshold must be non-negative, got {}",
+                params.angle_threshold
+            );
+        }
+        if params.batch_size == 0 || params.batch_size % 64 != 0 {
+            bail!(
+                "batch_size must be a positive multiple of 64, got {}",
+                params.batch_size
+            );
+        }
+        Ok(Box::new(PauliFrameSimulator {
+            n_qubits,
+            angle_threshold_quad: params.angle_threshold / std::f64::consts::FRAC_PI_2,
+            batch_size: params.batch_size,
+            rng: Pcg64Mcg::seed_from_u64(0),
+            plan: Vec::new(),
+            batch: None,
+            mode: Mode::Exact(Tableau::new(n_qubits)),
+            lane: 0,
+            cumulative_postselect_probability: 1.0,
+        }))
+    }
+}
+
+export_simulator_plugin!(crate::PauliFrameSimulatorFactory);
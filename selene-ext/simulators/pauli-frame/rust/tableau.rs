@@ -0,0 +1,164 @@
+//! A stabilizer tableau in the form of Aaronson and Gottesman (CHP).
+//!
+//! Rows 0..n hold the destabilizers and rows n..2n the stabilizers, with an
+//! additional scratch row used for deterministic measurements. Each row stores
+//! its X and Z parts as bit vectors over the qubits, along with a sign bit.
+use crate::Gate;
+
+struct Row {
+    x: Vec<u64>,
+    z: Vec<u64>,
+    sign: bool,
+}
+
+impl Row {
+    fn identity(words: usize) -> Self {
+        Self {
+            x: vec![0; words],
+            z: vec![0; words],
+            sign: false,
+        }
+    }
+}
+
+fn get(bits: &[u64], q: usize) -> bool {
+    (bits[q / 64] >> (q % 64)) & 1 == 1
+}
+
+fn set(bits: &mut [u64], q: usize, value: bool) {
+    let mask = 1u64 << (q % 64);
+    if value {
+        bits[q / 64] |= mask;
+    } else {
+        bits[q / 64] &= !mask;
+    }
+}
+
+pub struct Tableau {
+    n_qubits: usize,
+    rows: Vec<Row>,
+}
+
+impl Tableau {
+    pub fn new(n_qubits: u64) -> Self {
+        let n = n_qubits as usize;
+        let words = n.div_ceil(64);
+        let mut rows: Vec<Row> = (0..2 * n + 1).map(|_| Row::identity(words)).collect();
+        for q in 0..n {
+            set(&mut rows[q].x, q, true);
+            set(&mut rows[n + q].z, q, true);
+        }
+        Self { n_qubits: n, rows }
+    }
+
+    pub fn apply(&mut self, gate: Gate) {
+        match gate {
+            Gate::H(q) => self.h(q),
+            Gate::S(q) => self.s(q),
+            Gate::SDag(q) => {
+                self.s(q);
+                self.s(q);
+                self.s(q);
+            }
+            Gate::X(q) => {
+                for row in self.rows.iter_mut() {
+                    row.sign ^= get(&row.z, q);
+                }
+            }
+            Gate::Cx(control, target) => self.cx(control, target),
+        }
+    }
+
+    fn h(&mut self, q: usize) {
+        for row in self.rows.iter_mut() {
+            let (x, z) = (get(&row.x, q), get(&row.z, q));
+            row.sign ^= x && z;
+            set(&mut row.x, q, z);
+            set(&mut row.z, q, x);
+        }
+    }
+
+    fn s(&mut self, q: usize) {
+        for row in self.rows.iter_mut() {
+            let (x, z) = (get(&row.x, q), get(&row.z, q));
+            row.sign ^= x && z;
+            set(&mut row.z, q, z ^ x);
+        }
+    }
+
+    fn cx(&mut self, control: usize, target: usize) {
+        for row in self.rows.iter_mut() {
+            let (xc, zc) = (get(&row.x, control), get(&row.z, control));
+            let (xt, zt) = (get(&row.x, target), get(&row.z, target));
+            row.sign ^= xc && zt && !(xt ^ zc);
+            set(&mut row.x, target, xt ^ xc);
+            set(&mut row.z, control, zc ^ zt);
+        }
+    }
+
+    /// Left-multiply row `h` by row `i`, tracking the sign.
+    fn rowsum(&mut self, h: usize, i: usize) {
+        // The exponent of i picked up by multiplying the Pauli on each qubit.
+        let g = |x1: bool, z1: bool, x2: bool, z2: bool| -> i64 {
+            match (x1, z1) {
+                (false, false) => 0,
+                (true, true) => z2 as i64 - x2 as i64,
+                (true, false) => z2 as i64 * (2 * x2 as i64 - 1),
+                (false, true) => x2 as i64 * (1 - 2 * z2 as i64),
+            }
+        };
+        let mut total = 2 * self.rows[h].sign as i64 + 2 * self.rows[i].sign as i64;
+        for q in 0..self.n_qubits {
+            total += g(
+                get(&self.rows[i].x, q),
+                get(&self.rows[i].z, q),
+                get(&self.rows[h].x, q),
+                get(&self.rows[h].z, q),
+            );
+        }
+        let (source, target) = if h < i {
+            let (left, right) = self.rows.split_at_mut(i);
+            (&right[0], &mut left[h])
+        } else {
+            let (left, right) = self.rows.split_at_mut(h);
+            (&left[i], &mut right[0])
+        };
+        target.sign = total.rem_euclid(4) == 2;
+        for (t, s) in target.x.iter_mut().zip(source.x.iter()) {
+            *t ^= s;
+        }
+        for (t, s) in target.z.iter_mut().zip(source.z.iter()) {
+            *t ^= s;
+        }
+    }
+
+    /// Measure Z on `q`. If the outcome is random, `choose` selects it.
+    /// Returns the outcome and whether it was random.
+    pub fn measure(&mut self, q: usize, choose: impl FnOnce() -> bool) -> (bool, bool) {
+        let n = self.n_qubits;
+        if let Some(p) = (n..2 * n).find(|&i| get(&self.rows[i].x, q)) {
+            for i in (0..2 * n).filter(|&i| i != p) {
+                if get(&self.rows[i].x, q) {
+                    self.rowsum(i, p);
+                }
+            }
+            let words = self.rows[p].x.len();
+            let stabilizer = std::mem::replace(&mut self.rows[p], Row::identity(words));
+            self.rows[p - n] = stabilizer;
+            let outcome = choose();
+            set(&mut self.rows[p].z, q, true);
+            self.rows[p].sign = outcome;
+            (outcome, true)
+        } else {
+            let scratch = 2 * n;
+            let words = self.rows[scratch].x.len();
+            self.rows[scratch] = Row::identity(words);
+            for i in 0..n {
+                if get(&self.rows[i].x, q) {
+                    self.rowsum(scratch, i + n);
+                }
+            }
+            (self.rows[scratch].sign, false)
+        }
+    }
+}
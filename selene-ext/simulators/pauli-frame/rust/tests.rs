@@ -0,0 +1,146 @@
+use crate::PauliFrameSimulatorFactory;
+use selene_core::simulator::SimulatorInterface;
+use selene_core::simulator::conformance_testing::run_basic_tests;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use std::f64::consts::PI;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Announce the given shots, each seeded with its shot id, as Selene would.
+fn plan(sim: &mut impl SimulatorInterface, shots: Range<u64>) {
+    let shot_ids: Vec<u64> = shots.collect();
+    sim.plan_shots(&shot_ids, &shot_ids).unwrap();
+}
+
+/// A CNOT up to a Z on the control, which is sufficient for preparing
+/// computational basis correlations.
+fn cx(sim: &mut impl SimulatorInterface, control: u64, target: u64) {
+    sim.rxy(target, PI / 2.0, PI / 2.0).unwrap();
+    sim.rz(control, PI / 2.0).unwrap();
+    sim.rz(target, PI / 2.0).unwrap();
+    sim.rzz(control, target, -PI / 2.0).unwrap();
+    sim.rxy(target, -PI / 2.0, PI / 2.0).unwrap();
+}
+
+#[test]
+fn basic_conformance_test() {
+    let interface = Arc::new(PauliFrameSimulatorFactory);
+    let args = vec![];
+    run_basic_tests(interface, args);
+}
+
+#[test]
+fn ghz_shots_are_served_from_batches() {
+    let n_qubits = 20;
+    let mut sim = Arc::new(PauliFrameSimulatorFactory)
+        .init(n_qubits, &["", "--batch-size=64"])
+        .unwrap();
+    plan(&mut *sim, 0..200);
+    let mut ones = 0;
+    for shot in 0..200 {
+        sim.shot_start(shot, shot).unwrap();
+        sim.rxy(0, PI / 2.0, PI / 2.0).unwrap();
+        for q in 0..n_qubits - 1 {
+            cx(&mut *sim, q, q + 1);
+        }
+        let first = sim.measure(0).unwrap();
+        for q in 1..n_qubits {
+            assert_eq!(sim.measure(q).unwrap(), first);
+        }
+        ones += first as u32;
+        assert_eq!(sim.lane, shot % 64);
+        assert!(matches!(
+            sim.get_metric(1).unwrap(),
+            Some((_, selene_core::utils::MetricValue::Bool(true)))
+        ));
+        sim.shot_end().unwrap();
+    }
+    assert!((60..140).contains(&ones), "{ones} of 200 shots measured 1");
+}
+
+#[test]
+fn diverging_shots_fall_back_consistently() {
+    let mut sim = Arc::new(PauliFrameSimulatorFactory)
+        .init(3, &["", "--batch-size=64"])
+        .unwrap();
+    plan(&mut *sim, 0..64);
+    let mut served = 0;
+    for shot in 0..64 {
+        sim.shot_start(shot, shot).unwrap();
+        sim.rxy(0, PI / 2.0, PI / 2.0).unwrap();
+        cx(&mut *sim, 0, 1);
+        let first = sim.measure(0).unwrap();
+        // Measurement-dependent control flow
+        if first {
+            sim.rxy(2, PI, 0.0).unwrap();
+        }
+        cx(&mut *sim, 1, 2);
+        assert!(!sim.measure(2).unwrap());
+        assert_eq!(sim.measure(1).unwrap(), first);
+        if !matches!(sim.mode, crate::Mode::Exact(_)) {
+            served += 1;
+        }
+        sim.shot_end().unwrap();
+    }
+    // Shots that take the same branch as the recording are served from it.
+    assert!(served > 1 && served < 64, "{served} of 64 shots served");
+}
+
+#[test]
+fn postselection_falls_back_to_exact_simulation() {
+    let mut sim = Arc::new(PauliFrameSimulatorFactory)
+        .init(2, &["", "--batch-size=64"])
+        .unwrap();
+    for shot in 0..10 {
+        sim.shot_start(shot, shot).unwrap();
+        sim.rxy(0, PI / 2.0, PI / 2.0).unwrap();
+        cx(&mut *sim, 0, 1);
+        sim.postselect(0, true).unwrap();
+        assert!(sim.measure(1).unwrap());
+        approx::assert_relative_eq!(sim.cumulative_postselect_probability, 0.5);
+        sim.shot_end().unwrap();
+    }
+}
+
+/// Runs shots of a circuit with random outcomes, after planning the given
+/// shots, and returns the outcomes of each shot.
+fn sample_shots(batch_size: usize, planned: Range<u64>, shots: Range<u64>) -> Vec<Vec<bool>> {
+    let mut sim = Arc::new(PauliFrameSimulatorFactory)
+        .init(4, &["".to_string(), format!("--batch-size={batch_size}")])
+        .unwrap();
+    plan(&mut *sim, planned);
+    shots
+        .map(|shot| {
+            sim.shot_start(shot, shot).unwrap();
+            for q in 0..4 {
+                sim.rxy(q, PI / 2.0, PI / 2.0).unwrap();
+            }
+            cx(&mut *sim, 0, 1);
+            cx(&mut *sim, 2, 3);
+            let mut outcomes: Vec<bool> = (0..4).map(|q| sim.measure(q).unwrap()).collect();
+            sim.reset(1).unwrap();
+            sim.rxy(1, PI / 2.0, PI / 2.0).unwrap();
+            cx(&mut *sim, 1, 0);
+            outcomes.push(sim.measure(0).unwrap());
+            outcomes.push(sim.measure(1).unwrap());
+            sim.shot_end().unwrap();
+            outcomes
+        })
+        .collect()
+}
+
+#[test]
+fn shot_results_do_not_depend_on_batching() {
+    // Without a plan, each shot is simulated as a batch of its own.
+    let expected = sample_shots(64, 0..0, 0..150);
+    assert!(expected.iter().any(|outcomes| outcomes != &expected[0]));
+    for batch_size in [64, 128, 256] {
+        assert_eq!(
+            sample_shots(batch_size, 0..150, 0..150),
+            expected,
+            "batch size {batch_size}"
+        );
+    }
+    // A plan starting partway through puts each shot in a different lane.
+    assert_eq!(sample_shots(64, 37..150, 37..150), expected[37..]);
+}
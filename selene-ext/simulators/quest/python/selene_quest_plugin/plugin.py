@@ -12,8 +12,24 @@ class QuestPlugin(Simulator):
     """
     A plugin for using QuEST, the statevector simulation engine,
     as the backend simulator for selene.
+
+    By default a statevector is simulated, and noise channels are sampled as
+    a single trajectory per shot. In density-matrix mode, noise channels are
+    applied exactly instead, at a memory cost of 16 * 4^n bytes for n qubits.
+
+    Attributes:
+        density_matrix (bool): Simulate a density matrix rather than a
+                               statevector. State dumps then always contain
+                               the reduced density matrix of the requested
+                               qubits.
+        reduced_state_dumps (bool): Dump the reduced density matrix of the
+                                    requested qubits rather than the full
+                                    statevector.
     """
 
+    density_matrix: bool = False
+    reduced_state_dumps: bool = False
+
     def __post_init__(self):
         pass
 
@@ -31,7 +47,12 @@ class QuestPlugin(Simulator):
                 raise RuntimeError(f"Unsupported platform: {platform.system()}")
 
     def get_init_args(self):
-        return []
+        args = []
+        if self.density_matrix:
+            args.append("--density-matrix")
+        if self.reduced_state_dumps:
+            args.append("--reduced-state-dumps")
+        return args
 
     @staticmethod
     def extract_states_dict(
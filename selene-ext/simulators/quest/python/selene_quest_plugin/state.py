@@ -22,14 +22,23 @@ class TracedState(Generic[T]):
 
 @dataclass
 class SeleneQuestState:
-    """A quantum state in the Selene Quest simulator, as reported by `state_result` calls."""
+    """A quantum state in the Selene Quest simulator, as reported by `state_result` calls.
 
-    #: Complex vector of size 2^total_qubits
-    state: np.ndarray
+    Simulators that track mixed states report the reduced density matrix of the
+    specified qubits rather than the full state vector. In that case `state` is
+    None and `reduced_density_matrix` is populated instead.
+    """
+
+    #: Complex vector of size 2^total_qubits, or None if the simulator reported
+    #: a reduced density matrix instead
+    state: np.ndarray | None
     #: Total number of qubits in the state, i.e. n_qubits param to run_shots
     total_qubits: int
     #: User-specified qubits, in order of their specification
     specified_qubits: list[int]
+    #: Complex matrix of size 2^len(specified_qubits) x 2^len(specified_qubits),
+    #: with the first specified qubit as the most significant bit.
+    reduced_density_matrix: np.ndarray | None = None
 
     def get_density_matrix(self, zero_threshold: float = 1e-12) -> np.ndarray:
         """
@@ -43,6 +52,11 @@ class SeleneQuestState:
             Default is 1e-12.
 
         """
+        if self.reduced_density_matrix is not None:
+            return self._apply_zero_threshold(
+                self.reduced_density_matrix.copy(), zero_threshold
+            )
+        assert self.state is not None, "Either a state or a density matrix is required"
         state_tensor = self.state.reshape([2] * self.total_qubits)
 
         # move all specified qubits to the end, in the user-specified order
@@ -69,7 +83,10 @@ class SeleneQuestState:
         result = np.einsum("ai,aj->ij", reshaped, np.conj(reshaped))
         # the shape is now (2**n_specified, 2**n_specified)
         assert result.shape == (2**n_specified, 2**n_specified)
+        return self._apply_zero_threshold(result, zero_threshold)
 
+    @staticmethod
+    def _apply_zero_threshold(result: np.ndarray, zero_threshold: float) -> np.ndarray:
         if zero_threshold > 0:
             # set small (relative) values to zero for a cleaner output
             max_magnitude = np.max(np.abs(result))
@@ -231,19 +248,34 @@ class SeleneQuestState:
     def parse_from_file(filename: Path, cleanup: bool = True) -> "SeleneQuestState":
         with open(filename, "rb") as f:
             magic = f.read(12)
-            if magic != b"selene-quest":
+            if magic not in (b"selene-quest", b"selene-rdmat"):
                 raise ValueError("Invalid state file format")
             header_head = f.read(16)
             total_qubits, n_specified_qubits = struct.unpack("<QQ", header_head)
             specified_qubits = []
             for i in range(n_specified_qubits):
                 specified_qubits.append(struct.unpack("<Q", f.read(8))[0])
-            state_size = 2**total_qubits
-            state = np.fromfile(
-                f,
-                dtype=np.complex128,
-                count=state_size,
-            )
+            if magic == b"selene-quest":
+                state_size = 2**total_qubits
+                state = np.fromfile(
+                    f,
+                    dtype=np.complex128,
+                    count=state_size,
+                )
+                result = SeleneQuestState(state, total_qubits, specified_qubits)
+            else:
+                reduced_size = 2**n_specified_qubits
+                density_matrix = np.fromfile(
+                    f,
+                    dtype=np.complex128,
+                    count=reduced_size * reduced_size,
+                ).reshape((reduced_size, reduced_size))
+                result = SeleneQuestState(
+                    None,
+                    total_qubits,
+                    specified_qubits,
+                    reduced_density_matrix=density_matrix,
+                )
         if cleanup:
             filename.unlink()
-        return SeleneQuestState(state, total_qubits, specified_qubits)
+        return result
@@ -9,10 +9,28 @@
 // gate_definitions.py file, which provides the matrices used for each gate, as well
 // as giving their real/imaginary parts for simplicity. The outputs are provided
 // in the comments within the implementation of each gate within this source file.
+//
+// Noise channels are applied by trajectory sampling, choosing a single Kraus
+// operator K with probability ||K|ψ>||² and replacing the state with the
+// normalised K|ψ>. QuEST's own decoherence functions only act on density
+// matrices, so are not used for statevectors.
+//
+// With `--density-matrix`, the simulator runs on a density matrix instead, and
+// noise channels are applied exactly with QuEST's decoherence functions. Gates
+// are applied to both sides of the density matrix, so the `applyGate*` variants
+// are used throughout, as the `apply*` variants only left-multiply. Density
+// matrices are always dumped as the reduced density matrix of the requested
+// qubits, and `--reduced-state-dumps` does the same for statevectors.
 use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
 use selene_core::export_simulator_plugin;
-use selene_core::simulator::SimulatorInterface;
 use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::simulator::{
+    Pauli, SimulatorInterface, kraus_operator_size, validate_kraus_operators,
+    validate_pauli_channel,
+};
 use selene_core::utils::MetricValue;
 use std::io::Write;
 
@@ -25,6 +43,18 @@ use std::os::raw::{c_int, c_ulong};
 #[cfg(test)]
 mod tests;
 
+#[derive(Parser, Debug)]
+struct Params {
+    /// Simulate a density matrix rather than a statevector, so that noise
+    /// channels are applied exactly rather than by trajectory sampling.
+    #[arg(long)]
+    density_matrix: bool,
+    /// Dump the reduced density matrix of the requested qubits rather than the
+    /// full statevector. Density matrices are always dumped in this way.
+    #[arg(long)]
+    reduced_state_dumps: bool,
+}
+
 #[cfg(all(target_os = "windows", target_env = "gnu"))]
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn invalidQuESTInputError(err_msg: *const c_char, err_func: *const c_char) {
@@ -55,10 +85,31 @@ pub struct QuestSimulator {
     environment: quest_sys::QuESTEnv,
     qureg: Qureg,
     n_qubits: u64,
+    density_matrix: bool,
+    reduced_state_dumps: bool,
     cumulative_postselect_probability: f64,
+    /// Snapshots by handle, or None once released.
+    snapshots: Vec<Option<Qureg>>,
+    // Scratch space required by calcExpecPauliProd and kraus_channel, created
+    // on first use.
+    workspace: Option<Qureg>,
+    // Used to sample channel trajectories. Measurements use QuEST's own
+    // generator.
+    rng: Pcg64Mcg,
 }
 
 impl QuestSimulator {
+    /// The slot of the snapshot with the given handle, which is None if the
+    /// snapshot has been released.
+    fn snapshot_slot(&mut self, handle: u64) -> Result<&mut Option<Qureg>> {
+        let n_snapshots = self.snapshots.len();
+        self.snapshots.get_mut(handle as usize).ok_or_else(|| {
+            anyhow!(
+                "Snapshot {handle} does not exist. Only {n_snapshots} snapshots have been taken."
+            )
+        })
+    }
+
     fn seed(&mut self, seed: u64) {
         // seedQuest accepts an array of 'c_ulong's, so we need to split the
         // provided seed accordingly.
@@ -76,10 +127,132 @@ impl QuestSimulator {
             );
         }
     }
+
+    /// Create a register of the same kind as the simulated one.
+    fn create_qureg(&self) -> Qureg {
+        let n_qubits = self.n_qubits as c_int;
+        if self.density_matrix {
+            unsafe { quest_sys::createDensityQureg(n_qubits, self.environment) }
+        } else {
+            unsafe { quest_sys::createQureg(n_qubits, self.environment) }
+        }
+    }
+
+    /// QuEST aborts the process on invalid input, so qubit lists passed to
+    /// its calculation functions must be validated beforehand.
+    fn check_qubit_list(&self, name: &str, qubits: &[u64]) -> Result<()> {
+        for (i, &q) in qubits.iter().enumerate() {
+            if q >= self.n_qubits {
+                bail!(
+                    "{name}(q={q}) is out of bounds. q must be less than the number of qubits ({}).",
+                    self.n_qubits
+                );
+            }
+            if qubits[..i].contains(&q) {
+                bail!("{name} requires distinct qubits, but qubit {q} was provided twice.");
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply one of the single-qubit Kraus operators diag(1, sqrt(1 - p))
+    /// and `jump`, where `jump` is only supported on |1> and is applied with
+    /// probability p_jump.
+    fn apply_damping_trajectory(
+        &mut self,
+        q0: u64,
+        p: f64,
+        jump: quest_sys::ComplexMatrix2,
+        p_jump: f64,
+    ) {
+        if self.rng.random::<f64>() < p_jump {
+            let scale = 1.0 / p_jump.sqrt();
+            let scaled = quest_sys::ComplexMatrix2 {
+                real: jump.real.map(|row| row.map(|x| x * scale)),
+                imag: jump.imag.map(|row| row.map(|x| x * scale)),
+            };
+            unsafe { quest_sys::applyMatrix2(self.qureg, q0 as c_int, scaled) };
+        } else {
+            let scale = 1.0 / (1.0 - p_jump).sqrt();
+            let keep = quest_sys::ComplexMatrix2 {
+                real: [[scale, 0.0], [0.0, scale * (1.0 - p).sqrt()]],
+                imag: [[0.0, 0.0], [0.0, 0.0]],
+            };
+            unsafe { quest_sys::applyMatrix2(self.qureg, q0 as c_int, keep) };
+        }
+    }
+
+    /// The reduced density matrix of the given qubits, in row-major order with
+    /// the first qubit as the most significant bit of the row and column
+    /// indices, as in the density matrix plugin's dumps.
+    fn reduced_density_matrix(&self, qubits: &[u64]) -> Vec<[f64; 2]> {
+        let k = qubits.len();
+        let reduced_dimension = 1usize << k;
+        let specified_mask = qubits.iter().fold(0usize, |mask, &q| mask | (1 << q));
+        let reduce = |x: usize| {
+            qubits
+                .iter()
+                .enumerate()
+                .fold(0usize, |acc, (j, &q)| acc | (((x >> q) & 1) << (k - 1 - j)))
+        };
+        let expand = |x: usize| {
+            qubits
+                .iter()
+                .enumerate()
+                .fold(0usize, |acc, (j, &q)| acc | (((x >> (k - 1 - j)) & 1) << q))
+        };
+        let reals: *const f64 = self.qureg.stateVec.real;
+        let imags: *const f64 = self.qureg.stateVec.imag;
+        let element = |i: usize| unsafe { (*reals.add(i), *imags.add(i)) };
+        let mut result = vec![[0.0; 2]; reduced_dimension * reduced_dimension];
+        for row in 0..(1usize << self.n_qubits) {
+            let reduced_row = reduce(row);
+            for reduced_col in 0..reduced_dimension {
+                let col = (row & !specified_mask) | expand(reduced_col);
+                let (re, im) = if self.density_matrix {
+                    // QuEST stores ρ column-major, with ρ_rc at r + c * 2^n.
+                    element(row | (col << self.n_qubits))
+                } else {
+                    // ψ_r conj(ψ_c)
+                    let (a_re, a_im) = element(row);
+                    let (b_re, b_im) = element(col);
+                    (a_re * b_re + a_im * b_im, a_im * b_re - a_re * b_im)
+                };
+                let entry = &mut result[reduced_row * reduced_dimension + reduced_col];
+                entry[0] += re;
+                entry[1] += im;
+            }
+        }
+        result
+    }
+}
+
+/// Create a QuEST matrix on `n_qubits` qubits from the row-major, interleaved
+/// real and imaginary parts used by the simulator interface. The caller is
+/// responsible for destroying it.
+fn create_complex_matrix_n(n_qubits: usize, elements: &[f64]) -> quest_sys::ComplexMatrixN {
+    let dimension = 1usize << n_qubits;
+    let matrix = unsafe { quest_sys::createComplexMatrixN(n_qubits as c_int) };
+    for row in 0..dimension {
+        for col in 0..dimension {
+            let index = 2 * (row * dimension + col);
+            unsafe {
+                *(*matrix.real.add(row)).add(col) = elements[index];
+                *(*matrix.imag.add(row)).add(col) = elements[index + 1];
+            }
+        }
+    }
+    matrix
 }
 
 impl SimulatorInterface for QuestSimulator {
     fn exit(&mut self) -> Result<()> {
+        for snapshot in self.snapshots.drain(..).flatten() {
+            unsafe { quest_sys::destroyQureg(snapshot, self.environment) };
+        }
+        if let Some(workspace) = self.workspace.take() {
+            unsafe { quest_sys::destroyQureg(workspace, self.environment) };
+        }
         Ok(())
     }
 
@@ -87,6 +260,7 @@ impl SimulatorInterface for QuestSimulator {
         unsafe { quest_sys::initClassicalState(self.qureg, 0) };
         self.cumulative_postselect_probability = 1.0;
         self.seed(seed);
+        self.rng = Pcg64Mcg::seed_from_u64(seed);
         Ok(())
     }
 
@@ -182,7 +356,8 @@ impl SimulatorInterface for QuestSimulator {
             // ⎢                    ⎥
             // ⎣0    0       0     0⎦
             //
-            // We implement this using a sub-diagonal operator in QuEST.
+            // We implement this using a sub-diagonal operator in QuEST, applied
+            // as a gate so that density matrices are conjugated.
             let cos = theta.cos();
             let sin = theta.sin();
             let mut targets: [c_int; 2] = [q0 as c_int, q1 as c_int];
@@ -192,13 +367,98 @@ impl SimulatorInterface for QuestSimulator {
                 let op = quest_sys::createSubDiagonalOp(2);
                 std::ptr::copy_nonoverlapping(diag_real.as_ptr(), op.real, 4);
                 std::ptr::copy_nonoverlapping(diag_imag.as_ptr(), op.imag, 4);
-                quest_sys::applySubDiagonalOp(self.qureg, targets.as_mut_ptr(), 2, op);
+                quest_sys::applyGateSubDiagonalOp(self.qureg, targets.as_mut_ptr(), 2, op);
                 quest_sys::destroySubDiagonalOp(op);
             }
             Ok(())
         }
     }
 
+    fn u(&mut self, q0: u64, theta: f64, phi: f64, lambda: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            return Err(anyhow!(
+                "U(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            ));
+        }
+        // ⎡ cos(θ/2)            -e^{iλ}⋅sin(θ/2)     ⎤
+        // ⎣ e^{iφ}⋅sin(θ/2)     e^{i(φ+λ)}⋅cos(θ/2) ⎦
+        let cos = (theta / 2.0).cos();
+        let sin = (theta / 2.0).sin();
+        let u = quest_sys::ComplexMatrix2 {
+            real: [
+                [cos, -sin * lambda.cos()],
+                [sin * phi.cos(), cos * (phi + lambda).cos()],
+            ],
+            imag: [
+                [0.0, -sin * lambda.sin()],
+                [sin * phi.sin(), cos * (phi + lambda).sin()],
+            ],
+        };
+        unsafe { quest_sys::unitary(self.qureg, q0 as c_int, u) };
+        Ok(())
+    }
+
+    fn two_qubit_unitary(&mut self, q0: u64, q1: u64, matrix: &[f64; 32]) -> Result<()> {
+        if q0 >= self.n_qubits || q1 >= self.n_qubits {
+            return Err(anyhow!(
+                "TwoQubitUnitary(q0={q0}, q1={q1}) is out of bounds. q0 and q1 must be less than the number of qubits ({}).",
+                self.n_qubits
+            ));
+        }
+        if q0 == q1 {
+            bail!("TwoQubitUnitary requires distinct qubits, but qubit {q0} was provided twice.");
+        }
+        // QuEST treats the first target as the least significant bit, as we do.
+        // Unitarity has already been validated by Selene, to a looser tolerance
+        // than QuEST's own unitary check, so the unchecked gate is used.
+        let mut targets: [c_int; 2] = [q0 as c_int, q1 as c_int];
+        let u = create_complex_matrix_n(2, matrix);
+        unsafe {
+            quest_sys::applyGateMatrixN(self.qureg, targets.as_mut_ptr(), 2, u);
+            quest_sys::destroyComplexMatrixN(u);
+        }
+        Ok(())
+    }
+
+    fn diagonal_phase(&mut self, qubits: &[u64], phases: &[f64]) -> Result<()> {
+        for (i, &q) in qubits.iter().enumerate() {
+            if q >= self.n_qubits {
+                bail!(
+                    "DiagonalPhase(q={q}) is out of bounds. q must be less than the number of qubits ({}).",
+                    self.n_qubits
+                );
+            }
+            if qubits[..i].contains(&q) {
+                bail!("DiagonalPhase requires distinct qubits, but qubit {q} was provided twice.");
+            }
+        }
+        if phases.len() != 1 << qubits.len() {
+            bail!(
+                "DiagonalPhase on {} qubits requires {} phases, got {}.",
+                qubits.len(),
+                1usize << qubits.len(),
+                phases.len()
+            );
+        }
+        let mut targets: Vec<c_int> = qubits.iter().map(|&q| q as c_int).collect();
+        let diag_real: Vec<f64> = phases.iter().map(|phase| phase.cos()).collect();
+        let diag_imag: Vec<f64> = phases.iter().map(|phase| phase.sin()).collect();
+        unsafe {
+            let op = quest_sys::createSubDiagonalOp(targets.len() as c_int);
+            std::ptr::copy_nonoverlapping(diag_real.as_ptr(), op.real, phases.len());
+            std::ptr::copy_nonoverlapping(diag_imag.as_ptr(), op.imag, phases.len());
+            quest_sys::applyGateSubDiagonalOp(
+                self.qureg,
+                targets.as_mut_ptr(),
+                targets.len() as c_int,
+                op,
+            );
+            quest_sys::destroySubDiagonalOp(op);
+        }
+        Ok(())
+    }
+
     fn measure(&mut self, q0: u64) -> Result<bool> {
         if q0 >= self.n_qubits {
             Err(anyhow!(
@@ -228,8 +488,14 @@ impl SimulatorInterface for QuestSimulator {
                     "Postselection of {target_value} on qubit {q0} is too unlikely to postselect. The probability of this outcome is {postselect_probability:.2e}.",
                 ));
             }
-            let scale = 1.0 / postselect_probability.sqrt();
-            // Rescale the state vector to maintain normalization
+            // Rescale the state to maintain normalization. The matrix is only
+            // applied on the left of a density matrix, which is linear in the
+            // probability rather than its square root.
+            let scale = if self.density_matrix {
+                1.0 / postselect_probability
+            } else {
+                1.0 / postselect_probability.sqrt()
+            };
             let mat = quest_sys::ComplexMatrix2 {
                 real: [[scale, 0.0], [0.0, scale]],
                 imag: [[0.0, 0.0], [0.0, 0.0]],
@@ -255,6 +521,267 @@ impl SimulatorInterface for QuestSimulator {
             Ok(())
         }
     }
+    fn snapshot(&mut self) -> Result<u64> {
+        let snapshot = unsafe { quest_sys::createCloneQureg(self.qureg, self.environment) };
+        self.snapshots.push(Some(snapshot));
+        Ok(self.snapshots.len() as u64 - 1)
+    }
+    fn restore(&mut self, handle: u64) -> Result<()> {
+        let snapshot = self.snapshot_slot(handle)?;
+        let Some(snapshot) = *snapshot else {
+            bail!("Snapshot {handle} has been released.");
+        };
+        unsafe { quest_sys::cloneQureg(self.qureg, snapshot) };
+        Ok(())
+    }
+    fn release_snapshot(&mut self, handle: u64) -> Result<()> {
+        if let Some(snapshot) = self.snapshot_slot(handle)?.take() {
+            unsafe { quest_sys::destroyQureg(snapshot, self.environment) };
+        }
+        Ok(())
+    }
+    fn marginal_probabilities(&mut self, qubits: &[u64]) -> Result<Vec<f64>> {
+        self.check_qubit_list("MarginalProbabilities", qubits)?;
+        if qubits.is_empty() {
+            return Ok(vec![1.0]);
+        }
+        let mut targets: Vec<c_int> = qubits.iter().map(|&q| q as c_int).collect();
+        let mut probabilities = vec![0.0; 1 << qubits.len()];
+        unsafe {
+            quest_sys::calcProbOfAllOutcomes(
+                probabilities.as_mut_ptr(),
+                self.qureg,
+                targets.as_mut_ptr(),
+                targets.len() as c_int,
+            )
+        };
+        Ok(probabilities)
+    }
+    fn pauli_expectation(&mut self, pauli_string: &[(u64, Pauli)]) -> Result<f64> {
+        let qubits: Vec<u64> = pauli_string.iter().map(|&(q, _)| q).collect();
+        self.check_qubit_list("PauliExpectation", &qubits)?;
+        if qubits.is_empty() {
+            return Ok(1.0);
+        }
+        let workspace = match self.workspace {
+            Some(workspace) => workspace,
+            None => *self.workspace.insert(self.create_qureg()),
+        };
+        let mut targets: Vec<c_int> = qubits.iter().map(|&q| q as c_int).collect();
+        // Pauli discriminants match QuEST's pauliOpType codes.
+        let mut codes: Vec<quest_sys::pauliOpType> = pauli_string
+            .iter()
+            .map(|&(_, pauli)| pauli as quest_sys::pauliOpType)
+            .collect();
+        Ok(unsafe {
+            quest_sys::calcExpecPauliProd(
+                self.qureg,
+                targets.as_mut_ptr(),
+                codes.as_mut_ptr(),
+                targets.len() as c_int,
+                workspace,
+            )
+        })
+    }
+    fn pauli_channel(&mut self, q0: u64, p_x: f64, p_y: f64, p_z: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "PauliChannel(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        validate_pauli_channel(p_x, p_y, p_z)?;
+        if self.density_matrix {
+            // QuEST's mixPauli rejects channels in which any error is more
+            // likely than no error, so the channel is applied as a Kraus map.
+            let (i, x, y, z) = (
+                (1.0 - p_x - p_y - p_z).max(0.0).sqrt(),
+                p_x.sqrt(),
+                p_y.sqrt(),
+                p_z.sqrt(),
+            );
+            let mut operators = [
+                quest_sys::ComplexMatrix2 {
+                    real: [[i, 0.0], [0.0, i]],
+                    imag: [[0.0, 0.0], [0.0, 0.0]],
+                },
+                quest_sys::ComplexMatrix2 {
+                    real: [[0.0, x], [x, 0.0]],
+                    imag: [[0.0, 0.0], [0.0, 0.0]],
+                },
+                quest_sys::ComplexMatrix2 {
+                    real: [[0.0, 0.0], [0.0, 0.0]],
+                    imag: [[0.0, -y], [y, 0.0]],
+                },
+                quest_sys::ComplexMatrix2 {
+                    real: [[z, 0.0], [0.0, -z]],
+                    imag: [[0.0, 0.0], [0.0, 0.0]],
+                },
+            ];
+            unsafe {
+                quest_sys::mixNonTPKrausMap(self.qureg, q0 as c_int, operators.as_mut_ptr(), 4)
+            };
+            return Ok(());
+        }
+        let r = self.rng.random::<f64>();
+        if r < p_x {
+            unsafe { quest_sys::pauliX(self.qureg, q0 as c_int) };
+        } else if r < p_x + p_y {
+            unsafe { quest_sys::pauliY(self.qureg, q0 as c_int) };
+        } else if r < p_x + p_y + p_z {
+            unsafe { quest_sys::pauliZ(self.qureg, q0 as c_int) };
+        }
+        Ok(())
+    }
+    fn amplitude_damping(&mut self, q0: u64, gamma: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "AmplitudeDamping(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        if !(0.0..=1.0).contains(&gamma) {
+            bail!("Amplitude damping gamma must be a probability in [0, 1], got {gamma}");
+        }
+        if self.density_matrix {
+            unsafe { quest_sys::mixDamping(self.qureg, q0 as c_int, gamma) };
+            return Ok(());
+        }
+        let p1 = unsafe { quest_sys::calcProbOfOutcome(self.qureg, q0 as c_int, 1) };
+        // K1 = sqrt(γ)|0><1|
+        let jump = quest_sys::ComplexMatrix2 {
+            real: [[0.0, gamma.sqrt()], [0.0, 0.0]],
+            imag: [[0.0, 0.0], [0.0, 0.0]],
+        };
+        self.apply_damping_trajectory(q0, gamma, jump, gamma * p1);
+        Ok(())
+    }
+    fn phase_damping(&mut self, q0: u64, lambda: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "PhaseDamping(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        if !(0.0..=1.0).contains(&lambda) {
+            bail!("Phase damping lambda must be in [0, 1], got {lambda}");
+        }
+        if self.density_matrix {
+            // mixDephasing(p) scales the off-diagonal elements by 1 - 2p.
+            let p = (1.0 - (1.0 - lambda).sqrt()) / 2.0;
+            unsafe { quest_sys::mixDephasing(self.qureg, q0 as c_int, p) };
+            return Ok(());
+        }
+        let p1 = unsafe { quest_sys::calcProbOfOutcome(self.qureg, q0 as c_int, 1) };
+        // K1 = diag(0, sqrt(λ))
+        let jump = quest_sys::ComplexMatrix2 {
+            real: [[0.0, 0.0], [0.0, lambda.sqrt()]],
+            imag: [[0.0, 0.0], [0.0, 0.0]],
+        };
+        self.apply_damping_trajectory(q0, lambda, jump, lambda * p1);
+        Ok(())
+    }
+    fn kraus_channel(&mut self, qubits: &[u64], kraus_operators: &[f64]) -> Result<()> {
+        self.check_qubit_list("KrausChannel", qubits)?;
+        let n_operators = validate_kraus_operators(qubits.len(), kraus_operators)?;
+        let mut targets: Vec<c_int> = qubits.iter().map(|&q| q as c_int).collect();
+        if self.density_matrix {
+            if n_operators > 1 << (2 * qubits.len()) {
+                bail!(
+                    "QuEST supports at most {} Kraus operators on {} qubits, got {n_operators}.",
+                    1u64 << (2 * qubits.len()),
+                    qubits.len()
+                );
+            }
+            // The channel has already been validated by Selene, to a looser
+            // tolerance than QuEST's own check, so the unchecked map is used.
+            let mut operators: Vec<quest_sys::ComplexMatrixN> = kraus_operators
+                .chunks_exact(kraus_operator_size(qubits.len())?)
+                .map(|k| create_complex_matrix_n(qubits.len(), k))
+                .collect();
+            unsafe {
+                quest_sys::mixNonTPMultiQubitKrausMap(
+                    self.qureg,
+                    targets.as_mut_ptr(),
+                    targets.len() as c_int,
+                    operators.as_mut_ptr(),
+                    n_operators as c_int,
+                );
+                for operator in operators {
+                    quest_sys::destroyComplexMatrixN(operator);
+                }
+            }
+            return Ok(());
+        }
+        let workspace = match self.workspace {
+            Some(workspace) => workspace,
+            None => *self.workspace.insert(self.create_qureg()),
+        };
+        let dimension = 1usize << qubits.len();
+        let matrix = unsafe { quest_sys::createComplexMatrixN(targets.len() as c_int) };
+        let fill = |k: &[f64], scale: f64| {
+            for row in 0..dimension {
+                for col in 0..dimension {
+                    let index = 2 * (row * dimension + col);
+                    unsafe {
+                        *(*matrix.real.add(row)).add(col) = k[index] * scale;
+                        *(*matrix.imag.add(row)).add(col) = k[index + 1] * scale;
+                    }
+                }
+            }
+        };
+        let operators: Vec<&[f64]> = kraus_operators
+            .chunks_exact(kraus_operator_size(qubits.len())?)
+            .collect();
+        // The probability of each branch is the norm of K|ψ>, which is
+        // computed in the workspace.
+        let probabilities: Vec<f64> = operators
+            .iter()
+            .map(|k| {
+                fill(k, 1.0);
+                unsafe {
+                    quest_sys::cloneQureg(workspace, self.qureg);
+                    quest_sys::applyMatrixN(
+                        workspace,
+                        targets.as_mut_ptr(),
+                        targets.len() as c_int,
+                        matrix,
+                    );
+                    quest_sys::calcTotalProb(workspace)
+                }
+            })
+            .collect();
+        // Rounding may leave the probabilities summing to slightly less than
+        // one, in which case the last possible branch is taken.
+        let r = self.rng.random::<f64>();
+        let mut cumulative = 0.0;
+        let chosen = probabilities
+            .iter()
+            .enumerate()
+            .filter(|&(_, &p)| p > 0.0)
+            .find(|&(_, &p)| {
+                cumulative += p;
+                r < cumulative
+            })
+            .or_else(|| probabilities.iter().enumerate().rfind(|&(_, &p)| p > 0.0));
+        let result = match chosen {
+            Some((index, &probability)) => {
+                fill(operators[index], 1.0 / probability.sqrt());
+                unsafe {
+                    quest_sys::applyMatrixN(
+                        self.qureg,
+                        targets.as_mut_ptr(),
+                        targets.len() as c_int,
+                        matrix,
+                    )
+                };
+                Ok(())
+            }
+            None => Err(anyhow!("KrausChannel annihilated the state")),
+        };
+        unsafe { quest_sys::destroyComplexMatrixN(matrix) };
+        result
+    }
     fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
         match nth_metric {
             0 => Ok(Some((
@@ -265,6 +792,22 @@ impl SimulatorInterface for QuestSimulator {
         }
     }
     fn dump_state(&mut self, file: &std::path::Path, qubits: &[u64]) -> Result<()> {
+        if self.density_matrix || self.reduced_state_dumps {
+            self.check_qubit_list("DumpState", qubits)?;
+            let handle = std::fs::File::create(file)?;
+            let mut writer = std::io::BufWriter::new(handle);
+            writer.write_all(b"selene-rdmat")?;
+            writer.write_all(self.n_qubits.to_le_bytes().as_slice())?;
+            writer.write_all((qubits.len() as u64).to_le_bytes().as_slice())?;
+            for &q in qubits {
+                writer.write_all(q.to_le_bytes().as_slice())?;
+            }
+            for [real, imag] in self.reduced_density_matrix(qubits) {
+                writer.write_all(real.to_le_bytes().as_slice())?;
+                writer.write_all(imag.to_le_bytes().as_slice())?;
+            }
+            return Ok(());
+        }
         let handle = std::fs::File::create(file)?;
         let mut writer = std::io::BufWriter::new(handle);
         writer.write_all(b"selene-quest")?;
@@ -288,16 +831,26 @@ impl SimulatorInterface for QuestSimulator {
 #[derive(Default)]
 pub struct QuestSimulatorFactory;
 
-fn check_memory(n_qubits: u64) -> Result<()> {
+fn check_memory(n_qubits: u64, density_matrix: bool) -> Result<()> {
+    let (kind, max_qubits) = if density_matrix {
+        ("density matrix", 30)
+    } else {
+        ("statevector", 60)
+    };
     if n_qubits == 0 {
         bail!("Number of qubits must be greater than 0");
-    } else if n_qubits > 60 {
+    } else if n_qubits > max_qubits {
         bail!(
-            "It is impossible to describe more than 60 qubits in a statevector on a computer with a 64-bit address space."
+            "It is impossible to describe more than {max_qubits} qubits in a {kind} on a computer with a 64-bit address space."
         );
     }
     // check against the maximum size of a 64-bit address space
-    let bytes_required = bytesize::ByteSize::b(16 * (1 << n_qubits));
+    let n_amplitude_qubits = if density_matrix {
+        2 * n_qubits
+    } else {
+        n_qubits
+    };
+    let bytes_required = bytesize::ByteSize::b(16 * (1 << n_amplitude_qubits));
     let mut system = sysinfo::System::new();
     system.refresh_memory();
     let reported_available = system.available_memory();
@@ -305,7 +858,7 @@ fn check_memory(n_qubits: u64) -> Result<()> {
         eprintln!("-----------------------------------");
         eprintln!("Unable to determine available memory due to system limitations.");
         eprintln!("QuEST is going to try to allocate {bytes_required} of memory to");
-        eprintln!("store the statevector, and this will be multiplied by the number");
+        eprintln!("store the {kind}, and this will be multiplied by the number");
         eprintln!("of processes if running in multiprocessing mode.");
         eprintln!();
         eprintln!("If this fails, verify that your system has sufficient memory.");
@@ -314,7 +867,7 @@ fn check_memory(n_qubits: u64) -> Result<()> {
         let bytes_available = bytesize::ByteSize::b(reported_available);
         if bytes_required > bytes_available {
             bail!(
-                "Insufficient memory available ({bytes_available}) to allocate a state vector of {n_qubits} qubits ({bytes_required}).",
+                "Insufficient memory available ({bytes_available}) to allocate a {kind} of {n_qubits} qubits ({bytes_required}).",
             );
         }
     }
@@ -330,21 +883,26 @@ impl SimulatorInterfaceFactory for QuestSimulatorFactory {
         args: &[impl AsRef<str>],
     ) -> Result<Box<Self::Interface>> {
         let args: Vec<String> = args.iter().map(|s| s.as_ref().to_string()).collect();
-        if args.len() > 1 {
-            bail!(
-                "Expected no arguments for the quest plugin, got {} arguments: {:?}",
-                args.len() - 1,
-                args.iter().skip(1)
-            );
-        }
-        check_memory(n_qubits)?;
+        let params = Params::try_parse_from(args)
+            .map_err(|e| anyhow!("Error parsing arguments to quest plugin: {e}"))?;
+        check_memory(n_qubits, params.density_matrix)?;
         let environment = unsafe { quest_sys::createQuESTEnv() };
-        let qureg = unsafe { quest_sys::createQureg(n_qubits.try_into().unwrap(), environment) };
+        let n_qubits_int: c_int = n_qubits.try_into().unwrap();
+        let qureg = if params.density_matrix {
+            unsafe { quest_sys::createDensityQureg(n_qubits_int, environment) }
+        } else {
+            unsafe { quest_sys::createQureg(n_qubits_int, environment) }
+        };
         Ok(Box::new(QuestSimulator {
             environment,
             qureg,
             n_qubits,
+            density_matrix: params.density_matrix,
+            reduced_state_dumps: params.reduced_state_dumps,
             cumulative_postselect_probability: 1.0,
+            snapshots: Vec::new(),
+            workspace: None,
+            rng: Pcg64Mcg::seed_from_u64(0),
         }))
     }
 }
@@ -1,5 +1,8 @@
 use crate::QuestSimulatorFactory;
 use selene_core::simulator::conformance_testing::run_basic_tests;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::simulator::{Pauli, SimulatorInterface};
+use std::f64::consts::PI;
 use std::sync::Arc;
 #[test]
 fn basic_conformance_test() {
@@ -7,3 +10,178 @@ fn basic_conformance_test() {
     let args = vec![];
     run_basic_tests(interface, args);
 }
+
+#[test]
+fn snapshot_restore_branches_from_shared_state() {
+    let mut sim = Arc::new(QuestSimulatorFactory).init(2, &[""]).unwrap();
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(0, PI / 2.0, PI / 2.0).unwrap();
+    sim.rxy(1, PI, 0.0).unwrap();
+    let handle = sim.snapshot().unwrap();
+    let mut ones = 0;
+    for shot in 0..100 {
+        sim.shot_start(shot, shot).unwrap();
+        sim.restore(handle).unwrap();
+        // Qubit 1 was flipped before the snapshot.
+        assert!(sim.measure(1).unwrap());
+        if sim.measure(0).unwrap() {
+            ones += 1;
+        }
+    }
+    // Each branch measures the superposition on qubit 0 afresh.
+    assert!(ones > 10 && ones < 90);
+    assert!(sim.restore(handle + 1).is_err());
+    // Released snapshots can no longer be restored.
+    sim.release_snapshot(handle).unwrap();
+    assert!(sim.restore(handle).is_err());
+    assert!(sim.release_snapshot(handle + 1).is_err());
+}
+
+#[test]
+fn queries_do_not_disturb_the_state() {
+    let mut sim = Arc::new(QuestSimulatorFactory).init(3, &[""]).unwrap();
+    sim.shot_start(0, 0).unwrap();
+    // |+> on qubit 0, (|0> - i|1>)/√2 on qubit 1, |1> on qubit 2
+    sim.rxy(0, PI / 2.0, PI / 2.0).unwrap();
+    sim.rxy(1, PI / 2.0, 0.0).unwrap();
+    sim.rxy(2, PI, 0.0).unwrap();
+    for _ in 0..2 {
+        let probabilities = sim.marginal_probabilities(&[2, 0]).unwrap();
+        for (actual, expected) in probabilities.iter().zip([0.0, 0.5, 0.0, 0.5]) {
+            approx::assert_relative_eq!(*actual, expected, epsilon = 1e-9);
+        }
+        approx::assert_relative_eq!(
+            sim.pauli_expectation(&[(0, Pauli::X)]).unwrap(),
+            1.0,
+            epsilon = 1e-9
+        );
+        approx::assert_relative_eq!(
+            sim.pauli_expectation(&[(1, Pauli::Y)]).unwrap(),
+            -1.0,
+            epsilon = 1e-9
+        );
+        approx::assert_relative_eq!(
+            sim.pauli_expectation(&[(0, Pauli::X), (1, Pauli::Y), (2, Pauli::Z)])
+                .unwrap(),
+            1.0,
+            epsilon = 1e-9
+        );
+        approx::assert_relative_eq!(
+            sim.pauli_expectation(&[(0, Pauli::Z)]).unwrap(),
+            0.0,
+            epsilon = 1e-9
+        );
+    }
+    assert!(sim.marginal_probabilities(&[0, 0]).is_err());
+    assert!(sim.pauli_expectation(&[(3, Pauli::X)]).is_err());
+}
+
+#[test]
+fn kraus_channels_follow_a_single_trajectory() {
+    let mut sim = Arc::new(QuestSimulatorFactory).init(3, &[""]).unwrap();
+    // A correlated two-qubit bit flip, applying X⊗X with probability p
+    let p: f64 = 0.25;
+    let (keep, flip) = ((1.0 - p).sqrt(), p.sqrt());
+    let mut kraus = vec![0.0; 64];
+    for i in 0..4 {
+        kraus[2 * (i * 4 + i)] = keep;
+        kraus[32 + 2 * (i * 4 + (3 - i))] = flip;
+    }
+    let shots = 1000;
+    let mut flips = 0;
+    for shot in 0..shots {
+        sim.shot_start(shot, shot).unwrap();
+        sim.rxy(1, PI, 0.0).unwrap();
+        sim.kraus_channel(&[2, 0], &kraus).unwrap();
+        sim.amplitude_damping(1, 1.0).unwrap();
+        let probabilities = sim.marginal_probabilities(&[0, 1, 2]).unwrap();
+        // Each trajectory is normalised, with qubits 0 and 2 in agreement
+        // and qubit 1 decayed.
+        let (none, both) = (probabilities[0b000], probabilities[0b101]);
+        approx::assert_relative_eq!(none + both, 1.0, epsilon = 1e-12);
+        flips += (both > 0.5) as u64;
+    }
+    assert!((flips as f64 / shots as f64 - p).abs() < 0.07);
+}
+
+#[test]
+fn density_matrix_conformance_test() {
+    let interface = Arc::new(QuestSimulatorFactory);
+    let args = vec!["".to_string(), "--density-matrix".to_string()];
+    run_basic_tests(interface, args);
+}
+
+#[test]
+fn density_matrix_channels_are_exact() {
+    let mut sim = Arc::new(QuestSimulatorFactory)
+        .init(2, &["", "--density-matrix"])
+        .unwrap();
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(0, PI, 0.0).unwrap();
+    sim.amplitude_damping(0, 0.25).unwrap();
+    let probabilities = sim.marginal_probabilities(&[0]).unwrap();
+    approx::assert_relative_eq!(probabilities[1], 0.75, epsilon = 1e-12);
+    // Full dephasing of |+> leaves <X> at zero rather than ±1.
+    sim.rxy(1, PI / 2.0, PI / 2.0).unwrap();
+    sim.phase_damping(1, 1.0).unwrap();
+    let expectation = sim.pauli_expectation(&[(1, Pauli::X)]).unwrap();
+    approx::assert_relative_eq!(expectation, 0.0, epsilon = 1e-12);
+    sim.pauli_channel(1, 0.0, 0.0, 0.9).unwrap();
+    sim.postselect(0, true).unwrap();
+    let probabilities = sim.marginal_probabilities(&[0, 1]).unwrap();
+    approx::assert_relative_eq!(probabilities[0b01], 0.5, epsilon = 1e-12);
+    approx::assert_relative_eq!(probabilities[0b11], 0.5, epsilon = 1e-12);
+    // A correlated bit flip on both qubits with probability 1/4
+    let (keep, flip) = (0.75f64.sqrt(), 0.5);
+    let mut kraus = vec![0.0; 64];
+    for i in 0..4 {
+        kraus[2 * (i * 4 + i)] = keep;
+        kraus[32 + 2 * (i * 4 + (3 - i))] = flip;
+    }
+    sim.kraus_channel(&[0, 1], &kraus).unwrap();
+    let probabilities = sim.marginal_probabilities(&[0, 1]).unwrap();
+    approx::assert_relative_eq!(probabilities[0b00], 0.125, epsilon = 1e-12);
+    approx::assert_relative_eq!(probabilities[0b11], 0.375, epsilon = 1e-12);
+}
+
+#[test]
+fn reduced_state_dumps_trace_out_other_qubits() {
+    for mode in ["--reduced-state-dumps", "--density-matrix"] {
+        let mut sim = Arc::new(QuestSimulatorFactory)
+            .init(3, &["", mode])
+            .unwrap();
+        sim.shot_start(0, 0).unwrap();
+        // A Bell pair on qubits 0 and 2, with qubit 1 in |1>
+        sim.rxy(0, PI / 2.0, PI / 2.0).unwrap();
+        sim.rxy(2, PI / 2.0, PI / 2.0).unwrap();
+        sim.rzz(0, 2, PI / 2.0).unwrap();
+        sim.rxy(2, PI / 2.0, 0.0).unwrap();
+        sim.rxy(1, PI, 0.0).unwrap();
+        let file = std::env::temp_dir().join(format!(
+            "selene-quest-reduced-{}-{}",
+            std::process::id(),
+            mode.trim_start_matches('-')
+        ));
+        sim.dump_state(&file, &[1, 0]).unwrap();
+        let bytes = std::fs::read(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(&bytes[..12], b"selene-rdmat");
+        let header = 12 + 8 * 4;
+        let elements: Vec<f64> = bytes[header..]
+            .chunks_exact(8)
+            .map(|b| f64::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        assert_eq!(elements.len(), 2 * 16);
+        // Qubit 1 is |1> and qubit 0 is maximally mixed, so the reduced state
+        // is diag(0, 0, 1/2, 1/2) with qubit 1 as the most significant bit.
+        for row in 0..4 {
+            for col in 0..4 {
+                let expected = if row == col && row >= 2 { 0.5 } else { 0.0 };
+                let index = 2 * (row * 4 + col);
+                approx::assert_relative_eq!(elements[index], expected, epsilon = 1e-12);
+                approx::assert_relative_eq!(elements[index + 1], 0.0, epsilon = 1e-12);
+            }
+        }
+        assert!(sim.dump_state(&file, &[3]).is_err());
+    }
+}
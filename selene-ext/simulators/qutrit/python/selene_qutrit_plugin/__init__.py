@@ -0,0 +1,3 @@
+from .plugin import QutritPlugin
+
+__all__ = ["QutritPlugin"]
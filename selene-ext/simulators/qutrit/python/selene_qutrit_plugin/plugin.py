@@ -0,0 +1,48 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+
+from selene_core import Simulator
+
+
+@dataclass
+class QutritPlugin(Simulator):
+    """
+    A plugin for using a leakage-aware qutrit simulator as the backend
+    simulator for selene.
+
+    Each qubit is represented as a qutrit with an explicit leaked level |2>.
+    Gates act only on the computational subspace, and error models can drive
+    coherent leakage and seepage between |1> and |2>. Leaked measurements
+    report 2 for a qubit found in the leaked level.
+
+    The state is stored densely, so memory grows as 3^n in the number of
+    qubits.
+
+    Attributes:
+        leak_measurement_bias (float): The probability that a standard
+                                       measurement of a leaked qubit reports 1.
+    """
+
+    leak_measurement_bias: float = 0.5
+
+    def __post_init__(self):
+        assert 0 <= self.leak_measurement_bias <= 1, (
+            "leak_measurement_bias must be between 0 and 1"
+        )
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_qutrit_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_qutrit_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_qutrit_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    def get_init_args(self):
+        return [f"--leak-measurement-bias={self.leak_measurement_bias}"]
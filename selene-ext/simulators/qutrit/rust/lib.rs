@@ -0,0 +1,310 @@
+/// A leakage-aware qutrit simulator plugin for Selene.
+//
+// Each qubit is represented as a qutrit, with the usual computational levels
+// |0> and |1> and an explicit leaked level |2>. The standard gates act only on
+// the computational subspace: RXY and RZ act on levels 0 and 1 of a qutrit and
+// leave |2> untouched, and RZZ only applies a phase when both qutrits are in
+// the computational subspace. Gate definitions and the qubit ordering match the
+// QuEST plugin on that subspace.
+//
+// Transitions out of and into the computational subspace are driven by
+// leakage_rxy, which applies the RXY rotation to levels 1 and 2. Error models
+// use this for coherent leakage (|1> to |2>) and seepage (|2> to |1>), so a
+// leaked qubit no longer keeps evolving as if nothing had happened.
+//
+// measure_leaked distinguishes all three levels and returns 2 for a leaked
+// qubit. A standard measurement also collapses the qutrit, and reports a
+// leaked qubit as 1 with probability `leak_measurement_bias`, leaving it in
+// |2>. Reset returns the qutrit to |0> from any level.
+use anyhow::{Result, bail};
+use clap::Parser;
+use num_complex::Complex64;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use selene_core::export_simulator_plugin;
+use selene_core::simulator::SimulatorInterface;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::utils::MetricValue;
+
+mod state;
+use state::QutritState;
+
+#[cfg(test)]
+mod tests;
+
+const LEAKED: usize = 2;
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// The probability that a standard measurement of a leaked qubit reports 1.
+    #[arg(long, default_value_t = 0.5)]
+    leak_measurement_bias: f64,
+}
+
+pub struct QutritSimulator {
+    state: QutritState,
+    n_qubits: u64,
+    rng: Pcg64Mcg,
+    leak_measurement_bias: f64,
+    leaked_measurements: u64,
+    cumulative_postselect_probability: f64,
+}
+
+/// The RXY rotation on a two-level subspace.
+fn rxy_matrix(theta: f64, phi: f64) -> [[Complex64; 2]; 2] {
+    // ⎡ cos(θ/2)            -i⋅e^{-iφ}⋅sin(θ/2) ⎤
+    // ⎣ -i⋅e^{iφ}⋅sin(θ/2)   cos(θ/2)           ⎦
+    let cos = Complex64::new((theta / 2.0).cos(), 0.0);
+    let sin = (theta / 2.0).sin();
+    let upper = Complex64::new(0.0, -sin) * Complex64::from_polar(1.0, -phi);
+    let lower = Complex64::new(0.0, -sin) * Complex64::from_polar(1.0, phi);
+    [[cos, upper], [lower, cos]]
+}
+
+impl QutritSimulator {
+    /// Measure all three levels of qutrit `q0`, collapsing onto the outcome.
+    fn measure_level(&mut self, q0: u64) -> usize {
+        let probabilities = self.state.level_probabilities(q0);
+        let total: f64 = probabilities.iter().sum();
+        let mut sample = self.rng.random::<f64>() * total;
+        let mut level = LEAKED;
+        for (l, &p) in probabilities.iter().enumerate() {
+            if sample < p {
+                level = l;
+                break;
+            }
+            sample -= p;
+        }
+        // Guard against rounding leaving us on a level with no population.
+        while probabilities[level] == 0.0 {
+            level -= 1;
+        }
+        self.state
+            .project(q0, level, (total / probabilities[level]).sqrt());
+        if level == LEAKED {
+            self.leaked_measurements += 1;
+        }
+        level
+    }
+}
+
+impl SimulatorInterface for QutritSimulator {
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn shot_start(&mut self, _shot_id: u64, seed: u64) -> Result<()> {
+        self.state.initialise();
+        self.rng = Pcg64Mcg::seed_from_u64(seed);
+        self.leaked_measurements = 0;
+        self.cumulative_postselect_probability = 1.0;
+        Ok(())
+    }
+
+    fn shot_end(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn rz(&mut self, q0: u64, theta: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "RZ(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        // diag(e^{-iθ/2}, e^{iθ/2}) on the computational subspace
+        let phase = Complex64::from_polar(1.0, theta / 2.0);
+        self.state
+            .apply_diagonal(q0, [phase.conj(), phase, Complex64::new(1.0, 0.0)]);
+        Ok(())
+    }
+
+    fn rxy(&mut self, q0: u64, theta: f64, phi: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "RXY(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        self.state.apply_matrix(q0, (0, 1), rxy_matrix(theta, phi));
+        Ok(())
+    }
+
+    fn rzz(&mut self, q0: u64, q1: u64, theta: f64) -> Result<()> {
+        if q0 >= self.n_qubits || q1 >= self.n_qubits {
+            bail!(
+                "RZZ(q0={q0}, q1={q1}) is out of bounds. q0 and q1 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        if q0 == q1 {
+            bail!("RZZ(q0={q0}, q1={q1}) requires two distinct qubits.");
+        }
+        // diag(1, e^{iθ}, e^{iθ}, 1) on the computational subspace, matching the
+        // QuEST plugin, and the identity whenever either qutrit is leaked.
+        let one = Complex64::new(1.0, 0.0);
+        let phase = Complex64::from_polar(1.0, theta);
+        self.state
+            .apply_diagonal_2q(q0, q1, [[one, phase, one], [phase, one, one], [one; 3]]);
+        Ok(())
+    }
+
+    fn measure(&mut self, q0: u64) -> Result<bool> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Measure(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        Ok(match self.measure_level(q0) {
+            LEAKED => self.rng.random_bool(self.leak_measurement_bias),
+            level => level == 1,
+        })
+    }
+
+    fn measure_leaked(&mut self, q0: u64) -> Result<u64> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "MeasureLeaked(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        Ok(self.measure_level(q0) as u64)
+    }
+
+    fn leakage_rxy(&mut self, q0: u64, theta: f64, phi: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "LeakageRXY(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        self.state
+            .apply_matrix(q0, (1, LEAKED), rxy_matrix(theta, phi));
+        Ok(())
+    }
+
+    fn postselect(&mut self, q0: u64, target_value: bool) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Postselect(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        let probabilities = self.state.level_probabilities(q0);
+        let total: f64 = probabilities.iter().sum();
+        let postselect_probability = probabilities[target_value as usize] / total;
+        self.cumulative_postselect_probability *= postselect_probability;
+        if postselect_probability < 1e-10 {
+            bail!(
+                "Postselection of {} on qubit {q0} is too unlikely to postselect. The probability of this outcome is {postselect_probability:.2e}.",
+                target_value as u8
+            );
+        }
+        self.state.project(
+            q0,
+            target_value as usize,
+            1.0 / postselect_probability.sqrt(),
+        );
+        Ok(())
+    }
+
+    fn reset(&mut self, q0: u64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Reset(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        let level = self.measure_level(q0);
+        self.state.relabel(q0, level, 0);
+        Ok(())
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "leaked_population".to_string(),
+                MetricValue::F64(
+                    (0..self.n_qubits)
+                        .map(|q| self.state.level_probabilities(q)[LEAKED])
+                        .sum(),
+                ),
+            ))),
+            1 => Ok(Some((
+                "leaked_measurements".to_string(),
+                MetricValue::U64(self.leaked_measurements),
+            ))),
+            2 => Ok(Some((
+                "cumulative_postselect_probability".to_string(),
+                MetricValue::F64(self.cumulative_postselect_probability),
+            ))),
+            _ => Ok(None),
+        }
+    }
+}
+
+fn check_memory(n_qubits: u64) -> Result<()> {
+    if n_qubits == 0 {
+        bail!("Number of qubits must be greater than 0");
+    } else if n_qubits > 36 {
+        bail!(
+            "It is impossible to describe more than 36 qutrits in a dense state on a computer with a 64-bit address space."
+        );
+    }
+    let bytes_required = bytesize::ByteSize::b(16 * 3u64.pow(n_qubits as u32));
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    let reported_available = system.available_memory();
+    if reported_available == 0 {
+        eprintln!("-----------------------------------");
+        eprintln!("Unable to determine available memory due to system limitations.");
+        eprintln!("The qutrit simulator is going to try to allocate {bytes_required}");
+        eprintln!("of memory to store the state, and this will be multiplied by");
+        eprintln!("the number of processes if running in multiprocessing mode.");
+        eprintln!();
+        eprintln!("If this fails, verify that your system has sufficient memory.");
+        eprintln!("-----------------------------------");
+    } else {
+        let bytes_available = bytesize::ByteSize::b(reported_available);
+        if bytes_required > bytes_available {
+            bail!(
+                "Insufficient memory available ({bytes_available}) to allocate a qutrit state of {n_qubits} qubits ({bytes_required}).",
+            );
+        }
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct QutritSimulatorFactory;
+
+impl SimulatorInterfaceFactory for QutritSimulatorFactory {
+    type Interface = QutritSimulator;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        let args: Vec<String> = args.iter().map(|s| s.as_ref().to_string()).collect();
+        let params = Params::try_parse_from(&args)?;
+        check_memory(n_qubits)?;
+        if !(0.0..=1.0).contains(&params.leak_measurement_bias) {
+            bail!(
+                "leak_measurement_bias must be between 0 and 1, got {}",
+                params.leak_measurement_bias
+            );
+        }
+        Ok(Box::new(QutritSimulator {
+            state: QutritState::new(n_qubits),
+            n_qubits,
+            rng: Pcg64Mcg::seed_from_u64(0),
+            leak_measurement_bias: params.leak_measurement_bias,
+            leaked_measurements: 0,
+            cumulative_postselect_probability: 1.0,
+        }))
+    }
+}
+
+export_simulator_plugin!(crate::QutritSimulatorFactory);
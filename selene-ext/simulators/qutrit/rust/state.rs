@@ -0,0 +1,105 @@
+//! A dense state of qutrits, with levels |0>, |1> and |2>.
+//!
+//! Amplitudes are stored in base 3, with qutrit 0 as the least significant
+//! digit, so the amplitude of |l_{n-1} ... l_1 l_0> is found at index
+//! Σ_k l_k 3^k.
+use num_complex::Complex64;
+
+pub struct QutritState {
+    strides: Vec<usize>,
+    amplitudes: Vec<Complex64>,
+}
+
+impl QutritState {
+    pub fn new(n_qutrits: u64) -> Self {
+        let strides: Vec<usize> = (0..n_qutrits as u32).map(|k| 3usize.pow(k)).collect();
+        let size = 3usize.pow(n_qutrits as u32);
+        let mut result = Self {
+            strides,
+            amplitudes: vec![Complex64::new(0.0, 0.0); size],
+        };
+        result.initialise();
+        result
+    }
+
+    /// Return the register to |0...0>.
+    pub fn initialise(&mut self) {
+        self.amplitudes.fill(Complex64::new(0.0, 0.0));
+        self.amplitudes[0] = Complex64::new(1.0, 0.0);
+    }
+
+    fn level(&self, index: usize, q: u64) -> usize {
+        (index / self.strides[q as usize]) % 3
+    }
+
+    /// The indices of all basis states in which qutrit `q` is in |0>.
+    fn bases(&self, q: u64) -> impl Iterator<Item = usize> + '_ {
+        (0..self.amplitudes.len()).filter(move |&i| self.level(i, q) == 0)
+    }
+
+    /// Apply a 2x2 unitary to the two-dimensional subspace spanned by levels
+    /// `a` and `b` of qutrit `q`, leaving the remaining level untouched.
+    pub fn apply_matrix(&mut self, q: u64, (a, b): (usize, usize), matrix: [[Complex64; 2]; 2]) {
+        let stride = self.strides[q as usize];
+        let bases: Vec<usize> = self.bases(q).collect();
+        for base in bases {
+            let (ia, ib) = (base + a * stride, base + b * stride);
+            let (va, vb) = (self.amplitudes[ia], self.amplitudes[ib]);
+            self.amplitudes[ia] = matrix[0][0] * va + matrix[0][1] * vb;
+            self.amplitudes[ib] = matrix[1][0] * va + matrix[1][1] * vb;
+        }
+    }
+
+    /// Multiply each amplitude by the phase corresponding to the level of
+    /// qutrit `q`.
+    pub fn apply_diagonal(&mut self, q: u64, phases: [Complex64; 3]) {
+        for i in 0..self.amplitudes.len() {
+            let level = self.level(i, q);
+            self.amplitudes[i] *= phases[level];
+        }
+    }
+
+    /// Multiply each amplitude by the phase corresponding to the levels of
+    /// qutrits `q0` and `q1`, indexed as `phases[level(q0)][level(q1)]`.
+    pub fn apply_diagonal_2q(&mut self, q0: u64, q1: u64, phases: [[Complex64; 3]; 3]) {
+        for i in 0..self.amplitudes.len() {
+            let (l0, l1) = (self.level(i, q0), self.level(i, q1));
+            self.amplitudes[i] *= phases[l0][l1];
+        }
+    }
+
+    /// The probability of finding qutrit `q` in each of its levels.
+    pub fn level_probabilities(&self, q: u64) -> [f64; 3] {
+        let mut result = [0.0; 3];
+        for (i, amplitude) in self.amplitudes.iter().enumerate() {
+            result[self.level(i, q)] += amplitude.norm_sqr();
+        }
+        result
+    }
+
+    /// Project qutrit `q` onto `level`, scaling the remaining amplitudes by
+    /// `scale` to restore normalisation.
+    pub fn project(&mut self, q: u64, level: usize, scale: f64) {
+        for i in 0..self.amplitudes.len() {
+            if self.level(i, q) == level {
+                self.amplitudes[i] *= scale;
+            } else {
+                self.amplitudes[i] = Complex64::new(0.0, 0.0);
+            }
+        }
+    }
+
+    /// Move the population of qutrit `q` from level `from` to level `to`. The
+    /// qutrit must already have been projected onto `from`.
+    pub fn relabel(&mut self, q: u64, from: usize, to: usize) {
+        if from == to {
+            return;
+        }
+        let stride = self.strides[q as usize];
+        let bases: Vec<usize> = self.bases(q).collect();
+        for base in bases {
+            self.amplitudes
+                .swap(base + from * stride, base + to * stride);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::QutritSimulatorFactory;
+use selene_core::simulator::SimulatorInterface;
+use selene_core::simulator::conformance_testing::run_basic_tests;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// A CNOT up to a Z on the control, which is sufficient for preparing
+/// computational basis correlations.
+fn cx(sim: &mut impl SimulatorInterface, control: u64, target: u64) {
+    sim.rxy(target, PI / 2.0, PI / 2.0).unwrap();
+    sim.rz(control, PI / 2.0).unwrap();
+    sim.rz(target, PI / 2.0).unwrap();
+    sim.rzz(control, target, -PI / 2.0).unwrap();
+    sim.rxy(target, -PI / 2.0, PI / 2.0).unwrap();
+}
+
+#[test]
+fn basic_conformance_test() {
+    let interface = Arc::new(QutritSimulatorFactory);
+    let args = vec![];
+    run_basic_tests(interface, args);
+}
+
+#[test]
+fn leaked_qubit_is_reported_by_measure_leaked() {
+    let mut sim = Arc::new(QutritSimulatorFactory).init(2, &[""]).unwrap();
+    for shot in 0..10 {
+        sim.shot_start(shot, shot).unwrap();
+        sim.rxy(0, PI, 0.0).unwrap();
+        sim.leakage_rxy(0, PI, 0.0).unwrap();
+        // Gates on the computational subspace leave the leaked level alone.
+        sim.rxy(0, PI / 2.0, 0.3).unwrap();
+        sim.rz(0, 0.7).unwrap();
+        sim.rzz(0, 1, 1.1).unwrap();
+        assert_eq!(sim.measure_leaked(0).unwrap(), 2);
+        assert_eq!(sim.measure_leaked(1).unwrap(), 0);
+        sim.reset(0).unwrap();
+        assert_eq!(sim.measure_leaked(0).unwrap(), 0);
+    }
+}
+
+#[test]
+fn seepage_returns_to_computational_subspace() {
+    let mut sim = Arc::new(QutritSimulatorFactory).init(1, &[""]).unwrap();
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(0, PI, 0.0).unwrap();
+    sim.leakage_rxy(0, PI, 0.0).unwrap();
+    sim.leakage_rxy(0, PI, 0.0).unwrap();
+    assert_eq!(sim.measure_leaked(0).unwrap(), 1);
+}
+
+#[test]
+fn partial_leakage_statistics() {
+    let n_shots = 2000;
+    let mut sim = Arc::new(QutritSimulatorFactory)
+        .init(2, &["", "--leak-measurement-bias=1"])
+        .unwrap();
+    let mut leaked = 0;
+    for shot in 0..n_shots {
+        sim.shot_start(shot, shot).unwrap();
+        sim.rxy(0, PI, 0.0).unwrap();
+        cx(&mut *sim, 0, 1);
+        // Leak a quarter of the |1> population of qubit 0.
+        sim.leakage_rxy(0, PI / 3.0, 0.0).unwrap();
+        let outcome = sim.measure_leaked(0).unwrap();
+        if outcome == 2 {
+            leaked += 1;
+        } else {
+            assert_eq!(outcome, 1);
+        }
+        // Leakage does not disturb the correlation with the partner qubit.
+        assert!(sim.measure(1).unwrap());
+    }
+    approx::assert_relative_eq!(leaked as f64 / n_shots as f64, 0.25, epsilon = 0.03);
+}
+
+#[test]
+fn standard_measurement_of_leaked_qubit_uses_bias() {
+    let mut sim = Arc::new(QutritSimulatorFactory)
+        .init(1, &["", "--leak-measurement-bias=0"])
+        .unwrap();
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(0, PI, 0.0).unwrap();
+    sim.leakage_rxy(0, PI, 0.0).unwrap();
+    assert!(!sim.measure(0).unwrap());
+    // The measurement leaves the qubit leaked.
+    assert_eq!(sim.measure_leaked(0).unwrap(), 2);
+    assert_eq!(sim.leaked_measurements, 2);
+}
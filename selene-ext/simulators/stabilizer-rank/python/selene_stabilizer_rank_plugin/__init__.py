@@ -0,0 +1,3 @@
+from .plugin import StabilizerRankPlugin
+
+__all__ = ["StabilizerRankPlugin"]
@@ -0,0 +1,59 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+
+from selene_core import Simulator
+
+
+@dataclass
+class StabilizerRankPlugin(Simulator):
+    """
+    A plugin for simulating near-Clifford circuits by stabilizer-rank
+    decomposition.
+
+    Clifford operations are simulated at a cost comparable to a stabilizer
+    simulator such as Stim. Unlike Stim, rotations by non-Clifford angles are
+    also supported and are simulated exactly, but each one can double the
+    number of stabilizer states in the decomposition of the quantum state, so
+    this simulator is only suitable for circuits with a small number of
+    non-Clifford rotations.
+
+    The number of non-Clifford rotations and the size of the decomposition are
+    reported as the `non_clifford_gates`, `decomposition_size` and
+    `max_decomposition_size` simulator metrics.
+
+    Attributes:
+        angle_threshold (float): Angles within this distance of a multiple of
+                                 π/2 are treated as Clifford rotations.
+        max_decomposition_size (int): The largest number of stabilizer states
+                                      permitted in the decomposition. If it is
+                                      exceeded, the shot fails with an error.
+    """
+
+    angle_threshold: float = 1e-9
+    max_decomposition_size: int = 1 << 20
+
+    def __post_init__(self):
+        assert self.angle_threshold >= 0, "angle_threshold must be non-negative"
+        assert self.max_decomposition_size >= 1, (
+            "max_decomposition_size must be at least 1"
+        )
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_stabilizer_rank_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_stabilizer_rank_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_stabilizer_rank_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    def get_init_args(self):
+        return [
+            f"--angle-threshold={self.angle_threshold}",
+            f"--max-decomposition-size={self.max_decomposition_size}",
+        ]
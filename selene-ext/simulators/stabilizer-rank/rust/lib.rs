@@ -0,0 +1,336 @@
+/// A stabilizer-rank simulator plugin for Selene, for near-Clifford circuits.
+//
+// The state is stored as a sum of stabilizer states sharing a common Clifford
+// frame (see state.rs). Clifford operations only update the frame, so circuits
+// that are mostly Clifford are simulated at a cost close to that of a tableau
+// simulator. Each non-Clifford rotation at most doubles the number of terms in
+// the sum, so the cost grows exponentially in the number of non-Clifford
+// rotations rather than in the number of qubits. Measurements never increase
+// the number of terms, and often reduce it.
+//
+// Angles within `angle_threshold` of a multiple of π/2 are snapped to the
+// corresponding Clifford operation, as in the stim plugin. All other angles are
+// applied exactly. The number of non-Clifford rotations and the size of the
+// decomposition are reported as metrics. If the decomposition grows beyond
+// `max_decomposition_size` terms, the shot fails rather than exhausting memory.
+//
+// Gate definitions and the qubit ordering match the QuEST plugin, up to global
+// phase.
+use anyhow::{Result, bail};
+use clap::Parser;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use selene_core::export_simulator_plugin;
+use selene_core::simulator::SimulatorInterface;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::utils::MetricValue;
+
+mod pauli;
+mod state;
+use pauli::PauliString;
+use state::StabilizerSum;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// Angles within this distance of a multiple of π/2 are treated as
+    /// Clifford rotations.
+    #[arg(long, default_value_t = 1e-9)]
+    angle_threshold: f64,
+    /// The largest number of stabilizer states permitted in the decomposition
+    /// before the shot is aborted.
+    #[arg(long, default_value_t = 1 << 20)]
+    max_decomposition_size: u64,
+}
+
+pub struct StabilizerRankSimulator {
+    state: StabilizerSum,
+    n_qubits: u64,
+    rng: Pcg64Mcg,
+    angle_threshold_quad: f64,
+    max_decomposition_size: u64,
+    non_clifford_gates: u64,
+    max_decomposition_size_reached: u64,
+    cumulative_postselect_probability: f64,
+}
+
+impl StabilizerRankSimulator {
+    /// The number of quarter turns that `theta` is equivalent to, modulo 4, if
+    /// it is within the angle threshold of a multiple of π/2.
+    fn get_approximate_quadrant(&self, theta: f64) -> Option<u8> {
+        let quadrant_float = theta / std::f64::consts::FRAC_PI_2;
+        let quadrant_rounded = quadrant_float.round();
+        if (quadrant_float - quadrant_rounded).abs() > self.angle_threshold_quad {
+            return None;
+        }
+        Some((quadrant_rounded as i64).rem_euclid(4) as u8)
+    }
+
+    fn s_power(&mut self, q0: usize, quadrant: u8) {
+        match quadrant {
+            0 => (),
+            1 => self.state.s(q0),
+            2 => {
+                self.state.s(q0);
+                self.state.s(q0);
+            }
+            _ => self.state.s_dag(q0),
+        }
+    }
+
+    /// Apply exp(-iθP/2) for a non-Clifford angle θ, where `image` is the image
+    /// of P in the current frame.
+    fn rotate_non_clifford(&mut self, image: &PauliString, theta: f64) -> Result<()> {
+        self.state.rotate(image, theta);
+        self.non_clifford_gates += 1;
+        let size = self.state.decomposition_size() as u64;
+        self.max_decomposition_size_reached = self.max_decomposition_size_reached.max(size);
+        if size > self.max_decomposition_size {
+            bail!(
+                "The stabilizer decomposition has grown to {size} terms after {} non-Clifford rotations, exceeding the maximum of {}.",
+                self.non_clifford_gates,
+                self.max_decomposition_size
+            );
+        }
+        Ok(())
+    }
+
+    fn rotate_z(&mut self, q0: usize, theta: f64) -> Result<()> {
+        match self.get_approximate_quadrant(theta) {
+            Some(quadrant) => {
+                self.s_power(q0, quadrant);
+                Ok(())
+            }
+            None => self.rotate_non_clifford(&self.state.z_image(q0), theta),
+        }
+    }
+
+    fn rotate_x(&mut self, q0: usize, theta: f64) -> Result<()> {
+        match self.get_approximate_quadrant(theta) {
+            Some(quadrant) => {
+                self.state.h(q0);
+                self.s_power(q0, quadrant);
+                self.state.h(q0);
+                Ok(())
+            }
+            None => self.rotate_non_clifford(&self.state.x_image(q0), theta),
+        }
+    }
+
+    fn rotate_y(&mut self, q0: usize, theta: f64) -> Result<()> {
+        match self.get_approximate_quadrant(theta) {
+            Some(quadrant) => {
+                // Ry = S Rx S†
+                self.state.s_dag(q0);
+                self.state.h(q0);
+                self.s_power(q0, quadrant);
+                self.state.h(q0);
+                self.state.s(q0);
+                Ok(())
+            }
+            None => {
+                // Y = iXZ
+                let mut image = self.state.x_image(q0).multiply(&self.state.z_image(q0));
+                image.scale(1);
+                self.rotate_non_clifford(&image, theta)
+            }
+        }
+    }
+
+    fn measure_unchecked(&mut self, q0: u64) -> bool {
+        let sample = self.rng.random::<f64>();
+        let (outcome, _) = self.state.measure(q0 as usize, |p1| sample < p1);
+        outcome
+    }
+}
+
+impl SimulatorInterface for StabilizerRankSimulator {
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn shot_start(&mut self, _shot_id: u64, seed: u64) -> Result<()> {
+        self.state.initialise();
+        self.rng = Pcg64Mcg::seed_from_u64(seed);
+        self.non_clifford_gates = 0;
+        self.max_decomposition_size_reached = 1;
+        self.cumulative_postselect_probability = 1.0;
+        Ok(())
+    }
+
+    fn shot_end(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn rz(&mut self, q0: u64, theta: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "RZ(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        self.rotate_z(q0 as usize, theta)
+    }
+
+    fn rxy(&mut self, q0: u64, theta: f64, phi: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "RXY(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        let q = q0 as usize;
+        if self.get_approximate_quadrant(theta) == Some(0) {
+            return Ok(());
+        }
+        // The rotation axis is cos(φ)X + sin(φ)Y, which is a Pauli (up to sign)
+        // when φ is a multiple of π/2.
+        match self.get_approximate_quadrant(phi) {
+            Some(0) => return self.rotate_x(q, theta),
+            Some(1) => return self.rotate_y(q, theta),
+            Some(2) => return self.rotate_x(q, -theta),
+            Some(3) => return self.rotate_y(q, -theta),
+            _ => (),
+        }
+        if self.get_approximate_quadrant(theta) == Some(2) {
+            // RXY(π, φ) = X RZ(-2φ) up to global phase
+            self.rotate_z(q, -2.0 * phi)?;
+            self.state.x(q);
+            return Ok(());
+        }
+        // RXY(θ, φ) = RZ(φ) RX(θ) RZ(-φ)
+        self.rotate_z(q, -phi)?;
+        self.rotate_x(q, theta)?;
+        self.rotate_z(q, phi)
+    }
+
+    fn rzz(&mut self, q0: u64, q1: u64, theta: f64) -> Result<()> {
+        if q0 >= self.n_qubits || q1 >= self.n_qubits {
+            bail!(
+                "RZZ(q0={q0}, q1={q1}) is out of bounds. q0 and q1 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        if q0 == q1 {
+            bail!("RZZ(q0={q0}, q1={q1}) requires two distinct qubits.");
+        }
+        let (a, b) = (q0 as usize, q1 as usize);
+        match self.get_approximate_quadrant(theta) {
+            Some(quadrant) => {
+                self.state.cx(a, b);
+                self.s_power(b, quadrant);
+                self.state.cx(a, b);
+                Ok(())
+            }
+            None => {
+                let image = self.state.z_image(a).multiply(&self.state.z_image(b));
+                self.rotate_non_clifford(&image, theta)
+            }
+        }
+    }
+
+    fn measure(&mut self, q0: u64) -> Result<bool> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Measure(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        Ok(self.measure_unchecked(q0))
+    }
+
+    fn postselect(&mut self, q0: u64, target_value: bool) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Postselect(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        let (_, postselect_probability) = self.state.measure(q0 as usize, |_| target_value);
+        self.cumulative_postselect_probability *= postselect_probability;
+        if postselect_probability < 1e-10 {
+            bail!(
+                "Postselection of {} on qubit {q0} is too unlikely to postselect. The probability of this outcome is {postselect_probability:.2e}.",
+                target_value as u8
+            );
+        }
+        Ok(())
+    }
+
+    fn reset(&mut self, q0: u64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Reset(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        if self.measure_unchecked(q0) {
+            self.state.x(q0 as usize);
+        }
+        Ok(())
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "non_clifford_gates".to_string(),
+                MetricValue::U64(self.non_clifford_gates),
+            ))),
+            1 => Ok(Some((
+                "decomposition_size".to_string(),
+                MetricValue::U64(self.state.decomposition_size() as u64),
+            ))),
+            2 => Ok(Some((
+                "max_decomposition_size".to_string(),
+                MetricValue::U64(self.max_decomposition_size_reached),
+            ))),
+            3 => Ok(Some((
+                "cumulative_postselect_probability".to_string(),
+                MetricValue::F64(self.cumulative_postselect_probability),
+            ))),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct StabilizerRankSimulatorFactory;
+
+impl SimulatorInterfaceFactory for StabilizerRankSimulatorFactory {
+    type Interface = StabilizerRankSimulator;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        let args: Vec<String> = args.iter().map(|s| s.as_ref().to_string()).collect();
+        let params = Params::try_parse_from(&args)?;
+        if n_qubits == 0 {
+            bail!("Number of qubits must be greater than 0");
+        }
+        if params.angle_threshold < 0.0 {
+            bail!(
+                "angle_threshold must be non-negative, got {}",
+                params.angle_threshold
+            );
+        }
+        if params.max_decomposition_size == 0 {
+            bail!("max_decomposition_size must be at least 1");
+        }
+        Ok(Box::new(StabilizerRankSimulator {
+            state: StabilizerSum::new(n_qubits),
+            n_qubits,
+            rng: Pcg64Mcg::seed_from_u64(0),
+            angle_threshold_quad: params.angle_threshold / std::f64::consts::FRAC_PI_2,
+            max_decomposition_size: params.max_decomposition_size,
+            non_clifford_gates: 0,
+            max_decomposition_size_reached: 1,
+            cumulative_postselect_probability: 1.0,
+        }))
+    }
+}
+
+export_simulator_plugin!(crate::StabilizerRankSimulatorFactory);
@@ -0,0 +1,151 @@
+//! Bit-packed Pauli strings with phases.
+//!
+//! A Pauli string is stored as i^phase · X^x · Z^z, where x and z are bit
+//! vectors over the qubits and all X factors are ordered before all Z factors.
+
+/// A bit vector over the qubits of the register, packed into 64-bit words.
+pub type BitString = Vec<u64>;
+
+pub fn words_for(n_qubits: u64) -> usize {
+    (n_qubits as usize).div_ceil(64)
+}
+
+pub fn get_bit(bits: &[u64], qubit: usize) -> bool {
+    (bits[qubit / 64] >> (qubit % 64)) & 1 == 1
+}
+
+pub fn set_bit(bits: &mut [u64], qubit: usize, value: bool) {
+    let mask = 1u64 << (qubit % 64);
+    if value {
+        bits[qubit / 64] |= mask;
+    } else {
+        bits[qubit / 64] &= !mask;
+    }
+}
+
+pub fn flip_bit(bits: &mut [u64], qubit: usize) {
+    bits[qubit / 64] ^= 1u64 << (qubit % 64);
+}
+
+/// The parity of the bitwise AND of two bit strings.
+pub fn and_parity(a: &[u64], b: &[u64]) -> bool {
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| (x & y).count_ones())
+        .sum::<u32>()
+        % 2
+        == 1
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PauliString {
+    pub x: BitString,
+    pub z: BitString,
+    /// The power of i multiplying the string, modulo 4.
+    pub phase: u8,
+}
+
+impl PauliString {
+    pub fn identity(n_qubits: u64) -> Self {
+        let words = words_for(n_qubits);
+        Self {
+            x: vec![0; words],
+            z: vec![0; words],
+            phase: 0,
+        }
+    }
+
+    pub fn single_x(n_qubits: u64, qubit: usize) -> Self {
+        let mut result = Self::identity(n_qubits);
+        set_bit(&mut result.x, qubit, true);
+        result
+    }
+
+    pub fn single_z(n_qubits: u64, qubit: usize) -> Self {
+        let mut result = Self::identity(n_qubits);
+        set_bit(&mut result.z, qubit, true);
+        result
+    }
+
+    pub fn has_x(&self) -> bool {
+        self.x.iter().any(|&w| w != 0)
+    }
+
+    /// Multiply by i^power.
+    pub fn scale(&mut self, power: u8) {
+        self.phase = (self.phase + power) % 4;
+    }
+
+    /// The product self · other.
+    pub fn multiply(&self, other: &Self) -> Self {
+        // X^x1 Z^z1 X^x2 Z^z2 = (-1)^(z1·x2) X^(x1^x2) Z^(z1^z2)
+        let sign = if and_parity(&self.z, &other.x) { 2 } else { 0 };
+        Self {
+            x: self
+                .x
+                .iter()
+                .zip(other.x.iter())
+                .map(|(a, b)| a ^ b)
+                .collect(),
+            z: self
+                .z
+                .iter()
+                .zip(other.z.iter())
+                .map(|(a, b)| a ^ b)
+                .collect(),
+            phase: (self.phase + other.phase + sign) % 4,
+        }
+    }
+
+    /// Act on the computational basis state |basis>, returning the resulting
+    /// basis state and the power of i that multiplies it.
+    pub fn act_on(&self, basis: &[u64]) -> (BitString, u8) {
+        let sign = if and_parity(&self.z, basis) { 2 } else { 0 };
+        let flipped = basis
+            .iter()
+            .zip(self.x.iter())
+            .map(|(b, x)| b ^ x)
+            .collect();
+        (flipped, (self.phase + sign) % 4)
+    }
+
+    // The following replace P with G P G† for the named Clifford gate G.
+
+    pub fn conjugate_h(&mut self, q: usize) {
+        let (x, z) = (get_bit(&self.x, q), get_bit(&self.z, q));
+        if x && z {
+            self.scale(2);
+        }
+        set_bit(&mut self.x, q, z);
+        set_bit(&mut self.z, q, x);
+    }
+
+    pub fn conjugate_s_dag(&mut self, q: usize) {
+        if get_bit(&self.x, q) {
+            flip_bit(&mut self.z, q);
+            self.scale(3);
+        }
+    }
+
+    pub fn conjugate_cx(&mut self, control: usize, target: usize) {
+        if get_bit(&self.x, control) {
+            flip_bit(&mut self.x, target);
+        }
+        if get_bit(&self.z, target) {
+            flip_bit(&mut self.z, control);
+        }
+    }
+
+    pub fn conjugate_cz(&mut self, a: usize, b: usize) {
+        let (xa, xb) = (get_bit(&self.x, a), get_bit(&self.x, b));
+        if xa && xb {
+            self.scale(2);
+        }
+        if xb {
+            flip_bit(&mut self.z, a);
+        }
+        if xa {
+            flip_bit(&mut self.z, b);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! A quantum state represented as a weighted sum of stabilizer states that share
+//! a single Clifford frame.
+//!
+//! The state is stored as |ψ> = C Σ_x α_x |x>, where C is a Clifford operator
+//! and the sum runs over a sparse set of computational basis states. Each term
+//! C|x> is a stabilizer state, so the number of terms is the size of a
+//! stabilizer-rank decomposition of |ψ>.
+//!
+//! C is never stored directly. Instead we keep its Heisenberg images
+//! M(P) = C† P C for P in {X_k, Z_k}, from which the image of any Pauli can be
+//! formed by multiplication. This makes the operations cheap:
+//!
+//! - A Clifford gate G replaces C with G C, so M(P) becomes M(G† P G). Only the
+//!   images for the qubits G acts on change, and the coefficients are
+//!   untouched, so Clifford gates cost the same as in a tableau simulator.
+//! - A rotation exp(-iθP/2) about a Pauli P equals C exp(-iθM(P)/2) C†, so it
+//!   acts on the coefficients as cos(θ/2) - i sin(θ/2) M(P). M(P) maps each
+//!   basis state to a single basis state, so the number of terms at most
+//!   doubles, and does not grow at all when M(P) is diagonal.
+//! - Measuring Z_q amounts to measuring M(Z_q) on the coefficients. When M(Z_q)
+//!   is not diagonal, we first change frame by a Clifford W, chosen so that
+//!   W M(Z_q) W† = ±Z_j, by applying W to the coefficients and replacing C with
+//!   C W†. Measurement then filters the terms by bit j, so it never increases
+//!   the number of terms.
+use crate::pauli::{BitString, PauliString, flip_bit, get_bit, set_bit, words_for};
+use num_complex::Complex64;
+use std::collections::HashMap;
+
+/// Terms whose squared magnitude falls below this are dropped, which removes
+/// terms that cancel through interference.
+const PRUNE_THRESHOLD: f64 = 1e-24;
+
+const I: Complex64 = Complex64::new(0.0, 1.0);
+
+fn i_power(power: u8) -> Complex64 {
+    match power % 4 {
+        0 => Complex64::new(1.0, 0.0),
+        1 => I,
+        2 => Complex64::new(-1.0, 0.0),
+        _ => -I,
+    }
+}
+
+pub struct StabilizerSum {
+    n_qubits: u64,
+    x_images: Vec<PauliString>,
+    z_images: Vec<PauliString>,
+    coefficients: HashMap<BitString, Complex64>,
+}
+
+impl StabilizerSum {
+    pub fn new(n_qubits: u64) -> Self {
+        let mut result = Self {
+            n_qubits,
+            x_images: Vec::new(),
+            z_images: Vec::new(),
+            coefficients: HashMap::new(),
+        };
+        result.initialise();
+        result
+    }
+
+    /// Return the register to |0...0>.
+    pub fn initialise(&mut self) {
+        let n = self.n_qubits;
+        self.x_images = (0..n as usize)
+            .map(|q| PauliString::single_x(n, q))
+            .collect();
+        self.z_images = (0..n as usize)
+            .map(|q| PauliString::single_z(n, q))
+            .collect();
+        self.coefficients.clear();
+        self.coefficients
+            .insert(vec![0; words_for(n)], Complex64::new(1.0, 0.0));
+    }
+
+    /// The number of stabilizer states in the decomposition.
+    pub fn decomposition_size(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    // Clifford gates applied to the state, replacing C with G C.
+
+    pub fn h(&mut self, q: usize) {
+        std::mem::swap(&mut self.x_images[q], &mut self.z_images[q]);
+    }
+
+    pub fn s(&mut self, q: usize) {
+        // S† X S = -iXZ
+        let mut image = self.x_images[q].multiply(&self.z_images[q]);
+        image.scale(3);
+        self.x_images[q] = image;
+    }
+
+    pub fn s_dag(&mut self, q: usize) {
+        // S X S† = iXZ
+        let mut image = self.x_images[q].multiply(&self.z_images[q]);
+        image.scale(1);
+        self.x_images[q] = image;
+    }
+
+    pub fn x(&mut self, q: usize) {
+        self.z_images[q].scale(2);
+    }
+
+    pub fn cx(&mut self, control: usize, target: usize) {
+        // CX X_c CX = X_c X_t, CX Z_t CX = Z_c Z_t
+        self.x_images[control] = self.x_images[control].multiply(&self.x_images[target]);
+        self.z_images[target] = self.z_images[control].multiply(&self.z_images[target]);
+    }
+
+    /// The image M(X_q).
+    pub fn x_image(&self, q: usize) -> PauliString {
+        self.x_images[q].clone()
+    }
+
+    /// The image M(Z_q).
+    pub fn z_image(&self, q: usize) -> PauliString {
+        self.z_images[q].clone()
+    }
+
+    /// Apply exp(-iθP/2) for the Pauli P whose image in the frame is `image`.
+    pub fn rotate(&mut self, image: &PauliString, theta: f64) {
+        let cos = Complex64::new((theta / 2.0).cos(), 0.0);
+        let minus_i_sin = Complex64::new(0.0, -(theta / 2.0).sin());
+        let mut updated = HashMap::with_capacity(self.coefficients.len() * 2);
+        for (basis, amplitude) in self.coefficients.drain() {
+            let (target, power) = image.act_on(&basis);
+            *updated.entry(target).or_insert(Complex64::new(0.0, 0.0)) +=
+                amplitude * minus_i_sin * i_power(power);
+            *updated.entry(basis).or_insert(Complex64::new(0.0, 0.0)) += amplitude * cos;
+        }
+        updated.retain(|_, amplitude: &mut Complex64| amplitude.norm_sqr() > PRUNE_THRESHOLD);
+        self.coefficients = updated;
+    }
+
+    // Frame changes, applying G to the coefficients and replacing C with C G†.
+
+    fn conjugate_images(&mut self, f: impl Fn(&mut PauliString)) {
+        self.x_images.iter_mut().for_each(&f);
+        self.z_images.iter_mut().for_each(&f);
+    }
+
+    fn frame_cx(&mut self, control: usize, target: usize) {
+        self.coefficients = self
+            .coefficients
+            .drain()
+            .map(|(mut basis, amplitude)| {
+                if get_bit(&basis, control) {
+                    flip_bit(&mut basis, target);
+                }
+                (basis, amplitude)
+            })
+            .collect();
+        self.conjugate_images(|p| p.conjugate_cx(control, target));
+    }
+
+    fn frame_cz(&mut self, a: usize, b: usize) {
+        for (basis, amplitude) in self.coefficients.iter_mut() {
+            if get_bit(basis, a) && get_bit(basis, b) {
+                *amplitude = -*amplitude;
+            }
+        }
+        self.conjugate_images(|p| p.conjugate_cz(a, b));
+    }
+
+    fn frame_s_dag(&mut self, q: usize) {
+        for (basis, amplitude) in self.coefficients.iter_mut() {
+            if get_bit(basis, q) {
+                *amplitude *= -I;
+            }
+        }
+        self.conjugate_images(|p| p.conjugate_s_dag(q));
+    }
+
+    fn frame_h(&mut self, q: usize) {
+        let scale = std::f64::consts::FRAC_1_SQRT_2;
+        let mut updated = HashMap::with_capacity(self.coefficients.len() * 2);
+        for (basis, amplitude) in self.coefficients.drain() {
+            let sign = if get_bit(&basis, q) { -scale } else { scale };
+            let mut zero = basis.clone();
+            set_bit(&mut zero, q, false);
+            let mut one = basis;
+            set_bit(&mut one, q, true);
+            *updated.entry(zero).or_insert(Complex64::new(0.0, 0.0)) += amplitude * scale;
+            *updated.entry(one).or_insert(Complex64::new(0.0, 0.0)) += amplitude * sign;
+        }
+        updated.retain(|_, amplitude: &mut Complex64| amplitude.norm_sqr() > PRUNE_THRESHOLD);
+        self.coefficients = updated;
+        self.conjugate_images(|p| p.conjugate_h(q));
+    }
+
+    /// Change frame so that the Hermitian Pauli `image` becomes ±Z_j for some j,
+    /// updating `image` in place.
+    fn diagonalise(&mut self, image: &mut PauliString) {
+        if !image.has_x() {
+            return;
+        }
+        let n = self.n_qubits as usize;
+        let j = (0..n).find(|&q| get_bit(&image.x, q)).unwrap();
+        for q in (0..n).filter(|&q| q != j) {
+            if get_bit(&image.x, q) {
+                self.frame_cx(j, q);
+                image.conjugate_cx(j, q);
+            }
+        }
+        for q in (0..n).filter(|&q| q != j) {
+            if get_bit(&image.z, q) {
+                self.frame_cz(j, q);
+                image.conjugate_cz(j, q);
+            }
+        }
+        if get_bit(&image.z, j) {
+            // ±Y_j -> ±X_j
+            self.frame_s_dag(j);
+            image.conjugate_s_dag(j);
+        }
+        // ±X_j -> ±Z_j
+        self.frame_h(j);
+        image.conjugate_h(j);
+    }
+
+    /// Measure Z on `q`. `choose` is given the probability of the outcome being 1
+    /// and returns the outcome to collapse to. Returns the chosen outcome and its
+    /// probability.
+    pub fn measure(&mut self, q: usize, choose: impl FnOnce(f64) -> bool) -> (bool, f64) {
+        let mut image = self.z_image(q);
+        self.diagonalise(&mut image);
+        // image is now diagonal with eigenvalue i^phase (-1)^(z·x) on |x>, and
+        // an outcome of 1 corresponds to an eigenvalue of -1.
+        let is_one = |basis: &BitString| image.act_on(basis).1 == 2;
+        let p1: f64 = self
+            .coefficients
+            .iter()
+            .filter(|(basis, _)| is_one(basis))
+            .map(|(_, amplitude)| amplitude.norm_sqr())
+            .sum();
+        let total: f64 = self.coefficients.values().map(|a| a.norm_sqr()).sum();
+        let p1 = p1 / total;
+        let outcome = choose(p1);
+        let probability = if outcome { p1 } else { 1.0 - p1 };
+        if probability > 0.0 {
+            let scale = 1.0 / (probability * total).sqrt();
+            self.coefficients
+                .retain(|basis, _| is_one(basis) == outcome);
+            for amplitude in self.coefficients.values_mut() {
+                *amplitude *= scale;
+            }
+        }
+        (outcome, probability)
+    }
+}
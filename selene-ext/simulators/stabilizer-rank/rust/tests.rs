@@ -0,0 +1,98 @@
+use crate::StabilizerRankSimulatorFactory;
+use selene_core::simulator::SimulatorInterface;
+use selene_core::simulator::conformance_testing::run_basic_tests;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// A CNOT up to a Z on the control, which is sufficient for preparing
+/// computational basis correlations.
+fn cx(sim: &mut impl SimulatorInterface, control: u64, target: u64) {
+    sim.rxy(target, PI / 2.0, PI / 2.0).unwrap();
+    sim.rz(control, PI / 2.0).unwrap();
+    sim.rz(target, PI / 2.0).unwrap();
+    sim.rzz(control, target, -PI / 2.0).unwrap();
+    sim.rxy(target, -PI / 2.0, PI / 2.0).unwrap();
+}
+
+#[test]
+fn basic_conformance_test() {
+    let interface = Arc::new(StabilizerRankSimulatorFactory);
+    let args = vec![];
+    run_basic_tests(interface, args);
+}
+
+#[test]
+fn t_gate_probabilities() {
+    let mut sim = Arc::new(StabilizerRankSimulatorFactory)
+        .init(1, &[""])
+        .unwrap();
+    sim.shot_start(0, 0).unwrap();
+    // RY(π/2) T RY(-π/2) rotates |0> by π/4 away from the Z axis.
+    sim.rxy(0, PI / 2.0, PI / 2.0).unwrap();
+    sim.rz(0, PI / 4.0).unwrap();
+    sim.rxy(0, -PI / 2.0, PI / 2.0).unwrap();
+    assert_eq!(sim.non_clifford_gates, 1);
+    assert_eq!(sim.state.decomposition_size(), 2);
+    sim.postselect(0, true).unwrap();
+    approx::assert_relative_eq!(
+        sim.cumulative_postselect_probability,
+        (PI / 8.0).sin().powi(2),
+        epsilon = 1e-9
+    );
+    assert_eq!(sim.state.decomposition_size(), 1);
+}
+
+#[test]
+fn inverse_rotations_cancel() {
+    let mut sim = Arc::new(StabilizerRankSimulatorFactory)
+        .init(3, &[""])
+        .unwrap();
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(0, PI / 2.0, PI / 2.0).unwrap();
+    cx(&mut *sim, 0, 1);
+    sim.rxy(2, 0.3, 0.7).unwrap();
+    sim.rzz(1, 2, 0.4).unwrap();
+    sim.rzz(1, 2, -0.4).unwrap();
+    sim.rxy(2, -0.3, 0.7).unwrap();
+    assert!(sim.non_clifford_gates > 0);
+    assert_eq!(sim.state.decomposition_size(), 1);
+    sim.postselect(2, false).unwrap();
+    approx::assert_relative_eq!(sim.cumulative_postselect_probability, 1.0, epsilon = 1e-9);
+}
+
+#[test]
+fn non_clifford_ghz_state() {
+    let n_qubits = 100;
+    let mut sim = Arc::new(StabilizerRankSimulatorFactory)
+        .init(n_qubits, &[""])
+        .unwrap();
+    for shot in 0..10 {
+        sim.shot_start(shot, shot).unwrap();
+        sim.rxy(0, PI / 2.0, PI / 2.0).unwrap();
+        for q in 0..n_qubits - 1 {
+            cx(&mut *sim, q, q + 1);
+        }
+        // Diagonal rotations leave the computational basis correlations intact.
+        for q in 0..n_qubits {
+            sim.rz(q, 0.1 * q as f64).unwrap();
+        }
+        let first = sim.measure(0).unwrap();
+        for q in 1..n_qubits {
+            assert_eq!(sim.measure(q).unwrap(), first);
+        }
+    }
+}
+
+#[test]
+fn decomposition_limit_is_enforced() {
+    let mut sim = Arc::new(StabilizerRankSimulatorFactory)
+        .init(4, &["", "--max-decomposition-size=4"])
+        .unwrap();
+    sim.shot_start(0, 0).unwrap();
+    for q in 0..2 {
+        sim.rxy(q, PI / 4.0, 0.0).unwrap();
+    }
+    assert_eq!(sim.state.decomposition_size(), 4);
+    assert!(sim.rxy(2, PI / 4.0, 0.0).is_err());
+}
@@ -0,0 +1,3 @@
+from .plugin import StatevectorPlugin
+
+__all__ = ["StatevectorPlugin"]
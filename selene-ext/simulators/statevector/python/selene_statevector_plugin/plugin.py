@@ -0,0 +1,75 @@
+import platform
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Iterable, Iterator
+
+from hugr.qsystem.result import TaggedResult
+from selene_core import Simulator
+from selene_quest_plugin import QuestPlugin, SeleneQuestState
+
+
+@dataclass
+class StatevectorPlugin(Simulator):
+    """
+    A plugin for using a pure-Rust, multithreaded statevector simulator
+    as the backend simulator for selene.
+
+    The simulator has no native dependencies, and its state dumps use the
+    same format as the QuEST plugin, so they can be inspected with
+    `SeleneQuestState`.
+
+    Attributes:
+        threads (int): The number of threads used to update the statevector.
+                       Zero (the default) uses one thread per available core.
+                       When running shots across multiple processes, consider
+                       reducing this to avoid oversubscription.
+        dynamic_register (bool): Only hold the qubits that are currently
+                       allocated, growing and shrinking the statevector as
+                       the program allocates and frees qubits. Freed qubits
+                       are measured as they are removed. Defaults to False.
+    """
+
+    threads: int = 0
+    dynamic_register: bool = False
+
+    def __post_init__(self):
+        assert self.threads >= 0, "threads must be non-negative"
+
+    @property
+    def library_file(self):
+        libdir = Path(__file__).parent / "_dist/lib/"
+        match platform.system():
+            case "Linux":
+                return libdir / "libselene_statevector_plugin.so"
+            case "Darwin":
+                return libdir / "libselene_statevector_plugin.dylib"
+            case "Windows":
+                return libdir / "selene_statevector_plugin.dll"
+            case _:
+                raise RuntimeError(f"Unsupported platform: {platform.system()}")
+
+    def get_init_args(self):
+        args = [f"--threads={self.threads}"]
+        if self.dynamic_register:
+            args.append("--dynamic-register")
+        return args
+
+    @staticmethod
+    def extract_states_dict(
+        results: Iterable[TaggedResult],
+        cleanup: bool = True,
+    ) -> dict[str, SeleneQuestState]:
+        """Extract state results from a shot result stream and return them as a
+        dictionary keyed by the state tag. See `QuestPlugin.extract_states_dict`.
+        """
+        return QuestPlugin.extract_states_dict(results, cleanup=cleanup)
+
+    @staticmethod
+    def extract_states(
+        results: Iterable[TaggedResult],
+        cleanup: bool = True,
+    ) -> Iterator[tuple[str, SeleneQuestState]]:
+        """Extract state results from a shot result stream and return them as a
+        pair of (tag, state). See `QuestPlugin.extract_states`.
+        """
+        return QuestPlugin.extract_states(results, cleanup=cleanup)
@@ -0,0 +1,549 @@
+/// A pure-Rust statevector simulator plugin for Selene.
+//
+// Unlike the QuEST plugin, this simulator has no native dependencies, and it
+// distributes amplitude updates across a rayon thread pool once the register is
+// large enough to benefit from it. Gate definitions and the amplitude ordering
+// (qubit 0 is the least significant bit) match the QuEST plugin, and dump_state
+// writes the same `selene-quest` format, so the state utilities provided by
+// selene_quest_plugin can be used to inspect the output.
+//
+// Noise channels requested through the simulator interface are applied by
+// trajectory sampling: a single Kraus operator K is chosen with probability
+// ||K|ψ>||², and the state is replaced by the normalised K|ψ>. Averaged over
+// shots, this reproduces the action of the channel on the density matrix.
+//
+// With --dynamic-register, the statevector only holds the qubits that are
+// allocated, so memory scales with the number of qubits live at once rather
+// than with the maximum number of qubits. Qubits join the register when the
+// runtime allocates them (or when first used, for runtimes that do not send
+// allocation notifications), and are removed when freed. A freed qubit is
+// measured before removal, which leaves the remaining qubits in the same mixed
+// state as tracing it out would, averaged over shots.
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use num_complex::Complex64;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64Mcg;
+use selene_core::export_simulator_plugin;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::simulator::{
+    SimulatorInterface, kraus_operator_size, validate_kraus_operators, validate_pauli_channel,
+};
+use selene_core::utils::MetricValue;
+use std::io::Write;
+
+use selene_statevector_state::{StateVector, check_memory, check_qubit_count};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Parser, Debug)]
+struct Params {
+    /// The number of threads used to update the statevector. Zero (the default)
+    /// uses one thread per available core.
+    #[arg(long, default_value_t = 0)]
+    threads: usize,
+    /// Only hold the qubits that are currently allocated, growing and
+    /// shrinking the statevector as the program allocates and frees qubits.
+    #[arg(long, default_value_t = false)]
+    dynamic_register: bool,
+}
+
+pub struct StatevectorSimulator {
+    state: StateVector,
+    n_qubits: u64,
+    rng: Pcg64Mcg,
+    cumulative_postselect_probability: f64,
+    dynamic_register: bool,
+    // With a dynamic register, the position of each qubit in the statevector
+    // if it is live, and the qubit held at each position.
+    slots: Vec<Option<u64>>,
+    live_qubits: Vec<u64>,
+    max_register_qubits: u64,
+    // The largest register that memory has been checked for.
+    memory_checked_qubits: u64,
+}
+
+impl StatevectorSimulator {
+    fn measure_unchecked(&mut self, slot: u64) -> bool {
+        let p1 = self.state.probability_of_one(slot);
+        let outcome = self.rng.random::<f64>() < p1;
+        let probability = if outcome { p1 } else { 1.0 - p1 };
+        self.state.project(slot, outcome, 1.0 / probability.sqrt());
+        outcome
+    }
+
+    /// The position of `qubit` in the statevector, adding it to the register
+    /// in the |0> state if it is not live. Bounds must already be checked.
+    fn slot(&mut self, qubit: u64) -> Result<u64> {
+        if !self.dynamic_register {
+            return Ok(qubit);
+        }
+        if let Some(slot) = self.slots[qubit as usize] {
+            return Ok(slot);
+        }
+        let slot = self.state.n_qubits();
+        if slot == self.memory_checked_qubits {
+            check_memory(slot + 1)?;
+            self.memory_checked_qubits = slot + 1;
+        }
+        self.state.add_qubit();
+        self.max_register_qubits = self.max_register_qubits.max(slot + 1);
+        self.slots[qubit as usize] = Some(slot);
+        self.live_qubits.push(qubit);
+        Ok(slot)
+    }
+
+    fn slots(&mut self, qubits: &[u64]) -> Result<Vec<u64>> {
+        qubits.iter().map(|&q| self.slot(q)).collect()
+    }
+
+    /// The position of `qubit` in the statevector, without adding it to the
+    /// register. Bounds must already be checked.
+    fn live_slot(&self, qubit: u64) -> Result<u64> {
+        if !self.dynamic_register {
+            return Ok(qubit);
+        }
+        self.slots[qubit as usize]
+            .ok_or_else(|| anyhow!("Qubit {qubit} is not in the register, as it is not allocated"))
+    }
+}
+
+impl SimulatorInterface for StatevectorSimulator {
+    fn exit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn shot_start(&mut self, _shot_id: u64, seed: u64) -> Result<()> {
+        if self.dynamic_register {
+            self.state.clear();
+            self.slots.fill(None);
+            self.live_qubits.clear();
+            self.max_register_qubits = 0;
+        } else {
+            self.state.initialise();
+        }
+        self.rng = Pcg64Mcg::seed_from_u64(seed);
+        self.cumulative_postselect_probability = 1.0;
+        Ok(())
+    }
+
+    fn shot_end(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn rz(&mut self, q0: u64, theta: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "RZ(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        // diag(e^{-iθ/2}, e^{iθ/2}), matching QuEST's rotateZ
+        let slot = self.slot(q0)?;
+        let phase = Complex64::from_polar(1.0, theta / 2.0);
+        self.state.apply_diagonal(slot, phase.conj(), phase);
+        Ok(())
+    }
+
+    fn rxy(&mut self, q0: u64, theta: f64, phi: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "RXY(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        // ⎡ cos(θ/2)            -i⋅e^{-iφ}⋅sin(θ/2) ⎤
+        // ⎣ -i⋅e^{iφ}⋅sin(θ/2)   cos(θ/2)           ⎦
+        let cos = Complex64::new((theta / 2.0).cos(), 0.0);
+        let sin = (theta / 2.0).sin();
+        let upper = Complex64::new(0.0, -sin) * Complex64::from_polar(1.0, -phi);
+        let lower = Complex64::new(0.0, -sin) * Complex64::from_polar(1.0, phi);
+        let slot = self.slot(q0)?;
+        self.state.apply_matrix(slot, [[cos, upper], [lower, cos]]);
+        Ok(())
+    }
+
+    fn rzz(&mut self, q0: u64, q1: u64, theta: f64) -> Result<()> {
+        if q0 >= self.n_qubits || q1 >= self.n_qubits {
+            bail!(
+                "RZZ(q0={q0}, q1={q1}) is out of bounds. q0 and q1 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        // diag(1, e^{iθ}, e^{iθ}, 1), matching the QuEST plugin (which scales out
+        // a global phase of e^{iθ/2} from the symmetric definition).
+        let one = Complex64::new(1.0, 0.0);
+        let phase = Complex64::from_polar(1.0, theta);
+        let (s0, s1) = (self.slot(q0)?, self.slot(q1)?);
+        self.state
+            .apply_diagonal_2q(s0, s1, [one, phase, phase, one]);
+        Ok(())
+    }
+
+    fn u(&mut self, q0: u64, theta: f64, phi: f64, lambda: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "U(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        // ⎡ cos(θ/2)            -e^{iλ}⋅sin(θ/2)     ⎤
+        // ⎣ e^{iφ}⋅sin(θ/2)     e^{i(φ+λ)}⋅cos(θ/2) ⎦
+        let cos = (theta / 2.0).cos();
+        let sin = (theta / 2.0).sin();
+        let slot = self.slot(q0)?;
+        self.state.apply_matrix(
+            slot,
+            [
+                [
+                    Complex64::new(cos, 0.0),
+                    -Complex64::from_polar(sin, lambda),
+                ],
+                [
+                    Complex64::from_polar(sin, phi),
+                    Complex64::from_polar(cos, phi + lambda),
+                ],
+            ],
+        );
+        Ok(())
+    }
+
+    fn two_qubit_unitary(&mut self, q0: u64, q1: u64, matrix: &[f64; 32]) -> Result<()> {
+        if q0 >= self.n_qubits || q1 >= self.n_qubits {
+            bail!(
+                "TwoQubitUnitary(q0={q0}, q1={q1}) is out of bounds. q0 and q1 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        if q0 == q1 {
+            bail!("TwoQubitUnitary requires distinct qubits, but qubit {q0} was provided twice.");
+        }
+        let matrix: Vec<Complex64> = matrix
+            .chunks_exact(2)
+            .map(|v| Complex64::new(v[0], v[1]))
+            .collect();
+        let (s0, s1) = (self.slot(q0)?, self.slot(q1)?);
+        let amplitudes = self.state.transformed(&[s0, s1], &matrix);
+        self.state.replace(amplitudes, 1.0);
+        Ok(())
+    }
+
+    fn diagonal_phase(&mut self, qubits: &[u64], phases: &[f64]) -> Result<()> {
+        for (i, &q) in qubits.iter().enumerate() {
+            if q >= self.n_qubits {
+                bail!(
+                    "DiagonalPhase(q={q}) is out of bounds. q must be less than the number of qubits ({}).",
+                    self.n_qubits
+                );
+            }
+            if qubits[..i].contains(&q) {
+                bail!("DiagonalPhase requires distinct qubits, but qubit {q} was provided twice.");
+            }
+        }
+        if phases.len() != 1 << qubits.len() {
+            bail!(
+                "DiagonalPhase on {} qubits requires {} phases, got {}.",
+                qubits.len(),
+                1usize << qubits.len(),
+                phases.len()
+            );
+        }
+        let diagonal: Vec<Complex64> = phases
+            .iter()
+            .map(|&phase| Complex64::from_polar(1.0, phase))
+            .collect();
+        let slots = self.slots(qubits)?;
+        self.state.apply_diagonal_nq(&slots, &diagonal);
+        Ok(())
+    }
+
+    fn measure(&mut self, q0: u64) -> Result<bool> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Measure(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        let slot = self.slot(q0)?;
+        Ok(self.measure_unchecked(slot))
+    }
+
+    fn postselect(&mut self, q0: u64, target_value: bool) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Postselect(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        let slot = self.slot(q0)?;
+        let p1 = self.state.probability_of_one(slot);
+        let postselect_probability = if target_value { p1 } else { 1.0 - p1 };
+        self.cumulative_postselect_probability *= postselect_probability;
+        if postselect_probability < 1e-10 {
+            bail!(
+                "Postselection of {} on qubit {q0} is too unlikely to postselect. The probability of this outcome is {postselect_probability:.2e}.",
+                target_value as u8
+            );
+        }
+        self.state
+            .project(slot, target_value, 1.0 / postselect_probability.sqrt());
+        Ok(())
+    }
+
+    fn reset(&mut self, q0: u64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "Reset(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        let slot = self.slot(q0)?;
+        if self.measure_unchecked(slot) {
+            self.state.flip(slot);
+        }
+        Ok(())
+    }
+
+    fn qalloc(&mut self, q0: u64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "QAlloc(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        self.slot(q0)?;
+        Ok(())
+    }
+
+    fn qfree(&mut self, q0: u64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "QFree(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        if !self.dynamic_register {
+            return Ok(());
+        }
+        let Some(slot) = self.slots[q0 as usize].take() else {
+            return Ok(());
+        };
+        let outcome = self.measure_unchecked(slot);
+        self.state.remove_qubit(slot, outcome);
+        self.live_qubits.remove(slot as usize);
+        for &qubit in &self.live_qubits[slot as usize..] {
+            if let Some(s) = self.slots[qubit as usize].as_mut() {
+                *s -= 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn pauli_channel(&mut self, q0: u64, p_x: f64, p_y: f64, p_z: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "PauliChannel(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        validate_pauli_channel(p_x, p_y, p_z)?;
+        let slot = self.slot(q0)?;
+        let r = self.rng.random::<f64>();
+        let (zero, one, i) = (
+            Complex64::new(0.0, 0.0),
+            Complex64::new(1.0, 0.0),
+            Complex64::new(0.0, 1.0),
+        );
+        if r < p_x {
+            self.state.flip(slot);
+        } else if r < p_x + p_y {
+            self.state.apply_matrix(slot, [[zero, -i], [i, zero]]);
+        } else if r < p_x + p_y + p_z {
+            self.state.apply_diagonal(slot, one, -one);
+        }
+        Ok(())
+    }
+
+    fn amplitude_damping(&mut self, q0: u64, gamma: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "AmplitudeDamping(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        if !(0.0..=1.0).contains(&gamma) {
+            bail!("Amplitude damping gamma must be a probability in [0, 1], got {gamma}");
+        }
+        let slot = self.slot(q0)?;
+        let p1 = self.state.probability_of_one(slot);
+        let p_decay = gamma * p1;
+        if self.rng.random::<f64>() < p_decay {
+            // K1 = sqrt(γ)|0><1|
+            self.state.project(slot, true, 1.0 / p1.sqrt());
+            self.state.flip(slot);
+        } else {
+            // K0 = diag(1, sqrt(1 - γ))
+            let scale = 1.0 / (1.0 - p_decay).sqrt();
+            self.state.apply_diagonal(
+                slot,
+                Complex64::new(scale, 0.0),
+                Complex64::new(scale * (1.0 - gamma).sqrt(), 0.0),
+            );
+        }
+        Ok(())
+    }
+
+    fn phase_damping(&mut self, q0: u64, lambda: f64) -> Result<()> {
+        if q0 >= self.n_qubits {
+            bail!(
+                "PhaseDamping(q0={q0}) is out of bounds. q0 must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        if !(0.0..=1.0).contains(&lambda) {
+            bail!("Phase damping lambda must be in [0, 1], got {lambda}");
+        }
+        let slot = self.slot(q0)?;
+        let p1 = self.state.probability_of_one(slot);
+        let p_jump = lambda * p1;
+        if self.rng.random::<f64>() < p_jump {
+            // K1 = diag(0, sqrt(λ))
+            self.state.project(slot, true, 1.0 / p1.sqrt());
+        } else {
+            // K0 = diag(1, sqrt(1 - λ))
+            let scale = 1.0 / (1.0 - p_jump).sqrt();
+            self.state.apply_diagonal(
+                slot,
+                Complex64::new(scale, 0.0),
+                Complex64::new(scale * (1.0 - lambda).sqrt(), 0.0),
+            );
+        }
+        Ok(())
+    }
+
+    fn kraus_channel(&mut self, qubits: &[u64], kraus_operators: &[f64]) -> Result<()> {
+        for (i, &q) in qubits.iter().enumerate() {
+            if q >= self.n_qubits {
+                bail!(
+                    "KrausChannel(q={q}) is out of bounds. q must be less than the number of qubits ({}).",
+                    self.n_qubits
+                );
+            }
+            if qubits[..i].contains(&q) {
+                bail!("KrausChannel requires distinct qubits, but qubit {q} was provided twice.");
+            }
+        }
+        validate_kraus_operators(qubits.len(), kraus_operators)?;
+        let slots = self.slots(qubits)?;
+        let r = self.rng.random::<f64>();
+        let mut cumulative = 0.0;
+        let mut chosen = None;
+        for k in kraus_operators.chunks_exact(kraus_operator_size(qubits.len())?) {
+            let matrix: Vec<Complex64> = k
+                .chunks_exact(2)
+                .map(|v| Complex64::new(v[0], v[1]))
+                .collect();
+            let candidate = self.state.transformed(&slots, &matrix);
+            let probability = self.state.norm_sqr(&candidate);
+            if probability <= 0.0 {
+                continue;
+            }
+            cumulative += probability;
+            chosen = Some((candidate, probability));
+            // Rounding may leave the probabilities summing to slightly less
+            // than one, in which case the last possible branch is taken.
+            if r < cumulative {
+                break;
+            }
+        }
+        let Some((amplitudes, probability)) = chosen else {
+            bail!("KrausChannel annihilated the state");
+        };
+        self.state.replace(amplitudes, 1.0 / probability.sqrt());
+        Ok(())
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "cumulative_postselect_probability".to_string(),
+                MetricValue::F64(self.cumulative_postselect_probability),
+            ))),
+            1 => Ok(Some((
+                "max_register_qubits".to_string(),
+                MetricValue::U64(self.max_register_qubits),
+            ))),
+            _ => Ok(None),
+        }
+    }
+
+    fn dump_state(&mut self, file: &std::path::Path, qubits: &[u64]) -> Result<()> {
+        if let Some(q) = qubits.iter().find(|&&q| q >= self.n_qubits) {
+            bail!(
+                "DumpState(q={q}) is out of bounds. q must be less than the number of qubits ({}).",
+                self.n_qubits
+            );
+        }
+        // The dump describes the register as it is held, with the requested
+        // qubits given by their positions within it. Dumping leaves the
+        // register unchanged, so every requested qubit must be live.
+        let slots = qubits
+            .iter()
+            .map(|&q| self.live_slot(q))
+            .collect::<Result<Vec<u64>>>()?;
+        let handle = std::fs::File::create(file)?;
+        let mut writer = std::io::BufWriter::new(handle);
+        writer.write_all(b"selene-quest")?;
+        writer.write_all(self.state.n_qubits().to_le_bytes().as_slice())?;
+        writer.write_all((slots.len() as u64).to_le_bytes().as_slice())?;
+        for &q in &slots {
+            writer.write_all(q.to_le_bytes().as_slice())?;
+        }
+        for amplitude in self.state.amplitudes() {
+            writer.write_all(amplitude.re.to_le_bytes().as_slice())?;
+            writer.write_all(amplitude.im.to_le_bytes().as_slice())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct StatevectorSimulatorFactory;
+
+impl SimulatorInterfaceFactory for StatevectorSimulatorFactory {
+    type Interface = StatevectorSimulator;
+
+    fn init(
+        self: std::sync::Arc<Self>,
+        n_qubits: u64,
+        args: &[impl AsRef<str>],
+    ) -> Result<Box<Self::Interface>> {
+        let args: Vec<String> = args.iter().map(|s| s.as_ref().to_string()).collect();
+        let params = Params::try_parse_from(&args)?;
+        // A dynamic register checks memory as it grows instead.
+        if params.dynamic_register {
+            check_qubit_count(n_qubits)?;
+        } else {
+            check_memory(n_qubits)?;
+        }
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(params.threads)
+            .build()
+            .map_err(|e| anyhow!("Failed to create statevector thread pool: {e}"))?;
+        let register_qubits = if params.dynamic_register { 0 } else { n_qubits };
+        Ok(Box::new(StatevectorSimulator {
+            state: StateVector::new(register_qubits, pool),
+            n_qubits,
+            rng: Pcg64Mcg::seed_from_u64(0),
+            cumulative_postselect_probability: 1.0,
+            dynamic_register: params.dynamic_register,
+            slots: vec![None; n_qubits as usize],
+            live_qubits: Vec::new(),
+            max_register_qubits: register_qubits,
+            memory_checked_qubits: register_qubits,
+        }))
+    }
+}
+
+export_simulator_plugin!(crate::StatevectorSimulatorFactory);
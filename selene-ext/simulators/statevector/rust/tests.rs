@@ -0,0 +1,245 @@
+use crate::StatevectorSimulatorFactory;
+use selene_core::simulator::conformance_testing::run_basic_tests;
+use std::sync::Arc;
+#[test]
+fn basic_conformance_test() {
+    let interface = Arc::new(StatevectorSimulatorFactory);
+    let args = vec![];
+    run_basic_tests(interface, args);
+}
+
+#[test]
+fn multithreaded_conformance_test() {
+    let interface = Arc::new(StatevectorSimulatorFactory);
+    let args = vec!["--threads=4".to_string()];
+    run_basic_tests(interface, args);
+}
+
+#[test]
+fn dynamic_register_conformance_test() {
+    let interface = Arc::new(StatevectorSimulatorFactory);
+    let args = vec!["".to_string(), "--dynamic-register".to_string()];
+    run_basic_tests(interface, args);
+}
+
+#[test]
+fn dynamic_register_holds_only_live_qubits() {
+    use selene_core::simulator::SimulatorInterface;
+    use selene_core::simulator::interface::SimulatorInterfaceFactory;
+    use selene_core::utils::MetricValue;
+    use std::f64::consts::PI;
+    let factory = Arc::new(StatevectorSimulatorFactory);
+    let mut fixed = factory.clone().init(3, &[""]).unwrap();
+    let mut dynamic = factory.init(3, &["", "--dynamic-register"]).unwrap();
+    for sim in [&mut fixed, &mut dynamic] {
+        sim.shot_start(0, 0).unwrap();
+        for q in 0..3 {
+            sim.qalloc(q).unwrap();
+        }
+        sim.rxy(0, PI, 0.0).unwrap();
+        sim.rxy(2, 0.8, 0.4).unwrap();
+        sim.rzz(0, 2, 0.3).unwrap();
+        sim.rz(1, 0.6).unwrap();
+        // Qubit 0 is |1>, so dropping it from the register is deterministic.
+        sim.qfree(0).unwrap();
+    }
+    assert_eq!(fixed.state.n_qubits(), 3);
+    assert_eq!(dynamic.state.n_qubits(), 2);
+    // Qubits 1 and 2 have moved down to the first two positions.
+    for j in 0..4 {
+        let i = 1 | (j << 1);
+        let (a, b) = (fixed.state.amplitudes()[i], dynamic.state.amplitudes()[j]);
+        assert!((a - b).norm() < 1e-12, "{a} != {b}");
+    }
+    // A qubit that is used without being allocated joins the register.
+    assert!(!dynamic.measure(0).unwrap());
+    assert_eq!(dynamic.state.n_qubits(), 3);
+    dynamic.qfree(1).unwrap();
+    dynamic.qfree(1).unwrap();
+    assert_eq!(dynamic.state.n_qubits(), 2);
+    assert!(matches!(
+        dynamic.get_metric(1).unwrap(),
+        Some((name, MetricValue::U64(3))) if name == "max_register_qubits"
+    ));
+    assert!(dynamic.qalloc(3).is_err());
+
+    // Dumping the state does not add unallocated qubits to the register.
+    let file = std::env::temp_dir().join("selene_statevector_dynamic_dump.bin");
+    assert!(dynamic.dump_state(&file, &[2, 1]).is_err());
+    assert_eq!(dynamic.state.n_qubits(), 2);
+    dynamic.dump_state(&file, &[2, 0]).unwrap();
+    std::fs::remove_file(&file).unwrap();
+
+    dynamic.shot_start(1, 0).unwrap();
+    assert_eq!(dynamic.state.n_qubits(), 0);
+    assert!(matches!(
+        dynamic.get_metric(1).unwrap(),
+        Some((_, MetricValue::U64(0)))
+    ));
+}
+
+#[test]
+fn threaded_kernels_match_serial() {
+    use selene_core::simulator::SimulatorInterface;
+    use selene_core::simulator::interface::SimulatorInterfaceFactory;
+    // Large enough to take the parallel code paths.
+    let n_qubits = 16;
+    let mut serial = Arc::new(StatevectorSimulatorFactory)
+        .init(n_qubits, &["", "--threads=1"])
+        .unwrap();
+    let mut threaded = Arc::new(StatevectorSimulatorFactory)
+        .init(n_qubits, &["", "--threads=4"])
+        .unwrap();
+    for sim in [&mut serial, &mut threaded] {
+        sim.shot_start(0, 1234).unwrap();
+        for q in 0..n_qubits {
+            sim.rxy(q, 0.3 + q as f64 * 0.1, 0.7 * q as f64).unwrap();
+            sim.rz(q, 1.1 - q as f64 * 0.05).unwrap();
+        }
+        for q in 0..n_qubits - 1 {
+            sim.rzz(q, n_qubits - 1 - q, 0.4).unwrap();
+        }
+        sim.postselect(0, true).unwrap();
+        sim.reset(n_qubits - 1).unwrap();
+    }
+    for (a, b) in serial
+        .state
+        .amplitudes()
+        .iter()
+        .zip(threaded.state.amplitudes())
+    {
+        assert!((a - b).norm() < 1e-12);
+    }
+}
+
+#[test]
+fn channels_are_sampled_per_trajectory() {
+    use selene_core::simulator::SimulatorInterface;
+    use selene_core::simulator::interface::SimulatorInterfaceFactory;
+    let mut sim = Arc::new(StatevectorSimulatorFactory)
+        .init(3, &[""])
+        .unwrap();
+    // A correlated two-qubit bit flip, applying X⊗X with probability p
+    let p: f64 = 0.25;
+    let (keep, flip) = ((1.0 - p).sqrt(), p.sqrt());
+    let mut kraus = vec![0.0; 64];
+    for i in 0..4 {
+        kraus[2 * (i * 4 + i)] = keep;
+        kraus[32 + 2 * (i * 4 + (3 - i))] = flip;
+    }
+    let shots = 2000;
+    let (mut flips, mut decays) = (0, 0);
+    for shot in 0..shots {
+        sim.shot_start(shot, shot).unwrap();
+        sim.kraus_channel(&[2, 0], &kraus).unwrap();
+        let (q0, q2) = (sim.measure(0).unwrap(), sim.measure(2).unwrap());
+        assert_eq!(q0, q2);
+        flips += q0 as u64;
+        // Each trajectory stays normalised
+        sim.rxy(1, std::f64::consts::PI, 0.0).unwrap();
+        sim.amplitude_damping(1, 0.5).unwrap();
+        let norm: f64 = sim.state.amplitudes().iter().map(|a| a.norm_sqr()).sum();
+        assert!((norm - 1.0).abs() < 1e-12);
+        decays += !sim.measure(1).unwrap() as u64;
+    }
+    // Both fractions are well within 5 standard deviations of their means
+    assert!((flips as f64 / shots as f64 - p).abs() < 0.05);
+    assert!((decays as f64 / shots as f64 - 0.5).abs() < 0.06);
+
+    sim.shot_start(0, 0).unwrap();
+    sim.pauli_channel(0, 1.0, 0.0, 0.0).unwrap();
+    assert!(sim.measure(0).unwrap());
+    assert!(sim.pauli_channel(0, 0.6, 0.6, 0.0).is_err());
+    assert!(sim.kraus_channel(&[0, 0], &kraus).is_err());
+}
+
+#[test]
+fn extended_gates_match_their_decompositions() {
+    use num_complex::Complex64;
+    use rand::{Rng, SeedableRng};
+    use selene_core::runtime::decompose;
+    use selene_core::simulator::SimulatorInterface;
+    use selene_core::simulator::interface::{SimulatorInterfaceFactory, apply_decomposition};
+    let mut rng = rand_pcg::Pcg64Mcg::seed_from_u64(42);
+    let mut random_complex =
+        || Complex64::new(rng.random::<f64>() - 0.5, rng.random::<f64>() - 0.5);
+    // A random unitary, from Gram-Schmidt orthonormalisation of random columns
+    let mut columns: Vec<Vec<Complex64>> = (0..4)
+        .map(|_| (0..4).map(|_| random_complex()).collect())
+        .collect();
+    for i in 0..4 {
+        let (previous, rest) = columns.split_at_mut(i);
+        let column = &mut rest[0];
+        for other in previous.iter() {
+            let overlap: Complex64 = other
+                .iter()
+                .zip(column.iter())
+                .map(|(a, b)| a.conj() * b)
+                .sum();
+            for (c, o) in column.iter_mut().zip(other) {
+                *c -= overlap * o;
+            }
+        }
+        let norm = column.iter().map(|c| c.norm_sqr()).sum::<f64>().sqrt();
+        column.iter_mut().for_each(|c| *c /= norm);
+    }
+    let to_matrix = |element: &dyn Fn(usize, usize) -> Complex64| {
+        let mut matrix = [0.0; 32];
+        for row in 0..4 {
+            for col in 0..4 {
+                let value = element(row, col);
+                matrix[2 * (4 * row + col)] = value.re;
+                matrix[2 * (4 * row + col) + 1] = value.im;
+            }
+        }
+        matrix
+    };
+    let random_unitary = to_matrix(&|row, col| columns[col][row]);
+    // SWAP and CX exercise the cases in which rotations are skipped
+    let swap = to_matrix(&|row, col| {
+        let swapped = [0, 2, 1, 3][col];
+        Complex64::new((row == swapped) as u8 as f64, 0.0)
+    });
+    let cx = to_matrix(&|row, col| {
+        let target = if col & 1 == 1 { col ^ 2 } else { col };
+        Complex64::new((row == target) as u8 as f64, 0.0)
+    });
+
+    let factory = Arc::new(StatevectorSimulatorFactory);
+    let mut native = factory.clone().init(4, &[""]).unwrap();
+    let mut decomposed = factory.init(4, &[""]).unwrap();
+    let assert_equal_up_to_phase = |a: &[Complex64], b: &[Complex64]| {
+        let overlap: Complex64 = a.iter().zip(b).map(|(x, y)| x.conj() * y).sum();
+        assert!((overlap.norm() - 1.0).abs() < 1e-9, "overlap {overlap}");
+    };
+    for sim in [&mut native, &mut decomposed] {
+        sim.shot_start(0, 0).unwrap();
+        for q in 0..4 {
+            sim.rxy(q, 0.4 + 0.3 * q as f64, 0.2 * q as f64).unwrap();
+        }
+        sim.rzz(0, 3, 0.9).unwrap();
+    }
+    native.u(2, 1.3, -0.4, 2.2).unwrap();
+    apply_decomposition(decomposed.as_mut(), decompose::u_gate(2, 1.3, -0.4, 2.2)).unwrap();
+    assert_equal_up_to_phase(native.state.amplitudes(), decomposed.state.amplitudes());
+    for matrix in [&random_unitary, &swap, &cx] {
+        native.two_qubit_unitary(3, 1, matrix).unwrap();
+        apply_decomposition(
+            decomposed.as_mut(),
+            decompose::two_qubit_unitary_gate(3, 1, matrix),
+        )
+        .unwrap();
+        assert_equal_up_to_phase(native.state.amplitudes(), decomposed.state.amplitudes());
+    }
+    let phases = [0.1, -0.7, 1.9, 0.3, 2.5, -1.2, 0.0, 0.8];
+    native.diagonal_phase(&[2, 0, 3], &phases).unwrap();
+    apply_decomposition(
+        decomposed.as_mut(),
+        decompose::diagonal_phase_gate(&[2, 0, 3], &phases),
+    )
+    .unwrap();
+    assert_equal_up_to_phase(native.state.amplitudes(), decomposed.state.amplitudes());
+
+    assert!(native.two_qubit_unitary(1, 1, &swap).is_err());
+    assert!(native.diagonal_phase(&[0, 1], &phases).is_err());
+}
@@ -0,0 +1,337 @@
+//! The statevector storage and the kernels that act on it, shared by the
+//! statevector and hybrid simulator plugins.
+//!
+//! Amplitudes are stored with the same ordering as QuEST: qubit 0 is the least
+//! significant bit of the amplitude index. All kernels are written in terms of
+//! three traversal primitives (pairs of amplitudes differing in one bit, single
+//! indexed amplitudes, and indexed reductions), each of which dispatches to a
+//! rayon implementation once the register is large enough for threading to pay
+//! for itself, and to a plain loop otherwise.
+use anyhow::{Result, bail};
+use num_complex::Complex64;
+use rayon::prelude::*;
+
+/// Registers with fewer qubits than this are always processed on the calling
+/// thread, as the cost of dispatching work to the pool dominates.
+const PARALLEL_THRESHOLD_QUBITS: u64 = 14;
+
+/// The minimum number of amplitudes handed to a single rayon task.
+const MIN_TASK_LEN: usize = 1 << 12;
+
+pub struct StateVector {
+    n_qubits: u64,
+    amplitudes: Vec<Complex64>,
+    pool: rayon::ThreadPool,
+}
+
+impl StateVector {
+    pub fn new(n_qubits: u64, pool: rayon::ThreadPool) -> Self {
+        let mut amplitudes = vec![Complex64::new(0.0, 0.0); 1 << n_qubits];
+        amplitudes[0] = Complex64::new(1.0, 0.0);
+        Self {
+            n_qubits,
+            amplitudes,
+            pool,
+        }
+    }
+
+    pub fn amplitudes(&self) -> &[Complex64] {
+        &self.amplitudes
+    }
+
+    pub fn n_qubits(&self) -> u64 {
+        self.n_qubits
+    }
+
+    fn is_parallel(&self) -> bool {
+        self.n_qubits >= PARALLEL_THRESHOLD_QUBITS && self.pool.current_num_threads() > 1
+    }
+
+    /// Apply `f` to every pair of amplitudes (a0, a1) whose indices differ only
+    /// in bit `qubit`, where a0 has that bit unset.
+    fn for_each_pair(&mut self, qubit: u64, f: impl Fn(&mut Complex64, &mut Complex64) + Sync) {
+        let stride = 1usize << qubit;
+        if !self.is_parallel() {
+            for chunk in self.amplitudes.chunks_mut(stride << 1) {
+                let (lo, hi) = chunk.split_at_mut(stride);
+                lo.iter_mut()
+                    .zip(hi.iter_mut())
+                    .for_each(|(a0, a1)| f(a0, a1));
+            }
+            return;
+        }
+        let amplitudes = &mut self.amplitudes;
+        self.pool.install(|| {
+            if stride >= MIN_TASK_LEN {
+                // Few, large chunks: parallelise within each chunk.
+                amplitudes.par_chunks_mut(stride << 1).for_each(|chunk| {
+                    let (lo, hi) = chunk.split_at_mut(stride);
+                    lo.par_iter_mut()
+                        .zip(hi.par_iter_mut())
+                        .with_min_len(MIN_TASK_LEN)
+                        .for_each(|(a0, a1)| f(a0, a1));
+                });
+            } else {
+                // Many small chunks: parallelise across chunks.
+                amplitudes
+                    .par_chunks_mut(stride << 1)
+                    .with_min_len(MIN_TASK_LEN / stride)
+                    .for_each(|chunk| {
+                        let (lo, hi) = chunk.split_at_mut(stride);
+                        lo.iter_mut()
+                            .zip(hi.iter_mut())
+                            .for_each(|(a0, a1)| f(a0, a1));
+                    });
+            }
+        });
+    }
+
+    /// Apply `f` to every amplitude along with its index.
+    fn for_each_indexed(&mut self, f: impl Fn(usize, &mut Complex64) + Sync) {
+        if !self.is_parallel() {
+            self.amplitudes
+                .iter_mut()
+                .enumerate()
+                .for_each(|(i, a)| f(i, a));
+            return;
+        }
+        let amplitudes = &mut self.amplitudes;
+        self.pool.install(|| {
+            amplitudes
+                .par_iter_mut()
+                .with_min_len(MIN_TASK_LEN)
+                .enumerate()
+                .for_each(|(i, a)| f(i, a));
+        });
+    }
+
+    /// Sum `f` over every amplitude along with its index.
+    fn sum_indexed(&self, f: impl Fn(usize, &Complex64) -> f64 + Sync) -> f64 {
+        if !self.is_parallel() {
+            return self
+                .amplitudes
+                .iter()
+                .enumerate()
+                .map(|(i, a)| f(i, a))
+                .sum();
+        }
+        self.pool.install(|| {
+            self.amplitudes
+                .par_iter()
+                .with_min_len(MIN_TASK_LEN)
+                .enumerate()
+                .map(|(i, a)| f(i, a))
+                .sum()
+        })
+    }
+
+    /// Return the register to |0...0>.
+    pub fn initialise(&mut self) {
+        self.for_each_indexed(|i, a| {
+            *a = if i == 0 {
+                Complex64::new(1.0, 0.0)
+            } else {
+                Complex64::new(0.0, 0.0)
+            }
+        });
+    }
+
+    /// Replace the register with one of zero qubits, holding a single unit
+    /// amplitude.
+    pub fn clear(&mut self) {
+        self.n_qubits = 0;
+        self.amplitudes.clear();
+        self.amplitudes.push(Complex64::new(1.0, 0.0));
+    }
+
+    /// Append a qubit in the |0> state as the most significant bit.
+    pub fn add_qubit(&mut self) {
+        self.amplitudes
+            .resize(self.amplitudes.len() << 1, Complex64::new(0.0, 0.0));
+        self.n_qubits += 1;
+    }
+
+    /// Remove `qubit` from the register, keeping the amplitudes in which it
+    /// takes `value`. Higher qubits move down by one. The caller is
+    /// responsible for the qubit being in that state.
+    pub fn remove_qubit(&mut self, qubit: u64, value: bool) {
+        let low_mask = (1usize << qubit) - 1;
+        let value = (value as usize) << qubit;
+        let gather = |j: usize| ((j & !low_mask) << 1) | value | (j & low_mask);
+        let len = self.amplitudes.len() >> 1;
+        self.amplitudes = if !self.is_parallel() {
+            (0..len).map(|j| self.amplitudes[gather(j)]).collect()
+        } else {
+            self.pool.install(|| {
+                (0..len)
+                    .into_par_iter()
+                    .with_min_len(MIN_TASK_LEN)
+                    .map(|j| self.amplitudes[gather(j)])
+                    .collect()
+            })
+        };
+        self.n_qubits -= 1;
+    }
+
+    /// Apply a general 2x2 matrix, given in row-major order, to `qubit`.
+    pub fn apply_matrix(&mut self, qubit: u64, matrix: [[Complex64; 2]; 2]) {
+        self.for_each_pair(qubit, |a0, a1| {
+            let (x0, x1) = (*a0, *a1);
+            *a0 = matrix[0][0] * x0 + matrix[0][1] * x1;
+            *a1 = matrix[1][0] * x0 + matrix[1][1] * x1;
+        });
+    }
+
+    /// Apply diag(d0, d1) to `qubit`.
+    pub fn apply_diagonal(&mut self, qubit: u64, d0: Complex64, d1: Complex64) {
+        self.for_each_indexed(|i, a| {
+            *a *= if (i >> qubit) & 1 == 0 { d0 } else { d1 };
+        });
+    }
+
+    /// Apply a diagonal two-qubit operator, where `diagonal[b0 + 2 * b1]` is the
+    /// factor applied to amplitudes in which `qubit_0` is b0 and `qubit_1` is b1.
+    pub fn apply_diagonal_2q(&mut self, qubit_0: u64, qubit_1: u64, diagonal: [Complex64; 4]) {
+        self.for_each_indexed(|i, a| {
+            let b0 = (i >> qubit_0) & 1;
+            let b1 = (i >> qubit_1) & 1;
+            *a *= diagonal[b0 | (b1 << 1)];
+        });
+    }
+
+    /// Apply a diagonal operator on `qubits`, where `diagonal[k]` is the factor
+    /// applied to amplitudes in which `qubits[j]` takes the value of bit j of k.
+    pub fn apply_diagonal_nq(&mut self, qubits: &[u64], diagonal: &[Complex64]) {
+        self.for_each_indexed(|i, a| {
+            let k = qubits
+                .iter()
+                .enumerate()
+                .fold(0usize, |acc, (j, &q)| acc | (((i >> q) & 1) << j));
+            *a *= diagonal[k];
+        });
+    }
+
+    /// Apply a Pauli X to `qubit`.
+    pub fn flip(&mut self, qubit: u64) {
+        self.for_each_pair(qubit, std::mem::swap);
+    }
+
+    /// The probability of measuring `qubit` as 1.
+    pub fn probability_of_one(&self, qubit: u64) -> f64 {
+        self.sum_indexed(|i, a| {
+            if (i >> qubit) & 1 == 1 {
+                a.norm_sqr()
+            } else {
+                0.0
+            }
+        })
+    }
+
+    /// Zero out all amplitudes in which `qubit` differs from `value`, and scale
+    /// the remainder by `scale`.
+    pub fn project(&mut self, qubit: u64, value: bool, scale: f64) {
+        let value = value as usize;
+        self.for_each_indexed(|i, a| {
+            if (i >> qubit) & 1 == value {
+                *a *= scale;
+            } else {
+                *a = Complex64::new(0.0, 0.0);
+            }
+        });
+    }
+
+    /// Compute K|ψ> without modifying the state, where K is a row-major
+    /// 2^k x 2^k matrix acting on `qubits` with the first of `qubits` as the
+    /// least significant bit of its row and column indices.
+    pub fn transformed(&self, qubits: &[u64], matrix: &[Complex64]) -> Vec<Complex64> {
+        let d = 1usize << qubits.len();
+        let scatter = |x: usize| {
+            qubits
+                .iter()
+                .enumerate()
+                .fold(0usize, |acc, (j, &q)| acc | (((x >> j) & 1) << q))
+        };
+        let offsets: Vec<usize> = (0..d).map(scatter).collect();
+        let mask = offsets[d - 1];
+        let compute = |i: usize| {
+            let row = qubits
+                .iter()
+                .enumerate()
+                .fold(0usize, |acc, (j, &q)| acc | (((i >> q) & 1) << j));
+            let base = i & !mask;
+            (0..d)
+                .map(|col| matrix[row * d + col] * self.amplitudes[base | offsets[col]])
+                .sum()
+        };
+        if !self.is_parallel() {
+            return (0..self.amplitudes.len()).map(compute).collect();
+        }
+        self.pool.install(|| {
+            (0..self.amplitudes.len())
+                .into_par_iter()
+                .with_min_len(MIN_TASK_LEN)
+                .map(compute)
+                .collect()
+        })
+    }
+
+    /// Replace the amplitudes with `amplitudes` scaled by `scale`.
+    pub fn replace(&mut self, amplitudes: Vec<Complex64>, scale: f64) {
+        self.amplitudes = amplitudes;
+        self.for_each_indexed(|_, a| *a *= scale);
+    }
+
+    /// The squared norm of `amplitudes`.
+    pub fn norm_sqr(&self, amplitudes: &[Complex64]) -> f64 {
+        if !self.is_parallel() {
+            return amplitudes.iter().map(|a| a.norm_sqr()).sum();
+        }
+        self.pool.install(|| {
+            amplitudes
+                .par_iter()
+                .with_min_len(MIN_TASK_LEN)
+                .map(|a| a.norm_sqr())
+                .sum()
+        })
+    }
+}
+
+/// Check that a statevector of `n_qubits` qubits can be addressed.
+pub fn check_qubit_count(n_qubits: u64) -> Result<()> {
+    if n_qubits == 0 {
+        bail!("Number of qubits must be greater than 0");
+    } else if n_qubits > 60 {
+        bail!(
+            "It is impossible to describe more than 60 qubits in a statevector on a computer with a 64-bit address space."
+        );
+    }
+    Ok(())
+}
+
+/// Check that a statevector of `n_qubits` qubits fits in the available
+/// memory, warning on stderr if the available memory cannot be determined.
+pub fn check_memory(n_qubits: u64) -> Result<()> {
+    check_qubit_count(n_qubits)?;
+    let bytes_required = bytesize::ByteSize::b(16 * (1 << n_qubits));
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    let reported_available = system.available_memory();
+    if reported_available == 0 {
+        eprintln!("-----------------------------------");
+        eprintln!("Unable to determine available memory due to system limitations.");
+        eprintln!("The statevector simulator is going to try to allocate {bytes_required}");
+        eprintln!("of memory to store the statevector, and this will be multiplied by");
+        eprintln!("the number of processes if running in multiprocessing mode.");
+        eprintln!();
+        eprintln!("If this fails, verify that your system has sufficient memory.");
+        eprintln!("-----------------------------------");
+    } else {
+        let bytes_available = bytesize::ByteSize::b(reported_available);
+        if bytes_required > bytes_available {
+            bail!(
+                "Insufficient memory available ({bytes_available}) to allocate a state vector of {n_qubits} qubits ({bytes_required}).",
+            );
+        }
+    }
+    Ok(())
+}
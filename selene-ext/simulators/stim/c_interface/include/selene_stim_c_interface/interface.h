@@ -9,6 +9,8 @@ extern "C" {
 struct TableauSimulatorMin;
 TableauSimulatorMin* stim_tableausimulator_min_create(unsigned int n_qubits, uint64_t random_seed);
 void stim_tableausimulator_min_destroy(TableauSimulatorMin* sim);
+TableauSimulatorMin* stim_tableausimulator_min_clone(TableauSimulatorMin* sim);
+void stim_tableausimulator_min_copy_state(TableauSimulatorMin* sim, TableauSimulatorMin* source);
 void stim_tableausimulator_min_do_X(TableauSimulatorMin* sim, unsigned int q);
 void stim_tableausimulator_min_do_Y(TableauSimulatorMin* sim, unsigned int q);
 void stim_tableausimulator_min_do_Z(TableauSimulatorMin* sim, unsigned int q);
@@ -29,7 +31,9 @@ void stim_tableausimulator_min_do_ZCX(TableauSimulatorMin* sim, unsigned int q_c
 void stim_tableausimulator_min_do_ZCY(TableauSimulatorMin* sim, unsigned int q_control, unsigned int q_target);
 void stim_tableausimulator_min_do_ZCZ(TableauSimulatorMin* sim, unsigned int q_control, unsigned int q_target);
 bool stim_tableausimulator_min_do_MZ(TableauSimulatorMin* sim, unsigned int q);
+int8_t stim_tableausimulator_min_peek_pauli(TableauSimulatorMin* sim, const unsigned int* qubits, const uint8_t* paulis, unsigned int n);
 bool stim_tableausimulator_min_do_POSTSELECT_Z(TableauSimulatorMin* sim,unsigned int q, bool target_result);
+void stim_tableausimulator_min_to_state_vector(TableauSimulatorMin* sim, float* write);
 void stim_tableausimulator_min_get_stabilizers(TableauSimulatorMin* sim, char** write);
 void stim_tableausimulator_min_free_stabilizers(char* written);
 
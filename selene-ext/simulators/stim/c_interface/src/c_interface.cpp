@@ -12,6 +12,12 @@ TableauSimulatorMin* stim_tableausimulator_min_create(
 void stim_tableausimulator_min_destroy(TableauSimulatorMin* sim){
     delete sim;
 }
+TableauSimulatorMin* stim_tableausimulator_min_clone(TableauSimulatorMin* sim){
+    return new TableauSimulatorMin(*sim);
+}
+void stim_tableausimulator_min_copy_state(TableauSimulatorMin* sim, TableauSimulatorMin* source){
+    sim->inverse_state = source->inverse_state;
+}
 
 void stim_tableausimulator_min_do_X(TableauSimulatorMin* sim, unsigned int q) {
     sim->do_X(q);
@@ -93,10 +99,24 @@ bool stim_tableausimulator_min_do_MZ(TableauSimulatorMin* sim, unsigned int q) {
     return sim->do_MZ(q);
 }
 
+int8_t stim_tableausimulator_min_peek_pauli(TableauSimulatorMin* sim, const unsigned int* qubits, const uint8_t* paulis, unsigned int n) {
+    return sim->peek_pauli(qubits, paulis, n);
+}
+
 bool stim_tableausimulator_min_do_POSTSELECT_Z(TableauSimulatorMin* sim, unsigned int q, bool target_result) {
     return sim->do_POSTSELECT_Z(q, target_result);
 }
 
+void stim_tableausimulator_min_to_state_vector(TableauSimulatorMin* sim, float* write) {
+    // The tableau stores the inverse of the state preparation, and qubit 0
+    // is the least significant bit of the amplitude index (little endian).
+    std::vector<std::complex<float>> amplitudes = sim->inverse_state.inverse().to_state_vector(true);
+    for (size_t i = 0; i < amplitudes.size(); ++i) {
+        write[2 * i] = amplitudes[i].real();
+        write[2 * i + 1] = amplitudes[i].imag();
+    }
+}
+
 void stim_tableausimulator_min_get_stabilizers(TableauSimulatorMin* sim, char** write) {
     std::string str = sim->get_stabilizers();
     char* cstr = new char[str.size() + 1];
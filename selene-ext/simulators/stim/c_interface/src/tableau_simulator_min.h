@@ -125,6 +125,24 @@ struct TableauSimulatorMin{
         return false;
     }
 
+    // Returns the expectation value of a Pauli product, which is always -1, 0 or +1 for
+    // a stabilizer state, without modifying the state. Pauli codes are 0 => I, 1 => X,
+    // 2 => Y, 3 => Z.
+    int8_t peek_pauli(const unsigned int* qubits, const uint8_t* paulis, size_t n) const {
+        stim::PauliString<64> observable(inverse_state.num_qubits);
+        for (size_t k = 0; k < n; k++) {
+            observable.xs[qubits[k]] = paulis[k] == 1 || paulis[k] == 2;
+            observable.zs[qubits[k]] = paulis[k] == 2 || paulis[k] == 3;
+        }
+        // The state is inverse_state^-1 |0...0>, so the expectation value is that of
+        // the image of the observable under inverse_state in the |0...0> state.
+        stim::PauliString<64> image = inverse_state(observable.ref());
+        if (image.xs.not_zero()) {
+            return 0;
+        }
+        return image.sign ? -1 : +1;
+    }
+
     std::string get_stabilizers() {
         std::stringstream ss;
         for(auto const& pauli_string : inverse_state.inverse().stabilizers(true)){
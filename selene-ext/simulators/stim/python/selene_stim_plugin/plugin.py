@@ -19,12 +19,23 @@ class StimPlugin(Simulator):
     on the bloch sphere before they are considered invalid. This is to avoid numerical instability,
     or to inject approximations.
 
+    With `round_to_clifford`, angles beyond the threshold are instead rounded to the nearest
+    Clifford angle, and the number of rounded angles and the total rounding error (in radians)
+    are reported as the `rounded_angles` and `total_rounding_error` metrics. This allows Stim
+    to be used as a fast, approximate first pass over non-Clifford programs.
+
+    State dumps contain only the stabilizers of the requested qubits, with the remaining
+    qubits traced out.
+
     Attributes:
         angle_threshold (float, default 1e-4): The angle threshold for valid rotations. Must be
             greater than zero, as floating point errors can cause numerical instability.
+        round_to_clifford (bool, default False): Round angles beyond the threshold to the
+            nearest Clifford angle rather than raising an error.
     """
 
     angle_threshold: float = 1e-4
+    round_to_clifford: bool = False
 
     def __post_init__(self):
         assert self.angle_threshold > 0, (
@@ -32,9 +43,12 @@ class StimPlugin(Simulator):
         )
 
     def get_init_args(self):
-        return [
+        args = [
             f"--angle-threshold={self.angle_threshold}",
         ]
+        if self.round_to_clifford:
+            args.append("--round-to-clifford")
+        return args
 
     @property
     def library_file(self):
@@ -219,11 +219,17 @@ class Stabilizer:
 
 class StabilizerList:
     generators: list[Stabilizer]  # len: specified_qubits
+    n_qubits: int
 
-    def __init__(self, stabilizer_strings: list[str]):
+    def __init__(self, stabilizer_strings: list[str], n_qubits: int | None = None):
         self.generators = [
             Stabilizer(stabilizer_strings[i]) for i in range(len(stabilizer_strings))
         ]
+        # A mixed state may have fewer generators than qubits, or none at all,
+        # in which case the number of qubits must be given explicitly.
+        if n_qubits is None:
+            n_qubits = len(self.generators[0].paulis)
+        self.n_qubits = n_qubits
         assert all(
             len(stab.paulis) == len(self.generators[0].paulis)
             for stab in self.generators
@@ -232,7 +238,7 @@ class StabilizerList:
         )
 
     def clone(self) -> "StabilizerList":
-        return StabilizerList([repr(stab) for stab in self.generators])
+        return StabilizerList([repr(stab) for stab in self.generators], self.n_qubits)
 
     def __repr__(self) -> str:
         return "\n".join(repr(stab) for stab in self.generators)
@@ -282,11 +288,12 @@ class StabilizerList:
         for i in range(len(self.generators)):
             # Delete the traced out qubit from each remaining stabilizer
             self.generators[i].remove_qubit(qubit_index)
+        self.n_qubits -= 1
 
     def reduced_to_qubits(self, wanted_qubits: list[int]) -> "StabilizerList":
         # Trace out unspecified qubits from highest to lowest index
         result = self.clone()
-        for qubit_index in reversed(range(len(self.generators[0].paulis))):
+        for qubit_index in reversed(range(self.n_qubits)):
             if qubit_index not in wanted_qubits:
                 result.trace_out_qubit(qubit_index)
 
@@ -309,8 +316,7 @@ class StabilizerList:
 
     def as_density_matrix(self) -> np.ndarray:
         """Get the density matrix represented by the stabilizer list. Only to be used for small numbers of qubits."""
-        n_qubits = len(self.generators[0].paulis)
-        dim = 2**n_qubits
+        dim = 2**self.n_qubits
         result = np.zeros((dim, dim), dtype=complex)
         generator_matrices = [stab.as_matrix() for stab in self.generators]
         # for every combination of stabilizer generators, add their product to the density matrix
@@ -340,14 +346,23 @@ class SeleneStimState:
     """A quantum state in the Selene Stim simulator, as reported by `state_result` calls."""
 
     # Stabilizers of the entire register - not of just the specified qubits.
-    unreduced_stabilizer_list: StabilizerList
+    # None if the simulator only reported the stabilizers of the specified qubits.
+    unreduced_stabilizer_list: StabilizerList | None
     # Total number of qubits in the state, i.e. n_qubits param to run_shots
     total_qubits: int
     # User-specified qubits, in order of their specification
     specified_qubits: list[int]
+    # Stabilizers of the specified qubits, in order of their specification, if
+    # the simulator has already traced out the other qubits.
+    reduced_stabilizer_list: StabilizerList | None = None
 
     def get_reduced_stabilizers(self) -> StabilizerList:
         """Get the stabilizers reduced to the specified qubits."""
+        if self.reduced_stabilizer_list is not None:
+            return self.reduced_stabilizer_list.clone()
+        assert self.unreduced_stabilizer_list is not None, (
+            "Either the full or the reduced stabilizers must be provided"
+        )
         return self.unreduced_stabilizer_list.reduced_to_qubits(self.specified_qubits)
 
     def get_density_matrix(self) -> np.ndarray:
@@ -492,17 +507,29 @@ class SeleneStimState:
     @staticmethod
     def parse_from_file(filename: Path, cleanup: bool = True) -> "SeleneStimState":
         with open(filename, "rb") as f:
+            # "selene-stim" files hold the stabilizers of the full register,
+            # while "selene-stab" files hold only those of the specified qubits.
             magic = f.read(11)
-            if magic != b"selene-stim":
+            if magic not in (b"selene-stim", b"selene-stab"):
                 raise ValueError("Invalid state file format")
             header_head = f.read(16)
-            _, n_specified_qubits = struct.unpack("<QQ", header_head)
+            total_qubits, n_specified_qubits = struct.unpack("<QQ", header_head)
             specified_qubits = []
             for i in range(n_specified_qubits):
                 specified_qubits.append(struct.unpack("<Q", f.read(8))[0])
             stabilizers = f.read()
         if cleanup:
             filename.unlink()
+        if magic == b"selene-stab":
+            stabilizer_strings = stabilizers.decode("utf-8").split()
+            return SeleneStimState(
+                unreduced_stabilizer_list=None,
+                total_qubits=total_qubits,
+                specified_qubits=specified_qubits,
+                reduced_stabilizer_list=StabilizerList(
+                    stabilizer_strings, n_qubits=n_specified_qubits
+                ),
+            )
         return SeleneStimState.parse_from_stabilizer_strings(
             stabilizer_strings=stabilizers.decode("utf-8").strip().split("\n"),
             specified_qubits=specified_qubits,
@@ -1,3 +1,5 @@
+import struct
+
 import pytest
 from dataclasses import dataclass
 from selene_stim_plugin.state import StabilizerList, SeleneStimState, TracedState
@@ -223,3 +225,25 @@ def test_stabilizer_reduction(example: ReductionExample):
                 expected.state,
                 atol=1e-8,
             )
+
+
+def test_parse_reduced_state_file(tmp_path):
+    # Qubit 4 is |1> and qubit 2 is maximally mixed, as reported by the plugin
+    # after tracing out the rest of a 5-qubit register.
+    path = tmp_path / "state"
+    path.write_bytes(b"selene-stab" + struct.pack("<QQQQ", 5, 2, 4, 2) + b"-Z_\n")
+    state = SeleneStimState.parse_from_file(path)
+    assert not path.exists()
+    assert state.total_qubits == 5
+    assert state.specified_qubits == [4, 2]
+    assert state.get_reduced_stabilizers() == StabilizerList(["-Z_"])
+    np.testing.assert_allclose(
+        state.get_density_matrix(),
+        np.diag([0.0, 0.0, 0.5, 0.5]),
+        atol=1e-8,
+    )
+
+    # Without generators, the reduced state is maximally mixed.
+    path.write_bytes(b"selene-stab" + struct.pack("<QQQ", 5, 1, 0))
+    state = SeleneStimState.parse_from_file(path)
+    np.testing.assert_allclose(state.get_density_matrix(), np.eye(2) / 2, atol=1e-8)
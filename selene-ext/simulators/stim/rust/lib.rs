@@ -1,5 +1,4 @@
-mod bindings;
-mod wrapper;
+mod stabilizers;
 
 #[cfg(test)]
 mod tests;
@@ -8,12 +7,12 @@ use anyhow::{Result, anyhow};
 use clap::Parser;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use selene_core::export_simulator_plugin;
-use selene_core::simulator::SimulatorInterface;
 use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::simulator::{Pauli, SimulatorInterface};
 use selene_core::utils::MetricValue;
+use selene_stim_tableau::TableauSimulatorMin;
 use std::io::Write;
 use std::ops::Sub;
-use wrapper::TableauSimulatorMin;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, IntoPrimitive, TryFromPrimitive)]
 #[repr(u8)]
@@ -48,41 +47,89 @@ enum Octant {
 struct Params {
     #[arg(long)]
     angle_threshold: f64,
+    /// Round angles beyond the threshold to the nearest Clifford angle rather
+    /// than failing, accumulating the rounding error in the metrics.
+    #[arg(long)]
+    round_to_clifford: bool,
 }
 
 pub struct StimSimulator {
     simulator: TableauSimulatorMin,
     n_qubits: u64,
     angle_threshold_quad: f64,
+    round_to_clifford: bool,
+    /// Snapshots by handle, or None once released.
+    snapshots: Vec<Option<TableauSimulatorMin>>,
+    cumulative_postselect_probability: f64,
+    rounded_angles: u64,
+    total_rounding_error: f64,
 }
 impl StimSimulator {
-    fn get_approximate_quadrant(&self, theta: f64) -> Option<Quadrant> {
-        let quadrant_float = theta / std::f64::consts::FRAC_PI_2;
-        let quadrant_rounded = quadrant_float.round();
-        if (quadrant_float - quadrant_rounded).abs() > self.angle_threshold_quad {
-            return None; // Not within the threshold for any quadrant
+    /// The slot of the snapshot with the given handle, which is None if the
+    /// snapshot has been released.
+    fn snapshot_slot(&mut self, handle: u64) -> Result<&mut Option<TableauSimulatorMin>> {
+        let n_snapshots = self.snapshots.len();
+        self.snapshots.get_mut(handle as usize).ok_or_else(|| {
+            anyhow!(
+                "Snapshot {handle} does not exist. Only {n_snapshots} snapshots have been taken."
+            )
+        })
+    }
+
+    /// Round `angle` to the nearest multiple of `step`, returning that
+    /// multiple modulo `modulus`. Angles further than the threshold from any
+    /// multiple are rejected, unless rounding to Clifford angles is enabled,
+    /// in which case the rounding error is recorded.
+    fn round_angle(&mut self, angle: f64, step: f64, modulus: i64) -> Option<u8> {
+        let multiple_float = angle / step;
+        let multiple_rounded = multiple_float.round();
+        let error = multiple_float - multiple_rounded;
+        if error.abs() > self.angle_threshold_quad {
+            if !self.round_to_clifford {
+                return None; // Not within the threshold for any multiple
+            }
+            self.rounded_angles += 1;
+            self.total_rounding_error += (error * step).abs();
         }
-        let quadrant = (quadrant_rounded as i64).rem_euclid(4) as u8;
-        Some(Quadrant::try_from(quadrant).unwrap())
+        Some((multiple_rounded as i64).rem_euclid(modulus) as u8)
     }
-    fn get_approximate_octant(&self, theta: f64) -> Option<Octant> {
-        let octant_float = theta / std::f64::consts::FRAC_PI_4;
-        let octant_rounded = octant_float.round();
-        if (octant_float - octant_rounded).abs() > self.angle_threshold_quad {
-            return None; // Not within the threshold for any octant
+    fn get_approximate_quadrant(&mut self, theta: f64) -> Option<Quadrant> {
+        self.round_angle(theta, std::f64::consts::FRAC_PI_2, 4)
+            .map(|quadrant| Quadrant::try_from(quadrant).unwrap())
+    }
+    fn check_qubit_list(&self, name: &str, qubits: &[u64]) -> Result<Vec<u32>> {
+        for (i, &q) in qubits.iter().enumerate() {
+            if q >= self.n_qubits {
+                return Err(anyhow!(
+                    "{name}(q={q}) is out of bounds. q must be less than the number of qubits ({}).",
+                    self.n_qubits
+                ));
+            }
+            if qubits[..i].contains(&q) {
+                return Err(anyhow!(
+                    "{name} requires distinct qubits, but qubit {q} was provided twice."
+                ));
+            }
         }
-        let octant = (octant_rounded as i64).rem_euclid(8) as u8;
-        Some(Octant::try_from(octant).unwrap())
+        Ok(qubits.iter().map(|&q| q as u32).collect())
+    }
+    fn get_approximate_octant(&mut self, theta: f64) -> Option<Octant> {
+        self.round_angle(theta, std::f64::consts::FRAC_PI_4, 8)
+            .map(|octant| Octant::try_from(octant).unwrap())
     }
 }
 
 impl SimulatorInterface for StimSimulator {
     fn exit(&mut self) -> Result<()> {
+        self.snapshots.clear();
         Ok(())
     }
 
     fn shot_start(&mut self, _shot_id: u64, seed: u64) -> Result<()> {
         self.simulator = TableauSimulatorMin::new(self.n_qubits.try_into().unwrap(), seed);
+        self.cumulative_postselect_probability = 1.0;
+        self.rounded_angles = 0;
+        self.total_rounding_error = 0.0;
         Ok(())
     }
     fn shot_end(&mut self) -> Result<()> {
@@ -141,7 +188,15 @@ impl SimulatorInterface for StimSimulator {
             return Ok(());
         }
 
-        let Some(approx_phi) = self.get_approximate_octant(phi) else {
+        // When theta is pi/2 or 3pi/2, only multiples of pi/2 are Clifford
+        // angles for phi, so those are what phi is rounded to.
+        let approx_phi = match approx_theta {
+            Quadrant::FracPi2 | Quadrant::Frac3Pi2 if self.round_to_clifford => self
+                .get_approximate_quadrant(phi)
+                .map(|quadrant| Octant::try_from(2 * u8::from(quadrant)).unwrap()),
+            _ => self.get_approximate_octant(phi),
+        };
+        let Some(approx_phi) = approx_phi else {
             return Err(anyhow!(
                 "RXY(q0={q0}, theta={theta}, phi={phi}) is not representable in stabiliser form. When theta is nonzero, phi must be an (approximate) multiple of pi/4 for Clifford operations."
             ));
@@ -324,6 +379,14 @@ impl SimulatorInterface for StimSimulator {
             ))
         } else {
             let q_u32: u32 = qubit.try_into()?;
+            // The outcome is either deterministic or uniformly random.
+            let expectation = self.simulator.peek_pauli(&[q_u32], &[Pauli::Z as u8]);
+            let target_expectation = if target_value { -1 } else { 1 };
+            self.cumulative_postselect_probability *= match expectation {
+                0 => 0.5,
+                e if e == target_expectation => 1.0,
+                _ => 0.0,
+            };
             match self.simulator.postselect_z(q_u32, target_value) {
                 true => Ok(()),
                 false => Err(anyhow!(
@@ -348,22 +411,105 @@ impl SimulatorInterface for StimSimulator {
         }
     }
 
-    fn get_metric(&mut self, _nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
-        Ok(None)
+    fn snapshot(&mut self) -> Result<u64> {
+        self.snapshots.push(Some(self.simulator.duplicate()));
+        Ok(self.snapshots.len() as u64 - 1)
+    }
+
+    fn restore(&mut self, handle: u64) -> Result<()> {
+        let Some(snapshot) = self.snapshots.get(handle as usize) else {
+            return Err(anyhow!(
+                "Snapshot {handle} does not exist. Only {} snapshots have been taken.",
+                self.snapshots.len()
+            ));
+        };
+        let Some(snapshot) = snapshot else {
+            return Err(anyhow!("Snapshot {handle} has been released."));
+        };
+        self.simulator.copy_state_from(snapshot);
+        Ok(())
+    }
+
+    fn release_snapshot(&mut self, handle: u64) -> Result<()> {
+        self.snapshot_slot(handle)?.take();
+        Ok(())
+    }
+
+    fn marginal_probabilities(&mut self, qubits: &[u64]) -> Result<Vec<f64>> {
+        let qubits = self.check_qubit_list("MarginalProbabilities", qubits)?;
+        // Each outcome probability is a sum of Z-product expectation values:
+        //   P(b) = 2^-k Σ_S (-1)^|b ∧ S| <Z_S>
+        // over subsets S of the k qubits. We peek each <Z_S> and then apply
+        // the (self-inverse up to scale) Walsh-Hadamard transform in place.
+        let n_outcomes = 1usize << qubits.len();
+        let mut probabilities: Vec<f64> = (0..n_outcomes)
+            .map(|subset| {
+                let (targets, paulis): (Vec<u32>, Vec<u8>) = qubits
+                    .iter()
+                    .enumerate()
+                    .filter(|(k, _)| subset & (1 << k) != 0)
+                    .map(|(_, &q)| (q, Pauli::Z as u8))
+                    .unzip();
+                self.simulator.peek_pauli(&targets, &paulis) as f64
+            })
+            .collect();
+        let mut half = 1;
+        while half < n_outcomes {
+            for i in (0..n_outcomes).filter(|i| i & half == 0) {
+                let (a, b) = (probabilities[i], probabilities[i | half]);
+                probabilities[i] = a + b;
+                probabilities[i | half] = a - b;
+            }
+            half <<= 1;
+        }
+        for p in probabilities.iter_mut() {
+            *p /= n_outcomes as f64;
+        }
+        Ok(probabilities)
+    }
+
+    fn pauli_expectation(&mut self, pauli_string: &[(u64, Pauli)]) -> Result<f64> {
+        let qubits: Vec<u64> = pauli_string.iter().map(|&(q, _)| q).collect();
+        let targets = self.check_qubit_list("PauliExpectation", &qubits)?;
+        let paulis: Vec<u8> = pauli_string.iter().map(|&(_, p)| p as u8).collect();
+        Ok(self.simulator.peek_pauli(&targets, &paulis) as f64)
+    }
+
+    fn get_metric(&mut self, nth_metric: u8) -> Result<Option<(String, MetricValue)>> {
+        match nth_metric {
+            0 => Ok(Some((
+                "cumulative_postselect_probability".to_string(),
+                MetricValue::F64(self.cumulative_postselect_probability),
+            ))),
+            1 => Ok(Some((
+                "rounded_angles".to_string(),
+                MetricValue::U64(self.rounded_angles),
+            ))),
+            2 => Ok(Some((
+                "total_rounding_error".to_string(),
+                MetricValue::F64(self.total_rounding_error),
+            ))),
+            _ => Ok(None),
+        }
     }
 
     fn dump_state(&mut self, file: &std::path::Path, qubits: &[u64]) -> Result<()> {
+        // Only the stabilizer group of the requested qubits is written, with
+        // the other qubits traced out and the requested qubits in order.
+        self.check_qubit_list("DumpState", qubits)?;
         let handle = std::fs::File::create(file)?;
         let mut writer = std::io::BufWriter::new(handle);
-        writer.write_all(b"selene-stim")?;
+        writer.write_all(b"selene-stab")?;
         writer.write_all(self.n_qubits.to_le_bytes().as_slice())?;
         writer.write_all((qubits.len() as u64).to_le_bytes().as_slice())?;
         for &q in qubits {
             writer.write_all(q.to_le_bytes().as_slice())?;
         }
         let stab = self.simulator.get_stabilisers();
-        let stab_bytes = stab.as_bytes();
-        writer.write_all(stab_bytes)?;
+        for stabilizer in stabilizers::reduce_stabilizers(&stab, qubits) {
+            writer.write_all(stabilizer.as_bytes())?;
+            writer.write_all(b"\n")?;
+        }
         Ok(())
     }
 }
@@ -389,6 +535,11 @@ impl SimulatorInterfaceFactory for StimSimulatorFactory {
                     simulator: TableauSimulatorMin::new(n_u32, 0),
                     n_qubits,
                     angle_threshold_quad,
+                    round_to_clifford: params.round_to_clifford,
+                    snapshots: Vec::new(),
+                    cumulative_postselect_probability: 1.0,
+                    rounded_angles: 0,
+                    total_rounding_error: 0.0,
                 }))
             }
         }
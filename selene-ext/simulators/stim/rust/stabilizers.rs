@@ -0,0 +1,118 @@
+// Reduction of a stabiliser state to the stabiliser group of a subset of its
+// qubits. This is the Rust counterpart of `StabilizerList.reduced_to_qubits`
+// in the python package, used so that state dumps only contain the requested
+// qubits.
+
+/// A Pauli product with a phase, stored as a power of i. Paulis use the
+/// codes 0 => I, 1 => X, 2 => Y, 3 => Z.
+#[derive(Clone, Debug, PartialEq)]
+struct PauliString {
+    phase: u8,
+    paulis: Vec<u8>,
+}
+
+impl PauliString {
+    fn parse(stabilizer: &str) -> Self {
+        let (phase, paulis) = match stabilizer.strip_prefix('-') {
+            Some(rest) => (2, rest),
+            None => (0, stabilizer.strip_prefix('+').unwrap_or(stabilizer)),
+        };
+        let paulis = paulis
+            .chars()
+            .map(|c| match c {
+                'X' => 1,
+                'Y' => 2,
+                'Z' => 3,
+                _ => 0,
+            })
+            .collect();
+        Self { phase, paulis }
+    }
+
+    /// Replace self with the product self * other.
+    fn multiply_by(&mut self, other: &Self) {
+        // The phase is reduced as it is accumulated, as a product over many
+        // qubits would otherwise overflow it.
+        self.phase = (self.phase + other.phase) % 4;
+        for (a, &b) in self.paulis.iter_mut().zip(other.paulis.iter()) {
+            if *a != 0 && b != 0 && *a != b {
+                // XY = iZ, YZ = iX and ZX = iY, and the reverse orders pick
+                // up a factor of -i.
+                self.phase = (self.phase + if (b + 3 - *a) % 3 == 1 { 1 } else { 3 }) % 4;
+            }
+            *a ^= b;
+        }
+    }
+
+    /// The string on the given qubits, in order, in stim's notation.
+    fn restricted_to(&self, qubits: &[usize]) -> String {
+        debug_assert!(
+            self.phase.is_multiple_of(2),
+            "Stabilizers must have a real phase"
+        );
+        let sign = if self.phase == 0 { '+' } else { '-' };
+        std::iter::once(sign)
+            .chain(
+                qubits
+                    .iter()
+                    .map(|&q| ['_', 'X', 'Y', 'Z'][self.paulis[q] as usize]),
+            )
+            .collect()
+    }
+}
+
+/// Eliminate the given qubit from the generators, leaving generators of the
+/// subgroup of stabilizers that act as the identity on it.
+///
+/// At most two generators can act independently on a single qubit. These are
+/// used to clear the qubit from every other generator, then removed.
+fn trace_out_qubit(generators: &mut Vec<PauliString>, qubit: usize) {
+    let mut pivots: Vec<usize> = Vec::with_capacity(2);
+    for i in 0..generators.len() {
+        let pauli = generators[i].paulis[qubit];
+        if pauli == 0 {
+            continue;
+        }
+        let matching = pivots
+            .iter()
+            .position(|&p| generators[p].paulis[qubit] == pauli);
+        match (matching, pivots.as_slice()) {
+            (Some(p), _) => {
+                let pivot = generators[pivots[p]].clone();
+                generators[i].multiply_by(&pivot);
+            }
+            (None, [_, _]) => {
+                // The two pivots multiply to the remaining Pauli on this qubit.
+                let (first, second) =
+                    (generators[pivots[0]].clone(), generators[pivots[1]].clone());
+                generators[i].multiply_by(&first);
+                generators[i].multiply_by(&second);
+            }
+            (None, _) => pivots.push(i),
+        }
+    }
+    for &pivot in pivots.iter().rev() {
+        generators.remove(pivot);
+    }
+}
+
+/// Reduce the full stabilizer tableau, as newline-separated stabilizers in
+/// stim's notation, to the generators of the stabilizer group of the given
+/// qubits. The returned stabilizers only include the given qubits, in the
+/// order provided.
+pub fn reduce_stabilizers(stabilizers: &str, qubits: &[u64]) -> Vec<String> {
+    let mut generators: Vec<PauliString> = stabilizers
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| PauliString::parse(line.trim()))
+        .collect();
+    let n_qubits = generators.first().map_or(0, |g| g.paulis.len());
+    let qubits: Vec<usize> = qubits.iter().map(|&q| q as usize).collect();
+    for qubit in (0..n_qubits).filter(|q| !qubits.contains(q)) {
+        trace_out_qubit(&mut generators, qubit);
+    }
+    generators
+        .iter()
+        .map(|generator| generator.restricted_to(&qubits))
+        .collect()
+}
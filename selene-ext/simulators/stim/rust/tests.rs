@@ -1,5 +1,10 @@
 use crate::StimSimulatorFactory;
+use crate::stabilizers::reduce_stabilizers;
 use selene_core::simulator::conformance_testing::run_basic_tests;
+use selene_core::simulator::interface::SimulatorInterfaceFactory;
+use selene_core::simulator::{Pauli, SimulatorInterface};
+use selene_core::utils::MetricValue;
+use std::f64::consts::PI;
 use std::sync::Arc;
 #[test]
 fn basic_conformance_test() {
@@ -7,3 +12,120 @@ fn basic_conformance_test() {
     let args = vec!["".to_string(), "--angle-threshold=0.001".to_string()];
     run_basic_tests(interface, args);
 }
+
+#[test]
+fn queries_do_not_disturb_the_state() {
+    let mut sim = Arc::new(StimSimulatorFactory)
+        .init(3, &["", "--angle-threshold=0.001"])
+        .unwrap();
+    sim.shot_start(0, 0).unwrap();
+    // |+> on qubit 0, (|0> - i|1>)/√2 on qubit 1, |1> on qubit 2
+    sim.rxy(0, PI / 2.0, PI / 2.0).unwrap();
+    sim.rxy(1, PI / 2.0, 0.0).unwrap();
+    sim.rxy(2, PI, 0.0).unwrap();
+    for _ in 0..2 {
+        assert_eq!(
+            sim.marginal_probabilities(&[2, 0]).unwrap(),
+            vec![0.0, 0.5, 0.0, 0.5]
+        );
+        assert_eq!(sim.pauli_expectation(&[(0, Pauli::X)]).unwrap(), 1.0);
+        assert_eq!(sim.pauli_expectation(&[(1, Pauli::Y)]).unwrap(), -1.0);
+        assert_eq!(
+            sim.pauli_expectation(&[(0, Pauli::X), (1, Pauli::Y), (2, Pauli::Z)])
+                .unwrap(),
+            1.0
+        );
+        assert_eq!(sim.pauli_expectation(&[(0, Pauli::Z)]).unwrap(), 0.0);
+    }
+    assert!(sim.marginal_probabilities(&[0, 0]).is_err());
+}
+
+#[test]
+fn stabilizers_are_reduced_to_the_requested_qubits() {
+    // (|0000> + |1111>)/√2
+    let ghz = "+XXXX\n+ZZ__\n+_ZZ_\n+__ZZ\n";
+    assert_eq!(reduce_stabilizers(ghz, &[3, 1]), vec!["+ZZ"]);
+    assert_eq!(reduce_stabilizers(ghz, &[2]), Vec::<String>::new());
+    assert_eq!(reduce_stabilizers(ghz, &[0, 1, 2, 3]).len(), 4);
+    // The same state with -YYX = XXX.ZZ_ as a generator, so that the sign of
+    // ZZ on qubits 0 and 1 depends on the phases picked up in elimination.
+    let ghz = "+XXX\n-YYX\n+_ZZ\n";
+    assert_eq!(reduce_stabilizers(ghz, &[0, 1]), vec!["+ZZ"]);
+    assert_eq!(
+        reduce_stabilizers("+XXX\n+YYX\n+_ZZ\n", &[1, 0]),
+        vec!["-ZZ"]
+    );
+}
+
+#[test]
+fn stabilizer_phases_do_not_overflow_on_many_qubits() {
+    // Eliminating the traced out qubits multiplies -ZZZ...Z by YYY...Y, which
+    // anticommute on every traced qubit, picking up a phase of -i on each.
+    let n = 150;
+    let stabilizers = format!(
+        "+__{}\n+__{}\n-ZZ{}\n",
+        "Y".repeat(n),
+        "X".repeat(n),
+        "Z".repeat(n)
+    );
+    // (-i)^150 = -1 cancels the sign of the third generator.
+    assert_eq!(reduce_stabilizers(&stabilizers, &[0, 1]), vec!["+ZZ"]);
+}
+
+#[test]
+fn postselection_probability_is_tracked() {
+    let mut sim = Arc::new(StimSimulatorFactory)
+        .init(2, &["", "--angle-threshold=0.001"])
+        .unwrap();
+    sim.shot_start(0, 0).unwrap();
+    sim.rxy(0, PI / 2.0, 0.0).unwrap();
+    sim.rxy(1, PI, 0.0).unwrap();
+    sim.postselect(0, true).unwrap();
+    sim.postselect(0, true).unwrap();
+    sim.postselect(1, true).unwrap();
+    assert!(matches!(
+        sim.get_metric(0).unwrap(),
+        Some((name, MetricValue::F64(p))) if name == "cumulative_postselect_probability" && p == 0.5
+    ));
+    assert!(sim.postselect(1, false).is_err());
+    assert!(matches!(
+        sim.get_metric(0).unwrap(),
+        Some((_, MetricValue::F64(p))) if p == 0.0
+    ));
+    sim.shot_start(1, 1).unwrap();
+    assert!(matches!(
+        sim.get_metric(0).unwrap(),
+        Some((_, MetricValue::F64(p))) if p == 1.0
+    ));
+}
+
+#[test]
+fn near_clifford_angles_can_be_rounded() {
+    let mut sim = Arc::new(StimSimulatorFactory)
+        .init(2, &["", "--angle-threshold=0.001"])
+        .unwrap();
+    sim.shot_start(0, 0).unwrap();
+    assert!(sim.rz(0, 0.3).is_err());
+
+    let mut sim = Arc::new(StimSimulatorFactory)
+        .init(2, &["", "--angle-threshold=0.001", "--round-to-clifford"])
+        .unwrap();
+    sim.shot_start(0, 0).unwrap();
+    sim.rz(0, 0.3).unwrap();
+    // Theta rounds to pi, after which phi = 0.1 is treated as zero.
+    sim.rxy(1, PI - 0.2, 0.1).unwrap();
+    assert!(sim.measure(1).unwrap());
+    // Only multiples of pi/2 are Clifford for phi when theta is pi/2.
+    sim.rxy(0, PI / 2.0, PI / 4.0 + 0.1).unwrap();
+    assert!(matches!(
+        sim.get_metric(1).unwrap(),
+        Some((name, MetricValue::U64(4))) if name == "rounded_angles"
+    ));
+    let expected_error = 0.3 + 0.2 + 0.1 + (PI / 2.0 - PI / 4.0 - 0.1);
+    assert!(matches!(
+        sim.get_metric(2).unwrap(),
+        Some((name, MetricValue::F64(error)))
+            if name == "total_rounding_error" && (error - expected_error).abs() < 1e-12
+    ));
+    assert!(sim.get_metric(3).unwrap().is_none());
+}
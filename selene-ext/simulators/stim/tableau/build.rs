@@ -5,8 +5,8 @@ fn main() {
         return;
     }
 
-    println!("cargo:rerun-if-changed=c_interface");
-    let dst = cmake::Config::new("c_interface").build();
+    println!("cargo:rerun-if-changed=../c_interface");
+    let dst = cmake::Config::new("../c_interface").build();
     println!(
         "cargo:rustc-link-search=native={}",
         dst.join("lib").display()
@@ -1,11 +1,13 @@
 #![allow(unused)]
-use std::os::raw::{c_char, c_uint, c_ulonglong, c_void};
+use std::os::raw::{c_char, c_schar, c_uchar, c_uint, c_ulonglong, c_void};
 unsafe extern "C" {
     pub fn stim_tableausimulator_min_create(
         numQubits: c_uint,
         randomSeed: c_ulonglong,
     ) -> *mut c_void;
     pub fn stim_tableausimulator_min_destroy(rawptr: *mut c_void);
+    pub fn stim_tableausimulator_min_clone(rawptr: *mut c_void) -> *mut c_void;
+    pub fn stim_tableausimulator_min_copy_state(rawptr: *mut c_void, source: *mut c_void);
     pub fn stim_tableausimulator_min_do_X(rawptr: *mut c_void, q: c_uint);
     pub fn stim_tableausimulator_min_do_Y(rawptr: *mut c_void, q: c_uint);
     pub fn stim_tableausimulator_min_do_Z(rawptr: *mut c_void, q: c_uint);
@@ -26,11 +28,18 @@ unsafe extern "C" {
     pub fn stim_tableausimulator_min_do_ZCY(rawptr: *mut c_void, qc: c_uint, qt: c_uint);
     pub fn stim_tableausimulator_min_do_ZCZ(rawptr: *mut c_void, qc: c_uint, qt: c_uint);
     pub fn stim_tableausimulator_min_do_MZ(rawptr: *mut c_void, q: c_uint) -> bool;
+    pub fn stim_tableausimulator_min_peek_pauli(
+        rawptr: *mut c_void,
+        qubits: *const c_uint,
+        paulis: *const c_uchar,
+        n: c_uint,
+    ) -> c_schar;
     pub fn stim_tableausimulator_min_do_POSTSELECT_Z(
         rawptr: *mut c_void,
         q: c_uint,
         target_value: bool,
     ) -> bool;
+    pub fn stim_tableausimulator_min_to_state_vector(rawptr: *mut c_void, write: *mut f32);
     pub fn stim_tableausimulator_min_get_stabilizers(rawptr: *mut c_void, write: *mut *mut c_char);
     pub fn stim_tableausimulator_min_free_stabilizers(written: *mut c_char);
 }
@@ -0,0 +1,6 @@
+//! Rust bindings to the tableau simulator provided by Stim's C interface,
+//! shared by the Stim and hybrid simulator plugins.
+mod bindings;
+mod wrapper;
+
+pub use wrapper::TableauSimulatorMin;
@@ -1,5 +1,3 @@
-#![allow(dead_code)]
-
 use crate::bindings;
 
 pub struct TableauSimulatorMin {
@@ -11,6 +9,17 @@ impl TableauSimulatorMin {
             ptr: unsafe { bindings::stim_tableausimulator_min_create(num_qubits, random_seed) },
         }
     }
+    /// Create an independent copy of the simulator, including its random state.
+    pub fn duplicate(&self) -> Self {
+        Self {
+            ptr: unsafe { bindings::stim_tableausimulator_min_clone(self.ptr) },
+        }
+    }
+    /// Replace the stabilizer state with that of `source`, keeping the
+    /// current random state.
+    pub fn copy_state_from(&mut self, source: &Self) {
+        unsafe { bindings::stim_tableausimulator_min_copy_state(self.ptr, source.ptr) }
+    }
     pub fn x(&mut self, q: u32) {
         unsafe { bindings::stim_tableausimulator_min_do_X(self.ptr, q) }
     }
@@ -71,9 +80,31 @@ impl TableauSimulatorMin {
     pub fn cz(&mut self, q_control: u32, q_target: u32) {
         unsafe { bindings::stim_tableausimulator_min_do_ZCZ(self.ptr, q_control, q_target) }
     }
+    /// The expectation value of a Pauli product, as -1, 0 or +1, without
+    /// modifying the state. Pauli codes are 0 => I, 1 => X, 2 => Y, 3 => Z.
+    pub fn peek_pauli(&self, qubits: &[u32], paulis: &[u8]) -> i8 {
+        unsafe {
+            bindings::stim_tableausimulator_min_peek_pauli(
+                self.ptr,
+                qubits.as_ptr(),
+                paulis.as_ptr(),
+                qubits.len() as u32,
+            )
+        }
+    }
     pub fn postselect_z(&mut self, q: u32, target_value: bool) -> bool {
         unsafe { bindings::stim_tableausimulator_min_do_POSTSELECT_Z(self.ptr, q, target_value) }
     }
+    /// The amplitudes of the stabilizer state, as interleaved real and
+    /// imaginary parts, with qubit 0 as the least significant bit of the
+    /// amplitude index.
+    pub fn to_state_vector(&self, num_qubits: u32) -> Vec<f32> {
+        let mut amplitudes = vec![0.0; 2usize << num_qubits];
+        unsafe {
+            bindings::stim_tableausimulator_min_to_state_vector(self.ptr, amplitudes.as_mut_ptr())
+        };
+        amplitudes
+    }
     pub fn get_stabilisers(&mut self) -> String {
         let mut stringptr = std::ptr::null_mut();
         unsafe { bindings::stim_tableausimulator_min_get_stabilizers(self.ptr, &mut stringptr) };
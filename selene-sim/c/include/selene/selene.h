@@ -59,6 +59,16 @@ struct selene_u64_result_t selene_custom_runtime_call(struct SeleneInstance *ins
                                                       const uint8_t *data,
                                                       uint64_t data_length);
 
+/**
+ * Applies the phase e^{i phases[k]} to each basis state k of the provided
+ * qubits, where `phases` has 2^qubit_ids_length elements and the first qubit
+ * is the least significant bit of k.
+ */
+struct selene_void_result_t selene_diagonal_phase(struct SeleneInstance *instance,
+                                                  const uint64_t *qubit_ids,
+                                                  uint64_t qubit_ids_length,
+                                                  const double *phases);
+
 struct selene_void_result_t selene_dump_state(struct SeleneInstance *instance,
                                               struct selene_string_t message,
                                               const uint64_t *qubits,
@@ -280,6 +290,26 @@ struct selene_u64_result_t selene_shot_count(struct SeleneInstance *instance);
 struct selene_void_result_t selene_simulate_delay(struct SeleneInstance *instance,
                                                   uint64_t delay);
 
+/**
+ * Applies a general two-qubit unitary, provided as a 4x4 row-major matrix of
+ * interleaved real and imaginary parts (32 values), with the first qubit as
+ * the least significant bit of the matrix indices.
+ */
+struct selene_void_result_t selene_two_qubit_unitary(struct SeleneInstance *instance,
+                                                     uint64_t qubit_id,
+                                                     uint64_t qubit_id2,
+                                                     const double *matrix);
+
+/**
+ * Applies U(theta, phi, lambda) = RZ(phi) RY(theta) RZ(lambda), up to a
+ * global phase, to the provided qubit.
+ */
+struct selene_void_result_t selene_u(struct SeleneInstance *instance,
+                                     uint64_t qubit_id,
+                                     double theta,
+                                     double phi,
+                                     double lambda);
+
 /**
  * Read the output stream buffer from the point of the last read, up to a maximum length, copying it into the provided pointer.
  *
@@ -1,13 +1,45 @@
+from selene_calibrated_error_model_plugin import (
+    CalibratedPlugin as CalibratedErrorModel,
+)
+from selene_coherent_error_model_plugin import (
+    CoherentPlugin as CoherentErrorModel,
+)
+from selene_correlated_readout_error_model_plugin import (
+    CorrelatedReadoutPlugin as CorrelatedReadoutErrorModel,
+)
+from selene_crosstalk_error_model_plugin import (
+    CrosstalkPlugin as CrosstalkErrorModel,
+)
 from selene_depolarizing_error_model_plugin import (
     DepolarizingPlugin as DepolarizingErrorModel,
 )
+from selene_erasure_error_model_plugin import (
+    ErasurePlugin as ErasureErrorModel,
+)
 from selene_ideal_error_model_plugin import IdealPlugin as IdealErrorModel
+from selene_idle_dephasing_error_model_plugin import (
+    IdleDephasingPlugin as IdleDephasingErrorModel,
+)
+from selene_leakage_error_model_plugin import (
+    LeakagePlugin as LeakageErrorModel,
+)
+from selene_relaxation_error_model_plugin import (
+    RelaxationPlugin as RelaxationErrorModel,
+)
 from selene_simple_leakage_error_model_plugin import (
     SimpleLeakagePlugin as SimpleLeakageErrorModel,
 )
 
 __all__ = [
+    "CalibratedErrorModel",
+    "CoherentErrorModel",
+    "CorrelatedReadoutErrorModel",
+    "CrosstalkErrorModel",
     "DepolarizingErrorModel",
+    "ErasureErrorModel",
     "IdealErrorModel",
+    "IdleDephasingErrorModel",
+    "LeakageErrorModel",
+    "RelaxationErrorModel",
     "SimpleLeakageErrorModel",
 ]
@@ -1,13 +1,33 @@
 from selene_stim_plugin import StimPlugin as Stim
 from selene_quest_plugin import QuestPlugin as Quest
 from selene_coinflip_plugin import CoinflipPlugin as Coinflip
+from selene_bitstring_plugin import BitstringPlugin as Bitstring
+from selene_density_matrix_plugin import DensityMatrixPlugin as DensityMatrix
+from selene_mps_plugin import MpsPlugin as MPS
+from selene_stabilizer_rank_plugin import StabilizerRankPlugin as StabilizerRank
+from selene_pauli_frame_plugin import PauliFramePlugin as PauliFrame
+from selene_qutrit_plugin import QutritPlugin as Qutrit
 from selene_classical_replay_plugin import ClassicalReplayPlugin as ClassicalReplay
 from selene_quantum_replay_plugin import QuantumReplayPlugin as QuantumReplay
+from selene_cross_check_plugin import CrossCheckPlugin as CrossCheck
+from selene_fusion_plugin import FusionPlugin as Fusion
+from selene_statevector_plugin import StatevectorPlugin as Statevector
+from selene_hybrid_plugin import HybridPlugin as Hybrid
 
 __all__ = [
     "Stim",
     "Quest",
     "Coinflip",
+    "Bitstring",
+    "DensityMatrix",
+    "MPS",
+    "StabilizerRank",
+    "PauliFrame",
+    "Qutrit",
     "ClassicalReplay",
     "QuantumReplay",
+    "CrossCheck",
+    "Fusion",
+    "Statevector",
+    "Hybrid",
 ]
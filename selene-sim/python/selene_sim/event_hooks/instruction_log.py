@@ -240,6 +240,93 @@ class Rz(Operation):
         return Rz(qubit=next(it), theta=next(it))
 
 
+@dataclass
+class U(Operation):
+    qubit: int
+    theta: float
+    phi: float
+    lambda_: float
+
+    def append_to_circuit(self, circuit: "pytket.Circuit"):
+        assert PYTKET_AVAILABLE, "pytket is not available"
+        circuit.U3(
+            angle0=self.theta / math.pi,
+            angle1=self.phi / math.pi,
+            angle2=self.lambda_ / math.pi,
+            qubit=self.qubit,
+        )
+
+    def to_dict(self) -> dict:
+        return {
+            "op": "U",
+            "qubit": self.qubit,
+            "theta": self.theta,
+            "phi": self.phi,
+            "lambda": self.lambda_,
+        }
+
+    @staticmethod
+    def from_iterator(it: Iterator):
+        return U(qubit=next(it), theta=next(it), phi=next(it), lambda_=next(it))
+
+
+@dataclass
+class TwoQubitUnitary(Operation):
+    qubit0: int
+    qubit1: int
+    # row-major 4x4 matrix, with qubit0 as the least significant bit
+    matrix: list[list[complex]]
+
+    def append_to_circuit(self, circuit: "pytket.Circuit"):
+        assert PYTKET_AVAILABLE, "pytket is not available"
+        import numpy as np
+
+        # pytket treats the first qubit as the most significant bit
+        box = pytket.circuit.Unitary2qBox(np.array(self.matrix))
+        circuit.add_unitary2qbox(box, self.qubit1, self.qubit0)
+
+    def to_dict(self) -> dict:
+        return {
+            "op": "TwoQubitUnitary",
+            "qubit0": self.qubit0,
+            "qubit1": self.qubit1,
+            "matrix": self.matrix,
+        }
+
+    @staticmethod
+    def from_iterator(it: Iterator):
+        qubit0 = next(it)
+        qubit1 = next(it)
+        elements = [complex(next(it), next(it)) for _ in range(16)]
+        matrix = [elements[4 * row : 4 * row + 4] for row in range(4)]
+        return TwoQubitUnitary(qubit0=qubit0, qubit1=qubit1, matrix=matrix)
+
+
+@dataclass
+class DiagonalPhase(Operation):
+    qubits: list[int]
+    # the first qubit is the least significant bit of the phase index
+    phases: list[float]
+
+    def append_to_circuit(self, circuit: "pytket.Circuit"):
+        assert PYTKET_AVAILABLE, "pytket is not available"
+        import numpy as np
+
+        # pytket treats the first qubit as the most significant bit
+        box = pytket.circuit.DiagonalBox(np.exp(1j * np.array(self.phases)))
+        circuit.add_diagonal_box(box, list(reversed(self.qubits)))
+
+    def to_dict(self) -> dict:
+        return {"op": "DiagonalPhase", "qubits": self.qubits, "phases": self.phases}
+
+    @staticmethod
+    def from_iterator(it: Iterator):
+        n_qubits = next(it)
+        qubits = [next(it) for _ in range(n_qubits)]
+        phases = [next(it) for _ in range(1 << n_qubits)]
+        return DiagonalPhase(qubits=qubits, phases=phases)
+
+
 @dataclass
 class Reset(Operation):
     qubit: int
@@ -397,6 +484,12 @@ class Instruction:
                 operation = MeasureLeakedRequest.from_iterator(it)
             case 13:
                 operation = ClassicalDelay.from_iterator(it)
+            case 14:
+                operation = U.from_iterator(it)
+            case 15:
+                operation = TwoQubitUnitary.from_iterator(it)
+            case 16:
+                operation = DiagonalPhase.from_iterator(it)
         if operation is None:
             raise ValueError(f"Unknown instruction operation index {operation_idx}")
         return Instruction(source=source, operation=operation)
@@ -543,6 +636,43 @@ class ShotInstructions:
                             index=user_program_event_index,
                         )
                         user_program_event_index += 1
+                    case U(qubit=qubit, theta=theta, phi=phi, lambda_=lambda_):
+                        trace.add_user_program_event(
+                            GateEvent(
+                                gate_name="U",
+                                qubits=[qubit],
+                                params=[theta, phi, lambda_],
+                            ),
+                            index=user_program_event_index,
+                        )
+                        user_program_event_index += 1
+                    case TwoQubitUnitary(
+                        qubit0=qubit0, qubit1=qubit1, matrix=matrix
+                    ):
+                        trace.add_user_program_event(
+                            GateEvent(
+                                gate_name="TwoQubitUnitary",
+                                qubits=[qubit0, qubit1],
+                                params=[
+                                    part
+                                    for row in matrix
+                                    for z in row
+                                    for part in (z.real, z.imag)
+                                ],
+                            ),
+                            index=user_program_event_index,
+                        )
+                        user_program_event_index += 1
+                    case DiagonalPhase(qubits=qubits, phases=phases):
+                        trace.add_user_program_event(
+                            GateEvent(
+                                gate_name="DiagonalPhase",
+                                qubits=qubits,
+                                params=phases,
+                            ),
+                            index=user_program_event_index,
+                        )
+                        user_program_event_index += 1
                     case Reset(qubit=qubit):
                         trace.add_user_program_event(
                             ResetEvent(qubit=qubit), index=user_program_event_index
@@ -619,6 +749,43 @@ class ShotInstructions:
                             start_time_ns,
                             end_time_ns,
                         )
+                    case U(qubit=qubit, theta=theta, phi=phi, lambda_=lambda_):
+                        trace.add_runtime_event(
+                            GateEvent(
+                                gate_name="U",
+                                qubits=[qubit],
+                                params=[theta, phi, lambda_],
+                            ),
+                            start_time_ns,
+                            end_time_ns,
+                        )
+                    case TwoQubitUnitary(
+                        qubit0=qubit0, qubit1=qubit1, matrix=matrix
+                    ):
+                        trace.add_runtime_event(
+                            GateEvent(
+                                gate_name="TwoQubitUnitary",
+                                qubits=[qubit0, qubit1],
+                                params=[
+                                    part
+                                    for row in matrix
+                                    for z in row
+                                    for part in (z.real, z.imag)
+                                ],
+                            ),
+                            start_time_ns,
+                            end_time_ns,
+                        )
+                    case DiagonalPhase(qubits=qubits, phases=phases):
+                        trace.add_runtime_event(
+                            GateEvent(
+                                gate_name="DiagonalPhase",
+                                qubits=qubits,
+                                params=phases,
+                            ),
+                            start_time_ns,
+                            end_time_ns,
+                        )
                     case CustomOperation(tag=tag, data=data):
                         trace.add_runtime_event(
                             CustomEvent(payload=OpaquePayload(tag=tag, data=data)),
@@ -251,6 +251,28 @@ class SeleneSimLib(ctypes.CDLL):
             ctypes.c_double,
         ]
         self.selene_rzz.restype = selene_void_result_t
+        self.selene_u.argtypes = [
+            SeleneInstancePtr,
+            ctypes.c_uint64,
+            ctypes.c_double,
+            ctypes.c_double,
+            ctypes.c_double,
+        ]
+        self.selene_u.restype = selene_void_result_t
+        self.selene_two_qubit_unitary.argtypes = [
+            SeleneInstancePtr,
+            ctypes.c_uint64,
+            ctypes.c_uint64,
+            ctypes.POINTER(ctypes.c_double),
+        ]
+        self.selene_two_qubit_unitary.restype = selene_void_result_t
+        self.selene_diagonal_phase.argtypes = [
+            SeleneInstancePtr,
+            ctypes.POINTER(ctypes.c_uint64),
+            ctypes.c_uint64,
+            ctypes.POINTER(ctypes.c_double),
+        ]
+        self.selene_diagonal_phase.restype = selene_void_result_t
         self.selene_set_tc.argtypes = [SeleneInstancePtr, ctypes.c_uint64]
         self.selene_set_tc.restype = selene_void_result_t
         self.selene_shot_count.argtypes = [SeleneInstancePtr]
@@ -551,6 +573,43 @@ class InteractiveFullStack:
     def rzz(self, qubit_a: Qubit, qubit_b: Qubit, theta: float) -> None:
         self._call_void("selene_rzz", qubit_a.id, qubit_b.id, theta)
 
+    def u(self, qubit: Qubit, theta: float, phi: float, lambda_: float) -> None:
+        self._call_void("selene_u", qubit.id, theta, phi, lambda_)
+
+    def two_qubit_unitary(
+        self, qubit_a: Qubit, qubit_b: Qubit, matrix: list[list[complex]]
+    ) -> None:
+        """
+        Apply a 4x4 unitary, given as a list of rows, to the two qubits.
+        qubit_a is the least significant bit of the matrix indices.
+        """
+        values = [
+            part
+            for row in matrix
+            for element in row
+            for part in (complex(element).real, complex(element).imag)
+        ]
+        if len(values) != 32:
+            raise ValueError("A two-qubit unitary must be a 4x4 matrix")
+        self._call_void(
+            "selene_two_qubit_unitary",
+            qubit_a.id,
+            qubit_b.id,
+            (ctypes.c_double * 32)(*values),
+        )
+
+    def diagonal_phase(self, qubits: list[Qubit], phases: list[float]) -> None:
+        """
+        Apply the phase e^{i phases[k]} to each basis state k of the qubits,
+        where the first qubit is the least significant bit of k.
+        """
+        self._call_void(
+            "selene_diagonal_phase",
+            (ctypes.c_uint64 * len(qubits))(*[q.id for q in qubits]),
+            len(qubits),
+            (ctypes.c_double * len(phases))(*phases),
+        )
+
     def get_state(self, qubits: list[Qubit]):
         if not hasattr(self.simulator, "extract_states"):
             raise AttributeError(
@@ -47,6 +47,30 @@ CUSTOM_CB = ctypes.CFUNCTYPE(
 SET_BATCH_TIME_CB = ctypes.CFUNCTYPE(
     None, SeleneRuntimeGetOperationInstance, ctypes.c_uint64, ctypes.c_uint64
 )
+U_CB = ctypes.CFUNCTYPE(
+    None,
+    SeleneRuntimeGetOperationInstance,
+    ctypes.c_uint64,
+    ctypes.c_double,
+    ctypes.c_double,
+    ctypes.c_double,
+)
+TWO_QUBIT_UNITARY_CB = ctypes.CFUNCTYPE(
+    None,
+    SeleneRuntimeGetOperationInstance,
+    ctypes.c_uint64,
+    ctypes.c_uint64,
+    ctypes.POINTER(ctypes.c_double),
+)
+DIAGONAL_PHASE_CB = ctypes.CFUNCTYPE(
+    None,
+    SeleneRuntimeGetOperationInstance,
+    ctypes.POINTER(ctypes.c_uint64),
+    ctypes.c_uint64,
+    ctypes.POINTER(ctypes.c_double),
+)
+QALLOC_CB = ctypes.CFUNCTYPE(None, SeleneRuntimeGetOperationInstance, ctypes.c_uint64)
+QFREE_CB = ctypes.CFUNCTYPE(None, SeleneRuntimeGetOperationInstance, ctypes.c_uint64)
 
 
 class SeleneRuntimeGetOperationInterface(ctypes.Structure):
@@ -59,6 +83,11 @@ class SeleneRuntimeGetOperationInterface(ctypes.Structure):
         ("reset_fn", RESET_CB),
         ("custom_fn", CUSTOM_CB),
         ("set_batch_time_fn", SET_BATCH_TIME_CB),
+        ("u_fn", U_CB),
+        ("two_qubit_unitary_fn", TWO_QUBIT_UNITARY_CB),
+        ("diagonal_phase_fn", DIAGONAL_PHASE_CB),
+        ("qalloc_fn", QALLOC_CB),
+        ("qfree_fn", QFREE_CB),
     ]
 
 
@@ -93,6 +122,28 @@ class RZZGateOperation:
     theta: float
 
 
+@dataclass
+class UGateOperation:
+    qubit_id: int
+    theta: float
+    phi: float
+    lambda_: float
+
+
+@dataclass
+class TwoQubitUnitaryGateOperation:
+    qubit_id_a: int
+    qubit_id_b: int
+    # row-major 4x4 matrix, with qubit_id_a as the least significant bit
+    matrix: list[complex]
+
+
+@dataclass
+class DiagonalPhaseGateOperation:
+    qubit_ids: list[int]
+    phases: list[float]
+
+
 @dataclass
 class CustomOperation:
     tag: int
@@ -105,14 +156,29 @@ class MeasureLeakedOperation:
     result_id: int
 
 
+@dataclass
+class QAllocOperation:
+    qubit_id: int
+
+
+@dataclass
+class QFreeOperation:
+    qubit_id: int
+
+
 RuntimeOperation = (
     MeasureOperation
     | ResetOperation
     | RXYGateOperation
     | RZGateOperation
     | RZZGateOperation
+    | UGateOperation
+    | TwoQubitUnitaryGateOperation
+    | DiagonalPhaseGateOperation
     | CustomOperation
     | MeasureLeakedOperation
+    | QAllocOperation
+    | QFreeOperation
 )
 
 
@@ -140,6 +206,22 @@ class OperationBatch:
         self.operations.append(RZZGateOperation(qubit_id_a, qubit_id_b, theta))
         self.invoked = True
 
+    def u(self, qubit_id: int, theta: float, phi: float, lambda_: float):
+        self.operations.append(UGateOperation(qubit_id, theta, phi, lambda_))
+        self.invoked = True
+
+    def two_qubit_unitary(
+        self, qubit_id_a: int, qubit_id_b: int, matrix: list[complex]
+    ):
+        self.operations.append(
+            TwoQubitUnitaryGateOperation(qubit_id_a, qubit_id_b, matrix)
+        )
+        self.invoked = True
+
+    def diagonal_phase(self, qubit_ids: list[int], phases: list[float]):
+        self.operations.append(DiagonalPhaseGateOperation(qubit_ids, phases))
+        self.invoked = True
+
     def measure(self, qubit_id: int, result_id: int):
         self.operations.append(MeasureOperation(qubit_id, result_id))
         self.invoked = True
@@ -156,6 +238,14 @@ class OperationBatch:
         self.operations.append(CustomOperation(tag, data))
         self.invoked = True
 
+    def qalloc(self, qubit_id: int):
+        self.operations.append(QAllocOperation(qubit_id))
+        self.invoked = True
+
+    def qfree(self, qubit_id: int):
+        self.operations.append(QFreeOperation(qubit_id))
+        self.invoked = True
+
     def __repr__(self) -> str:
         return f"OperationBatch(start_time_nanos={self.start_time_nanos}, duration_nanos={self.duration_nanos}, operations={self.operations})"
 
@@ -185,6 +275,39 @@ def callback_rzz(
     OperationBatch.from_ptr(instance).rzz(qubit_id_a, qubit_id_b, theta)
 
 
+def callback_u(
+    instance: SeleneRuntimeGetOperationInstance,
+    qubit_id: int,
+    theta: float,
+    phi: float,
+    lambda_: float,
+):
+    OperationBatch.from_ptr(instance).u(qubit_id, theta, phi, lambda_)
+
+
+def callback_two_qubit_unitary(
+    instance: SeleneRuntimeGetOperationInstance,
+    qubit_id_a: int,
+    qubit_id_b: int,
+    matrix_ptr: ctypes._Pointer,
+):
+    matrix = [complex(matrix_ptr[2 * i], matrix_ptr[2 * i + 1]) for i in range(16)]
+    OperationBatch.from_ptr(instance).two_qubit_unitary(
+        qubit_id_a, qubit_id_b, matrix
+    )
+
+
+def callback_diagonal_phase(
+    instance: SeleneRuntimeGetOperationInstance,
+    qubit_ids_ptr: ctypes._Pointer,
+    n_qubits: int,
+    phases_ptr: ctypes._Pointer,
+):
+    qubit_ids = qubit_ids_ptr[:n_qubits]
+    phases = phases_ptr[: 1 << n_qubits]
+    OperationBatch.from_ptr(instance).diagonal_phase(qubit_ids, phases)
+
+
 def callback_measure(
     instance: SeleneRuntimeGetOperationInstance, qubit_id: int, result_id: int
 ):
@@ -211,6 +334,14 @@ def callback_custom(
     OperationBatch.from_ptr(instance).custom(tag, data)
 
 
+def callback_qalloc(instance: SeleneRuntimeGetOperationInstance, qubit_id: int):
+    OperationBatch.from_ptr(instance).qalloc(qubit_id)
+
+
+def callback_qfree(instance: SeleneRuntimeGetOperationInstance, qubit_id: int):
+    OperationBatch.from_ptr(instance).qfree(qubit_id)
+
+
 def callback_set_batch_time(
     instance: SeleneRuntimeGetOperationInstance, start_time: int, duration: int
 ):
@@ -226,6 +357,11 @@ OPERATION_BATCH_CALLBACKS = SeleneRuntimeGetOperationInterface(
     reset_fn=RESET_CB(callback_reset),
     custom_fn=CUSTOM_CB(callback_custom),
     set_batch_time_fn=SET_BATCH_TIME_CB(callback_set_batch_time),
+    u_fn=U_CB(callback_u),
+    two_qubit_unitary_fn=TWO_QUBIT_UNITARY_CB(callback_two_qubit_unitary),
+    diagonal_phase_fn=DIAGONAL_PHASE_CB(callback_diagonal_phase),
+    qalloc_fn=QALLOC_CB(callback_qalloc),
+    qfree_fn=QFREE_CB(callback_qfree),
 )
 
 SeleneRuntimeInstancePtr = ctypes.POINTER(SeleneRuntimeInstance)
@@ -301,6 +437,28 @@ class SeleneSimRuntimeLib(ctypes.CDLL):
             ctypes.c_double,
         ]
         self.selene_runtime_rzz_gate.restype = ctypes.c_int32
+        self.selene_runtime_u_gate.argtypes = [
+            SeleneRuntimeInstancePtr,
+            ctypes.c_uint64,
+            ctypes.c_double,
+            ctypes.c_double,
+            ctypes.c_double,
+        ]
+        self.selene_runtime_u_gate.restype = ctypes.c_int32
+        self.selene_runtime_two_qubit_unitary_gate.argtypes = [
+            SeleneRuntimeInstancePtr,
+            ctypes.c_uint64,
+            ctypes.c_uint64,
+            ctypes.POINTER(ctypes.c_double),
+        ]
+        self.selene_runtime_two_qubit_unitary_gate.restype = ctypes.c_int32
+        self.selene_runtime_diagonal_phase_gate.argtypes = [
+            SeleneRuntimeInstancePtr,
+            ctypes.POINTER(ctypes.c_uint64),
+            ctypes.c_uint64,
+            ctypes.POINTER(ctypes.c_double),
+        ]
+        self.selene_runtime_diagonal_phase_gate.restype = ctypes.c_int32
         self.selene_runtime_measure.argtypes = [
             SeleneRuntimeInstancePtr,
             ctypes.c_uint64,
@@ -449,6 +607,36 @@ class InteractiveRuntime:
         ):
             raise RuntimeError("Failed to apply RZZ operation on Selene runtime")
 
+    def u(self, qubit: int, theta: float, phi: float, lambda_: float):
+        if 0 != self._lib.selene_runtime_u_gate(
+            self._instance, qubit, theta, phi, lambda_
+        ):
+            raise RuntimeError("Failed to apply U operation on Selene runtime")
+
+    def two_qubit_unitary(self, qubit_a: int, qubit_b: int, matrix: list[complex]):
+        if len(matrix) != 16:
+            raise ValueError("A two-qubit unitary requires 16 matrix elements")
+        values = [part for z in matrix for part in (z.real, z.imag)]
+        if 0 != self._lib.selene_runtime_two_qubit_unitary_gate(
+            self._instance, qubit_a, qubit_b, (ctypes.c_double * 32)(*values)
+        ):
+            raise RuntimeError(
+                "Failed to apply two-qubit unitary operation on Selene runtime"
+            )
+
+    def diagonal_phase(self, qubits: list[int], phases: list[float]):
+        if len(phases) != 1 << len(qubits):
+            raise ValueError("A diagonal phase gate requires 2^len(qubits) phases")
+        if 0 != self._lib.selene_runtime_diagonal_phase_gate(
+            self._instance,
+            (ctypes.c_uint64 * len(qubits))(*qubits),
+            len(qubits),
+            (ctypes.c_double * len(phases))(*phases),
+        ):
+            raise RuntimeError(
+                "Failed to apply diagonal phase operation on Selene runtime"
+            )
+
     def measure(self, qubit: int) -> int:
         future_ref = ctypes.c_uint64()
         if 0 != self._lib.selene_runtime_measure(
@@ -62,6 +62,28 @@ class SeleneSimSimulatorLib(ctypes.CDLL):
             ctypes.c_double,
         ]
         self.selene_simulator_operation_rzz.restype = ctypes.c_int32
+        self.selene_simulator_operation_u.argtypes = [
+            SeleneSimulatorInstancePtr,
+            ctypes.c_uint64,
+            ctypes.c_double,
+            ctypes.c_double,
+            ctypes.c_double,
+        ]
+        self.selene_simulator_operation_u.restype = ctypes.c_int32
+        self.selene_simulator_operation_two_qubit_unitary.argtypes = [
+            SeleneSimulatorInstancePtr,
+            ctypes.c_uint64,
+            ctypes.c_uint64,
+            ctypes.POINTER(ctypes.c_double),
+        ]
+        self.selene_simulator_operation_two_qubit_unitary.restype = ctypes.c_int32
+        self.selene_simulator_operation_diagonal_phase.argtypes = [
+            SeleneSimulatorInstancePtr,
+            ctypes.POINTER(ctypes.c_uint64),
+            ctypes.c_uint64,
+            ctypes.POINTER(ctypes.c_double),
+        ]
+        self.selene_simulator_operation_diagonal_phase.restype = ctypes.c_int32
         self.selene_simulator_operation_measure.argtypes = [
             SeleneSimulatorInstancePtr,
             ctypes.c_uint64,
@@ -78,6 +100,86 @@ class SeleneSimSimulatorLib(ctypes.CDLL):
             ctypes.c_uint64,
         ]
         self.selene_simulator_operation_reset.restype = ctypes.c_int32
+        self.selene_simulator_operation_qalloc.argtypes = [
+            SeleneSimulatorInstancePtr,
+            ctypes.c_uint64,
+        ]
+        self.selene_simulator_operation_qalloc.restype = ctypes.c_int32
+        self.selene_simulator_operation_qfree.argtypes = [
+            SeleneSimulatorInstancePtr,
+            ctypes.c_uint64,
+        ]
+        self.selene_simulator_operation_qfree.restype = ctypes.c_int32
+        self.selene_simulator_operation_measure_leaked.argtypes = [
+            SeleneSimulatorInstancePtr,
+            ctypes.c_uint64,
+        ]
+        self.selene_simulator_operation_measure_leaked.restype = ctypes.c_int32
+        self.selene_simulator_operation_leakage_rxy.argtypes = [
+            SeleneSimulatorInstancePtr,
+            ctypes.c_uint64,
+            ctypes.c_double,
+            ctypes.c_double,
+        ]
+        self.selene_simulator_operation_leakage_rxy.restype = ctypes.c_int32
+        self.selene_simulator_snapshot.argtypes = [
+            SeleneSimulatorInstancePtr,
+            ctypes.POINTER(ctypes.c_uint64),
+        ]
+        self.selene_simulator_snapshot.restype = ctypes.c_int32
+        self.selene_simulator_restore.argtypes = [
+            SeleneSimulatorInstancePtr,
+            ctypes.c_uint64,
+        ]
+        self.selene_simulator_restore.restype = ctypes.c_int32
+        self.selene_simulator_release_snapshot.argtypes = [
+            SeleneSimulatorInstancePtr,
+            ctypes.c_uint64,
+        ]
+        self.selene_simulator_release_snapshot.restype = ctypes.c_int32
+        self.selene_simulator_marginal_probabilities.argtypes = [
+            SeleneSimulatorInstancePtr,
+            ctypes.POINTER(ctypes.c_uint64),
+            ctypes.c_uint64,
+            ctypes.POINTER(ctypes.c_double),
+        ]
+        self.selene_simulator_marginal_probabilities.restype = ctypes.c_int32
+        self.selene_simulator_pauli_expectation.argtypes = [
+            SeleneSimulatorInstancePtr,
+            ctypes.POINTER(ctypes.c_uint64),
+            ctypes.POINTER(ctypes.c_uint8),
+            ctypes.c_uint64,
+            ctypes.POINTER(ctypes.c_double),
+        ]
+        self.selene_simulator_pauli_expectation.restype = ctypes.c_int32
+        self.selene_simulator_channel_pauli.argtypes = [
+            SeleneSimulatorInstancePtr,
+            ctypes.c_uint64,
+            ctypes.c_double,
+            ctypes.c_double,
+            ctypes.c_double,
+        ]
+        self.selene_simulator_channel_pauli.restype = ctypes.c_int32
+        self.selene_simulator_channel_amplitude_damping.argtypes = [
+            SeleneSimulatorInstancePtr,
+            ctypes.c_uint64,
+            ctypes.c_double,
+        ]
+        self.selene_simulator_channel_amplitude_damping.restype = ctypes.c_int32
+        self.selene_simulator_channel_phase_damping.argtypes = [
+            SeleneSimulatorInstancePtr,
+            ctypes.c_uint64,
+            ctypes.c_double,
+        ]
+        self.selene_simulator_channel_phase_damping.restype = ctypes.c_int32
+        self.selene_simulator_channel_kraus.argtypes = [
+            SeleneSimulatorInstancePtr,
+            ctypes.POINTER(ctypes.c_uint64),
+            ctypes.c_uint64,
+            ctypes.POINTER(ctypes.c_double),
+            ctypes.c_uint64,
+        ]
+        self.selene_simulator_channel_kraus.restype = ctypes.c_int32
         self.selene_simulator_get_metrics.argtypes = [
             SeleneSimulatorInstancePtr,
             ctypes.c_uint8,
@@ -147,16 +249,87 @@ class InteractiveSimulator:
         ):
             raise RuntimeError("Failed to apply RZZ operation on Selene simulator")
 
+    def u(self, qubit: int, theta: float, phi: float, lambda_: float):
+        if 0 != self._lib.selene_simulator_operation_u(
+            self._instance, qubit, theta, phi, lambda_
+        ):
+            raise RuntimeError("Failed to apply U operation on Selene simulator")
+
+    def two_qubit_unitary(
+        self, qubit_a: int, qubit_b: int, matrix: list[list[complex]]
+    ):
+        """
+        Apply a 4x4 unitary, given as a list of rows, to the two qubits.
+        qubit_a is the least significant bit of the matrix indices.
+        """
+        values = [
+            part
+            for row in matrix
+            for element in row
+            for part in (complex(element).real, complex(element).imag)
+        ]
+        if len(values) != 32:
+            raise ValueError("A two-qubit unitary must be a 4x4 matrix")
+        if 0 != self._lib.selene_simulator_operation_two_qubit_unitary(
+            self._instance, qubit_a, qubit_b, (ctypes.c_double * 32)(*values)
+        ):
+            raise RuntimeError(
+                "Failed to apply two-qubit unitary operation on Selene simulator"
+            )
+
+    def diagonal_phase(self, qubits: list[int], phases: list[float]):
+        """
+        Apply the phase e^{i phases[k]} to each basis state k of the qubits,
+        where the first qubit is the least significant bit of k.
+        """
+        if len(phases) != 1 << len(qubits):
+            raise ValueError("A diagonal phase gate requires 2^len(qubits) phases")
+        if 0 != self._lib.selene_simulator_operation_diagonal_phase(
+            self._instance,
+            (ctypes.c_uint64 * len(qubits))(*qubits),
+            len(qubits),
+            (ctypes.c_double * len(phases))(*phases),
+        ):
+            raise RuntimeError(
+                "Failed to apply diagonal phase operation on Selene simulator"
+            )
+
     def measure(self, qubit: int) -> bool:
         result = self._lib.selene_simulator_operation_measure(self._instance, qubit)
         if result not in (0, 1):
             raise RuntimeError("Failed to apply MEASURE operation on Selene simulator")
         return bool(result)
 
+    def measure_leaked(self, qubit: int) -> int:
+        result = self._lib.selene_simulator_operation_measure_leaked(
+            self._instance, qubit
+        )
+        if result not in (0, 1, 2):
+            raise RuntimeError(
+                "Failed to apply MEASURE_LEAKED operation on Selene simulator"
+            )
+        return result
+
+    def leakage_rxy(self, qubit: int, theta: float, phi: float):
+        if 0 != self._lib.selene_simulator_operation_leakage_rxy(
+            self._instance, qubit, theta, phi
+        ):
+            raise RuntimeError(
+                "Failed to apply LEAKAGE_RXY operation on Selene simulator"
+            )
+
     def reset(self, qubit: int):
         if 0 != self._lib.selene_simulator_operation_reset(self._instance, qubit):
             raise RuntimeError("Failed to apply RESET operation on Selene simulator")
 
+    def qalloc(self, qubit: int):
+        if 0 != self._lib.selene_simulator_operation_qalloc(self._instance, qubit):
+            raise RuntimeError("Failed to notify Selene simulator of QALLOC")
+
+    def qfree(self, qubit: int):
+        if 0 != self._lib.selene_simulator_operation_qfree(self._instance, qubit):
+            raise RuntimeError("Failed to notify Selene simulator of QFREE")
+
     def postselect(self, qubit: int, value: bool):
         if 0 != self._lib.selene_simulator_operation_postselect(
             self._instance, qubit, value
@@ -165,6 +338,116 @@ class InteractiveSimulator:
                 "Failed to apply POSTSELECT operation on Selene simulator"
             )
 
+    def snapshot(self) -> int:
+        handle = ctypes.c_uint64()
+        if 0 != self._lib.selene_simulator_snapshot(
+            self._instance, ctypes.byref(handle)
+        ):
+            raise RuntimeError("Failed to take a snapshot of the Selene simulator")
+        return handle.value
+
+    def restore(self, handle: int):
+        if 0 != self._lib.selene_simulator_restore(self._instance, handle):
+            raise RuntimeError(
+                f"Failed to restore snapshot {handle} on Selene simulator"
+            )
+
+    def release_snapshot(self, handle: int):
+        if 0 != self._lib.selene_simulator_release_snapshot(self._instance, handle):
+            raise RuntimeError(
+                f"Failed to release snapshot {handle} on Selene simulator"
+            )
+
+    def marginal_probabilities(self, qubits: list[int]) -> list[float]:
+        """
+        Get the probability of each Z-basis outcome of the given qubits without
+        disturbing the state. The result is indexed such that the first qubit
+        provided is the least significant bit.
+        """
+        qubit_array = (ctypes.c_uint64 * len(qubits))(*qubits)
+        probabilities = (ctypes.c_double * (1 << len(qubits)))()
+        if 0 != self._lib.selene_simulator_marginal_probabilities(
+            self._instance, qubit_array, len(qubits), probabilities
+        ):
+            raise RuntimeError(
+                "Failed to compute marginal probabilities on Selene simulator"
+            )
+        return list(probabilities)
+
+    def pauli_expectation(self, pauli_string: dict[int, str]) -> float:
+        """
+        Get the expectation value of a pauli string without disturbing the
+        state. The pauli string maps qubits to one of "I", "X", "Y" or "Z",
+        with unlisted qubits acted on by the identity.
+        """
+        codes = {"I": 0, "X": 1, "Y": 2, "Z": 3}
+        qubits = list(pauli_string.keys())
+        paulis = [codes[pauli_string[q].upper()] for q in qubits]
+        qubit_array = (ctypes.c_uint64 * len(qubits))(*qubits)
+        pauli_array = (ctypes.c_uint8 * len(paulis))(*paulis)
+        expectation = ctypes.c_double()
+        if 0 != self._lib.selene_simulator_pauli_expectation(
+            self._instance,
+            qubit_array,
+            pauli_array,
+            len(qubits),
+            ctypes.byref(expectation),
+        ):
+            raise RuntimeError(
+                "Failed to compute pauli expectation value on Selene simulator"
+            )
+        return expectation.value
+
+    def pauli_channel(self, qubit: int, p_x: float, p_y: float, p_z: float):
+        if 0 != self._lib.selene_simulator_channel_pauli(
+            self._instance, qubit, p_x, p_y, p_z
+        ):
+            raise RuntimeError(
+                f"Failed to apply pauli channel to qubit {qubit} on Selene simulator"
+            )
+
+    def amplitude_damping(self, qubit: int, gamma: float):
+        if 0 != self._lib.selene_simulator_channel_amplitude_damping(
+            self._instance, qubit, gamma
+        ):
+            raise RuntimeError(
+                f"Failed to apply amplitude damping to qubit {qubit} on Selene simulator"
+            )
+
+    def phase_damping(self, qubit: int, lambda_: float):
+        if 0 != self._lib.selene_simulator_channel_phase_damping(
+            self._instance, qubit, lambda_
+        ):
+            raise RuntimeError(
+                f"Failed to apply phase damping to qubit {qubit} on Selene simulator"
+            )
+
+    def kraus_channel(
+        self, qubits: list[int], kraus_operators: list[list[list[complex]]]
+    ):
+        """
+        Apply the channel defined by the given Kraus operators, each a square
+        matrix of dimension 2^len(qubits) given as a list of rows. The first
+        qubit provided is the least significant bit of the matrix indices.
+        """
+        values = [
+            part
+            for operator in kraus_operators
+            for row in operator
+            for element in row
+            for part in (complex(element).real, complex(element).imag)
+        ]
+        qubit_array = (ctypes.c_uint64 * len(qubits))(*qubits)
+        value_array = (ctypes.c_double * len(values))(*values)
+        if 0 != self._lib.selene_simulator_channel_kraus(
+            self._instance,
+            qubit_array,
+            len(qubits),
+            value_array,
+            len(kraus_operators),
+        ):
+            raise RuntimeError("Failed to apply kraus channel on Selene simulator")
+
     def get_metrics(self) -> dict[str, int | float | bool]:
         # for i in 0...255, calls selene_simulator_get_metrics with:
         # - that index
@@ -0,0 +1,52 @@
+from math import pi
+
+import pytest
+
+from selene_sim import Quest, SimpleRuntime, SoftRZRuntime
+from selene_sim.backends import CorrelatedReadoutErrorModel
+from selene_sim.event_hooks import MetricStore
+from selene_sim.interactive import InteractiveFullStack
+
+# Qubits 0 and 1 always report 01 and 10 the other way round when measured
+# together, and are otherwise error-free.
+CONFUSION = """
+blocks:
+  - qubits: [0, 1]
+    matrix:
+      - [1, 0, 0, 0]
+      - [0, 0, 1, 0]
+      - [0, 1, 0, 0]
+      - [0, 0, 0, 1]
+"""
+
+
+@pytest.mark.parametrize(
+    "runtime, swapped", [(SoftRZRuntime(), True), (SimpleRuntime(), False)]
+)
+def test_blocks_need_measurements_in_the_same_batch(tmp_path, runtime, swapped):
+    # The soft_rz runtime places both measurements in one batch, so the block's
+    # confusion matrix is used. The simple runtime emits one operation per
+    # batch, so each measurement falls back to the per-qubit readout error.
+    confusion_file = tmp_path / "confusion.yaml"
+    confusion_file.write_text(CONFUSION)
+    metric_store = MetricStore()
+    s = InteractiveFullStack(
+        simulator=Quest(random_seed=1234),
+        runtime=runtime,
+        error_model=CorrelatedReadoutErrorModel(
+            random_seed=5678, confusion_file=confusion_file
+        ),
+        n_qubits=2,
+        event_hook=metric_store,
+    )
+    metrics = metric_store.shots[0]
+    q0 = s.qalloc()
+    q1 = s.qalloc()
+    s.rxy(q0, pi, 0)
+    c0 = s.lazy_measure(q0)
+    c1 = s.lazy_measure(q1)
+    assert (s.future_read_bool(c0), s.future_read_bool(c1)) == (
+        (False, True) if swapped else (True, False)
+    )
+    assert metrics["error_model"]["measurements"] == 2
+    assert metrics["error_model"]["correlated_assignment_errors"] == int(swapped)
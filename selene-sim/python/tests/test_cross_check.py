@@ -0,0 +1,113 @@
+from math import pi, sin
+from textwrap import dedent
+
+import pytest
+from selene_sim import CrossCheck, Quest, Stim
+from selene_sim.build import build
+from selene_sim.event_hooks import MetricStore
+from selene_sim.exceptions import SelenePanicError
+
+
+def test_clifford_program_does_not_diverge(compiled_guppy):
+    guppy_source = dedent(
+        """
+        from guppylang.decorator import guppy
+        from guppylang.std.builtins import result
+        from guppylang.std.quantum import qubit, h, s, cx, measure, reset
+
+        @guppy
+        def main() -> None:
+            q0: qubit = qubit()
+            q1: qubit = qubit()
+            q2: qubit = qubit()
+            h(q0)
+            s(q0)
+            cx(q0, q1)
+            # Resetting an unentangled qubit keeps the simulators in step.
+            h(q2)
+            s(q2)
+            s(q2)
+            h(q2)
+            reset(q2)
+            result("c0", measure(q0))
+            result("c1", measure(q1))
+            result("c2", measure(q2))
+        """
+    )
+    llvm_file = compiled_guppy(
+        program_name="cross_check_clifford",
+        guppy_source=guppy_source,
+    )
+    runner = build(llvm_file)
+    metric_store = MetricStore()
+    simulator = CrossCheck(
+        primary=Stim(random_seed=1234),
+        secondary=Quest(random_seed=5678),
+        error_on_divergence=True,
+    )
+    shots = [
+        dict(shot)
+        for shot in runner.run_shots(
+            simulator=simulator,
+            n_qubits=3,
+            n_shots=50,
+            event_hook=metric_store,
+        )
+    ]
+    assert all(shot["c0"] == shot["c1"] and shot["c2"] == 0 for shot in shots)
+    assert len(metric_store.shots) == 50
+    for metrics in metric_store.shots:
+        assert metrics["simulator"]["checked_measurements"] == 3
+        assert metrics["simulator"]["divergent_measurements"] == 0
+        assert metrics["simulator"]["max_probability_divergence"] < 1e-6
+
+
+def test_non_clifford_program_diverges(compiled_guppy):
+    guppy_source = dedent(
+        """
+        from guppylang.decorator import guppy
+        from guppylang.std.angles import pi
+        from guppylang.std.builtins import result
+        from guppylang.std.quantum import qubit, rx, measure
+
+        @guppy
+        def main() -> None:
+            q0: qubit = qubit()
+            rx(q0, pi * 0.3)
+            result("c0", measure(q0))
+        """
+    )
+    llvm_file = compiled_guppy(
+        program_name="cross_check_non_clifford",
+        guppy_source=guppy_source,
+    )
+    runner = build(llvm_file)
+
+    # Stim rounds the rotation to RX(pi/2), under which 1 is measured with
+    # probability 1/2 rather than sin^2(0.15 pi).
+    def cross_check(error_on_divergence):
+        return CrossCheck(
+            primary=Stim(random_seed=1234, round_to_clifford=True),
+            secondary=Quest(random_seed=5678),
+            error_on_divergence=error_on_divergence,
+        )
+
+    metric_store = MetricStore()
+    shots = list(
+        runner.run_shots(
+            simulator=cross_check(False),
+            n_qubits=1,
+            n_shots=10,
+            event_hook=metric_store,
+        )
+    )
+    assert len(shots) == 10
+    for metrics in metric_store.shots:
+        assert metrics["simulator"]["checked_measurements"] == 1
+        assert metrics["simulator"]["divergent_measurements"] == 1
+        assert metrics["simulator"]["max_probability_divergence"] == pytest.approx(
+            0.5 - sin(0.15 * pi) ** 2
+        )
+
+    with pytest.raises(SelenePanicError, match="Simulators diverged"):
+        list(runner.run_shots(simulator=cross_check(True), n_qubits=1, n_shots=10))
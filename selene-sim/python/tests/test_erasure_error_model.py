@@ -0,0 +1,74 @@
+from textwrap import dedent
+
+from selene_sim import Quest
+from selene_sim.build import build
+from selene_sim.backends import ErasureErrorModel
+from selene_sim.event_hooks import MetricStore
+
+
+def test_erasure_is_heralded(compiled_guppy):
+    guppy_source = dedent(
+        """
+        from guppylang.decorator import guppy
+        from guppylang.std.builtins import result
+        from guppylang.std.qsystem import measure_leaked
+        from guppylang.std.quantum import qubit, h, reset
+
+
+        @guppy
+        def main() -> None:
+            q1: qubit = qubit()
+            q2: qubit = qubit()
+            h(q1)
+            h(q2)
+            # q2's erasure flag is cleared by the reset, and as a reset is
+            # not a gate, it cannot be erased again.
+            reset(q2)
+            r1 = measure_leaked(q1).to_result()
+            result("c1", 2 if r1.is_nothing() else 1 if r1.unwrap() else 0)
+            r2 = measure_leaked(q2).to_result()
+            result("c2", 2 if r2.is_nothing() else 1 if r2.unwrap() else 0)
+        """
+    )
+    llvm_file = compiled_guppy(
+        program_name="erasure_heralding",
+        guppy_source=guppy_source,
+    )
+    runner = build(llvm_file, "erasure_heralding")
+    error_model = ErasureErrorModel(
+        random_seed=91827364,  # for reproducibility
+        p_1q=0,  # begin with no errors (mutate during the test)
+        p_2q=0,  # constant zero for this test
+    )
+    simulator = Quest(random_seed=46372819)
+
+    def run(error_model):
+        metric_store = MetricStore()
+        shots = [
+            dict(shot)
+            for shot in runner.run_shots(
+                simulator=simulator,
+                error_model=error_model,
+                n_qubits=2,
+                n_shots=100,
+                event_hook=metric_store,
+            )
+        ]
+        return shots, metric_store.shots
+
+    # Without erasure, neither qubit is reported as leaked, and the reset
+    # qubit is in |0>.
+    shots, metrics = run(error_model)
+    assert all(shot["c1"] in (0, 1) and shot["c2"] == 0 for shot in shots)
+    assert all(m["error_model"]["erasures"] == 0 for m in metrics)
+
+    # With certain erasure on single-qubit gates, both qubits are erased by
+    # their first gate. The erasure of q1 is heralded by measure_leaked,
+    # whereas the reset clears the erasure of q2.
+    error_model.p_1q = 1
+    shots, metrics = run(error_model)
+    assert all(shot["c1"] == 2 and shot["c2"] == 0 for shot in shots)
+    assert len(metrics) == 100
+    for m in metrics:
+        assert m["error_model"]["erasures"] == 2
+        assert m["error_model"]["erasure_detections"] == 1
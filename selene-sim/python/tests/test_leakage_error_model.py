@@ -0,0 +1,39 @@
+from math import pi
+
+import pytest
+
+from selene_sim import Quest, SimpleRuntime, SoftRZRuntime
+from selene_sim.backends import LeakageErrorModel
+from selene_sim.event_hooks import MetricStore
+from selene_sim.interactive import InteractiveFullStack
+from selene_leakage_error_model_plugin import LRU_TAG
+
+
+@pytest.mark.parametrize("runtime", [SimpleRuntime(), SoftRZRuntime()])
+def test_lru_custom_call(runtime):
+    # Leakage reduction units are requested with a custom runtime call, which
+    # each runtime passes on to the error model as a custom operation.
+    metric_store = MetricStore()
+    s = InteractiveFullStack(
+        simulator=Quest(random_seed=1234),
+        runtime=runtime,
+        error_model=LeakageErrorModel(random_seed=5678, p_leak_rxy=1),
+        n_qubits=2,
+        event_hook=metric_store,
+    )
+    metrics = metric_store.shots[0]
+    q = s.qalloc()
+    s.rxy(q, pi, 0)
+    # Reading the result forces the runtime to emit the gate, which leaks q.
+    assert s.future_read_u64(s.lazy_measure_leaked(q)) == 2
+    assert metrics["error_model"]["gate_leaks"] == 1
+    assert metrics["error_model"]["lru_returns"] == 0
+
+    # Calls with other tags are forwarded too, but ignored by the error model.
+    assert s.custom_runtime_call(LRU_TAG + 1, q.id.to_bytes(8, "little")) == 0
+    assert s.future_read_u64(s.lazy_measure_leaked(q)) == 2
+    assert metrics["error_model"]["lru_returns"] == 0
+
+    assert s.custom_runtime_call(LRU_TAG, q.id.to_bytes(8, "little")) == 0
+    assert s.future_read_u64(s.lazy_measure_leaked(q)) == 0
+    assert metrics["error_model"]["lru_returns"] == 1
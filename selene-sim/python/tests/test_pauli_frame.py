@@ -0,0 +1,49 @@
+from textwrap import dedent
+
+from selene_sim.build import build
+from selene_sim import PauliFrame, IdealErrorModel
+
+
+def test_pauli_frame_batching_is_deterministic(compiled_guppy):
+    guppy_source = dedent(
+        """
+        from guppylang.decorator import guppy
+        from guppylang.std.quantum import qubit, h, cx, measure_array
+        from guppylang.std.builtins import result, array
+
+
+        @guppy
+        def main() -> None:
+            qubits = array(qubit() for _ in range(6))
+            for i in range(3):
+                h(qubits[2 * i])
+                cx(qubits[2 * i], qubits[2 * i + 1])
+            result("bits", measure_array(qubits))
+        """
+    )
+    llvm_file = compiled_guppy(
+        program_name="pauli_frame_batching",
+        guppy_source=guppy_source,
+    )
+    runner = build(llvm_file, "no_results")
+    error_model = IdealErrorModel()
+
+    def run(batch_size, **kwargs):
+        simulator = PauliFrame(random_seed=72519, batch_size=batch_size)
+        return list(
+            dict(a)
+            for a in runner.run_shots(
+                simulator=simulator, error_model=error_model, n_qubits=6, **kwargs
+            )
+        )
+
+    full_results = run(64, n_shots=300)
+    assert len(set(str(r) for r in full_results)) > 1
+
+    # Each shot draws its randomness from its own seed, so the results of a
+    # shot do not depend on which batch, or which lane of it, serves the shot.
+    assert run(256, n_shots=300) == full_results
+    assert run(64, n_shots=200, shot_offset=100) == full_results[100:]
+    assert (
+        run(128, n_shots=30, shot_offset=5, shot_increment=10) == full_results[5::10]
+    )
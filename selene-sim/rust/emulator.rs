@@ -4,7 +4,7 @@ use anyhow::{Result, anyhow};
 use selene_core::error_model::plugin::ErrorModelPluginInterface;
 use selene_core::error_model::{ErrorModel, ErrorModelInterface};
 use selene_core::runtime::plugin::RuntimePluginInterface;
-use selene_core::runtime::{Runtime, RuntimeInterface as _};
+use selene_core::runtime::{Runtime, RuntimeInterface as _, decompose};
 
 pub struct Emulator {
     pub runtime: Runtime,
@@ -106,6 +106,31 @@ impl Emulator {
         self.event_hooks.on_user_call(&Operation::RZ(q0, theta));
         self.process_runtime()
     }
+    pub fn user_issued_u(&mut self, q0: u64, theta: f64, phi: f64, lambda: f64) -> Result<()> {
+        self.runtime.u_gate(q0, theta, phi, lambda)?;
+        self.event_hooks
+            .on_user_call(&Operation::U(q0, theta, phi, lambda));
+        self.process_runtime()
+    }
+    pub fn user_issued_two_qubit_unitary(
+        &mut self,
+        q0: u64,
+        q1: u64,
+        matrix: &[f64; 32],
+    ) -> Result<()> {
+        decompose::validate_two_qubit_unitary(q0, q1, matrix)?;
+        self.runtime.two_qubit_unitary_gate(q0, q1, matrix)?;
+        self.event_hooks
+            .on_user_call(&Operation::TwoQubitUnitary(q0, q1, Box::new(*matrix)));
+        self.process_runtime()
+    }
+    pub fn user_issued_diagonal_phase(&mut self, qubits: &[u64], phases: &[f64]) -> Result<()> {
+        decompose::validate_diagonal_phases(qubits, phases)?;
+        self.runtime.diagonal_phase_gate(qubits, phases)?;
+        self.event_hooks
+            .on_user_call(&Operation::DiagonalPhase(qubits.to_vec(), phases.to_vec()));
+        self.process_runtime()
+    }
     pub fn user_issued_reset(&mut self, q0: u64) -> Result<()> {
         self.runtime.reset(q0)?;
         self.event_hooks.on_user_call(&Operation::Reset(q0));
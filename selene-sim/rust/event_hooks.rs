@@ -22,6 +22,9 @@ pub enum Operation {
     LocalBarrier(Vec<u64>, u64),
     Custom(u64, Vec<u8>),
     ClassicalDelay(u64),
+    U(u64, f64, f64, f64),
+    TwoQubitUnitary(u64, u64, Box<[f64; 32]>),
+    DiagonalPhase(Vec<u64>, Vec<f64>),
 }
 
 pub trait EventHook {
@@ -86,6 +86,31 @@ impl Instruction {
                 encoder.write(13u64)?;
                 encoder.write(*duration)?;
             }
+            Operation::U(qubit1, theta, phi, lambda) => {
+                encoder.write(14u64)?;
+                encoder.write(*qubit1)?;
+                encoder.write(*theta)?;
+                encoder.write(*phi)?;
+                encoder.write(*lambda)?;
+            }
+            Operation::TwoQubitUnitary(qubit1, qubit2, matrix) => {
+                encoder.write(15u64)?;
+                encoder.write(*qubit1)?;
+                encoder.write(*qubit2)?;
+                for value in matrix.iter() {
+                    encoder.write(*value)?;
+                }
+            }
+            Operation::DiagonalPhase(qubits, phases) => {
+                encoder.write(16u64)?;
+                encoder.write(qubits.len() as u64)?;
+                for qubit in qubits.iter() {
+                    encoder.write(*qubit)?;
+                }
+                for phase in phases.iter() {
+                    encoder.write(*phase)?;
+                }
+            }
         }
         Ok(())
     }
@@ -131,6 +156,22 @@ impl EventHook for InstructionLog {
                 runtime::Operation::Custom { custom_tag, data } => {
                     Operation::Custom(*custom_tag as u64, data.to_vec())
                 }
+                runtime::Operation::UGate {
+                    qubit_id,
+                    theta,
+                    phi,
+                    lambda,
+                } => Operation::U(*qubit_id, *theta, *phi, *lambda),
+                runtime::Operation::TwoQubitUnitaryGate {
+                    qubit_id_1,
+                    qubit_id_2,
+                    matrix,
+                } => Operation::TwoQubitUnitary(*qubit_id_1, *qubit_id_2, matrix.clone()),
+                runtime::Operation::DiagonalPhaseGate { qubit_ids, phases } => {
+                    Operation::DiagonalPhase(qubit_ids.to_vec(), phases.to_vec())
+                }
+                // Allocations are already logged as user program calls
+                runtime::Operation::QAlloc { .. } | runtime::Operation::QFree { .. } => continue,
             };
             self.entries.push(Instruction {
                 source: Source::RuntimeOptimiser,
@@ -15,6 +15,9 @@ struct UserProgramMetrics {
     rxy_count: u64,
     rz_count: u64,
     rzz_count: u64,
+    u_count: u64,
+    two_qubit_unitary_count: u64,
+    diagonal_phase_count: u64,
     global_barrier_count: u64,
     local_barrier_count: u64,
 }
@@ -38,6 +41,9 @@ impl UserProgramMetrics {
             Operation::RXY(..) => self.rxy_count += 1,
             Operation::RZ(..) => self.rz_count += 1,
             Operation::RZZ(..) => self.rzz_count += 1,
+            Operation::U(..) => self.u_count += 1,
+            Operation::TwoQubitUnitary(..) => self.two_qubit_unitary_count += 1,
+            Operation::DiagonalPhase(..) => self.diagonal_phase_count += 1,
             Operation::LocalBarrier(..) => self.local_barrier_count += 1,
             Operation::GlobalBarrier(..) => self.global_barrier_count += 1,
             _ => {}
@@ -85,6 +91,18 @@ impl UserProgramMetrics {
         encoder.write(self.rz_count)?;
         encoder.end_message()?;
         encoder.begin_message(time_cursor)?;
+        encoder.write("METRICS:INT:user_program:u_count")?;
+        encoder.write(self.u_count)?;
+        encoder.end_message()?;
+        encoder.begin_message(time_cursor)?;
+        encoder.write("METRICS:INT:user_program:two_qubit_unitary_count")?;
+        encoder.write(self.two_qubit_unitary_count)?;
+        encoder.end_message()?;
+        encoder.begin_message(time_cursor)?;
+        encoder.write("METRICS:INT:user_program:diagonal_phase_count")?;
+        encoder.write(self.diagonal_phase_count)?;
+        encoder.end_message()?;
+        encoder.begin_message(time_cursor)?;
         encoder.write("METRICS:INT:user_program:global_barrier_count")?;
         encoder.write(self.global_barrier_count)?;
         encoder.end_message()?;
@@ -119,6 +137,12 @@ struct PostRuntimeMetrics {
     rzz_individual_count: u64,
     rz_batch_count: u64,
     rz_individual_count: u64,
+    u_batch_count: u64,
+    u_individual_count: u64,
+    two_qubit_unitary_batch_count: u64,
+    two_qubit_unitary_individual_count: u64,
+    diagonal_phase_batch_count: u64,
+    diagonal_phase_individual_count: u64,
     total_duration_ns: u64,
 }
 
@@ -131,6 +155,9 @@ impl PostRuntimeMetrics {
         let mut measure_leaked_count = 0;
         let mut reset_count = 0;
         let mut custom_op_count = 0;
+        let mut u_count = 0;
+        let mut two_qubit_unitary_count = 0;
+        let mut diagonal_phase_count = 0;
         for op in batch.iter_ops() {
             match op {
                 runtime::Operation::RXYGate { .. } => {
@@ -154,6 +181,17 @@ impl PostRuntimeMetrics {
                 runtime::Operation::Custom { .. } => {
                     custom_op_count += 1;
                 }
+                runtime::Operation::UGate { .. } => {
+                    u_count += 1;
+                }
+                runtime::Operation::TwoQubitUnitaryGate { .. } => {
+                    two_qubit_unitary_count += 1;
+                }
+                runtime::Operation::DiagonalPhaseGate { .. } => {
+                    diagonal_phase_count += 1;
+                }
+                // Allocation notifications are not operations on the qubits
+                runtime::Operation::QAlloc { .. } | runtime::Operation::QFree { .. } => {}
             }
         }
 
@@ -189,6 +227,18 @@ impl PostRuntimeMetrics {
             self.custom_op_batch_count += 1;
             self.custom_op_individual_count += custom_op_count;
         }
+        if u_count > 0 {
+            self.u_batch_count += 1;
+            self.u_individual_count += u_count;
+        }
+        if two_qubit_unitary_count > 0 {
+            self.two_qubit_unitary_batch_count += 1;
+            self.two_qubit_unitary_individual_count += two_qubit_unitary_count;
+        }
+        if diagonal_phase_count > 0 {
+            self.diagonal_phase_batch_count += 1;
+            self.diagonal_phase_individual_count += diagonal_phase_count;
+        }
     }
     pub fn write(
         &self,
@@ -252,6 +302,30 @@ impl PostRuntimeMetrics {
         encoder.write(self.rzz_individual_count)?;
         encoder.end_message()?;
         encoder.begin_message(time_cursor)?;
+        encoder.write("METRICS:INT:post_runtime:u_batch_count")?;
+        encoder.write(self.u_batch_count)?;
+        encoder.end_message()?;
+        encoder.begin_message(time_cursor)?;
+        encoder.write("METRICS:INT:post_runtime:u_individual_count")?;
+        encoder.write(self.u_individual_count)?;
+        encoder.end_message()?;
+        encoder.begin_message(time_cursor)?;
+        encoder.write("METRICS:INT:post_runtime:two_qubit_unitary_batch_count")?;
+        encoder.write(self.two_qubit_unitary_batch_count)?;
+        encoder.end_message()?;
+        encoder.begin_message(time_cursor)?;
+        encoder.write("METRICS:INT:post_runtime:two_qubit_unitary_individual_count")?;
+        encoder.write(self.two_qubit_unitary_individual_count)?;
+        encoder.end_message()?;
+        encoder.begin_message(time_cursor)?;
+        encoder.write("METRICS:INT:post_runtime:diagonal_phase_batch_count")?;
+        encoder.write(self.diagonal_phase_batch_count)?;
+        encoder.end_message()?;
+        encoder.begin_message(time_cursor)?;
+        encoder.write("METRICS:INT:post_runtime:diagonal_phase_individual_count")?;
+        encoder.write(self.diagonal_phase_individual_count)?;
+        encoder.end_message()?;
+        encoder.begin_message(time_cursor)?;
         encoder.write("METRICS:INT:post_runtime:total_duration_ns")?;
         encoder.write(self.total_duration_ns)?;
         encoder.end_message()
@@ -1,6 +1,6 @@
 use super::selene_instance::SeleneInstance;
 use crate::selene_instance::configuration::Configuration;
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 
 #[repr(C)]
 pub struct VoidResult {
@@ -542,6 +542,71 @@ pub unsafe extern "C" fn selene_rzz(
     })
 }
 
+/// Applies U(theta, phi, lambda) = RZ(phi) RY(theta) RZ(lambda), up to a
+/// global phase, to the provided qubit.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn selene_u(
+    instance: *mut SeleneInstance,
+    qubit_id: u64,
+    theta: f64,
+    phi: f64,
+    lambda: f64,
+) -> VoidResult {
+    with_instance_void(instance, |instance| {
+        instance.u(qubit_id, theta, phi, lambda)
+    })
+}
+
+/// Applies a general two-qubit unitary, provided as a 4x4 row-major matrix of
+/// interleaved real and imaginary parts (32 values), with the first qubit as
+/// the least significant bit of the matrix indices.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn selene_two_qubit_unitary(
+    instance: *mut SeleneInstance,
+    qubit_id: u64,
+    qubit_id2: u64,
+    matrix: *const f64,
+) -> VoidResult {
+    let matrix = unsafe { &*(matrix as *const [f64; 32]) };
+    with_instance_void(instance, |instance| {
+        instance.two_qubit_unitary(qubit_id, qubit_id2, matrix)
+    })
+}
+
+/// Applies the phase e^{i phases[k]} to each basis state k of the provided
+/// qubits, where `phases` has 2^qubit_ids_length elements and the first qubit
+/// is the least significant bit of k. At most as many qubits may be provided
+/// as the instance has.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn selene_diagonal_phase(
+    instance: *mut SeleneInstance,
+    qubit_ids: *const u64,
+    qubit_ids_length: u64,
+    phases: *const f64,
+) -> VoidResult {
+    with_instance_void(instance, |instance| {
+        // The qubits must be distinct, so there can be no more of them than
+        // the instance has. Check this before reading either array, as the
+        // phases array has 2^qubit_ids_length entries.
+        if qubit_ids_length > instance.config.n_qubits {
+            return Err(anyhow!(
+                "A diagonal phase gate was given {qubit_ids_length} qubits, but only {} are available",
+                instance.config.n_qubits
+            ));
+        }
+        let qubit_ids = unsafe { std::slice::from_raw_parts(qubit_ids, qubit_ids_length as usize) };
+        let phases = unsafe {
+            std::slice::from_raw_parts(
+                phases,
+                1usize
+                    .checked_shl(qubit_ids_length as u32)
+                    .ok_or_else(|| anyhow!("Too many qubits provided to a diagonal phase gate"))?,
+            )
+        };
+        instance.diagonal_phase(qubit_ids, phases)
+    })
+}
+
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn selene_qubit_reset(instance: *mut SeleneInstance, q: u64) -> VoidResult {
     with_instance_void(instance, |instance| instance.emulator.user_issued_reset(q))
@@ -14,6 +14,12 @@ use rand_pcg::Pcg32;
 use selene_core::encoder::OutputStream;
 use selene_core::error_model::ErrorModelInterface;
 use selene_core::runtime::RuntimeInterface;
+use std::ops::Range;
+
+/// The number of upcoming shots announced to the error model and simulator at
+/// a time, so that simulators that serve many shots from a single pass can
+/// prepare them.
+const SHOT_PLAN_LENGTH: u64 = 4096;
 
 pub struct SeleneInstance {
     pub config: Configuration,
@@ -22,6 +28,8 @@ pub struct SeleneInstance {
     pub time_cursor: u64,
     pub shot_number: u64,
     pub prng: Option<Pcg32>,
+    /// The shot indices most recently announced through plan_shots.
+    pub planned_shots: Range<u64>,
 }
 
 impl SeleneInstance {
@@ -59,6 +67,7 @@ impl SeleneInstance {
             time_cursor: 0,
             shot_number: shot_offset,
             prng: None,
+            planned_shots: 0..0,
         })
     }
 
@@ -80,13 +89,19 @@ impl SeleneInstance {
     pub fn shot_start(&mut self, shot_index: u64) -> Result<()> {
         // Establish the internal shot ID based on the offset and increment,
         // and use it for seeding the various components.
-        let shot_id = self.config.shots.offset + self.config.shots.increment * shot_index;
+        let shot_id = self.shot_id(shot_index);
         self.shot_number = shot_id;
         self.print_shot_start()?;
         let runtime_seed = self.config.runtime.seed + shot_id;
         let error_model_seed = self.config.error_model.seed + shot_id;
         let simulator_seed = self.config.simulator.seed + shot_id;
 
+        // Announce the shots that follow whenever we move past those announced
+        // last, so that a simulator can serve them from a single pass.
+        if !self.planned_shots.contains(&shot_index) {
+            self.plan_shots(shot_index)?;
+        }
+
         // Now we fire off any shot start event hooks and prepare the
         // runtime and error model for the new shot.
         self.emulator.event_hooks.on_shot_start(shot_id);
@@ -100,6 +115,34 @@ impl SeleneInstance {
         Ok(())
     }
 
+    /// The shot ID of the shot with the given index, accounting for the shot
+    /// offset and increment.
+    fn shot_id(&self, shot_index: u64) -> u64 {
+        self.config.shots.offset + self.config.shots.increment * shot_index
+    }
+
+    /// Announce the shots from `first_index` onwards, up to SHOT_PLAN_LENGTH
+    /// of them, to the error model along with the seeds they will be started
+    /// with.
+    fn plan_shots(&mut self, first_index: u64) -> Result<()> {
+        let end_index = self
+            .config
+            .shots
+            .count
+            .clamp(first_index + 1, first_index + SHOT_PLAN_LENGTH);
+        let shot_ids: Vec<u64> = (first_index..end_index)
+            .map(|index| self.shot_id(index))
+            .collect();
+        let seeds_from = |base: u64| -> Vec<u64> { shot_ids.iter().map(|id| base + id).collect() };
+        let error_model_seeds = seeds_from(self.config.error_model.seed);
+        let simulator_seeds = seeds_from(self.config.simulator.seed);
+        self.emulator
+            .error_model
+            .plan_shots(&shot_ids, &error_model_seeds, &simulator_seeds)?;
+        self.planned_shots = first_index..end_index;
+        Ok(())
+    }
+
     /// End the current shot, flushing any remaining operations
     /// through the runtime and error model, writing out any
     /// stored metadata, and informing event hooks that the shot
@@ -22,6 +22,24 @@ impl SeleneInstance {
         self.emulator.user_issued_rzz(qubit_id, qubit_id2, theta)
     }
 
+    pub fn u(&mut self, qubit_id: u64, theta: f64, phi: f64, lambda: f64) -> Result<()> {
+        self.emulator.user_issued_u(qubit_id, theta, phi, lambda)
+    }
+
+    pub fn two_qubit_unitary(
+        &mut self,
+        qubit_id: u64,
+        qubit_id2: u64,
+        matrix: &[f64; 32],
+    ) -> Result<()> {
+        self.emulator
+            .user_issued_two_qubit_unitary(qubit_id, qubit_id2, matrix)
+    }
+
+    pub fn diagonal_phase(&mut self, qubit_ids: &[u64], phases: &[f64]) -> Result<()> {
+        self.emulator.user_issued_diagonal_phase(qubit_ids, phases)
+    }
+
     pub fn qubit_reset(&mut self, q: u64) -> Result<()> {
         self.emulator.user_issued_reset(q)
     }